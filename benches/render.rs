@@ -0,0 +1,53 @@
+//! Rendering a 5 MB history inline versus through `render`, the way call_tool does, with
+//! other calls waiting on the runtime. Run with `cargo bench --bench render`.
+//!
+//! Reports the total time of the large render and how long a small render queued behind it
+//! took: inline the small one waits for the large one, off the runtime it does not.
+
+use amadeus_mcp::render;
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+
+const ROUNDS: u32 = 10;
+
+fn history() -> Value {
+    Value::Array(
+        (0..60_000)
+            .map(|i| json!({ "hash": format!("tx-{}", i), "nonce": i, "tx": { "args": ["receiver", "1000", "AMA"], "signer": "signer" } }))
+            .collect(),
+    )
+}
+
+/// Time for the large render and for a small one started right after it.
+async fn round(huge: Value, inline: bool) -> (Duration, Duration) {
+    let started = Instant::now();
+    let large = tokio::spawn(async move {
+        if inline {
+            serde_json::to_string_pretty(&huge).unwrap()
+        } else {
+            render::render(huge, true).await.unwrap()
+        }
+    });
+    let small = tokio::spawn(async move {
+        render::render(json!({ "height": 100 }), true).await.unwrap();
+        started.elapsed()
+    });
+    let small = small.await.unwrap();
+    large.await.unwrap();
+    (started.elapsed(), small)
+}
+
+fn main() {
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+    let huge = history();
+    println!("payload: {} bytes", huge.to_string().len());
+    for (name, inline) in [("inline to_string_pretty", true), ("render (blocking pool)", false)] {
+        let (mut large, mut small) = (Duration::ZERO, Duration::ZERO);
+        for _ in 0..ROUNDS {
+            let (l, s) = runtime.block_on(round(huge.clone(), inline));
+            large += l;
+            small += s;
+        }
+        println!("{:<24} large {:>10.2?}  small call waited {:>10.2?}", name, large / ROUNDS, small / ROUNDS);
+    }
+}
@@ -0,0 +1,211 @@
+//! Ed25519 signatures over tool results, for deployments where a relay or proxy sits
+//! between this server and the agent and callers need to check a result came from here
+//! unmodified.
+//!
+//! The signed message is the compact JSON of `{"payload", "request_id", "timestamp"}`,
+//! where `payload` is the result without its `signature` field. Keys serialize sorted, so
+//! a verifier re-serializing the parsed result gets the same bytes.
+
+use crate::config::decode_signing_seed;
+use crate::secret::Secret;
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
+use serde_json::{json, Value};
+use std::fmt;
+use zeroize::Zeroize;
+
+pub const SIGNATURE_ALGORITHM: &str = "ed25519";
+/// How the signed bytes are formed, as told to verifiers by get_signing_public_key.
+pub const SIGNED_MESSAGE_FORMAT: &str = "compact JSON with sorted keys of {\"payload\": <result without its signature field>, \"request_id\": <signature.request_id>, \"timestamp\": <signature.timestamp>}";
+/// Field added to every signed result.
+pub const SIGNATURE_FIELD: &str = "signature";
+
+pub struct ResponseSigner {
+    key: Ed25519KeyPair,
+    public_key: String,
+}
+
+impl ResponseSigner {
+    pub fn from_secret(seed: &Secret) -> Result<Self, String> {
+        let mut seed = decode_signing_seed(seed.expose())?;
+        let key = Ed25519KeyPair::from_seed_unchecked(&seed).map_err(|e| e.to_string());
+        seed.zeroize();
+        let key = key?;
+        let public_key = bs58::encode(key.public_key().as_ref()).into_string();
+        Ok(Self { key, public_key })
+    }
+
+    /// Verification key, base58.
+    pub fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    /// Adds a `signature` block to `result` covering it, `timestamp` and `request_id`.
+    /// Non-object results are left unsigned since there is nowhere to put the block.
+    pub fn sign(&self, result: &mut Value, timestamp: u64, request_id: &str) {
+        let Some(fields) = result.as_object_mut() else {
+            return;
+        };
+        fields.remove(SIGNATURE_FIELD);
+        let message = signed_message(&Value::Object(fields.clone()), timestamp, request_id);
+        let signature = self.key.sign(&message);
+        fields.insert(
+            SIGNATURE_FIELD.to_string(),
+            json!({
+                "algorithm": SIGNATURE_ALGORITHM,
+                "public_key": self.public_key,
+                "timestamp": timestamp,
+                "request_id": request_id,
+                "value": bs58::encode(signature.as_ref()).into_string(),
+            }),
+        );
+    }
+}
+
+impl fmt::Debug for ResponseSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseSigner").field("public_key", &self.public_key).finish_non_exhaustive()
+    }
+}
+
+/// The bytes signed for `payload`.
+pub fn signed_message(payload: &Value, timestamp: u64, request_id: &str) -> Vec<u8> {
+    serde_json::to_vec(&json!({ "payload": payload, "request_id": request_id, "timestamp": timestamp }))
+        .unwrap_or_default()
+}
+
+/// Checks the `signature` block of a signed result against `public_key` (base58).
+pub fn verify(result: &Value, public_key: &str) -> Result<(), String> {
+    let mut payload = result.as_object().cloned().ok_or("result is not an object")?;
+    let block = payload.remove(SIGNATURE_FIELD).ok_or("result is not signed")?;
+    if block["algorithm"] != SIGNATURE_ALGORITHM {
+        return Err(format!("unsupported algorithm {}", block["algorithm"]));
+    }
+    let timestamp = block["timestamp"].as_u64().ok_or("signature has no timestamp")?;
+    let request_id = block["request_id"].as_str().ok_or("signature has no request_id")?;
+    let value = block["value"].as_str().ok_or("signature has no value")?;
+    let signature = bs58::decode(value).into_vec().map_err(|_| "signature is not base58")?;
+    let key = bs58::decode(public_key).into_vec().map_err(|_| "public key is not base58")?;
+    let message = signed_message(&Value::Object(payload), timestamp, request_id);
+    UnparsedPublicKey::new(&ED25519, key)
+        .verify(&message, &signature)
+        .map_err(|_| "signature does not match".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signer(byte: u8) -> ResponseSigner {
+        ResponseSigner::from_secret(&Secret::new(hex::encode([byte; 32]))).unwrap()
+    }
+
+    fn signed() -> (ResponseSigner, Value) {
+        let signer = signer(7);
+        let mut result = json!({ "height": 42, "entries": [{ "hash": "abc", "txs": 3 }], "note": null });
+        signer.sign(&mut result, 1_700_000_000, "req-1");
+        (signer, result)
+    }
+
+    #[test]
+    fn sign_then_verify_round_trips() {
+        let (signer, result) = signed();
+        let block = &result[SIGNATURE_FIELD];
+        assert_eq!(block["algorithm"], SIGNATURE_ALGORITHM);
+        assert_eq!(block["public_key"], signer.public_key());
+        assert_eq!(block["timestamp"], 1_700_000_000);
+        assert_eq!(block["request_id"], "req-1");
+        assert_eq!(verify(&result, signer.public_key()), Ok(()));
+    }
+
+    #[test]
+    fn verify_survives_reserializing_the_result() {
+        let (signer, result) = signed();
+        let text = serde_json::to_string_pretty(&result).unwrap();
+        let parsed: Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(verify(&parsed, signer.public_key()), Ok(()));
+    }
+
+    #[test]
+    fn tampering_fails_verification() {
+        let (signer, result) = signed();
+        type Tamper = fn(&mut Value);
+        let tampered: [(&str, Tamper); 7] = [
+            ("payload value", |r| r["height"] = json!(43)),
+            ("nested payload value", |r| r["entries"][0]["txs"] = json!(4)),
+            ("added payload field", |r| r["extra"] = json!(true)),
+            ("removed payload field", |r| {
+                r.as_object_mut().unwrap().remove("note");
+            }),
+            ("timestamp", |r| r[SIGNATURE_FIELD]["timestamp"] = json!(1_700_000_001)),
+            ("request_id", |r| r[SIGNATURE_FIELD]["request_id"] = json!("req-2")),
+            ("signature value", |r| {
+                let mut bytes = bs58::decode(r[SIGNATURE_FIELD]["value"].as_str().unwrap()).into_vec().unwrap();
+                bytes[0] ^= 1;
+                r[SIGNATURE_FIELD]["value"] = json!(bs58::encode(bytes).into_string());
+            }),
+        ];
+        for (what, tamper) in tampered {
+            let mut copy = result.clone();
+            tamper(&mut copy);
+            assert_eq!(verify(&copy, signer.public_key()), Err("signature does not match".to_string()), "{}", what);
+        }
+    }
+
+    #[test]
+    fn another_key_does_not_verify() {
+        let (_, result) = signed();
+        assert_eq!(verify(&result, signer(8).public_key()), Err("signature does not match".to_string()));
+    }
+
+    #[test]
+    fn malformed_signature_blocks_are_rejected() {
+        let (signer, result) = signed();
+        let cases: Vec<(&str, Value)> = vec![
+            ("result is not signed", {
+                let mut r = result.clone();
+                r.as_object_mut().unwrap().remove(SIGNATURE_FIELD);
+                r
+            }),
+            ("unsupported algorithm", {
+                let mut r = result.clone();
+                r[SIGNATURE_FIELD]["algorithm"] = json!("rsa");
+                r
+            }),
+            ("signature has no timestamp", {
+                let mut r = result.clone();
+                r[SIGNATURE_FIELD]["timestamp"] = json!("1700000000");
+                r
+            }),
+            ("signature has no request_id", {
+                let mut r = result.clone();
+                r[SIGNATURE_FIELD]["request_id"] = json!(null);
+                r
+            }),
+            ("signature is not base58", {
+                let mut r = result.clone();
+                r[SIGNATURE_FIELD]["value"] = json!("0OIl");
+                r
+            }),
+            ("result is not an object", json!([1, 2])),
+        ];
+        for (expected, value) in cases {
+            let err = verify(&value, signer.public_key()).unwrap_err();
+            assert!(err.starts_with(expected), "{}: {}", expected, err);
+        }
+    }
+
+    #[test]
+    fn resigning_replaces_the_previous_block() {
+        let (signer, mut result) = signed();
+        signer.sign(&mut result, 1_700_000_100, "req-2");
+        assert_eq!(result[SIGNATURE_FIELD]["request_id"], "req-2");
+        assert_eq!(verify(&result, signer.public_key()), Ok(()));
+    }
+
+    #[test]
+    fn non_object_results_stay_unsigned() {
+        let mut result = json!("text");
+        signer(7).sign(&mut result, 1, "req");
+        assert_eq!(result, json!("text"));
+    }
+}
@@ -0,0 +1,192 @@
+use super::types::TransactionAction;
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Bytes of a public key, the only fixed-length value agents usually want to recognize.
+const ADDRESS_BYTES: usize = 48;
+/// Shorter base58 strings are more likely text that happens to use the alphabet.
+const MIN_BINARY_BYTES: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ArgKind {
+    Integer,
+    /// base58 of a 48-byte public key
+    Address,
+    /// base58 of other binary data
+    Base58,
+    Utf8,
+    /// Could not be interpreted; shown as the node sent it
+    Raw,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DecodedArg {
+    #[serde(rename = "type")]
+    pub kind: ArgKind,
+    pub value: String,
+}
+
+/// A contract call in readable form, next to the node's `tx.action`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DecodedAction {
+    pub contract: String,
+    pub function: String,
+    pub args: Vec<DecodedArg>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attached_symbol: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attached_amount: Option<String>,
+    /// Set when part of the action could not be decoded; those parts are shown raw
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decode_error: Option<String>,
+}
+
+/// Decodes a contract call. `None` for other operations, which carry no call to decode.
+pub fn decode(action: &TransactionAction) -> Option<DecodedAction> {
+    if action.op != "call" {
+        return None;
+    }
+    let mut problems = Vec::new();
+    let args = action
+        .args
+        .iter()
+        .enumerate()
+        .map(|(i, arg)| {
+            let decoded = decode_arg(arg);
+            if decoded.kind == ArgKind::Raw {
+                problems.push(format!("argument {} is neither text nor base58", i));
+            }
+            decoded
+        })
+        .collect();
+    if let Some(amount) = action.attached_amount.as_deref().filter(|a| a.parse::<i128>().is_err()) {
+        problems.push(format!("attached amount {:?} is not an integer", amount));
+    }
+
+    Some(DecodedAction {
+        contract: action.contract.clone(),
+        function: action.function.clone(),
+        args,
+        attached_symbol: action.attached_symbol.clone(),
+        attached_amount: action.attached_amount.clone(),
+        decode_error: (!problems.is_empty()).then(|| problems.join("; ")),
+    })
+}
+
+/// Tags a contract storage value like a call argument. Strings are tagged as arguments
+/// are; integral numbers are integers; anything else is kept raw as its JSON text.
+pub fn decode_value(value: &Value) -> DecodedArg {
+    match value {
+        Value::String(text) => decode_arg(text),
+        Value::Number(n) if n.is_i64() || n.is_u64() => DecodedArg { kind: ArgKind::Integer, value: n.to_string() },
+        other => DecodedArg { kind: ArgKind::Raw, value: other.to_string() },
+    }
+}
+
+/// Tags one argument as the node rendered it: integers first, then base58 long enough to
+/// be binary, then text. Text the node had to replace bytes in is left raw.
+fn decode_arg(arg: &str) -> DecodedArg {
+    let tagged = |kind| DecodedArg { kind, value: arg.to_string() };
+    if let Ok(n) = arg.parse::<i128>() {
+        return DecodedArg { kind: ArgKind::Integer, value: n.to_string() };
+    }
+    match bs58::decode(arg).into_vec() {
+        Ok(bytes) if bytes.len() == ADDRESS_BYTES => return tagged(ArgKind::Address),
+        Ok(bytes) if bytes.len() >= MIN_BINARY_BYTES => return tagged(ArgKind::Base58),
+        _ => {}
+    }
+    if arg.contains(char::REPLACEMENT_CHARACTER) || arg.chars().any(|c| c.is_control() && !c.is_whitespace()) {
+        tagged(ArgKind::Raw)
+    } else {
+        tagged(ArgKind::Utf8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn action(value: Value) -> TransactionAction {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn address(byte: u8) -> String {
+        bs58::encode([byte; ADDRESS_BYTES]).into_string()
+    }
+
+    #[test]
+    fn a_transfer_decodes_its_receiver_amount_and_symbol() {
+        let receiver = address(2);
+        let decoded = decode(&action(json!({
+            "op": "call", "contract": "Coin", "function": "transfer", "args": [receiver, "1000000000", "AMA"]
+        })))
+        .unwrap();
+        assert_eq!(
+            serde_json::to_value(&decoded).unwrap(),
+            json!({
+                "contract": "Coin",
+                "function": "transfer",
+                "args": [
+                    { "type": "address", "value": receiver },
+                    { "type": "integer", "value": "1000000000" },
+                    { "type": "utf8", "value": "AMA" }
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn a_multi_argument_call_tags_each_argument() {
+        let blob = bs58::encode([9u8; 32]).into_string();
+        let decoded = decode(&action(json!({
+            "op": "call",
+            "contract": "Exchange",
+            "function": "swap",
+            "args": [blob, "-5", "memo with spaces", "abc", address(3)],
+            "attached_symbol": "USDX",
+            "attached_amount": "250"
+        })))
+        .unwrap();
+        let kinds: Vec<ArgKind> = decoded.args.iter().map(|a| a.kind).collect();
+        // Short base58-looking text stays text
+        assert_eq!(kinds, vec![ArgKind::Base58, ArgKind::Integer, ArgKind::Utf8, ArgKind::Utf8, ArgKind::Address]);
+        assert_eq!((decoded.attached_symbol.as_deref(), decoded.attached_amount.as_deref()), (Some("USDX"), Some("250")));
+        assert_eq!(decoded.decode_error, None);
+    }
+
+    #[test]
+    fn undecodable_parts_stay_raw_with_a_note() {
+        let decoded = decode(&action(json!({
+            "op": "call",
+            "contract": "Coin",
+            "function": "transfer",
+            "args": ["ok", "bad\u{FFFD}bytes", "bell\u{7}"],
+            "attached_amount": "1.5"
+        })))
+        .unwrap();
+        assert_eq!(decoded.args[1].kind, ArgKind::Raw);
+        assert_eq!(decoded.args[1].value, "bad\u{FFFD}bytes");
+        assert_eq!(decoded.args[2].kind, ArgKind::Raw);
+        assert_eq!(
+            decoded.decode_error.as_deref(),
+            Some("argument 1 is neither text nor base58; argument 2 is neither text nor base58; attached amount \"1.5\" is not an integer")
+        );
+    }
+
+    #[test]
+    fn only_calls_are_decoded() {
+        assert!(decode(&action(json!({ "op": "deploy", "contract": "X", "function": "", "args": [] }))).is_none());
+    }
+
+    #[test]
+    fn storage_values_are_tagged_like_arguments() {
+        assert_eq!(decode_value(&json!(42)).kind, ArgKind::Integer);
+        assert_eq!(decode_value(&json!(address(1))).kind, ArgKind::Address);
+        let raw = decode_value(&json!({ "a": 1.5 }));
+        assert_eq!((raw.kind, raw.value.as_str()), (ArgKind::Raw, r#"{"a":1.5}"#));
+        assert_eq!(decode_value(&json!(1.5)).kind, ArgKind::Raw);
+    }
+}
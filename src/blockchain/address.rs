@@ -0,0 +1,307 @@
+//! Account addresses: 48-byte BLS12-381 G1 public keys, written in base58. Parsing forgives
+//! the usual paste mistakes (whitespace, hex instead of base58) and names exactly what is
+//! wrong with anything else, so tools fail on the address instead of further downstream.
+//!
+//! Parsing checks the encoding and length only, like the node's lookups; whether the bytes
+//! are a usable public key is [`Address::check_point`], which validate_address reports.
+
+use super::types::PUBLIC_KEY_LEN;
+use serde::Serialize;
+use std::fmt;
+
+/// Characters kept at each end of [`Address::short`].
+const SHORT_EDGE_CHARS: usize = 6;
+const BASE58_ALPHABET: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Encoding {
+    Base58,
+    Hex,
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Base58 => "base58",
+            Self::Hex => "hex",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum AddressError {
+    #[error("address is empty")]
+    Empty,
+    #[error("{character:?} at position {index} is neither base58 (which has no 0, O, I or l) nor hex")]
+    BadEncoding { character: char, index: usize },
+    #[error("decodes from {encoding} to {len} bytes; a public key is 48")]
+    WrongLength { encoding: Encoding, len: usize },
+    #[error("the 48 bytes are not a compressed point on the BLS12-381 G1 curve")]
+    NotOnCurve,
+    #[error("the point is not in the G1 subgroup, so it cannot be a public key")]
+    NotInSubgroup,
+    #[error("the point at infinity is not a valid public key")]
+    Infinity,
+    #[error("give the address as base58: {canonical}")]
+    NotCanonical { canonical: String },
+}
+
+impl AddressError {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Empty => "empty",
+            Self::BadEncoding { .. } => "bad_encoding",
+            Self::WrongLength { .. } => "wrong_length",
+            Self::NotOnCurve => "not_on_curve",
+            Self::NotInSubgroup => "not_in_subgroup",
+            Self::Infinity => "infinity",
+            Self::NotCanonical { .. } => "not_canonical",
+        }
+    }
+}
+
+/// A validated public key, kept with its canonical base58 form.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Address {
+    key: [u8; PUBLIC_KEY_LEN],
+    base58: String,
+}
+
+impl Address {
+    /// Parses base58 or hex (with or without `0x`) of 48 bytes, ignoring any whitespace.
+    pub fn parse(input: &str) -> Result<Self, AddressError> {
+        let (encoding, bytes) = decode(input)?;
+        let key: [u8; PUBLIC_KEY_LEN] =
+            bytes.try_into().map_err(|bytes: Vec<u8>| AddressError::WrongLength { encoding, len: bytes.len() })?;
+        Ok(Self { base58: bs58::encode(key).into_string(), key })
+    }
+
+    /// Whether the bytes are a compressed point in the G1 subgroup, as any key that can sign is.
+    pub fn check_point(&self) -> Result<(), AddressError> {
+        let point = blst::min_pk::PublicKey::from_bytes(&self.key).map_err(|_| AddressError::NotOnCurve)?;
+        point.validate().map_err(|e| match e {
+            blst::BLST_ERROR::BLST_PK_IS_INFINITY => AddressError::Infinity,
+            _ => AddressError::NotInSubgroup,
+        })
+    }
+
+    /// Like [`Address::parse`], but `input` must already be the canonical base58, as where
+    /// the string is used verbatim.
+    pub fn from_base58(input: &str) -> Result<Self, AddressError> {
+        let address = Self::parse(input)?;
+        if address.base58 != input {
+            return Err(AddressError::NotCanonical { canonical: address.base58 });
+        }
+        Ok(address)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.base58
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.key
+    }
+
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.key)
+    }
+
+    /// The base58 form cut to its ends, for display.
+    pub fn short(&self) -> String {
+        let chars: Vec<char> = self.base58.chars().collect();
+        let head: String = chars[..SHORT_EDGE_CHARS].iter().collect();
+        let tail: String = chars[chars.len() - SHORT_EDGE_CHARS..].iter().collect();
+        format!("{}…{}", head, tail)
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.base58)
+    }
+}
+
+/// The validate_address verdict for one input. The encodings are given whenever the input
+/// decodes to 48 bytes, even when they are not a public key.
+#[derive(Debug, Clone, Serialize)]
+pub struct AddressCheck {
+    pub input: String,
+    pub valid: bool,
+    /// "valid", or the [`AddressError`] code
+    pub verdict: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// The encoding the input decoded from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<Encoding>,
+    /// True when the input had whitespace or a `0x` prefix, or was hex: pass `base58` instead
+    pub normalized: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base58: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hex: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub short: Option<String>,
+}
+
+impl AddressCheck {
+    pub fn new(input: &str) -> Self {
+        let encoding = decode(input).ok().map(|(encoding, _)| encoding);
+        match Address::parse(input) {
+            Ok(address) => {
+                let point = address.check_point();
+                Self {
+                    input: input.to_string(),
+                    valid: point.is_ok(),
+                    verdict: point.as_ref().map_or_else(AddressError::as_str, |_| "valid"),
+                    detail: point.err().map(|e| e.to_string()),
+                    encoding,
+                    normalized: address.base58 != input,
+                    short: Some(address.short()),
+                    hex: Some(address.to_hex()),
+                    base58: Some(address.base58),
+                }
+            }
+            Err(e) => Self {
+                input: input.to_string(),
+                valid: false,
+                verdict: e.as_str(),
+                detail: Some(e.to_string()),
+                encoding,
+                normalized: false,
+                base58: None,
+                hex: None,
+                short: None,
+            },
+        }
+    }
+}
+
+/// Base58 when it decodes to a key's length, hex otherwise; the error describes whichever
+/// reading got furthest.
+fn decode(input: &str) -> Result<(Encoding, Vec<u8>), AddressError> {
+    let compact: String = input.chars().filter(|c| !c.is_whitespace()).collect();
+    if compact.is_empty() {
+        return Err(AddressError::Empty);
+    }
+    if let Some(digits) = compact.strip_prefix("0x").or_else(|| compact.strip_prefix("0X")) {
+        return hex::decode(digits)
+            .map(|bytes| (Encoding::Hex, bytes))
+            .map_err(|_| bad_character(digits, |c| c.is_ascii_hexdigit(), 2, Encoding::Hex));
+    }
+    match bs58::decode(&compact).into_vec() {
+        Ok(bytes) if bytes.len() == PUBLIC_KEY_LEN => Ok((Encoding::Base58, bytes)),
+        base58 => match (hex::decode(&compact), base58) {
+            (Ok(bytes), _) => Ok((Encoding::Hex, bytes)),
+            (Err(_), Ok(bytes)) => Ok((Encoding::Base58, bytes)),
+            (Err(_), Err(_)) => Err(bad_character(&compact, |c| BASE58_ALPHABET.contains(c), 0, Encoding::Base58)),
+        },
+    }
+}
+
+/// The first character of `digits` that `allowed` rejects, its position counted from
+/// `offset`. Odd-length hex has none and is reported by length.
+fn bad_character(digits: &str, allowed: impl Fn(char) -> bool, offset: usize, encoding: Encoding) -> AddressError {
+    digits
+        .chars()
+        .position(|c| !allowed(c))
+        .map(|index| AddressError::BadEncoding { character: digits.chars().nth(index).unwrap_or_default(), index: index + offset })
+        .unwrap_or(AddressError::WrongLength { encoding, len: digits.len() / 2 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIGNER: &str = "29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw";
+
+    /// A key that can sign, unlike the sample addresses above.
+    fn public_key() -> String {
+        let secret = blst::min_pk::SecretKey::key_gen(&[7u8; 32], &[]).unwrap();
+        bs58::encode(secret.sk_to_pk().to_bytes()).into_string()
+    }
+
+    #[test]
+    fn base58_and_hex_parse_to_the_same_key() {
+        let address = Address::parse(SIGNER).unwrap();
+        assert_eq!(address.as_str(), SIGNER);
+        assert_eq!(address.as_bytes().len(), PUBLIC_KEY_LEN);
+
+        let hex = address.to_hex();
+        for pasted in [hex.clone(), format!("0x{}", hex), format!("0X{}", hex.to_uppercase()), format!(" {}\n", SIGNER)] {
+            assert_eq!(Address::parse(&pasted).unwrap(), address, "{}", pasted);
+        }
+        let spaced: String = SIGNER.chars().flat_map(|c| [c, ' ']).collect();
+        assert_eq!(Address::parse(&spaced).unwrap().as_str(), SIGNER);
+    }
+
+    #[test]
+    fn errors_name_what_is_wrong() {
+        assert_eq!(Address::parse(" \t"), Err(AddressError::Empty));
+        assert_eq!(Address::parse("29Vit0N55"), Err(AddressError::BadEncoding { character: '0', index: 5 }));
+        assert_eq!(Address::parse("0xabzz"), Err(AddressError::BadEncoding { character: 'z', index: 4 }));
+        assert_eq!(Address::parse("0xabc"), Err(AddressError::WrongLength { encoding: Encoding::Hex, len: 1 }));
+        assert_eq!(
+            Address::parse(&bs58::encode([1u8; 32]).into_string()),
+            Err(AddressError::WrongLength { encoding: Encoding::Base58, len: 32 })
+        );
+        assert_eq!(
+            Address::parse(&hex::encode([1u8; 47])),
+            Err(AddressError::WrongLength { encoding: Encoding::Hex, len: 47 })
+        );
+        assert_eq!(AddressError::WrongLength { encoding: Encoding::Hex, len: 47 }.as_str(), "wrong_length");
+        assert_eq!(
+            AddressError::BadEncoding { character: 'l', index: 3 }.to_string(),
+            "'l' at position 3 is neither base58 (which has no 0, O, I or l) nor hex"
+        );
+    }
+
+    #[test]
+    fn from_base58_wants_the_canonical_form() {
+        assert!(Address::from_base58(SIGNER).is_ok());
+        let hex = Address::parse(SIGNER).unwrap().to_hex();
+        assert_eq!(Address::from_base58(&hex), Err(AddressError::NotCanonical { canonical: SIGNER.to_string() }));
+        assert!(matches!(Address::from_base58(&format!("{} ", SIGNER)), Err(AddressError::NotCanonical { .. })));
+    }
+
+    #[test]
+    fn short_keeps_both_ends() {
+        let address = Address::parse(SIGNER).unwrap();
+        assert_eq!(address.short(), "29VitN…SERYBw");
+        assert_eq!(address.to_string(), SIGNER);
+    }
+
+    #[test]
+    fn points_are_checked_separately_from_parsing() {
+        assert_eq!(Address::parse(&public_key()).unwrap().check_point(), Ok(()));
+        let mut infinity = [0u8; PUBLIC_KEY_LEN];
+        infinity[0] = 0xc0;
+        assert_eq!(Address::parse(&bs58::encode(infinity).into_string()).unwrap().check_point(), Err(AddressError::Infinity));
+        let garbage = Address::parse(&bs58::encode([0xffu8; PUBLIC_KEY_LEN]).into_string()).unwrap();
+        assert_eq!(garbage.check_point(), Err(AddressError::NotOnCurve));
+    }
+
+    #[test]
+    fn checks_report_every_encoding_of_a_valid_key() {
+        let key = public_key();
+        let check = AddressCheck::new(&key);
+        assert!(check.valid && !check.normalized);
+        assert_eq!((check.verdict, check.encoding), ("valid", Some(Encoding::Base58)));
+        assert_eq!(check.base58.as_deref(), Some(key.as_str()));
+
+        let hex = AddressCheck::new(check.hex.as_deref().unwrap());
+        assert!(hex.valid && hex.normalized);
+        assert_eq!((hex.encoding, hex.base58.as_deref()), (Some(Encoding::Hex), Some(key.as_str())));
+
+        // Well-formed but not a key: the encodings are still given
+        let off_curve = AddressCheck::new(&bs58::encode([0xffu8; PUBLIC_KEY_LEN]).into_string());
+        assert!(!off_curve.valid && off_curve.hex.is_some());
+        assert_eq!(off_curve.verdict, "not_on_curve");
+
+        let bad = AddressCheck::new("not-an-address");
+        assert!(!bad.valid && bad.base58.is_none());
+        assert_eq!(bad.verdict, "bad_encoding");
+    }
+}
@@ -0,0 +1,341 @@
+//! Activity statistics over an account's history: what it sent and received per symbol,
+//! who it dealt with most, and what it paid to execute, so callers need not re-derive them
+//! from raw transactions.
+
+use super::full_history::FullHistory;
+use super::trace;
+use super::types::Transaction;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Counterparties kept in each ranking.
+pub const TOP_COUNTERPARTIES: usize = 10;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SymbolTotals {
+    /// Atoms, as a decimal string
+    pub sent: String,
+    /// Atoms, as a decimal string
+    pub received: String,
+    pub transfers_out: usize,
+    pub transfers_in: usize,
+    #[serde(skip)]
+    sent_atoms: u128,
+    #[serde(skip)]
+    received_atoms: u128,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CounterpartyCount {
+    pub address: String,
+    pub transfers: usize,
+    pub sent: usize,
+    pub received: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CounterpartyVolume {
+    pub address: String,
+    /// Atoms moved either way, as a decimal string
+    pub volume: String,
+    pub sent: String,
+    pub received: String,
+    pub transfers: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Tally {
+    sent: usize,
+    received: usize,
+    /// Per symbol: atoms sent, atoms received, transfers
+    symbols: BTreeMap<String, (u128, u128, usize)>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountActivity {
+    pub address: String,
+    pub tx_count: usize,
+    /// Transactions the address signed
+    pub signed: usize,
+    /// Transactions whose receipt reports failure; they move no tokens and are left out of the totals
+    pub failed: usize,
+    /// Transfers from the address to itself, left out of the totals
+    pub self_transfers: usize,
+    /// Per symbol
+    pub totals: BTreeMap<String, SymbolTotals>,
+    /// Most transfers first
+    pub top_by_count: Vec<CounterpartyCount>,
+    /// Per symbol, most atoms moved first; volumes of different symbols are not comparable
+    pub top_by_volume: BTreeMap<String, Vec<CounterpartyVolume>>,
+    /// Sum of exec_used over the transactions the address signed, as a decimal string
+    pub fees: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_height: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_height: Option<u64>,
+    /// Unix seconds of the oldest transaction's entry
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_timestamp: Option<u64>,
+    /// Unix seconds of the newest transaction's entry
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_timestamp: Option<u64>,
+    /// False when the history was cut short by the transaction cap
+    pub complete: bool,
+}
+
+impl AccountActivity {
+    /// Statistics of `address` over `transactions`, counting each hash once. Timestamps are
+    /// left unset, as transactions do not carry them; see [`AccountActivity::from_history`].
+    pub fn new(address: &str, transactions: &[Transaction]) -> Self {
+        let mut seen = HashSet::new();
+        let mut activity = Self {
+            address: address.to_string(),
+            tx_count: 0,
+            signed: 0,
+            failed: 0,
+            self_transfers: 0,
+            totals: BTreeMap::new(),
+            top_by_count: Vec::new(),
+            top_by_volume: BTreeMap::new(),
+            fees: String::new(),
+            first_height: None,
+            last_height: None,
+            first_timestamp: None,
+            last_timestamp: None,
+            complete: true,
+        };
+        let mut fees: u128 = 0;
+        let mut counterparties: HashMap<String, Tally> = HashMap::new();
+        for tx in transactions.iter().filter(|tx| seen.insert(tx.hash.as_str())) {
+            activity.tx_count += 1;
+            let height = tx.metadata.entry_height;
+            activity.first_height = Some(activity.first_height.map_or(height, |h| h.min(height)));
+            activity.last_height = Some(activity.last_height.map_or(height, |h| h.max(height)));
+            if tx.tx.signer == address {
+                activity.signed += 1;
+                fees = fees.saturating_add(tx.receipt.exec_used.parse().unwrap_or(0));
+            }
+            if !tx.receipt.success {
+                activity.failed += 1;
+                continue;
+            }
+            let Some(transfer) = trace::transfer(tx) else { continue };
+            let outgoing = transfer.from == address;
+            let other = match (outgoing, transfer.to == address) {
+                (true, true) => {
+                    activity.self_transfers += 1;
+                    continue;
+                }
+                (true, false) => transfer.to,
+                (false, true) => transfer.from,
+                (false, false) => continue,
+            };
+            let totals = activity.totals.entry(transfer.symbol.to_string()).or_default();
+            let tally = counterparties.entry(other.to_string()).or_default();
+            let per_symbol = tally.symbols.entry(transfer.symbol.to_string()).or_default();
+            per_symbol.2 += 1;
+            if outgoing {
+                totals.sent_atoms = totals.sent_atoms.saturating_add(transfer.amount);
+                totals.transfers_out += 1;
+                tally.sent += 1;
+                per_symbol.0 = per_symbol.0.saturating_add(transfer.amount);
+            } else {
+                totals.received_atoms = totals.received_atoms.saturating_add(transfer.amount);
+                totals.transfers_in += 1;
+                tally.received += 1;
+                per_symbol.1 = per_symbol.1.saturating_add(transfer.amount);
+            }
+        }
+        for totals in activity.totals.values_mut() {
+            totals.sent = totals.sent_atoms.to_string();
+            totals.received = totals.received_atoms.to_string();
+        }
+        activity.fees = fees.to_string();
+        activity.top_by_count = top_by_count(&counterparties);
+        activity.top_by_volume = top_by_volume(&counterparties);
+        activity
+    }
+
+    /// Statistics over a fetched history, with its entry timestamps and completeness.
+    pub fn from_history(history: &FullHistory) -> Self {
+        Self {
+            first_timestamp: history.first_timestamp,
+            last_timestamp: history.last_timestamp,
+            complete: history.complete,
+            ..Self::new(&history.address, &history.transactions)
+        }
+    }
+}
+
+fn top_by_count(counterparties: &HashMap<String, Tally>) -> Vec<CounterpartyCount> {
+    let mut ranked: Vec<CounterpartyCount> = counterparties
+        .iter()
+        .map(|(address, tally)| CounterpartyCount {
+            address: address.clone(),
+            transfers: tally.sent + tally.received,
+            sent: tally.sent,
+            received: tally.received,
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.transfers.cmp(&a.transfers).then_with(|| a.address.cmp(&b.address)));
+    ranked.truncate(TOP_COUNTERPARTIES);
+    ranked
+}
+
+fn top_by_volume(counterparties: &HashMap<String, Tally>) -> BTreeMap<String, Vec<CounterpartyVolume>> {
+    let mut by_symbol: BTreeMap<String, Vec<(u128, CounterpartyVolume)>> = BTreeMap::new();
+    for (address, tally) in counterparties {
+        for (symbol, (sent, received, transfers)) in &tally.symbols {
+            let volume = sent.saturating_add(*received);
+            by_symbol.entry(symbol.clone()).or_default().push((
+                volume,
+                CounterpartyVolume {
+                    address: address.clone(),
+                    volume: volume.to_string(),
+                    sent: sent.to_string(),
+                    received: received.to_string(),
+                    transfers: *transfers,
+                },
+            ));
+        }
+    }
+    by_symbol
+        .into_iter()
+        .map(|(symbol, mut ranked)| {
+            ranked.sort_by(|(a_volume, a), (b_volume, b)| b_volume.cmp(a_volume).then_with(|| a.address.cmp(&b.address)));
+            ranked.truncate(TOP_COUNTERPARTIES);
+            (symbol, ranked.into_iter().map(|(_, volume)| volume).collect())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const ME: &str = "me";
+
+    #[allow(clippy::too_many_arguments)]
+    fn tx(hash: &str, height: u64, signer: &str, to: &str, amount: u128, symbol: &str, success: bool, exec_used: u64) -> Transaction {
+        serde_json::from_value(json!({
+            "hash": hash,
+            "metadata": { "entry_hash": "entry", "entry_height": height },
+            "signature": "sig",
+            "result": { "error": "ok" },
+            "tx": {
+                "action": { "args": [to, amount.to_string(), symbol], "function": "transfer", "op": "call", "contract": "Coin" },
+                "nonce": height,
+                "signer": signer
+            },
+            "receipt": { "success": success, "result": "ok", "logs": [], "exec_used": exec_used.to_string() }
+        }))
+        .unwrap()
+    }
+
+    fn sent(hash: &str, height: u64, to: &str, amount: u128, symbol: &str) -> Transaction {
+        tx(hash, height, ME, to, amount, symbol, true, 10)
+    }
+
+    fn received(hash: &str, height: u64, from: &str, amount: u128, symbol: &str) -> Transaction {
+        tx(hash, height, from, ME, amount, symbol, true, 10)
+    }
+
+    fn history() -> Vec<Transaction> {
+        vec![
+            sent("s1", 10, "alice", 100, "AMA"),
+            sent("s2", 12, "alice", 50, "AMA"),
+            received("r1", 11, "bob", 1_000, "AMA"),
+            received("r2", 15, "alice", 7, "USDC"),
+            sent("s3", 20, "carol", 3, "USDC"),
+            // Failed: counted, charged, but moves nothing
+            tx("f1", 5, ME, "carol", 999, "AMA", false, 4),
+            // To itself: counted, left out of the totals
+            sent("self", 30, ME, 500, "AMA"),
+            // A repeated page
+            sent("s1", 10, "alice", 100, "AMA"),
+        ]
+    }
+
+    #[test]
+    fn totals_are_per_symbol_and_skip_failed_and_self_transfers() {
+        let activity = AccountActivity::new(ME, &history());
+        assert_eq!((activity.tx_count, activity.signed, activity.failed, activity.self_transfers), (7, 5, 1, 1));
+
+        let ama = &activity.totals["AMA"];
+        assert_eq!((ama.sent.as_str(), ama.received.as_str()), ("150", "1000"));
+        assert_eq!((ama.transfers_out, ama.transfers_in), (2, 1));
+        let usdc = &activity.totals["USDC"];
+        assert_eq!((usdc.sent.as_str(), usdc.received.as_str()), ("3", "7"));
+        assert_eq!(activity.totals.len(), 2);
+    }
+
+    #[test]
+    fn fees_are_charged_only_for_signed_transactions() {
+        let activity = AccountActivity::new(ME, &history());
+        // s1, s2, s3 and self at 10 each, the failed one at 4; received ones are the sender's cost
+        assert_eq!(activity.fees, "44");
+    }
+
+    #[test]
+    fn heights_span_the_history_and_timestamps_come_from_it() {
+        let activity = AccountActivity::new(ME, &history());
+        assert_eq!((activity.first_height, activity.last_height), (Some(5), Some(30)));
+        assert_eq!((activity.first_timestamp, activity.last_timestamp), (None, None));
+        assert!(activity.complete);
+
+        let fetched = FullHistory {
+            address: ME.to_string(),
+            count: 1,
+            pages: 1,
+            duplicates: 0,
+            complete: false,
+            max_transactions: 1,
+            first_height: Some(10),
+            last_height: Some(10),
+            first_timestamp: Some(1_700_000_000),
+            last_timestamp: Some(1_700_000_050),
+            transactions: vec![sent("s1", 10, "alice", 100, "AMA")],
+        };
+        let activity = AccountActivity::from_history(&fetched);
+        assert_eq!((activity.first_timestamp, activity.last_timestamp), (Some(1_700_000_000), Some(1_700_000_050)));
+        assert!(!activity.complete);
+        assert_eq!(activity.totals["AMA"].sent, "100");
+    }
+
+    #[test]
+    fn counterparties_rank_by_count_and_by_volume_per_symbol() {
+        let activity = AccountActivity::new(ME, &history());
+        let by_count: Vec<(&str, usize, usize, usize)> =
+            activity.top_by_count.iter().map(|c| (c.address.as_str(), c.transfers, c.sent, c.received)).collect();
+        assert_eq!(by_count, vec![("alice", 3, 2, 1), ("bob", 1, 0, 1), ("carol", 1, 1, 0)]);
+
+        let ama: Vec<(&str, &str)> =
+            activity.top_by_volume["AMA"].iter().map(|c| (c.address.as_str(), c.volume.as_str())).collect();
+        assert_eq!(ama, vec![("bob", "1000"), ("alice", "150")]);
+        let usdc = &activity.top_by_volume["USDC"];
+        assert_eq!((usdc[0].address.as_str(), usdc[0].received.as_str(), usdc[0].transfers), ("alice", "7", 1));
+        assert_eq!((usdc[1].address.as_str(), usdc[1].sent.as_str()), ("carol", "3"));
+    }
+
+    #[test]
+    fn rankings_keep_the_top_ten() {
+        let history: Vec<Transaction> =
+            (0..15u128).map(|i| sent(&format!("t{}", i), i as u64, &format!("peer{:02}", i), i + 1, "AMA")).collect();
+        let activity = AccountActivity::new(ME, &history);
+        assert_eq!(activity.top_by_count.len(), TOP_COUNTERPARTIES);
+        assert_eq!(activity.top_by_count[0].address, "peer00", "ties break by address");
+        let volume = &activity.top_by_volume["AMA"];
+        assert_eq!(volume.len(), TOP_COUNTERPARTIES);
+        assert_eq!((volume[0].address.as_str(), volume[9].address.as_str()), ("peer14", "peer05"));
+    }
+
+    #[test]
+    fn an_empty_history_has_no_activity() {
+        let activity = AccountActivity::new(ME, &[]);
+        assert_eq!((activity.tx_count, activity.fees.as_str()), (0, "0"));
+        assert!(activity.totals.is_empty() && activity.top_by_count.is_empty() && activity.top_by_volume.is_empty());
+        let fields = serde_json::to_value(&activity).unwrap();
+        assert!(fields.get("first_height").is_none() && fields.get("first_timestamp").is_none());
+    }
+}
@@ -0,0 +1,152 @@
+/// Where the node mounts its API, unless `BLOCKCHAIN_API_PREFIX` says otherwise.
+pub const DEFAULT_API_PREFIX: &str = "/api";
+/// Where the node mounts transaction submission, unless `BLOCKCHAIN_TX_API_PREFIX` says
+/// otherwise. Same as the API prefix on stock nodes.
+pub const DEFAULT_TX_API_PREFIX: &str = "/api";
+/// Prefix node paths are written with in the catalog and in error messages.
+const CANONICAL_PREFIX: &str = "/api";
+
+/// Builds node paths under the configured prefixes, so no caller hard-codes `/api`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiPaths {
+    api: String,
+    tx: String,
+}
+
+impl Default for ApiPaths {
+    fn default() -> Self {
+        Self { api: DEFAULT_API_PREFIX.to_string(), tx: DEFAULT_TX_API_PREFIX.to_string() }
+    }
+}
+
+impl ApiPaths {
+    pub fn new(api: String, tx: String) -> Result<Self, String> {
+        Ok(Self { api: validate_prefix(api)?, tx: validate_prefix(tx)? })
+    }
+
+    pub fn api_prefix(&self) -> &str {
+        &self.api
+    }
+
+    pub fn tx_prefix(&self) -> &str {
+        &self.tx
+    }
+
+    /// `chain/tip` -> `/api/chain/tip`
+    pub fn api(&self, path: &str) -> String {
+        join(&self.api, path)
+    }
+
+    /// `tx/submit` -> `/api/tx/submit`
+    pub fn tx(&self, path: &str) -> String {
+        join(&self.tx, path)
+    }
+
+    /// A catalog path (`/api/chain/tip`) under the configured API prefix. Other paths are
+    /// returned as they are.
+    pub fn rebase(&self, canonical: &str) -> String {
+        match canonical.strip_prefix(CANONICAL_PREFIX).filter(|rest| rest.starts_with('/')) {
+            Some(rest) => self.api(rest),
+            None => canonical.to_string(),
+        }
+    }
+
+    /// The catalog form of a path built under the configured API prefix; the inverse of
+    /// [`rebase`](Self::rebase).
+    pub fn canonical(&self, path: &str) -> String {
+        match path.strip_prefix(self.api.as_str()).filter(|rest| rest.starts_with('/')) {
+            Some(rest) => join(CANONICAL_PREFIX, rest),
+            None => path.to_string(),
+        }
+    }
+}
+
+/// A prefix must start with `/` and not end with one; empty mounts the API at the root.
+pub fn validate_prefix(prefix: String) -> Result<String, String> {
+    let prefix = prefix.trim().to_string();
+    if prefix.is_empty() {
+        return Ok(prefix);
+    }
+    if !prefix.starts_with('/') {
+        return Err(format!("{} must begin with /", prefix));
+    }
+    if prefix.ends_with('/') {
+        return Err(format!("{} must not end with /", prefix));
+    }
+    if prefix.contains("//") || prefix.contains(['?', '#']) {
+        return Err(format!("{} is not a plain path", prefix));
+    }
+    Ok(prefix)
+}
+
+/// Joins `prefix` and `path` with exactly one `/` between them.
+pub fn join(prefix: &str, path: &str) -> String {
+    format!("{}/{}", prefix.trim_end_matches('/'), path.trim_start_matches('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn joining_puts_exactly_one_slash_between_prefix_and_path() {
+        for (prefix, path, expected) in [
+            ("/api", "chain/tip", "/api/chain/tip"),
+            ("/api", "/chain/tip", "/api/chain/tip"),
+            ("/api/", "//chain/tip", "/api/chain/tip"),
+            ("", "chain/tip", "/chain/tip"),
+            ("", "/chain/tip", "/chain/tip"),
+            ("/node/amadeus/v2", "tx/submit", "/node/amadeus/v2/tx/submit"),
+            ("/api", "", "/api/"),
+        ] {
+            assert_eq!(join(prefix, path), expected, "{:?} + {:?}", prefix, path);
+        }
+    }
+
+    #[test]
+    fn prefixes_are_validated() {
+        assert_eq!(validate_prefix("/api".into()), Ok("/api".into()));
+        assert_eq!(validate_prefix(" /node/v2 ".into()), Ok("/node/v2".into()));
+        assert_eq!(validate_prefix("".into()), Ok("".into()));
+        assert_eq!(validate_prefix("  ".into()), Ok("".into()));
+        assert_eq!(validate_prefix("api".into()), Err("api must begin with /".into()));
+        assert_eq!(validate_prefix("/api/".into()), Err("/api/ must not end with /".into()));
+        assert_eq!(validate_prefix("/".into()), Err("/ must not end with /".into()));
+        for bad in ["/api//v1", "/api?x=1", "/api#v1"] {
+            assert!(validate_prefix(bad.into()).unwrap_err().contains("not a plain path"), "{}", bad);
+        }
+        assert!(ApiPaths::new("/api".into(), "api/v1".into()).is_err());
+    }
+
+    #[test]
+    fn paths_are_built_under_their_own_prefix() {
+        let stock = ApiPaths::default();
+        assert_eq!((stock.api("chain/tip"), stock.tx("tx/submit")), ("/api/chain/tip".into(), "/api/tx/submit".into()));
+
+        let custom = ApiPaths::new("/node/v2".into(), "/api/v1".into()).unwrap();
+        assert_eq!(custom.api("chain/tip"), "/node/v2/chain/tip");
+        assert_eq!(custom.tx("tx/submit"), "/api/v1/tx/submit");
+        assert_eq!((custom.api_prefix(), custom.tx_prefix()), ("/node/v2", "/api/v1"));
+
+        let root = ApiPaths::new(String::new(), String::new()).unwrap();
+        assert_eq!(root.api("chain/tip"), "/chain/tip");
+    }
+
+    #[test]
+    fn catalog_paths_rebase_and_back() {
+        for paths in [
+            ApiPaths::default(),
+            ApiPaths::new("/node/v2".into(), "/api".into()).unwrap(),
+            ApiPaths::new(String::new(), String::new()).unwrap(),
+        ] {
+            let built = paths.rebase("/api/chain/tip");
+            assert_eq!(built, paths.api("chain/tip"));
+            assert_eq!(paths.canonical(&built), "/api/chain/tip");
+        }
+        let nested = ApiPaths::new("/node/v2".into(), "/api".into()).unwrap();
+        // Only whole segments match a prefix
+        assert_eq!(nested.rebase("/apix/chain/tip"), "/apix/chain/tip");
+        assert_eq!(nested.canonical("/node/v20/chain/tip"), "/node/v20/chain/tip");
+        assert_eq!(nested.canonical("/other/path"), "/other/path");
+    }
+}
@@ -0,0 +1,93 @@
+//! JSON-RPC 2.0 batching of node GETs, for deployments that expose a batch endpoint.
+//! One POST carries many `GET` calls; each answer is matched back by id, so a failed
+//! call only fails its own item.
+
+use serde_json::{json, Value};
+
+/// Batch endpoint relative to the read API prefix; probed at startup.
+pub const BATCH_ENDPOINT: &str = "/api/rpc/batch";
+/// Calls per batch; larger fan-outs are split into several batches.
+pub const MAX_BATCH_CALLS: usize = 100;
+
+/// Body of a batch GETting each of `paths`; the call id is the path's index.
+pub fn request_body(paths: &[String]) -> Value {
+    Value::Array(
+        paths
+            .iter()
+            .enumerate()
+            .map(|(id, path)| json!({ "jsonrpc": "2.0", "id": id, "method": "GET", "params": { "path": path } }))
+            .collect(),
+    )
+}
+
+/// Splits a batch response into one result per requested path, in request order. Calls
+/// the node answered with an error, or did not answer at all, fail on their own.
+pub fn split_response(response: &Value, calls: usize) -> Result<Vec<Result<Value, String>>, String> {
+    let answers = response.as_array().ok_or("batch response is not an array")?;
+    let mut results: Vec<Result<Value, String>> = vec![Err("no answer in the batch response".to_string()); calls];
+    for answer in answers {
+        let Some(slot) = answer["id"].as_u64().and_then(|id| results.get_mut(id as usize)) else {
+            continue;
+        };
+        *slot = match (answer.get("result"), answer.get("error")) {
+            (Some(result), None) => Ok(result.clone()),
+            (_, Some(error)) => Err(error["message"].as_str().map_or_else(|| error.to_string(), String::from)),
+            (None, None) => Err("batch answer has neither result nor error".to_string()),
+        };
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_path_is_a_get_call_identified_by_its_index() {
+        let body = request_body(&["/api/chain/height/1".to_string(), "/api/chain/height/2".to_string()]);
+        assert_eq!(
+            body,
+            json!([
+                { "jsonrpc": "2.0", "id": 0, "method": "GET", "params": { "path": "/api/chain/height/1" } },
+                { "jsonrpc": "2.0", "id": 1, "method": "GET", "params": { "path": "/api/chain/height/2" } }
+            ])
+        );
+    }
+
+    #[test]
+    fn answers_are_matched_by_id_not_position() {
+        let response = json!([
+            { "jsonrpc": "2.0", "id": 1, "result": { "n": 1 } },
+            { "jsonrpc": "2.0", "id": 0, "result": { "n": 0 } }
+        ]);
+        let results = split_response(&response, 2).unwrap();
+        assert_eq!(results, vec![Ok(json!({ "n": 0 })), Ok(json!({ "n": 1 }))]);
+    }
+
+    #[test]
+    fn a_failed_call_fails_alone() {
+        let response = json!([
+            { "jsonrpc": "2.0", "id": 0, "result": { "n": 0 } },
+            { "jsonrpc": "2.0", "id": 1, "error": { "code": -32000, "message": "height pruned" } },
+            { "jsonrpc": "2.0", "id": 2, "error": { "code": -32000 } },
+            { "jsonrpc": "2.0", "id": 3 }
+        ]);
+        let results = split_response(&response, 5).unwrap();
+        assert_eq!(results[0], Ok(json!({ "n": 0 })));
+        assert_eq!(results[1], Err("height pruned".to_string()));
+        assert_eq!(results[2], Err(r#"{"code":-32000}"#.to_string()));
+        assert_eq!(results[3], Err("batch answer has neither result nor error".to_string()));
+        assert_eq!(results[4], Err("no answer in the batch response".to_string()));
+    }
+
+    #[test]
+    fn unknown_ids_are_ignored() {
+        let response = json!([{ "id": 7, "result": 1 }, { "id": "0", "result": 2 }, { "result": 3 }]);
+        assert_eq!(split_response(&response, 1).unwrap(), vec![Err("no answer in the batch response".to_string())]);
+    }
+
+    #[test]
+    fn a_response_that_is_not_an_array_fails_the_batch() {
+        assert_eq!(split_response(&json!({ "error": "not_found" }), 1).unwrap_err(), "batch response is not an array");
+    }
+}
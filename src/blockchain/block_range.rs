@@ -0,0 +1,118 @@
+use super::types::BlockEntry;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Most heights one get_block_range call may span.
+pub const MAX_BLOCK_RANGE: u64 = 100;
+
+/// The entries at one height, with the counts a summary needs.
+#[derive(Debug, Clone, Serialize)]
+pub struct HeightEntries {
+    pub height: u64,
+    pub entry_count: usize,
+    pub tx_count: u64,
+    pub entries: Vec<BlockEntry>,
+}
+
+/// A height that could not be fetched; the rest of the range is still returned.
+#[derive(Debug, Clone, Serialize)]
+pub struct FailedHeight {
+    pub height: u64,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BlockRange {
+    pub start_height: u64,
+    pub end_height: u64,
+    pub entry_count: usize,
+    pub tx_count: u64,
+    /// Every fetched height of the range, in height order
+    pub heights: Vec<HeightEntries>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub failed: Vec<FailedHeight>,
+}
+
+impl BlockRange {
+    /// Groups `entries` by height over `start..=end`. Heights in `failed` are reported
+    /// there; any other height without entries is listed with none.
+    pub fn assemble(start: u64, end: u64, entries: Vec<BlockEntry>, mut failed: Vec<FailedHeight>) -> Self {
+        let mut by_height: BTreeMap<u64, Vec<BlockEntry>> = BTreeMap::new();
+        for entry in entries {
+            by_height.entry(entry.header.height).or_default().push(entry);
+        }
+        failed.sort_by_key(|f| f.height);
+        let heights: Vec<HeightEntries> = (start..=end)
+            .filter(|h| !failed.iter().any(|f| f.height == *h))
+            .map(|height| {
+                let entries = by_height.remove(&height).unwrap_or_default();
+                HeightEntries {
+                    height,
+                    entry_count: entries.len(),
+                    tx_count: entries.iter().map(|e| e.tx_count).sum(),
+                    entries,
+                }
+            })
+            .collect();
+        Self {
+            start_height: start,
+            end_height: end,
+            entry_count: heights.iter().map(|h| h.entry_count).sum(),
+            tx_count: heights.iter().map(|h| h.tx_count).sum(),
+            heights,
+            failed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn entry(height: u64, hash: &str, tx_count: u64) -> BlockEntry {
+        serde_json::from_value(json!({
+            "hash": hash,
+            "header": {
+                "slot": height, "height": height, "dr": "dr", "vr": "vr", "prev_hash": "prev", "signer": "signer",
+                "root_tx": "rt", "root_validator": "rv", "prev_slot": height.saturating_sub(1)
+            },
+            "tx_count": tx_count
+        }))
+        .unwrap()
+    }
+
+    fn failed(height: u64) -> FailedHeight {
+        FailedHeight { height, error: "HTTP 500".into() }
+    }
+
+    #[test]
+    fn entries_are_grouped_by_height_in_order() {
+        let entries = vec![entry(12, "c", 4), entry(10, "a", 1), entry(10, "b", 2)];
+        let range = BlockRange::assemble(10, 12, entries, Vec::new());
+
+        let heights: Vec<_> = range.heights.iter().map(|h| (h.height, h.entry_count, h.tx_count)).collect();
+        assert_eq!(heights, vec![(10, 2, 3), (11, 0, 0), (12, 1, 4)]);
+        assert_eq!((range.entry_count, range.tx_count), (3, 7));
+        let hashes: Vec<_> = range.heights[0].entries.iter().map(|e| e.hash.as_str()).collect();
+        assert_eq!(hashes, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn failed_heights_are_reported_apart_and_sorted() {
+        let range = BlockRange::assemble(1, 4, vec![entry(1, "a", 1), entry(4, "d", 1)], vec![failed(3), failed(2)]);
+        assert_eq!(range.heights.iter().map(|h| h.height).collect::<Vec<_>>(), vec![1, 4]);
+        assert_eq!(range.failed.iter().map(|f| f.height).collect::<Vec<_>>(), vec![2, 3]);
+        assert_eq!(range.tx_count, 2);
+    }
+
+    #[test]
+    fn entries_outside_the_range_are_ignored() {
+        let range = BlockRange::assemble(5, 5, vec![entry(4, "a", 9), entry(5, "b", 1), entry(6, "c", 9)], Vec::new());
+        assert_eq!((range.start_height, range.end_height, range.entry_count, range.tx_count), (5, 5, 1, 1));
+
+        // With no failures, `failed` is left out of the output
+        let json = serde_json::to_value(&range).unwrap();
+        assert!(json.get("failed").is_none());
+    }
+}
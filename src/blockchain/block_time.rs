@@ -0,0 +1,455 @@
+//! Finding the entries produced around a moment. Entry timestamps grow with height, so
+//! the heights between genesis and the tip are binary-searched one probe at a time.
+
+use super::clock;
+use super::types::BlockEntry;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Probes one search may spend unless `MCP_MAX_TIME_PROBES` says otherwise; enough for
+/// about 2^38 heights.
+pub const MAX_TIME_PROBES: u64 = 40;
+pub const GENESIS_HEIGHT: u64 = 0;
+
+/// A moment as a client sends it: unix seconds, or RFC3339 text.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum TimeTarget {
+    Unix(u64),
+    Text(String),
+}
+
+impl TimeTarget {
+    /// Unix seconds; fractional seconds are dropped.
+    pub fn unix_secs(&self) -> Result<u64, String> {
+        match self {
+            Self::Unix(secs) => Ok(*secs),
+            Self::Text(text) => {
+                let text = text.trim();
+                if text.bytes().all(|b| b.is_ascii_digit()) {
+                    return text.parse().map_err(|_| format!("timestamp {} is too large", text));
+                }
+                parse_rfc3339(text).ok_or_else(|| {
+                    format!("timestamp {} is neither unix seconds nor RFC3339 (e.g. 2024-06-01T12:00:00Z)", text)
+                })
+            }
+        }
+    }
+}
+
+/// Where the target fell relative to the chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimePosition {
+    /// An entry carries the target timestamp
+    Exact,
+    Between,
+    /// Before the genesis entry; only `above` is set
+    BeforeGenesis,
+    /// After the tip entry but not later than now; only `below` is set
+    AfterTip,
+    /// Later than now; only `below` is set
+    Future,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TimedEntry {
+    pub height: u64,
+    pub hash: String,
+    /// Unix seconds
+    pub timestamp: u64,
+    pub timestamp_ms: u64,
+    /// Seconds from the target to this entry; negative before it
+    pub offset_secs: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BlockAtTime {
+    /// Unix seconds searched for
+    pub target: u64,
+    pub position: TimePosition,
+    /// Latest entry found at or before the target
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub below: Option<TimedEntry>,
+    /// Earliest entry found after the target
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub above: Option<TimedEntry>,
+    pub probes: u64,
+    /// False when the probe budget ran out with heights still between `below` and `above`
+    pub converged: bool,
+    /// Probed heights without a timestamped entry, which the search stepped around
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct Probe {
+    height: u64,
+    hash: String,
+    timestamp_ms: u64,
+}
+
+/// Binary search state: ask `next_height`, fetch it, `record` the entries, repeat.
+#[derive(Debug, Clone)]
+pub struct TimeSearch {
+    target_ms: u64,
+    tip: u64,
+    max_probes: u64,
+    probes: u64,
+    /// Where the lower bound is looked for until one is found: genesis, then upwards past
+    /// heights without a timestamp
+    first: u64,
+    /// Where the upper bound is looked for until one is found: the tip, then downwards
+    last: u64,
+    low: Option<Probe>,
+    high: Option<Probe>,
+    /// Probed heights without a timestamped entry
+    skipped: BTreeSet<u64>,
+}
+
+impl TimeSearch {
+    pub fn new(target_secs: u64, tip: u64, max_probes: u64) -> Self {
+        Self {
+            target_ms: target_secs.saturating_mul(1000),
+            tip,
+            max_probes,
+            probes: 0,
+            first: GENESIS_HEIGHT,
+            last: tip,
+            low: None,
+            high: None,
+            skipped: BTreeSet::new(),
+        }
+    }
+
+    /// The height to probe next: genesis, then the tip, then the middle of the bracket.
+    /// `None` once the answer is known or the budget is spent.
+    pub fn next_height(&self) -> Option<u64> {
+        if self.probes >= self.max_probes {
+            return None;
+        }
+        self.pending()
+    }
+
+    /// The height the search still needs, whatever the budget.
+    fn pending(&self) -> Option<u64> {
+        let Some(low) = &self.low else {
+            return (self.first <= self.last).then_some(self.first);
+        };
+        if low.timestamp_ms > self.target_ms {
+            return None;
+        }
+        let Some(high) = &self.high else {
+            return (self.last > low.height).then_some(self.last);
+        };
+        if high.timestamp_ms <= self.target_ms {
+            return None;
+        }
+        // The unprobed height nearest the middle, if any is left between the bounds
+        let mid = low.height + (high.height - low.height) / 2;
+        let up = (mid.max(low.height + 1)..high.height).find(|h| !self.skipped.contains(h));
+        let down = (low.height + 1..mid).rev().find(|h| !self.skipped.contains(h));
+        match (up, down) {
+            (Some(up), Some(down)) => Some(if up - mid <= mid - down { up } else { down }),
+            (up, down) => up.or(down),
+        }
+    }
+
+    /// Takes the entries at the height `next_height` gave. The first timestamped entry
+    /// places the height; a height without one is stepped around: the next height up or
+    /// down while a bound is still missing, a neighbour of the middle otherwise.
+    pub fn record(&mut self, height: u64, entries: &[BlockEntry]) {
+        self.probes += 1;
+        let Some((entry, timestamp)) = entries.iter().find_map(|e| e.header.timestamp.map(|t| (e, t))) else {
+            if self.low.is_none() {
+                self.first = height.saturating_add(1);
+            } else if self.high.is_none() {
+                self.last = height.saturating_sub(1);
+            }
+            self.skipped.insert(height);
+            return;
+        };
+        let probe = Probe { height, hash: entry.hash.clone(), timestamp_ms: clock::entry_millis(timestamp) };
+        if self.low.is_none() {
+            self.low = Some(probe);
+        } else if self.high.is_none() || probe.timestamp_ms > self.target_ms {
+            self.high = Some(probe);
+        } else {
+            self.low = Some(probe);
+        }
+    }
+
+    /// The result, with `now_secs` telling a moment after the tip from one in the future.
+    pub fn finish(self, now_secs: u64) -> BlockAtTime {
+        let target = self.target_ms / 1000;
+        let timed = |probe: Probe| TimedEntry {
+            height: probe.height,
+            hash: probe.hash,
+            timestamp: probe.timestamp_ms / 1000,
+            timestamp_ms: probe.timestamp_ms,
+            offset_secs: (probe.timestamp_ms as i64 - self.target_ms as i64) / 1000,
+        };
+        let converged = self.pending().is_none();
+        let tip = self.tip;
+        let after = |latest: Probe| {
+            let which = if latest.height == tip { "the tip entry" } else { "the latest entry with a timestamp" };
+            let (position, note) = if target > now_secs {
+                (TimePosition::Future, format!("the target is in the future; below is {}", which))
+            } else {
+                (TimePosition::AfterTip, format!("no entry has been produced since the target; below is {}", which))
+            };
+            (position, Some(latest), None, Some(note))
+        };
+        let (position, below, above, note) = match (self.low, self.high) {
+            (Some(low), _) if low.timestamp_ms > self.target_ms => {
+                let note = if low.height == GENESIS_HEIGHT {
+                    "the target is before the genesis entry; above is the genesis entry".to_string()
+                } else {
+                    format!("the target is before the first entry with a timestamp, at height {}; above is that entry", low.height)
+                };
+                (TimePosition::BeforeGenesis, None, Some(low), Some(note))
+            }
+            (Some(_), Some(high)) if high.timestamp_ms < self.target_ms => after(high),
+            // Nothing above `low` carries a timestamp
+            (Some(low), None) if converged && low.timestamp_ms < self.target_ms => after(low),
+            (Some(_), Some(high)) if high.timestamp_ms == self.target_ms => (TimePosition::Exact, Some(high), None, None),
+            (None, _) => {
+                let note = if converged {
+                    "no entry between genesis and the tip has a timestamp"
+                } else {
+                    "the probe budget ran out before an entry with a timestamp was found"
+                };
+                (TimePosition::Between, None, None, Some(note.to_string()))
+            }
+            (low, high) => {
+                let exact = low.as_ref().is_some_and(|low| low.timestamp_ms == self.target_ms);
+                (if exact { TimePosition::Exact } else { TimePosition::Between }, low, high, None)
+            }
+        };
+        BlockAtTime {
+            target,
+            position,
+            below: below.map(timed),
+            above: above.map(timed),
+            probes: self.probes,
+            converged,
+            skipped: self.skipped.into_iter().collect(),
+            note,
+        }
+    }
+}
+
+/// Unix seconds of `YYYY-MM-DDTHH:MM:SS[.fraction](Z|±HH:MM)`; a space may stand in for `T`.
+fn parse_rfc3339(text: &str) -> Option<u64> {
+    let (date, time) = text.split_once(['T', 't', ' '])?;
+    let mut date = date.splitn(3, '-');
+    let year: i64 = date.next()?.parse().ok()?;
+    let month: u32 = date.next()?.parse().ok()?;
+    let day: u32 = date.next()?.parse().ok()?;
+
+    let (clock, offset_secs) = if let Some(clock) = time.strip_suffix(['Z', 'z']) {
+        (clock, 0i64)
+    } else {
+        let split = time.rfind(['+', '-'])?;
+        let (clock, offset) = time.split_at(split);
+        let (hours, minutes) = offset[1..].split_once(':')?;
+        let offset = hours.parse::<i64>().ok()? * 3600 + minutes.parse::<i64>().ok()? * 60;
+        (clock, if time[split..].starts_with('-') { -offset } else { offset })
+    };
+    let clock = clock.split_once('.').map_or(clock, |(whole, _)| whole);
+    let mut clock = clock.splitn(3, ':');
+    let hour: i64 = clock.next()?.parse().ok()?;
+    let minute: i64 = clock.next()?.parse().ok()?;
+    let second: i64 = clock.next()?.parse().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+
+    let secs = days_from_civil(year, month, day) * 86_400 + hour * 3600 + minute * 60 + second - offset_secs;
+    u64::try_from(secs).ok()
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date; the inverse of the locale's
+/// calendar conversion.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = year - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = i64::from((month + 9) % 12);
+    let doy = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Unix seconds of the genesis entry in the test chains; each height adds `STEP`.
+    const GENESIS_SECS: u64 = 1_700_000_000;
+    const STEP: u64 = 10;
+    const TIP: u64 = 1_000;
+    const NOW: u64 = GENESIS_SECS + STEP * TIP + 3_600;
+
+    fn entry(height: u64, timestamp: Option<u64>) -> BlockEntry {
+        serde_json::from_value(json!({
+            "hash": format!("h{}", height),
+            "header": {
+                "slot": height,
+                "height": height,
+                "dr": "dr",
+                "vr": "vr",
+                "prev_hash": "prev",
+                "signer": "signer",
+                "root_tx": "rt",
+                "root_validator": "rv",
+                "prev_slot": height.saturating_sub(1),
+                "timestamp": timestamp
+            },
+            "tx_count": 0
+        }))
+        .unwrap()
+    }
+
+    /// Runs a search over heights `0..=tip`, where `empty` heights have no entries and
+    /// `untimed` ones an entry without a timestamp.
+    fn search(target: u64, tip: u64, max_probes: u64, empty: &[u64], untimed: &[u64]) -> BlockAtTime {
+        let mut search = TimeSearch::new(target, tip, max_probes);
+        let mut probed = Vec::new();
+        while let Some(height) = search.next_height() {
+            assert!(height <= tip, "probed {} beyond the tip", height);
+            assert!(!probed.contains(&height), "probed {} twice", height);
+            probed.push(height);
+            let entries = match (empty.contains(&height), untimed.contains(&height)) {
+                (true, _) => vec![],
+                (false, true) => vec![entry(height, None)],
+                (false, false) => vec![entry(height, Some(GENESIS_SECS + STEP * height))],
+            };
+            search.record(height, &entries);
+        }
+        search.finish(NOW)
+    }
+
+    fn heights(found: &BlockAtTime) -> (Option<u64>, Option<u64>) {
+        (found.below.as_ref().map(|e| e.height), found.above.as_ref().map(|e| e.height))
+    }
+
+    #[test]
+    fn target_between_two_entries() {
+        let found = search(GENESIS_SECS + STEP * 500 + 5, TIP, MAX_TIME_PROBES, &[], &[]);
+        assert_eq!(found.position, TimePosition::Between);
+        assert_eq!(heights(&found), (Some(500), Some(501)));
+        assert!(found.converged);
+        assert_eq!((found.below.unwrap().offset_secs, found.above.unwrap().offset_secs), (-5, 5));
+    }
+
+    #[test]
+    fn target_on_an_entry() {
+        let found = search(GENESIS_SECS + STEP * 300, TIP, MAX_TIME_PROBES, &[], &[]);
+        assert_eq!(found.position, TimePosition::Exact);
+        assert_eq!(found.below.unwrap().height, 300);
+    }
+
+    #[test]
+    fn target_before_genesis() {
+        let found = search(GENESIS_SECS - 60, TIP, MAX_TIME_PROBES, &[], &[]);
+        assert_eq!(found.position, TimePosition::BeforeGenesis);
+        assert_eq!(heights(&found), (None, Some(GENESIS_HEIGHT)));
+        assert_eq!(found.probes, 1);
+        assert!(found.note.unwrap().contains("genesis entry"));
+    }
+
+    #[test]
+    fn target_in_the_future_or_after_the_tip() {
+        let future = search(NOW + 86_400, TIP, MAX_TIME_PROBES, &[], &[]);
+        assert_eq!(future.position, TimePosition::Future);
+        assert_eq!(heights(&future), (Some(TIP), None));
+        assert_eq!(future.probes, 2);
+
+        let after_tip = search(NOW - 60, TIP, MAX_TIME_PROBES, &[], &[]);
+        assert_eq!(after_tip.position, TimePosition::AfterTip);
+        assert!(after_tip.note.unwrap().contains("the tip entry"));
+    }
+
+    #[test]
+    fn untimed_genesis_moves_the_lower_bound_up() {
+        let found = search(GENESIS_SECS + STEP * 500 + 5, TIP, MAX_TIME_PROBES, &[], &[0]);
+        assert_eq!(heights(&found), (Some(500), Some(501)));
+        assert_eq!(found.skipped, [0]);
+
+        let before = search(GENESIS_SECS - 60, TIP, MAX_TIME_PROBES, &[0], &[1]);
+        assert_eq!(before.position, TimePosition::BeforeGenesis);
+        assert_eq!(heights(&before), (None, Some(2)));
+        assert!(before.note.unwrap().contains("height 2"));
+        assert_eq!(before.skipped, [0, 1]);
+    }
+
+    #[test]
+    fn empty_tip_moves_the_upper_bound_down() {
+        let found = search(NOW + 86_400, TIP, MAX_TIME_PROBES, &[TIP], &[TIP - 1]);
+        assert_eq!(found.position, TimePosition::Future);
+        assert_eq!(heights(&found), (Some(TIP - 2), None));
+        assert!(found.converged);
+        assert!(found.note.unwrap().contains("latest entry with a timestamp"));
+    }
+
+    #[test]
+    fn empty_heights_inside_the_bracket_are_stepped_around() {
+        let gap: Vec<u64> = (498..=502).collect();
+        let found = search(GENESIS_SECS + STEP * 500 + 5, TIP, MAX_TIME_PROBES, &gap[..3], &gap[3..]);
+        assert_eq!(found.position, TimePosition::Between);
+        assert_eq!(heights(&found), (Some(497), Some(503)));
+        assert!(found.converged);
+        assert!(found.skipped.iter().all(|h| gap.contains(h)), "{:?}", found.skipped);
+    }
+
+    #[test]
+    fn chain_without_timestamps() {
+        let all: Vec<u64> = (0..=5).collect();
+        let found = search(GENESIS_SECS, 5, MAX_TIME_PROBES, &[], &all);
+        assert_eq!(heights(&found), (None, None));
+        assert!(found.converged);
+        assert_eq!(found.probes, 6);
+        assert!(found.note.unwrap().contains("no entry"));
+    }
+
+    #[test]
+    fn probe_budget_is_respected() {
+        let found = search(GENESIS_SECS + STEP * 500 + 5, TIP, 3, &[], &[]);
+        assert_eq!(found.probes, 3);
+        assert!(!found.converged);
+        assert_eq!(heights(&found), (Some(500), Some(TIP)));
+
+        // Untimed heights count against the budget too
+        let untimed: Vec<u64> = (0..10).collect();
+        let starved = search(GENESIS_SECS + STEP * 500, TIP, 4, &[], &untimed);
+        assert_eq!(starved.probes, 4);
+        assert!(!starved.converged);
+        assert_eq!(heights(&starved), (None, None));
+        assert!(starved.note.unwrap().contains("budget"));
+    }
+
+    #[test]
+    fn full_budget_converges_on_a_large_chain() {
+        // Second timestamps stay below the millisecond cutoff up to here
+        let tip = 1u64 << 30;
+        let found = search(GENESIS_SECS + STEP * 123_456_789 + 1, tip, MAX_TIME_PROBES, &[], &[]);
+        assert!(found.converged);
+        assert_eq!(heights(&found), (Some(123_456_789), Some(123_456_790)));
+        assert!(found.probes <= MAX_TIME_PROBES);
+    }
+
+    #[test]
+    fn time_targets_parse() {
+        assert_eq!(TimeTarget::Unix(5).unix_secs(), Ok(5));
+        assert_eq!(TimeTarget::Text("1718000000".into()).unix_secs(), Ok(1_718_000_000));
+        assert_eq!(TimeTarget::Text("2024-06-01T12:00:00Z".into()).unix_secs(), Ok(1_717_243_200));
+        assert_eq!(TimeTarget::Text("2024-06-01T14:00:00.250+02:00".into()).unix_secs(), Ok(1_717_243_200));
+        assert!(TimeTarget::Text("2024-13-01T00:00:00Z".into()).unix_secs().is_err());
+        assert!(TimeTarget::Text("yesterday".into()).unix_secs().is_err());
+    }
+}
@@ -0,0 +1,100 @@
+use super::error::{BlockchainError, Result};
+use super::types::{BlockEntry, Transaction};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+pub const DEFAULT_BLOCK_TXS: u64 = 100;
+pub const MAX_BLOCK_TXS: u64 = 500;
+
+/// An entry from `chain/height_with_txs` with its transactions embedded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockEntryWithTxs {
+    #[serde(flatten)]
+    pub entry: BlockEntry,
+    #[serde(default)]
+    pub txs: Vec<Transaction>,
+}
+
+/// The entries in a `chain/height_with_txs` response.
+pub fn entries_from(response: &Value) -> Result<Vec<BlockEntryWithTxs>> {
+    if response.get("error").and_then(Value::as_str) != Some("ok") {
+        return Err(BlockchainError::InvalidResponse("failed to get block entries".to_string()));
+    }
+    let entries = response
+        .get("entries")
+        .ok_or_else(|| BlockchainError::InvalidResponse("missing entries field".to_string()))?;
+    serde_json::from_value(entries.clone())
+        .map_err(|e| BlockchainError::InvalidResponse(format!("failed to parse entries: {}", e)))
+}
+
+/// The transactions of one entry, from `chain/txs_in_entry`.
+#[derive(Debug, Clone, Serialize)]
+pub struct EntryTxs {
+    pub entry_hash: String,
+    pub count: usize,
+    pub txs: Vec<Transaction>,
+    /// Transactions whose shape the typed form does not cover, as the node sent them
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub undecoded: Vec<Value>,
+}
+
+/// The transactions in a `chain/txs_in_entry` response. A transaction that does not fit
+/// `Transaction` is kept raw in `undecoded` rather than failing the whole entry.
+pub fn txs_from(entry_hash: &str, response: &Value) -> Result<EntryTxs> {
+    match response.get("error").and_then(Value::as_str) {
+        Some("ok") => {}
+        Some("not_found") => {
+            return Err(BlockchainError::NotFound { argument: "entry_hash".to_string(), value: entry_hash.to_string() });
+        }
+        _ => return Err(BlockchainError::InvalidResponse("failed to get entry transactions".to_string())),
+    }
+    let items = response
+        .get("txs")
+        .and_then(Value::as_array)
+        .ok_or_else(|| BlockchainError::InvalidResponse("missing txs field".to_string()))?;
+    let mut txs = Vec::with_capacity(items.len());
+    let mut undecoded = Vec::new();
+    for item in items {
+        match serde_json::from_value::<Transaction>(item.clone()) {
+            Ok(tx) => txs.push(tx.with_decoded_action()),
+            Err(_) => undecoded.push(item.clone()),
+        }
+    }
+    Ok(EntryTxs { entry_hash: entry_hash.to_string(), count: items.len(), txs, undecoded })
+}
+
+/// One entry with a page of its transactions. `next_tx_offset` and `note` are set when
+/// transactions were left out, so a large entry is never cut short silently.
+#[derive(Debug, Clone, Serialize)]
+pub struct EntryTxsPage {
+    #[serde(flatten)]
+    pub entry: BlockEntry,
+    /// Transactions the node returned for the entry
+    pub txs_total: usize,
+    pub tx_offset: usize,
+    pub txs: Vec<Transaction>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_tx_offset: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// Keeps at most `limit` transactions of each entry, starting at `offset`.
+pub fn page(entries: Vec<BlockEntryWithTxs>, offset: usize, limit: usize) -> Vec<EntryTxsPage> {
+    entries
+        .into_iter()
+        .map(|BlockEntryWithTxs { entry, txs }| {
+            let txs_total = txs.len();
+            let txs: Vec<Transaction> = txs.into_iter().skip(offset).take(limit).collect();
+            let end = offset.saturating_add(txs.len());
+            let next_tx_offset = (end < txs_total).then_some(end);
+            let note = next_tx_offset.map(|next| {
+                format!(
+                    "showing transactions {}..{} of {}; call again with tx_offset={} for the next page",
+                    offset, end, txs_total, next
+                )
+            });
+            EntryTxsPage { entry, txs_total, tx_offset: offset, txs, next_tx_offset, note }
+        })
+        .collect()
+}
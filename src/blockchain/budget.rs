@@ -0,0 +1,195 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Width of one sliding-window bucket; the hourly count is exact to this granularity.
+const BUCKET_SECS: u64 = 60;
+const WINDOW_SECS: u64 = 3600;
+
+/// Caps on the upstream requests one MCP session may cause. `None` is unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BudgetLimits {
+    /// Over the session's lifetime (`MCP_SESSION_REQUEST_LIMIT`)
+    pub total: Option<u64>,
+    /// Within the last hour (`MCP_SESSION_REQUEST_LIMIT_PER_HOUR`)
+    pub per_hour: Option<u64>,
+}
+
+impl BudgetLimits {
+    pub fn is_unlimited(&self) -> bool {
+        self.total.is_none() && self.per_hour.is_none()
+    }
+}
+
+/// Upstream requests counted for one session: a lifetime total plus per-minute buckets
+/// covering the last hour. Serializable so the worker can keep it in D1 between requests.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionBudget {
+    total: u64,
+    /// (bucket start in unix seconds, requests), oldest first
+    buckets: VecDeque<(u64, u64)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct BudgetUsage {
+    pub total: u64,
+    pub last_hour: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit_per_hour: Option<u64>,
+    pub exhausted: bool,
+}
+
+impl SessionBudget {
+    /// Counts `requests` upstream requests made at `now` (unix seconds).
+    pub fn record(&mut self, requests: u64, now: u64) {
+        if requests == 0 {
+            return;
+        }
+        self.total += requests;
+        let bucket = now - now % BUCKET_SECS;
+        match self.buckets.back_mut() {
+            Some((start, count)) if *start == bucket => *count += requests,
+            _ => self.buckets.push_back((bucket, requests)),
+        }
+        self.prune(now);
+    }
+
+    /// Requests made in the hour before `now`.
+    pub fn last_hour(&self, now: u64) -> u64 {
+        let since = now.saturating_sub(WINDOW_SECS);
+        self.buckets.iter().filter(|(start, _)| start + BUCKET_SECS > since).map(|(_, n)| n).sum()
+    }
+
+    fn prune(&mut self, now: u64) {
+        let since = now.saturating_sub(WINDOW_SECS);
+        while self.buckets.front().is_some_and(|(start, _)| start + BUCKET_SECS <= since) {
+            self.buckets.pop_front();
+        }
+    }
+
+    /// Why the session may not call more tools, if it has used up either limit.
+    pub fn exhausted(&self, limits: BudgetLimits, now: u64) -> Option<String> {
+        if let Some(limit) = limits.total.filter(|limit| self.total >= *limit) {
+            return Some(format!("this session made {} upstream requests, its limit is {}", self.total, limit));
+        }
+        let last_hour = self.last_hour(now);
+        limits.per_hour.filter(|limit| last_hour >= *limit).map(|limit| {
+            format!("this session made {} upstream requests in the last hour, its hourly limit is {}", last_hour, limit)
+        })
+    }
+
+    pub fn usage(&self, limits: BudgetLimits, now: u64) -> BudgetUsage {
+        BudgetUsage {
+            total: self.total,
+            last_hour: self.last_hour(now),
+            limit: limits.total,
+            limit_per_hour: limits.per_hour,
+            exhausted: self.exhausted(limits, now).is_some(),
+        }
+    }
+}
+
+/// Error payload for tool calls refused because the session's budget is used up. The
+/// message is addressed to the agent: further calls will fail the same way.
+pub fn exhausted_error(reason: &str, usage: BudgetUsage) -> serde_json::Value {
+    serde_json::json!({
+        "reason": reason,
+        "usage": usage,
+        "message": "The upstream request budget of this session is exhausted. Stop calling tools and summarize what you found so far.",
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: u64 = 3600;
+    /// A minute boundary, so bucket edges are easy to follow
+    const T0: u64 = 1_718_000_040;
+
+    fn limits(total: Option<u64>, per_hour: Option<u64>) -> BudgetLimits {
+        BudgetLimits { total, per_hour }
+    }
+
+    #[test]
+    fn requests_within_the_hour_are_counted() {
+        let mut budget = SessionBudget::default();
+        budget.record(3, T0);
+        budget.record(2, T0 + 30);
+        budget.record(0, T0 + 45);
+        budget.record(4, T0 + 20 * 60);
+        assert_eq!(budget.last_hour(T0 + 20 * 60), 9);
+        assert_eq!(budget.buckets.len(), 2, "requests in the same minute share a bucket");
+    }
+
+    #[test]
+    fn the_window_slides_a_minute_at_a_time() {
+        let mut budget = SessionBudget::default();
+        budget.record(5, T0);
+        budget.record(1, T0 + 30 * 60);
+        assert_eq!(budget.last_hour(T0 + HOUR - 1), 6);
+        // The first minute's bucket leaves the window once its last second is over an hour old
+        assert_eq!(budget.last_hour(T0 + HOUR + 59), 6);
+        assert_eq!(budget.last_hour(T0 + HOUR + 60), 1);
+        assert_eq!(budget.last_hour(T0 + 2 * HOUR), 0);
+
+        budget.record(1, T0 + 2 * HOUR);
+        assert_eq!(budget.buckets.len(), 1, "expired buckets are dropped when recording");
+        assert_eq!(budget.total, 7, "the lifetime total never slides");
+    }
+
+    #[test]
+    fn unlimited_budgets_are_never_exhausted() {
+        let mut budget = SessionBudget::default();
+        budget.record(1_000_000, T0);
+        assert!(BudgetLimits::default().is_unlimited());
+        assert_eq!(budget.exhausted(BudgetLimits::default(), T0), None);
+    }
+
+    #[test]
+    fn the_lifetime_limit_is_reached_at_the_limit() {
+        let mut budget = SessionBudget::default();
+        budget.record(9, T0);
+        assert_eq!(budget.exhausted(limits(Some(10), None), T0), None);
+        budget.record(1, T0 + 2 * HOUR);
+        assert_eq!(
+            budget.exhausted(limits(Some(10), None), T0 + 2 * HOUR).as_deref(),
+            Some("this session made 10 upstream requests, its limit is 10")
+        );
+    }
+
+    #[test]
+    fn the_hourly_limit_recovers_as_the_window_slides() {
+        let limits = limits(None, Some(5));
+        let mut budget = SessionBudget::default();
+        budget.record(5, T0);
+        assert_eq!(
+            budget.exhausted(limits, T0 + 10).as_deref(),
+            Some("this session made 5 upstream requests in the last hour, its hourly limit is 5")
+        );
+        assert_eq!(budget.exhausted(limits, T0 + HOUR + 60), None);
+    }
+
+    #[test]
+    fn usage_reports_counts_and_limits() {
+        let mut budget = SessionBudget::default();
+        budget.record(4, T0);
+        budget.record(2, T0 + HOUR + 120);
+        let usage = budget.usage(limits(Some(6), Some(100)), T0 + HOUR + 120);
+        assert_eq!(usage, BudgetUsage { total: 6, last_hour: 2, limit: Some(6), limit_per_hour: Some(100), exhausted: true });
+        assert_eq!(
+            serde_json::to_value(budget.usage(BudgetLimits::default(), T0 + HOUR + 120)).unwrap(),
+            serde_json::json!({ "total": 6, "last_hour": 2, "exhausted": false })
+        );
+    }
+
+    #[test]
+    fn budgets_survive_a_round_trip_through_storage() {
+        let mut budget = SessionBudget::default();
+        budget.record(3, T0);
+        budget.record(1, T0 + 90);
+        let stored: SessionBudget = serde_json::from_str(&serde_json::to_string(&budget).unwrap()).unwrap();
+        assert_eq!(stored.usage(BudgetLimits::default(), T0 + 90), budget.usage(BudgetLimits::default(), T0 + 90));
+    }
+}
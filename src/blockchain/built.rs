@@ -0,0 +1,351 @@
+//! Record of the transactions this server built, so history can tell them apart from
+//! activity that went through other tools. Matching and aging are pure; the stdio server
+//! keeps the record in a file next to the disk cache and the worker in D1.
+
+use super::freshness::STALE_BLOB_SECS;
+use super::symbol;
+use super::types::{Argument, TransactionRequest};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Built transactions kept; the oldest are forgotten first.
+pub const MAX_BUILT_TRANSACTIONS: usize = 1000;
+/// A blob not seen on chain this long after it was built is reported abandoned. Clock
+/// nonces are stale by then, so it is unlikely to be submitted at all.
+pub const ABANDONED_AFTER_SECS: u64 = STALE_BLOB_SECS;
+/// File the stdio server keeps the record in, inside `MCP_DISK_CACHE_DIR`.
+pub const BUILT_LOG_FILE: &str = "built_transactions.json";
+pub const DEFAULT_BUILT_LIST_LIMIT: u64 = 50;
+pub const MAX_BUILT_LIST_LIMIT: u64 = 1000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuiltTransaction {
+    pub transaction_hash: String,
+    pub signer: String,
+    pub contract: String,
+    pub function: String,
+    /// Recipient of a Coin.transfer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub destination: Option<String>,
+    /// Transferred or attached atoms
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub amount: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    pub built_at: u64,
+    /// Entry height the transaction was first seen at in a lookup
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub observed_height: Option<u64>,
+}
+
+impl BuiltTransaction {
+    pub fn new(req: &TransactionRequest, transaction_hash: &str, built_at: u64) -> Self {
+        let is_transfer = req.contract == "Coin" && req.function == "transfer";
+        let text = |arg: Option<&Argument>| match arg {
+            Some(Argument::String(s)) | Some(Argument::Utf8 { utf8: s }) => Some(s.clone()),
+            Some(Argument::Number(n)) => Some(n.to_string()),
+            _ => None,
+        };
+        let (destination, amount) = if is_transfer {
+            let destination = match req.args.first() {
+                Some(Argument::Base58 { b58 }) => Some(b58.clone()),
+                other => text(other),
+            };
+            (destination, text(req.args.get(1)))
+        } else {
+            (None, req.attached_amount.clone())
+        };
+        Self {
+            transaction_hash: transaction_hash.to_string(),
+            signer: req.signer.clone(),
+            contract: req.contract.clone(),
+            function: req.function.clone(),
+            destination,
+            amount,
+            symbol: symbol::requested_symbol(req).map(str::to_string),
+            built_at,
+            observed_height: None,
+        }
+    }
+
+    pub fn status(&self, now: u64) -> BuiltStatus {
+        match self.observed_height {
+            Some(_) => BuiltStatus::Observed,
+            None if now.saturating_sub(self.built_at) >= ABANDONED_AFTER_SECS => BuiltStatus::Abandoned,
+            None => BuiltStatus::Pending,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BuiltStatus {
+    /// Built recently and not seen on chain yet
+    Pending,
+    /// Seen on chain in a get_transaction or history lookup
+    Observed,
+    /// Not seen on chain `ABANDONED_AFTER_SECS` after it was built
+    Abandoned,
+}
+
+/// Transaction hashes in a get_transaction or history output, with the entry height each
+/// was included at: a transaction object, a list of them, or a compact list's `items`.
+pub fn transactions_in(output: &Value) -> Vec<(String, Option<u64>)> {
+    match output {
+        Value::Array(items) => items.iter().flat_map(transactions_in).collect(),
+        Value::Object(fields) => match (fields.get("hash").and_then(Value::as_str), fields.get("items")) {
+            (Some(hash), _) => {
+                let height = output["metadata"]["entry_height"].as_u64().or_else(|| output["height"].as_u64());
+                vec![(hash.to_string(), height)]
+            }
+            (None, Some(items)) => transactions_in(items),
+            _ => Vec::new(),
+        },
+        _ => Vec::new(),
+    }
+}
+
+/// Marks the transactions in `output` that `built` holds with `built_by_this_server` and
+/// their `built_at`.
+pub fn annotate(output: &mut Value, built: &[BuiltTransaction]) {
+    match output {
+        Value::Array(items) => items.iter_mut().for_each(|item| annotate(item, built)),
+        Value::Object(fields) => {
+            let hash = fields.get("hash").and_then(Value::as_str);
+            if let Some(origin) = hash.and_then(|hash| built.iter().find(|b| b.transaction_hash == hash)) {
+                fields.insert("built_by_this_server".to_string(), true.into());
+                fields.insert("built_at".to_string(), origin.built_at.into());
+            } else if let Some(items) = fields.get_mut("items") {
+                annotate(items, built);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Built transactions, oldest first.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BuiltLog {
+    transactions: Vec<BuiltTransaction>,
+}
+
+impl BuiltLog {
+    /// Adds a built transaction; rebuilding the same hash keeps the first record.
+    pub fn record(&mut self, built: BuiltTransaction) {
+        if self.find(&built.transaction_hash).is_some() {
+            return;
+        }
+        self.transactions.push(built);
+        if self.transactions.len() > MAX_BUILT_TRANSACTIONS {
+            let excess = self.transactions.len() - MAX_BUILT_TRANSACTIONS;
+            self.transactions.drain(..excess);
+        }
+    }
+
+    pub fn find(&self, hash: &str) -> Option<&BuiltTransaction> {
+        self.transactions.iter().find(|b| b.transaction_hash == hash)
+    }
+
+    /// The built transactions among `hashes`.
+    pub fn matching(&self, hashes: &[(String, Option<u64>)]) -> Vec<BuiltTransaction> {
+        hashes.iter().filter_map(|(hash, _)| self.find(hash).cloned()).collect()
+    }
+
+    /// Records the entry heights transactions were seen at; true when anything changed.
+    pub fn observe(&mut self, seen: &[(String, Option<u64>)]) -> bool {
+        let mut changed = false;
+        for (hash, height) in seen {
+            if let Some(built) = self.transactions.iter_mut().find(|b| &b.transaction_hash == hash) {
+                if built.observed_height.is_none() && height.is_some() {
+                    built.observed_height = *height;
+                    changed = true;
+                }
+            }
+        }
+        changed
+    }
+
+    /// Newest first, optionally only one signer's or one status.
+    pub fn list(&self, signer: Option<&str>, status: Option<BuiltStatus>, limit: usize, now: u64) -> Vec<Value> {
+        self.transactions
+            .iter()
+            .rev()
+            .filter(|b| signer.is_none_or(|s| b.signer == s))
+            .filter(|b| status.is_none_or(|s| b.status(now) == s))
+            .take(limit)
+            .map(|b| listed(b, now))
+            .collect()
+    }
+
+    /// Reads the record from `dir`; missing or unreadable files start an empty one.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(dir: &str) -> Self {
+        std::fs::read(std::path::Path::new(dir).join(BUILT_LOG_FILE))
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    /// Writes the record to `dir`, through a temporary file so a crash cannot truncate it.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self, dir: &str) -> std::io::Result<()> {
+        let path = std::path::Path::new(dir).join(BUILT_LOG_FILE);
+        let tmp = path.with_extension("json.tmp");
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(&tmp, serde_json::to_vec(self)?)?;
+        std::fs::rename(tmp, path)
+    }
+}
+
+/// A built transaction as list_built_transactions shows it, with its status.
+pub fn listed(built: &BuiltTransaction, now: u64) -> Value {
+    let mut value = serde_json::to_value(built).unwrap_or_default();
+    value["status"] = serde_json::json!(built.status(now));
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SIGNER: &str = "29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw";
+    const RECEIVER: &str = "42KfUgyU2XHCPwDD6g9gryCvtj9vqJAvM6h3Yzs3VRbuyT2JCHwvuk5iAbzXrioerf";
+    const NOW: u64 = 1_700_000_000;
+
+    fn transfer() -> TransactionRequest {
+        TransactionRequest {
+            signer: SIGNER.to_string(),
+            contract: "Coin".to_string(),
+            function: "transfer".to_string(),
+            args: vec![
+                Argument::Base58 { b58: RECEIVER.to_string() },
+                Argument::String("1000000000".to_string()),
+                Argument::String("AMA".to_string()),
+            ],
+            attached_symbol: None,
+            attached_amount: None,
+            nonce: None,
+        }
+    }
+
+    fn built(hash: &str, built_at: u64) -> BuiltTransaction {
+        BuiltTransaction::new(&transfer(), hash, built_at)
+    }
+
+    #[test]
+    fn a_transfer_records_destination_amount_and_symbol() {
+        let record = built("h1", NOW);
+        assert_eq!(record.destination.as_deref(), Some(RECEIVER));
+        assert_eq!(record.amount.as_deref(), Some("1000000000"));
+        assert_eq!(record.symbol.as_deref(), Some("AMA"));
+        assert_eq!((record.built_at, record.observed_height), (NOW, None));
+    }
+
+    #[test]
+    fn other_calls_record_the_attached_amount() {
+        let mut req = transfer();
+        req.contract = "Nft".to_string();
+        req.function = "mint".to_string();
+        req.attached_symbol = Some("AMA".to_string());
+        req.attached_amount = Some("5".to_string());
+        let record = BuiltTransaction::new(&req, "h1", NOW);
+        assert_eq!((record.destination, record.amount.as_deref()), (None, Some("5")));
+    }
+
+    #[test]
+    fn unseen_transactions_age_into_abandoned() {
+        let mut record = built("h1", NOW);
+        assert_eq!(record.status(NOW), BuiltStatus::Pending);
+        assert_eq!(record.status(NOW + ABANDONED_AFTER_SECS - 1), BuiltStatus::Pending);
+        assert_eq!(record.status(NOW + ABANDONED_AFTER_SECS), BuiltStatus::Abandoned);
+        // A clock behind the build time is not an age
+        assert_eq!(record.status(NOW - 10), BuiltStatus::Pending);
+        record.observed_height = Some(7);
+        assert_eq!(record.status(NOW + 10 * ABANDONED_AFTER_SECS), BuiltStatus::Observed);
+    }
+
+    #[test]
+    fn hashes_are_found_in_every_lookup_shape() {
+        let tx = json!({ "hash": "a", "metadata": { "entry_height": 5 } });
+        assert_eq!(transactions_in(&tx), vec![("a".to_string(), Some(5))]);
+
+        let compact = json!({ "count": 2, "items": [{ "hash": "b", "height": 6 }, { "hash": "c" }] });
+        assert_eq!(transactions_in(&compact), vec![("b".to_string(), Some(6)), ("c".to_string(), None)]);
+
+        let list = json!([tx, { "note": "no hash" }]);
+        assert_eq!(transactions_in(&list), vec![("a".to_string(), Some(5))]);
+        assert!(transactions_in(&json!("a")).is_empty());
+    }
+
+    #[test]
+    fn only_built_transactions_are_marked() {
+        let mut output = json!({ "items": [{ "hash": "mine" }, { "hash": "theirs" }] });
+        annotate(&mut output, &[built("mine", NOW)]);
+        assert_eq!(output["items"][0]["built_by_this_server"], true);
+        assert_eq!(output["items"][0]["built_at"], NOW);
+        assert!(output["items"][1].get("built_by_this_server").is_none());
+    }
+
+    #[test]
+    fn rebuilding_keeps_the_first_record_and_the_log_is_capped() {
+        let mut log = BuiltLog::default();
+        log.record(built("h1", NOW));
+        log.record(built("h1", NOW + 5));
+        assert_eq!(log.find("h1").unwrap().built_at, NOW);
+
+        for i in 0..MAX_BUILT_TRANSACTIONS as u64 {
+            log.record(built(&format!("n{}", i), NOW + i));
+        }
+        assert!(log.find("h1").is_none());
+        assert!(log.find("n0").is_some());
+        assert_eq!(log.list(None, None, usize::MAX, NOW).len(), MAX_BUILT_TRANSACTIONS);
+    }
+
+    #[test]
+    fn observing_sets_the_first_height_once() {
+        let mut log = BuiltLog::default();
+        log.record(built("h1", NOW));
+        assert!(!log.observe(&[("h1".to_string(), None), ("other".to_string(), Some(3))]));
+        assert!(log.observe(&[("h1".to_string(), Some(9))]));
+        assert!(!log.observe(&[("h1".to_string(), Some(12))]));
+        assert_eq!(log.find("h1").unwrap().observed_height, Some(9));
+        assert_eq!(log.matching(&[("h1".to_string(), None), ("other".to_string(), None)]).len(), 1);
+    }
+
+    #[test]
+    fn listing_is_newest_first_and_filtered() {
+        let mut log = BuiltLog::default();
+        log.record(built("old", NOW - ABANDONED_AFTER_SECS));
+        log.record(built("seen", NOW - 20));
+        log.record(built("new", NOW - 10));
+        let mut other = built("other", NOW);
+        other.signer = RECEIVER.to_string();
+        log.record(other);
+        log.observe(&[("seen".to_string(), Some(4))]);
+
+        let hashes = |items: Vec<Value>| items.iter().map(|i| i["transaction_hash"].as_str().unwrap().to_string()).collect::<Vec<_>>();
+        assert_eq!(hashes(log.list(None, None, 10, NOW)), ["other", "new", "seen", "old"]);
+        assert_eq!(hashes(log.list(Some(SIGNER), None, 2, NOW)), ["new", "seen"]);
+        assert_eq!(hashes(log.list(None, Some(BuiltStatus::Abandoned), 10, NOW)), ["old"]);
+        assert_eq!(log.list(None, Some(BuiltStatus::Observed), 10, NOW)[0]["status"], "observed");
+    }
+
+    #[test]
+    fn the_log_survives_a_restart() {
+        let dir = std::env::temp_dir().join(format!("amadeus-mcp-built-{}", std::process::id()));
+        let dir = dir.to_str().unwrap();
+        assert!(BuiltLog::load(dir).find("h1").is_none());
+
+        let mut log = BuiltLog::default();
+        log.record(built("h1", NOW));
+        log.observe(&[("h1".to_string(), Some(2))]);
+        log.save(dir).unwrap();
+        assert_eq!(BuiltLog::load(dir).find("h1").unwrap().observed_height, Some(2));
+
+        std::fs::write(std::path::Path::new(dir).join(BUILT_LOG_FILE), "not json").unwrap();
+        assert!(BuiltLog::load(dir).find("h1").is_none());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,87 @@
+use crate::catalog::{EndpointRequirement, ENDPOINT_REQUIREMENTS};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Which of the endpoints in `ENDPOINT_REQUIREMENTS` a node lacks, learned from startup
+/// probes or from a confirmed 404. Endpoints not known to be missing are assumed present.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct NodeCapabilities {
+    /// Whether a full probe has completed
+    pub probed: bool,
+    pub missing: BTreeSet<&'static str>,
+    /// Whether the node answered the batch endpoint probe; fan-outs then go through it
+    pub batch: bool,
+}
+
+impl NodeCapabilities {
+    pub fn mark_missing(&mut self, requirement: &EndpointRequirement) {
+        self.missing.insert(requirement.endpoint);
+    }
+
+    pub fn mark_present(&mut self, requirement: &EndpointRequirement) {
+        self.missing.remove(requirement.endpoint);
+    }
+
+    /// The missing endpoint `tool` depends on, if any.
+    pub fn unavailable_reason(&self, tool: &str) -> Option<&'static str> {
+        ENDPOINT_REQUIREMENTS
+            .iter()
+            .find(|r| r.tools.contains(&tool) && self.missing.contains(r.endpoint))
+            .map(|r| r.endpoint)
+    }
+
+    /// Every tool that cannot work against this node, with the endpoint it is missing.
+    pub fn unavailable_tools(&self) -> BTreeMap<&'static str, &'static str> {
+        ENDPOINT_REQUIREMENTS
+            .iter()
+            .filter(|r| self.missing.contains(r.endpoint))
+            .flat_map(|r| r.tools.iter().map(move |tool| (*tool, r.endpoint)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::{endpoint_requirement, TOOL_DOCS};
+
+    fn requirement(endpoint: &str) -> &'static EndpointRequirement {
+        ENDPOINT_REQUIREMENTS.iter().find(|r| r.endpoint == endpoint).unwrap()
+    }
+
+    #[test]
+    fn a_node_missing_two_endpoints_loses_exactly_their_tools() {
+        let mut capabilities = NodeCapabilities::default();
+        capabilities.mark_missing(requirement("/api/contract/richlist"));
+        capabilities.mark_missing(requirement("/api/chain/height_with_txs/"));
+
+        let unavailable = capabilities.unavailable_tools();
+        assert_eq!(unavailable.get("get_richlist"), Some(&"/api/contract/richlist"));
+        assert_eq!(unavailable.get("get_block_with_txs"), Some(&"/api/chain/height_with_txs/"));
+        let expected = requirement("/api/contract/richlist").tools.len() + requirement("/api/chain/height_with_txs/").tools.len();
+        assert_eq!(unavailable.len(), expected);
+        assert_eq!(capabilities.unavailable_reason("get_chain_stats"), None);
+
+        capabilities.mark_present(requirement("/api/contract/richlist"));
+        assert_eq!(capabilities.unavailable_reason("get_richlist"), None);
+        assert_eq!(capabilities.unavailable_reason("get_block_with_txs"), Some("/api/chain/height_with_txs/"));
+    }
+
+    #[test]
+    fn requirements_name_catalog_tools_and_probe_their_own_endpoint() {
+        for requirement in ENDPOINT_REQUIREMENTS {
+            assert_eq!(endpoint_requirement(requirement.probe).map(|r| r.endpoint), Some(requirement.endpoint));
+            for tool in requirement.tools {
+                assert!(TOOL_DOCS.iter().any(|d| d.name == *tool), "{} is not a catalog tool", tool);
+            }
+        }
+    }
+
+    #[test]
+    fn paths_map_to_their_endpoint() {
+        assert_eq!(endpoint_requirement("/api/chain/height_with_txs/12?x=1").map(|r| r.endpoint), Some("/api/chain/height_with_txs/"));
+        assert_eq!(endpoint_requirement("/api/contract/richlist").map(|r| r.endpoint), Some("/api/contract/richlist"));
+        assert!(endpoint_requirement("/api/contract/richlistx").is_none());
+        assert!(endpoint_requirement("/api/chain/stats").is_none());
+    }
+}
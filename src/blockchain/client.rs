@@ -1,19 +1,88 @@
 use super::{
+    api_path::ApiPaths,
+    batch::{self, BATCH_ENDPOINT},
+    block_range::{BlockRange, FailedHeight, MAX_BLOCK_RANGE},
+    block_time::{BlockAtTime, TimeSearch},
+    block_txs::{self, BlockEntryWithTxs, EntryTxs},
+    cache::TtlCache,
+    capabilities::NodeCapabilities,
+    emission::{SnapshotLog, StatsSnapshot},
+    epoch,
+    consistency::Consistency,
+    decimals::DecimalsRegistry,
+    disk_cache::DiskCache,
     error::{BlockchainError, Result},
+    freshness::{self, AccountNonce},
+    health::NodeHealth,
+    full_history::{FullHistory, HistoryCollector, HistoryPage, DEFAULT_FULL_HISTORY_PAGE, FULL_HISTORY_SORT},
+    history_filter::{FilteredHistory, HistoryFilter, HistoryScan, DEFAULT_FILTERED_LIMIT, HISTORY_SCAN_PAGE},
+    investigate::{AccountCoverage, Investigation, Relationship, INVESTIGATE_PAGE},
+    labels,
+    limits::QueryLimits,
+    metrics,
+    offline::FixtureStore,
+    preview::{self, CallPreview},
+    recent_blocks::RecentBlocks,
+    richlist,
+    signer_blocks::{SignedEntries, MAX_SIGNER_RANGE},
+    state_diff::{ContractStateLog, StateObservation},
+    state_export::{self, ExportPage, KeyListing},
+    templates,
+    token::{self, TokenInfo, TokenList},
+    trace::{FundsGraph, FundsTrace, TraceDirection},
+    transfer_check::{TransferRequest, TransferValidation},
+    tx_pool::{TxPool, TX_POOL_PATH},
     types::*,
 };
+use crate::catalog::{self, ENDPOINT_REQUIREMENTS};
+use crate::config::ServerConfig;
+use crate::telemetry;
 use crate::wasm::tx;
 use reqwest::{header, Client, Response};
-use std::time::Duration;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, RwLock,
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 use tokio_retry::{
     strategy::{jitter, ExponentialBackoff},
-    Retry,
+    RetryIf,
 };
-use tracing::warn;
+use tracing::{debug, field::Empty, info, info_span, warn, Instrument};
+
+const EPOCH_SCORE_TTL: Duration = Duration::from_secs(60);
+/// How long a fetched richlist serves windows before it is fetched again; paging through
+/// it or looking up several addresses then costs one node request.
+const RICHLIST_TTL: Duration = Duration::from_secs(60);
+/// What a 404 about the requested item, rather than the endpoint, is reported as.
+const ITEM_NOT_FOUND: &str = "HTTP 404 Not Found: request failed";
+/// Heights fetched at once by `entries_in_window`.
+const WINDOW_FETCH_CONCURRENCY: usize = 8;
+/// Validators whose score and emission address are fetched at once by `get_validator_details`.
+const VALIDATOR_FETCH_CONCURRENCY: usize = 8;
 
 #[derive(Clone)]
 pub struct BlockchainClient {
     client: Client,
+    epoch_scores: Arc<TtlCache<String, Vec<EpochScore>>>,
+    /// Full richlist per node URL
+    richlists: Arc<TtlCache<String, Vec<RichlistEntry>>>,
+    stats_history: Arc<SnapshotLog>,
+    contract_history: Arc<ContractStateLog>,
+    /// Endpoints each node URL is known to lack
+    capabilities: Arc<RwLock<HashMap<String, NodeCapabilities>>>,
+    paths: ApiPaths,
+    /// Finalized entries and transactions kept across restarts, when configured
+    disk_cache: Option<Arc<DiskCache>>,
+    /// Highest finalized height seen per node URL; transactions at or below it are final
+    /// and may go to the disk cache
+    finalized_heights: Arc<RwLock<HashMap<String, u64>>>,
+    /// Offline mode: node requests are answered from these fixtures, never the network
+    offline: Option<Arc<FixtureStore>>,
+    limits: QueryLimits,
 }
 
 impl BlockchainClient {
@@ -25,10 +94,98 @@ impl BlockchainClient {
             .build()
             .map_err(BlockchainError::HttpRequest)?;
 
-        Ok(Self { client })
+        Ok(Self {
+            client,
+            epoch_scores: Arc::new(TtlCache::new(EPOCH_SCORE_TTL)),
+            richlists: Arc::new(TtlCache::new(RICHLIST_TTL)),
+            stats_history: Arc::new(SnapshotLog::default()),
+            contract_history: Arc::new(ContractStateLog::default()),
+            capabilities: Arc::new(RwLock::new(HashMap::new())),
+            paths: ApiPaths::default(),
+            disk_cache: None,
+            finalized_heights: Arc::new(RwLock::new(HashMap::new())),
+            offline: None,
+            limits: QueryLimits::default(),
+        })
+    }
+
+    /// Client with the API paths and, when `MCP_DISK_CACHE_DIR` is set, the disk cache
+    /// from `config`. With `MCP_OFFLINE=1` it answers from the fixture directory instead and
+    /// the disk cache is not used.
+    pub fn from_config(config: &ServerConfig) -> Result<Self> {
+        let client = Self::new(config.mainnet_url.clone())?
+            .with_api_paths(config.api_paths.clone())
+            .with_limits(config.query_limits);
+        if config.offline {
+            let store = FixtureStore::open(&config.offline_dir).map_err(|e| {
+                BlockchainError::Configuration(format!("MCP_OFFLINE_DIR {}: {}", config.offline_dir, e))
+            })?;
+            let missing = store.missing();
+            if !missing.is_empty() {
+                warn!(dir = %config.offline_dir, ?missing, "no fixtures for these node paths, tools using them fail offline");
+            }
+            info!(dir = %config.offline_dir, "offline mode, answering from fixtures");
+            return Ok(client.with_offline(store));
+        }
+        match &config.disk_cache_dir {
+            Some(dir) => {
+                let cache = DiskCache::open(dir, config.disk_cache_max_bytes).map_err(|e| {
+                    BlockchainError::Configuration(format!("MCP_DISK_CACHE_DIR {}: {}", dir, e))
+                })?;
+                Ok(client.with_disk_cache(cache))
+            }
+            None => Ok(client),
+        }
+    }
+
+    /// Keeps what `previous` learned about the chain and its nodes: epoch scores, richlists, recorded
+    /// stats and contract snapshots, and finalized heights, all keyed by node URL. Endpoint
+    /// capabilities are kept only when the API paths are unchanged, as they were probed there.
+    pub fn with_state_of(mut self, previous: &BlockchainClient) -> Self {
+        self.epoch_scores = previous.epoch_scores.clone();
+        self.richlists = previous.richlists.clone();
+        self.stats_history = previous.stats_history.clone();
+        self.contract_history = previous.contract_history.clone();
+        self.finalized_heights = previous.finalized_heights.clone();
+        if self.paths == previous.paths {
+            self.capabilities = previous.capabilities.clone();
+        }
+        self
+    }
+
+    /// Mounts node paths under `paths` instead of the default `/api` prefixes.
+    pub fn with_api_paths(mut self, paths: ApiPaths) -> Self {
+        self.paths = paths;
+        self
+    }
+
+    /// Refuses tool arguments above `limits` before asking the node.
+    pub fn with_limits(mut self, limits: QueryLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn limits(&self) -> &QueryLimits {
+        &self.limits
+    }
+
+    /// Serves finalized entries and transactions from `cache` before asking the node.
+    pub fn with_disk_cache(mut self, cache: DiskCache) -> Self {
+        self.disk_cache = Some(Arc::new(cache));
+        self
     }
 
-    #[tracing::instrument(skip(self), fields(contract=%req.contract, function=%req.function))]
+    /// Answers node requests from `store` and refuses anything that would change the chain.
+    pub fn with_offline(mut self, store: FixtureStore) -> Self {
+        self.offline = Some(Arc::new(store));
+        self
+    }
+
+    pub fn api_paths(&self) -> &ApiPaths {
+        &self.paths
+    }
+
+    #[tracing::instrument(skip_all, fields(contract=%telemetry::address_field(&req.contract), function=%req.function))]
     pub async fn create_transaction_blob(
         &self,
         req: TransactionRequest,
@@ -36,18 +193,9 @@ impl BlockchainClient {
         let signer_pk = bs58::decode(&req.signer)
             .into_vec()
             .map_err(|_| BlockchainError::ValidationFailed("invalid signer base58".into()))?;
+        templates::check_request(&req).map_err(BlockchainError::ValidationFailed)?;
 
-        let args: Result<Vec<Vec<u8>>> = req.args.iter().map(|arg| match arg {
-            Argument::String(s) => Ok(s.as_bytes().to_vec()),
-            Argument::Number(n) => Ok(n.to_string().as_bytes().to_vec()),
-            Argument::Base58 { b58 } => bs58::decode(b58)
-                .into_vec()
-                .map_err(|_| BlockchainError::ValidationFailed("invalid base58 arg".into())),
-            Argument::Hex { hex } => hex::decode(hex.trim_start_matches("0x"))
-                .map_err(|_| BlockchainError::ValidationFailed("invalid hex arg".into())),
-            Argument::Utf8 { utf8 } => Ok(utf8.as_bytes().to_vec()),
-        }).collect();
-        let args = args?;
+        let args = req.args.iter().map(Argument::to_bytes).collect::<Result<Vec<_>>>()?;
 
         let attached_symbol = req.attached_symbol.as_ref().map(|s| s.as_bytes());
         let attached_amount = req.attached_amount.as_ref().map(|s| s.as_bytes());
@@ -67,68 +215,147 @@ impl BlockchainClient {
             signing_payload: hex::encode(unsigned.signing_hash),
             transaction_hash: bs58::encode(unsigned.signing_hash).into_string(),
             tx_bytes: unsigned.tx_blob,
+            nonce: unsigned.nonce,
         })
     }
 
-    #[tracing::instrument(skip(self, tx), fields(tx_hash))]
+    #[tracing::instrument(skip_all, fields(tx_hash))]
     pub async fn submit_signed_transaction(&self, tx: SignedTransaction, url: &str) -> Result<SubmitResponse> {
+        if self.offline.is_some() {
+            return Err(BlockchainError::Offline("transactions cannot be submitted".to_string()));
+        }
         let finalized = tx::finalize_transaction(&tx.transaction, &tx.signature)
             .map_err(|e| BlockchainError::ValidationFailed(e.into()))?;
-        let tx_hash = bs58::encode(&finalized.hash).into_string();
-        let txu_b58 = bs58::encode(&finalized.packed).into_string();
-        let full_url = format!("{}/api/tx/submit", url);
+        self.submit_packed_transaction(&finalized.packed, &finalized.hash, url).await
+    }
 
-        let response = self.client
+    /// Broadcasts an already signed and packed `TxU` whose hash is `hash`.
+    pub async fn submit_packed_transaction(&self, packed: &[u8], hash: &[u8], url: &str) -> Result<SubmitResponse> {
+        if self.offline.is_some() {
+            return Err(BlockchainError::Offline("transactions cannot be submitted".to_string()));
+        }
+        let tx_hash = bs58::encode(hash).into_string();
+        let txu_b58 = bs58::encode(packed).into_string();
+        let full_url = format!("{}{}", url.trim_end_matches('/'), self.paths.tx("tx/submit"));
+
+        let request = self.client
             .post(&full_url)
             .header(header::CONTENT_TYPE, "text/plain")
-            .body(txu_b58)
+            .body(txu_b58);
+        metrics::record(|m| m.upstream_request());
+        let response = telemetry::inject_trace_context(request)
             .send()
             .await
             .map_err(BlockchainError::HttpRequest)?;
 
-        if !response.status().is_success() {
-            return Err(BlockchainError::InvalidResponse(format!("HTTP {}", response.status())));
+        // Rejections may come back as 4xx with an {"error": ...} body; keep those classifiable
+        let status = response.status();
+        let api_response: serde_json::Value = match self.parse_response(response).await {
+            Ok(body) => body,
+            Err(_) if !status.is_success() => {
+                return Err(BlockchainError::InvalidResponse(format!("HTTP {}", status)))
+            }
+            Err(e) => return Err(e),
+        };
+        if !status.is_success() && !api_response.get("error").is_some_and(|e| e.is_string()) {
+            return Err(BlockchainError::InvalidResponse(format!("HTTP {}", status)));
         }
 
-        let api_response: serde_json::Value = self.parse_response(response).await?;
-        let error = api_response.get("error").and_then(|e| e.as_str()).unwrap_or("unknown");
-
-        Ok(SubmitResponse {
-            error: error.to_string(),
-            tx_hash: if error == "ok" { Some(tx_hash) } else { None },
-        })
+        Ok(SubmitResponse::from_node(&api_response, tx_hash))
     }
 
-    #[tracing::instrument(skip(self), fields(address=%address))]
+    /// Returns the balances of `address`. An address the chain has never seen yields an
+    /// empty list with `exists: false`; only undecodable addresses are `AccountNotFound`.
+    #[tracing::instrument(skip_all, fields(address=%telemetry::address_field(address)))]
     pub async fn get_account_balance(&self, address: &str, url: &str) -> Result<AccountBalance> {
-        let path = format!("/api/wallet/balance_all/{}", address);
+        if !is_valid_address(address) {
+            return Err(BlockchainError::AccountNotFound {
+                address: address.to_string(),
+            });
+        }
+
+        let path = self.paths.api(&format!("wallet/balance_all/{}", address));
         let response = self.retry_request_with_url(url, "GET", &path, None).await?;
         let api_response: serde_json::Value = self.parse_response(response).await?;
 
-        if api_response.get("error").and_then(|e| e.as_str()) != Some("ok") {
+        let Some(balances) = AccountBalance::balances_from(&api_response)? else {
+            return Ok(AccountBalance { address: address.to_string(), balances: Vec::new(), exists: false });
+        };
+
+        let exists = balances.iter().any(|b| b.flat > 0) || self.account_exists(address, url).await?;
+
+        Ok(AccountBalance {
+            address: address.to_string(),
+            balances,
+            exists,
+        })
+    }
+
+    /// Balance of one `symbol` from the node's per-symbol endpoint, cheaper than
+    /// `get_account_balance` when only one token matters. A symbol the account does not
+    /// hold is a zero balance.
+    pub async fn get_symbol_balance(&self, address: &str, symbol: &str, url: &str) -> Result<Balance> {
+        if !is_valid_address(address) {
             return Err(BlockchainError::AccountNotFound {
                 address: address.to_string(),
             });
         }
+        if symbol.is_empty() || !symbol.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(BlockchainError::ValidationFailed(format!("symbol {:?} is not alphanumeric", symbol)));
+        }
 
-        let balances_data = api_response.get("balances").ok_or_else(|| {
-            BlockchainError::InvalidResponse("missing balances field".to_string())
-        })?;
+        let path = self.paths.api(&format!("wallet/balance/{}/{}", address, symbol));
+        let response = self.retry_request_with_url(url, "GET", &path, None).await?;
+        let api_response: serde_json::Value = self.parse_response(response).await?;
 
-        let balances: Vec<Balance> =
-            serde_json::from_value(balances_data.clone()).map_err(|e| {
-                BlockchainError::InvalidResponse(format!("failed to parse balances: {}", e))
-            })?;
+        match (api_response.get("error").and_then(|e| e.as_str()), api_response.get("balance")) {
+            (Some("ok"), Some(balance)) => serde_json::from_value(balance.clone()).map_err(|e| {
+                BlockchainError::InvalidResponse(format!("failed to parse balance: {}", e))
+            }),
+            _ => Ok(Balance::zero(symbol)),
+        }
+    }
 
+    /// `get_account_balance` narrowed to `symbol`: `balances` holds just that symbol, zero
+    /// when it is not held, and `exists` is worked out the same way.
+    pub async fn get_account_symbol_balance(&self, address: &str, symbol: &str, url: &str) -> Result<AccountBalance> {
+        let balance = self.get_symbol_balance(address, symbol, url).await?;
+        let exists = balance.flat > 0 || self.account_exists(address, url).await?;
         Ok(AccountBalance {
             address: address.to_string(),
-            balances,
+            balances: vec![balance],
+            exists,
         })
     }
 
-    #[tracing::instrument(skip(self))]
+    /// Dry-runs `req`: the address, symbol and amount checks, then the source balance when
+    /// they allow it. Decimal amounts convert with `decimals`; without it they fail.
+    pub async fn validate_transfer(&self, req: &TransferRequest, decimals: Option<&DecimalsRegistry>, url: &str) -> TransferValidation {
+        let mut report = TransferValidation::check(req, decimals);
+        if report.needs_balance() {
+            let account = self.get_account_balance(&req.source, url).await;
+            report.check_balance(account.as_ref().map(|a| a.balances.as_slice()).map_err(|e| e.to_string()));
+        }
+        report
+    }
+
+    /// Whether the chain has seen `address`, judged by it having any transaction history.
+    #[tracing::instrument(skip_all, fields(address=%telemetry::address_field(address)))]
+    pub async fn account_exists(&self, address: &str, url: &str) -> Result<bool> {
+        let path = self.paths.api(&format!("chain/tx_events_by_account/{}?limit=1", address));
+        let response = self.retry_request_with_url(url, "GET", &path, None).await?;
+        let api_response: serde_json::Value = self.parse_response(response).await?;
+
+        // Unknown accounts come back with an error envelope and no txs
+        Ok(api_response
+            .get("txs")
+            .and_then(|txs| txs.as_array())
+            .is_some_and(|txs| !txs.is_empty()))
+    }
+
+    #[tracing::instrument(skip_all)]
     pub async fn get_chain_stats(&self, url: &str) -> Result<ChainStats> {
-        let response = self.retry_request_with_url(url, "GET", "/api/chain/stats", None).await?;
+        let response = self.retry_request_with_url(url, "GET", &self.paths.api("chain/stats"), None).await?;
         let api_response: serde_json::Value = self.parse_response(response).await?;
 
         if api_response.get("error").and_then(|e| e.as_str()) != Some("ok") {
@@ -141,16 +368,393 @@ impl BlockchainClient {
             .get("stats")
             .ok_or_else(|| BlockchainError::InvalidResponse("missing stats field".to_string()))?;
 
-        serde_json::from_value(stats.clone())
-            .map_err(|e| BlockchainError::InvalidResponse(format!("failed to parse stats: {}", e)))
+        let stats: ChainStats = serde_json::from_value(stats.clone())
+            .map_err(|e| BlockchainError::InvalidResponse(format!("failed to parse stats: {}", e)))?;
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        if let Some(snapshot) = StatsSnapshot::from_stats(&stats, now) {
+            self.stats_history.record(url, snapshot);
+        }
+
+        Ok(stats)
+    }
+
+    /// Times a chain/stats call to `url` and compares its height with `reference`'s, read
+    /// at the same time.
+    pub async fn health_check(&self, url: &str, reference: Option<&str>) -> NodeHealth {
+        let timed = async {
+            let started = Instant::now();
+            let stats = self.get_chain_stats(url).await.map_err(|e| e.to_string())?;
+            Ok((stats, started.elapsed().as_millis() as u64))
+        };
+        let reference_stats = async {
+            match reference {
+                Some(reference) => Some((reference, self.get_chain_stats(reference).await.map_err(|e| e.to_string()))),
+                None => None,
+            }
+        };
+        let (node, reference) = tokio::join!(timed, reference_stats);
+        NodeHealth::new(url, node, reference)
+    }
+
+    /// The entry at the chain tip.
+    #[tracing::instrument(skip_all)]
+    pub async fn get_entry_tip(&self, url: &str) -> Result<BlockEntry> {
+        let response = self.retry_request_with_url(url, "GET", &self.paths.api("chain/tip"), None).await?;
+        let api_response: serde_json::Value = self.parse_response(response).await?;
+        if api_response.get("error").and_then(|e| e.as_str()) != Some("ok") {
+            return Err(BlockchainError::InvalidResponse("failed to get tip entry".to_string()));
+        }
+
+        let entry = api_response
+            .get("entry")
+            .ok_or_else(|| BlockchainError::InvalidResponse("missing entry field".to_string()))?;
+
+        serde_json::from_value(entry.clone())
+            .map_err(|e| BlockchainError::InvalidResponse(format!("failed to parse tip entry: {}", e)))
+    }
+
+    /// The node's AMA richlist, largest holders first. Cached for `RICHLIST_TTL`, so
+    /// successive windows of it come from one fetch.
+    #[tracing::instrument(skip_all)]
+    pub async fn get_richlist(&self, url: &str) -> Result<Vec<RichlistEntry>> {
+        if let Some(holders) = self.richlists.get(&url.to_string()) {
+            metrics::record(|m| m.cache_hit());
+            return Ok(holders);
+        }
+        let response = self.retry_request_with_url(url, "GET", &self.paths.api("contract/richlist"), None).await?;
+        let api_response: serde_json::Value = self.parse_response(response).await?;
+        let holders = richlist::holders_from(&api_response)?;
+        self.richlists.insert(url.to_string(), holders.clone());
+        Ok(holders)
+    }
+
+    /// Height of the current tip entry.
+    pub async fn tip_height(&self, url: &str) -> Result<u64> {
+        self.get_entry_tip(url).await.map(|entry| entry.header.height)
+    }
+
+    /// The tip height and the (height, entry timestamp) samples the epoch pace is measured
+    /// from. Heights without a timestamped entry are left out.
+    #[tracing::instrument(skip_all)]
+    pub async fn epoch_pace_samples(&self, url: &str) -> Result<(u64, Vec<(u64, u64)>)> {
+        let tip = self.get_entry_tip(url).await?;
+        let height = tip.header.height;
+        let mut samples: Vec<(u64, u64)> = tip.header.timestamp.map(|t| (height, t)).into_iter().collect();
+        for sampled in epoch::sample_heights(height).into_iter().skip(1) {
+            let entries = self.get_block_by_height(sampled, url).await?;
+            if let Some(timestamp) = entries.iter().find_map(|e| e.header.timestamp) {
+                samples.push((sampled, timestamp));
+            }
+        }
+        Ok((height, samples))
+    }
+
+    /// Entries of the `blocks` heights ending at the tip, with the first and last height.
+    #[tracing::instrument(skip_all)]
+    pub async fn entries_in_window(&self, url: &str, blocks: u64) -> Result<(u64, u64, Vec<BlockEntry>)> {
+        self.limits.check_block_span("blocks", blocks)?;
+        let tip = self.tip_height(url).await?;
+        let from = tip.saturating_sub(blocks.saturating_sub(1));
+        let mut entries = Vec::new();
+        let heights = match self.batched_heights(url, (from..=tip).collect()).await {
+            Ok(heights) => heights,
+            Err((fetched, rest)) => {
+                entries.extend(fetched);
+                rest
+            }
+        };
+        for chunk in heights.chunks(WINDOW_FETCH_CONCURRENCY) {
+            let mut fetches = tokio::task::JoinSet::new();
+            for &height in chunk {
+                let (client, url) = (self.clone(), url.to_string());
+                fetches.spawn(metrics::inherit(async move { client.get_block_by_height(height, &url).await }));
+            }
+            while let Some(fetched) = fetches.join_next().await {
+                let fetched = fetched.map_err(|e| BlockchainError::InvalidResponse(format!("entry fetch failed: {}", e)))?;
+                entries.extend(fetched?);
+            }
+        }
+        Ok((from, tip, entries))
+    }
+
+    /// Entries of the heights `start..=end`, fetched like a window: batched when the node
+    /// allows, otherwise `WINDOW_FETCH_CONCURRENCY` heights at a time. A height that fails
+    /// is reported in the result rather than failing the range.
+    #[tracing::instrument(skip_all)]
+    pub async fn entries_in_range(&self, url: &str, start: u64, end: u64) -> Result<BlockRange> {
+        if start > end {
+            return Err(BlockchainError::ValidationFailed(format!(
+                "start_height {} is above end_height {}",
+                start, end
+            )));
+        }
+        let span = end - start + 1;
+        if span > MAX_BLOCK_RANGE {
+            return Err(BlockchainError::ValidationFailed(format!(
+                "range of {} heights is above the maximum of {}",
+                span, MAX_BLOCK_RANGE
+            )));
+        }
+        self.limits.check_block_span("end_height", span)?;
+
+        let (entries, failed) = self.entries_at_heights(url, (start..=end).collect()).await?;
+        Ok(BlockRange::assemble(start, end, entries, failed))
+    }
+
+    /// Entries `signer` produced over `start..=end`, fetched like get_block_range. Heights
+    /// that fail are listed in the result and left out of the share.
+    #[tracing::instrument(skip_all, fields(signer = %telemetry::address_field(signer)))]
+    pub async fn entries_signed_by(&self, url: &str, signer: &str, start: u64, end: u64) -> Result<SignedEntries> {
+        if !is_valid_address(signer) {
+            return Err(BlockchainError::ValidationFailed(format!("signer {} is not a base58 public key", signer)));
+        }
+        if start > end {
+            return Err(BlockchainError::ValidationFailed(format!(
+                "start_height {} is above end_height {}",
+                start, end
+            )));
+        }
+        let span = end - start + 1;
+        if span > MAX_SIGNER_RANGE {
+            return Err(BlockchainError::ValidationFailed(format!(
+                "range of {} heights is above the maximum of {}",
+                span, MAX_SIGNER_RANGE
+            )));
+        }
+        self.limits.check_block_span("end_height", span)?;
+
+        let (entries, failed) = self.entries_at_heights(url, (start..=end).collect()).await?;
+        Ok(SignedEntries::new(signer, start, end, entries, failed))
+    }
+
+    /// The entries just before and after `target_secs`, binary-searched between genesis and
+    /// the height chain/stats reports with at most `max_probes` entry fetches (default: the
+    /// server's limit).
+    pub async fn block_at_time(&self, url: &str, target_secs: u64, max_probes: Option<u64>) -> Result<BlockAtTime> {
+        let max_probes = max_probes.unwrap_or(self.limits.max_time_probes);
+        self.limits.check_time_probes(max_probes)?;
+        let tip = self.get_chain_stats(url).await?.height;
+        let mut search = TimeSearch::new(target_secs, tip, max_probes);
+        while let Some(height) = search.next_height() {
+            let entries = self.get_block_by_height(height, url).await?;
+            search.record(height, &entries);
+        }
+        Ok(search.finish(SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)))
+    }
+
+    /// The newest `count` heights up to the height chain/stats reports, fetched like
+    /// get_block_range.
+    pub async fn recent_blocks(&self, url: &str, count: u64) -> Result<RecentBlocks> {
+        self.limits.check_block_span("count", count)?;
+        let tip = self.get_chain_stats(url).await?.height;
+        let start = tip.saturating_sub(count.saturating_sub(1));
+        let (entries, failed) = self.entries_at_heights(url, (start..=tip).collect()).await?;
+        Ok(RecentBlocks::new(tip, start, entries, failed))
+    }
+
+    /// Entries at `heights`, batched when the node allows and otherwise
+    /// `WINDOW_FETCH_CONCURRENCY` heights at a time, with the heights that failed.
+    async fn entries_at_heights(&self, url: &str, heights: Vec<u64>) -> Result<(Vec<BlockEntry>, Vec<FailedHeight>)> {
+        let mut entries = Vec::new();
+        let heights = match self.batched_heights(url, heights).await {
+            Ok(heights) => heights,
+            Err((fetched, rest)) => {
+                entries.extend(fetched);
+                rest
+            }
+        };
+        let mut failed = Vec::new();
+        for chunk in heights.chunks(WINDOW_FETCH_CONCURRENCY) {
+            let mut fetches = tokio::task::JoinSet::new();
+            for &height in chunk {
+                let (client, url) = (self.clone(), url.to_string());
+                fetches.spawn(metrics::inherit(async move { (height, client.get_block_by_height(height, &url).await) }));
+            }
+            while let Some(fetched) = fetches.join_next().await {
+                let (height, fetched) =
+                    fetched.map_err(|e| BlockchainError::InvalidResponse(format!("entry fetch failed: {}", e)))?;
+                match fetched {
+                    Ok(fetched) => entries.extend(fetched),
+                    Err(e) => failed.push(FailedHeight { height, error: e.to_string() }),
+                }
+            }
+        }
+        Ok((entries, failed))
     }
 
-    #[tracing::instrument(skip(self), fields(height=%height))]
+    /// Fetches `heights` through the node's batch endpoint when it has one. Heights the
+    /// disk cache holds, and heights whose batched call failed, are left for separate
+    /// requests: `Err((fetched entries, remaining heights))`. `Ok(heights)` hands all of
+    /// them back when batching is unavailable.
+    async fn batched_heights(&self, url: &str, heights: Vec<u64>) -> std::result::Result<Vec<u64>, (Vec<BlockEntry>, Vec<u64>)> {
+        if !self.capabilities(url).batch {
+            return Ok(heights);
+        }
+        let path = |height: u64| self.paths.api(&format!("chain/height/{}", height));
+        let (cached, uncached): (Vec<u64>, Vec<u64>) = heights
+            .into_iter()
+            .partition(|&h| self.disk_cache.as_ref().is_some_and(|cache| cache.get("GET", url, &path(h)).is_some()));
+        let paths: Vec<String> = uncached.iter().map(|&h| path(h)).collect();
+        let Some(answers) = self.batch_get(url, &paths).await else {
+            return Ok(cached.into_iter().chain(uncached).collect());
+        };
+
+        let mut fetched = Vec::new();
+        let mut rest = cached;
+        for ((height, path), answer) in uncached.into_iter().zip(&paths).zip(answers) {
+            let parsed = answer.and_then(|response| {
+                let entries = Self::entries_from(&response)?;
+                if let (true, Some(cache)) = (self.note_height_response(url, &response), &self.disk_cache) {
+                    cache.put("GET", url, path, &response);
+                }
+                Ok(entries)
+            });
+            match parsed {
+                Ok(entries) => fetched.extend(entries),
+                Err(e) => {
+                    debug!(height, error = %e, "batched height failed, fetching it separately");
+                    rest.push(height);
+                }
+            }
+        }
+        Err((fetched, rest))
+    }
+
+    /// GETs each of `paths` in one request per batch of `QueryLimits::batch_size`, with one result per path.
+    /// `None` when the node has no batch endpoint or a batch request itself failed, in
+    /// which case the endpoint is not used again until the next probe.
+    async fn batch_get(&self, url: &str, paths: &[String]) -> Option<Vec<Result<serde_json::Value>>> {
+        let endpoint = self.paths.rebase(BATCH_ENDPOINT);
+        let mut results = Vec::with_capacity(paths.len());
+        for chunk in paths.chunks(self.limits.batch_size()) {
+            let body = batch::request_body(chunk);
+            let answered = async {
+                let response = self.retry_request_with_url(url, "POST", &endpoint, Some(&body)).await?;
+                let value: serde_json::Value = self.parse_response(response).await?;
+                batch::split_response(&value, chunk.len()).map_err(BlockchainError::InvalidResponse)
+            }
+            .await;
+            match answered {
+                Ok(items) => results.extend(items.into_iter().map(|item| item.map_err(BlockchainError::InvalidResponse))),
+                Err(e) => {
+                    warn!(error = %e, "batch request failed, using separate requests");
+                    self.update_capabilities(url, |c| c.batch = false);
+                    return None;
+                }
+            }
+        }
+        Some(results)
+    }
+
+    /// Entries from the tip downwards, stopping at the first finalized one or after
+    /// `max_entries` heights.
+    #[tracing::instrument(skip_all)]
+    pub async fn entries_until_finalized(&self, url: &str, max_entries: u64) -> Result<Vec<BlockEntry>> {
+        self.limits.check_block_span("sample_entries", max_entries)?;
+        let tip = self.tip_height(url).await?;
+        let mut sampled = Vec::new();
+        for height in (tip.saturating_sub(max_entries - 1)..=tip).rev() {
+            let entries = self.get_block_by_height(height, url).await?;
+            let finalized = entries.iter().any(|e| e.consensus.as_ref().is_some_and(|c| c.finality_reached));
+            sampled.extend(entries);
+            if finalized {
+                break;
+            }
+        }
+        Ok(sampled)
+    }
+
+    /// Chain stats snapshots recorded so far for `url`, oldest first.
+    pub fn stats_snapshots(&self, url: &str) -> Vec<StatsSnapshot> {
+        self.stats_history.snapshots(url)
+    }
+
+    #[tracing::instrument(skip_all, fields(height=%height))]
     pub async fn get_block_by_height(&self, height: u64, url: &str) -> Result<Vec<BlockEntry>> {
-        let path = format!("/api/chain/height/{}", height);
-        let response = self.retry_request_with_url(url, "GET", &path, None).await?;
+        let path = self.paths.api(&format!("chain/height/{}", height));
+        let api_response = self
+            .immutable_get(url, &path, |response| self.note_height_response(url, response))
+            .await?;
+        Self::entries_from(&api_response)
+    }
+
+    /// The entries at `height` with their transactions embedded.
+    pub async fn get_block_with_txs(&self, height: u64, url: &str) -> Result<Vec<BlockEntryWithTxs>> {
+        let path = self.paths.api(&format!("chain/height_with_txs/{}", height));
+        let api_response = self
+            .immutable_get(url, &path, |response| self.note_height_response(url, response))
+            .await?;
+        block_txs::entries_from(&api_response)
+    }
+
+    /// The entry with base58 `hash`. An unknown hash is `NotFound`.
+    pub async fn get_entry_by_hash(&self, hash: &str, url: &str) -> Result<BlockEntry> {
+        if !is_base58(hash) {
+            return Err(BlockchainError::ValidationFailed(format!("entry hash {} is not base58", hash)));
+        }
+        let path = self.paths.api(&format!("chain/hash/{}", hash));
+        let api_response = match self
+            .immutable_get(url, &path, |response| response["entry"]["consensus"]["finality_reached"] == true)
+            .await
+        {
+            Err(BlockchainError::InvalidResponse(message)) if message == ITEM_NOT_FOUND => {
+                return Err(BlockchainError::NotFound { argument: "hash".to_string(), value: hash.to_string() });
+            }
+            result => result?,
+        };
+        BlockEntry::from_hash_response(hash, &api_response)
+    }
+
+    /// The transactions of the entry `entry_hash`. An unknown hash is `NotFound`.
+    pub async fn get_txs_in_entry(&self, entry_hash: &str, url: &str) -> Result<EntryTxs> {
+        let not_found = || BlockchainError::NotFound { argument: "entry_hash".to_string(), value: entry_hash.to_string() };
+        if !is_base58(entry_hash) {
+            return Err(not_found());
+        }
+        let path = self.paths.api(&format!("chain/txs_in_entry/{}", entry_hash));
+        let response = match self.retry_request_with_url(url, "GET", &path, None).await {
+            Err(BlockchainError::InvalidResponse(message)) if message == ITEM_NOT_FOUND => return Err(not_found()),
+            result => result?,
+        };
+        let api_response: serde_json::Value = self.parse_response(response).await?;
+        block_txs::txs_from(entry_hash, &api_response)
+    }
+
+    /// The node's pending transactions. The pool has no items of its own, so any 404 means
+    /// the node lacks the endpoint.
+    pub async fn get_tx_pool(&self, url: &str) -> Result<TxPool> {
+        let path = self.paths.api(TX_POOL_PATH);
+        let response = match self.retry_request_with_url(url, "GET", &path, None).await {
+            Err(BlockchainError::InvalidResponse(message)) if message == ITEM_NOT_FOUND => {
+                return Err(BlockchainError::EndpointUnsupported { endpoint: self.paths.canonical(&path) });
+            }
+            result => result?,
+        };
         let api_response: serde_json::Value = self.parse_response(response).await?;
+        TxPool::from_node(&api_response)
+    }
+
+    /// Records the highest finalized entry in a `chain/height` response and says whether
+    /// the whole response is final, i.e. may go to the disk cache.
+    fn note_height_response(&self, url: &str, response: &serde_json::Value) -> bool {
+        let entries = response["entries"].as_array().map(Vec::as_slice).unwrap_or_default();
+        let finalized = entries
+            .iter()
+            .filter(|e| e["consensus"]["finality_reached"] == true)
+            .filter_map(|e| e["header"]["height"].as_u64())
+            .max();
+        if let Some(height) = finalized {
+            let mut heights = self.finalized_heights.write().unwrap();
+            let known = heights.entry(url.to_string()).or_default();
+            *known = (*known).max(height);
+        }
+        response["error"] == "ok"
+            && !entries.is_empty()
+            && entries.iter().all(|e| e["consensus"]["finality_reached"] == true)
+    }
 
+    fn entries_from(api_response: &serde_json::Value) -> Result<Vec<BlockEntry>> {
         if api_response.get("error").and_then(|e| e.as_str()) != Some("ok") {
             return Err(BlockchainError::InvalidResponse(
                 "failed to get block entries".to_string(),
@@ -166,24 +770,46 @@ impl BlockchainClient {
         })
     }
 
-    #[tracing::instrument(skip(self), fields(tx_hash=%tx_hash))]
+    /// The latest nonce `address` signed with, read from its newest transactions, and the
+    /// nonce to build its next one with. An account that never transacted has nonce 0.
+    #[tracing::instrument(skip_all, fields(address=%telemetry::address_field(address)))]
+    pub async fn get_account_nonce(&self, address: &str, url: &str) -> Result<AccountNonce> {
+        if !is_valid_address(address) {
+            return Err(BlockchainError::AccountNotFound { address: address.to_string() });
+        }
+        let history = self
+            .get_transaction_history(address, Some(freshness::NONCE_HISTORY_LIMIT), None, Some("desc"), url)
+            .await?;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        Ok(AccountNonce::new(address, &history, now))
+    }
+
+    #[tracing::instrument(skip_all, fields(tx_hash=%tx_hash))]
     pub async fn get_transaction(&self, tx_hash: &str, url: &str) -> Result<Transaction> {
-        let path = format!("/api/chain/tx/{}", tx_hash);
-        let response = self.retry_request_with_url(url, "GET", &path, None).await?;
-        let api_response: serde_json::Value = self.parse_response(response).await?;
+        let path = self.paths.api(&format!("chain/tx/{}", tx_hash));
+        let not_found = || BlockchainError::NotFound { argument: "tx_hash".to_string(), value: tx_hash.to_string() };
+        let api_response = match self
+            .immutable_get(url, &path, |response| {
+                response["metadata"]["entry_height"]
+                    .as_u64()
+                    .is_some_and(|height| self.finalized_heights.read().unwrap().get(url).is_some_and(|f| height <= *f))
+            })
+            .await
+        {
+            Err(BlockchainError::InvalidResponse(message)) if message == ITEM_NOT_FOUND => return Err(not_found()),
+            result => result?,
+        };
 
         if api_response.get("result").and_then(|r| r.get("error")).and_then(|e| e.as_str()) == Some("not_found") {
-            return Err(BlockchainError::InvalidResponse(
-                "transaction not found".to_string(),
-            ));
+            return Err(not_found());
         }
 
-        serde_json::from_value(api_response).map_err(|e| {
+        serde_json::from_value(api_response).map(Transaction::with_decoded_action).map_err(|e| {
             BlockchainError::InvalidResponse(format!("failed to parse transaction: {}", e))
         })
     }
 
-    #[tracing::instrument(skip(self), fields(address=%address))]
+    #[tracing::instrument(skip_all, fields(address=%telemetry::address_field(address)))]
     pub async fn get_transaction_history(
         &self,
         address: &str,
@@ -192,7 +818,8 @@ impl BlockchainClient {
         sort: Option<&str>,
         url: &str,
     ) -> Result<Vec<Transaction>> {
-        let mut path = format!("/api/chain/tx_events_by_account/{}", address);
+        self.limits.check_history(limit)?;
+        let mut path = self.paths.api(&format!("chain/tx_events_by_account/{}", address));
         let mut params = vec![];
 
         if let Some(l) = limit {
@@ -217,14 +844,124 @@ impl BlockchainClient {
             .get("txs")
             .ok_or_else(|| BlockchainError::InvalidResponse("missing txs field".to_string()))?;
 
-        serde_json::from_value(txs.clone())
+        serde_json::from_value::<Vec<Transaction>>(txs.clone())
+            .map(|txs| txs.into_iter().map(Transaction::with_decoded_action).collect())
             .map_err(|e| BlockchainError::InvalidResponse(format!("failed to parse txs: {}", e)))
     }
 
-    #[tracing::instrument(skip(self))]
-    pub async fn get_validators(&self, url: &str) -> Result<Vec<ValidatorInfo>> {
+    /// Pages through the history of `address` from `offset` until `limit` transactions pass
+    /// `filter`, the history runs out, or the scan budget is spent. A time range costs one
+    /// entry lookup per distinct height among the otherwise matching transactions.
+    #[tracing::instrument(skip_all, fields(address = %telemetry::address_field(address)))]
+    pub async fn get_filtered_history(
+        &self,
+        address: &str,
+        filter: &HistoryFilter,
+        limit: Option<u32>,
+        offset: Option<u32>,
+        sort: Option<&str>,
+        url: &str,
+    ) -> Result<FilteredHistory<Vec<Transaction>>> {
+        let wanted = limit.unwrap_or(DEFAULT_FILTERED_LIMIT);
+        self.limits.check_history(Some(wanted))?;
+        let page_size = self.limits.history_page(HISTORY_SCAN_PAGE);
+        let mut scan = HistoryScan::new(offset);
+        let mut timestamps: HashMap<u64, Option<u64>> = HashMap::new();
+
+        while scan.wants_more(wanted) {
+            let page = self
+                .get_transaction_history(address, Some(page_size), Some(scan.offset()), sort, url)
+                .await?;
+            scan.pages += 1;
+            scan.exhausted = (page.len() as u32) < page_size;
+            for tx in page {
+                if scan.matched.len() as u32 >= wanted {
+                    scan.exhausted = false;
+                    break;
+                }
+                scan.scanned += 1;
+                if !filter.matches(address, &tx) {
+                    continue;
+                }
+                if filter.needs_timestamps() {
+                    let height = tx.metadata.entry_height;
+                    let timestamp = match timestamps.get(&height) {
+                        Some(timestamp) => *timestamp,
+                        None => {
+                            let entries = self.get_block_by_height(height, url).await?;
+                            let timestamp = entries.iter().find_map(|e| e.header.timestamp);
+                            timestamps.insert(height, timestamp);
+                            timestamp
+                        }
+                    };
+                    if !filter.in_time_range(timestamp) {
+                        continue;
+                    }
+                }
+                scan.matched.push(tx);
+            }
+        }
+        Ok(scan.finish())
+    }
+
+    /// Reads the whole history of `address` oldest first, `page_size` transactions a page,
+    /// until a short page or `max_transactions` (the configured full history limit by
+    /// default). `on_page` runs after each page; pages are fetched one at a time, so
+    /// dropping the future stops the fetch between requests.
+    #[tracing::instrument(skip_all, fields(address = %telemetry::address_field(address)))]
+    pub async fn get_full_transaction_history<F, Fut>(
+        &self,
+        address: &str,
+        page_size: Option<u32>,
+        max_transactions: Option<u64>,
+        url: &str,
+        mut on_page: F,
+    ) -> Result<FullHistory>
+    where
+        F: FnMut(HistoryPage) -> Fut,
+        Fut: std::future::Future<Output = ()>,
+    {
+        let page_size = page_size.unwrap_or(self.limits.history_page(DEFAULT_FULL_HISTORY_PAGE));
+        self.limits.check_history(Some(page_size))?;
+        let max_transactions = max_transactions.unwrap_or(self.limits.max_full_history);
+        self.limits.check_full_history(max_transactions)?;
+
+        let mut collector = HistoryCollector::new(max_transactions);
+        while collector.wants_more() {
+            let page = self
+                .get_transaction_history(address, Some(page_size), Some(collector.offset()), Some(FULL_HISTORY_SORT), url)
+                .await?;
+            on_page(collector.push(page, page_size)).await;
+        }
+
+        let mut timestamps = [None, None];
+        if let Some((first, last)) = collector.height_bounds() {
+            for (slot, height) in timestamps.iter_mut().zip([first, last]) {
+                let entries = self.get_block_by_height(height, url).await?;
+                *slot = entries.iter().find_map(|e| e.header.timestamp);
+            }
+        }
+        Ok(collector.finish(address, timestamps[0], timestamps[1]))
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn get_peer_nodes(&self, url: &str) -> Result<Vec<PeerNode>> {
+        let response = self
+            .retry_request_with_url(url, "GET", &self.paths.api("peer/nodes"), None)
+            .await?;
+        let api_response: serde_json::Value = self.parse_response(response).await?;
+
+        let nodes = api_response
+            .get("nodes")
+            .ok_or_else(|| BlockchainError::InvalidResponse("missing nodes field".to_string()))?;
+        serde_json::from_value(nodes.clone())
+            .map_err(|e| BlockchainError::InvalidResponse(format!("failed to parse nodes: {}", e)))
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn get_validator_keys(&self, url: &str) -> Result<Vec<String>> {
         let response = self
-            .retry_request_with_url(url, "GET", "/api/peer/trainers", None)
+            .retry_request_with_url(url, "GET", &self.paths.api("peer/trainers"), None)
             .await?;
         let api_response: serde_json::Value = self.parse_response(response).await?;
 
@@ -243,18 +980,440 @@ impl BlockchainClient {
         })
     }
 
-    #[tracing::instrument(skip(self), fields(contract=%contract_address, key=%key))]
+    #[tracing::instrument(skip_all)]
+    pub async fn get_removed_trainers(&self, url: &str) -> Result<RemovedTrainers> {
+        let response = self
+            .retry_request_with_url(url, "GET", &self.paths.api("peer/removed_trainers"), None)
+            .await?;
+        let api_response: serde_json::Value = self.parse_response(response).await?;
+        RemovedTrainers::from_node(&api_response)
+    }
+
+    #[tracing::instrument(skip_all)]
+    pub async fn get_epoch_scores(&self, url: &str) -> Result<Vec<EpochScore>> {
+        if let Some(scores) = self.epoch_scores.get(&url.to_string()) {
+            metrics::record(|m| m.cache_hit());
+            return Ok(scores);
+        }
+
+        let response = self
+            .retry_request_with_url(url, "GET", &self.paths.api("epoch/score"), None)
+            .await?;
+        let api_response: serde_json::Value = self.parse_response(response).await?;
+        let scores = EpochScore::from_response(&api_response)?;
+
+        self.epoch_scores.insert(url.to_string(), scores.clone());
+        Ok(scores)
+    }
+
+    /// Epoch scores, highest first. With `address`, only that validator's score, read from
+    /// `/api/epoch/score/{pk}` and ranked against the full list.
+    #[tracing::instrument(skip_all)]
+    pub async fn get_epoch_score(&self, url: &str, address: Option<&str>) -> Result<Vec<EpochScore>> {
+        let Some(pk) = address else {
+            return self.get_epoch_scores(url).await;
+        };
+        if !is_valid_address(pk) {
+            return Err(BlockchainError::ValidationFailed(format!("invalid address {}", pk)));
+        }
+
+        let response = self
+            .retry_request_with_url(url, "GET", &self.paths.api(&format!("epoch/score/{}", pk)), None)
+            .await?;
+        let api_response: serde_json::Value = self.parse_response(response).await?;
+        let all = self.get_epoch_scores(url).await?;
+        Ok(vec![EpochScore::for_validator(pk, &api_response, &all)?])
+    }
+
+    /// The address the validator `pk` has its emissions paid to, if it set one.
+    #[tracing::instrument(skip_all, fields(pk=%telemetry::address_field(pk)))]
+    pub async fn get_emission_address(&self, pk: &str, url: &str) -> Result<EmissionAddress> {
+        if !is_valid_address(pk) {
+            return Err(BlockchainError::AccountNotFound { address: pk.to_string() });
+        }
+
+        let path = self.paths.api(&format!("epoch/get_emission_address/{}", pk));
+        let response = self.retry_request_with_url(url, "GET", &path, None).await?;
+        let api_response: serde_json::Value = self.parse_response(response).await?;
+        EmissionAddress::from_node(pk, &api_response)
+    }
+
+    /// Trainer set joined with epoch scores. When `keys_only` is set, or the score
+    /// endpoint is unavailable, entries carry only the public key.
+    #[tracing::instrument(skip_all)]
+    pub async fn get_validators(&self, url: &str, keys_only: bool) -> Result<Vec<ValidatorInfo>> {
+        let keys = self.get_validator_keys(url).await?;
+        if keys_only {
+            return Ok(ValidatorInfo::join(keys, None));
+        }
+
+        let scores = match self.get_epoch_scores(url).await {
+            Ok(scores) => Some(scores),
+            Err(e) => {
+                warn!("epoch scores unavailable, returning keys only: {}", e);
+                None
+            }
+        };
+
+        Ok(ValidatorInfo::join(keys, scores.as_deref()))
+    }
+
+    /// Trainers with their score, rank, epoch and emission address; only `public_key` when
+    /// given. Each validator's lookups run `VALIDATOR_FETCH_CONCURRENCY` at a time, and one
+    /// that fails leaves its fields `None` rather than failing the call.
+    #[tracing::instrument(skip_all)]
+    pub async fn get_validator_details(&self, url: &str, public_key: Option<&str>) -> Result<Vec<ValidatorInfo>> {
+        let mut keys = self.get_validator_keys(url).await?;
+        if let Some(pk) = public_key {
+            if !is_valid_address(pk) {
+                return Err(BlockchainError::AccountNotFound { address: pk.to_string() });
+            }
+            if !keys.iter().any(|key| key == pk) {
+                return Err(BlockchainError::NotFound { argument: "public_key".to_string(), value: pk.to_string() });
+            }
+            keys = vec![pk.to_string()];
+        }
+        let epoch = match self.tip_height(url).await {
+            Ok(height) => Some(height / epoch::EPOCH_HEIGHTS),
+            Err(e) => {
+                warn!("tip unavailable, leaving the epoch out: {}", e);
+                None
+            }
+        };
+        // Fills the score cache once, before every lookup below would miss it at the same time
+        if let Err(e) = self.get_epoch_scores(url).await {
+            warn!("epoch scores unavailable: {}", e);
+        }
+
+        let mut validators = Vec::with_capacity(keys.len());
+        for chunk in keys.chunks(VALIDATOR_FETCH_CONCURRENCY) {
+            let mut fetches = tokio::task::JoinSet::new();
+            for pk in chunk {
+                let (client, url, pk) = (self.clone(), url.to_string(), pk.clone());
+                fetches.spawn(metrics::inherit(async move {
+                    let (score, emission) =
+                        tokio::join!(client.get_epoch_score(&url, Some(&pk)), client.get_emission_address(&pk, &url));
+                    let score = score.ok().and_then(|scores| scores.into_iter().next());
+                    ValidatorInfo {
+                        score: score.as_ref().map(|s| s.score),
+                        rank: score.as_ref().map(|s| s.rank),
+                        epoch: score.as_ref().and(epoch),
+                        emission_address: emission.ok().and_then(|e| e.emission_address),
+                        pk,
+                    }
+                }));
+            }
+            while let Some(fetched) = fetches.join_next().await {
+                validators.push(
+                    fetched.map_err(|e| BlockchainError::InvalidResponse(format!("validator fetch failed: {}", e)))?,
+                );
+            }
+        }
+        // Join order is completion order; keep the trainer list's
+        validators.sort_by_key(|v| keys.iter().position(|key| *key == v.pk));
+        Ok(validators)
+    }
+
+    #[tracing::instrument(skip_all, fields(contract=%telemetry::address_field(contract_address), key=%telemetry::redact_text(key)))]
     pub async fn get_contract_state(
         &self,
         contract_address: &str,
         key: &str,
         url: &str,
     ) -> Result<serde_json::Value> {
-        let path = format!("/api/contract/get/{}/{}", contract_address, key);
+        let path = self.paths.api(&format!("contract/get/{}/{}", contract_address, key));
         let response = self.retry_request_with_url(url, "GET", &path, None).await?;
         self.parse_response(response).await
     }
 
+    /// A Coin storage value, `None` when the key is absent.
+    async fn coin_value(&self, key: &str, url: &str) -> Result<Option<serde_json::Value>> {
+        match self.get_contract_state(token::COIN_CONTRACT, key, url).await {
+            Err(BlockchainError::InvalidResponse(message)) if message == ITEM_NOT_FOUND => Ok(None),
+            result => result.map(Some),
+        }
+    }
+
+    /// Supply, decimals and burned amount of `symbol`; `NotFound` when it has no supply.
+    pub async fn get_token_info(&self, symbol: &str, registry: &DecimalsRegistry, url: &str) -> Result<TokenInfo> {
+        if !symbol.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(token::unknown_symbol(symbol));
+        }
+        let supply = self.coin_value(&format!("{}{}", token::TOTAL_SUPPLY_PREFIX, symbol), url).await?;
+        let total_supply = token::total_supply(symbol, supply.as_ref())?;
+        self.token_info(symbol, total_supply, registry, url).await
+    }
+
+    /// Every token with a total supply, up to `MAX_LISTED_TOKENS`.
+    pub async fn list_tokens(&self, registry: &DecimalsRegistry, url: &str) -> Result<TokenList> {
+        let limit = token::MAX_LISTED_TOKENS.min(self.limits.max_contract_keys);
+        let page = self
+            .export_contract_state(token::COIN_CONTRACT, token::TOTAL_SUPPLY_PREFIX, None, limit, url)
+            .await?;
+        let mut tokens = Vec::new();
+        for (symbol, supply) in token::supplies(&page) {
+            tokens.push(self.token_info(&symbol, supply, registry, url).await?);
+        }
+        Ok(TokenList::new(tokens, page.complete))
+    }
+
+    async fn token_info(&self, symbol: &str, total_supply: u128, registry: &DecimalsRegistry, url: &str) -> Result<TokenInfo> {
+        let decimals = self.coin_value(&format!("{}{}", token::DECIMALS_PREFIX, symbol), url).await?;
+        let burned = self.coin_value(&token::burned_key(symbol), url).await?;
+        Ok(TokenInfo::new(
+            symbol,
+            total_supply,
+            decimals.as_ref().and_then(token::state_integer),
+            burned.as_ref().and_then(token::state_integer),
+            registry,
+        ))
+    }
+
+    /// One page of contract storage under `prefix`, resuming after the key `after`.
+    #[tracing::instrument(skip_all, fields(contract=%telemetry::address_field(contract_address), prefix=%telemetry::redact_text(prefix)))]
+    pub async fn export_contract_state(
+        &self,
+        contract_address: &str,
+        prefix: &str,
+        after: Option<&str>,
+        limit: u64,
+        url: &str,
+    ) -> Result<ExportPage> {
+        self.limits.check_contract_keys(limit)?;
+        // One key more than the page holds, to know whether another page follows
+        let path = self.paths.api(&state_export::request_path(contract_address, prefix, after, limit + 1));
+        let response = self.retry_request_with_url(url, "GET", &path, None).await?;
+        let value = self.parse_response(response).await?;
+        state_export::page(&value, contract_address, prefix, after, limit)
+    }
+
+    /// Up to `limit` keys of contract storage under `prefix` with their values, resuming
+    /// after the key `after`.
+    pub async fn list_contract_state(
+        &self,
+        contract_address: &str,
+        prefix: &str,
+        after: Option<&str>,
+        limit: u64,
+        url: &str,
+    ) -> Result<KeyListing> {
+        self.limits.check_contract_keys(limit)?;
+        let path = self.paths.api(&state_export::request_path(contract_address, prefix, after, limit + 1));
+        let response = self.retry_request_with_url(url, "GET", &path, None).await?;
+        let value = self.parse_response(response).await?;
+        state_export::listing(&value, contract_address, prefix, after, limit)
+    }
+
+    /// Executes a contract call on the node without a transaction and reports its result
+    /// or revert reason.
+    #[tracing::instrument(skip_all, fields(contract=%telemetry::address_field(contract), function=%function))]
+    pub async fn preview_contract_call(&self, contract: &str, function: &str, args: &[Argument], url: &str) -> Result<CallPreview> {
+        let body = preview::request_body(args)?;
+        let path = self.paths.api(&format!("{}/{}/{}", preview::VIEW_PATH, contract, function));
+        let response = self.retry_request_with_url(url, "POST", &path, Some(&body)).await?;
+        let value: serde_json::Value = self.parse_response(response).await?;
+        Ok(preview::interpret(contract, function, value))
+    }
+
+    /// GETs an arbitrary node path; the caller is responsible for vetting `path`.
+    #[tracing::instrument(skip_all, fields(path=%telemetry::redact_text(path)))]
+    pub async fn raw_query(&self, path: &str, url: &str) -> Result<serde_json::Value> {
+        let response = self.retry_request_with_url(url, "GET", path, None).await?;
+        self.parse_response(response).await
+    }
+
+    /// Answers "how do these two accounts relate" within `max_requests` upstream calls: both
+    /// balances first, then history pages for both accounts fetched in pairs until each
+    /// history ends or the budget is spent.
+    #[tracing::instrument(skip_all, fields(address_a=%telemetry::address_field(a), address_b=%telemetry::address_field(b)))]
+    pub async fn investigate_addresses(&self, a: &str, b: &str, max_requests: u32, url: &str) -> Result<Investigation> {
+        let start_height = self.tip_height(url).await?;
+        let (balance_a, balance_b) = tokio::join!(self.get_account_balance(a, url), self.get_account_balance(b, url));
+        let (balance_a, balance_b) = (balance_a?, balance_b?);
+        // A balance with no positive entry costs a second call to check whether the account exists
+        let balance_cost = |balance: &AccountBalance| if balance.balances.iter().any(|b| b.flat > 0) { 1 } else { 2 };
+        let mut used = balance_cost(&balance_a) + balance_cost(&balance_b);
+
+        // Accounts the chain has never seen have no history to page through
+        let mut history_a = Vec::new();
+        let mut history_b = Vec::new();
+        let mut complete_a = !balance_a.exists;
+        let mut complete_b = !balance_b.exists;
+
+        while (!complete_a || !complete_b) && used < max_requests {
+            // Spend the last request on whichever side is still open
+            let fetch_a = !complete_a;
+            let fetch_b = !complete_b && (!fetch_a || used + 2 <= max_requests);
+            let (page_a, page_b) = tokio::join!(
+                async {
+                    match fetch_a {
+                        true => self
+                            .get_transaction_history(a, Some(INVESTIGATE_PAGE), Some(history_a.len() as u32), None, url)
+                            .await
+                            .map(Some),
+                        false => Ok(None),
+                    }
+                },
+                async {
+                    match fetch_b {
+                        true => self
+                            .get_transaction_history(b, Some(INVESTIGATE_PAGE), Some(history_b.len() as u32), None, url)
+                            .await
+                            .map(Some),
+                        false => Ok(None),
+                    }
+                }
+            );
+            used += fetch_a as u32 + fetch_b as u32;
+            if let Some(txs) = page_a? {
+                complete_a = txs.len() < INVESTIGATE_PAGE as usize;
+                history_a.extend(txs);
+            }
+            if let Some(txs) = page_b? {
+                complete_b = txs.len() < INVESTIGATE_PAGE as usize;
+                history_b.extend(txs);
+            }
+        }
+
+        let end_height = self.tip_height(url).await?;
+        Ok(Investigation {
+            relationship: Relationship::from_histories(a, b, &history_a, &history_b),
+            consistency: Consistency::between(start_height, end_height),
+            address_a: AccountCoverage {
+                balance: balance_a,
+                transactions_examined: history_a.len(),
+                history_complete: complete_a,
+                frequent_counterparties: labels::frequent_counterparties(a, &history_a),
+            },
+            address_b: AccountCoverage {
+                balance: balance_b,
+                transactions_examined: history_b.len(),
+                history_complete: complete_b,
+                frequent_counterparties: labels::frequent_counterparties(b, &history_b),
+            },
+            requests_used: used,
+            max_requests,
+            budget_exhausted: !(complete_a && complete_b),
+        })
+    }
+
+    /// Follows transfers out of (or into) `address` breadth first for up to `max_hops`,
+    /// reading one history page of `txs_per_address` per expanded address and stopping once
+    /// `max_requests` pages have been read.
+    #[tracing::instrument(skip_all, fields(address=%telemetry::address_field(address)))]
+    pub async fn trace_funds(
+        &self,
+        address: &str,
+        direction: TraceDirection,
+        max_hops: u32,
+        txs_per_address: u32,
+        max_requests: u32,
+        url: &str,
+    ) -> Result<(FundsGraph, u32)> {
+        let mut trace = FundsTrace::new(address, direction);
+        let mut frontier = vec![address.to_string()];
+        let mut used = 0;
+
+        for _ in 0..max_hops {
+            let mut next = Vec::new();
+            for address in frontier {
+                if used == max_requests {
+                    break;
+                }
+                let history = self
+                    .get_transaction_history(&address, Some(txs_per_address), None, None, url)
+                    .await?;
+                used += 1;
+                next.extend(trace.expand(&address, &history));
+            }
+            frontier = next;
+        }
+        Ok((trace.finish(max_hops), used))
+    }
+
+    /// Forgets height-keyed data recorded for `url` at or above `height`, which may describe
+    /// entries that a reorg has replaced.
+    pub fn invalidate_from(&self, url: &str, height: u64) {
+        self.stats_history.discard_from(url, height);
+        self.contract_history.discard_from(url, height);
+    }
+
+    /// Remembers a contract value read at `height` for later diffs.
+    pub fn record_contract_state(&self, url: &str, contract: &str, key: &str, height: u64, value: serde_json::Value) {
+        let recorded_at = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        self.contract_history
+            .record(url, contract, key, StateObservation { height, recorded_at, value });
+    }
+
+    /// The latest recorded value of a contract key at or before `height`.
+    pub fn contract_state_at(&self, url: &str, contract: &str, key: &str, height: u64) -> Option<StateObservation> {
+        self.contract_history.at_or_before(url, contract, key, height)
+    }
+
+    /// Endpoints `url` is known to lack.
+    pub fn capabilities(&self, url: &str) -> NodeCapabilities {
+        self.capabilities.read().unwrap().get(url).cloned().unwrap_or_default()
+    }
+
+    /// Sends a HEAD to each tracked endpoint's probe path and records which ones 404.
+    /// Other failures say nothing about support and leave the endpoint as it was.
+    #[tracing::instrument(skip_all)]
+    pub async fn probe_endpoints(&self, url: &str) -> NodeCapabilities {
+        for requirement in ENDPOINT_REQUIREMENTS {
+            match self.probe(url, &self.paths.rebase(requirement.probe)).await {
+                Some(true) => self.update_capabilities(url, |c| c.mark_present(requirement)),
+                Some(false) => {
+                    warn!(endpoint = requirement.endpoint, tools = ?requirement.tools, "node lacks endpoint");
+                    self.update_capabilities(url, |c| c.mark_missing(requirement));
+                }
+                None => {}
+            }
+        }
+        if let Some(present) = self.probe(url, &self.paths.rebase(BATCH_ENDPOINT)).await {
+            self.update_capabilities(url, |c| c.batch = present);
+        }
+        self.update_capabilities(url, |c| c.probed = true);
+        self.capabilities(url)
+    }
+
+    /// Whether `path` exists on the node, `None` when the probe itself failed.
+    async fn probe(&self, base_url: &str, path: &str) -> Option<bool> {
+        if let Some(store) = &self.offline {
+            return Some(store.get(&self.paths.canonical(path)).is_some());
+        }
+        let url = format!("{}{}", base_url.trim_end_matches('/'), path);
+        metrics::record(|m| m.upstream_request());
+        let request = self.client.head(&url).timeout(Duration::from_secs(5));
+        let response = telemetry::inject_trace_context(request).send().await.ok()?;
+        Some(response.status() != reqwest::StatusCode::NOT_FOUND)
+    }
+
+    fn update_capabilities(&self, url: &str, update: impl FnOnce(&mut NodeCapabilities)) {
+        update(self.capabilities.write().unwrap().entry(url.to_string()).or_default());
+    }
+
+    /// GETs `path`, answering from the disk cache when it holds the response. Responses
+    /// `finalized` accepts are written to the disk cache; everything else could still change.
+    async fn immutable_get(
+        &self,
+        url: &str,
+        path: &str,
+        finalized: impl FnOnce(&serde_json::Value) -> bool,
+    ) -> Result<serde_json::Value> {
+        if let Some(cached) = self.disk_cache.as_ref().and_then(|cache| cache.get("GET", url, path)) {
+            metrics::record(|m| m.cache_hit());
+            return Ok(cached);
+        }
+        let response = self.retry_request_with_url(url, "GET", path, None).await?;
+        let value: serde_json::Value = self.parse_response(response).await?;
+        if let Some(cache) = &self.disk_cache {
+            if finalized(&value) {
+                cache.put("GET", url, path, &value);
+            }
+        }
+        Ok(value)
+    }
+
     async fn retry_request_with_url(
         &self,
         base_url: &str,
@@ -262,48 +1421,118 @@ impl BlockchainClient {
         path: &str,
         body: Option<&serde_json::Value>,
     ) -> Result<Response> {
+        if let Some(store) = &self.offline {
+            return self.fixture_response(store, method, path);
+        }
         let retry_strategy = ExponentialBackoff::from_millis(100).map(jitter).take(3);
 
         let url = format!("{}{}", base_url.trim_end_matches('/'), path);
+        let attempt = AtomicUsize::new(0);
 
-        Retry::spawn(retry_strategy, || async {
-            let mut request = match method {
-                "GET" => self.client.get(&url),
-                "POST" => self.client.post(&url),
-                _ => {
-                    return Err(BlockchainError::Configuration(format!(
-                        "unsupported method: {}",
-                        method
-                    )))
-                }
-            };
-
-            request = request.header(header::CONTENT_TYPE, "application/json");
-
-            if let Some(json) = body {
-                request = request.json(json);
-            }
-
-            request
-                .send()
-                .await
-                .map_err(BlockchainError::HttpRequest)
-                .and_then(|resp| {
-                    if resp.status().is_success() {
-                        Ok(resp)
-                    } else {
-                        Err(BlockchainError::InvalidResponse(format!(
-                            "HTTP {}: request failed",
-                            resp.status()
-                        )))
+        let result = RetryIf::spawn(
+            retry_strategy,
+            || {
+                let n = attempt.fetch_add(1, Ordering::Relaxed) + 1;
+                metrics::record(|m| {
+                    m.upstream_request();
+                    if n > 1 {
+                        m.retry();
                     }
-                })
-        })
-        .await
-        .map_err(|e| {
-            warn!("retry exhausted: {}", e);
-            BlockchainError::NetworkRetryExhausted { attempts: 3 }
-        })
+                });
+                let span = info_span!(
+                    "upstream_request",
+                    method,
+                    endpoint = %telemetry::redact_text(&self.paths.canonical(path)),
+                    attempt = n,
+                    status = Empty,
+                );
+                self.send_attempt(&url, method, body).instrument(span)
+            },
+            // A 404 will not go away on retry
+            |e: &BlockchainError| !matches!(e, BlockchainError::EndpointUnsupported { .. }),
+        )
+        .await;
+
+        match result {
+            Ok(response) => Ok(response),
+            Err(BlockchainError::EndpointUnsupported { .. }) => Err(self.confirm_missing(base_url, path).await),
+            Err(e) => {
+                warn!("retry exhausted: {}", e);
+                Err(BlockchainError::NetworkRetryExhausted { attempts: 3 })
+            }
+        }
+    }
+
+    /// The recorded answer to a node request in offline mode. Only reads are answered.
+    fn fixture_response(&self, store: &FixtureStore, method: &str, path: &str) -> Result<Response> {
+        if method != "GET" {
+            return Err(BlockchainError::Offline(format!("{} {} would reach the node", method, path)));
+        }
+        let value = store
+            .get(&self.paths.canonical(path))
+            .ok_or_else(|| BlockchainError::Offline(format!("no fixture in {} for {}", store.dir().display(), path)))?;
+        metrics::record(|m| m.cache_hit());
+        Ok(Response::from(http::Response::new(value.to_string())))
+    }
+
+    /// Turns a 404 into `EndpointUnsupported` when the endpoint's probe 404s too, and
+    /// records it; otherwise the 404 was about the requested item, not the endpoint.
+    async fn confirm_missing(&self, base_url: &str, path: &str) -> BlockchainError {
+        let not_found = BlockchainError::InvalidResponse(ITEM_NOT_FOUND.to_string());
+        let Some(requirement) = catalog::endpoint_requirement(&self.paths.canonical(path)) else {
+            return not_found;
+        };
+        if self.probe(base_url, &self.paths.rebase(requirement.probe)).await != Some(false) {
+            return not_found;
+        }
+        warn!(endpoint = requirement.endpoint, tools = ?requirement.tools, "node lacks endpoint");
+        self.update_capabilities(base_url, |c| c.mark_missing(requirement));
+        BlockchainError::EndpointUnsupported {
+            endpoint: requirement.endpoint.to_string(),
+        }
+    }
+
+    /// One upstream attempt, run inside its `upstream_request` span.
+    async fn send_attempt(
+        &self,
+        url: &str,
+        method: &str,
+        body: Option<&serde_json::Value>,
+    ) -> Result<Response> {
+        let mut request = match method {
+            "GET" => self.client.get(url),
+            "POST" => self.client.post(url),
+            _ => {
+                return Err(BlockchainError::Configuration(format!(
+                    "unsupported method: {}",
+                    method
+                )))
+            }
+        };
+
+        request = request.header(header::CONTENT_TYPE, "application/json");
+
+        if let Some(json) = body {
+            request = request.json(json);
+        }
+
+        let resp = telemetry::inject_trace_context(request)
+            .send()
+            .await
+            .map_err(BlockchainError::HttpRequest)?;
+        tracing::Span::current().record("status", resp.status().as_u16());
+
+        if resp.status().is_success() {
+            Ok(resp)
+        } else if resp.status() == reqwest::StatusCode::NOT_FOUND {
+            // Not retried; retry_request_with_url works out whether the endpoint or the item is missing
+            Err(BlockchainError::EndpointUnsupported { endpoint: url.to_string() })
+        } else {
+            Err(BlockchainError::InvalidResponse(format!(
+                "HTTP {}: request failed",
+                resp.status()
+            )))
+        }
     }
 
     async fn parse_response<T: serde::de::DeserializeOwned>(
@@ -324,3 +1553,90 @@ impl BlockchainClient {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::offline::ScratchNode;
+
+    const URL: &str = "https://nodes.amadeus.bot";
+
+    fn trainers(node: &ScratchNode) {
+        node.set("trainers", serde_json::json!({ "error": "ok", "trainers": ["pk_a", "pk_b", "pk_c"] }));
+    }
+
+    fn snapshot(height: u64) -> StatsSnapshot {
+        StatsSnapshot { height, burned: 1.0, circulating: 2.0, recorded_at: 1_700_000_000 }
+    }
+
+    #[test]
+    fn with_state_of_keeps_history_and_caches() {
+        let previous = BlockchainClient::new(URL.to_string()).unwrap();
+        previous.stats_history.record(URL, snapshot(10));
+        previous.update_capabilities(URL, |c| {
+            c.probed = true;
+            c.batch = true;
+        });
+        previous.finalized_heights.write().unwrap().insert(URL.to_string(), 9);
+
+        let client = BlockchainClient::new(URL.to_string()).unwrap().with_state_of(&previous);
+        assert_eq!(client.stats_snapshots(URL).iter().map(|s| s.height).collect::<Vec<_>>(), vec![10]);
+        assert!(client.capabilities(URL).probed && client.capabilities(URL).batch);
+        assert_eq!(client.finalized_heights.read().unwrap().get(URL), Some(&9));
+
+        // Shared, not copied: what either records is seen by both
+        client.stats_history.record(URL, snapshot(11));
+        assert_eq!(previous.stats_snapshots(URL).len(), 2);
+    }
+
+    #[test]
+    fn with_state_of_drops_capabilities_probed_under_other_paths() {
+        let previous = BlockchainClient::new(URL.to_string()).unwrap();
+        previous.stats_history.record(URL, snapshot(10));
+        previous.update_capabilities(URL, |c| {
+            c.probed = true;
+            c.batch = true;
+        });
+
+        let paths = ApiPaths::new("/node/api".to_string(), "/node/api".to_string()).unwrap();
+        let client = BlockchainClient::new(URL.to_string()).unwrap().with_api_paths(paths).with_state_of(&previous);
+        assert_eq!(client.capabilities(URL), NodeCapabilities::default());
+        assert_eq!(client.stats_snapshots(URL).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn validators_fall_back_to_keys_when_scores_are_unavailable() {
+        let node = ScratchNode::new();
+        trainers(&node);
+        // The fixture store has no epoch/score route, so the score fetch fails
+        let validators = node.client().get_validators(URL, false).await.unwrap();
+        assert_eq!(validators.iter().map(|v| v.pk.as_str()).collect::<Vec<_>>(), ["pk_a", "pk_b", "pk_c"]);
+        assert!(validators.iter().all(|v| v.score.is_none() && v.rank.is_none()));
+        assert_eq!(serde_json::to_value(&validators[0]).unwrap(), serde_json::json!({ "pk": "pk_a" }));
+    }
+
+    #[tokio::test]
+    async fn validators_carry_scores_when_available() {
+        let node = ScratchNode::new();
+        trainers(&node);
+        let client = node.client();
+        let scores = EpochScore::rank(vec![("pk_b".to_string(), 9.0), ("pk_a".to_string(), 4.5)]);
+        client.epoch_scores.insert(URL.to_string(), scores);
+
+        let validators = client.get_validators(URL, false).await.unwrap();
+        let scored: Vec<_> = validators.iter().map(|v| (v.pk.as_str(), v.score, v.rank)).collect();
+        assert_eq!(scored, [("pk_a", Some(4.5), Some(2)), ("pk_b", Some(9.0), Some(1)), ("pk_c", None, None)]);
+
+        let keys_only = client.get_validators(URL, true).await.unwrap();
+        assert!(keys_only.iter().all(|v| v.score.is_none()));
+    }
+
+    #[tokio::test]
+    async fn validators_fail_when_the_trainer_list_does() {
+        let node = ScratchNode::new();
+        node.set("trainers", serde_json::json!({ "error": "unavailable" }));
+        assert!(node.client().get_validators(URL, false).await.is_err());
+        node.remove("trainers");
+        assert!(matches!(node.client().get_validators(URL, true).await, Err(BlockchainError::Offline(_))));
+    }
+}
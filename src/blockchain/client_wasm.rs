@@ -1,16 +1,126 @@
 use super::{
+    api_path::ApiPaths,
+    block_time::{BlockAtTime, TimeSearch},
+    block_txs::{self, BlockEntryWithTxs, EntryTxs},
+    decimals::DecimalsRegistry,
     error::{BlockchainError, Result},
+    failover::{self, NodeReply, NodeTransport},
+    freshness::{self, AccountNonce},
+    health::NodeHealth,
+    full_history::{FullHistory, HistoryCollector, HistoryPage, DEFAULT_FULL_HISTORY_PAGE, FULL_HISTORY_SORT},
+    history_filter::{FilteredHistory, HistoryFilter, HistoryScan, DEFAULT_FILTERED_LIMIT, HISTORY_SCAN_PAGE},
+    limits::QueryLimits,
+    metrics::CallMetrics,
+    preview::{self, CallPreview},
+    richlist,
+    state_export::{self, ExportPage, KeyListing},
+    templates,
+    token::{self, TokenInfo, TokenList},
+    transfer_check::{TransferRequest, TransferValidation},
+    tx_pool::{TxPool, TX_POOL_PATH},
     types::*,
 };
+use crate::config::UpstreamTransport;
 use crate::wasm::tx;
-use worker::{Fetch, Method, Request, RequestInit};
+use std::{collections::HashMap, rc::Rc, time::Duration};
+use worker::{Delay, Fetch, Fetcher, Method, Request, RequestInit, Response};
 
 #[derive(Clone)]
-pub struct BlockchainClient {}
+pub struct BlockchainClient {
+    base_url: String,
+    /// Service binding used instead of public fetch for requests to `base_url`
+    service: Option<Rc<Fetcher>>,
+    /// Nodes tried in order when `base_url` is unavailable
+    fallback_urls: Vec<String>,
+    /// Upstream activity of the tool call this client serves
+    metrics: Rc<CallMetrics>,
+    paths: ApiPaths,
+    limits: QueryLimits,
+}
 
 impl BlockchainClient {
-    pub fn new(_base_url: String) -> Result<Self> {
-        Ok(Self {})
+    pub fn new(base_url: String) -> Result<Self> {
+        Ok(Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            service: None,
+            fallback_urls: Vec::new(),
+            metrics: Rc::default(),
+            paths: ApiPaths::default(),
+            limits: QueryLimits::default(),
+        })
+    }
+
+    /// Mounts node paths under `paths` instead of the default `/api` prefixes.
+    pub fn with_api_paths(mut self, paths: ApiPaths) -> Self {
+        self.paths = paths;
+        self
+    }
+
+    pub fn api_paths(&self) -> &ApiPaths {
+        &self.paths
+    }
+
+    /// Refuses tool arguments above `limits` before asking the node.
+    pub fn with_limits(mut self, limits: QueryLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn limits(&self) -> &QueryLimits {
+        &self.limits
+    }
+
+    /// Full URL of API `path` (`chain/tip`) on the node at `base_url`.
+    pub fn api_url(&self, base_url: &str, path: &str) -> String {
+        format!("{}{}", base_url.trim_end_matches('/'), self.paths.api(path))
+    }
+
+    /// Full URL of transaction API `path` (`tx/submit`) on the node at `base_url`.
+    pub fn tx_url(&self, base_url: &str, path: &str) -> String {
+        format!("{}{}", base_url.trim_end_matches('/'), self.paths.tx(path))
+    }
+
+    /// Backup nodes for requests to the base URL, used when it keeps failing with a 5xx,
+    /// an HTML error page or a network error.
+    pub fn with_fallbacks(mut self, urls: Vec<String>) -> Self {
+        self.fallback_urls = urls.into_iter().map(|u| u.trim_end_matches('/').to_string()).collect();
+        self
+    }
+
+    /// Routes requests to the base URL through a service binding, skipping the public
+    /// TLS handshake. Other URLs (e.g. testnet) still go over public fetch.
+    pub fn with_service(mut self, service: Fetcher) -> Self {
+        self.service = Some(Rc::new(service));
+        self
+    }
+
+    /// A copy of this client counting into fresh metrics, for measuring one tool call.
+    pub fn measured(&self) -> (Self, Rc<CallMetrics>) {
+        let metrics = Rc::new(CallMetrics::default());
+        (Self { metrics: metrics.clone(), ..self.clone() }, metrics)
+    }
+
+    async fn send(&self, url: &str, request: Request) -> Result<Response> {
+        let response = match &self.service {
+            Some(service) if UpstreamTransport::is_bound_url(&self.base_url, url) => service.fetch_request(request).await,
+            _ => Fetch::Request(request).send().await,
+        };
+        response.map_err(|e| BlockchainError::HttpRequestWasm(e.to_string()))
+    }
+
+    /// Sends `init` to `url`, retrying 5xx and non-JSON responses, then the same path on
+    /// each fallback node. Returns the body of the first good response.
+    async fn execute(&self, url: &str, init: &RequestInit) -> Result<String> {
+        let candidates = failover::candidates(url, &self.base_url, &self.fallback_urls);
+        failover::fetch(&FetchTransport { client: self, init }, &candidates, &self.metrics).await
+    }
+
+    /// GETs a full URL and parses the body as JSON without interpreting it.
+    pub async fn get_raw(&self, url: &str) -> Result<serde_json::Value> {
+        let mut init = RequestInit::new();
+        init.with_method(Method::Get);
+        let text = self.execute(url, &init).await?;
+        serde_json::from_str(&text).map_err(|e| BlockchainError::InvalidResponse(e.to_string()))
     }
 
     pub async fn create_transaction_blob(
@@ -20,18 +130,9 @@ impl BlockchainClient {
         let signer_pk = bs58::decode(&req.signer)
             .into_vec()
             .map_err(|_| BlockchainError::ValidationFailed("invalid signer base58".into()))?;
+        templates::check_request(&req).map_err(BlockchainError::ValidationFailed)?;
 
-        let args: Result<Vec<Vec<u8>>> = req.args.iter().map(|arg| match arg {
-            Argument::String(s) => Ok(s.as_bytes().to_vec()),
-            Argument::Number(n) => Ok(n.to_string().as_bytes().to_vec()),
-            Argument::Base58 { b58 } => bs58::decode(b58)
-                .into_vec()
-                .map_err(|_| BlockchainError::ValidationFailed("invalid base58 arg".into())),
-            Argument::Hex { hex } => hex::decode(hex.trim_start_matches("0x"))
-                .map_err(|_| BlockchainError::ValidationFailed("invalid hex arg".into())),
-            Argument::Utf8 { utf8 } => Ok(utf8.as_bytes().to_vec()),
-        }).collect();
-        let args = args?;
+        let args = req.args.iter().map(Argument::to_bytes).collect::<Result<Vec<_>>>()?;
 
         let attached_symbol = req.attached_symbol.as_ref().map(|s| s.as_bytes());
         let attached_amount = req.attached_amount.as_ref().map(|s| s.as_bytes());
@@ -51,6 +152,7 @@ impl BlockchainClient {
             signing_payload: hex::encode(unsigned.signing_hash),
             transaction_hash: bs58::encode(unsigned.signing_hash).into_string(),
             tx_bytes: unsigned.tx_blob,
+            nonce: unsigned.nonce,
         })
     }
 
@@ -59,7 +161,7 @@ impl BlockchainClient {
             .map_err(|e| BlockchainError::ValidationFailed(e.into()))?;
         let tx_hash = bs58::encode(&finalized.hash).into_string();
         let txu_b58 = bs58::encode(&finalized.packed).into_string();
-        let full_url = format!("{}/api/tx/submit", url);
+        let full_url = self.tx_url(url, "tx/submit");
 
         let mut init = RequestInit::new();
         init.with_method(Method::Post);
@@ -73,50 +175,113 @@ impl BlockchainClient {
         let request = Request::new_with_init(&full_url, &init)
             .map_err(|e| BlockchainError::HttpRequestWasm(e.to_string()))?;
 
-        let mut response = Fetch::Request(request).send().await
-            .map_err(|e| BlockchainError::HttpRequestWasm(e.to_string()))?;
+        let mut response = self.send(&full_url, request).await?;
 
+        // Not retried: a resubmit after a lost response could double-send. Rejections may come
+        // back as 4xx with an {"error": ...} body; keep those classifiable
         let status = response.status_code();
-        if !(200..300).contains(&status) {
-            return Err(BlockchainError::InvalidResponse(format!("HTTP {}", status)));
+        if status >= 500 {
+            return Err(BlockchainError::UpstreamUnavailable { status });
         }
+        let success = (200..300).contains(&status);
 
         let text = response.text().await
             .map_err(|e| BlockchainError::HttpRequestWasm(e.to_string()))?;
 
-        let api_response: serde_json::Value = serde_json::from_str(&text)
-            .map_err(|e| BlockchainError::InvalidResponse(e.to_string()))?;
-        let error = api_response.get("error").and_then(|e| e.as_str()).unwrap_or("unknown");
+        let api_response: serde_json::Value = match serde_json::from_str(&text) {
+            Ok(body) => body,
+            Err(_) if !success => return Err(BlockchainError::InvalidResponse(format!("HTTP {}", status))),
+            Err(e) => return Err(BlockchainError::InvalidResponse(e.to_string())),
+        };
+        if !success && !api_response.get("error").is_some_and(|e| e.is_string()) {
+            return Err(BlockchainError::InvalidResponse(format!("HTTP {}", status)));
+        }
 
-        Ok(SubmitResponse {
-            error: error.to_string(),
-            tx_hash: if error == "ok" { Some(tx_hash) } else { None },
-        })
+        Ok(SubmitResponse::from_node(&api_response, tx_hash))
     }
 
     pub async fn get_account_balance(&self, address: &str, url: &str) -> Result<AccountBalance> {
-        let path = format!("/api/wallet/balance_all/{}", address);
+        if !is_valid_address(address) {
+            return Err(BlockchainError::AccountNotFound {
+                address: address.to_string(),
+            });
+        }
+
+        let path = self.paths.api(&format!("wallet/balance_all/{}", address));
         let resp: serde_json::Value = self.request_with_url(url, "GET", &path, None).await?;
 
-        if resp.get("error").and_then(|e| e.as_str()) != Some("ok") {
+        let Some(balances) = AccountBalance::balances_from(&resp)? else {
+            return Ok(AccountBalance { address: address.to_string(), balances: Vec::new(), exists: false });
+        };
+
+        let exists = balances.iter().any(|b| b.flat > 0) || self.account_exists(address, url).await?;
+
+        Ok(AccountBalance {
+            address: address.to_string(),
+            balances,
+            exists,
+        })
+    }
+
+    /// Balance of one `symbol` from the node's per-symbol endpoint, cheaper than
+    /// `get_account_balance` when only one token matters. A symbol the account does not
+    /// hold is a zero balance.
+    pub async fn get_symbol_balance(&self, address: &str, symbol: &str, url: &str) -> Result<Balance> {
+        if !is_valid_address(address) {
             return Err(BlockchainError::AccountNotFound {
                 address: address.to_string(),
             });
         }
+        if symbol.is_empty() || !symbol.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(BlockchainError::ValidationFailed(format!("symbol {:?} is not alphanumeric", symbol)));
+        }
+
+        let path = self.paths.api(&format!("wallet/balance/{}/{}", address, symbol));
+        let resp: serde_json::Value = self.request_with_url(url, "GET", &path, None).await?;
 
-        let balances = resp
-            .get("balances")
-            .ok_or_else(|| BlockchainError::InvalidResponse("missing balances".into()))?;
+        match (resp.get("error").and_then(|e| e.as_str()), resp.get("balance")) {
+            (Some("ok"), Some(balance)) => serde_json::from_value(balance.clone())
+                .map_err(|e| BlockchainError::InvalidResponse(e.to_string())),
+            _ => Ok(Balance::zero(symbol)),
+        }
+    }
 
+    /// `get_account_balance` narrowed to `symbol`: `balances` holds just that symbol, zero
+    /// when it is not held, and `exists` is worked out the same way.
+    pub async fn get_account_symbol_balance(&self, address: &str, symbol: &str, url: &str) -> Result<AccountBalance> {
+        let balance = self.get_symbol_balance(address, symbol, url).await?;
+        let exists = balance.flat > 0 || self.account_exists(address, url).await?;
         Ok(AccountBalance {
             address: address.to_string(),
-            balances: serde_json::from_value(balances.clone())
-                .map_err(|e| BlockchainError::InvalidResponse(e.to_string()))?,
+            balances: vec![balance],
+            exists,
         })
     }
 
+    /// Dry-runs `req`: the address, symbol and amount checks, then the source balance when
+    /// they allow it. Decimal amounts convert with `decimals`; without it they fail.
+    pub async fn validate_transfer(&self, req: &TransferRequest, decimals: Option<&DecimalsRegistry>, url: &str) -> TransferValidation {
+        let mut report = TransferValidation::check(req, decimals);
+        if report.needs_balance() {
+            let account = self.get_account_balance(&req.source, url).await;
+            report.check_balance(account.as_ref().map(|a| a.balances.as_slice()).map_err(|e| e.to_string()));
+        }
+        report
+    }
+
+    pub async fn account_exists(&self, address: &str, url: &str) -> Result<bool> {
+        let path = self.paths.api(&format!("chain/tx_events_by_account/{}?limit=1", address));
+        let resp: serde_json::Value = self.request_with_url(url, "GET", &path, None).await?;
+
+        // Unknown accounts come back with an error envelope and no txs
+        Ok(resp
+            .get("txs")
+            .and_then(|txs| txs.as_array())
+            .is_some_and(|txs| !txs.is_empty()))
+    }
+
     pub async fn get_chain_stats(&self, url: &str) -> Result<ChainStats> {
-        let resp: serde_json::Value = self.request_with_url(url, "GET", "/api/chain/stats", None).await?;
+        let resp: serde_json::Value = self.request_with_url(url, "GET", &self.paths.api("chain/stats"), None).await?;
 
         let stats = resp
             .get("stats")
@@ -126,8 +291,86 @@ impl BlockchainClient {
             .map_err(|e| BlockchainError::InvalidResponse(e.to_string()))
     }
 
+    /// Times a chain/stats call to `url` and compares its height with `reference`'s. The
+    /// calls run one after the other, so the reference is read slightly later.
+    pub async fn health_check(&self, url: &str, reference: Option<&str>) -> NodeHealth {
+        let started = worker::Date::now().as_millis();
+        let node = self
+            .get_chain_stats(url)
+            .await
+            .map(|stats| (stats, worker::Date::now().as_millis().saturating_sub(started)))
+            .map_err(|e| e.to_string());
+        let reference = match reference {
+            Some(reference) => Some((reference, self.get_chain_stats(reference).await.map_err(|e| e.to_string()))),
+            None => None,
+        };
+        NodeHealth::new(url, node, reference)
+    }
+
+    /// The entries just before and after `target_secs`, binary-searched between genesis and
+    /// the height chain/stats reports with at most `max_probes` entry fetches (default: the
+    /// server's limit).
+    pub async fn block_at_time(&self, url: &str, target_secs: u64, max_probes: Option<u64>) -> Result<BlockAtTime> {
+        let max_probes = max_probes.unwrap_or(self.limits.max_time_probes);
+        self.limits.check_time_probes(max_probes)?;
+        let tip = self.get_chain_stats(url).await?.height;
+        let mut search = TimeSearch::new(target_secs, tip, max_probes);
+        while let Some(height) = search.next_height() {
+            let entries = self.get_block_by_height(height, url).await?;
+            search.record(height, &entries);
+        }
+        Ok(search.finish(worker::Date::now().as_millis() / 1000))
+    }
+
+    /// The entries at `height` with their transactions embedded.
+    pub async fn get_block_with_txs(&self, height: u64, url: &str) -> Result<Vec<BlockEntryWithTxs>> {
+        let path = self.paths.api(&format!("chain/height_with_txs/{}", height));
+        let resp: serde_json::Value = self.request_with_url(url, "GET", &path, None).await?;
+        block_txs::entries_from(&resp)
+    }
+
+    /// The entry with base58 `hash`. An unknown hash is `NotFound`.
+    pub async fn get_entry_by_hash(&self, hash: &str, url: &str) -> Result<BlockEntry> {
+        if !is_base58(hash) {
+            return Err(BlockchainError::ValidationFailed(format!("entry hash {} is not base58", hash)));
+        }
+        let path = self.paths.api(&format!("chain/hash/{}", hash));
+        let resp: serde_json::Value = match self.request_with_url(url, "GET", &path, None).await {
+            Err(BlockchainError::InvalidResponse(message)) if message == "HTTP 404" => {
+                return Err(BlockchainError::NotFound { argument: "hash".to_string(), value: hash.to_string() });
+            }
+            result => result?,
+        };
+        BlockEntry::from_hash_response(hash, &resp)
+    }
+
+    /// The transactions of the entry `entry_hash`. An unknown hash is `NotFound`.
+    pub async fn get_txs_in_entry(&self, entry_hash: &str, url: &str) -> Result<EntryTxs> {
+        let not_found = || BlockchainError::NotFound { argument: "entry_hash".to_string(), value: entry_hash.to_string() };
+        if !is_base58(entry_hash) {
+            return Err(not_found());
+        }
+        let path = self.paths.api(&format!("chain/txs_in_entry/{}", entry_hash));
+        let resp: serde_json::Value = match self.request_with_url(url, "GET", &path, None).await {
+            Err(BlockchainError::InvalidResponse(message)) if message == "HTTP 404" => return Err(not_found()),
+            result => result?,
+        };
+        block_txs::txs_from(entry_hash, &resp)
+    }
+
+    pub async fn get_tx_pool(&self, url: &str) -> Result<TxPool> {
+        let path = self.paths.api(TX_POOL_PATH);
+        let resp: serde_json::Value = match self.request_with_url(url, "GET", &path, None).await {
+            Err(BlockchainError::InvalidResponse(message)) if message == "HTTP 404" => {
+                return Err(BlockchainError::EndpointUnsupported { endpoint: path });
+            }
+            result => result?,
+        };
+        TxPool::from_node(&resp)
+    }
+
     pub async fn get_block_by_height(&self, height: u64, url: &str) -> Result<Vec<BlockEntry>> {
-        let path = format!("/api/chain/height/{}", height);
+        let path = self.paths.api(&format!("chain/height/{}", height));
         let resp: serde_json::Value = self.request_with_url(url, "GET", &path, None).await?;
 
         let entries = resp
@@ -138,17 +381,28 @@ impl BlockchainClient {
             .map_err(|e| BlockchainError::InvalidResponse(e.to_string()))
     }
 
+    /// The latest nonce `address` signed with, read from its newest transactions, and the
+    /// nonce to build its next one with. An account that never transacted has nonce 0.
+    pub async fn get_account_nonce(&self, address: &str, url: &str) -> Result<AccountNonce> {
+        if !is_valid_address(address) {
+            return Err(BlockchainError::AccountNotFound { address: address.to_string() });
+        }
+        let history = self
+            .get_transaction_history(address, Some(freshness::NONCE_HISTORY_LIMIT), None, Some("desc"), url)
+            .await?;
+        Ok(AccountNonce::new(address, &history, worker::Date::now().as_millis() / 1000))
+    }
+
     pub async fn get_transaction(&self, tx_hash: &str, url: &str) -> Result<Transaction> {
-        let path = format!("/api/chain/tx/{}", tx_hash);
+        let path = self.paths.api(&format!("chain/tx/{}", tx_hash));
         let resp: serde_json::Value = self.request_with_url(url, "GET", &path, None).await?;
 
         if resp.get("result").and_then(|r| r.get("error")).and_then(|e| e.as_str()) == Some("not_found") {
-            return Err(BlockchainError::InvalidResponse(
-                "transaction not found".into(),
-            ));
+            return Err(BlockchainError::NotFound { argument: "tx_hash".to_string(), value: tx_hash.to_string() });
         }
 
         serde_json::from_value(resp)
+            .map(Transaction::with_decoded_action)
             .map_err(|e| BlockchainError::InvalidResponse(e.to_string()))
     }
 
@@ -160,7 +414,8 @@ impl BlockchainClient {
         sort: Option<&str>,
         url: &str,
     ) -> Result<Vec<Transaction>> {
-        let mut path = format!("/api/chain/tx_events_by_account/{}", address);
+        self.limits.check_history(limit)?;
+        let mut path = self.paths.api(&format!("chain/tx_events_by_account/{}", address));
         let mut params = vec![];
         if let Some(l) = limit {
             params.push(format!("limit={}", l));
@@ -181,12 +436,102 @@ impl BlockchainClient {
             .get("txs")
             .ok_or_else(|| BlockchainError::InvalidResponse("missing txs".into()))?;
 
-        serde_json::from_value(txs.clone())
+        serde_json::from_value::<Vec<Transaction>>(txs.clone())
+            .map(|txs| txs.into_iter().map(Transaction::with_decoded_action).collect())
             .map_err(|e| BlockchainError::InvalidResponse(e.to_string()))
     }
 
-    pub async fn get_validators(&self, url: &str) -> Result<Vec<ValidatorInfo>> {
-        let resp: serde_json::Value = self.request_with_url(url, "GET", "/api/peer/trainers", None).await?;
+    pub async fn get_filtered_history(
+        &self,
+        address: &str,
+        filter: &HistoryFilter,
+        limit: Option<u32>,
+        offset: Option<u32>,
+        sort: Option<&str>,
+        url: &str,
+    ) -> Result<FilteredHistory<Vec<Transaction>>> {
+        let wanted = limit.unwrap_or(DEFAULT_FILTERED_LIMIT);
+        self.limits.check_history(Some(wanted))?;
+        let page_size = self.limits.history_page(HISTORY_SCAN_PAGE);
+        let mut scan = HistoryScan::new(offset);
+        let mut timestamps: HashMap<u64, Option<u64>> = HashMap::new();
+
+        while scan.wants_more(wanted) {
+            let page = self
+                .get_transaction_history(address, Some(page_size), Some(scan.offset()), sort, url)
+                .await?;
+            scan.pages += 1;
+            scan.exhausted = (page.len() as u32) < page_size;
+            for tx in page {
+                if scan.matched.len() as u32 >= wanted {
+                    scan.exhausted = false;
+                    break;
+                }
+                scan.scanned += 1;
+                if !filter.matches(address, &tx) {
+                    continue;
+                }
+                if filter.needs_timestamps() {
+                    let height = tx.metadata.entry_height;
+                    let timestamp = match timestamps.get(&height) {
+                        Some(timestamp) => *timestamp,
+                        None => {
+                            let entries = self.get_block_by_height(height, url).await?;
+                            let timestamp = entries.iter().find_map(|e| e.header.timestamp);
+                            timestamps.insert(height, timestamp);
+                            timestamp
+                        }
+                    };
+                    if !filter.in_time_range(timestamp) {
+                        continue;
+                    }
+                }
+                scan.matched.push(tx);
+            }
+        }
+        Ok(scan.finish())
+    }
+
+    /// Reads the whole history of `address` oldest first, `page_size` transactions a page,
+    /// until a short page or `max_transactions` (the configured full history limit by
+    /// default). `on_page` runs after each page.
+    pub async fn get_full_transaction_history<F, Fut>(
+        &self,
+        address: &str,
+        page_size: Option<u32>,
+        max_transactions: Option<u64>,
+        url: &str,
+        mut on_page: F,
+    ) -> Result<FullHistory>
+    where
+        F: FnMut(HistoryPage) -> Fut,
+        Fut: std::future::Future<Output = ()>,
+    {
+        let page_size = page_size.unwrap_or(self.limits.history_page(DEFAULT_FULL_HISTORY_PAGE));
+        self.limits.check_history(Some(page_size))?;
+        let max_transactions = max_transactions.unwrap_or(self.limits.max_full_history);
+        self.limits.check_full_history(max_transactions)?;
+
+        let mut collector = HistoryCollector::new(max_transactions);
+        while collector.wants_more() {
+            let page = self
+                .get_transaction_history(address, Some(page_size), Some(collector.offset()), Some(FULL_HISTORY_SORT), url)
+                .await?;
+            on_page(collector.push(page, page_size)).await;
+        }
+
+        let mut timestamps = [None, None];
+        if let Some((first, last)) = collector.height_bounds() {
+            for (slot, height) in timestamps.iter_mut().zip([first, last]) {
+                let entries = self.get_block_by_height(height, url).await?;
+                *slot = entries.iter().find_map(|e| e.header.timestamp);
+            }
+        }
+        Ok(collector.finish(address, timestamps[0], timestamps[1]))
+    }
+
+    pub async fn get_validator_keys(&self, url: &str) -> Result<Vec<String>> {
+        let resp: serde_json::Value = self.request_with_url(url, "GET", &self.paths.api("peer/trainers"), None).await?;
 
         let trainers = resp
             .get("trainers")
@@ -196,16 +541,169 @@ impl BlockchainClient {
             .map_err(|e| BlockchainError::InvalidResponse(e.to_string()))
     }
 
+    pub async fn get_removed_trainers(&self, url: &str) -> Result<RemovedTrainers> {
+        let resp: serde_json::Value = self.request_with_url(url, "GET", &self.paths.api("peer/removed_trainers"), None).await?;
+        RemovedTrainers::from_node(&resp)
+    }
+
+    pub async fn get_peer_nodes(&self, url: &str) -> Result<Vec<PeerNode>> {
+        let resp: serde_json::Value = self.request_with_url(url, "GET", &self.paths.api("peer/nodes"), None).await?;
+
+        let nodes = resp
+            .get("nodes")
+            .ok_or_else(|| BlockchainError::InvalidResponse("missing nodes".into()))?;
+
+        serde_json::from_value(nodes.clone())
+            .map_err(|e| BlockchainError::InvalidResponse(e.to_string()))
+    }
+
+    /// The node's AMA richlist, largest holders first.
+    pub async fn get_richlist(&self, url: &str) -> Result<Vec<RichlistEntry>> {
+        let resp: serde_json::Value = self.request_with_url(url, "GET", &self.paths.api("contract/richlist"), None).await?;
+        richlist::holders_from(&resp)
+    }
+
+    /// The address the validator `pk` has its emissions paid to, if it set one.
+    pub async fn get_emission_address(&self, pk: &str, url: &str) -> Result<EmissionAddress> {
+        if !is_valid_address(pk) {
+            return Err(BlockchainError::AccountNotFound { address: pk.to_string() });
+        }
+
+        let path = self.paths.api(&format!("epoch/get_emission_address/{}", pk));
+        let resp: serde_json::Value = self.request_with_url(url, "GET", &path, None).await?;
+        EmissionAddress::from_node(pk, &resp)
+    }
+
+    /// Epoch scores, highest first. With `address`, only that validator's score, read from
+    /// `/api/epoch/score/{pk}` and ranked against the full list.
+    pub async fn get_epoch_score(&self, url: &str, address: Option<&str>) -> Result<Vec<EpochScore>> {
+        let Some(pk) = address else {
+            return self.get_epoch_scores(url).await;
+        };
+        if !is_valid_address(pk) {
+            return Err(BlockchainError::ValidationFailed(format!("invalid address {}", pk)));
+        }
+
+        let path = self.paths.api(&format!("epoch/score/{}", pk));
+        let resp: serde_json::Value = self.request_with_url(url, "GET", &path, None).await?;
+        let all = self.get_epoch_scores(url).await?;
+        Ok(vec![EpochScore::for_validator(pk, &resp, &all)?])
+    }
+
+    pub async fn get_epoch_scores(&self, url: &str) -> Result<Vec<EpochScore>> {
+        let resp: serde_json::Value = self.request_with_url(url, "GET", &self.paths.api("epoch/score"), None).await?;
+        EpochScore::from_response(&resp)
+    }
+
+    pub async fn get_validators(&self, url: &str, keys_only: bool) -> Result<Vec<ValidatorInfo>> {
+        let keys = self.get_validator_keys(url).await?;
+        let scores = if keys_only {
+            None
+        } else {
+            self.get_epoch_scores(url).await.ok()
+        };
+        Ok(ValidatorInfo::join(keys, scores.as_deref()))
+    }
+
     pub async fn get_contract_state(
         &self,
         contract_address: &str,
         key: &str,
         url: &str,
     ) -> Result<serde_json::Value> {
-        let path = format!("/api/contract/get/{}/{}", contract_address, key);
+        let path = self.paths.api(&format!("contract/get/{}/{}", contract_address, key));
         self.request_with_url(url, "GET", &path, None).await
     }
 
+    /// A Coin storage value, `None` when the key is absent.
+    async fn coin_value(&self, key: &str, url: &str) -> Result<Option<serde_json::Value>> {
+        match self.get_contract_state(token::COIN_CONTRACT, key, url).await {
+            Err(BlockchainError::InvalidResponse(message)) if message == "HTTP 404" => Ok(None),
+            result => result.map(Some),
+        }
+    }
+
+    /// Supply, decimals and burned amount of `symbol`; `NotFound` when it has no supply.
+    pub async fn get_token_info(&self, symbol: &str, registry: &DecimalsRegistry, url: &str) -> Result<TokenInfo> {
+        if !symbol.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(token::unknown_symbol(symbol));
+        }
+        let supply = self.coin_value(&format!("{}{}", token::TOTAL_SUPPLY_PREFIX, symbol), url).await?;
+        let total_supply = token::total_supply(symbol, supply.as_ref())?;
+        self.token_info(symbol, total_supply, registry, url).await
+    }
+
+    /// Every token with a total supply, up to `MAX_LISTED_TOKENS`.
+    pub async fn list_tokens(&self, registry: &DecimalsRegistry, url: &str) -> Result<TokenList> {
+        let limit = token::MAX_LISTED_TOKENS.min(self.limits.max_contract_keys);
+        let page = self
+            .export_contract_state(token::COIN_CONTRACT, token::TOTAL_SUPPLY_PREFIX, None, limit, url)
+            .await?;
+        let mut tokens = Vec::new();
+        for (symbol, supply) in token::supplies(&page) {
+            tokens.push(self.token_info(&symbol, supply, registry, url).await?);
+        }
+        Ok(TokenList::new(tokens, page.complete))
+    }
+
+    async fn token_info(&self, symbol: &str, total_supply: u128, registry: &DecimalsRegistry, url: &str) -> Result<TokenInfo> {
+        let decimals = self.coin_value(&format!("{}{}", token::DECIMALS_PREFIX, symbol), url).await?;
+        let burned = self.coin_value(&token::burned_key(symbol), url).await?;
+        Ok(TokenInfo::new(
+            symbol,
+            total_supply,
+            decimals.as_ref().and_then(token::state_integer),
+            burned.as_ref().and_then(token::state_integer),
+            registry,
+        ))
+    }
+
+    /// One page of contract storage under `prefix`, resuming after the key `after`.
+    pub async fn export_contract_state(
+        &self,
+        contract_address: &str,
+        prefix: &str,
+        after: Option<&str>,
+        limit: u64,
+        url: &str,
+    ) -> Result<ExportPage> {
+        self.limits.check_contract_keys(limit)?;
+        // One key more than the page holds, to know whether another page follows
+        let path = self.paths.api(&state_export::request_path(contract_address, prefix, after, limit + 1));
+        let value: serde_json::Value = self.request_with_url(url, "GET", &path, None).await?;
+        state_export::page(&value, contract_address, prefix, after, limit)
+    }
+
+    /// Up to `limit` keys of contract storage under `prefix` with their values, resuming
+    /// after the key `after`.
+    pub async fn list_contract_state(
+        &self,
+        contract_address: &str,
+        prefix: &str,
+        after: Option<&str>,
+        limit: u64,
+        url: &str,
+    ) -> Result<KeyListing> {
+        self.limits.check_contract_keys(limit)?;
+        let path = self.paths.api(&state_export::request_path(contract_address, prefix, after, limit + 1));
+        let value: serde_json::Value = self.request_with_url(url, "GET", &path, None).await?;
+        state_export::listing(&value, contract_address, prefix, after, limit)
+    }
+
+    /// Executes a contract call on the node without a transaction and reports its result
+    /// or revert reason.
+    pub async fn preview_contract_call(&self, contract: &str, function: &str, args: &[Argument], url: &str) -> Result<CallPreview> {
+        let body = preview::request_body(args)?;
+        let path = self.paths.api(&format!("{}/{}/{}", preview::VIEW_PATH, contract, function));
+        let value: serde_json::Value = self.request_with_url(url, "POST", &path, Some(&body)).await?;
+        Ok(preview::interpret(contract, function, value))
+    }
+
+    /// GETs an arbitrary node path; the caller is responsible for vetting `path`.
+    pub async fn raw_query(&self, path: &str, url: &str) -> Result<serde_json::Value> {
+        self.request_with_url(url, "GET", path, None).await
+    }
+
     async fn request_with_url<T: serde::de::DeserializeOwned>(
         &self,
         base_url: &str,
@@ -235,24 +733,32 @@ impl BlockchainClient {
             ));
         }
 
-        let request = Request::new_with_init(&url, &init)
-            .map_err(|e| BlockchainError::HttpRequestWasm(e.to_string()))?;
+        let text = self.execute(&url, &init).await?;
+        serde_json::from_str(&text).map_err(|e| BlockchainError::InvalidResponse(e.to_string()))
+    }
+}
 
-        let mut response = Fetch::Request(request)
-            .send()
-            .await
-            .map_err(|e| BlockchainError::HttpRequestWasm(e.to_string()))?;
+/// Sends one request of `execute`'s fetch loop.
+struct FetchTransport<'a> {
+    client: &'a BlockchainClient,
+    init: &'a RequestInit,
+}
 
+impl NodeTransport for FetchTransport<'_> {
+    async fn send(&self, url: &str) -> Result<NodeReply> {
+        let request = Request::new_with_init(url, self.init)
+            .map_err(|e| BlockchainError::HttpRequestWasm(e.to_string()))?;
+        let mut response = self.client.send(url, request).await?;
         let status = response.status_code();
-        if !(200..300).contains(&status) {
-            return Err(BlockchainError::InvalidResponse(format!("HTTP {}", status)));
-        }
-
-        let text = response
+        let content_type = response.headers().get("Content-Type").ok().flatten().unwrap_or_default();
+        let body = response
             .text()
             .await
             .map_err(|e| BlockchainError::HttpRequestWasm(e.to_string()))?;
+        Ok(NodeReply { status, content_type, body })
+    }
 
-        serde_json::from_str(&text).map_err(|e| BlockchainError::InvalidResponse(e.to_string()))
+    async fn pause(&self, delay: Duration) {
+        Delay::from(delay).await;
     }
 }
@@ -0,0 +1,117 @@
+use serde::Serialize;
+use std::time::Duration;
+
+/// Clock differences up to this much are normal: entries are a few seconds old by the
+/// time they are read. Overridden by `MCP_CLOCK_SKEW_SECS`.
+pub const DEFAULT_CLOCK_SKEW_THRESHOLD: Duration = Duration::from_secs(30);
+
+/// Entry timestamps above this are milliseconds rather than seconds.
+const MILLIS_CUTOFF: u64 = 100_000_000_000;
+
+/// Unix seconds of an entry timestamp, which nodes send in seconds or milliseconds.
+pub fn entry_secs(timestamp: u64) -> u64 {
+    if timestamp > MILLIS_CUTOFF {
+        timestamp / 1000
+    } else {
+        timestamp
+    }
+}
+
+/// Unix milliseconds of an entry timestamp; second timestamps are scaled up.
+pub fn entry_millis(timestamp: u64) -> u64 {
+    if timestamp > MILLIS_CUTOFF {
+        timestamp
+    } else {
+        timestamp * 1000
+    }
+}
+
+/// How far the local clock is from the chain's, as seen on the latest entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ClockSkew {
+    /// Local time minus the entry's timestamp; positive when the local clock is ahead
+    pub skew_secs: i64,
+    /// The entry's timestamp in unix seconds
+    pub chain_time: u64,
+}
+
+impl ClockSkew {
+    pub fn measure(local_secs: u64, entry_timestamp: u64) -> Self {
+        let chain_time = entry_secs(entry_timestamp);
+        Self { skew_secs: local_secs as i64 - chain_time as i64, chain_time }
+    }
+
+    pub fn exceeds(&self, threshold: Duration) -> bool {
+        self.skew_secs.unsigned_abs() > threshold.as_secs()
+    }
+
+    /// Warning for outputs whose nonce depends on the clock, when the skew is past
+    /// `threshold`.
+    pub fn warning(&self, threshold: Duration) -> Option<String> {
+        self.exceeds(threshold).then(|| {
+            format!(
+                "the server clock is {}s {} the chain, so clock-based nonces are derived from the chain instead",
+                self.skew_secs.unsigned_abs(),
+                if self.skew_secs > 0 { "ahead of" } else { "behind" }
+            )
+        })
+    }
+}
+
+/// Nonce to use instead of the local clock while it is skewed: above the signer's latest
+/// nonce and no earlier than the chain's own time, in nanoseconds like clock nonces.
+pub fn chain_nonce(latest_nonce: Option<i128>, chain_time: u64) -> i128 {
+    let from_chain_time = chain_time as i128 * 1_000_000_000;
+    latest_nonce.map_or(from_chain_time, |latest| (latest + 1).max(from_chain_time))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u64 = 1_718_000_000;
+
+    #[test]
+    fn entry_timestamps_are_read_in_seconds_or_milliseconds() {
+        assert_eq!(entry_secs(NOW), NOW);
+        assert_eq!(entry_secs(NOW * 1000 + 999), NOW);
+        assert_eq!(entry_millis(NOW), NOW * 1000);
+        assert_eq!(entry_millis(NOW * 1000 + 5), NOW * 1000 + 5);
+    }
+
+    #[test]
+    fn skew_is_local_time_minus_chain_time() {
+        let ahead = ClockSkew::measure(NOW + 90, NOW * 1000);
+        assert_eq!(ahead, ClockSkew { skew_secs: 90, chain_time: NOW });
+        assert_eq!(ClockSkew::measure(NOW - 45, NOW).skew_secs, -45);
+    }
+
+    #[test]
+    fn only_skew_past_the_threshold_warns() {
+        let threshold = DEFAULT_CLOCK_SKEW_THRESHOLD;
+        for skew in [0, 30, -30] {
+            let measured = ClockSkew::measure((NOW as i64 + skew) as u64, NOW);
+            assert!(!measured.exceeds(threshold) && measured.warning(threshold).is_none(), "{}", skew);
+        }
+        assert_eq!(
+            ClockSkew::measure(NOW + 31, NOW).warning(threshold).as_deref(),
+            Some("the server clock is 31s ahead of the chain, so clock-based nonces are derived from the chain instead")
+        );
+        assert_eq!(
+            ClockSkew::measure(NOW - 3600, NOW).warning(threshold).as_deref(),
+            Some("the server clock is 3600s behind the chain, so clock-based nonces are derived from the chain instead")
+        );
+        // A configured threshold replaces the default
+        assert!(!ClockSkew::measure(NOW + 90, NOW).exceeds(Duration::from_secs(120)));
+    }
+
+    #[test]
+    fn chain_nonces_follow_the_signer_and_the_chain_time() {
+        let chain_time_nonce = NOW as i128 * 1_000_000_000;
+        assert_eq!(chain_nonce(None, NOW), chain_time_nonce);
+        // An older latest nonce is overtaken by the chain's time
+        assert_eq!(chain_nonce(Some(5), NOW), chain_time_nonce);
+        // A nonce from a signer whose clock ran ahead is stepped past
+        assert_eq!(chain_nonce(Some(chain_time_nonce + 10), NOW), chain_time_nonce + 11);
+    }
+}
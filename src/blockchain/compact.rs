@@ -0,0 +1,360 @@
+use super::block_txs::EntryTxsPage;
+use super::richlist::{AddressRank, RichlistWindow};
+use super::types::{BlockEntry, RichlistEntry, Transaction};
+use serde::{Deserialize, Serialize};
+
+/// Number of records kept when a long list is collapsed in compact mode.
+pub const COMPACT_LIST_ITEMS: usize = 10;
+
+const SHORT_HASH_LEN: usize = 10;
+
+/// Abbreviates a base58 hash or key to a prefix that is still useful for lookups by eye.
+pub fn short_hash(hash: &str) -> String {
+    match hash.char_indices().nth(SHORT_HASH_LEN) {
+        Some((idx, _)) => format!("{}…", &hash[..idx]),
+        None => hash.to_string(),
+    }
+}
+
+/// A long list collapsed into its total count and the first few items.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactList<T> {
+    pub count: usize,
+    pub items: Vec<T>,
+    #[serde(skip_serializing_if = "is_zero", default)]
+    pub omitted: usize,
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+impl<T> CompactList<T> {
+    pub fn new<I: IntoIterator<Item = T>>(items: I) -> Self {
+        let mut count = 0;
+        let mut kept = Vec::with_capacity(COMPACT_LIST_ITEMS);
+        for item in items {
+            if kept.len() < COMPACT_LIST_ITEMS {
+                kept.push(item);
+            }
+            count += 1;
+        }
+        Self {
+            count,
+            omitted: count - kept.len(),
+            items: kept,
+        }
+    }
+}
+
+/// Abbreviated transaction: who paid whom, how much, and when.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactTransaction {
+    pub hash: String,
+    pub from: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    /// Entry height the transaction was included at
+    pub height: u64,
+}
+
+impl From<&Transaction> for CompactTransaction {
+    fn from(tx: &Transaction) -> Self {
+        let action = &tx.tx.action;
+        let is_transfer = action.contract == "Coin" && action.function == "transfer";
+        let arg = |i: usize| {
+            if is_transfer {
+                action.args.get(i).cloned()
+            } else {
+                None
+            }
+        };
+
+        Self {
+            hash: short_hash(&tx.hash),
+            from: short_hash(&tx.tx.signer),
+            to: arg(0).map(|to| short_hash(&to)),
+            amount: arg(1),
+            symbol: arg(2),
+            height: tx.metadata.entry_height,
+        }
+    }
+}
+
+/// Abbreviated entry header.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactBlockEntry {
+    pub hash: String,
+    pub height: u64,
+    pub slot: u64,
+    pub signer: String,
+    pub tx_count: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finalized: Option<bool>,
+}
+
+impl From<&BlockEntry> for CompactBlockEntry {
+    fn from(entry: &BlockEntry) -> Self {
+        Self {
+            hash: short_hash(&entry.hash),
+            height: entry.header.height,
+            slot: entry.header.slot,
+            signer: short_hash(&entry.header.signer),
+            tx_count: entry.tx_count,
+            finalized: entry.consensus.as_ref().map(|c| c.finality_reached),
+        }
+    }
+}
+
+/// Abbreviated entry with its page of transactions, for get_block_with_txs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactEntryTxsPage {
+    #[serde(flatten)]
+    pub entry: CompactBlockEntry,
+    pub txs_total: usize,
+    pub tx_offset: usize,
+    pub txs: CompactList<CompactTransaction>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_tx_offset: Option<usize>,
+}
+
+impl From<&EntryTxsPage> for CompactEntryTxsPage {
+    fn from(page: &EntryTxsPage) -> Self {
+        Self {
+            entry: CompactBlockEntry::from(&page.entry),
+            txs_total: page.txs_total,
+            tx_offset: page.tx_offset,
+            txs: CompactList::new(page.txs.iter().map(CompactTransaction::from)),
+            next_tx_offset: page.next_tx_offset,
+        }
+    }
+}
+
+/// Abbreviated richlist holder: rank, address prefix and balance in AMA.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactHolder {
+    pub rank: usize,
+    pub address: String,
+    pub float: f64,
+}
+
+/// Abbreviated richlist page, for get_richlist.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactRichlist {
+    pub total: usize,
+    pub entries: CompactList<CompactHolder>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub around: Option<AddressRank>,
+}
+
+impl From<&RichlistWindow<RichlistEntry>> for CompactRichlist {
+    fn from(window: &RichlistWindow<RichlistEntry>) -> Self {
+        Self {
+            total: window.total,
+            entries: CompactList::new(window.entries.iter().map(|holder| CompactHolder {
+                rank: holder.rank,
+                address: short_hash(&holder.entry.address),
+                float: holder.entry.float,
+            })),
+            around: window.around.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::block_txs::{self, BlockEntryWithTxs};
+    use crate::blockchain::richlist::{self, WindowRequest};
+    use serde_json::{json, Value};
+
+    fn transfer_json() -> Value {
+        json!({
+            "hash": "2qo2mC7GvBBEPZTmZeYXsc5KhzboKSen6DekBytar1fy",
+            "metadata": { "entry_hash": "9fQxTz4dD3wS1kMoBvVY1SLDoXJ6YhZcQ2vD5rQm8qWz", "entry_height": 1250000 },
+            "signature": "3sig",
+            "result": { "error": "ok" },
+            "tx": {
+                "action": {
+                    "args": ["42KfUgyU2XHCPwDD6g9gryCvtj9vqJAvM6h3Yzs3VRbuyT2JCHwvuk5iAbzXrioerf", "1000000000", "AMA"],
+                    "function": "transfer",
+                    "op": "call",
+                    "contract": "Coin"
+                },
+                "nonce": 1718000000000000000u64,
+                "signer": "29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw"
+            },
+            "receipt": { "success": true, "result": "ok", "logs": [], "exec_used": "10000000" }
+        })
+    }
+
+    fn entry_json() -> Value {
+        json!({
+            "hash": "9fQxTz4dD3wS1kMoBvVY1SLDoXJ6YhZcQ2vD5rQm8qWz",
+            "header": {
+                "slot": 1250003,
+                "height": 1250000,
+                "dr": "dr",
+                "vr": "vr",
+                "prev_hash": "prev",
+                "signer": "6V2nBXn6iBhZpGzRLo4Vd7tTmX7dWhGhWBpJRuLJc1fRfgKXVgnWTFJpEHqYDeHVgE",
+                "root_tx": "rt",
+                "root_validator": "rv",
+                "prev_slot": 1250002,
+                "timestamp": 1718000000
+            },
+            "tx_count": 1,
+            "consensus": { "score": 0.9, "finality_reached": true, "mut_hash": "mh" }
+        })
+    }
+
+    fn transfer() -> Transaction {
+        serde_json::from_value(transfer_json()).unwrap()
+    }
+
+    fn entry() -> BlockEntry {
+        serde_json::from_value(entry_json()).unwrap()
+    }
+
+    #[test]
+    fn full_transaction_keeps_the_node_shape() {
+        assert_eq!(serde_json::to_value(transfer()).unwrap(), transfer_json());
+    }
+
+    #[test]
+    fn compact_transaction_shape() {
+        let compact = CompactTransaction::from(&transfer());
+        assert_eq!(
+            serde_json::to_value(compact).unwrap(),
+            json!({
+                "hash": "2qo2mC7GvB…",
+                "from": "29VitN55TT…",
+                "to": "42KfUgyU2X…",
+                "amount": "1000000000",
+                "symbol": "AMA",
+                "height": 1250000
+            })
+        );
+    }
+
+    #[test]
+    fn compact_contract_call_leaves_out_transfer_fields() {
+        let mut tx = transfer();
+        tx.tx.action.contract = "Epoch".to_string();
+        tx.tx.action.function = "submit_sol".to_string();
+        assert_eq!(
+            serde_json::to_value(CompactTransaction::from(&tx)).unwrap(),
+            json!({ "hash": "2qo2mC7GvB…", "from": "29VitN55TT…", "height": 1250000 })
+        );
+    }
+
+    #[test]
+    fn full_entry_keeps_the_node_shape() {
+        assert_eq!(serde_json::to_value(entry()).unwrap(), entry_json());
+    }
+
+    #[test]
+    fn compact_entry_shape() {
+        assert_eq!(
+            serde_json::to_value(CompactBlockEntry::from(&entry())).unwrap(),
+            json!({
+                "hash": "9fQxTz4dD3…",
+                "height": 1250000,
+                "slot": 1250003,
+                "signer": "6V2nBXn6iB…",
+                "tx_count": 1,
+                "finalized": true
+            })
+        );
+    }
+
+    #[test]
+    fn compact_list_collapses_past_the_limit() {
+        let list = CompactList::new(0..COMPACT_LIST_ITEMS + 2);
+        assert_eq!(
+            serde_json::to_value(list).unwrap(),
+            json!({ "count": 12, "items": [0, 1, 2, 3, 4, 5, 6, 7, 8, 9], "omitted": 2 })
+        );
+        assert_eq!(serde_json::to_value(CompactList::new([7])).unwrap(), json!({ "count": 1, "items": [7] }));
+    }
+
+    #[test]
+    fn short_hash_keeps_short_values() {
+        assert_eq!(short_hash("abc"), "abc");
+        assert_eq!(short_hash("0123456789"), "0123456789");
+        assert_eq!(short_hash("0123456789a"), "0123456789…");
+    }
+
+    #[test]
+    fn compact_entry_txs_page_shape() {
+        let entries = vec![BlockEntryWithTxs { entry: entry(), txs: vec![transfer(), transfer()] }];
+        let pages = block_txs::page(entries, 0, 1);
+        let full = serde_json::to_value(&pages).unwrap();
+        assert_eq!(full[0]["txs_total"], 2);
+        assert_eq!(full[0]["next_tx_offset"], 1);
+        assert_eq!(full[0]["txs"][0], transfer_json());
+        assert_eq!(full[0]["header"], entry_json()["header"]);
+
+        assert_eq!(
+            serde_json::to_value(CompactEntryTxsPage::from(&pages[0])).unwrap(),
+            json!({
+                "hash": "9fQxTz4dD3…",
+                "height": 1250000,
+                "slot": 1250003,
+                "signer": "6V2nBXn6iB…",
+                "tx_count": 1,
+                "finalized": true,
+                "txs_total": 2,
+                "tx_offset": 0,
+                "txs": {
+                    "count": 1,
+                    "items": [{
+                        "hash": "2qo2mC7GvB…",
+                        "from": "29VitN55TT…",
+                        "to": "42KfUgyU2X…",
+                        "amount": "1000000000",
+                        "symbol": "AMA",
+                        "height": 1250000
+                    }]
+                },
+                "next_tx_offset": 1
+            })
+        );
+    }
+
+    #[test]
+    fn compact_richlist_shape() {
+        let holders = vec![
+            RichlistEntry { address: "29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw".to_string(), flat: 3_000_000_000, float: 3.0 },
+            RichlistEntry { address: "42KfUgyU2XHCPwDD6g9gryCvtj9vqJAvM6h3Yzs3VRbuyT2JCHwvuk5iAbzXrioerf".to_string(), flat: 1_500_000_000, float: 1.5 },
+        ];
+        let window = richlist::window(&holders, |h| h.address.as_str(), |h| h.float, &WindowRequest::default());
+        assert_eq!(
+            serde_json::to_value(&window).unwrap(),
+            json!({
+                "total": 2,
+                "entries": [
+                    { "rank": 1, "address": holders[0].address, "flat": 3_000_000_000u64, "float": 3.0 },
+                    { "rank": 2, "address": holders[1].address, "flat": 1_500_000_000u64, "float": 1.5 }
+                ]
+            })
+        );
+        assert_eq!(
+            serde_json::to_value(CompactRichlist::from(&window)).unwrap(),
+            json!({
+                "total": 2,
+                "entries": {
+                    "count": 2,
+                    "items": [
+                        { "rank": 1, "address": "29VitN55TT…", "float": 3.0 },
+                        { "rank": 2, "address": "42KfUgyU2X…", "float": 1.5 }
+                    ]
+                }
+            })
+        );
+    }
+}
@@ -0,0 +1,102 @@
+use super::decimals::DecimalsRegistry;
+use crate::locale::Locale;
+use crate::wasm::tx::Tx;
+use std::time::Duration;
+
+/// How long a submit waits for the user to answer the confirmation prompt.
+pub const ELICIT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// A call argument as the user would recognise it: text when it is printable, base58 otherwise
+/// (public keys and other raw bytes).
+fn render_arg(arg: &[u8]) -> String {
+    match std::str::from_utf8(arg) {
+        Ok(text) if !text.is_empty() && text.chars().all(|c| !c.is_control()) => text.to_string(),
+        _ => bs58::encode(arg).into_string(),
+    }
+}
+
+/// `atoms` of `symbol` with its decimals applied, falling back to the raw atom count.
+fn render_amount(decimals: &DecimalsRegistry, locale: Locale, atoms: &str, symbol: &str) -> String {
+    atoms
+        .parse::<u128>()
+        .ok()
+        .zip(decimals.get(symbol))
+        .map(|(atoms, places)| locale.format_amount(atoms, places, symbol))
+        .unwrap_or_else(|| format!("{} atoms of {}", atoms, symbol))
+}
+
+/// Human-readable summary of the unsigned blob about to be submitted, shown in the
+/// confirmation prompt, with amounts in the display locale. Fails when the blob does not decode.
+pub fn summarize(
+    blob_b58: &str,
+    network: Option<&str>,
+    decimals: &DecimalsRegistry,
+    locale: Locale,
+) -> Result<String, &'static str> {
+    let bytes = bs58::decode(blob_b58).into_vec().map_err(|_| "invalid blob base58")?;
+    let tx: Tx = vecpak::from_slice(&bytes).map_err(|_| "failed to decode tx")?;
+    let action = &tx.action;
+    let args: Vec<String> = action.args.iter().map(|a| render_arg(a)).collect();
+
+    let mut lines = vec![format!("Submit this transaction to {}?", network.unwrap_or("mainnet"))];
+    lines.push(format!("Signer: {}", bs58::encode(&tx.signer).into_string()));
+    match (action.contract.as_str(), action.function.as_str(), args.as_slice()) {
+        ("Coin", "transfer", [to, amount, rest @ ..]) => {
+            let symbol = rest.first().map_or("AMA", String::as_str);
+            lines.push(format!("Transfer {} to {}", render_amount(decimals, locale, amount, symbol), to));
+        }
+        _ => lines.push(format!("Call {}.{}({})", action.contract, action.function, args.join(", "))),
+    }
+    if let (Some(symbol), Some(amount)) = (&action.attached_symbol, &action.attached_amount) {
+        let symbol = render_arg(symbol);
+        lines.push(format!("Attached: {}", render_amount(decimals, locale, &render_arg(amount), &symbol)));
+    }
+    lines.push(format!("Nonce: {}", tx.nonce));
+    Ok(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::{BlockchainClient, TransactionRequest};
+    use serde_json::json;
+
+    const SIGNER: &str = "29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw";
+    const RECEIVER: &str = "42KfUgyU2XHCPwDD6g9gryCvtj9vqJAvM6h3Yzs3VRbuyT2JCHwvuk5iAbzXrioerf";
+
+    async fn blob(args: serde_json::Value, function: &str) -> String {
+        let request: TransactionRequest = serde_json::from_value(json!({
+            "signer": SIGNER,
+            "contract": "Coin",
+            "function": function,
+            "args": args,
+            "nonce": 42
+        }))
+        .unwrap();
+        BlockchainClient::new(String::new()).unwrap().create_transaction_blob(request).await.unwrap().blob
+    }
+
+    #[tokio::test]
+    async fn transfers_are_summarized_in_the_display_locale() {
+        let blob = blob(json!([{ "b58": RECEIVER }, "1234500000000", "AMA"]), "transfer").await;
+        let decimals = DecimalsRegistry::default();
+        assert_eq!(
+            summarize(&blob, None, &decimals, Locale::En).unwrap(),
+            format!("Submit this transaction to mainnet?\nSigner: {}\nTransfer 1,234.5 AMA to {}\nNonce: 42", SIGNER, RECEIVER)
+        );
+        let de = summarize(&blob, Some("testnet"), &decimals, Locale::De).unwrap();
+        assert!(de.starts_with("Submit this transaction to testnet?"), "{}", de);
+        assert!(de.contains(&format!("Transfer 1.234,5 AMA to {}", RECEIVER)), "{}", de);
+    }
+
+    #[tokio::test]
+    async fn unknown_symbols_and_other_calls_are_shown_raw() {
+        let decimals = DecimalsRegistry::default();
+        let unknown = blob(json!([{ "b58": RECEIVER }, "1234500", "NOPE"]), "transfer").await;
+        assert!(summarize(&unknown, None, &decimals, Locale::De).unwrap().contains("Transfer 1234500 atoms of NOPE"));
+
+        let call = blob(json!(["a", "b"]), "burn").await;
+        assert!(summarize(&call, None, &decimals, Locale::En).unwrap().contains("Call Coin.burn(a, b)"));
+        assert_eq!(summarize("0OIl", None, &decimals, Locale::En).unwrap_err(), "invalid blob base58");
+    }
+}
@@ -0,0 +1,39 @@
+use serde::Serialize;
+
+/// Tip heights read before and after a multi-call aggregation. The node cannot pin reads to
+/// a height, so when the chain moved in between the sub-results may describe different
+/// heights and the answer is flagged instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Consistency {
+    pub start_height: u64,
+    pub end_height: u64,
+    /// True when no entry was added while the aggregation ran
+    pub consistent: bool,
+    /// Entries added while the aggregation ran
+    pub drift: u64,
+}
+
+impl Consistency {
+    pub fn between(start_height: u64, end_height: u64) -> Self {
+        let drift = end_height.saturating_sub(start_height);
+        Self {
+            start_height,
+            end_height,
+            consistent: drift == 0,
+            drift,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drift_is_the_entries_added_between_the_reads() {
+        assert_eq!(Consistency::between(100, 100), Consistency { start_height: 100, end_height: 100, consistent: true, drift: 0 });
+        assert_eq!(Consistency::between(100, 103), Consistency { start_height: 100, end_height: 103, consistent: false, drift: 3 });
+        // A tip that went backwards (a lagging node behind a balancer) adds no entries
+        assert!(Consistency::between(100, 99).consistent);
+    }
+}
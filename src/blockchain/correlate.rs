@@ -0,0 +1,191 @@
+use super::types::{BlockEntry, PeerNode, ValidatorInfo};
+use serde::Serialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PeerStatus {
+    /// Exactly one peer carries the validator's key and it is online
+    Connected,
+    /// Exactly one peer carries the key but reports itself offline
+    Offline,
+    /// No peer carries the key
+    NotConnected,
+    /// Several peers carry the key; which one produces cannot be told apart
+    Duplicate,
+    /// The peer list has no keys at all, so nothing can be matched
+    Unavailable,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidatorNode {
+    pub validator: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rank: Option<u64>,
+    pub status: PeerStatus,
+    /// Peers carrying the validator's key; more than one only for `duplicate`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub peers: Vec<PeerNode>,
+    /// Version of the matched peer, when exactly one matched
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Height of the validator's latest entry in the window, `None` when it signed none
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_signed_height: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Correlation {
+    pub from_height: u64,
+    pub to_height: u64,
+    /// Whether the peer list exposes public keys; without them every status is `unavailable`
+    pub peer_keys_available: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    pub validators: Vec<ValidatorNode>,
+}
+
+/// Joins the trainer set with the peer list by public key and with the window's entries
+/// by signer.
+pub fn correlate(
+    trainers: &[ValidatorInfo], peers: &[PeerNode], entries: &[BlockEntry], from_height: u64, to_height: u64,
+) -> Correlation {
+    let mut last_signed: HashMap<&str, u64> = HashMap::new();
+    for entry in entries {
+        let height = last_signed.entry(entry.header.signer.as_str()).or_default();
+        *height = (*height).max(entry.header.height);
+    }
+    let peer_keys_available = peers.iter().any(|p| p.pk.as_deref().is_some_and(|pk| !pk.is_empty()));
+
+    let validators = trainers
+        .iter()
+        .map(|trainer| {
+            let matched: Vec<PeerNode> =
+                peers.iter().filter(|p| p.pk.as_deref() == Some(trainer.pk.as_str())).cloned().collect();
+            let status = match matched.as_slice() {
+                _ if !peer_keys_available => PeerStatus::Unavailable,
+                [] => PeerStatus::NotConnected,
+                [peer] if peer.online == Some(false) => PeerStatus::Offline,
+                [_] => PeerStatus::Connected,
+                _ => PeerStatus::Duplicate,
+            };
+            ValidatorNode {
+                validator: trainer.pk.clone(),
+                rank: trainer.rank,
+                status,
+                version: match matched.as_slice() {
+                    [peer] => peer.version.clone(),
+                    _ => None,
+                },
+                peers: matched,
+                last_signed_height: last_signed.get(trainer.pk.as_str()).copied(),
+            }
+        })
+        .collect();
+
+    Correlation {
+        from_height,
+        to_height,
+        peer_keys_available,
+        note: (!peer_keys_available)
+            .then(|| "the node's peer list does not expose public keys, so validators cannot be matched to peers".to_string()),
+        validators,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn entry(height: u64, signer: &str) -> BlockEntry {
+        serde_json::from_value(json!({
+            "hash": format!("entry-{}", height),
+            "header": {
+                "slot": height, "height": height, "dr": "dr", "vr": "vr", "prev_hash": "prev", "signer": signer,
+                "root_tx": "rtx", "root_validator": "rv", "prev_slot": height
+            },
+            "tx_count": 0
+        }))
+        .unwrap()
+    }
+
+    fn trainer(pk: &str, rank: u64) -> ValidatorInfo {
+        ValidatorInfo { pk: pk.into(), score: None, rank: Some(rank), epoch: None, emission_address: None }
+    }
+
+    fn peer(pk: Option<&str>, ip4: &str, version: &str, online: Option<bool>) -> PeerNode {
+        PeerNode { pk: pk.map(Into::into), ip4: Some(ip4.into()), version: Some(version.into()), online, ..PeerNode::default() }
+    }
+
+    fn trainers() -> Vec<ValidatorInfo> {
+        vec![trainer("A", 1), trainer("B", 2), trainer("C", 3), trainer("D", 4)]
+    }
+
+    fn node<'a>(correlation: &'a Correlation, validator: &str) -> &'a ValidatorNode {
+        correlation.validators.iter().find(|v| v.validator == validator).unwrap()
+    }
+
+    #[test]
+    fn validators_are_matched_to_their_peer_and_last_entry() {
+        let peers = vec![
+            peer(Some("A"), "10.0.0.1", "1.2.0", Some(true)),
+            peer(Some("B"), "10.0.0.2", "1.1.9", Some(false)),
+            // A peer that is not a validator is simply not reported
+            peer(Some("Z"), "10.0.0.9", "1.2.0", Some(true)),
+        ];
+        let entries: Vec<BlockEntry> = [(100, "A"), (101, "B"), (102, "A"), (103, "X")].iter().map(|(h, s)| entry(*h, s)).collect();
+        let correlation = correlate(&trainers(), &peers, &entries, 100, 103);
+
+        assert!(correlation.peer_keys_available && correlation.note.is_none());
+        let a = node(&correlation, "A");
+        assert_eq!((a.status, a.version.as_deref(), a.last_signed_height), (PeerStatus::Connected, Some("1.2.0"), Some(102)));
+        assert_eq!(a.peers[0].ip4.as_deref(), Some("10.0.0.1"));
+        let b = node(&correlation, "B");
+        assert_eq!((b.status, b.version.as_deref(), b.last_signed_height), (PeerStatus::Offline, Some("1.1.9"), Some(101)));
+        // Trainer order and ranks are kept
+        let order: Vec<(&str, Option<u64>)> = correlation.validators.iter().map(|v| (v.validator.as_str(), v.rank)).collect();
+        assert_eq!(order, vec![("A", Some(1)), ("B", Some(2)), ("C", Some(3)), ("D", Some(4))]);
+    }
+
+    #[test]
+    fn validators_without_a_peer_are_not_connected() {
+        let peers = vec![peer(Some("A"), "10.0.0.1", "1.2.0", Some(true)), peer(None, "10.0.0.5", "1.2.0", None)];
+        let correlation = correlate(&trainers(), &peers, &[entry(100, "C")], 100, 100);
+        let c = node(&correlation, "C");
+        assert_eq!((c.status, c.version.as_deref(), c.last_signed_height), (PeerStatus::NotConnected, None, Some(100)));
+        assert!(c.peers.is_empty());
+        let d = node(&correlation, "D");
+        assert_eq!((d.status, d.last_signed_height), (PeerStatus::NotConnected, None));
+        assert_eq!(
+            serde_json::to_value(d).unwrap(),
+            json!({ "validator": "D", "rank": 4, "status": "not_connected" })
+        );
+    }
+
+    #[test]
+    fn a_key_on_several_peers_is_a_duplicate_without_a_version() {
+        let peers = vec![
+            peer(Some("A"), "10.0.0.1", "1.2.0", Some(true)),
+            peer(Some("A"), "10.0.0.7", "1.1.0", Some(true)),
+            peer(Some("B"), "10.0.0.2", "1.2.0", Some(true)),
+        ];
+        let correlation = correlate(&trainers(), &peers, &[], 90, 100);
+        let a = node(&correlation, "A");
+        assert_eq!((a.status, a.version.as_deref()), (PeerStatus::Duplicate, None));
+        let ips: Vec<_> = a.peers.iter().map(|p| p.ip4.as_deref().unwrap()).collect();
+        assert_eq!(ips, vec!["10.0.0.1", "10.0.0.7"]);
+        assert_eq!(node(&correlation, "B").status, PeerStatus::Connected);
+    }
+
+    #[test]
+    fn a_peer_list_without_keys_makes_every_match_unavailable() {
+        let peers = vec![peer(None, "10.0.0.1", "1.2.0", Some(true)), peer(Some(""), "10.0.0.2", "1.2.0", Some(true))];
+        let correlation = correlate(&trainers(), &peers, &[entry(100, "A")], 100, 100);
+        assert!(!correlation.peer_keys_available);
+        assert!(correlation.note.as_deref().unwrap().contains("does not expose public keys"));
+        assert!(correlation.validators.iter().all(|v| v.status == PeerStatus::Unavailable && v.peers.is_empty()));
+        // Signing data does not depend on the peer list
+        assert_eq!(node(&correlation, "A").last_signed_height, Some(100));
+    }
+}
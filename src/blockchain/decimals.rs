@@ -0,0 +1,157 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// Decimals of the native token: 1 AMA is 10^9 atoms.
+pub const AMA_DECIMALS: u32 = 9;
+
+/// Symbols whose decimals are known without asking the chain.
+const BUILTIN_DECIMALS: &[(&str, u32)] = &[("AMA", AMA_DECIMALS)];
+
+/// Largest decimals value accepted from any source; 10^38 is the most an i128 atom count holds.
+pub const MAX_DECIMALS: u32 = 38;
+
+/// Per-symbol decimals. Built-in defaults are replaced by `SYMBOL_DECIMALS` overrides,
+/// and on-chain token metadata replaces both when it is available.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecimalsRegistry {
+    decimals: BTreeMap<String, u32>,
+}
+
+/// Two sources disagreeing about a symbol's decimals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecimalsConflict {
+    pub symbol: String,
+    pub configured: u32,
+    pub onchain: u32,
+}
+
+impl Default for DecimalsRegistry {
+    fn default() -> Self {
+        Self {
+            decimals: BUILTIN_DECIMALS.iter().map(|(s, d)| (s.to_string(), *d)).collect(),
+        }
+    }
+}
+
+impl DecimalsRegistry {
+    /// Built-in defaults with `overrides` applied on top.
+    pub fn with_overrides(overrides: &BTreeMap<String, u32>) -> Self {
+        let mut registry = Self::default();
+        registry.decimals.extend(overrides.iter().map(|(s, d)| (s.to_ascii_uppercase(), *d)));
+        registry
+    }
+
+    /// Records decimals read from on-chain token metadata, which win over configured values.
+    /// Returns the conflict when a configured value disagreed, for the caller to log.
+    pub fn merge_onchain(&mut self, symbol: &str, decimals: u32) -> Option<DecimalsConflict> {
+        let symbol = symbol.to_ascii_uppercase();
+        let previous = self.decimals.insert(symbol.clone(), decimals);
+        previous.filter(|p| *p != decimals).map(|configured| DecimalsConflict {
+            symbol,
+            configured,
+            onchain: decimals,
+        })
+    }
+
+    pub fn get(&self, symbol: &str) -> Option<u32> {
+        self.decimals.get(&symbol.to_ascii_uppercase()).copied()
+    }
+
+    /// Atom count for `whole` units of `symbol`, `None` for unknown symbols or overflow.
+    pub fn to_atoms(&self, symbol: &str, whole: u64) -> Option<i128> {
+        10i128.checked_pow(self.get(symbol)?)?.checked_mul(whole as i128)
+    }
+}
+
+impl FromStr for DecimalsRegistry {
+    type Err = String;
+
+    /// Parses `SYMBOL=DECIMALS` pairs separated by commas, e.g. `AMA=9,USDX=6`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let overrides = s
+            .split(',')
+            .map(str::trim)
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (symbol, decimals) = pair
+                    .split_once('=')
+                    .ok_or_else(|| format!("expected SYMBOL=DECIMALS, got {}", pair))?;
+                let decimals = decimals
+                    .trim()
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|d| *d <= MAX_DECIMALS)
+                    .ok_or_else(|| format!("invalid decimals for {}: {}", symbol.trim(), decimals.trim()))?;
+                Ok((symbol.trim().to_string(), decimals))
+            })
+            .collect::<Result<BTreeMap<_, _>, String>>()?;
+        Ok(Self::with_overrides(&overrides))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn overrides(pairs: &[(&str, u32)]) -> BTreeMap<String, u32> {
+        pairs.iter().map(|(s, d)| (s.to_string(), *d)).collect()
+    }
+
+    #[test]
+    fn builtins_apply_without_configuration() {
+        let registry = DecimalsRegistry::default();
+        assert_eq!(registry.get("AMA"), Some(AMA_DECIMALS));
+        assert_eq!(registry.get("ama"), Some(AMA_DECIMALS));
+        assert_eq!(registry.get("USDX"), None);
+    }
+
+    #[test]
+    fn overrides_replace_builtins_and_add_symbols() {
+        let registry = DecimalsRegistry::with_overrides(&overrides(&[("ama", 6), ("usdx", 2)]));
+        assert_eq!(registry.get("AMA"), Some(6));
+        assert_eq!(registry.get("USDX"), Some(2));
+    }
+
+    #[test]
+    fn onchain_metadata_wins_over_configured_values_and_reports_conflicts() {
+        let mut registry = DecimalsRegistry::with_overrides(&overrides(&[("USDX", 6)]));
+        assert_eq!(
+            registry.merge_onchain("usdx", 8),
+            Some(DecimalsConflict { symbol: "USDX".into(), configured: 6, onchain: 8 })
+        );
+        assert_eq!(registry.get("USDX"), Some(8));
+
+        // Agreement, or a symbol nothing configured, is not a conflict
+        assert_eq!(registry.merge_onchain("AMA", AMA_DECIMALS), None);
+        assert_eq!(registry.merge_onchain("NEW", 4), None);
+        assert_eq!(registry.get("NEW"), Some(4));
+
+        // On-chain metadata also wins over a built-in
+        assert!(registry.merge_onchain("AMA", 12).is_some());
+        assert_eq!(registry.get("AMA"), Some(12));
+    }
+
+    #[test]
+    fn parses_the_override_setting() {
+        let registry: DecimalsRegistry = " usdx=6 , AMA = 9,".parse().unwrap();
+        assert_eq!(registry.get("USDX"), Some(6));
+        assert_eq!(registry.get("AMA"), Some(9));
+        assert_eq!("".parse::<DecimalsRegistry>().unwrap(), DecimalsRegistry::default());
+
+        for bad in ["USDX", "USDX=six", "USDX=-1", "USDX=39"] {
+            assert!(bad.parse::<DecimalsRegistry>().is_err(), "{}", bad);
+        }
+        assert!("USDX=38".parse::<DecimalsRegistry>().is_ok());
+    }
+
+    #[test]
+    fn atoms_follow_the_registry() {
+        let registry = DecimalsRegistry::with_overrides(&overrides(&[("USDX", 2), ("HUGE", MAX_DECIMALS)]));
+        // The faucet's 100 AMA is 10^11 atoms
+        assert_eq!(registry.to_atoms("AMA", 100), Some(100_000_000_000));
+        assert_eq!(registry.to_atoms("USDX", 5), Some(500));
+        assert_eq!(registry.to_atoms("NONE", 1), None);
+        assert_eq!(registry.to_atoms("HUGE", 1), Some(10i128.pow(MAX_DECIMALS)));
+        assert_eq!(registry.to_atoms("HUGE", u64::MAX), None);
+    }
+}
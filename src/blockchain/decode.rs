@@ -0,0 +1,177 @@
+//! Unpacking a vecpak transaction blob without a node, to check what a blob does before
+//! signing or submitting it.
+
+use crate::wasm::tx::{self, Tx};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlobKind {
+    /// A `Tx` as create_transaction returns it
+    Unsigned,
+    /// A `TxU` carrying the hash and signature, as submit_transaction sends it
+    Signed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ByteEncoding {
+    Utf8,
+    Hex,
+}
+
+/// Raw bytes, as text when they are printable UTF-8 and hex otherwise.
+#[derive(Debug, Clone, Serialize)]
+pub struct BlobBytes {
+    pub encoding: ByteEncoding,
+    pub value: String,
+}
+
+impl BlobBytes {
+    pub fn new(bytes: &[u8]) -> Self {
+        match std::str::from_utf8(bytes) {
+            Ok(text) if !text.chars().any(|c| c.is_control() && !c.is_whitespace()) => {
+                Self { encoding: ByteEncoding::Utf8, value: text.to_string() }
+            }
+            _ => Self { encoding: ByteEncoding::Hex, value: hex::encode(bytes) },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DecodedTransaction {
+    pub kind: BlobKind,
+    pub signer: String,
+    /// A string because it does not fit a JSON number exactly
+    pub nonce: String,
+    pub op: String,
+    pub contract: String,
+    pub function: String,
+    pub args: Vec<BlobBytes>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attached_symbol: Option<BlobBytes>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attached_amount: Option<BlobBytes>,
+    /// Hex SHA-256 of the unsigned transaction, the payload a signer signs
+    pub signing_hash: String,
+    /// Base58 of the same hash, as create_transaction and the node report it
+    pub transaction_hash: String,
+    /// Base58 signature of a signed blob
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// For a signed blob, whether the hash it carries is the one computed here
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash_matches: Option<bool>,
+}
+
+impl DecodedTransaction {
+    fn new(kind: BlobKind, tx: &Tx, hash: [u8; 32]) -> Self {
+        let action = &tx.action;
+        Self {
+            kind,
+            signer: bs58::encode(&tx.signer).into_string(),
+            nonce: tx.nonce.to_string(),
+            op: action.op.clone(),
+            contract: action.contract.clone(),
+            function: action.function.clone(),
+            args: action.args.iter().map(|arg| BlobBytes::new(arg)).collect(),
+            attached_symbol: action.attached_symbol.as_deref().map(BlobBytes::new),
+            attached_amount: action.attached_amount.as_deref().map(BlobBytes::new),
+            signing_hash: hex::encode(hash),
+            transaction_hash: bs58::encode(hash).into_string(),
+            signature: None,
+            hash_matches: None,
+        }
+    }
+}
+
+/// Decodes a base58 blob as an unsigned `Tx`, or failing that as a signed `TxU`.
+pub fn decode_blob(blob_b58: &str) -> Result<DecodedTransaction, String> {
+    let bytes = bs58::decode(blob_b58.trim())
+        .into_vec()
+        .map_err(|e| format!("blob is not valid base58: {}", e))?;
+
+    if let Ok(tx) = vecpak::from_slice::<Tx>(&bytes) {
+        return Ok(DecodedTransaction::new(BlobKind::Unsigned, &tx, Sha256::digest(&bytes).into()));
+    }
+    let signed = tx::decode_signed(&bytes)
+        .map_err(|_| "blob is neither an unsigned (Tx) nor a signed (TxU) transaction".to_string())?;
+    let unsigned = vecpak::to_vec(&signed.tx).map_err(|_| "failed to re-encode the signed transaction".to_string())?;
+    let hash: [u8; 32] = Sha256::digest(&unsigned).into();
+    Ok(DecodedTransaction {
+        signature: Some(bs58::encode(&signed.signature).into_string()),
+        hash_matches: Some(signed.hash == hash),
+        ..DecodedTransaction::new(BlobKind::Signed, &signed.tx, hash)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unsigned() -> tx::UnsignedTx {
+        let args = [vec![2u8; 48], b"1000000000".to_vec(), b"AMA".to_vec()];
+        tx::build_unsigned(&[1u8; 48], "Coin", "transfer", &args, None, None, Some(42)).unwrap()
+    }
+
+    #[test]
+    fn an_unsigned_blob_decodes_with_its_signing_hash() {
+        let built = unsigned();
+        let decoded = decode_blob(&bs58::encode(&built.tx_blob).into_string()).unwrap();
+        assert_eq!(decoded.kind, BlobKind::Unsigned);
+        assert_eq!(decoded.signer, bs58::encode([1u8; 48]).into_string());
+        assert_eq!((decoded.nonce.as_str(), decoded.contract.as_str(), decoded.function.as_str()), ("42", "Coin", "transfer"));
+        assert_eq!(decoded.signing_hash, hex::encode(built.signing_hash));
+        assert_eq!(decoded.transaction_hash, bs58::encode(built.signing_hash).into_string());
+        assert_eq!((decoded.signature, decoded.hash_matches), (None, None));
+
+        // The receiver key is binary, so it comes back as hex
+        let receiver = hex::encode([2u8; 48]);
+        let args: Vec<_> = decoded.args.iter().map(|a| (a.encoding, a.value.as_str())).collect();
+        assert_eq!(
+            args,
+            [(ByteEncoding::Hex, receiver.as_str()), (ByteEncoding::Utf8, "1000000000"), (ByteEncoding::Utf8, "AMA")]
+        );
+    }
+
+    #[test]
+    fn a_signed_blob_decodes_with_its_signature() {
+        let built = unsigned();
+        let signature = bs58::encode([7u8; 96]).into_string();
+        let signed = tx::finalize_transaction(&bs58::encode(&built.tx_blob).into_string(), &signature).unwrap();
+        let decoded = decode_blob(&bs58::encode(&signed.packed).into_string()).unwrap();
+        assert_eq!(decoded.kind, BlobKind::Signed);
+        assert_eq!(decoded.signature.as_deref(), Some(signature.as_str()));
+        assert_eq!(decoded.hash_matches, Some(true));
+        assert_eq!(decoded.signing_hash, hex::encode(built.signing_hash));
+    }
+
+    #[test]
+    fn a_signed_blob_with_a_foreign_hash_is_flagged() {
+        let built = unsigned();
+        let tx = vecpak::from_slice::<Tx>(&built.tx_blob).unwrap();
+        let forged = tx::TxU { hash: vec![0; 32], signature: vec![7; 96], tx };
+        let decoded = decode_blob(&bs58::encode(vecpak::to_vec(&forged).unwrap()).into_string()).unwrap();
+        assert_eq!(decoded.hash_matches, Some(false));
+    }
+
+    #[test]
+    fn malformed_blobs_say_why() {
+        assert!(decode_blob("0OIl").unwrap_err().starts_with("blob is not valid base58"));
+        assert_eq!(
+            decode_blob(&bs58::encode([0xffu8; 40]).into_string()).unwrap_err(),
+            "blob is neither an unsigned (Tx) nor a signed (TxU) transaction"
+        );
+    }
+
+    #[test]
+    fn bytes_are_text_only_when_printable() {
+        let text = BlobBytes::new(b"AMA 1.5\n");
+        assert_eq!((text.encoding, text.value.as_str()), (ByteEncoding::Utf8, "AMA 1.5\n"));
+        let control = BlobBytes::new(&[b'A', 0x01]);
+        assert_eq!((control.encoding, control.value.as_str()), (ByteEncoding::Hex, "4101"));
+        let binary = BlobBytes::new(&[0xff, 0xfe]);
+        assert_eq!((binary.encoding, binary.value.as_str()), (ByteEncoding::Hex, "fffe"));
+    }
+}
@@ -0,0 +1,231 @@
+use sha2::{Digest, Sha256};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::SystemTime,
+};
+use tracing::warn;
+
+/// Node responses for finalized data, kept on disk across restarts (`MCP_DISK_CACHE_DIR`).
+/// One JSON file per request, named by the sha256 of method, node URL and path. Reads
+/// bump the file's modification time, and the least recently used files are evicted once
+/// the directory grows past its cap.
+pub struct DiskCache {
+    dir: PathBuf,
+    max_bytes: u64,
+    /// Bytes currently stored; only the writer that pushes it over the cap evicts
+    size: Mutex<u64>,
+}
+
+impl DiskCache {
+    pub fn open(dir: impl Into<PathBuf>, max_bytes: u64) -> std::io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        let size = entries(&dir).iter().map(|(_, len, _)| len).sum();
+        Ok(Self { dir, max_bytes, size: Mutex::new(size) })
+    }
+
+    fn file(&self, method: &str, url: &str, path: &str) -> PathBuf {
+        let key = Sha256::digest(format!("{} {}{}", method, url.trim_end_matches('/'), path));
+        self.dir.join(format!("{}.json", hex::encode(key)))
+    }
+
+    /// The stored response, if any. A file that no longer parses is deleted so the
+    /// response is fetched again.
+    pub fn get(&self, method: &str, url: &str, path: &str) -> Option<serde_json::Value> {
+        let file = self.file(method, url, path);
+        let bytes = fs::read(&file).ok()?;
+        match serde_json::from_slice(&bytes) {
+            Ok(value) => {
+                let _ = fs::File::options()
+                    .write(true)
+                    .open(&file)
+                    .and_then(|f| f.set_modified(SystemTime::now()));
+                Some(value)
+            }
+            Err(e) => {
+                warn!(file = %file.display(), error = %e, "dropping corrupt disk cache entry");
+                if fs::remove_file(&file).is_ok() {
+                    self.shrink(bytes.len() as u64);
+                }
+                None
+            }
+        }
+    }
+
+    /// Stores a response. Only call this for data that can no longer change.
+    pub fn put(&self, method: &str, url: &str, path: &str, value: &serde_json::Value) {
+        let file = self.file(method, url, path);
+        let Ok(bytes) = serde_json::to_vec(value) else { return };
+        let previous = fs::metadata(&file).map(|m| m.len()).unwrap_or(0);
+        // Written aside and renamed so a crash never leaves a half-written entry behind
+        let partial = file.with_extension("partial");
+        if let Err(e) = fs::write(&partial, &bytes).and_then(|_| fs::rename(&partial, &file)) {
+            warn!(file = %file.display(), error = %e, "failed to write disk cache entry");
+            let _ = fs::remove_file(&partial);
+            return;
+        }
+
+        let mut size = self.size.lock().unwrap_or_else(|e| e.into_inner());
+        *size = size.saturating_sub(previous) + bytes.len() as u64;
+        if *size > self.max_bytes {
+            *size = evict(&self.dir, self.max_bytes);
+        }
+    }
+
+    fn shrink(&self, bytes: u64) {
+        let mut size = self.size.lock().unwrap_or_else(|e| e.into_inner());
+        *size = size.saturating_sub(bytes);
+    }
+}
+
+/// Cache files with their size and last access, as modification time.
+fn entries(dir: &Path) -> Vec<(PathBuf, u64, SystemTime)> {
+    let Ok(read) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    read.filter_map(|entry| {
+        let entry = entry.ok()?;
+        let path = entry.path();
+        if path.extension()? != "json" {
+            return None;
+        }
+        let metadata = entry.metadata().ok()?;
+        Some((path, metadata.len(), metadata.modified().ok()?))
+    })
+    .collect()
+}
+
+/// Deletes least recently used files until the directory holds at most `max_bytes`, and
+/// returns what is left.
+fn evict(dir: &Path, max_bytes: u64) -> u64 {
+    let mut files = entries(dir);
+    files.sort_by_key(|(_, _, accessed)| *accessed);
+    let mut size: u64 = files.iter().map(|(_, len, _)| len).sum();
+    for (path, len, _) in files {
+        if size <= max_bytes {
+            break;
+        }
+        if fs::remove_file(&path).is_ok() {
+            size -= len;
+        }
+    }
+    size
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::time::Duration;
+
+    const NODE: &str = "https://nodes.amadeus.bot";
+
+    /// A cache directory removed when the test ends.
+    struct Scratch(PathBuf);
+
+    impl Scratch {
+        fn new() -> Self {
+            use std::sync::atomic::{AtomicUsize, Ordering};
+            static NEXT: AtomicUsize = AtomicUsize::new(0);
+            Self(std::env::temp_dir().join(format!(
+                "amadeus-mcp-disk-cache-{}-{}",
+                std::process::id(),
+                NEXT.fetch_add(1, Ordering::Relaxed)
+            )))
+        }
+    }
+
+    impl Drop for Scratch {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn path(height: u64) -> String {
+        format!("/api/chain/height/{}", height)
+    }
+
+    /// A response of exactly `len` bytes once serialized.
+    fn sized(len: usize) -> serde_json::Value {
+        let value = json!({ "pad": "" });
+        json!({ "pad": "x".repeat(len - serde_json::to_vec(&value).unwrap().len()) })
+    }
+
+    fn age(cache: &DiskCache, height: u64, secs_ago: u64) {
+        let file = fs::File::options().write(true).open(cache.file("GET", NODE, &path(height))).unwrap();
+        file.set_modified(SystemTime::now() - Duration::from_secs(secs_ago)).unwrap();
+    }
+
+    #[test]
+    fn entries_survive_reopening() {
+        let dir = Scratch::new();
+        let response = json!({ "error": "ok", "entries": [{ "hash": "a" }] });
+        DiskCache::open(&dir.0, 1 << 20).unwrap().put("GET", NODE, &path(1), &response);
+
+        let reopened = DiskCache::open(&dir.0, 1 << 20).unwrap();
+        assert_eq!(reopened.get("GET", NODE, &path(1)), Some(response.clone()));
+        // A trailing slash on the node URL is the same entry; another node or path is not
+        assert_eq!(reopened.get("GET", &format!("{}/", NODE), &path(1)), Some(response));
+        assert_eq!(reopened.get("GET", "https://other.example", &path(1)), None);
+        assert_eq!(reopened.get("GET", NODE, &path(2)), None);
+        assert_eq!(*reopened.size.lock().unwrap(), entries(&dir.0)[0].1);
+    }
+
+    #[test]
+    fn the_least_recently_used_entries_are_evicted_past_the_cap() {
+        let dir = Scratch::new();
+        let cache = DiskCache::open(&dir.0, 300).unwrap();
+        for height in 1..=3 {
+            cache.put("GET", NODE, &path(height), &sized(100));
+        }
+        age(&cache, 1, 30);
+        age(&cache, 2, 20);
+        age(&cache, 3, 10);
+        // Reading 1 makes 2 the oldest
+        assert!(cache.get("GET", NODE, &path(1)).is_some());
+
+        cache.put("GET", NODE, &path(4), &sized(100));
+        assert!(cache.get("GET", NODE, &path(2)).is_none());
+        for height in [1, 3, 4] {
+            assert!(cache.get("GET", NODE, &path(height)).is_some(), "{}", height);
+        }
+        assert_eq!(*cache.size.lock().unwrap(), 300);
+    }
+
+    #[test]
+    fn rewriting_an_entry_does_not_count_it_twice() {
+        let dir = Scratch::new();
+        let cache = DiskCache::open(&dir.0, 250).unwrap();
+        cache.put("GET", NODE, &path(1), &sized(100));
+        cache.put("GET", NODE, &path(2), &sized(100));
+        cache.put("GET", NODE, &path(1), &sized(100));
+        assert_eq!(*cache.size.lock().unwrap(), 200);
+        assert_eq!(entries(&dir.0).len(), 2);
+    }
+
+    #[test]
+    fn a_corrupt_entry_is_deleted_and_missed() {
+        let dir = Scratch::new();
+        let cache = DiskCache::open(&dir.0, 1 << 20).unwrap();
+        cache.put("GET", NODE, &path(1), &json!({ "error": "ok" }));
+        let file = cache.file("GET", NODE, &path(1));
+        fs::write(&file, b"{\"error\": \"o").unwrap();
+
+        assert_eq!(cache.get("GET", NODE, &path(1)), None);
+        assert!(!file.exists());
+        // The refetched response is stored again
+        cache.put("GET", NODE, &path(1), &json!({ "error": "ok" }));
+        assert_eq!(cache.get("GET", NODE, &path(1)), Some(json!({ "error": "ok" })));
+    }
+
+    #[test]
+    fn leftover_partial_files_are_not_entries() {
+        let dir = Scratch::new();
+        fs::create_dir_all(&dir.0).unwrap();
+        fs::write(dir.0.join("abc.partial"), b"{").unwrap();
+        let cache = DiskCache::open(&dir.0, 1 << 20).unwrap();
+        assert_eq!(*cache.size.lock().unwrap(), 0);
+    }
+}
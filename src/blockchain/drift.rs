@@ -0,0 +1,382 @@
+//! Detects node responses drifting away from the types they are parsed into. Each known
+//! endpoint's body is parsed strictly as usual and also compared field by field with the
+//! JSON Schema of the target type, so new types and fields are covered without a separate
+//! list of expected names.
+
+use super::types::{Balance, BlockEntry, ChainStats, PeerNode, Transaction};
+use super::BlockchainClient;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::{debug, warn};
+
+/// Placeholder in check paths for an address known to the chain, taken from the tip.
+const SAMPLE_ADDRESS: &str = "{address}";
+
+/// One node endpoint and the type its body is parsed into.
+pub struct DriftCheck {
+    /// Path after the API prefix
+    pub path: &'static str,
+    /// Envelope field holding the typed part of the response
+    pub field: &'static str,
+    schema: fn() -> schemars::Schema,
+    parse: fn(&Value) -> Result<(), String>,
+}
+
+fn schema_of<T: JsonSchema>() -> schemars::Schema {
+    schemars::schema_for!(T)
+}
+
+fn parse_as<T: DeserializeOwned>(value: &Value) -> Result<(), String> {
+    T::deserialize(value).map(|_| ()).map_err(|e| e.to_string())
+}
+
+pub const DRIFT_CHECKS: &[DriftCheck] = &[
+    DriftCheck { path: "chain/stats", field: "stats", schema: schema_of::<ChainStats>, parse: parse_as::<ChainStats> },
+    DriftCheck { path: "chain/tip", field: "entry", schema: schema_of::<BlockEntry>, parse: parse_as::<BlockEntry> },
+    DriftCheck { path: "peer/nodes", field: "nodes", schema: schema_of::<Vec<PeerNode>>, parse: parse_as::<Vec<PeerNode>> },
+    DriftCheck { path: "peer/trainers", field: "trainers", schema: schema_of::<Vec<String>>, parse: parse_as::<Vec<String>> },
+    DriftCheck {
+        path: "wallet/balance_all/{address}",
+        field: "balances",
+        schema: schema_of::<Vec<Balance>>,
+        parse: parse_as::<Vec<Balance>>,
+    },
+    DriftCheck {
+        path: "chain/tx_events_by_account/{address}?limit=5",
+        field: "txs",
+        schema: schema_of::<Vec<Transaction>>,
+        parse: parse_as::<Vec<Transaction>>,
+    },
+];
+
+impl DriftCheck {
+    /// Node path for the check, with `address` standing in for the sample address.
+    pub fn path_for(&self, address: &str) -> String {
+        self.path.replace(SAMPLE_ADDRESS, address)
+    }
+
+    pub fn needs_address(&self) -> bool {
+        self.path.contains(SAMPLE_ADDRESS)
+    }
+
+    /// Compares one response with the check's type.
+    pub fn assess(&self, response: &Value) -> EndpointDrift {
+        let mut drift = EndpointDrift::new(self.path);
+        let Some(body) = response.get(self.field) else {
+            drift.status = DriftStatus::Broken;
+            drift.missing.insert(self.field.to_string());
+            drift.error = Some(format!("response has no {} field", self.field));
+            return drift;
+        };
+        let schema = (self.schema)();
+        compare(schema.as_value(), schema.as_value(), body, self.field, &mut drift);
+        drift.status = match (self.parse)(body) {
+            Err(e) => {
+                drift.error = Some(e);
+                DriftStatus::Broken
+            }
+            Ok(()) if !drift.missing.is_empty() || !drift.unexpected.is_empty() => DriftStatus::Changed,
+            Ok(()) => DriftStatus::Ok,
+        };
+        drift
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DriftStatus {
+    Ok,
+    /// Still parses, but fields were added or optional ones dropped
+    Changed,
+    /// No longer parses into the type
+    Broken,
+    /// Could not be fetched, so nothing was compared
+    Unchecked,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EndpointDrift {
+    pub endpoint: &'static str,
+    pub status: DriftStatus,
+    /// Fields the type requires or knows that the response lacks, as dotted paths
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    pub missing: BTreeSet<String>,
+    /// Fields in the response the type does not know
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    pub unexpected: BTreeSet<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl EndpointDrift {
+    fn new(endpoint: &'static str) -> Self {
+        Self { endpoint, status: DriftStatus::Ok, missing: BTreeSet::new(), unexpected: BTreeSet::new(), error: None }
+    }
+
+    pub fn unchecked(endpoint: &'static str, error: String) -> Self {
+        Self { status: DriftStatus::Unchecked, error: Some(error), ..Self::new(endpoint) }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DriftReport {
+    pub checked_at: u64,
+    /// Endpoints whose status is changed or broken
+    pub drifted: usize,
+    pub endpoints: Vec<EndpointDrift>,
+}
+
+impl DriftReport {
+    pub fn new(endpoints: Vec<EndpointDrift>, checked_at: u64) -> Self {
+        let drifted = endpoints
+            .iter()
+            .filter(|e| matches!(e.status, DriftStatus::Changed | DriftStatus::Broken))
+            .count();
+        Self { checked_at, drifted, endpoints }
+    }
+
+    pub fn is_broken(&self) -> bool {
+        self.endpoints.iter().any(|e| e.status == DriftStatus::Broken)
+    }
+}
+
+/// Follows `$ref`s into the root schema's `$defs`.
+fn resolve<'a>(root: &'a Value, schema: &'a Value) -> &'a Value {
+    match schema.get("$ref").and_then(Value::as_str).and_then(|r| r.strip_prefix("#/$defs/")) {
+        Some(name) => resolve(root, &root["$defs"][name]),
+        None => schema,
+    }
+}
+
+/// The branch of an `anyOf`/`oneOf` (how optional structs are written) that describes a
+/// non-null value.
+fn non_null<'a>(root: &'a Value, schema: &'a Value) -> &'a Value {
+    let schema = resolve(root, schema);
+    let branches = schema.get("anyOf").or_else(|| schema.get("oneOf")).and_then(Value::as_array);
+    match branches.and_then(|b| b.iter().find(|s| s.get("type").and_then(Value::as_str) != Some("null"))) {
+        Some(branch) => non_null(root, branch),
+        None => schema,
+    }
+}
+
+/// Records fields of `value` at `path` that `schema` does not describe, and fields it
+/// requires that `value` lacks.
+fn compare(root: &Value, schema: &Value, value: &Value, path: &str, drift: &mut EndpointDrift) {
+    let schema = non_null(root, schema);
+    match value {
+        Value::Object(fields) => {
+            let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+                return;
+            };
+            let required = schema.get("required").and_then(Value::as_array).map(Vec::as_slice).unwrap_or_default();
+            for name in required.iter().filter_map(Value::as_str) {
+                if !fields.contains_key(name) {
+                    drift.missing.insert(format!("{}.{}", path, name));
+                }
+            }
+            // Flattened maps allow any extra field
+            let open = schema.get("additionalProperties").is_some_and(|a| a != &Value::Bool(false));
+            for (name, field) in fields {
+                match properties.get(name) {
+                    Some(field_schema) => compare(root, field_schema, field, &format!("{}.{}", path, name), drift),
+                    None if !open => {
+                        drift.unexpected.insert(format!("{}.{}", path, name));
+                    }
+                    None => {}
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for item in items {
+                    compare(root, item_schema, item, &format!("{}[]", path), drift);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Fetches every known endpoint from `url` and compares it with its type.
+pub async fn check(client: &BlockchainClient, url: &str, now: u64) -> DriftReport {
+    let address = client
+        .get_entry_tip(url)
+        .await
+        .map(|tip| tip.header.signer)
+        .map_err(|e| e.to_string());
+    let mut endpoints = Vec::with_capacity(DRIFT_CHECKS.len());
+    for check in DRIFT_CHECKS {
+        let path = match (&address, check.needs_address()) {
+            (Err(e), true) => {
+                endpoints.push(EndpointDrift::unchecked(check.path, format!("no sample address: {}", e)));
+                continue;
+            }
+            (Ok(address), _) => check.path_for(address),
+            (Err(_), false) => check.path.to_string(),
+        };
+        endpoints.push(match client.raw_query(&client.api_paths().api(&path), url).await {
+            Ok(response) => check.assess(&response),
+            Err(e) => EndpointDrift::unchecked(check.path, e.to_string()),
+        });
+    }
+    DriftReport::new(endpoints, now)
+}
+
+/// Background drift check; stops when dropped.
+pub struct DriftMonitor {
+    report: watch::Receiver<Option<DriftReport>>,
+    _stop: watch::Sender<()>,
+}
+
+impl DriftMonitor {
+    pub fn spawn(client: BlockchainClient, url: String, interval: Duration) -> Self {
+        let (report_tx, report) = watch::channel(None);
+        let (stop, mut stop_rx) = watch::channel(());
+        tokio::spawn(async move {
+            loop {
+                let report = check(&client, &url, now()).await;
+                if report.drifted > 0 {
+                    let drifted: Vec<&str> = report
+                        .endpoints
+                        .iter()
+                        .filter(|e| matches!(e.status, DriftStatus::Changed | DriftStatus::Broken))
+                        .map(|e| e.endpoint)
+                        .collect();
+                    warn!(endpoints = ?drifted, "node responses drifted from the parsed types");
+                } else {
+                    debug!("no node response drift");
+                }
+                report_tx.send_replace(Some(report));
+                tokio::select! {
+                    _ = stop_rx.changed() => break,
+                    _ = tokio::time::sleep(interval) => {}
+                }
+            }
+        });
+        Self { report, _stop: stop }
+    }
+
+    /// The latest report, `None` before the first check finished.
+    pub fn current(&self) -> Option<DriftReport> {
+        self.report.borrow().clone()
+    }
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn check_for(path: &str) -> &'static DriftCheck {
+        DRIFT_CHECKS.iter().find(|c| c.path == path).unwrap()
+    }
+
+    fn tip(header: Value) -> Value {
+        json!({ "error": "ok", "entry": { "hash": "h", "header": header, "tx_count": 0 } })
+    }
+
+    fn header() -> Value {
+        json!({
+            "slot": 9, "height": 9, "dr": "dr", "vr": "vr", "prev_hash": "prev", "signer": "pk",
+            "root_tx": "rt", "root_validator": "rv", "prev_slot": 8, "timestamp": 1_700_000_000_000u64
+        })
+    }
+
+    #[test]
+    fn a_matching_response_is_ok() {
+        let drift = check_for("chain/tip").assess(&tip(header()));
+        assert_eq!(drift.status, DriftStatus::Ok, "{:?}", drift);
+        assert!(drift.missing.is_empty() && drift.unexpected.is_empty() && drift.error.is_none());
+    }
+
+    #[test]
+    fn a_renamed_required_field_breaks_the_parse() {
+        let mut renamed = header();
+        let height = renamed.as_object_mut().unwrap().remove("height").unwrap();
+        renamed["block_height"] = height;
+        let drift = check_for("chain/tip").assess(&tip(renamed));
+        assert_eq!(drift.status, DriftStatus::Broken);
+        assert_eq!(drift.missing.iter().collect::<Vec<_>>(), ["entry.header.height"]);
+        assert_eq!(drift.unexpected.iter().collect::<Vec<_>>(), ["entry.header.block_height"]);
+        assert!(drift.error.unwrap().contains("height"));
+    }
+
+    #[test]
+    fn an_added_field_is_a_change_that_still_parses() {
+        let mut response = tip(header());
+        response["entry"]["header"]["proposer_score"] = json!(3);
+        response["entry"]["mask"] = json!("ff");
+        let drift = check_for("chain/tip").assess(&response);
+        assert_eq!(drift.status, DriftStatus::Changed);
+        assert_eq!(drift.unexpected.iter().collect::<Vec<_>>(), ["entry.header.proposer_score", "entry.mask"]);
+        assert!(drift.missing.is_empty());
+    }
+
+    #[test]
+    fn types_that_keep_extra_fields_accept_additions() {
+        let stats = json!({ "error": "ok", "stats": { "height": 5, "pflops": 1.5, "new_metric": 2 } });
+        assert_eq!(check_for("chain/stats").assess(&stats).status, DriftStatus::Ok);
+        let renamed = json!({ "error": "ok", "stats": { "block_height": 5 } });
+        let drift = check_for("chain/stats").assess(&renamed);
+        assert_eq!(drift.status, DriftStatus::Broken);
+        assert_eq!(drift.missing.iter().collect::<Vec<_>>(), ["stats.height"]);
+    }
+
+    #[test]
+    fn list_items_are_compared_one_by_one() {
+        let txs = json!({ "error": "ok", "txs": [{
+            "hash": "h",
+            "metadata": { "entry_hash": "e", "entry_height": 1 },
+            "signature": "s",
+            "result": { "error": "ok" },
+            "tx": { "action": { "args": [], "function": "f", "op": "call", "contract": "C" }, "nonce": 1, "signer": "pk" },
+            "receipt": { "success": true, "result": "ok", "logs": [], "exec_used": "0" },
+            "gas": 3
+        }] });
+        let drift = check_for("chain/tx_events_by_account/{address}?limit=5").assess(&txs);
+        assert!(drift.unexpected.contains("txs[].gas"), "{:?}", drift);
+        assert_eq!(drift.status, DriftStatus::Changed);
+    }
+
+    #[test]
+    fn a_missing_envelope_field_is_broken() {
+        let drift = check_for("peer/trainers").assess(&json!({ "error": "ok", "validators": [] }));
+        assert_eq!(drift.status, DriftStatus::Broken);
+        assert_eq!(drift.missing.iter().collect::<Vec<_>>(), ["trainers"]);
+        assert_eq!(drift.error.as_deref(), Some("response has no trainers field"));
+        let retyped = check_for("peer/trainers").assess(&json!({ "error": "ok", "trainers": [1, 2] }));
+        assert_eq!(retyped.status, DriftStatus::Broken);
+    }
+
+    #[test]
+    fn addresses_are_filled_in_where_needed() {
+        let balances = check_for("wallet/balance_all/{address}");
+        assert!(balances.needs_address());
+        assert_eq!(balances.path_for("pk"), "wallet/balance_all/pk");
+        assert!(!check_for("chain/stats").needs_address());
+    }
+
+    #[test]
+    fn the_report_counts_changed_and_broken_endpoints() {
+        let changed = EndpointDrift { status: DriftStatus::Changed, ..EndpointDrift::new("a") };
+        let unchecked = EndpointDrift::unchecked("b", "timeout".to_string());
+        let report = DriftReport::new(vec![changed.clone(), unchecked, EndpointDrift::new("c")], 7);
+        assert_eq!((report.drifted, report.is_broken()), (1, false));
+        let broken = EndpointDrift { status: DriftStatus::Broken, ..EndpointDrift::new("d") };
+        let report = DriftReport::new(vec![changed, broken], 7);
+        assert_eq!((report.drifted, report.is_broken()), (2, true));
+        assert_eq!(serde_json::to_value(&report).unwrap()["endpoints"][1], json!({ "endpoint": "d", "status": "broken" }));
+    }
+}
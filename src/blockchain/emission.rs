@@ -0,0 +1,285 @@
+use super::block_time::{BlockAtTime, GENESIS_HEIGHT};
+use super::types::ChainStats;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+/// Snapshots kept per network; at one per entry this covers well over a day.
+pub const MAX_SNAPSHOTS: usize = 4096;
+/// Why no window is summed entry by entry: supply is only reported as chain-wide totals.
+pub const DIRECT_UNAVAILABLE: &str =
+    "nodes expose no per-entry emission or burn figures, so windows are measured by diffing chain stats snapshots";
+
+/// `burned`/`circulating` readings from chain stats at a given height.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsSnapshot {
+    pub height: u64,
+    pub burned: f64,
+    pub circulating: f64,
+    /// Unix seconds when the snapshot was taken
+    pub recorded_at: u64,
+}
+
+impl StatsSnapshot {
+    pub fn from_stats(stats: &ChainStats, recorded_at: u64) -> Option<Self> {
+        Some(Self {
+            height: stats.height,
+            burned: stats.burned?,
+            circulating: stats.circulating?,
+            recorded_at,
+        })
+    }
+}
+
+/// Stats snapshots recorded by this process, keyed by node URL.
+#[derive(Debug, Default)]
+pub struct SnapshotLog {
+    inner: Mutex<HashMap<String, VecDeque<StatsSnapshot>>>,
+}
+
+impl SnapshotLog {
+    pub fn record(&self, url: &str, snapshot: StatsSnapshot) {
+        let mut inner = self.inner.lock().unwrap();
+        let log = inner.entry(url.to_string()).or_default();
+        if log.back().is_some_and(|last| last.height >= snapshot.height) {
+            return;
+        }
+        if log.len() == MAX_SNAPSHOTS {
+            log.pop_front();
+        }
+        log.push_back(snapshot);
+    }
+
+    /// Drops snapshots taken at or above `height`, e.g. after a reorg.
+    pub fn discard_from(&self, url: &str, height: u64) {
+        if let Some(log) = self.inner.lock().unwrap().get_mut(url) {
+            log.retain(|s| s.height < height);
+        }
+    }
+
+    pub fn snapshots(&self, url: &str) -> Vec<StatsSnapshot> {
+        let inner = self.inner.lock().unwrap();
+        inner.get(url).map(|log| log.iter().cloned().collect()).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmissionStats {
+    pub methodology: String,
+    pub confidence: String,
+    pub requested_blocks: u64,
+    pub covered_blocks: u64,
+    pub from_height: u64,
+    pub to_height: u64,
+    pub from_recorded_at: u64,
+    pub to_recorded_at: u64,
+    /// AMA emitted in the window (change in circulating plus change in burned)
+    pub emitted: f64,
+    /// AMA burned in the window
+    pub burned: f64,
+    /// Change in circulating supply
+    pub net_issuance: f64,
+    /// Unix seconds the window was asked to start at, when given as `since`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<u64>,
+    /// The direct methodology, summing per-entry emission, and why it was not used
+    pub direct: DirectMethod,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirectMethod {
+    pub available: bool,
+    pub reason: String,
+}
+
+impl DirectMethod {
+    pub fn unavailable() -> Self {
+        Self { available: false, reason: DIRECT_UNAVAILABLE.to_string() }
+    }
+}
+
+/// How a get_emission_stats window is given.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmissionWindow {
+    /// Entries ending at the current height
+    Blocks(u64),
+    /// Unix seconds the window starts at
+    Since(u64),
+}
+
+/// Entries from the height the block-time search placed `since` at up to `end_height`,
+/// at least one. A moment before genesis starts the window at genesis.
+pub fn blocks_since(found: &BlockAtTime, end_height: u64) -> u64 {
+    let start = found.below.as_ref().or(found.above.as_ref()).map_or(GENESIS_HEIGHT, |entry| entry.height);
+    end_height.saturating_sub(start).max(1)
+}
+
+/// Picks the snapshot to diff against for a window ending at `end`: the latest one at or
+/// before the window start, or else the earliest one recorded inside the window.
+pub fn window_start(snapshots: &[StatsSnapshot], end: &StatsSnapshot, blocks: u64) -> Option<StatsSnapshot> {
+    let start_height = end.height.saturating_sub(blocks);
+    let candidates = snapshots.iter().filter(|s| s.height < end.height);
+
+    candidates
+        .clone()
+        .filter(|s| s.height <= start_height)
+        .max_by_key(|s| s.height)
+        .or_else(|| candidates.min_by_key(|s| s.height))
+        .cloned()
+}
+
+/// Diffs two snapshots. Confidence reflects how much of the requested window the
+/// snapshots actually cover.
+pub fn diff(start: &StatsSnapshot, end: &StatsSnapshot, requested_blocks: u64) -> EmissionStats {
+    let covered_blocks = end.height.saturating_sub(start.height);
+    let coverage = if requested_blocks == 0 {
+        1.0
+    } else {
+        covered_blocks as f64 / requested_blocks as f64
+    };
+    let confidence = if (0.9..=1.1).contains(&coverage) {
+        "high"
+    } else if coverage >= 0.5 {
+        "medium"
+    } else {
+        "low"
+    };
+
+    let burned = end.burned - start.burned;
+    let net_issuance = end.circulating - start.circulating;
+
+    EmissionStats {
+        methodology: "snapshot_diff".to_string(),
+        confidence: confidence.to_string(),
+        requested_blocks,
+        covered_blocks,
+        from_height: start.height,
+        to_height: end.height,
+        from_recorded_at: start.recorded_at,
+        to_recorded_at: end.recorded_at,
+        emitted: net_issuance + burned,
+        burned,
+        net_issuance,
+        since: None,
+        direct: DirectMethod::unavailable(),
+    }
+}
+
+/// Emission over the `blocks` entries ending at `end`, or `None` when no earlier snapshot
+/// exists to diff against.
+pub fn emission_stats(snapshots: &[StatsSnapshot], end: &StatsSnapshot, blocks: u64) -> Option<EmissionStats> {
+    window_start(snapshots, end, blocks).map(|start| diff(&start, end, blocks))
+}
+
+/// Response used when there is nothing to diff against, rather than guessing.
+pub fn insufficient_data(blocks: u64, snapshots_available: usize) -> Value {
+    json!({
+        "status": "insufficient_historical_data",
+        "requested_blocks": blocks,
+        "snapshots_available": snapshots_available,
+        "message": "no chain stats snapshot recorded before the window end; retry once more history has accumulated",
+        "direct": DirectMethod::unavailable(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::block_time::{TimePosition, TimedEntry};
+
+    fn snapshot(height: u64, burned: f64, circulating: f64) -> StatsSnapshot {
+        StatsSnapshot { height, burned, circulating, recorded_at: 1_700_000_000 + height }
+    }
+
+    fn timed(height: u64) -> TimedEntry {
+        TimedEntry { height, hash: String::new(), timestamp: 0, timestamp_ms: 0, offset_secs: 0 }
+    }
+
+    fn found(below: Option<u64>, above: Option<u64>) -> BlockAtTime {
+        BlockAtTime {
+            target: 1_700_000_000,
+            position: TimePosition::Between,
+            below: below.map(timed),
+            above: above.map(timed),
+            probes: 4,
+            converged: true,
+            skipped: Vec::new(),
+            note: None,
+        }
+    }
+
+    #[test]
+    fn diff_sums_issuance_and_burn() {
+        let stats = diff(&snapshot(100, 10.0, 1_000.0), &snapshot(200, 12.5, 1_040.0), 100);
+        assert_eq!(stats.burned, 2.5);
+        assert_eq!(stats.net_issuance, 40.0);
+        assert_eq!(stats.emitted, 42.5);
+        assert_eq!((stats.from_height, stats.to_height, stats.covered_blocks), (100, 200, 100));
+        assert_eq!(stats.methodology, "snapshot_diff");
+        assert!(!stats.direct.available);
+    }
+
+    #[test]
+    fn supply_shrinking_faster_than_emission() {
+        let stats = diff(&snapshot(0, 0.0, 1_000.0), &snapshot(10, 30.0, 990.0), 10);
+        assert_eq!(stats.net_issuance, -10.0);
+        assert_eq!(stats.emitted, 20.0);
+    }
+
+    #[test]
+    fn confidence_follows_coverage() {
+        let end = snapshot(1_000, 0.0, 0.0);
+        // Covering far more than asked, when snapshots are sparse, is not "high" either
+        let cases = [(900, 100, "high"), (905, 100, "high"), (940, 100, "medium"), (990, 100, "low"), (0, 100, "medium"), (1_000, 0, "high")];
+        for (start, requested, confidence) in cases {
+            let stats = diff(&snapshot(start, 0.0, 0.0), &end, requested);
+            assert_eq!(stats.confidence, confidence, "{} of {}", 1_000 - start, requested);
+        }
+    }
+
+    #[test]
+    fn window_start_prefers_the_latest_snapshot_before_the_window() {
+        let snapshots = [snapshot(10, 0.0, 0.0), snapshot(40, 0.0, 0.0), snapshot(60, 0.0, 0.0), snapshot(100, 0.0, 0.0)];
+        let end = snapshot(100, 0.0, 0.0);
+        assert_eq!(window_start(&snapshots, &end, 50).unwrap().height, 40);
+        // Nothing old enough: the earliest inside the window, never the end itself
+        assert_eq!(window_start(&snapshots, &end, 95).unwrap().height, 10);
+        assert_eq!(window_start(&snapshots[2..], &end, 95).unwrap().height, 60);
+        assert!(window_start(&snapshots[3..], &end, 10).is_none());
+    }
+
+    #[test]
+    fn missing_history_is_insufficient_rather_than_guessed() {
+        let end = snapshot(100, 1.0, 1.0);
+        assert!(emission_stats(&[], &end, 10).is_none());
+        assert!(emission_stats(std::slice::from_ref(&end), &end, 10).is_none());
+
+        let response = insufficient_data(10, 1);
+        assert_eq!(response["status"], "insufficient_historical_data");
+        assert_eq!(response["snapshots_available"], 1);
+        assert_eq!(response["direct"]["available"], false);
+    }
+
+    #[test]
+    fn since_maps_to_blocks_from_the_found_height() {
+        assert_eq!(blocks_since(&found(Some(900), Some(901)), 1_000), 100);
+        // Before genesis only the genesis entry is above
+        assert_eq!(blocks_since(&found(None, Some(0)), 1_000), 1_000);
+        // After the tip the window still covers one entry
+        assert_eq!(blocks_since(&found(Some(1_000), None), 1_000), 1);
+        assert_eq!(blocks_since(&found(None, None), 1_000), 1_000);
+    }
+
+    #[test]
+    fn snapshot_log_keeps_heights_increasing() {
+        let log = SnapshotLog::default();
+        for height in [10, 20, 15, 20, 30] {
+            log.record("node", snapshot(height, 0.0, 0.0));
+        }
+        assert_eq!(log.snapshots("node").iter().map(|s| s.height).collect::<Vec<_>>(), [10, 20, 30]);
+        log.discard_from("node", 20);
+        assert_eq!(log.snapshots("node").iter().map(|s| s.height).collect::<Vec<_>>(), [10]);
+        assert!(log.snapshots("other").is_empty());
+    }
+}
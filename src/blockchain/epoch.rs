@@ -0,0 +1,157 @@
+//! Where the chain is within its epoch. Epochs are fixed spans of heights, so the epoch
+//! follows from the tip height; the time to the next one is projected from the entry
+//! timestamps of recent heights.
+
+use super::clock;
+use serde::Serialize;
+
+/// Heights per epoch.
+pub const EPOCH_HEIGHTS: u64 = 100_000;
+/// Heights back from the tip that the production pace is measured over.
+pub const EPOCH_PACE_SPAN: u64 = 1_000;
+/// Heights sampled across the span, the tip included.
+pub const EPOCH_PACE_SAMPLES: u64 = 5;
+
+/// Heights whose timestamps the pace is measured from, newest first.
+pub fn sample_heights(tip: u64) -> Vec<u64> {
+    let step = EPOCH_PACE_SPAN / (EPOCH_PACE_SAMPLES - 1);
+    (0..EPOCH_PACE_SAMPLES)
+        .filter_map(|i| tip.checked_sub(i * step))
+        .collect()
+}
+
+/// Seconds per entry between the oldest and newest of `samples` (height, entry
+/// timestamp), `None` without two samples that are apart in both height and time.
+pub fn seconds_per_entry(samples: &[(u64, u64)]) -> Option<f64> {
+    let (first_height, first_time) = samples.iter().min_by_key(|(height, _)| *height)?;
+    let (last_height, last_time) = samples.iter().max_by_key(|(height, _)| *height)?;
+    let (first_secs, last_secs) = (clock::entry_secs(*first_time), clock::entry_secs(*last_time));
+    if last_height <= first_height || last_secs <= first_secs {
+        return None;
+    }
+    Some((last_secs - first_secs) as f64 / (last_height - first_height) as f64)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EpochInfo {
+    pub epoch: u64,
+    pub height: u64,
+    pub epoch_start_height: u64,
+    pub next_epoch_height: u64,
+    /// Heights since the epoch started, from 0
+    pub height_in_epoch: u64,
+    pub blocks_remaining: u64,
+    /// Share of the epoch behind the tip, 0 to 1
+    pub progress: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seconds_per_entry: Option<f64>,
+    /// Projected from `seconds_per_entry`; absent when the pace could not be measured
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimated_secs_to_next: Option<u64>,
+    /// Unix seconds the next epoch is projected to start at
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimated_next_epoch_at: Option<u64>,
+    /// Heights whose timestamps the pace was measured from
+    pub pace_samples: usize,
+}
+
+/// The epoch at `tip`, with the time to the next one projected from `samples` (height,
+/// entry timestamp). The newest sample's timestamp anchors the projected start time.
+pub fn epoch_info(tip: u64, samples: &[(u64, u64)]) -> EpochInfo {
+    let epoch = tip / EPOCH_HEIGHTS;
+    let epoch_start_height = epoch * EPOCH_HEIGHTS;
+    let next_epoch_height = epoch_start_height + EPOCH_HEIGHTS;
+    let height_in_epoch = tip - epoch_start_height;
+    let blocks_remaining = next_epoch_height - tip;
+
+    let pace = seconds_per_entry(samples);
+    let estimated_secs_to_next = pace.map(|secs| (secs * blocks_remaining as f64).round() as u64);
+    let newest_time = samples
+        .iter()
+        .max_by_key(|(height, _)| *height)
+        .map(|(_, timestamp)| clock::entry_secs(*timestamp));
+
+    EpochInfo {
+        epoch,
+        height: tip,
+        epoch_start_height,
+        next_epoch_height,
+        height_in_epoch,
+        blocks_remaining,
+        progress: height_in_epoch as f64 / EPOCH_HEIGHTS as f64,
+        seconds_per_entry: pace,
+        estimated_secs_to_next,
+        estimated_next_epoch_at: estimated_secs_to_next.zip(newest_time).map(|(secs, at)| at + secs),
+        pace_samples: samples.len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const T0: u64 = 1_700_000_000;
+
+    /// The sampled heights below `tip`, with entries `secs` apart from `T0` at the oldest.
+    fn paced(tip: u64, secs: f64) -> Vec<(u64, u64)> {
+        let oldest = tip - EPOCH_PACE_SPAN;
+        sample_heights(tip).into_iter().map(|h| (h, T0 + ((h - oldest) as f64 * secs) as u64)).collect()
+    }
+
+    #[test]
+    fn samples_span_the_last_thousand_heights_newest_first() {
+        assert_eq!(sample_heights(250_000), vec![250_000, 249_750, 249_500, 249_250, 249_000]);
+        assert_eq!(sample_heights(600), vec![600, 350, 100], "heights below zero are dropped");
+    }
+
+    #[test]
+    fn the_epoch_follows_from_the_tip_height() {
+        let info = epoch_info(250_000, &[]);
+        assert_eq!((info.epoch, info.epoch_start_height, info.next_epoch_height), (2, 200_000, 300_000));
+        assert_eq!((info.height_in_epoch, info.blocks_remaining), (50_000, 50_000));
+        assert_eq!(info.progress, 0.5);
+
+        let start = epoch_info(300_000, &[]);
+        assert_eq!((start.epoch, start.height_in_epoch, start.blocks_remaining), (3, 0, EPOCH_HEIGHTS));
+        let last = epoch_info(299_999, &[]);
+        assert_eq!((last.epoch, last.blocks_remaining), (2, 1));
+    }
+
+    #[test]
+    fn the_time_to_the_next_epoch_is_projected_from_the_pace() {
+        let samples = paced(250_000, 0.5);
+        let info = epoch_info(250_000, &samples);
+        assert_eq!(info.seconds_per_entry, Some(0.5));
+        assert_eq!(info.estimated_secs_to_next, Some(25_000));
+        assert_eq!(info.estimated_next_epoch_at, Some(T0 + 500 + 25_000));
+        assert_eq!(info.pace_samples, 5);
+    }
+
+    #[test]
+    fn millisecond_timestamps_give_the_same_pace() {
+        let millis: Vec<(u64, u64)> = paced(250_000, 0.5).into_iter().map(|(h, t)| (h, t * 1000)).collect();
+        let info = epoch_info(250_000, &millis);
+        assert_eq!(info.seconds_per_entry, Some(0.5));
+        assert_eq!(info.estimated_next_epoch_at, Some(T0 + 500 + 25_000));
+    }
+
+    #[test]
+    fn sample_order_does_not_matter() {
+        let mut samples = paced(250_000, 2.0);
+        samples.swap(0, 3);
+        assert_eq!(seconds_per_entry(&samples), Some(2.0));
+    }
+
+    #[test]
+    fn no_estimate_without_two_distinct_samples() {
+        assert_eq!(seconds_per_entry(&[]), None);
+        assert_eq!(seconds_per_entry(&[(250_000, T0)]), None);
+        assert_eq!(seconds_per_entry(&[(250_000, T0), (249_000, T0)]), None, "no time passed");
+        assert_eq!(seconds_per_entry(&[(250_000, T0), (249_000, T0 + 10)]), None, "time ran backwards");
+
+        let info = epoch_info(250_000, &[(250_000, 0)]);
+        assert_eq!((info.seconds_per_entry, info.estimated_secs_to_next, info.estimated_next_epoch_at), (None, None, None));
+        let fields = serde_json::to_value(&info).unwrap();
+        assert!(fields.get("estimated_secs_to_next").is_none() && fields.get("seconds_per_entry").is_none());
+    }
+}
@@ -22,12 +22,27 @@ pub enum BlockchainError {
     #[error("Insufficient balance: required {required}, available {available}")]
     InsufficientBalance { required: String, available: String },
 
+    #[error("upstream node is unavailable (HTTP {status})")]
+    UpstreamUnavailable { status: u16 },
+
+    #[error("{argument} {requested} is above this server's limit of {max}")]
+    LimitExceeded { argument: String, requested: u64, max: u64 },
+
+    #[error("{argument} {value} was not found")]
+    NotFound { argument: String, value: String },
+
+    #[error("upstream node does not support {endpoint}")]
+    EndpointUnsupported { endpoint: String },
+
     #[error("Network error after {attempts} retries")]
     NetworkRetryExhausted { attempts: usize },
 
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
 
+    #[error("offline mode: {0}")]
+    Offline(String),
+
     #[error("Configuration error: {0}")]
     Configuration(String),
 }
@@ -0,0 +1,222 @@
+use super::error::{BlockchainError, Result};
+use super::metrics::CallMetrics;
+use std::time::Duration;
+
+/// Attempts per node before moving on to the next fallback.
+pub const MAX_ATTEMPTS: usize = 2;
+pub const RETRY_DELAY: Duration = Duration::from_millis(300);
+
+/// An HTTP response from a node, before it is checked.
+#[derive(Debug, Clone)]
+pub struct NodeReply {
+    pub status: u16,
+    pub content_type: String,
+    pub body: String,
+}
+
+/// Sends requests for the worker client's fetch loop.
+// The worker runtime is single-threaded, so the futures need not be Send
+#[allow(async_fn_in_trait)]
+pub trait NodeTransport {
+    /// One request to `url`; network failures are errors, any HTTP response is a reply.
+    async fn send(&self, url: &str) -> Result<NodeReply>;
+    /// Waits before a retry.
+    async fn pause(&self, delay: Duration);
+}
+
+/// `url` followed by the same path on each fallback node, when `url` is under `base_url`.
+pub fn candidates(url: &str, base_url: &str, fallback_urls: &[String]) -> Vec<String> {
+    let mut candidates = vec![url.to_string()];
+    if let Some(path) = url.strip_prefix(base_url) {
+        candidates.extend(fallback_urls.iter().map(|base| format!("{}{}", base, path)));
+    }
+    candidates
+}
+
+/// The body of a good reply. Maintenance pages (5xx, or HTML where JSON was expected) are
+/// reported as `UpstreamUnavailable` so they can be retried instead of failing to parse.
+pub fn check(reply: NodeReply) -> Result<String> {
+    if reply.status >= 500 {
+        return Err(BlockchainError::UpstreamUnavailable { status: reply.status });
+    }
+    if !(200..300).contains(&reply.status) {
+        return Err(BlockchainError::InvalidResponse(format!("HTTP {}", reply.status)));
+    }
+    if reply.content_type.contains("text/html") {
+        return Err(BlockchainError::UpstreamUnavailable { status: reply.status });
+    }
+    Ok(reply.body)
+}
+
+fn retryable(error: &BlockchainError) -> bool {
+    match error {
+        BlockchainError::UpstreamUnavailable { .. } => true,
+        #[cfg(target_arch = "wasm32")]
+        BlockchainError::HttpRequestWasm(_) => true,
+        #[cfg(not(target_arch = "wasm32"))]
+        BlockchainError::HttpRequest(_) => true,
+        _ => false,
+    }
+}
+
+/// Tries each candidate up to `MAX_ATTEMPTS` times, moving on when a node is unavailable,
+/// and returns the body of the first good reply. Other errors are returned straight away.
+pub async fn fetch<T: NodeTransport>(transport: &T, candidates: &[String], metrics: &CallMetrics) -> Result<String> {
+    let mut last_error = None;
+    for (i, candidate) in candidates.iter().enumerate() {
+        for attempt in 0..MAX_ATTEMPTS {
+            if attempt > 0 {
+                transport.pause(RETRY_DELAY).await;
+            }
+            if i > 0 || attempt > 0 {
+                metrics.retry();
+            }
+            metrics.upstream_request();
+            match transport.send(candidate).await.and_then(check) {
+                Err(e) if retryable(&e) => last_error = Some(e),
+                result => return result,
+            }
+        }
+    }
+    Err(last_error.unwrap_or(BlockchainError::NetworkRetryExhausted { attempts: MAX_ATTEMPTS }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::collections::{HashMap, VecDeque};
+
+    const BASE: &str = "https://nodes.amadeus.bot";
+    const BACKUP: &str = "https://backup.example";
+    const PATH: &str = "/api/chain/stats";
+
+    fn json(body: &str) -> Result<NodeReply> {
+        Ok(NodeReply { status: 200, content_type: "application/json".into(), body: body.into() })
+    }
+
+    fn maintenance(status: u16) -> Result<NodeReply> {
+        Ok(NodeReply {
+            status,
+            content_type: "text/html; charset=UTF-8".into(),
+            body: "<html><title>Origin is unreachable</title></html>".into(),
+        })
+    }
+
+    async fn network_error() -> Result<NodeReply> {
+        Err(BlockchainError::HttpRequest(reqwest::get("not a url").await.unwrap_err()))
+    }
+
+    /// Answers each URL from its script in order and records what was sent.
+    #[derive(Default)]
+    struct Flaky {
+        scripts: RefCell<HashMap<String, VecDeque<Result<NodeReply>>>>,
+        sent: RefCell<Vec<String>>,
+        pauses: RefCell<Vec<Duration>>,
+    }
+
+    impl Flaky {
+        fn script(self, url: &str, replies: Vec<Result<NodeReply>>) -> Self {
+            self.scripts.borrow_mut().insert(url.to_string(), replies.into());
+            self
+        }
+    }
+
+    impl NodeTransport for Flaky {
+        async fn send(&self, url: &str) -> Result<NodeReply> {
+            self.sent.borrow_mut().push(url.to_string());
+            self.scripts
+                .borrow_mut()
+                .get_mut(url)
+                .and_then(VecDeque::pop_front)
+                .unwrap_or_else(|| panic!("unscripted request to {}", url))
+        }
+
+        async fn pause(&self, delay: Duration) {
+            self.pauses.borrow_mut().push(delay);
+        }
+    }
+
+    async fn run(transport: &Flaky) -> (Result<String>, CallMetrics) {
+        let urls = candidates(&format!("{}{}", BASE, PATH), BASE, &[BACKUP.to_string()]);
+        let metrics = CallMetrics::default();
+        (fetch(transport, &urls, &metrics).await, metrics)
+    }
+
+    #[test]
+    fn fallbacks_get_the_same_path_only_for_base_urls() {
+        let fallbacks = vec![BACKUP.to_string(), "https://third.example".to_string()];
+        assert_eq!(
+            candidates(&format!("{}{}?limit=5", BASE, PATH), BASE, &fallbacks),
+            vec![
+                format!("{}{}?limit=5", BASE, PATH),
+                format!("{}{}?limit=5", BACKUP, PATH),
+                format!("https://third.example{}?limit=5", PATH),
+            ]
+        );
+        let testnet = "https://testnet.amadeus.bot/api/chain/stats";
+        assert_eq!(candidates(testnet, BASE, &fallbacks), vec![testnet.to_string()]);
+    }
+
+    #[test]
+    fn replies_are_checked_for_maintenance_pages() {
+        assert_eq!(check(json("{}").unwrap()).unwrap(), "{}");
+        for status in [500, 502, 523] {
+            let e = check(maintenance(status).unwrap()).unwrap_err();
+            assert!(matches!(e, BlockchainError::UpstreamUnavailable { status: s } if s == status));
+            assert_eq!(e.to_string(), format!("upstream node is unavailable (HTTP {})", status));
+        }
+        // A CDN can front an error page with a 200
+        assert!(matches!(check(maintenance(200).unwrap()), Err(BlockchainError::UpstreamUnavailable { status: 200 })));
+        assert!(matches!(check(NodeReply { status: 404, ..json("{}").unwrap() }), Err(BlockchainError::InvalidResponse(m)) if m == "HTTP 404"));
+    }
+
+    #[tokio::test]
+    async fn a_maintenance_page_is_retried_on_the_same_node() {
+        let node = Flaky::default().script(&format!("{}{}", BASE, PATH), vec![maintenance(523), json(r#"{"height":7}"#)]);
+        let (body, metrics) = run(&node).await;
+        assert_eq!(body.unwrap(), r#"{"height":7}"#);
+        assert_eq!(node.sent.borrow().len(), 2);
+        assert_eq!(*node.pauses.borrow(), vec![RETRY_DELAY]);
+        let timing = metrics.timing(Duration::ZERO);
+        assert_eq!((timing.upstream_requests, timing.retries), (2, 1));
+    }
+
+    #[tokio::test]
+    async fn an_unavailable_node_fails_over_to_the_backup() {
+        let node = Flaky::default()
+            .script(&format!("{}{}", BASE, PATH), vec![maintenance(502), network_error().await])
+            .script(&format!("{}{}", BACKUP, PATH), vec![json("{}")]);
+        let (body, metrics) = run(&node).await;
+        assert_eq!(body.unwrap(), "{}");
+        assert_eq!(
+            *node.sent.borrow(),
+            vec![format!("{}{}", BASE, PATH), format!("{}{}", BASE, PATH), format!("{}{}", BACKUP, PATH)]
+        );
+        // No pause before the first request to a new node
+        assert_eq!(node.pauses.borrow().len(), 1);
+        assert_eq!(metrics.timing(Duration::ZERO).retries, 2);
+    }
+
+    #[tokio::test]
+    async fn the_last_unavailable_status_is_reported_when_every_node_fails() {
+        let node = Flaky::default()
+            .script(&format!("{}{}", BASE, PATH), vec![maintenance(502), maintenance(502)])
+            .script(&format!("{}{}", BACKUP, PATH), vec![maintenance(503), maintenance(523)]);
+        let (body, _) = run(&node).await;
+        assert!(matches!(body, Err(BlockchainError::UpstreamUnavailable { status: 523 })));
+        assert_eq!(node.sent.borrow().len(), 2 * MAX_ATTEMPTS);
+    }
+
+    #[tokio::test]
+    async fn client_errors_are_not_retried() {
+        let node = Flaky::default().script(
+            &format!("{}{}", BASE, PATH),
+            vec![Ok(NodeReply { status: 404, content_type: "application/json".into(), body: "{}".into() })],
+        );
+        let (body, metrics) = run(&node).await;
+        assert!(matches!(body, Err(BlockchainError::InvalidResponse(_))));
+        assert_eq!(node.sent.borrow().len(), 1);
+        assert_eq!(metrics.timing(Duration::ZERO).retries, 0);
+    }
+}
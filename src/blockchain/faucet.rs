@@ -0,0 +1,193 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// Symbol claimed when the caller does not name one.
+pub const DEFAULT_FAUCET_SYMBOL: &str = "AMA";
+/// Whole AMA sent per claim when `FAUCET_TOKENS` is not set.
+const DEFAULT_FAUCET_AMOUNT: u64 = 100;
+/// Seconds one IP waits between two claims of the same symbol.
+pub const CLAIM_COOLDOWN_SECS: f64 = 86400.0;
+
+/// Testnet tokens the faucet hands out, with the whole units sent per claim
+/// (`FAUCET_TOKENS`, e.g. `{"AMA":100,"TESTX":5000}`). Amounts are converted to atoms
+/// through the decimals registry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FaucetTokens {
+    tokens: BTreeMap<String, u64>,
+}
+
+impl Default for FaucetTokens {
+    fn default() -> Self {
+        Self { tokens: BTreeMap::from([(DEFAULT_FAUCET_SYMBOL.to_string(), DEFAULT_FAUCET_AMOUNT)]) }
+    }
+}
+
+impl FaucetTokens {
+    pub fn symbols(&self) -> Vec<&str> {
+        self.tokens.keys().map(String::as_str).collect()
+    }
+
+    /// The configured symbol and its drip amount for a claim of `symbol`, AMA when absent.
+    /// Symbols match case-insensitively.
+    pub fn resolve(&self, symbol: Option<&str>) -> Result<(&str, u64), String> {
+        let requested = symbol.map(str::trim).filter(|s| !s.is_empty()).unwrap_or(DEFAULT_FAUCET_SYMBOL);
+        self.tokens
+            .iter()
+            .find(|(s, _)| s.eq_ignore_ascii_case(requested))
+            .map(|(s, amount)| (s.as_str(), *amount))
+            .ok_or_else(|| {
+                format!("the faucet does not hand out {}; available: {}", requested, self.symbols().join(", "))
+            })
+    }
+}
+
+impl FromStr for FaucetTokens {
+    type Err = String;
+
+    /// Parses a JSON object of symbol to whole units per claim.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens: BTreeMap<String, u64> =
+            serde_json::from_str(s).map_err(|e| format!("expected a JSON object of symbol to amount: {}", e))?;
+        if tokens.is_empty() {
+            return Err("at least one token is required".to_string());
+        }
+        if let Some((symbol, _)) = tokens.iter().find(|(_, amount)| **amount == 0) {
+            return Err(format!("{}: amount must be at least 1", symbol));
+        }
+        if let Some(symbol) = tokens.keys().find(|s| s.trim().is_empty() || s.trim() != s.as_str()) {
+            return Err(format!("invalid symbol {:?}", symbol));
+        }
+        Ok(Self { tokens })
+    }
+}
+
+/// Remembers when each IP last claimed each symbol.
+// The worker runtime is single-threaded, so the futures need not be Send
+#[allow(async_fn_in_trait)]
+pub trait ClaimLedger {
+    /// Unix seconds of the last claim of `symbol` from `ip`.
+    async fn last_claim(&self, ip: &str, symbol: &str) -> Result<Option<f64>, String>;
+    /// Records a claim of `symbol` from `ip` to `address` at `now`, replacing the earlier one.
+    async fn record(&self, ip: &str, symbol: &str, address: &str, now: f64) -> Result<(), String>;
+}
+
+/// Fails with the remaining wait when `ip` claimed `symbol` less than a day before `now`.
+/// Claims of other symbols do not count.
+pub async fn check_cooldown<L: ClaimLedger>(ledger: &L, ip: &str, symbol: &str, now: f64) -> Result<(), String> {
+    let Some(claimed_at) = ledger.last_claim(ip, symbol).await? else {
+        return Ok(());
+    };
+    let elapsed = now - claimed_at;
+    if elapsed < CLAIM_COOLDOWN_SECS {
+        let remaining = (CLAIM_COOLDOWN_SECS - elapsed) as i64;
+        return Err(format!(
+            "can only claim {} once per day, wait {}h {}m",
+            symbol,
+            remaining / 3600,
+            (remaining % 3600) / 60
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+
+    const NOW: f64 = 1_718_000_000.0;
+    const HOUR: f64 = 3600.0;
+
+    /// Claims kept in memory, keyed like the D1 table.
+    #[derive(Default)]
+    struct Ledger {
+        claims: RefCell<HashMap<(String, String), (String, f64)>>,
+    }
+
+    impl ClaimLedger for Ledger {
+        async fn last_claim(&self, ip: &str, symbol: &str) -> Result<Option<f64>, String> {
+            Ok(self.claims.borrow().get(&(ip.to_string(), symbol.to_string())).map(|(_, at)| *at))
+        }
+
+        async fn record(&self, ip: &str, symbol: &str, address: &str, now: f64) -> Result<(), String> {
+            self.claims.borrow_mut().insert((ip.to_string(), symbol.to_string()), (address.to_string(), now));
+            Ok(())
+        }
+    }
+
+    /// Checks the cooldown and records the claim when it passes, as the faucet tool does.
+    async fn claim(ledger: &Ledger, ip: &str, symbol: &str, now: f64) -> Result<(), String> {
+        check_cooldown(ledger, ip, symbol, now).await?;
+        ledger.record(ip, symbol, "address", now).await
+    }
+
+    fn tokens() -> FaucetTokens {
+        r#"{"AMA":100,"TESTX":5000}"#.parse().unwrap()
+    }
+
+    #[test]
+    fn ama_is_handed_out_by_default() {
+        let tokens = FaucetTokens::default();
+        assert_eq!(tokens.symbols(), vec!["AMA"]);
+        assert_eq!(tokens.resolve(None), Ok(("AMA", 100)));
+        assert_eq!(tokens.resolve(Some(" ")), Ok(("AMA", 100)));
+    }
+
+    #[test]
+    fn configured_symbols_resolve_case_insensitively() {
+        let tokens = tokens();
+        assert_eq!(tokens.symbols(), vec!["AMA", "TESTX"]);
+        assert_eq!(tokens.resolve(Some("testx")), Ok(("TESTX", 5000)));
+        assert_eq!(tokens.resolve(None), Ok(("AMA", 100)));
+        assert_eq!(
+            tokens.resolve(Some("USDC")),
+            Err("the faucet does not hand out USDC; available: AMA, TESTX".to_string())
+        );
+    }
+
+    #[test]
+    fn bad_token_maps_are_rejected() {
+        for (value, error) in [
+            ("{}", "at least one token is required"),
+            (r#"{"AMA":0}"#, "AMA: amount must be at least 1"),
+            (r#"{" AMA":5}"#, "invalid symbol \" AMA\""),
+        ] {
+            assert_eq!(value.parse::<FaucetTokens>(), Err(error.to_string()), "{}", value);
+        }
+        assert!(r#"["AMA"]"#.parse::<FaucetTokens>().unwrap_err().starts_with("expected a JSON object"));
+        assert!(r#"{"AMA":-1}"#.parse::<FaucetTokens>().is_err());
+    }
+
+    #[tokio::test]
+    async fn a_second_claim_of_the_same_symbol_waits_a_day() {
+        let ledger = Ledger::default();
+        claim(&ledger, "1.2.3.4", "AMA", NOW).await.unwrap();
+        assert_eq!(
+            claim(&ledger, "1.2.3.4", "AMA", NOW + 90.0 * 60.0).await,
+            Err("can only claim AMA once per day, wait 22h 30m".to_string())
+        );
+        claim(&ledger, "1.2.3.4", "AMA", NOW + CLAIM_COOLDOWN_SECS).await.unwrap();
+        assert_eq!(ledger.claims.borrow()[&("1.2.3.4".to_string(), "AMA".to_string())].1, NOW + CLAIM_COOLDOWN_SECS);
+    }
+
+    #[tokio::test]
+    async fn cooldowns_are_independent_per_symbol() {
+        let ledger = Ledger::default();
+        claim(&ledger, "1.2.3.4", "AMA", NOW).await.unwrap();
+        claim(&ledger, "1.2.3.4", "TESTX", NOW + HOUR).await.unwrap();
+        assert!(claim(&ledger, "1.2.3.4", "TESTX", NOW + 2.0 * HOUR).await.unwrap_err().starts_with("can only claim TESTX"));
+        // The AMA cooldown still runs from the AMA claim, not the later TESTX one
+        assert!(claim(&ledger, "1.2.3.4", "AMA", NOW + 2.0 * HOUR).await.unwrap_err().ends_with("wait 22h 0m"));
+        claim(&ledger, "1.2.3.4", "AMA", NOW + CLAIM_COOLDOWN_SECS).await.unwrap();
+        assert!(claim(&ledger, "1.2.3.4", "TESTX", NOW + CLAIM_COOLDOWN_SECS).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn cooldowns_are_independent_per_ip() {
+        let ledger = Ledger::default();
+        claim(&ledger, "1.2.3.4", "AMA", NOW).await.unwrap();
+        claim(&ledger, "5.6.7.8", "AMA", NOW).await.unwrap();
+        assert_eq!(ledger.claims.borrow().len(), 2);
+    }
+}
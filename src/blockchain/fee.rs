@@ -0,0 +1,128 @@
+//! Fee estimates before signing. Nodes expose no fee endpoint, so the estimate applies the
+//! protocol's byte rule to the transaction as it will be submitted: the packed `TxU`, whose
+//! hash and signature have fixed lengths and can be sized before the signature exists.
+
+use super::address::Address;
+use super::decimals::{DecimalsRegistry, AMA_DECIMALS};
+use super::decode::BlobKind;
+use super::transfer_check::{self, TransferRequest};
+use crate::wasm::tx::{self, Tx, TxAction, TxU};
+use serde::Serialize;
+
+/// Bytes of the SHA-256 hash a signed transaction carries.
+const HASH_LEN: usize = 32;
+/// Bytes of a compressed BLS12-381 G2 signature.
+const SIGNATURE_LEN: usize = 96;
+/// Atoms in a hundredth of an AMA, the unit the byte rule charges in.
+const ATOMS_PER_CENT: u64 = 10_000_000;
+/// Cents every transaction pays, whatever its size.
+pub const FEE_BASE_CENTS: u64 = 1;
+/// Signed bytes covered by each further cent.
+pub const FEE_BYTES_PER_CENT: u64 = 1024;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FeeEstimate {
+    /// What was sized: a given blob, or a transfer drafted from its fields
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<BlobKind>,
+    /// Bytes of the unsigned `Tx`, the blob create_transaction returns
+    pub tx_bytes: usize,
+    /// Bytes of the signed `TxU` as submitted, which the fee is charged on
+    pub signed_bytes: usize,
+    /// AMA atoms
+    pub fee_atoms: u64,
+    /// Whole AMA
+    pub fee: f64,
+    /// "byte_rule": worked out locally, as no node endpoint quotes fees
+    pub basis: &'static str,
+}
+
+impl FeeEstimate {
+    /// Sizes a base58 blob, unsigned from create_transaction or already signed.
+    pub fn for_blob(blob_b58: &str) -> Result<Self, String> {
+        let bytes = bs58::decode(blob_b58.trim())
+            .into_vec()
+            .map_err(|e| format!("blob is not valid base58: {}", e))?;
+        if let Ok(tx) = vecpak::from_slice::<Tx>(&bytes) {
+            return Self::for_tx(tx, Some(BlobKind::Unsigned));
+        }
+        let signed = tx::decode_signed(&bytes)
+            .map_err(|_| "blob is neither an unsigned (Tx) nor a signed (TxU) transaction".to_string())?;
+        let tx_bytes = vecpak::to_vec(&signed.tx).map_err(|_| "failed to re-encode the signed transaction".to_string())?;
+        Ok(Self::new(Some(BlobKind::Signed), tx_bytes.len(), bytes.len()))
+    }
+
+    /// Sizes the Coin.transfer `req` describes, encoded as create_transaction would with a
+    /// clock nonce. A decimal amount is converted with `decimals`.
+    pub fn for_transfer(req: &TransferRequest, decimals: &DecimalsRegistry) -> Result<Self, String> {
+        let source = Address::parse(&req.source).map_err(|e| format!("source {}: {}", req.source, e))?;
+        let destination = Address::parse(&req.destination).map_err(|e| format!("destination {}: {}", req.destination, e))?;
+        let atoms = transfer_check::parse_amount(&req.amount, decimals.get(&req.symbol))?;
+        let action = TxAction {
+            op: "call".to_string(),
+            contract: "Coin".to_string(),
+            function: "transfer".to_string(),
+            args: vec![destination.as_bytes().to_vec(), atoms.to_string().into_bytes(), req.symbol.as_bytes().to_vec()],
+            attached_symbol: None,
+            attached_amount: None,
+        };
+        Self::for_tx(Tx { signer: source.as_bytes().to_vec(), nonce: tx::clock_nonce(), action }, None)
+    }
+
+    fn for_tx(tx: Tx, kind: Option<BlobKind>) -> Result<Self, String> {
+        let tx_bytes = vecpak::to_vec(&tx).map_err(|_| "failed to encode tx".to_string())?.len();
+        let placeholder = TxU { hash: vec![0; HASH_LEN], signature: vec![0; SIGNATURE_LEN], tx };
+        let signed_bytes = vecpak::to_vec(&placeholder).map_err(|_| "failed to encode txu".to_string())?.len();
+        Ok(Self::new(kind, tx_bytes, signed_bytes))
+    }
+
+    fn new(kind: Option<BlobKind>, tx_bytes: usize, signed_bytes: usize) -> Self {
+        let fee_atoms = fee_atoms(signed_bytes);
+        Self {
+            kind,
+            tx_bytes,
+            signed_bytes,
+            fee_atoms,
+            fee: fee_atoms as f64 / 10f64.powi(AMA_DECIMALS as i32),
+            basis: "byte_rule",
+        }
+    }
+}
+
+/// The byte rule: a base charge plus a cent for each full `FEE_BYTES_PER_CENT` signed bytes.
+pub fn fee_atoms(signed_bytes: usize) -> u64 {
+    (FEE_BASE_CENTS + signed_bytes as u64 / FEE_BYTES_PER_CENT) * ATOMS_PER_CENT
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `(1 + bytes/1024) * 10_000_000`, as the API documents the fee for epoch 295 on.
+    fn byte_rule(signed_bytes: u64) -> u64 {
+        (1 + signed_bytes / 1024) * 10_000_000
+    }
+
+    #[test]
+    fn each_full_kilobyte_adds_a_cent() {
+        for (bytes, atoms) in [(0, 10_000_000), (1023, 10_000_000), (1024, 20_000_000), (2048, 30_000_000)] {
+            assert_eq!(fee_atoms(bytes), atoms, "{} bytes", bytes);
+            assert_eq!(fee_atoms(bytes), byte_rule(bytes as u64), "{} bytes", bytes);
+        }
+    }
+
+    #[test]
+    fn the_estimate_reports_whole_ama_alongside_atoms() {
+        let estimate = FeeEstimate::new(None, 900, 1100);
+        assert_eq!((estimate.fee_atoms, estimate.basis), (20_000_000, "byte_rule"));
+        assert_eq!(estimate.fee, 0.02);
+    }
+
+    #[test]
+    fn a_malformed_blob_is_not_sized() {
+        let not_base58 = FeeEstimate::for_blob("0OIl").unwrap_err();
+        assert!(not_base58.starts_with("blob is not valid base58"), "{}", not_base58);
+        let not_a_tx = FeeEstimate::for_blob(&bs58::encode([0xffu8; 40]).into_string()).unwrap_err();
+        assert_eq!(not_a_tx, "blob is neither an unsigned (Tx) nor a signed (TxU) transaction");
+    }
+}
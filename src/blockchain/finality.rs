@@ -0,0 +1,174 @@
+use super::emission::StatsSnapshot;
+use super::types::BlockEntry;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+pub const DEFAULT_FINALITY_SAMPLE: u32 = 32;
+pub const MAX_FINALITY_SAMPLE: u32 = 128;
+/// Only snapshots this recent count towards the entry production rate.
+pub const PACE_WINDOW_SECS: u64 = 600;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FinalityEstimate {
+    /// Expected seconds until a transaction submitted now is in a finalized entry
+    pub seconds: f64,
+    /// Range from the faster and slower production intervals observed
+    pub low_seconds: f64,
+    pub high_seconds: f64,
+    /// Entries between the tip and the newest finalized entry
+    pub finality_lag: u64,
+    pub seconds_per_entry: f64,
+    pub entries_sampled: usize,
+    /// Production intervals the rate was measured over
+    pub rate_samples: usize,
+}
+
+/// Entries the newest finalized entry trails the tip by, or `None` when no sampled entry
+/// is finalized (or none carries consensus data).
+pub fn finality_lag(entries: &[BlockEntry]) -> Option<u64> {
+    let tip = entries.iter().map(|e| e.header.height).max()?;
+    let finalized = entries
+        .iter()
+        .filter(|e| e.consensus.as_ref().is_some_and(|c| c.finality_reached))
+        .map(|e| e.header.height)
+        .max()?;
+    Some(tip.saturating_sub(finalized))
+}
+
+/// Seconds per entry for each interval in which the height advanced. Time spent while the
+/// height stood still is carried into the next advancing interval, so stalls show up as
+/// slow intervals rather than disappearing.
+pub fn entry_pace(snapshots: &[StatsSnapshot]) -> Vec<f64> {
+    let newest = snapshots.iter().map(|s| s.recorded_at).max().unwrap_or(0);
+    let mut recent = snapshots
+        .iter()
+        .filter(|s| s.recorded_at + PACE_WINDOW_SECS >= newest);
+
+    let Some(mut from) = recent.next() else {
+        return Vec::new();
+    };
+    let mut pace = Vec::new();
+    for snapshot in recent {
+        if snapshot.height > from.height && snapshot.recorded_at > from.recorded_at {
+            let entries = (snapshot.height - from.height) as f64;
+            pace.push((snapshot.recorded_at - from.recorded_at) as f64 / entries);
+            from = snapshot;
+        }
+    }
+    pace
+}
+
+/// Combines the finality lag seen in `entries` with the production rate recorded in
+/// `snapshots`. `None` without a finalized entry or at least two production intervals.
+pub fn estimate(entries: &[BlockEntry], snapshots: &[StatsSnapshot]) -> Option<FinalityEstimate> {
+    let finality_lag = finality_lag(entries)?;
+    let mut pace = entry_pace(snapshots);
+    if pace.len() < 2 {
+        return None;
+    }
+    pace.sort_by(f64::total_cmp);
+
+    let seconds_per_entry = pace.iter().sum::<f64>() / pace.len() as f64;
+    let percentile = |p: f64| pace[((pace.len() - 1) as f64 * p).round() as usize];
+    // A transaction submitted now lands in the next entry, which then has to finalize
+    let entries_needed = (finality_lag + 1) as f64;
+
+    Some(FinalityEstimate {
+        seconds: entries_needed * seconds_per_entry,
+        low_seconds: entries_needed * percentile(0.1),
+        high_seconds: entries_needed * percentile(0.9),
+        finality_lag,
+        seconds_per_entry,
+        entries_sampled: entries.len(),
+        rate_samples: pace.len(),
+    })
+}
+
+/// Response used when there is not enough data to estimate, rather than guessing.
+pub fn insufficient_data(entries_sampled: usize, rate_samples: usize) -> Value {
+    json!({
+        "status": "insufficient_data",
+        "entries_sampled": entries_sampled,
+        "rate_samples": rate_samples,
+        "message": "need a finalized entry among the sampled ones and at least two recorded production intervals; retry once the server has followed the tip for a while or sample more entries"
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Entries from `from` to `to` where everything up to `finalized` has reached finality.
+    fn entries(from: u64, to: u64, finalized: Option<u64>) -> Vec<BlockEntry> {
+        (from..=to)
+            .map(|height| {
+                serde_json::from_value(json!({
+                    "hash": format!("e{}", height),
+                    "header": {
+                        "slot": height, "height": height, "dr": "dr", "vr": "vr", "prev_hash": "p",
+                        "signer": "s", "root_tx": "rt", "root_validator": "rv", "prev_slot": height
+                    },
+                    "tx_count": 0,
+                    "consensus": { "score": 1.0, "finality_reached": finalized.is_some_and(|f| height <= f), "mut_hash": "m" }
+                }))
+                .unwrap()
+            })
+            .collect()
+    }
+
+    fn snapshots(points: &[(u64, u64)]) -> Vec<StatsSnapshot> {
+        points
+            .iter()
+            .map(|&(recorded_at, height)| StatsSnapshot { height, burned: 0.0, circulating: 0.0, recorded_at })
+            .collect()
+    }
+
+    #[test]
+    fn lag_is_measured_from_the_newest_finalized_entry() {
+        assert_eq!(finality_lag(&entries(100, 110, Some(107))), Some(3));
+        assert_eq!(finality_lag(&entries(100, 110, Some(110))), Some(0));
+        assert_eq!(finality_lag(&entries(100, 110, None)), None);
+        assert_eq!(finality_lag(&[]), None);
+    }
+
+    #[test]
+    fn steady_production_gives_a_narrow_estimate() {
+        // One entry every 2 seconds
+        let pace = snapshots(&[(1000, 100), (1010, 105), (1020, 110), (1030, 115)]);
+        let estimate = estimate(&entries(100, 115, Some(113)), &pace).unwrap();
+        assert_eq!(estimate.seconds_per_entry, 2.0);
+        assert_eq!(estimate.finality_lag, 2);
+        assert_eq!(estimate.seconds, 6.0);
+        assert_eq!((estimate.low_seconds, estimate.high_seconds), (6.0, 6.0));
+        assert_eq!((estimate.entries_sampled, estimate.rate_samples), (16, 3));
+    }
+
+    #[test]
+    fn a_stall_is_carried_into_the_next_advancing_interval() {
+        // The height stands still from 1010 to 1050, then moves one entry
+        let pace = entry_pace(&snapshots(&[(1000, 100), (1010, 110), (1030, 110), (1050, 110), (1060, 111)]));
+        assert_eq!(pace, vec![1.0, 50.0]);
+
+        let estimate = estimate(&entries(100, 111, Some(111)), &snapshots(&[(1000, 100), (1010, 110), (1060, 111)])).unwrap();
+        assert!(estimate.high_seconds > estimate.low_seconds);
+        assert_eq!(estimate.high_seconds, 50.0);
+        assert_eq!(estimate.low_seconds, 1.0);
+    }
+
+    #[test]
+    fn only_recent_snapshots_set_the_pace() {
+        let old = PACE_WINDOW_SECS + 100;
+        let pace = entry_pace(&snapshots(&[(1000, 0), (1000 + old - 20, 10), (1000 + old - 10, 20), (1000 + old, 30)]));
+        assert_eq!(pace, vec![1.0, 1.0]);
+    }
+
+    #[test]
+    fn too_little_data_is_not_guessed() {
+        let finalized = entries(100, 110, Some(108));
+        assert!(estimate(&finalized, &snapshots(&[(1000, 100), (1010, 105)])).is_none());
+        assert!(estimate(&entries(100, 110, None), &snapshots(&[(1000, 100), (1010, 105), (1020, 110)])).is_none());
+        // A chain that never advanced has no production intervals at all
+        assert!(entry_pace(&snapshots(&[(1000, 100), (1010, 100), (1020, 100)])).is_empty());
+        assert_eq!(insufficient_data(11, 1)["status"], "insufficient_data");
+    }
+}
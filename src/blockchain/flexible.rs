@@ -0,0 +1,157 @@
+//! Lenient deserializers for numeric tool arguments. Clients often send heights and limits
+//! as strings ("12345") or as whole floats (12345.0); both are accepted, while fractional
+//! values are rejected with a message naming the offending input.
+
+use serde::{de::Error, Deserialize, Deserializer};
+use serde_json::Value;
+
+fn whole_number(value: &Value) -> Result<i128, String> {
+    let parsed = match value {
+        Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+            (Some(i), _) => return Ok(i as i128),
+            (_, Some(u)) => return Ok(u as i128),
+            _ => n.as_f64(),
+        },
+        Value::String(s) => {
+            let s = s.trim();
+            if let Ok(i) = s.parse::<i128>() {
+                return Ok(i);
+            }
+            s.parse::<f64>().ok()
+        }
+        _ => return Err(format!("expected a whole number, got {}", value)),
+    };
+
+    match parsed {
+        Some(f) if f.is_finite() && f.fract() == 0.0 => Ok(f as i128),
+        Some(_) => Err(format!("expected a whole number, got {} (fractional values are not accepted)", value)),
+        None => Err(format!("expected a whole number, got {}", value)),
+    }
+}
+
+fn convert<T: TryFrom<i128>>(value: &Value) -> Result<T, String> {
+    let n = whole_number(value)?;
+    T::try_from(n).map_err(|_| format!("{} is out of range", n))
+}
+
+fn de_flexible<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: TryFrom<i128>,
+{
+    let value = Value::deserialize(deserializer)?;
+    convert(&value).map_err(D::Error::custom)
+}
+
+fn de_flexible_opt<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: TryFrom<i128>,
+{
+    match Option::<Value>::deserialize(deserializer)? {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => convert(&value).map(Some).map_err(D::Error::custom),
+    }
+}
+
+pub fn de_flexible_u64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    de_flexible(deserializer)
+}
+
+pub fn de_flexible_opt_u32<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u32>, D::Error> {
+    de_flexible_opt(deserializer)
+}
+
+pub fn de_flexible_opt_u64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+    de_flexible_opt(deserializer)
+}
+
+pub fn de_flexible_opt_i64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<i64>, D::Error> {
+    de_flexible_opt(deserializer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::types::{HeightQuery, TransactionHistoryQuery};
+    #[cfg(not(target_arch = "wasm32"))]
+    use rmcp::handler::server::wrapper::Parameters;
+    use serde_json::json;
+
+    #[derive(Debug, Deserialize)]
+    struct Signed {
+        #[serde(default, deserialize_with = "de_flexible_opt_i64")]
+        value: Option<i64>,
+    }
+
+    /// The worker reads tool arguments with `from_value`.
+    fn worker_height(args: Value) -> Result<u64, String> {
+        serde_json::from_value::<HeightQuery>(args).map(|q| q.height).map_err(|e| e.to_string())
+    }
+
+    /// The stdio server reads them through rmcp's `Parameters`, which deserializes the
+    /// arguments object the same way.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn server_height(args: Value) -> Result<u64, String> {
+        serde_json::from_value::<Parameters<HeightQuery>>(args).map(|Parameters(q)| q.height).map_err(|e| e.to_string())
+    }
+
+    fn both_paths(args: Value) -> Result<u64, String> {
+        let worker = worker_height(args.clone());
+        #[cfg(not(target_arch = "wasm32"))]
+        assert_eq!(worker, server_height(args), "the two paths disagree");
+        worker
+    }
+
+    #[test]
+    fn whole_numbers_in_any_form_are_accepted() {
+        for height in [json!(1250000), json!("1250000"), json!(" 1250000 "), json!(1250000.0), json!("1250000.0"), json!("1.25e6")] {
+            assert_eq!(both_paths(json!({ "height": height })), Ok(1_250_000), "{}", height);
+        }
+        assert_eq!(both_paths(json!({ "height": u64::MAX })), Ok(u64::MAX));
+        assert_eq!(both_paths(json!({ "height": u64::MAX.to_string() })), Ok(u64::MAX));
+    }
+
+    #[test]
+    fn fractional_values_are_rejected_by_name() {
+        for height in [json!(12.5), json!("12.5"), json!(0.1)] {
+            let e = both_paths(json!({ "height": height })).unwrap_err();
+            assert!(e.contains("fractional values are not accepted"), "{}", e);
+            assert!(e.contains(&height.to_string()), "{} does not name {}", e, height);
+        }
+    }
+
+    #[test]
+    fn non_numbers_are_rejected() {
+        for height in [json!("tip"), json!(true), json!([1]), json!({ "h": 1 })] {
+            let e = both_paths(json!({ "height": height })).unwrap_err();
+            assert!(e.contains("expected a whole number"), "{}", e);
+        }
+        assert!(both_paths(json!({})).unwrap_err().contains("missing field `height`"));
+    }
+
+    #[test]
+    fn out_of_range_values_are_rejected() {
+        assert!(both_paths(json!({ "height": -1 })).unwrap_err().contains("-1 is out of range"));
+        assert!(both_paths(json!({ "height": "18446744073709551616" })).unwrap_err().contains("out of range"));
+
+        let e = serde_json::from_value::<TransactionHistoryQuery>(json!({ "address": "a", "limit": 4294967296u64 })).unwrap_err();
+        assert!(e.to_string().contains("4294967296 is out of range"), "{}", e);
+    }
+
+    #[test]
+    fn optional_values_may_be_absent_or_null() {
+        let query: TransactionHistoryQuery = serde_json::from_value(json!({ "address": "a", "limit": "25", "offset": null })).unwrap();
+        assert_eq!((query.limit, query.offset), (Some(25), None));
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let Parameters(query) =
+                serde_json::from_value::<Parameters<TransactionHistoryQuery>>(json!({ "address": "a", "limit": "25" })).unwrap();
+            assert_eq!((query.limit, query.offset), (Some(25), None));
+        }
+
+        assert_eq!(serde_json::from_value::<Signed>(json!({})).unwrap().value, None);
+        assert_eq!(serde_json::from_value::<Signed>(json!({ "value": "-7" })).unwrap().value, Some(-7));
+        assert!(serde_json::from_value::<Signed>(json!({ "value": "-7.5" })).is_err());
+    }
+}
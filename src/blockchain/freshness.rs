@@ -0,0 +1,267 @@
+use super::clock;
+use super::types::Transaction;
+use crate::wasm::tx::Tx;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+
+/// Nonces further ahead of the clock than this are flagged; create_transaction nonces are
+/// nanosecond timestamps.
+pub const FUTURE_NONCE_TOLERANCE_SECS: u64 = 600;
+/// Blobs built by this server longer ago than this get an age note even when still usable.
+pub const STALE_BLOB_SECS: u64 = 3600;
+/// How long the stdio server remembers the blobs it built, for checking them at submit.
+pub const BLOB_ORIGIN_TTL: Duration = Duration::from_secs(24 * 3600);
+pub const MAX_BLOB_ORIGINS: usize = 1024;
+/// Newest transactions read to find the signer's latest nonce.
+pub const NONCE_HISTORY_LIMIT: u32 = 20;
+
+/// Where a blob's nonce came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NonceBasis {
+    /// Taken from the clock at build time
+    Clock,
+    /// Passed by the caller
+    Explicit,
+    /// Derived from the signer's latest nonce and the chain's time, because the local
+    /// clock was skewed
+    Chain,
+}
+
+/// What this server recorded when it built a blob.
+#[derive(Debug, Clone, Serialize)]
+pub struct BlobOrigin {
+    pub built_at: u64,
+    pub nonce_basis: NonceBasis,
+}
+
+/// The parts of an unsigned blob the freshness check needs.
+#[derive(Debug, Clone)]
+pub struct BlobNonce {
+    pub signer: String,
+    pub nonce: i128,
+    /// Same as create_transaction's transaction_hash
+    pub transaction_hash: String,
+}
+
+pub fn read_blob(blob_b58: &str) -> Result<BlobNonce, &'static str> {
+    let bytes = bs58::decode(blob_b58).into_vec().map_err(|_| "invalid blob base58")?;
+    let tx: Tx = vecpak::from_slice(&bytes).map_err(|_| "failed to decode tx")?;
+    Ok(BlobNonce {
+        signer: bs58::encode(&tx.signer).into_string(),
+        nonce: tx.nonce,
+        transaction_hash: bs58::encode(Sha256::digest(&bytes)).into_string(),
+    })
+}
+
+/// Highest nonce among the transactions `address` signed in `history`.
+pub fn latest_nonce(address: &str, history: &[Transaction]) -> Option<i128> {
+    history
+        .iter()
+        .filter(|tx| tx.tx.signer == address)
+        .map(|tx| tx.tx.nonce as i128)
+        .max()
+}
+
+/// An account's latest nonce and the one to build its next transaction with. Nonces are
+/// strings because they do not fit a JSON number exactly.
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountNonce {
+    pub address: String,
+    /// Highest nonce the account has signed with; "0" when it never sent a transaction
+    pub nonce: String,
+    pub has_transacted: bool,
+    /// Above `nonce` and no earlier than now, in nanoseconds like clock nonces
+    pub next_nonce: String,
+}
+
+impl AccountNonce {
+    /// Reads the latest nonce of `address` from its newest transactions in `history`.
+    pub fn new(address: &str, history: &[Transaction], now_secs: u64) -> Self {
+        let latest = latest_nonce(address, history);
+        Self {
+            address: address.to_string(),
+            nonce: latest.unwrap_or(0).to_string(),
+            has_transacted: latest.is_some(),
+            next_nonce: clock::chain_nonce(latest, now_secs).to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FreshnessStatus {
+    /// The nonce is above everything the signer has used and not far in the future
+    Fresh,
+    /// The signer already used this nonce or a higher one; the node will reject the blob
+    Consumed,
+    /// The nonce is far ahead of the clock
+    Gapped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Freshness {
+    pub status: FreshnessStatus,
+    /// Nonces as decimal strings, since they exceed what JSON numbers hold exactly
+    pub blob_nonce: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latest_account_nonce: Option<String>,
+    /// `this_server` when the blob was built here, `unknown` otherwise
+    pub origin: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub built_at: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub age_secs: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce_basis: Option<NonceBasis>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+}
+
+/// Judges a blob's nonce against the signer's latest on-chain nonce and the clock. `origin`
+/// is known only for blobs this server built; it adds the build age but does not change
+/// the status.
+pub fn evaluate(blob_nonce: i128, latest: Option<i128>, origin: Option<&BlobOrigin>, now_secs: u64) -> Freshness {
+    let age_secs = origin.map(|o| now_secs.saturating_sub(o.built_at));
+    let future_limit = (now_secs + FUTURE_NONCE_TOLERANCE_SECS) as i128 * 1_000_000_000;
+
+    let (status, warning) = match latest {
+        Some(latest) if blob_nonce <= latest => (
+            FreshnessStatus::Consumed,
+            Some(format!(
+                "nonce {} is not above the signer's latest nonce {}: another transaction from this signer landed after the blob was built; rebuild it with create_transaction and sign again",
+                blob_nonce, latest
+            )),
+        ),
+        _ if blob_nonce > future_limit => (
+            FreshnessStatus::Gapped,
+            Some(format!(
+                "nonce {} is more than {} seconds ahead of the clock; the node may reject it",
+                blob_nonce, FUTURE_NONCE_TOLERANCE_SECS
+            )),
+        ),
+        _ => (
+            FreshnessStatus::Fresh,
+            age_secs
+                .filter(|age| *age > STALE_BLOB_SECS)
+                .map(|age| format!("built {} minutes ago; the nonce is still usable", age / 60)),
+        ),
+    };
+
+    Freshness {
+        status,
+        blob_nonce: blob_nonce.to_string(),
+        latest_account_nonce: latest.map(|n| n.to_string()),
+        origin: if origin.is_some() { "this_server" } else { "unknown" },
+        built_at: origin.map(|o| o.built_at),
+        age_secs,
+        nonce_basis: origin.map(|o| o.nonce_basis),
+        warning,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const NOW: u64 = 1_718_000_000;
+    const SECOND: i128 = 1_000_000_000;
+
+    fn at(secs: u64) -> i128 {
+        secs as i128 * SECOND
+    }
+
+    fn built(ago: u64) -> BlobOrigin {
+        BlobOrigin { built_at: NOW - ago, nonce_basis: NonceBasis::Clock }
+    }
+
+    fn signed(signer: &str, nonce: i64) -> Transaction {
+        serde_json::from_value(json!({
+            "hash": format!("h{}", nonce),
+            "metadata": { "entry_hash": "entry", "entry_height": 1 },
+            "signature": "sig",
+            "result": { "error": "ok" },
+            "tx": {
+                "action": { "args": [], "function": "noop", "op": "call", "contract": "Contract" },
+                "nonce": nonce,
+                "signer": signer
+            },
+            "receipt": { "success": true, "result": "ok", "logs": [], "exec_used": "0" }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn a_nonce_at_or_below_the_latest_is_consumed() {
+        for blob_nonce in [at(NOW - 60), at(NOW - 120)] {
+            let freshness = evaluate(blob_nonce, Some(at(NOW - 60)), Some(&built(300)), NOW);
+            assert_eq!(freshness.status, FreshnessStatus::Consumed);
+            assert_eq!(freshness.latest_account_nonce, Some(at(NOW - 60).to_string()));
+            assert!(freshness.warning.unwrap().contains("rebuild it with create_transaction"));
+        }
+    }
+
+    #[test]
+    fn a_nonce_above_the_latest_is_fresh() {
+        let freshness = evaluate(at(NOW - 10), Some(at(NOW - 60)), Some(&built(10)), NOW);
+        assert_eq!(freshness.status, FreshnessStatus::Fresh);
+        assert_eq!(freshness.warning, None);
+
+        // An account that never transacted has nothing to consume the nonce
+        let first = evaluate(at(NOW), None, None, NOW);
+        assert_eq!((first.status, first.latest_account_nonce), (FreshnessStatus::Fresh, None));
+    }
+
+    #[test]
+    fn an_old_but_usable_blob_gets_an_age_note() {
+        let freshness = evaluate(at(NOW - 7200), Some(at(NOW - 9000)), Some(&built(7200)), NOW);
+        assert_eq!(freshness.status, FreshnessStatus::Fresh);
+        assert_eq!(freshness.warning.as_deref(), Some("built 120 minutes ago; the nonce is still usable"));
+        assert_eq!(evaluate(at(NOW), None, Some(&built(STALE_BLOB_SECS)), NOW).warning, None);
+    }
+
+    #[test]
+    fn a_nonce_far_ahead_of_the_clock_is_gapped() {
+        let limit = at(NOW + FUTURE_NONCE_TOLERANCE_SECS);
+        assert_eq!(evaluate(limit, None, None, NOW).status, FreshnessStatus::Fresh);
+        let gapped = evaluate(limit + 1, Some(at(NOW - 60)), None, NOW);
+        assert_eq!(gapped.status, FreshnessStatus::Gapped);
+        assert!(gapped.warning.unwrap().contains("ahead of the clock"));
+        // Consumed wins over gapped: the latest nonce is already even further ahead
+        assert_eq!(evaluate(limit + 1, Some(limit + 2), None, NOW).status, FreshnessStatus::Consumed);
+    }
+
+    #[test]
+    fn only_blobs_built_here_report_their_origin() {
+        let unknown = evaluate(at(NOW), None, None, NOW);
+        assert_eq!(unknown.origin, "unknown");
+        assert_eq!((unknown.built_at, unknown.age_secs, unknown.nonce_basis), (None, None, None));
+        let json = serde_json::to_value(&unknown).unwrap();
+        assert!(json.get("built_at").is_none() && json.get("nonce_basis").is_none());
+
+        let origin = BlobOrigin { built_at: NOW - 90, nonce_basis: NonceBasis::Chain };
+        let here = evaluate(at(NOW), None, Some(&origin), NOW);
+        assert_eq!(here.origin, "this_server");
+        assert_eq!((here.built_at, here.age_secs, here.nonce_basis), (Some(NOW - 90), Some(90), Some(NonceBasis::Chain)));
+        assert_eq!(serde_json::to_value(&here).unwrap()["nonce_basis"], "chain");
+    }
+
+    #[test]
+    fn the_latest_nonce_only_counts_transactions_the_address_signed() {
+        let history = vec![signed("A", 5), signed("B", 9), signed("A", 7), signed("A", 6)];
+        assert_eq!(latest_nonce("A", &history), Some(7));
+        assert_eq!(latest_nonce("C", &history), None);
+
+        let account = AccountNonce::new("C", &history, NOW);
+        assert_eq!((account.nonce.as_str(), account.has_transacted), ("0", false));
+        assert!(account.next_nonce.parse::<i128>().unwrap() >= at(NOW));
+    }
+
+    #[test]
+    fn unreadable_blobs_are_rejected() {
+        assert_eq!(read_blob("0OIl").unwrap_err(), "invalid blob base58");
+        assert_eq!(read_blob(&bs58::encode([1, 2, 3]).into_string()).unwrap_err(), "failed to decode tx");
+    }
+}
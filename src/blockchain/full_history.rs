@@ -0,0 +1,139 @@
+//! Collecting an account's whole transaction history, page by page, for exports that need
+//! every transaction rather than one page.
+
+use super::clock;
+use super::types::Transaction;
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// Page size when the caller gives none.
+pub const DEFAULT_FULL_HISTORY_PAGE: u32 = 100;
+/// Most transactions one fetch collects unless `MCP_MAX_FULL_HISTORY` says otherwise.
+pub const MAX_FULL_HISTORY: u64 = 10_000;
+/// Pages are read oldest first, so transactions arriving mid-fetch land after the pages
+/// already read instead of shifting them.
+pub const FULL_HISTORY_SORT: &str = "asc";
+
+/// Handed to the page callback after each page.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct HistoryPage {
+    /// From 1
+    pub page: u32,
+    /// Offset the page was requested at
+    pub offset: u32,
+    /// Rows the node returned for the page
+    pub rows: usize,
+    /// Distinct transactions collected so far
+    pub collected: usize,
+    /// The fetch's cap, for progress reporting
+    pub max_transactions: u64,
+}
+
+/// Accumulates pages, dropping transactions a shifted page repeats.
+#[derive(Debug, Clone)]
+pub struct HistoryCollector {
+    max_transactions: u64,
+    seen: HashSet<String>,
+    transactions: Vec<Transaction>,
+    pages: u32,
+    rows: u32,
+    duplicates: u32,
+    exhausted: bool,
+}
+
+impl HistoryCollector {
+    pub fn new(max_transactions: u64) -> Self {
+        Self {
+            max_transactions,
+            seen: HashSet::new(),
+            transactions: Vec::new(),
+            pages: 0,
+            rows: 0,
+            duplicates: 0,
+            exhausted: false,
+        }
+    }
+
+    /// Offset of the next page: every row the node returned so far, duplicates included.
+    pub fn offset(&self) -> u32 {
+        self.rows
+    }
+
+    pub fn wants_more(&self) -> bool {
+        !self.exhausted && (self.transactions.len() as u64) < self.max_transactions
+    }
+
+    /// Adds a page requested with `page_size`; a shorter page ends the history.
+    pub fn push(&mut self, page: Vec<Transaction>, page_size: u32) -> HistoryPage {
+        let offset = self.rows;
+        let rows = page.len();
+        self.pages += 1;
+        self.rows += rows as u32;
+        self.exhausted = (rows as u32) < page_size;
+        for tx in page {
+            if (self.transactions.len() as u64) >= self.max_transactions {
+                self.exhausted = false;
+                break;
+            }
+            if self.seen.insert(tx.hash.clone()) {
+                self.transactions.push(tx);
+            } else {
+                self.duplicates += 1;
+            }
+        }
+        HistoryPage {
+            page: self.pages,
+            offset,
+            rows,
+            collected: self.transactions.len(),
+            max_transactions: self.max_transactions,
+        }
+    }
+
+    /// Heights of the oldest and newest collected transactions, to look their timestamps up.
+    pub fn height_bounds(&self) -> Option<(u64, u64)> {
+        let heights = self.transactions.iter().map(|tx| tx.metadata.entry_height);
+        Some((heights.clone().min()?, heights.max()?))
+    }
+
+    /// `first_timestamp` and `last_timestamp` are the entry timestamps at `height_bounds`.
+    pub fn finish(self, address: &str, first_timestamp: Option<u64>, last_timestamp: Option<u64>) -> FullHistory {
+        let bounds = self.height_bounds();
+        FullHistory {
+            address: address.to_string(),
+            count: self.transactions.len(),
+            pages: self.pages,
+            duplicates: self.duplicates,
+            complete: self.exhausted,
+            max_transactions: self.max_transactions,
+            first_height: bounds.map(|(first, _)| first),
+            last_height: bounds.map(|(_, last)| last),
+            first_timestamp: first_timestamp.map(clock::entry_secs),
+            last_timestamp: last_timestamp.map(clock::entry_secs),
+            transactions: self.transactions,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FullHistory {
+    pub address: String,
+    pub count: usize,
+    pub pages: u32,
+    /// Rows dropped because an earlier page already had them
+    pub duplicates: u32,
+    /// False when the cap stopped the fetch before the history ended
+    pub complete: bool,
+    pub max_transactions: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_height: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_height: Option<u64>,
+    /// Unix seconds of the oldest transaction's entry
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_timestamp: Option<u64>,
+    /// Unix seconds of the newest transaction's entry
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_timestamp: Option<u64>,
+    pub transactions: Vec<Transaction>,
+}
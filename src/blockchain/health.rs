@@ -0,0 +1,142 @@
+//! Whether the configured node answers and keeps up with the chain: one timed chain/stats
+//! call, with its height compared to a reference node's when one is configured.
+
+use super::types::ChainStats;
+use serde::Serialize;
+
+/// Entries the node may trail the reference by before it counts as stale.
+pub const STALE_HEIGHT_LAG: u64 = 10;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeHealth {
+    pub url: String,
+    pub reachable: bool,
+    /// Round trip of the chain/stats call, retries included
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tx_pool_size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reference_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reference_height: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reference_error: Option<String>,
+    /// Reference height minus the node's; negative when the node is ahead
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height_lag: Option<i64>,
+    /// True when the node trails the reference by more than `STALE_HEIGHT_LAG` entries
+    pub stale: bool,
+}
+
+impl NodeHealth {
+    /// `node` is the node's stats and the call's latency in milliseconds; `reference` the
+    /// reference node's URL and stats, when one is configured.
+    pub fn new(
+        url: &str,
+        node: Result<(ChainStats, u64), String>,
+        reference: Option<(&str, Result<ChainStats, String>)>,
+    ) -> Self {
+        let (stats, latency_ms, error) = match node {
+            Ok((stats, latency_ms)) => (Some(stats), Some(latency_ms), None),
+            Err(e) => (None, None, Some(e)),
+        };
+        let (reference_url, reference) = match reference {
+            Some((url, stats)) => (Some(url.to_string()), Some(stats)),
+            None => (None, None),
+        };
+        let reference_height = reference.as_ref().and_then(|r| r.as_ref().ok()).map(|s| s.height);
+        let height = stats.as_ref().map(|s| s.height);
+        let height_lag = height.zip(reference_height).map(|(height, reference)| reference as i64 - height as i64);
+        Self {
+            url: url.to_string(),
+            reachable: stats.is_some(),
+            latency_ms,
+            height,
+            tx_pool_size: stats.and_then(|s| s.tx_pool_size),
+            error,
+            reference_url,
+            reference_height,
+            reference_error: reference.and_then(Result::err),
+            height_lag,
+            stale: height_lag.is_some_and(|lag| lag > STALE_HEIGHT_LAG as i64),
+        }
+    }
+
+    /// Why the node should not be trusted, if it should not.
+    pub fn warning(&self) -> Option<String> {
+        if let Some(error) = &self.error {
+            return Some(format!("node {} is unreachable: {}", self.url, error));
+        }
+        if self.stale {
+            return Some(format!(
+                "node {} is {} entries behind {}; its answers may be stale",
+                self.url,
+                self.height_lag.unwrap_or_default(),
+                self.reference_url.as_deref().unwrap_or("the reference node")
+            ));
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const NODE: &str = "https://node.example";
+    const REFERENCE: &str = "https://reference.example";
+
+    fn stats(height: u64) -> ChainStats {
+        serde_json::from_value(json!({ "height": height, "tx_pool_size": 3 })).unwrap()
+    }
+
+    #[test]
+    fn a_node_in_step_with_the_reference_is_healthy() {
+        let health = NodeHealth::new(NODE, Ok((stats(100), 42)), Some((REFERENCE, Ok(stats(100 + STALE_HEIGHT_LAG)))));
+        assert!(health.reachable && !health.stale);
+        assert_eq!((health.height, health.latency_ms, health.tx_pool_size), (Some(100), Some(42), Some(3)));
+        assert_eq!((health.reference_height, health.height_lag), (Some(110), Some(10)));
+        assert_eq!(health.warning(), None);
+    }
+
+    #[test]
+    fn trailing_past_the_lag_is_stale() {
+        let health = NodeHealth::new(NODE, Ok((stats(100), 5)), Some((REFERENCE, Ok(stats(111)))));
+        assert!(health.stale);
+        assert_eq!(
+            health.warning().unwrap(),
+            "node https://node.example is 11 entries behind https://reference.example; its answers may be stale"
+        );
+
+        // Ahead of the reference is a negative lag, never stale
+        let ahead = NodeHealth::new(NODE, Ok((stats(120), 5)), Some((REFERENCE, Ok(stats(100)))));
+        assert_eq!((ahead.height_lag, ahead.stale), (Some(-20), false));
+    }
+
+    #[test]
+    fn an_unreachable_node_says_why() {
+        let health = NodeHealth::new(NODE, Err("connection refused".into()), Some((REFERENCE, Ok(stats(100)))));
+        assert!(!health.reachable && !health.stale);
+        assert_eq!((health.height, health.latency_ms, health.height_lag), (None, None, None));
+        assert_eq!(health.warning().unwrap(), "node https://node.example is unreachable: connection refused");
+    }
+
+    #[test]
+    fn a_failing_reference_leaves_the_lag_unknown() {
+        let health = NodeHealth::new(NODE, Ok((stats(100), 5)), Some((REFERENCE, Err("HTTP 502".into()))));
+        assert_eq!(health.reference_error.as_deref(), Some("HTTP 502"));
+        assert_eq!((health.reference_height, health.height_lag, health.stale), (None, None, false));
+        assert_eq!(health.warning(), None);
+
+        let alone = NodeHealth::new(NODE, Ok((stats(100), 5)), None);
+        let json = serde_json::to_value(&alone).unwrap();
+        assert!(json.get("reference_url").is_none() && json.get("height_lag").is_none());
+        assert_eq!(json["stale"], false);
+    }
+}
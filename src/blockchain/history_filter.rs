@@ -0,0 +1,148 @@
+//! Filters for get_transaction_history. The node only pages an account's history, so the
+//! clients pull pages and keep the matching transactions until enough are found.
+
+use super::clock;
+use super::investigate;
+use super::trace;
+use super::types::Transaction;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Matching transactions returned when a filtered call gives no limit.
+pub const DEFAULT_FILTERED_LIMIT: u32 = 20;
+/// History page size while scanning.
+pub const HISTORY_SCAN_PAGE: u32 = 100;
+/// Most raw transactions one filtered call scans; the rest is reached through `next_offset`.
+pub const MAX_HISTORY_SCAN: u32 = 2_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    /// Signed by the account
+    Sent,
+    /// Transfers to the account signed by someone else
+    Received,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HistoryFilter {
+    pub direction: Option<Direction>,
+    pub symbol: Option<String>,
+    /// Unix seconds, inclusive
+    pub after_timestamp: Option<u64>,
+    /// Unix seconds, exclusive
+    pub before_timestamp: Option<u64>,
+    pub counterparty: Option<String>,
+}
+
+impl HistoryFilter {
+    pub fn is_empty(&self) -> bool {
+        self.direction.is_none()
+            && self.symbol.is_none()
+            && self.counterparty.is_none()
+            && !self.needs_timestamps()
+    }
+
+    /// Whether matching needs the timestamps of the transactions' entries.
+    pub fn needs_timestamps(&self) -> bool {
+        self.after_timestamp.is_some() || self.before_timestamp.is_some()
+    }
+
+    /// Whether `tx` in the history of `address` passes every filter but the time range.
+    pub fn matches(&self, address: &str, tx: &Transaction) -> bool {
+        let transfer = trace::transfer(tx);
+        let direction_ok = match self.direction {
+            None => true,
+            Some(Direction::Sent) => tx.tx.signer == address,
+            Some(Direction::Received) => tx.tx.signer != address && transfer.as_ref().is_some_and(|t| t.to == address),
+        };
+        let symbol_ok = self.symbol.as_deref().is_none_or(|wanted| {
+            let symbol = transfer.as_ref().map(|t| t.symbol).or(tx.tx.action.attached_symbol.as_deref());
+            symbol.is_some_and(|s| s.eq_ignore_ascii_case(wanted))
+        });
+        let counterparty_ok = self
+            .counterparty
+            .as_deref()
+            .is_none_or(|wanted| investigate::counterparty(tx, address).as_deref() == Some(wanted));
+        direction_ok && symbol_ok && counterparty_ok
+    }
+
+    /// Whether an entry timestamp falls in the time range. An entry without one only passes
+    /// when no range is set.
+    pub fn in_time_range(&self, entry_timestamp: Option<u64>) -> bool {
+        if !self.needs_timestamps() {
+            return true;
+        }
+        let Some(secs) = entry_timestamp.map(clock::entry_secs) else {
+            return false;
+        };
+        self.after_timestamp.is_none_or(|after| secs >= after) && self.before_timestamp.is_none_or(|before| secs < before)
+    }
+}
+
+/// Progress of a filtered scan through an account's history.
+#[derive(Debug, Clone)]
+pub struct HistoryScan {
+    start_offset: u32,
+    pub scanned: u32,
+    pub pages: u32,
+    pub exhausted: bool,
+    pub matched: Vec<Transaction>,
+}
+
+impl HistoryScan {
+    pub fn new(offset: Option<u32>) -> Self {
+        Self { start_offset: offset.unwrap_or(0), scanned: 0, pages: 0, exhausted: false, matched: Vec::new() }
+    }
+
+    /// Offset of the next transaction to look at.
+    pub fn offset(&self) -> u32 {
+        self.start_offset + self.scanned
+    }
+
+    /// Whether another page should be pulled to find `wanted` matches.
+    pub fn wants_more(&self, wanted: u32) -> bool {
+        !self.exhausted && (self.matched.len() as u32) < wanted && self.scanned < MAX_HISTORY_SCAN
+    }
+
+    pub fn finish(self) -> FilteredHistory<Vec<Transaction>> {
+        let next_offset = (!self.exhausted).then(|| self.offset());
+        FilteredHistory {
+            count: self.matched.len(),
+            scanned: self.scanned,
+            pages: self.pages,
+            complete: self.exhausted,
+            next_offset,
+            transactions: self.matched,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FilteredHistory<T> {
+    /// Matching transactions returned
+    pub count: usize,
+    /// Raw transactions looked at to find them, the cost of the filter
+    pub scanned: u32,
+    /// History pages pulled from the node
+    pub pages: u32,
+    /// True when the whole history after `offset` was scanned
+    pub complete: bool,
+    /// Pass as `offset` with the same filters to continue scanning
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_offset: Option<u32>,
+    pub transactions: T,
+}
+
+impl<T> FilteredHistory<T> {
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> FilteredHistory<U> {
+        FilteredHistory {
+            count: self.count,
+            scanned: self.scanned,
+            pages: self.pages,
+            complete: self.complete,
+            next_offset: self.next_offset,
+            transactions: f(self.transactions),
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use super::types::SubmitResponse;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+
+/// How long a submit result is remembered for replay.
+pub const IDEMPOTENCY_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+/// Most keys the native server keeps at once; the oldest is dropped beyond this.
+pub const MAX_IDEMPOTENCY_KEYS: usize = 1024;
+
+/// Storage key for a caller's idempotency key. `scope` is whoever submitted (a session or
+/// client address on the worker), so two callers picking the same key never collide, and
+/// the network is part of the key so a testnet submit cannot replay onto mainnet.
+pub fn scoped_key(scope: &str, network: Option<&str>, key: &str) -> String {
+    let mut hasher = Sha256::new();
+    for part in [scope, network.unwrap_or("mainnet"), key] {
+        hasher.update((part.len() as u64).to_be_bytes());
+        hasher.update(part.as_bytes());
+    }
+    format!("submit:{}", hex::encode(hasher.finalize()))
+}
+
+/// Only accepted submissions are remembered; a rejected or failed submit is attempted again
+/// when retried with the same key.
+pub fn should_record(response: &SubmitResponse) -> bool {
+    response.rejection.is_none() && response.tx_hash.is_some()
+}
+
+/// Why a recorded key refuses a submit of another transaction.
+pub const KEY_REUSED: &str = "idempotency key reused with a different transaction";
+
+/// An accepted submit as remembered under its key. The blob digest ties the key to the
+/// transaction it was first used for, so a retry can only ever replay that one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmitRecord {
+    pub blob_digest: String,
+    #[serde(flatten)]
+    pub response: SubmitResponse,
+}
+
+impl SubmitRecord {
+    /// The recorded result when `blob` is the one submitted, [`KEY_REUSED`] otherwise.
+    pub fn replay(&self, blob: &str) -> Result<&SubmitResponse, &'static str> {
+        if self.blob_digest == blob_digest(blob) {
+            Ok(&self.response)
+        } else {
+            Err(KEY_REUSED)
+        }
+    }
+}
+
+/// SHA-256 of the submitted blob, ignoring surrounding whitespace.
+pub fn blob_digest(blob: &str) -> String {
+    hex::encode(Sha256::digest(blob.trim().as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn keys_are_scoped_by_caller_and_network() {
+        let key = scoped_key("session-a", None, "k1");
+        assert_eq!(key, scoped_key("session-a", Some("mainnet"), "k1"));
+        assert!(key.starts_with("submit:"));
+        assert_ne!(key, scoped_key("session-b", None, "k1"));
+        assert_ne!(key, scoped_key("session-a", Some("testnet"), "k1"));
+        assert_ne!(key, scoped_key("session-a", None, "k2"));
+        // Parts are length-prefixed, so moving a boundary does not collide
+        assert_ne!(scoped_key("ab", None, "c"), scoped_key("a", None, "bc"));
+    }
+
+    #[test]
+    fn only_accepted_submits_are_recorded() {
+        let accepted = SubmitResponse::from_node(&json!({ "error": "ok" }), "hash".to_string());
+        let rejected = SubmitResponse::from_node(&json!({ "error": "invalid_signature" }), "hash".to_string());
+        assert!(should_record(&accepted));
+        assert!(!should_record(&rejected));
+    }
+
+    #[test]
+    fn a_record_replays_only_the_same_blob() {
+        let accepted = SubmitResponse::from_node(&json!({ "error": "ok" }), "hash".to_string());
+        let record = SubmitRecord { blob_digest: blob_digest("blob-a"), response: accepted };
+        assert_eq!(record.replay("blob-a").unwrap().tx_hash.as_deref(), Some("hash"));
+        assert_eq!(record.replay(" blob-a\n").unwrap().tx_hash.as_deref(), Some("hash"));
+        assert_eq!(record.replay("blob-b").unwrap_err(), KEY_REUSED);
+    }
+
+    #[test]
+    fn a_record_round_trips_through_storage() {
+        let accepted = SubmitResponse::from_node(&json!({ "error": "ok" }), "hash".to_string());
+        let record = SubmitRecord { blob_digest: blob_digest("blob-a"), response: accepted };
+        let stored = serde_json::to_value(&record).unwrap();
+        assert_eq!(stored["tx_hash"], "hash");
+        assert_eq!(stored["blob_digest"], blob_digest("blob-a"));
+        let loaded: SubmitRecord = serde_json::from_value(stored).unwrap();
+        assert!(loaded.replay("blob-a").is_ok());
+    }
+}
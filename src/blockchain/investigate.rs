@@ -0,0 +1,185 @@
+use super::compact::CompactTransaction;
+use super::consistency::Consistency;
+use super::labels::CounterpartyShare;
+use super::types::{AccountBalance, Transaction};
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+/// History page size used while walking both accounts.
+pub const INVESTIGATE_PAGE: u32 = 100;
+pub const DEFAULT_INVESTIGATE_BUDGET: u32 = 10;
+pub const MAX_INVESTIGATE_BUDGET: u32 = 50;
+
+/// The other side of a `Coin.transfer` involving `address`, if `tx` is one.
+pub fn counterparty(tx: &Transaction, address: &str) -> Option<String> {
+    let action = &tx.tx.action;
+    if action.contract != "Coin" || action.function != "transfer" {
+        return None;
+    }
+    let to = action.args.first()?;
+    if tx.tx.signer == address {
+        Some(to.clone())
+    } else if to == address {
+        Some(tx.tx.signer.clone())
+    } else {
+        None
+    }
+}
+
+/// What the fetched history pages say about how two accounts relate.
+#[derive(Debug, Clone, Serialize)]
+pub struct Relationship {
+    /// Transfers between the two accounts, in either direction, oldest first
+    pub direct: Vec<CompactTransaction>,
+    pub a_to_b: usize,
+    pub b_to_a: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_interaction_height: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_interaction_height: Option<u64>,
+    /// Accounts both addresses transferred with, excluding each other
+    pub shared_counterparties: Vec<String>,
+}
+
+impl Relationship {
+    pub fn from_histories(a: &str, b: &str, history_a: &[Transaction], history_b: &[Transaction]) -> Self {
+        // A transfer between the two shows up in both histories, so dedupe by hash
+        let mut seen = BTreeSet::new();
+        let mut direct: Vec<&Transaction> = history_a
+            .iter()
+            .chain(history_b)
+            .filter(|tx| counterparty(tx, a).as_deref() == Some(b))
+            .filter(|tx| seen.insert(tx.hash.as_str()))
+            .collect();
+        direct.sort_by_key(|tx| tx.metadata.entry_height);
+
+        let a_to_b = direct.iter().filter(|tx| tx.tx.signer == a).count();
+        let counterparties = |history: &[Transaction], of: &str, other: &str| -> BTreeSet<String> {
+            history
+                .iter()
+                .filter_map(|tx| counterparty(tx, of))
+                .filter(|cp| cp != other && cp != of)
+                .collect()
+        };
+        let shared = counterparties(history_a, a, b)
+            .intersection(&counterparties(history_b, b, a))
+            .cloned()
+            .collect();
+
+        Self {
+            a_to_b,
+            b_to_a: direct.len() - a_to_b,
+            first_interaction_height: direct.first().map(|tx| tx.metadata.entry_height),
+            last_interaction_height: direct.last().map(|tx| tx.metadata.entry_height),
+            direct: direct.into_iter().map(CompactTransaction::from).collect(),
+            shared_counterparties: shared,
+        }
+    }
+}
+
+/// One side of an investigation: the balance and how much history was read.
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountCoverage {
+    pub balance: AccountBalance,
+    pub transactions_examined: usize,
+    /// True when the whole history was read, false when the budget ran out first
+    pub history_complete: bool,
+    /// Accounts this one transfers with most, within the history read
+    pub frequent_counterparties: Vec<CounterpartyShare>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Investigation {
+    pub address_a: AccountCoverage,
+    pub address_b: AccountCoverage,
+    pub relationship: Relationship,
+    /// Tip heights around the reads; not consistent when the chain moved mid-investigation
+    pub consistency: Consistency,
+    /// Balance and history reads; the two tip reads that bracket them are not counted
+    pub requests_used: u32,
+    pub max_requests: u32,
+    /// True when the answer is partial because the request budget was spent
+    pub budget_exhausted: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const A: &str = "alice";
+    const B: &str = "bob";
+
+    fn call(hash: &str, signer: &str, function: &str, to: &str, height: u64) -> Transaction {
+        serde_json::from_value(json!({
+            "hash": hash,
+            "metadata": { "entry_hash": format!("entry{}", height), "entry_height": height },
+            "signature": "sig",
+            "result": { "error": "ok" },
+            "tx": {
+                "action": { "args": [to, "1000000000", "AMA"], "function": function, "op": "call", "contract": "Coin" },
+                "nonce": height,
+                "signer": signer
+            },
+            "receipt": { "success": true, "result": "ok", "logs": [], "exec_used": "0" }
+        }))
+        .unwrap()
+    }
+
+    fn transfer(hash: &str, from: &str, to: &str, height: u64) -> Transaction {
+        call(hash, from, "transfer", to, height)
+    }
+
+    #[test]
+    fn counterparty_is_the_other_side_of_a_coin_transfer() {
+        assert_eq!(counterparty(&transfer("t", A, B, 1), A).as_deref(), Some(B));
+        assert_eq!(counterparty(&transfer("t", A, B, 1), B).as_deref(), Some(A));
+        assert_eq!(counterparty(&transfer("t", A, B, 1), "carol"), None);
+        assert_eq!(counterparty(&call("t", A, "mint", B, 1), A), None);
+    }
+
+    #[test]
+    fn direct_transfers_are_deduplicated_across_both_histories_and_ordered() {
+        let history_a = vec![transfer("t3", B, A, 30), transfer("t1", A, B, 10), transfer("x", A, "carol", 20)];
+        let history_b = vec![transfer("t1", A, B, 10), transfer("t2", A, B, 25), transfer("t3", B, A, 30)];
+        let relationship = Relationship::from_histories(A, B, &history_a, &history_b);
+
+        let heights: Vec<u64> = relationship.direct.iter().map(|tx| tx.height).collect();
+        assert_eq!(heights, vec![10, 25, 30]);
+        assert_eq!((relationship.a_to_b, relationship.b_to_a), (2, 1));
+        assert_eq!(relationship.first_interaction_height, Some(10));
+        assert_eq!(relationship.last_interaction_height, Some(30));
+    }
+
+    #[test]
+    fn shared_counterparties_are_the_intersection_excluding_the_pair() {
+        let history_a = vec![
+            transfer("1", A, "carol", 1),
+            transfer("2", "dave", A, 2),
+            transfer("3", A, "erin", 3),
+            transfer("4", A, B, 4),
+            // Self-transfers and other calls do not make counterparties
+            transfer("5", A, A, 5),
+            call("6", A, "mint", "frank", 6),
+        ];
+        let history_b = vec![
+            transfer("7", "carol", B, 7),
+            transfer("8", B, "dave", 8),
+            transfer("4", A, B, 4),
+            transfer("9", B, "frank", 9),
+            transfer("10", B, B, 10),
+        ];
+        let relationship = Relationship::from_histories(A, B, &history_a, &history_b);
+        assert_eq!(relationship.shared_counterparties, vec!["carol".to_string(), "dave".to_string()]);
+        assert_eq!(relationship.direct.len(), 1);
+    }
+
+    #[test]
+    fn unrelated_accounts_have_an_empty_relationship() {
+        let relationship = Relationship::from_histories(A, B, &[transfer("1", A, "carol", 1)], &[transfer("2", B, "dave", 2)]);
+        assert!(relationship.direct.is_empty() && relationship.shared_counterparties.is_empty());
+        assert_eq!(relationship.first_interaction_height, None);
+        let output = serde_json::to_value(&relationship).unwrap();
+        assert!(output.get("first_interaction_height").is_none());
+    }
+}
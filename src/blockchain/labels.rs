@@ -0,0 +1,512 @@
+//! Address labels ("exchange hot wallet") added at runtime and shown next to addresses in
+//! tool outputs. Validation, matching and the counterparty heuristic are pure; the stdio
+//! server keeps the book in a file next to the disk cache and the worker in D1.
+
+use super::investigate::counterparty;
+use super::types::{is_valid_address, Transaction};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// User labels kept; adding more fails until some are replaced.
+pub const MAX_LABELS: usize = 10_000;
+pub const MAX_LABEL_LEN: usize = 64;
+/// File the stdio server keeps the labels in, inside `MCP_DISK_CACHE_DIR`.
+pub const LABELS_FILE: &str = "address_labels.json";
+/// Share of an account's transfers a counterparty needs before it is suggested for a label.
+pub const SUGGEST_MIN_SHARE_PERCENT: usize = 25;
+/// Transfers with a counterparty needed for a suggestion, so short histories suggest nothing.
+pub const SUGGEST_MIN_TRANSFERS: usize = 3;
+/// Frequent counterparties reported per account.
+pub const MAX_FREQUENT_COUNTERPARTIES: usize = 5;
+/// Output fields holding a single address; a labelled one gets a `<field>_label` sibling.
+pub const ADDRESS_FIELDS: &[&str] = &["address", "signer", "from", "to", "destination", "counterparty", "emission_address"];
+/// Output field listing an account's frequent counterparties; unlabelled ones get a suggestion.
+pub const FREQUENT_COUNTERPARTIES_FIELD: &str = "frequent_counterparties";
+
+/// Labels every deployment knows. User labels can neither relabel these addresses nor
+/// take these names.
+const SYSTEM_LABELS: &[(&str, &str)] = &[("111111111111111111111111111111111111111111111111", "burn")];
+/// System contract names, reserved so a label never reads like a contract call target.
+const RESERVED_LABELS: &[&str] = &["Coin", "Epoch", "Contract", "Nft"];
+
+/// The built-in label of `address`, if it has one.
+pub fn system_label(address: &str) -> Option<&'static str> {
+    SYSTEM_LABELS.iter().find(|(a, _)| *a == address).map(|(_, label)| *label)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct AddressLabel {
+    pub address: String,
+    pub label: String,
+    /// Unix seconds the label was set; filled in on import when missing
+    #[serde(default)]
+    pub added_at: u64,
+}
+
+/// Why a label was refused, and which argument to fix.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LabelRejection {
+    pub field: &'static str,
+    pub reason: String,
+}
+
+impl LabelRejection {
+    fn new(field: &'static str, reason: impl Into<String>) -> Self {
+        Self { field, reason: reason.into() }
+    }
+}
+
+impl std::fmt::Display for LabelRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.field, self.reason)
+    }
+}
+
+/// Checks a user label for `address` and returns it trimmed.
+pub fn validate(address: &str, label: &str) -> Result<String, LabelRejection> {
+    if !is_valid_address(address) {
+        return Err(LabelRejection::new("address", "not a base58 48-byte public key"));
+    }
+    if let Some(builtin) = system_label(address) {
+        return Err(LabelRejection::new("address", format!("already has the built-in label '{}'", builtin)));
+    }
+    let label = label.trim();
+    if label.is_empty() {
+        return Err(LabelRejection::new("label", "must not be empty"));
+    }
+    if label.chars().count() > MAX_LABEL_LEN {
+        return Err(LabelRejection::new("label", format!("longer than {} characters", MAX_LABEL_LEN)));
+    }
+    if label.chars().any(char::is_control) {
+        return Err(LabelRejection::new("label", "must not contain control characters"));
+    }
+    let builtin = SYSTEM_LABELS.iter().map(|(_, l)| *l).chain(RESERVED_LABELS.iter().copied());
+    if let Some(taken) = builtin.into_iter().find(|b| b.eq_ignore_ascii_case(label)) {
+        return Err(LabelRejection::new("label", format!("'{}' is a built-in label", taken)));
+    }
+    Ok(label.to_string())
+}
+
+/// What setting a label did.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum LabelChange {
+    Added,
+    Updated { previous_label: String },
+    Unchanged,
+}
+
+/// Outcome of an import.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportReport {
+    pub added: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub rejected: Vec<RejectedLabel>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RejectedLabel {
+    pub address: String,
+    pub label: String,
+    pub reason: String,
+}
+
+impl ImportReport {
+    pub fn count(&mut self, change: &LabelChange) {
+        match change {
+            LabelChange::Added => self.added += 1,
+            LabelChange::Updated { .. } => self.updated += 1,
+            LabelChange::Unchanged => self.unchanged += 1,
+        }
+    }
+
+    pub fn reject(&mut self, entry: &AddressLabel, rejection: &LabelRejection) {
+        self.rejected.push(RejectedLabel {
+            address: entry.address.clone(),
+            label: entry.label.clone(),
+            reason: rejection.to_string(),
+        });
+    }
+}
+
+/// User labels by address.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LabelBook {
+    labels: BTreeMap<String, AddressLabel>,
+}
+
+impl LabelBook {
+    /// Labels `address`, replacing its previous label.
+    pub fn set(&mut self, address: &str, label: &str, now: u64) -> Result<LabelChange, LabelRejection> {
+        let label = validate(address, label)?;
+        let change = match self.labels.get(address) {
+            Some(existing) if existing.label == label => return Ok(LabelChange::Unchanged),
+            Some(existing) => LabelChange::Updated { previous_label: existing.label.clone() },
+            None if self.labels.len() >= MAX_LABELS => {
+                return Err(LabelRejection::new("address", format!("at most {} labels are kept; replace one instead", MAX_LABELS)));
+            }
+            None => LabelChange::Added,
+        };
+        self.labels
+            .insert(address.to_string(), AddressLabel { address: address.to_string(), label, added_at: now });
+        Ok(change)
+    }
+
+    /// The label shown for `address`: its built-in one, else the user's.
+    pub fn get(&self, address: &str) -> Option<&str> {
+        system_label(address).or_else(|| self.labels.get(address).map(|l| l.label.as_str()))
+    }
+
+    /// The user labels, by address.
+    pub fn export(&self) -> Vec<AddressLabel> {
+        self.labels.values().cloned().collect()
+    }
+
+    /// Adds `entries`, after dropping every existing label when `replace` is set. Invalid
+    /// entries are reported and skipped; the rest still apply.
+    pub fn import(&mut self, entries: &[AddressLabel], replace: bool, now: u64) -> ImportReport {
+        if replace {
+            self.labels.clear();
+        }
+        let mut report = ImportReport::default();
+        for entry in entries {
+            let added_at = if entry.added_at == 0 { now } else { entry.added_at };
+            match self.set(&entry.address, &entry.label, added_at) {
+                Ok(change) => report.count(&change),
+                Err(rejection) => report.reject(entry, &rejection),
+            }
+        }
+        report
+    }
+
+    /// The labels of those `addresses` that have one.
+    pub fn labels_for(&self, addresses: &[String]) -> HashMap<String, String> {
+        addresses
+            .iter()
+            .filter_map(|a| self.get(a).map(|label| (a.clone(), label.to_string())))
+            .collect()
+    }
+
+    /// Reads the labels from `dir`; missing or unreadable files start an empty book.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(dir: &str) -> Self {
+        std::fs::read(std::path::Path::new(dir).join(LABELS_FILE))
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    /// Writes the labels to `dir`, through a temporary file so a crash cannot truncate it.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self, dir: &str) -> std::io::Result<()> {
+        let path = std::path::Path::new(dir).join(LABELS_FILE);
+        let tmp = path.with_extension("json.tmp");
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(&tmp, serde_json::to_vec(self)?)?;
+        std::fs::rename(tmp, path)
+    }
+}
+
+/// Distinct values of the `ADDRESS_FIELDS` in `output`, at any depth.
+pub fn addresses_in(output: &Value) -> Vec<String> {
+    fn collect(value: &Value, found: &mut BTreeSet<String>) {
+        match value {
+            Value::Array(items) => items.iter().for_each(|item| collect(item, found)),
+            Value::Object(fields) => {
+                for (key, value) in fields {
+                    match value.as_str() {
+                        Some(address) if ADDRESS_FIELDS.contains(&key.as_str()) => {
+                            found.insert(address.to_string());
+                        }
+                        _ => collect(value, found),
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    let mut found = BTreeSet::new();
+    collect(output, &mut found);
+    found.into_iter().collect()
+}
+
+/// Adds `<field>_label` next to every address field of `output` that `labels` (or the
+/// built-in labels) name, and a label suggestion to unlabelled frequent counterparties.
+pub fn annotate(output: &mut Value, labels: &HashMap<String, String>) {
+    let label_of = |address: &str| labels.get(address).cloned().or_else(|| system_label(address).map(str::to_string));
+    match output {
+        Value::Array(items) => items.iter_mut().for_each(|item| annotate(item, labels)),
+        Value::Object(fields) => {
+            let found: Vec<(String, String)> = fields
+                .iter()
+                .filter(|(key, _)| ADDRESS_FIELDS.contains(&key.as_str()))
+                .filter_map(|(key, value)| Some((format!("{}_label", key), label_of(value.as_str()?)?)))
+                .collect();
+            for (key, value) in fields.iter_mut() {
+                annotate(value, labels);
+                if key == FREQUENT_COUNTERPARTIES_FIELD {
+                    suggest(value);
+                }
+            }
+            for (key, label) in found {
+                fields.insert(key, label.into());
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Suggests a label for each already annotated frequent counterparty that has none.
+fn suggest(counterparties: &mut Value) {
+    for entry in counterparties.as_array_mut().into_iter().flatten() {
+        if entry.get("address_label").is_some() {
+            continue;
+        }
+        let share = entry["share_percent"].as_u64().unwrap_or_default();
+        entry["suggestion"] = format!("seen in {}% of transfers; label it with add_address_label", share).into();
+    }
+}
+
+/// An account `address` transferred with often, by share of its transfers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CounterpartyShare {
+    pub address: String,
+    pub transfers: usize,
+    pub share_percent: usize,
+}
+
+/// The counterparties in at least `SUGGEST_MIN_SHARE_PERCENT` of the `Coin.transfer`s of
+/// `address` in `history` (and at least `SUGGEST_MIN_TRANSFERS` of them), most frequent first.
+pub fn frequent_counterparties(address: &str, history: &[Transaction]) -> Vec<CounterpartyShare> {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    let mut seen = BTreeSet::new();
+    let mut total = 0;
+    for tx in history.iter().filter(|tx| seen.insert(tx.hash.as_str())) {
+        if let Some(other) = counterparty(tx, address).filter(|other| other != address) {
+            *counts.entry(other).or_default() += 1;
+            total += 1;
+        }
+    }
+    let mut frequent: Vec<CounterpartyShare> = counts
+        .into_iter()
+        .filter(|(_, transfers)| *transfers >= SUGGEST_MIN_TRANSFERS && transfers * 100 >= total * SUGGEST_MIN_SHARE_PERCENT)
+        .map(|(address, transfers)| CounterpartyShare { address, transfers, share_percent: transfers * 100 / total })
+        .collect();
+    frequent.sort_by(|a, b| b.transfers.cmp(&a.transfers).then_with(|| a.address.cmp(&b.address)));
+    frequent.truncate(MAX_FREQUENT_COUNTERPARTIES);
+    frequent
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const A: &str = "29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw";
+    const B: &str = "42KfUgyU2XHCPwDD6g9gryCvtj9vqJAvM6h3Yzs3VRbuyT2JCHwvuk5iAbzXrioerf";
+    const BURN: &str = "111111111111111111111111111111111111111111111111";
+
+    fn address(n: u32) -> String {
+        let mut pk = [7u8; 48];
+        pk[..4].copy_from_slice(&n.to_be_bytes());
+        bs58::encode(pk).into_string()
+    }
+
+    fn label(address: &str, label: &str, added_at: u64) -> AddressLabel {
+        AddressLabel { address: address.to_string(), label: label.to_string(), added_at }
+    }
+
+    fn transfer(hash: &str, from: &str, to: &str) -> Transaction {
+        serde_json::from_value(json!({
+            "hash": hash,
+            "metadata": { "entry_hash": "entry", "entry_height": 1 },
+            "signature": "sig",
+            "result": { "error": "ok" },
+            "tx": {
+                "action": { "args": [to, "1000000000", "AMA"], "function": "transfer", "op": "call", "contract": "Coin" },
+                "nonce": 1,
+                "signer": from
+            },
+            "receipt": { "success": true, "result": "ok", "logs": [], "exec_used": "0" }
+        }))
+        .unwrap()
+    }
+
+    fn transfers(from: &str, to: &str, count: usize, prefix: &str) -> Vec<Transaction> {
+        (0..count).map(|i| transfer(&format!("{}{}", prefix, i), from, to)).collect()
+    }
+
+    #[test]
+    fn valid_labels_are_trimmed() {
+        assert_eq!(validate(A, "  exchange hot wallet ").unwrap(), "exchange hot wallet");
+        assert_eq!(validate(A, &"x".repeat(MAX_LABEL_LEN)).unwrap().len(), MAX_LABEL_LEN);
+    }
+
+    #[test]
+    fn invalid_labels_name_the_argument_to_fix() {
+        let rejected = |address: &str, label: &str| validate(address, label).unwrap_err().to_string();
+        assert_eq!(rejected("alice", "a"), "address: not a base58 48-byte public key");
+        assert_eq!(rejected(A, "   "), "label: must not be empty");
+        assert_eq!(rejected(A, &"x".repeat(MAX_LABEL_LEN + 1)), "label: longer than 64 characters");
+        assert_eq!(rejected(A, "hot\nwallet"), "label: must not contain control characters");
+    }
+
+    #[test]
+    fn built_in_addresses_and_names_cannot_be_taken() {
+        assert_eq!(system_label(BURN), Some("burn"));
+        assert_eq!(validate(BURN, "sink").unwrap_err().to_string(), "address: already has the built-in label 'burn'");
+        assert_eq!(validate(A, "BURN").unwrap_err().to_string(), "label: 'burn' is a built-in label");
+        for reserved in RESERVED_LABELS {
+            let err = validate(A, &reserved.to_lowercase()).unwrap_err();
+            assert_eq!(err.field, "label");
+            assert_eq!(err.reason, format!("'{}' is a built-in label", reserved));
+        }
+    }
+
+    #[test]
+    fn setting_a_label_reports_what_changed() {
+        let mut book = LabelBook::default();
+        assert_eq!(book.set(A, "exchange", 1), Ok(LabelChange::Added));
+        assert_eq!(book.set(A, " exchange ", 2), Ok(LabelChange::Unchanged));
+        assert_eq!(book.set(A, "cold wallet", 3), Ok(LabelChange::Updated { previous_label: "exchange".into() }));
+        assert_eq!(book.get(A), Some("cold wallet"));
+        assert_eq!(book.export(), vec![label(A, "cold wallet", 3)]);
+        assert!(book.set(A, "Coin", 4).is_err());
+        assert_eq!(book.get(A), Some("cold wallet"));
+        assert_eq!(book.get(BURN), Some("burn"));
+        assert_eq!(book.get(B), None);
+    }
+
+    #[test]
+    fn the_book_is_capped_but_existing_labels_can_still_change() {
+        let mut book = LabelBook::default();
+        for n in 0..MAX_LABELS as u32 {
+            book.set(&address(n), "bulk", 1).unwrap();
+        }
+        let err = book.set(&address(MAX_LABELS as u32), "one too many", 1).unwrap_err();
+        assert_eq!(err.field, "address");
+        assert!(err.reason.contains("at most 10000 labels"), "{}", err.reason);
+        assert!(matches!(book.set(&address(0), "renamed", 2), Ok(LabelChange::Updated { .. })));
+    }
+
+    #[test]
+    fn import_merges_or_replaces_and_reports_each_rejection() {
+        let mut book = LabelBook::default();
+        book.set(A, "exchange", 1).unwrap();
+        let entries = vec![label(A, "exchange", 5), label(B, "treasury", 0), label(BURN, "sink", 5), label(&address(1), "Epoch", 5)];
+
+        let report = book.import(&entries, false, 9);
+        assert_eq!((report.added, report.updated, report.unchanged), (1, 0, 1));
+        let reasons: Vec<&str> = report.rejected.iter().map(|r| r.reason.as_str()).collect();
+        assert_eq!(reasons, vec!["address: already has the built-in label 'burn'", "label: 'Epoch' is a built-in label"]);
+        assert_eq!(report.rejected[1].label, "Epoch");
+        assert_eq!(book.export().iter().find(|l| l.address == B).unwrap().added_at, 9, "missing added_at becomes now");
+
+        let report = book.import(&[label(B, "treasury", 3)], true, 10);
+        assert_eq!(report.added, 1);
+        assert_eq!(book.export(), vec![label(B, "treasury", 3)]);
+    }
+
+    #[test]
+    fn export_then_import_round_trips() {
+        let mut book = LabelBook::default();
+        book.set(A, "exchange", 1).unwrap();
+        book.set(B, "treasury", 2).unwrap();
+        let mut copy = LabelBook::default();
+        let report = copy.import(&book.export(), true, 99);
+        assert_eq!(report.added, 2);
+        assert_eq!(copy.export(), book.export());
+    }
+
+    #[test]
+    fn labels_survive_a_save_and_load() {
+        let dir = std::env::temp_dir().join(format!("amadeus-labels-{}", std::process::id()));
+        let dir = dir.to_str().unwrap();
+        assert!(LabelBook::load(dir).export().is_empty(), "a missing file starts an empty book");
+
+        let mut book = LabelBook::default();
+        book.set(A, "exchange", 1).unwrap();
+        book.save(dir).unwrap();
+        assert_eq!(LabelBook::load(dir).export(), book.export());
+
+        std::fs::write(std::path::Path::new(dir).join(LABELS_FILE), b"not json").unwrap();
+        assert!(LabelBook::load(dir).export().is_empty(), "an unreadable file starts an empty book");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn addresses_are_found_at_any_depth_and_labelled_in_place() {
+        let mut output = json!({
+            "address": A,
+            "txs": [{ "signer": A, "to": B, "amount": "5" }, { "from": BURN, "hash": A }],
+        });
+        assert_eq!(addresses_in(&output), vec![BURN.to_string(), A.to_string(), B.to_string()]);
+
+        let mut book = LabelBook::default();
+        book.set(A, "exchange", 1).unwrap();
+        let labels = book.labels_for(&addresses_in(&output));
+        annotate(&mut output, &labels);
+        assert_eq!(
+            output,
+            json!({
+                "address": A,
+                "address_label": "exchange",
+                "txs": [
+                    { "signer": A, "signer_label": "exchange", "to": B, "amount": "5" },
+                    { "from": BURN, "from_label": "burn", "hash": A },
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn frequent_counterparties_need_both_a_share_and_a_count() {
+        let mut history = transfers(A, "carol", 4, "c");
+        history.extend(transfers("dave", A, 3, "d"));
+        history.extend(transfers(A, "erin", 2, "e"));
+        history.extend(transfers(A, "frank", 3, "f"));
+        history.extend(transfers(A, "grace", 1, "g"));
+        // A repeated page must not count twice
+        history.extend(transfers(A, "carol", 4, "c"));
+        // Neither self transfers nor other contracts count
+        history.push(transfer("self", A, A));
+
+        let frequent = frequent_counterparties(A, &history);
+        let found: Vec<(&str, usize, usize)> =
+            frequent.iter().map(|c| (c.address.as_str(), c.transfers, c.share_percent)).collect();
+        // 13 transfers: carol's 4 clear the 25% share, dave's and frank's 3 do not
+        assert_eq!(found, vec![("carol", 4, 30)]);
+
+        // Short histories suggest nothing even at a full share
+        assert!(frequent_counterparties(A, &transfers(A, "carol", SUGGEST_MIN_TRANSFERS - 1, "c")).is_empty());
+    }
+
+    #[test]
+    fn counterparties_are_ordered_by_transfers_then_address() {
+        let mut history = transfers(A, "r", 3, "r");
+        history.extend(transfers(A, "q", 3, "q"));
+        history.extend(transfers(A, "p", 6, "p"));
+        let found: Vec<(String, usize)> =
+            frequent_counterparties(A, &history).into_iter().map(|c| (c.address, c.share_percent)).collect();
+        assert_eq!(found, vec![("p".to_string(), 50), ("q".to_string(), 25), ("r".to_string(), 25)]);
+    }
+
+    #[test]
+    fn unlabelled_frequent_counterparties_get_a_suggestion() {
+        let mut output = json!({
+            "address": A,
+            "frequent_counterparties": [
+                { "address": B, "transfers": 4, "share_percent": 40 },
+                { "address": BURN, "transfers": 3, "share_percent": 30 },
+            ],
+        });
+        annotate(&mut output, &HashMap::new());
+        let counterparties = &output["frequent_counterparties"];
+        assert_eq!(counterparties[0]["suggestion"], "seen in 40% of transfers; label it with add_address_label");
+        assert_eq!(counterparties[1]["address_label"], "burn");
+        assert!(counterparties[1].get("suggestion").is_none());
+    }
+}
@@ -0,0 +1,217 @@
+use super::batch::MAX_BATCH_CALLS;
+use super::block_time::MAX_TIME_PROBES;
+use super::error::{BlockchainError, Result};
+use super::full_history::MAX_FULL_HISTORY;
+use super::richlist::MAX_RICHLIST_LIMIT;
+use super::signers::MAX_SIGNER_WINDOW;
+use super::state_export::MAX_EXPORT_LIMIT;
+use serde::Serialize;
+use std::str::FromStr;
+
+/// How much one tool call may ask of the node, so self-hosted nodes on modest hardware
+/// can be protected. Checked by the clients before any request goes out; a tool argument
+/// above a limit fails with `limit_exceeded`. Shown in get_capabilities.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct QueryLimits {
+    /// Largest `limit` for one transaction history page; `None` leaves it to the node
+    pub max_history_limit: Option<u32>,
+    /// Most entries one window (signer distribution, liveness, finality) may span
+    pub max_block_span: u64,
+    /// Calls per request to the node's batch endpoint
+    pub max_batch_calls: usize,
+    /// Contract storage keys per export page
+    pub max_contract_keys: u64,
+    /// Holders per richlist page
+    pub max_richlist_limit: u64,
+    /// Transactions one get_full_transaction_history call collects
+    pub max_full_history: u64,
+    /// Entry fetches one get_block_by_timestamp search may make
+    pub max_time_probes: u64,
+}
+
+impl Default for QueryLimits {
+    /// The limits the tool schemas already had.
+    fn default() -> Self {
+        Self {
+            max_history_limit: None,
+            max_block_span: MAX_SIGNER_WINDOW,
+            max_batch_calls: MAX_BATCH_CALLS,
+            max_contract_keys: MAX_EXPORT_LIMIT,
+            max_richlist_limit: MAX_RICHLIST_LIMIT,
+            max_full_history: MAX_FULL_HISTORY,
+            max_time_probes: MAX_TIME_PROBES,
+        }
+    }
+}
+
+/// `MCP_QUERY_LIMITS` presets; the individual `MCP_MAX_*` settings override them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LimitsPreset {
+    #[default]
+    Default,
+    /// For a node on a small VPS or a home machine
+    Small,
+}
+
+impl FromStr for LimitsPreset {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "default" | "" => Ok(Self::Default),
+            "small" => Ok(Self::Small),
+            other => Err(format!("unknown query limits preset: {} (default or small)", other)),
+        }
+    }
+}
+
+impl LimitsPreset {
+    pub fn limits(self) -> QueryLimits {
+        match self {
+            Self::Default => QueryLimits::default(),
+            Self::Small => QueryLimits {
+                max_history_limit: Some(50),
+                max_block_span: 100,
+                max_batch_calls: 10,
+                max_contract_keys: 100,
+                max_richlist_limit: 50,
+                max_full_history: 1_000,
+                max_time_probes: 32,
+            },
+        }
+    }
+}
+
+impl QueryLimits {
+    pub fn check_history(&self, limit: Option<u32>) -> Result<()> {
+        match (limit, self.max_history_limit) {
+            (Some(limit), Some(max)) => check("limit", limit.into(), max.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// `argument` names the tool argument the span came from, for the error.
+    pub fn check_block_span(&self, argument: &str, blocks: u64) -> Result<()> {
+        check(argument, blocks, self.max_block_span)
+    }
+
+    pub fn check_contract_keys(&self, limit: u64) -> Result<()> {
+        check("limit", limit, self.max_contract_keys)
+    }
+
+    pub fn check_richlist(&self, limit: u64) -> Result<()> {
+        check("limit", limit, self.max_richlist_limit)
+    }
+
+    pub fn check_full_history(&self, max_transactions: u64) -> Result<()> {
+        check("max_transactions", max_transactions, self.max_full_history)
+    }
+
+    pub fn check_time_probes(&self, max_probes: u64) -> Result<()> {
+        check("max_probes", max_probes, self.max_time_probes)
+    }
+
+    /// History page size for scans that would use `preferred`, within the history limit.
+    pub fn history_page(&self, preferred: u32) -> u32 {
+        self.max_history_limit.map_or(preferred, |max| max.min(preferred))
+    }
+
+    /// Calls per batch request, never above what the batch endpoint accepts.
+    pub fn batch_size(&self) -> usize {
+        self.max_batch_calls.clamp(1, MAX_BATCH_CALLS)
+    }
+}
+
+fn check(argument: &str, requested: u64, max: u64) -> Result<()> {
+    if requested > max {
+        return Err(BlockchainError::LimitExceeded { argument: argument.to_string(), requested, max });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn small() -> QueryLimits {
+        LimitsPreset::Small.limits()
+    }
+
+    /// The argument, request and maximum a check failed with.
+    fn exceeded(result: Result<()>) -> (String, u64, u64) {
+        match result {
+            Err(BlockchainError::LimitExceeded { argument, requested, max }) => (argument, requested, max),
+            other => panic!("expected limit_exceeded, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn history_limit() {
+        assert!(QueryLimits::default().check_history(Some(u32::MAX)).is_ok());
+        assert!(small().check_history(None).is_ok());
+        assert!(small().check_history(Some(50)).is_ok());
+        assert_eq!(exceeded(small().check_history(Some(51))), ("limit".to_string(), 51, 50));
+        assert_eq!(QueryLimits::default().history_page(100), 100);
+        assert_eq!(small().history_page(100), 50);
+        assert_eq!(small().history_page(20), 20);
+    }
+
+    #[test]
+    fn block_span() {
+        assert!(QueryLimits::default().check_block_span("blocks", MAX_SIGNER_WINDOW).is_ok());
+        assert!(small().check_block_span("blocks", 100).is_ok());
+        assert_eq!(exceeded(small().check_block_span("end_height", 101)), ("end_height".to_string(), 101, 100));
+    }
+
+    #[test]
+    fn batch_calls() {
+        assert_eq!(QueryLimits::default().batch_size(), MAX_BATCH_CALLS);
+        assert_eq!(small().batch_size(), 10);
+        let limits = |max_batch_calls| QueryLimits { max_batch_calls, ..QueryLimits::default() };
+        assert_eq!(limits(0).batch_size(), 1);
+        assert_eq!(limits(MAX_BATCH_CALLS * 5).batch_size(), MAX_BATCH_CALLS);
+    }
+
+    #[test]
+    fn contract_keys() {
+        assert!(QueryLimits::default().check_contract_keys(MAX_EXPORT_LIMIT).is_ok());
+        assert_eq!(exceeded(small().check_contract_keys(101)), ("limit".to_string(), 101, 100));
+    }
+
+    #[test]
+    fn richlist_limit() {
+        assert!(QueryLimits::default().check_richlist(MAX_RICHLIST_LIMIT).is_ok());
+        assert_eq!(exceeded(small().check_richlist(51)), ("limit".to_string(), 51, 50));
+    }
+
+    #[test]
+    fn full_history() {
+        assert!(QueryLimits::default().check_full_history(MAX_FULL_HISTORY).is_ok());
+        assert_eq!(exceeded(small().check_full_history(1_001)), ("max_transactions".to_string(), 1_001, 1_000));
+    }
+
+    #[test]
+    fn time_probes() {
+        assert!(QueryLimits::default().check_time_probes(MAX_TIME_PROBES).is_ok());
+        assert_eq!(exceeded(small().check_time_probes(33)), ("max_probes".to_string(), 33, 32));
+    }
+
+    #[test]
+    fn the_small_preset_tightens_every_limit() {
+        let (default, small) = (QueryLimits::default(), small());
+        assert!(small.max_history_limit.is_some() && default.max_history_limit.is_none());
+        assert!(small.max_block_span < default.max_block_span);
+        assert!(small.max_batch_calls < default.max_batch_calls);
+        assert!(small.max_contract_keys < default.max_contract_keys);
+        assert!(small.max_richlist_limit < default.max_richlist_limit);
+        assert!(small.max_full_history < default.max_full_history);
+        assert!(small.max_time_probes < default.max_time_probes);
+    }
+
+    #[test]
+    fn presets_parse() {
+        assert_eq!(" Small ".parse::<LimitsPreset>(), Ok(LimitsPreset::Small));
+        assert_eq!("".parse::<LimitsPreset>(), Ok(LimitsPreset::Default));
+        assert_eq!("tiny".parse::<LimitsPreset>().unwrap_err(), "unknown query limits preset: tiny (default or small)");
+    }
+}
@@ -0,0 +1,197 @@
+use super::clock::entry_secs;
+use super::types::BlockEntry;
+use serde::Serialize;
+use std::time::Duration;
+
+/// Entries sampled for the longest gap when the caller does not say.
+pub const DEFAULT_LIVENESS_SAMPLE: u64 = 50;
+pub const MAX_LIVENESS_SAMPLE: u64 = 500;
+
+/// Silence after which the chain counts as degraded or stalled. Overridden by
+/// `MCP_LIVENESS_DEGRADED_SECS` and `MCP_LIVENESS_STALLED_SECS`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LivenessThresholds {
+    pub degraded: Duration,
+    pub stalled: Duration,
+}
+
+impl Default for LivenessThresholds {
+    fn default() -> Self {
+        Self { degraded: Duration::from_secs(15), stalled: Duration::from_secs(60) }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Healthy,
+    /// Entries are coming, but a recent gap or the current silence is past the
+    /// degraded threshold
+    Degraded,
+    /// No entry for longer than the stalled threshold
+    Stalled,
+    /// No entry timestamps to judge by
+    Unknown,
+}
+
+/// The verdict for `since_last_entry` seconds of silence and the longest gap between
+/// sampled entries.
+pub fn verdict(since_last_entry: Option<u64>, longest_gap: Option<u64>, thresholds: LivenessThresholds) -> Verdict {
+    let Some(since_last_entry) = since_last_entry else {
+        return Verdict::Unknown;
+    };
+    if since_last_entry >= thresholds.stalled.as_secs() {
+        Verdict::Stalled
+    } else if since_last_entry.max(longest_gap.unwrap_or(0)) >= thresholds.degraded.as_secs() {
+        Verdict::Degraded
+    } else {
+        Verdict::Healthy
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Liveness {
+    pub verdict: Verdict,
+    /// Seconds since the newest entry's timestamp
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since_last_entry_secs: Option<u64>,
+    /// Longest wait between consecutive sampled entries
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub longest_gap_secs: Option<u64>,
+    /// Height of the entry that ended the longest gap
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub longest_gap_at: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub txs_per_sec: Option<f64>,
+    pub entries_sampled: usize,
+    pub degraded_after_secs: u64,
+    pub stalled_after_secs: u64,
+}
+
+/// Judges liveness from sampled entries, the newest entry's timestamp (which may be
+/// newer than the sample, e.g. from the tip follower) and the node's reported rate.
+pub fn assess(
+    entries: &[BlockEntry], tip_timestamp: Option<u64>, txs_per_sec: Option<f64>, now: u64,
+    thresholds: LivenessThresholds,
+) -> Liveness {
+    let mut times: Vec<(u64, u64)> = entries
+        .iter()
+        .filter_map(|e| e.header.timestamp.map(|ts| (e.header.height, entry_secs(ts))))
+        .collect();
+    times.sort_unstable();
+    times.dedup_by_key(|(height, _)| *height);
+
+    let longest = times
+        .windows(2)
+        .map(|pair| (pair[1].1.saturating_sub(pair[0].1), pair[1].0))
+        .max_by_key(|(gap, _)| *gap);
+    let newest = times.iter().map(|(_, ts)| *ts).chain(tip_timestamp.map(entry_secs)).max();
+    let since_last_entry = newest.map(|ts| now.saturating_sub(ts));
+
+    Liveness {
+        verdict: verdict(since_last_entry, longest.map(|(gap, _)| gap), thresholds),
+        since_last_entry_secs: since_last_entry,
+        longest_gap_secs: longest.map(|(gap, _)| gap),
+        longest_gap_at: longest.map(|(_, height)| height),
+        txs_per_sec,
+        entries_sampled: entries.len(),
+        degraded_after_secs: thresholds.degraded.as_secs(),
+        stalled_after_secs: thresholds.stalled.as_secs(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn thresholds() -> LivenessThresholds {
+        LivenessThresholds { degraded: Duration::from_secs(15), stalled: Duration::from_secs(60) }
+    }
+
+    fn entry(height: u64, timestamp: Option<u64>) -> BlockEntry {
+        serde_json::from_value(json!({
+            "hash": format!("h{}", height),
+            "header": {
+                "slot": height, "height": height, "dr": "dr", "vr": "vr", "prev_hash": "prev", "signer": "signer",
+                "root_tx": "rt", "root_validator": "rv", "prev_slot": height.saturating_sub(1), "timestamp": timestamp
+            },
+            "tx_count": 0
+        }))
+        .unwrap()
+    }
+
+    /// Entries from height 1 at the given seconds.
+    fn sequence(times: &[u64]) -> Vec<BlockEntry> {
+        times.iter().enumerate().map(|(i, &ts)| entry(i as u64 + 1, Some(ts))).collect()
+    }
+
+    #[test]
+    fn verdict_follows_the_thresholds() {
+        let t = thresholds();
+        assert_eq!(verdict(None, Some(100), t), Verdict::Unknown);
+        assert_eq!(verdict(Some(0), None, t), Verdict::Healthy);
+        assert_eq!(verdict(Some(14), Some(14), t), Verdict::Healthy);
+        assert_eq!(verdict(Some(15), None, t), Verdict::Degraded);
+        assert_eq!(verdict(Some(2), Some(15), t), Verdict::Degraded);
+        assert_eq!(verdict(Some(59), Some(200), t), Verdict::Degraded);
+        assert_eq!(verdict(Some(60), Some(1), t), Verdict::Stalled);
+    }
+
+    #[test]
+    fn steady_production_is_healthy() {
+        let entries = sequence(&[NOW - 8, NOW - 6, NOW - 4, NOW - 2]);
+        let liveness = assess(&entries, None, Some(3.5), NOW, thresholds());
+        assert_eq!(liveness.verdict, Verdict::Healthy);
+        assert_eq!((liveness.since_last_entry_secs, liveness.longest_gap_secs), (Some(2), Some(2)));
+        assert_eq!(liveness.txs_per_sec, Some(3.5));
+        assert_eq!(liveness.entries_sampled, 4);
+        assert_eq!((liveness.degraded_after_secs, liveness.stalled_after_secs), (15, 60));
+    }
+
+    #[test]
+    fn a_past_gap_degrades_even_when_entries_resumed() {
+        let entries = sequence(&[NOW - 40, NOW - 38, NOW - 8, NOW - 6]);
+        let liveness = assess(&entries, None, None, NOW, thresholds());
+        assert_eq!(liveness.verdict, Verdict::Degraded);
+        assert_eq!((liveness.longest_gap_secs, liveness.longest_gap_at), (Some(30), Some(3)));
+    }
+
+    #[test]
+    fn silence_past_the_threshold_stalls() {
+        let entries = sequence(&[NOW - 94, NOW - 92, NOW - 90]);
+        let liveness = assess(&entries, None, None, NOW, thresholds());
+        assert_eq!(liveness.verdict, Verdict::Stalled);
+        assert_eq!(liveness.since_last_entry_secs, Some(90));
+    }
+
+    #[test]
+    fn a_newer_tip_timestamp_ends_the_silence() {
+        let entries = sequence(&[NOW - 94, NOW - 92, NOW - 90]);
+        // The follower's tip is in milliseconds, as nodes stamp entries
+        let liveness = assess(&entries, Some((NOW - 1) * 1000), None, NOW, thresholds());
+        assert_eq!(liveness.verdict, Verdict::Healthy);
+        assert_eq!(liveness.since_last_entry_secs, Some(1));
+    }
+
+    #[test]
+    fn samples_are_ordered_deduplicated_and_untimed_entries_skipped() {
+        let mut entries = vec![entry(3, Some(NOW - 2)), entry(1, Some(NOW - 10)), entry(2, None), entry(3, Some(NOW - 2))];
+        entries.push(entry(4, Some((NOW - 1) * 1000)));
+        let liveness = assess(&entries, None, None, NOW, thresholds());
+        assert_eq!((liveness.longest_gap_secs, liveness.longest_gap_at), (Some(8), Some(3)));
+        assert_eq!(liveness.since_last_entry_secs, Some(1));
+        assert_eq!(liveness.entries_sampled, 5);
+    }
+
+    #[test]
+    fn no_timestamps_is_unknown() {
+        let liveness = assess(&[entry(1, None)], None, None, NOW, thresholds());
+        assert_eq!(liveness.verdict, Verdict::Unknown);
+        assert_eq!(liveness.since_last_entry_secs, None);
+        assert_eq!(liveness.longest_gap_secs, None);
+        assert_eq!(serde_json::to_value(&liveness).unwrap()["verdict"], "unknown");
+    }
+}
@@ -0,0 +1,114 @@
+use serde::Serialize;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+/// Upstream activity of a single tool call, reported back to the agent as `timing`.
+#[derive(Debug, Default)]
+pub struct CallMetrics {
+    upstream_requests: AtomicU32,
+    retries: AtomicU32,
+    cache_hits: AtomicU32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Timing {
+    pub duration_ms: u64,
+    /// Requests sent to the node, retries included
+    pub upstream_requests: u32,
+    pub retries: u32,
+    /// Answers served from a cache or the tip follower instead of the node
+    pub cache_hits: u32,
+}
+
+impl CallMetrics {
+    pub fn upstream_request(&self) {
+        self.upstream_requests.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn retry(&self) {
+        self.retries.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn cache_hit(&self) {
+        self.cache_hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn timing(&self, duration: Duration) -> Timing {
+        Timing {
+            duration_ms: duration.as_millis() as u64,
+            upstream_requests: self.upstream_requests.load(Ordering::Relaxed),
+            retries: self.retries.load(Ordering::Relaxed),
+            cache_hits: self.cache_hits.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+tokio::task_local! {
+    /// Metrics of the tool call running on this task. Work spawned onto other tasks (the tip
+    /// follower, endpoint probes) is not attributed to any call.
+    pub static CALL_METRICS: std::sync::Arc<CallMetrics>;
+}
+
+/// Records into the current call's metrics, if a call is being measured on this task.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn record(f: impl FnOnce(&CallMetrics)) {
+    let _ = CALL_METRICS.try_with(|metrics| f(metrics));
+}
+
+/// Runs `future` under the current call's metrics, for work the call spawns onto other
+/// tasks.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn inherit<F: std::future::Future>(future: F) -> impl std::future::Future<Output = F::Output> {
+    let current = CALL_METRICS.try_with(std::sync::Arc::clone).ok();
+    async move {
+        match current {
+            Some(metrics) => CALL_METRICS.scope(metrics, future).await,
+            None => future.await,
+        }
+    }
+}
+
+/// Adds `timing` to a JSON object output; other outputs are left alone.
+pub fn attach(output: &mut serde_json::Value, timing: Timing) {
+    if let Some(fields) = output.as_object_mut() {
+        fields.insert("timing".to_string(), serde_json::json!(timing));
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn only_the_scoped_call_is_counted() {
+        record(|m| m.upstream_request());
+
+        let metrics = Arc::new(CallMetrics::default());
+        CALL_METRICS
+            .scope(metrics.clone(), async {
+                record(|m| m.upstream_request());
+                record(|m| m.retry());
+                // Work the call spawns is attributed to it only through `inherit`
+                tokio::spawn(async { record(|m| m.cache_hit()) }).await.unwrap();
+                tokio::spawn(inherit(async { record(|m| m.cache_hit()) })).await.unwrap();
+            })
+            .await;
+
+        let timing = metrics.timing(Duration::from_millis(1500));
+        assert_eq!(timing, Timing { duration_ms: 1500, upstream_requests: 1, retries: 1, cache_hits: 1 });
+    }
+
+    #[test]
+    fn timing_is_only_attached_to_objects() {
+        let timing = CallMetrics::default().timing(Duration::ZERO);
+        let mut object = serde_json::json!({ "height": 1 });
+        attach(&mut object, timing);
+        assert_eq!(object["timing"], serde_json::json!({ "duration_ms": 0, "upstream_requests": 0, "retries": 0, "cache_hits": 0 }));
+
+        let mut list = serde_json::json!([1, 2]);
+        attach(&mut list, timing);
+        assert_eq!(list, serde_json::json!([1, 2]));
+    }
+}
@@ -1,15 +1,87 @@
 #[cfg(not(target_arch = "wasm32"))]
+pub mod cache;
+pub mod action;
+pub mod address;
+pub mod analytics;
+pub mod api_path;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod batch;
+pub mod block_range;
+pub mod block_time;
+pub mod block_txs;
+pub mod budget;
+pub mod built;
+pub mod capabilities;
+pub mod clock;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod client;
 #[cfg(target_arch = "wasm32")]
 pub mod client_wasm;
 
+pub mod compact;
+pub mod confirm;
+pub mod consistency;
+pub mod correlate;
+pub mod decimals;
+pub mod decode;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod disk_cache;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod drift;
+pub mod emission;
+pub mod epoch;
 pub mod error;
+pub mod failover;
+pub mod faucet;
+pub mod fee;
+pub mod finality;
+pub mod flexible;
+pub mod freshness;
+pub mod full_history;
+pub mod health;
+pub mod history_filter;
+pub mod idempotency;
+pub mod investigate;
+pub mod labels;
+pub mod limits;
+pub mod liveness;
+pub mod metrics;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod offline;
+pub mod poll_plan;
+pub mod preview;
+pub mod range;
+pub mod recovery;
+pub mod rebuild;
+pub mod recent_blocks;
+pub mod rejection;
+pub mod reorg;
+pub mod richlist;
+pub mod server_key;
+pub mod session;
+pub mod signer_blocks;
+pub mod signers;
+pub mod signing;
+pub mod state_diff;
+pub mod state_export;
+pub mod symbol;
+pub mod templates;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod tip;
+pub mod token;
+pub mod trace;
+pub mod transfer_check;
+pub mod tx_pool;
 pub mod types;
+pub mod wait;
+pub mod watch;
 
 #[cfg(not(target_arch = "wasm32"))]
 pub use client::BlockchainClient;
 #[cfg(target_arch = "wasm32")]
 pub use client_wasm::BlockchainClient;
 
+pub use compact::{CompactBlockEntry, CompactEntryTxsPage, CompactList, CompactRichlist, CompactTransaction};
 pub use error::BlockchainError;
+pub use rejection::SubmitRejection;
 pub use types::*;
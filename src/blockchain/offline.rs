@@ -0,0 +1,188 @@
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// Node paths, after the API prefix, and the fixture answering them. Every path under a
+/// route gets the same answer, whatever address or height it names.
+pub const ROUTES: &[(&str, &str)] = &[
+    ("chain/stats", "stats"),
+    ("chain/tip", "tip"),
+    ("chain/height_with_txs/", "block_with_txs"),
+    ("wallet/balance_all/", "balances"),
+    ("chain/tx_events_by_account/", "history"),
+    ("peer/trainers", "trainers"),
+    ("contract/richlist", "richlist"),
+    ("contract/get/", "contract_state"),
+];
+
+/// Recorded node responses served instead of the network in offline mode.
+#[derive(Debug, Clone)]
+pub struct FixtureStore {
+    dir: PathBuf,
+}
+
+impl FixtureStore {
+    pub fn open(dir: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let dir = dir.into();
+        if !dir.is_dir() {
+            return Err(std::io::Error::new(std::io::ErrorKind::NotFound, "not a directory"));
+        }
+        Ok(Self { dir })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn scenario(path: &str) -> Option<&'static str> {
+        let path = path.strip_prefix("/api/")?;
+        let path = path.split('?').next().unwrap_or(path);
+        ROUTES
+            .iter()
+            .find(|(route, _)| if route.ends_with('/') { path.starts_with(route) } else { path == *route })
+            .map(|(_, scenario)| *scenario)
+    }
+
+    /// The recorded answer for a canonical (`/api/...`) node path, `None` when no fixture
+    /// covers it.
+    pub fn get(&self, canonical_path: &str) -> Option<Value> {
+        let scenario = Self::scenario(canonical_path)?;
+        let text = std::fs::read_to_string(self.dir.join(format!("{}.json", scenario))).ok()?;
+        let mut value = serde_json::from_str(&text).ok()?;
+        fill_placeholders(&mut value);
+        Some(value)
+    }
+
+    /// Routes whose fixture file is missing or does not parse.
+    pub fn missing(&self) -> Vec<&'static str> {
+        ROUTES
+            .iter()
+            .filter(|(_, scenario)| {
+                std::fs::read_to_string(self.dir.join(format!("{}.json", scenario)))
+                    .ok()
+                    .and_then(|text| serde_json::from_str::<Value>(&text).ok())
+                    .is_none()
+            })
+            .map(|(route, _)| *route)
+            .collect()
+    }
+}
+
+/// Puts values back in place of the placeholders `fixtures refresh` normalizes to, so
+/// the answers parse and stay the same from run to run.
+fn fill_placeholders(value: &mut Value) {
+    match value {
+        Value::String(s) => match s.as_str() {
+            "<height>" => *value = Value::from(1_000_000u64),
+            "<timestamp>" => *value = Value::from(1_700_000_000u64),
+            "<metric>" => *value = Value::from(0u64),
+            // 32 zero bytes in base58
+            "<hash>" => *value = Value::from("11111111111111111111111111111111"),
+            _ => {}
+        },
+        Value::Object(fields) => fields.values_mut().for_each(fill_placeholders),
+        Value::Array(items) => items.iter_mut().for_each(fill_placeholders),
+        _ => {}
+    }
+}
+
+/// A scratch fixture directory for tests, answering a client in place of a node and
+/// removed when dropped.
+#[cfg(test)]
+pub(crate) struct ScratchNode {
+    dir: PathBuf,
+}
+
+#[cfg(test)]
+impl ScratchNode {
+    pub(crate) fn new() -> Self {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir()
+            .join(format!("amadeus-mcp-node-{}-{}", std::process::id(), NEXT.fetch_add(1, Ordering::Relaxed)));
+        std::fs::create_dir_all(&dir).unwrap();
+        Self { dir }
+    }
+
+    /// Sets the answer for every path of a `ROUTES` scenario.
+    pub(crate) fn set(&self, scenario: &str, response: Value) -> &Self {
+        std::fs::write(self.dir.join(format!("{}.json", scenario)), response.to_string()).unwrap();
+        self
+    }
+
+    pub(crate) fn remove(&self, scenario: &str) {
+        let _ = std::fs::remove_file(self.dir.join(format!("{}.json", scenario)));
+    }
+
+    pub(crate) fn client(&self) -> super::BlockchainClient {
+        super::BlockchainClient::new(String::new()).unwrap().with_offline(FixtureStore::open(&self.dir).unwrap())
+    }
+}
+
+#[cfg(test)]
+impl Drop for ScratchNode {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn paths_under_a_route_share_its_fixture() {
+        for (path, scenario) in [
+            ("/api/chain/stats", Some("stats")),
+            ("/api/chain/stats?network=x", Some("stats")),
+            ("/api/wallet/balance_all/29Vit", Some("balances")),
+            ("/api/chain/height_with_txs/42", Some("block_with_txs")),
+            ("/api/contract/get/Coin/key", Some("contract_state")),
+            // Exact routes do not swallow longer paths
+            ("/api/chain/stats/extra", None),
+            ("/api/chain/tx/abc", None),
+            ("/chain/stats", None),
+        ] {
+            assert_eq!(FixtureStore::scenario(path), scenario, "{}", path);
+        }
+    }
+
+    #[test]
+    fn placeholders_are_filled_everywhere() {
+        let mut value = json!({
+            "stats": { "height": "<height>", "tps": "<metric>" },
+            "entries": [{ "hash": "<hash>", "ts": "<timestamp>", "label": "<other>" }]
+        });
+        fill_placeholders(&mut value);
+        assert_eq!(
+            value,
+            json!({
+                "stats": { "height": 1_000_000, "tps": 0 },
+                "entries": [{ "hash": "11111111111111111111111111111111", "ts": 1_700_000_000, "label": "<other>" }]
+            })
+        );
+        assert_eq!(bs58::decode("11111111111111111111111111111111").into_vec().unwrap(), vec![0u8; 32]);
+    }
+
+    #[test]
+    fn recorded_answers_are_served_and_gaps_reported() {
+        let node = ScratchNode::new();
+        node.set("stats", json!({ "error": "ok", "stats": { "height": "<height>" } }));
+        std::fs::write(node.dir.join("tip.json"), "not json").unwrap();
+        let store = FixtureStore::open(&node.dir).unwrap();
+
+        assert_eq!(store.get("/api/chain/stats"), Some(json!({ "error": "ok", "stats": { "height": 1_000_000 } })));
+        assert_eq!(store.get("/api/chain/tip"), None);
+        assert_eq!(store.get("/api/peer/trainers"), None);
+        let missing = store.missing();
+        assert!(missing.contains(&"chain/tip") && missing.contains(&"peer/trainers"));
+        assert!(!missing.contains(&"chain/stats"));
+        assert_eq!(missing.len(), ROUTES.len() - 1);
+    }
+
+    #[test]
+    fn a_missing_directory_is_an_error() {
+        let node = ScratchNode::new();
+        assert!(FixtureStore::open(node.dir.join("absent")).is_err());
+    }
+}
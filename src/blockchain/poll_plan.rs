@@ -0,0 +1,266 @@
+//! Scheduling for polled resource subscriptions: subscriptions backed by the same node
+//! request share one poll, polls are spread over the interval instead of firing together,
+//! and the interval backs off while the node is throttling.
+//!
+//! Pure planning only; the caller owns the timers and the requests.
+
+use super::error::BlockchainError;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(10);
+/// The interval never backs off past this.
+pub const MAX_POLL_INTERVAL: Duration = Duration::from_secs(300);
+/// Successful polls in a row before a backed-off interval steps back down.
+const RECOVERY_POLLS: u32 = 3;
+
+/// What answers a subscribed resource.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PollSource {
+    /// Already kept fresh by the tip follower; never polled separately
+    TipFollower,
+    /// A GET of this canonical node path; subscriptions with the same path share it
+    Endpoint(String),
+}
+
+/// The source of one of the server's resources, `None` for resources that never change.
+pub fn source_for(uri: &str) -> Option<PollSource> {
+    match uri.split('?').next().unwrap_or(uri) {
+        "amadeus://status" => Some(PollSource::TipFollower),
+        "amadeus://validators" => Some(PollSource::Endpoint("/api/peer/trainers".to_string())),
+        _ => None,
+    }
+}
+
+/// One node request per interval, at `offset` into it, refreshing every URI in `uris`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduledPoll {
+    pub path: String,
+    pub offset: Duration,
+    pub uris: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PollPlan {
+    pub interval: Duration,
+    pub polls: Vec<ScheduledPoll>,
+    /// Subscriptions refreshed whenever the tip follower moves
+    pub follower_uris: Vec<String>,
+}
+
+/// Groups `subscriptions` by source and staggers one poll per endpoint evenly across
+/// `interval`. Subscriptions without a source are left out.
+pub fn plan(subscriptions: &[String], interval: Duration) -> PollPlan {
+    plan_with(subscriptions, interval, source_for)
+}
+
+/// `plan` with the sources looked up by `source`.
+fn plan_with(subscriptions: &[String], interval: Duration, source: impl Fn(&str) -> Option<PollSource>) -> PollPlan {
+    let mut endpoints: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut follower_uris = Vec::new();
+    for uri in subscriptions {
+        match source(uri) {
+            Some(PollSource::TipFollower) => follower_uris.push(uri.clone()),
+            Some(PollSource::Endpoint(path)) => endpoints.entry(path).or_default().push(uri.clone()),
+            None => {}
+        }
+    }
+
+    let slot = interval / endpoints.len().max(1) as u32;
+    let polls = endpoints
+        .into_iter()
+        .enumerate()
+        .map(|(i, (path, uris))| ScheduledPoll { path, offset: slot * i as u32, uris })
+        .collect();
+    PollPlan { interval, polls, follower_uris }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PollOutcome {
+    Ok,
+    /// The node answered 429 or is unavailable; polling less often gives it room
+    Throttled,
+    /// Other failures say nothing about load
+    Failed,
+}
+
+impl PollOutcome {
+    pub fn of<T>(result: &Result<T, BlockchainError>) -> Self {
+        match result {
+            Ok(_) => Self::Ok,
+            Err(BlockchainError::UpstreamUnavailable { .. } | BlockchainError::NetworkRetryExhausted { .. }) => {
+                Self::Throttled
+            }
+            Err(_) => Self::Failed,
+        }
+    }
+}
+
+/// The polling interval, doubled on each throttled poll up to `MAX_POLL_INTERVAL` and
+/// halved back toward the base after `RECOVERY_POLLS` good polls in a row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PollInterval {
+    base: Duration,
+    current: Duration,
+    good_polls: u32,
+}
+
+impl PollInterval {
+    pub fn new(base: Duration) -> Self {
+        Self { base, current: base, good_polls: 0 }
+    }
+
+    pub fn current(&self) -> Duration {
+        self.current
+    }
+
+    /// Records a poll's outcome and returns the new interval when it changed, for the
+    /// caller to replan and tell subscribers.
+    pub fn record(&mut self, outcome: PollOutcome) -> Option<Duration> {
+        let previous = self.current;
+        match outcome {
+            PollOutcome::Throttled => {
+                self.good_polls = 0;
+                self.current = (self.current * 2).min(MAX_POLL_INTERVAL.max(self.base));
+            }
+            PollOutcome::Ok if self.current > self.base => {
+                self.good_polls += 1;
+                if self.good_polls >= RECOVERY_POLLS {
+                    self.good_polls = 0;
+                    self.current = (self.current / 2).max(self.base);
+                }
+            }
+            PollOutcome::Ok | PollOutcome::Failed => {}
+        }
+        (self.current != previous).then_some(self.current)
+    }
+}
+
+/// Logging-notification payload telling subscribers the polling interval changed.
+pub fn interval_changed_message(interval: Duration, reason: &str) -> serde_json::Value {
+    serde_json::json!({
+        "event": "pollIntervalChanged",
+        "interval_secs": interval.as_secs(),
+        "reason": reason,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uris(uris: &[&str]) -> Vec<String> {
+        uris.iter().map(|u| u.to_string()).collect()
+    }
+
+    #[test]
+    fn subscriptions_to_the_same_endpoint_share_a_poll() {
+        let plan = plan(
+            &uris(&["amadeus://validators", "amadeus://validators?offset=100&limit=50", "amadeus://status"]),
+            DEFAULT_POLL_INTERVAL,
+        );
+        assert_eq!(
+            plan.polls,
+            vec![ScheduledPoll {
+                path: "/api/peer/trainers".into(),
+                offset: Duration::ZERO,
+                uris: uris(&["amadeus://validators", "amadeus://validators?offset=100&limit=50"]),
+            }]
+        );
+        // Status is refreshed by the tip follower, never by its own poll
+        assert_eq!(plan.follower_uris, uris(&["amadeus://status"]));
+    }
+
+    #[test]
+    fn resources_that_never_change_are_not_polled() {
+        let plan = plan(&uris(&["amadeus://tools/get_balance", "other://thing"]), DEFAULT_POLL_INTERVAL);
+        assert!(plan.polls.is_empty() && plan.follower_uris.is_empty());
+        assert_eq!(source_for("amadeus://status?x=1"), Some(PollSource::TipFollower));
+        assert_eq!(source_for("amadeus://tools/get_balance"), None);
+    }
+
+    /// Resources named after the path that answers them, as balance subscriptions would be.
+    fn by_path(uri: &str) -> Option<PollSource> {
+        uri.strip_prefix("test://").map(|path| PollSource::Endpoint(format!("/api/{}", path)))
+    }
+
+    #[test]
+    fn polls_are_spread_evenly_across_the_interval() {
+        let subscriptions = uris(&["test://wallet/balance_all/b", "test://chain/stats", "test://wallet/balance_all/a", "test://chain/stats"]);
+        let plan = plan_with(&subscriptions, Duration::from_secs(12), by_path);
+        let polls: Vec<(&str, u64, usize)> =
+            plan.polls.iter().map(|p| (p.path.as_str(), p.offset.as_secs(), p.uris.len())).collect();
+        assert_eq!(
+            polls,
+            vec![("/api/chain/stats", 0, 2), ("/api/wallet/balance_all/a", 4, 1), ("/api/wallet/balance_all/b", 8, 1)]
+        );
+        assert_eq!(plan.interval, Duration::from_secs(12));
+        assert!(self::plan(&[], DEFAULT_POLL_INTERVAL).polls.is_empty());
+    }
+
+    #[test]
+    fn throttling_doubles_the_interval_up_to_the_cap() {
+        let mut interval = PollInterval::new(DEFAULT_POLL_INTERVAL);
+        let seen: Vec<Option<u64>> =
+            (0..7).map(|_| interval.record(PollOutcome::Throttled).map(|d| d.as_secs())).collect();
+        assert_eq!(seen, vec![Some(20), Some(40), Some(80), Some(160), Some(300), None, None]);
+        assert_eq!(interval.current(), MAX_POLL_INTERVAL);
+    }
+
+    #[test]
+    fn good_polls_step_the_interval_back_down() {
+        let mut interval = PollInterval::new(DEFAULT_POLL_INTERVAL);
+        interval.record(PollOutcome::Throttled);
+        interval.record(PollOutcome::Throttled);
+        assert_eq!(interval.current(), Duration::from_secs(40));
+
+        assert_eq!(interval.record(PollOutcome::Ok), None);
+        assert_eq!(interval.record(PollOutcome::Ok), None);
+        assert_eq!(interval.record(PollOutcome::Ok), Some(Duration::from_secs(20)));
+        // A throttled poll restarts the count
+        interval.record(PollOutcome::Ok);
+        interval.record(PollOutcome::Ok);
+        assert_eq!(interval.record(PollOutcome::Throttled), Some(Duration::from_secs(40)));
+        for _ in 0..6 {
+            interval.record(PollOutcome::Ok);
+        }
+        assert_eq!(interval.current(), DEFAULT_POLL_INTERVAL);
+        // At the base, good polls change nothing
+        assert_eq!(interval.record(PollOutcome::Ok), None);
+    }
+
+    #[test]
+    fn other_failures_leave_the_interval_alone() {
+        let mut interval = PollInterval::new(DEFAULT_POLL_INTERVAL);
+        interval.record(PollOutcome::Throttled);
+        interval.record(PollOutcome::Ok);
+        interval.record(PollOutcome::Ok);
+        assert_eq!(interval.record(PollOutcome::Failed), None);
+        // Failed polls neither count as good nor reset the good ones
+        assert_eq!(interval.record(PollOutcome::Ok), Some(DEFAULT_POLL_INTERVAL));
+    }
+
+    #[test]
+    fn a_base_above_the_cap_is_kept() {
+        let base = Duration::from_secs(600);
+        let mut interval = PollInterval::new(base);
+        assert_eq!(interval.record(PollOutcome::Throttled), None);
+        assert_eq!(interval.current(), base);
+    }
+
+    #[test]
+    fn outcomes_are_classified_by_error() {
+        assert_eq!(PollOutcome::of::<()>(&Ok(())), PollOutcome::Ok);
+        assert_eq!(PollOutcome::of::<()>(&Err(BlockchainError::UpstreamUnavailable { status: 429 })), PollOutcome::Throttled);
+        assert_eq!(PollOutcome::of::<()>(&Err(BlockchainError::NetworkRetryExhausted { attempts: 3 })), PollOutcome::Throttled);
+        assert_eq!(PollOutcome::of::<()>(&Err(BlockchainError::InvalidResponse("x".into()))), PollOutcome::Failed);
+    }
+
+    #[test]
+    fn subscribers_are_told_the_new_interval() {
+        assert_eq!(
+            interval_changed_message(Duration::from_secs(40), "node throttling"),
+            serde_json::json!({ "event": "pollIntervalChanged", "interval_secs": 40, "reason": "node throttling" })
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use super::error::BlockchainError;
+use super::types::Argument;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// Node endpoint that executes a contract call read-only, without a transaction. Nodes
+/// without it are detected by the endpoint probe and the preview tool marked unavailable.
+pub const VIEW_PATH: &str = "contract/view";
+
+/// POST body for the view endpoint: the call arguments as base58 of their bytes, encoded
+/// exactly as create_transaction encodes them.
+pub fn request_body(args: &[Argument]) -> Result<Value, BlockchainError> {
+    let args = args
+        .iter()
+        .map(|arg| arg.to_bytes().map(|bytes| bs58::encode(bytes).into_string()))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(json!({ "args": args }))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PreviewStatus {
+    Ok,
+    /// The call would fail; `reason` says why
+    Reverted,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CallPreview {
+    pub contract: String,
+    pub function: String,
+    pub status: PreviewStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Reads the view endpoint's answer: `{"error": "ok", "result": ...}` on success, any
+/// other `error` is the revert reason.
+pub fn interpret(contract: &str, function: &str, response: Value) -> CallPreview {
+    let (status, result, reason) = match response.get("error").and_then(Value::as_str) {
+        Some("ok") | None => (PreviewStatus::Ok, Some(response.get("result").cloned().unwrap_or(response)), None),
+        Some(reason) => (PreviewStatus::Reverted, None, Some(reason.to_string())),
+    };
+    CallPreview { contract: contract.to_string(), function: function.to_string(), status, result, reason }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: &str = "29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw";
+
+    #[test]
+    fn arguments_are_sent_as_base58_of_their_transaction_bytes() {
+        let args: Vec<Argument> = serde_json::from_value(json!([
+            { "b58": ADDRESS }, "AMA", 42, { "hex": "0x0102" }, { "utf8": "memo" }
+        ]))
+        .unwrap();
+        let body = request_body(&args).unwrap();
+        let expected: Vec<String> = args.iter().map(|a| bs58::encode(a.to_bytes().unwrap()).into_string()).collect();
+        assert_eq!(body, json!({ "args": expected }));
+        // A base58 argument goes through as the same base58
+        assert_eq!(body["args"][0], ADDRESS);
+        assert_eq!(body["args"][2], bs58::encode(b"42").into_string());
+        assert_eq!(body["args"][3], bs58::encode([1u8, 2]).into_string());
+    }
+
+    #[test]
+    fn unencodable_arguments_fail_before_the_call() {
+        let args: Vec<Argument> = serde_json::from_value(json!([{ "hex": "zz" }])).unwrap();
+        assert!(matches!(request_body(&args), Err(BlockchainError::ValidationFailed(m)) if m == "invalid hex arg"));
+        assert_eq!(request_body(&[]).unwrap(), json!({ "args": [] }));
+    }
+
+    #[test]
+    fn an_ok_answer_is_the_result() {
+        let preview = interpret("Coin", "balance", json!({ "error": "ok", "result": "1000" }));
+        assert_eq!((preview.status, preview.result, preview.reason), (PreviewStatus::Ok, Some(json!("1000")), None));
+        assert_eq!(
+            serde_json::to_value(interpret("Coin", "balance", json!({ "error": "ok", "result": 5 }))).unwrap(),
+            json!({ "contract": "Coin", "function": "balance", "status": "ok", "result": 5 })
+        );
+        // A node that answers without an envelope returned the result itself
+        assert_eq!(interpret("Coin", "balance", json!({ "amount": 5 })).result, Some(json!({ "amount": 5 })));
+    }
+
+    #[test]
+    fn any_other_error_is_a_revert() {
+        let preview = interpret("Coin", "transfer", json!({ "error": "insufficient_funds" }));
+        assert_eq!(preview.status, PreviewStatus::Reverted);
+        assert_eq!((&preview.result, preview.reason.as_deref()), (&None, Some("insufficient_funds")));
+        assert_eq!(serde_json::to_value(&preview).unwrap()["status"], "reverted");
+    }
+}
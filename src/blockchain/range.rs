@@ -0,0 +1,131 @@
+//! `?offset=&limit=` range hints on list resources, so a client can read a long list in
+//! slices. Ranges count records, not bytes.
+
+use serde::Serialize;
+
+/// Records returned when a range gives an offset but no limit.
+pub const DEFAULT_RANGE_LIMIT: usize = 100;
+pub const MAX_RANGE_LIMIT: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Range {
+    pub offset: usize,
+    pub limit: usize,
+}
+
+/// Where a slice sits in the full list, embedded next to the records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct RangeInfo {
+    pub offset: usize,
+    pub limit: usize,
+    pub total_available: usize,
+    /// Offset of the next slice, `None` on the last one
+    pub next_offset: Option<usize>,
+}
+
+/// Splits `amadeus://validators?offset=10` into the resource URI and its query.
+pub fn split_uri(uri: &str) -> (&str, Option<&str>) {
+    match uri.split_once('?') {
+        Some((base, query)) => (base, Some(query)),
+        None => (uri, None),
+    }
+}
+
+impl Range {
+    /// The range a query asks for; `None` without a query, meaning the whole list.
+    pub fn from_query(query: Option<&str>) -> Result<Option<Self>, String> {
+        let Some(query) = query.filter(|q| !q.is_empty()) else {
+            return Ok(None);
+        };
+        let mut range = Range { offset: 0, limit: DEFAULT_RANGE_LIMIT };
+        for pair in query.split('&').filter(|p| !p.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let number = value
+                .parse::<usize>()
+                .map_err(|_| format!("{} must be a non-negative integer, got {:?}", key, value))?;
+            match key {
+                "offset" => range.offset = number,
+                "limit" if (1..=MAX_RANGE_LIMIT).contains(&number) => range.limit = number,
+                "limit" => return Err(format!("limit must be between 1 and {}", MAX_RANGE_LIMIT)),
+                _ => return Err(format!("unknown parameter {}, expected offset or limit", key)),
+            }
+        }
+        Ok(Some(range))
+    }
+
+    /// The records of `items` in this range. An offset past the end gives an empty slice,
+    /// still with the total.
+    pub fn apply<'a, T>(&self, items: &'a [T]) -> (&'a [T], RangeInfo) {
+        let start = self.offset.min(items.len());
+        let end = start.saturating_add(self.limit).min(items.len());
+        let info = RangeInfo {
+            offset: self.offset,
+            limit: self.limit,
+            total_available: items.len(),
+            next_offset: (end < items.len()).then_some(end),
+        };
+        (&items[start..end], info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queries_parse_into_ranges() {
+        assert_eq!(Range::from_query(None), Ok(None));
+        assert_eq!(Range::from_query(Some("")), Ok(None));
+        assert_eq!(Range::from_query(Some("offset=10")), Ok(Some(Range { offset: 10, limit: DEFAULT_RANGE_LIMIT })));
+        assert_eq!(Range::from_query(Some("limit=5")), Ok(Some(Range { offset: 0, limit: 5 })));
+        assert_eq!(Range::from_query(Some("limit=5&offset=20&")), Ok(Some(Range { offset: 20, limit: 5 })));
+        assert_eq!(
+            Range::from_query(Some(&format!("limit={}", MAX_RANGE_LIMIT))),
+            Ok(Some(Range { offset: 0, limit: MAX_RANGE_LIMIT }))
+        );
+    }
+
+    #[test]
+    fn bad_queries_are_rejected() {
+        assert_eq!(Range::from_query(Some("offset=-1")).unwrap_err(), "offset must be a non-negative integer, got \"-1\"");
+        assert_eq!(Range::from_query(Some("offset")).unwrap_err(), "offset must be a non-negative integer, got \"\"");
+        assert_eq!(Range::from_query(Some("limit=0")).unwrap_err(), "limit must be between 1 and 1000");
+        assert_eq!(Range::from_query(Some("limit=1001")).unwrap_err(), "limit must be between 1 and 1000");
+        assert_eq!(Range::from_query(Some("page=2")).unwrap_err(), "unknown parameter page, expected offset or limit");
+    }
+
+    #[test]
+    fn slices_carry_the_total_and_the_next_offset() {
+        let items: Vec<u32> = (0..25).collect();
+        let (slice, info) = Range { offset: 0, limit: 10 }.apply(&items);
+        assert_eq!(slice, &items[..10]);
+        assert_eq!(info, RangeInfo { offset: 0, limit: 10, total_available: 25, next_offset: Some(10) });
+
+        let (slice, info) = Range { offset: 20, limit: 10 }.apply(&items);
+        assert_eq!(slice, &items[20..]);
+        assert_eq!(info.next_offset, None);
+
+        // Exactly reaching the end leaves nothing for a next slice
+        assert_eq!(Range { offset: 15, limit: 10 }.apply(&items).1.next_offset, None);
+    }
+
+    #[test]
+    fn an_offset_past_the_end_is_an_empty_slice_with_the_total() {
+        let items = [1, 2, 3];
+        for offset in [3, 4, usize::MAX] {
+            let (slice, info) = Range { offset, limit: usize::MAX }.apply(&items);
+            assert!(slice.is_empty());
+            assert_eq!((info.total_available, info.next_offset), (3, None));
+        }
+        let (slice, info) = Range { offset: 0, limit: 10 }.apply::<u8>(&[]);
+        assert!(slice.is_empty());
+        assert_eq!(info.total_available, 0);
+    }
+
+    #[test]
+    fn uris_split_at_the_query() {
+        assert_eq!(split_uri("amadeus://validators?offset=10"), ("amadeus://validators", Some("offset=10")));
+        assert_eq!(split_uri("amadeus://validators"), ("amadeus://validators", None));
+        assert_eq!(split_uri("amadeus://validators?"), ("amadeus://validators", Some("")));
+    }
+}
@@ -0,0 +1,120 @@
+use super::types::UnsignedTransactionBlob;
+use serde::{Deserialize, Serialize};
+
+/// Outcome of rebuilding a transaction and comparing it with a blob built elsewhere.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlobComparison {
+    pub equal: bool,
+    /// Byte offset of the first mismatch, or the length of the shorter blob when one is
+    /// a prefix of the other
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_difference: Option<usize>,
+    pub rebuilt_len: usize,
+    pub supplied_len: usize,
+    pub rebuilt_blob: String,
+    pub transaction_hash: String,
+}
+
+/// Compares a locally rebuilt transaction with a base58 `blob`.
+pub fn compare_blob(rebuilt: &UnsignedTransactionBlob, blob: &str) -> Result<BlobComparison, String> {
+    let supplied = bs58::decode(blob.trim())
+        .into_vec()
+        .map_err(|e| format!("blob is not valid base58: {}", e))?;
+    let first_difference = rebuilt
+        .tx_bytes
+        .iter()
+        .zip(&supplied)
+        .position(|(a, b)| a != b)
+        .or_else(|| (rebuilt.tx_bytes.len() != supplied.len()).then(|| rebuilt.tx_bytes.len().min(supplied.len())));
+
+    Ok(BlobComparison {
+        equal: first_difference.is_none(),
+        first_difference,
+        rebuilt_len: rebuilt.tx_bytes.len(),
+        supplied_len: supplied.len(),
+        rebuilt_blob: rebuilt.blob.clone(),
+        transaction_hash: rebuilt.transaction_hash.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::types::{Argument, TransactionRequest};
+    use crate::blockchain::BlockchainClient;
+
+    const SIGNER: &str = "29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw";
+    const RECEIVER: &str = "42KfUgyU2XHCPwDD6g9gryCvtj9vqJAvM6h3Yzs3VRbuyT2JCHwvuk5iAbzXrioerf";
+
+    fn request(nonce: Option<i64>) -> TransactionRequest {
+        TransactionRequest {
+            signer: SIGNER.to_string(),
+            contract: "Coin".to_string(),
+            function: "transfer".to_string(),
+            args: vec![
+                Argument::Base58 { b58: RECEIVER.to_string() },
+                Argument::String("1000000000".to_string()),
+                Argument::String("AMA".to_string()),
+            ],
+            attached_symbol: None,
+            attached_amount: None,
+            nonce,
+        }
+    }
+
+    async fn build(request: TransactionRequest) -> UnsignedTransactionBlob {
+        BlockchainClient::new(String::new()).unwrap().create_transaction_blob(request).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn explicit_nonce_builds_identical_blobs_and_hashes() {
+        let first = build(request(Some(1_718_000_000_000_000_000))).await;
+        let second = build(request(Some(1_718_000_000_000_000_000))).await;
+        assert_eq!(first.blob, second.blob);
+        assert_eq!(first.tx_bytes, second.tx_bytes);
+        assert_eq!(first.transaction_hash, second.transaction_hash);
+        assert_eq!(first.signing_payload, second.signing_payload);
+        assert_eq!(first.nonce, 1_718_000_000_000_000_000);
+
+        let other = build(request(Some(1_718_000_000_000_000_001))).await;
+        assert_ne!(other.transaction_hash, first.transaction_hash);
+    }
+
+    #[tokio::test]
+    async fn rebuilt_blob_compares_equal_to_the_original() {
+        let original = build(request(Some(42))).await;
+        let rebuilt = build(request(Some(42))).await;
+        let comparison = compare_blob(&rebuilt, &format!(" {}\n", original.blob)).unwrap();
+        assert!(comparison.equal);
+        assert_eq!(comparison.first_difference, None);
+        assert_eq!((comparison.rebuilt_len, comparison.supplied_len), (original.tx_bytes.len(), original.tx_bytes.len()));
+        assert_eq!(comparison.transaction_hash, original.transaction_hash);
+    }
+
+    #[tokio::test]
+    async fn comparison_reports_the_first_differing_byte() {
+        let rebuilt = build(request(Some(42))).await;
+
+        let mut changed = rebuilt.tx_bytes.clone();
+        changed[5] ^= 0xff;
+        let comparison = compare_blob(&rebuilt, &bs58::encode(&changed).into_string()).unwrap();
+        assert!(!comparison.equal);
+        assert_eq!(comparison.first_difference, Some(5));
+
+        let truncated = &rebuilt.tx_bytes[..10];
+        let comparison = compare_blob(&rebuilt, &bs58::encode(truncated).into_string()).unwrap();
+        assert_eq!(comparison.first_difference, Some(10));
+        assert_eq!(comparison.supplied_len, 10);
+
+        let mut longer = rebuilt.tx_bytes.clone();
+        longer.push(0);
+        let comparison = compare_blob(&rebuilt, &bs58::encode(&longer).into_string()).unwrap();
+        assert_eq!(comparison.first_difference, Some(rebuilt.tx_bytes.len()));
+    }
+
+    #[tokio::test]
+    async fn comparison_rejects_a_blob_that_is_not_base58() {
+        let rebuilt = build(request(Some(42))).await;
+        assert!(compare_blob(&rebuilt, "0OIl").unwrap_err().contains("base58"));
+    }
+}
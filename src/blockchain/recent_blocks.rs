@@ -0,0 +1,152 @@
+//! A digest of the newest heights, so "what happened on chain lately" takes one call.
+
+use super::block_range::FailedHeight;
+use super::clock;
+use super::types::BlockEntry;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Heights get_recent_blocks covers when the caller gives no count.
+pub const DEFAULT_RECENT_BLOCKS: u64 = 10;
+pub const MAX_RECENT_BLOCKS: u64 = 50;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RecentHeight {
+    pub height: u64,
+    pub entry_hashes: Vec<String>,
+    /// Signers of the entries, in entry order; one unless the height has several entries
+    pub signers: Vec<String>,
+    pub tx_count: u64,
+    /// Unix milliseconds of the first entry
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp_ms: Option<u64>,
+    /// Milliseconds since the height below, when both timestamps are known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interval_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RecentBlocks {
+    /// Height chain/stats reported; the newest height covered
+    pub tip_height: u64,
+    pub start_height: u64,
+    pub tx_count: u64,
+    pub unique_signers: usize,
+    /// Mean of the known intervals
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avg_interval_ms: Option<u64>,
+    /// Fetched heights, oldest first
+    pub heights: Vec<RecentHeight>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub failed: Vec<FailedHeight>,
+}
+
+impl RecentBlocks {
+    /// Summarizes `start..=tip` from its `entries`; heights in `failed` are left out and
+    /// break the interval of the height above them.
+    pub fn new(tip: u64, start: u64, entries: Vec<BlockEntry>, mut failed: Vec<FailedHeight>) -> Self {
+        failed.sort_by_key(|f| f.height);
+        let mut by_height: BTreeMap<u64, Vec<BlockEntry>> = BTreeMap::new();
+        for entry in entries {
+            by_height.entry(entry.header.height).or_default().push(entry);
+        }
+        let mut heights: Vec<RecentHeight> = Vec::new();
+        for height in (start..=tip).filter(|h| !failed.iter().any(|f| f.height == *h)) {
+            let entries = by_height.remove(&height).unwrap_or_default();
+            let timestamp_ms = entries.first().and_then(|e| e.header.timestamp).map(clock::entry_millis);
+            let below = heights.last().filter(|prev| prev.height + 1 == height).and_then(|prev| prev.timestamp_ms);
+            heights.push(RecentHeight {
+                height,
+                entry_hashes: entries.iter().map(|e| e.hash.clone()).collect(),
+                signers: entries.iter().map(|e| e.header.signer.clone()).collect(),
+                tx_count: entries.iter().map(|e| e.tx_count).sum(),
+                timestamp_ms,
+                interval_ms: timestamp_ms.zip(below).and_then(|(at, below)| at.checked_sub(below)),
+            });
+        }
+        let intervals: Vec<u64> = heights.iter().filter_map(|h| h.interval_ms).collect();
+        let signers: BTreeSet<&str> = heights.iter().flat_map(|h| h.signers.iter().map(String::as_str)).collect();
+        Self {
+            tip_height: tip,
+            start_height: start,
+            tx_count: heights.iter().map(|h| h.tx_count).sum(),
+            unique_signers: signers.len(),
+            avg_interval_ms: (!intervals.is_empty()).then(|| intervals.iter().sum::<u64>() / intervals.len() as u64),
+            heights,
+            failed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn entry(height: u64, signer: &str, timestamp: Option<u64>, tx_count: u64) -> BlockEntry {
+        serde_json::from_value(json!({
+            "hash": format!("h{}{}", height, signer),
+            "header": {
+                "slot": height, "height": height, "dr": "dr", "vr": "vr", "prev_hash": "prev", "signer": signer,
+                "root_tx": "rt", "root_validator": "rv", "prev_slot": height.saturating_sub(1), "timestamp": timestamp
+            },
+            "tx_count": tx_count
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn heights_are_summarized_oldest_first() {
+        let entries = vec![
+            entry(3, "v1", Some(1_700_000_001_000), 2),
+            entry(1, "v1", Some(1_700_000_000_000), 1),
+            entry(2, "v2", Some(1_700_000_000_400), 0),
+            entry(2, "v3", Some(1_700_000_000_500), 4),
+        ];
+        let recent = RecentBlocks::new(3, 1, entries, Vec::new());
+
+        assert_eq!(recent.heights.iter().map(|h| h.height).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(recent.heights[1].signers, vec!["v2", "v3"]);
+        assert_eq!(recent.heights[1].entry_hashes, vec!["h2v2", "h2v3"]);
+        assert_eq!((recent.tx_count, recent.unique_signers), (7, 3));
+        // The first entry at a height sets its timestamp
+        let intervals: Vec<_> = recent.heights.iter().map(|h| h.interval_ms).collect();
+        assert_eq!(intervals, vec![None, Some(400), Some(600)]);
+        assert_eq!(recent.avg_interval_ms, Some(500));
+    }
+
+    #[test]
+    fn second_timestamps_are_read_as_milliseconds() {
+        let entries = vec![entry(1, "v", Some(1_700_000_000), 0), entry(2, "v", Some(1_700_000_002), 0)];
+        let recent = RecentBlocks::new(2, 1, entries, Vec::new());
+        assert_eq!(recent.heights[0].timestamp_ms, Some(1_700_000_000_000));
+        assert_eq!(recent.heights[1].interval_ms, Some(2000));
+    }
+
+    #[test]
+    fn a_failed_height_breaks_the_interval_above_it() {
+        let entries = vec![entry(1, "v", Some(1_000_000_000_000), 0), entry(3, "v", Some(1_000_000_002_000), 0)];
+        let failed = vec![FailedHeight { height: 2, error: "timeout".into() }];
+        let recent = RecentBlocks::new(3, 1, entries, failed);
+
+        assert_eq!(recent.heights.iter().map(|h| h.height).collect::<Vec<_>>(), vec![1, 3]);
+        assert_eq!(recent.heights[1].interval_ms, None);
+        assert_eq!(recent.avg_interval_ms, None);
+        assert_eq!(recent.failed.len(), 1);
+    }
+
+    #[test]
+    fn missing_or_backwards_timestamps_give_no_interval() {
+        let entries = vec![
+            entry(1, "v", Some(1_000_000_005_000), 0),
+            entry(2, "v", Some(1_000_000_004_000), 0),
+            entry(3, "v", None, 0),
+        ];
+        let recent = RecentBlocks::new(4, 1, entries, Vec::new());
+        let intervals: Vec<_> = recent.heights.iter().map(|h| h.interval_ms).collect();
+        assert_eq!(intervals, vec![None, None, None, None]);
+        // A height with no entries is still listed, empty
+        assert!(recent.heights[3].entry_hashes.is_empty());
+        assert_eq!(recent.heights[3].timestamp_ms, None);
+    }
+}
@@ -0,0 +1,333 @@
+//! Machine-readable next steps for errors. Every error mapped from a `BlockchainError`,
+//! and the server-side errors with an obvious remedy, carry one under `recovery` in their
+//! data, serialized the same way by the stdio server and the worker.
+
+use super::error::BlockchainError;
+use super::rejection::SubmitRejection;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Data key the hint is attached under.
+pub const RECOVERY_FIELD: &str = "recovery";
+/// Wait suggested when the node rate-limits us without saying for how long.
+pub const RATE_LIMIT_RETRY_SECS: u64 = 10;
+/// Tool that reports whether the chain and node are making progress.
+pub const HEALTH_TOOL: &str = "get_liveness";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Recovery {
+    /// Repeat the same call after `seconds`
+    RetryAfter { seconds: u64 },
+    /// The node is failing or unreachable; repeat later, after `check` if given
+    RetryLater {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        check: Option<&'static str>,
+    },
+    /// The address is malformed or unknown to the chain; check where it came from
+    VerifyAddress,
+    /// Correct the argument and call again; `field` names it when known
+    FixField {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        field: Option<String>,
+    },
+    /// The signer lacks the funds; top it up or send less
+    CheckBalance,
+    /// The transaction blob is outdated; build it again with create_transaction
+    RebuildTransaction,
+    /// This node cannot answer; use another node or another tool
+    UseAnotherNode,
+    /// The server's configuration prevents this call; only its operator can change that
+    ChangeConfiguration,
+    /// Unexpected failure that retrying will not fix; report it
+    Report,
+}
+
+impl Recovery {
+    fn fix_field(field: impl Into<String>) -> Self {
+        Self::FixField { field: Some(field.into()) }
+    }
+
+    /// One of each action with what it asks of the caller, for the docs resource.
+    pub fn vocabulary() -> Vec<(Recovery, &'static str)> {
+        vec![
+            (Self::RetryAfter { seconds: RATE_LIMIT_RETRY_SECS }, "repeat the same call after the given number of seconds"),
+            (Self::RetryLater { check: Some(HEALTH_TOOL) }, "the node is failing or unreachable; repeat later, after calling `check` if given"),
+            (Self::VerifyAddress, "the address is malformed or unknown to the chain; check where it came from"),
+            (Self::fix_field("signer"), "correct the named argument (`field` is omitted when unknown) and call again"),
+            (Self::CheckBalance, "the signer cannot cover the amount; top it up or send less"),
+            (Self::RebuildTransaction, "the blob is outdated; build it again with create_transaction, sign and submit the new one"),
+            (Self::UseAnotherNode, "this node cannot answer; point the server at another node or use another tool"),
+            (Self::ChangeConfiguration, "the server's configuration prevents this call; only its operator can change it"),
+            (Self::Report, "an unexpected failure that retrying will not fix; report it with the error message"),
+        ]
+    }
+}
+
+/// The hint for `error`. Deliberately exhaustive, so a new variant needs a hint to compile.
+pub fn for_error(error: &BlockchainError) -> Recovery {
+    match error {
+        #[cfg(not(target_arch = "wasm32"))]
+        BlockchainError::HttpRequest(e) if e.status().is_some_and(|s| s.as_u16() == 429) => {
+            Recovery::RetryAfter { seconds: RATE_LIMIT_RETRY_SECS }
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        BlockchainError::HttpRequest(_) => Recovery::RetryLater { check: Some(HEALTH_TOOL) },
+        #[cfg(target_arch = "wasm32")]
+        BlockchainError::HttpRequestWasm(_) => Recovery::RetryLater { check: Some(HEALTH_TOOL) },
+        BlockchainError::UpstreamUnavailable { status: 429 } => Recovery::RetryAfter { seconds: RATE_LIMIT_RETRY_SECS },
+        BlockchainError::UpstreamUnavailable { .. } | BlockchainError::NetworkRetryExhausted { .. } => {
+            Recovery::RetryLater { check: Some(HEALTH_TOOL) }
+        }
+        BlockchainError::InvalidResponse(_) | BlockchainError::Serialization(_) => Recovery::Report,
+        BlockchainError::ValidationFailed(message) => Recovery::FixField { field: validation_field(message) },
+        BlockchainError::LimitExceeded { argument, .. } | BlockchainError::NotFound { argument, .. } => {
+            Recovery::fix_field(argument.clone())
+        }
+        BlockchainError::AccountNotFound { .. } => Recovery::VerifyAddress,
+        BlockchainError::InsufficientBalance { .. } => Recovery::CheckBalance,
+        BlockchainError::EndpointUnsupported { .. } => Recovery::UseAnotherNode,
+        BlockchainError::Offline(_) | BlockchainError::Configuration(_) => Recovery::ChangeConfiguration,
+    }
+}
+
+/// The hint for a transaction the node refused.
+pub fn for_rejection(rejection: &SubmitRejection) -> Recovery {
+    match rejection {
+        SubmitRejection::BadSignature => Recovery::fix_field("signature"),
+        SubmitRejection::InsufficientFunds => Recovery::CheckBalance,
+        SubmitRejection::NonceConflict | SubmitRejection::Expired => Recovery::RebuildTransaction,
+        SubmitRejection::TooLarge => Recovery::fix_field("args"),
+        SubmitRejection::Unknown { .. } => Recovery::Report,
+    }
+}
+
+/// The argument a builder validation message is about, when it names one.
+fn validation_field(message: &str) -> Option<String> {
+    if message.starts_with("invalid signer") {
+        Some("signer".to_string())
+    } else if message.starts_with("argument ") || message.ends_with(" arg") {
+        Some("args".to_string())
+    } else {
+        None
+    }
+}
+
+/// The hint for an error the server raises itself, by its code (the error message) and
+/// data. `None` for codes that have no useful next step, such as a declined confirmation.
+pub fn for_code(code: &str, data: Option<&Value>) -> Option<Recovery> {
+    let field = |pointer: &str| data.and_then(|d| d.pointer(pointer)).and_then(Value::as_str).map(str::to_string);
+    Some(match code {
+        "validation_failed" => Recovery::FixField {
+            // validator errors are keyed by field
+            field: data
+                .and_then(|d| d["errors"].as_object())
+                .and_then(|errors| errors.keys().next().cloned())
+                .or_else(|| field("/field")),
+        },
+        "unknown_arguments" => Recovery::FixField { field: field("/unknown/0/name") },
+        "invalid_address" => Recovery::VerifyAddress,
+        "invalid_label" => Recovery::FixField { field: field("/field") },
+        "height_beyond_tip" => Recovery::fix_field("height"),
+        "invalid_cursor" => Recovery::fix_field("cursor"),
+        "limit_exceeded" | "not_found" => Recovery::FixField { field: field("/argument") },
+        "nonce_required" => Recovery::fix_field("nonce"),
+        "invalid_blob" => Recovery::fix_field("blob"),
+        "raw_query_rejected" => Recovery::fix_field("path"),
+        "insufficient_balance" => Recovery::CheckBalance,
+        "stale_blob" => Recovery::RebuildTransaction,
+        "endpoint_unsupported" | "history_unavailable" => Recovery::UseAnotherNode,
+        "offline_mode" | "tool_disabled" | "session_budget_exhausted" => Recovery::ChangeConfiguration,
+        "blockchain_error" | "serialization_error" => Recovery::Report,
+        _ => return None,
+    })
+}
+
+/// Adds `recovery` to an error's data object, creating the object if needed. An existing
+/// hint is kept.
+pub fn attach(data: &mut Option<Value>, recovery: &Recovery) {
+    let data = data.get_or_insert_with(|| Value::Object(Default::default()));
+    if let Value::Object(fields) = data {
+        fields
+            .entry(RECOVERY_FIELD)
+            .or_insert_with(|| serde_json::to_value(recovery).unwrap_or(Value::Null));
+    }
+}
+
+/// The vocabulary served at `catalog::ERROR_RECOVERY_URI`.
+pub fn doc() -> String {
+    let actions: String = Recovery::vocabulary()
+        .iter()
+        .map(|(example, meaning)| format!("- {}\n  {}\n", serde_json::to_string(example).unwrap_or_default(), meaning))
+        .collect();
+    format!(
+        "Error recovery hints\n\nErrors carry a `{}` object in their data naming what to try next. `action` is one of:\n\n{}\nUnknown actions may be added later; treat them like `report`.\n",
+        RECOVERY_FIELD, actions
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// One of every `BlockchainError` variant the native build has, with its hint. The
+    /// match in `listed` has no wildcard, so a new variant fails to compile until it is
+    /// added here.
+    async fn every_variant() -> Vec<(BlockchainError, Recovery)> {
+        let http = reqwest::get("not a url").await.unwrap_err();
+        let serde = serde_json::from_str::<u64>("x").unwrap_err();
+        let retry_later = Recovery::RetryLater { check: Some(HEALTH_TOOL) };
+        let cases = vec![
+            (BlockchainError::HttpRequest(http), retry_later.clone()),
+            (BlockchainError::InvalidResponse("bad".to_string()), Recovery::Report),
+            (BlockchainError::ValidationFailed("invalid signer key".to_string()), Recovery::fix_field("signer")),
+            (BlockchainError::AccountNotFound { address: "x".to_string() }, Recovery::VerifyAddress),
+            (
+                BlockchainError::InsufficientBalance { required: "2".to_string(), available: "1".to_string() },
+                Recovery::CheckBalance,
+            ),
+            (BlockchainError::UpstreamUnavailable { status: 503 }, retry_later.clone()),
+            (
+                BlockchainError::LimitExceeded { argument: "limit".to_string(), requested: 9, max: 1 },
+                Recovery::fix_field("limit"),
+            ),
+            (
+                BlockchainError::NotFound { argument: "tx_hash".to_string(), value: "h".to_string() },
+                Recovery::fix_field("tx_hash"),
+            ),
+            (BlockchainError::EndpointUnsupported { endpoint: "e".to_string() }, Recovery::UseAnotherNode),
+            (BlockchainError::NetworkRetryExhausted { attempts: 3 }, retry_later),
+            (BlockchainError::Serialization(serde), Recovery::Report),
+            (BlockchainError::Offline("o".to_string()), Recovery::ChangeConfiguration),
+            (BlockchainError::Configuration("c".to_string()), Recovery::ChangeConfiguration),
+        ];
+        for (error, _) in &cases {
+            listed(error);
+        }
+        cases
+    }
+
+    fn listed(error: &BlockchainError) {
+        match error {
+            BlockchainError::HttpRequest(_)
+            | BlockchainError::InvalidResponse(_)
+            | BlockchainError::ValidationFailed(_)
+            | BlockchainError::AccountNotFound { .. }
+            | BlockchainError::InsufficientBalance { .. }
+            | BlockchainError::UpstreamUnavailable { .. }
+            | BlockchainError::LimitExceeded { .. }
+            | BlockchainError::NotFound { .. }
+            | BlockchainError::EndpointUnsupported { .. }
+            | BlockchainError::NetworkRetryExhausted { .. }
+            | BlockchainError::Serialization(_)
+            | BlockchainError::Offline(_)
+            | BlockchainError::Configuration(_) => {}
+        }
+    }
+
+    #[tokio::test]
+    async fn every_error_variant_has_its_hint() {
+        for (error, expected) in every_variant().await {
+            assert_eq!(for_error(&error), expected, "{}", error);
+        }
+    }
+
+    #[test]
+    fn rate_limits_ask_for_a_wait() {
+        assert_eq!(
+            for_error(&BlockchainError::UpstreamUnavailable { status: 429 }),
+            Recovery::RetryAfter { seconds: RATE_LIMIT_RETRY_SECS }
+        );
+    }
+
+    #[test]
+    fn validation_messages_name_their_field_when_they_can() {
+        for (message, field) in [
+            ("invalid signer: bad base58", Some("signer")),
+            ("argument 2 is not valid base58", Some("args")),
+            ("bad b58 arg", Some("args")),
+            ("contract name is empty", None),
+        ] {
+            let hint = for_error(&BlockchainError::ValidationFailed(message.to_string()));
+            assert_eq!(hint, Recovery::FixField { field: field.map(str::to_string) }, "{}", message);
+        }
+    }
+
+    #[test]
+    fn hints_serialize_with_an_action_tag() {
+        let cases = [
+            (Recovery::RetryAfter { seconds: 10 }, json!({ "action": "retry_after", "seconds": 10 })),
+            (Recovery::RetryLater { check: Some(HEALTH_TOOL) }, json!({ "action": "retry_later", "check": HEALTH_TOOL })),
+            (Recovery::RetryLater { check: None }, json!({ "action": "retry_later" })),
+            (Recovery::VerifyAddress, json!({ "action": "verify_address" })),
+            (Recovery::fix_field("height"), json!({ "action": "fix_field", "field": "height" })),
+            (Recovery::FixField { field: None }, json!({ "action": "fix_field" })),
+            (Recovery::CheckBalance, json!({ "action": "check_balance" })),
+            (Recovery::RebuildTransaction, json!({ "action": "rebuild_transaction" })),
+            (Recovery::UseAnotherNode, json!({ "action": "use_another_node" })),
+            (Recovery::ChangeConfiguration, json!({ "action": "change_configuration" })),
+            (Recovery::Report, json!({ "action": "report" })),
+        ];
+        for (hint, expected) in cases {
+            assert_eq!(serde_json::to_value(&hint).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn every_rejection_has_a_hint() {
+        for (rejection, expected) in [
+            (SubmitRejection::BadSignature, Recovery::fix_field("signature")),
+            (SubmitRejection::InsufficientFunds, Recovery::CheckBalance),
+            (SubmitRejection::NonceConflict, Recovery::RebuildTransaction),
+            (SubmitRejection::Expired, Recovery::RebuildTransaction),
+            (SubmitRejection::TooLarge, Recovery::fix_field("args")),
+            (SubmitRejection::Unknown { raw: "x".to_string() }, Recovery::Report),
+        ] {
+            assert_eq!(for_rejection(&rejection), expected);
+        }
+    }
+
+    #[test]
+    fn server_codes_map_to_hints() {
+        let errors = json!({ "errors": { "limit": [{ "code": "range" }] } });
+        assert_eq!(for_code("validation_failed", Some(&errors)), Some(Recovery::fix_field("limit")));
+        assert_eq!(
+            for_code("validation_failed", Some(&json!({ "field": "symbol" }))),
+            Some(Recovery::fix_field("symbol"))
+        );
+        assert_eq!(for_code("validation_failed", None), Some(Recovery::FixField { field: None }));
+        assert_eq!(
+            for_code("unknown_arguments", Some(&json!({ "unknown": [{ "name": "adress" }] }))),
+            Some(Recovery::fix_field("adress"))
+        );
+        assert_eq!(
+            for_code("limit_exceeded", Some(&json!({ "argument": "blocks" }))),
+            Some(Recovery::fix_field("blocks"))
+        );
+        assert_eq!(for_code("stale_blob", None), Some(Recovery::RebuildTransaction));
+        assert_eq!(for_code("tool_disabled", None), Some(Recovery::ChangeConfiguration));
+        assert_eq!(for_code("submission_not_confirmed", None), None);
+    }
+
+    #[test]
+    fn attach_creates_data_and_keeps_an_existing_hint() {
+        let mut data = None;
+        attach(&mut data, &Recovery::Report);
+        assert_eq!(data, Some(json!({ "recovery": { "action": "report" } })));
+
+        let mut data = Some(json!({ "message": "m", "recovery": { "action": "check_balance" } }));
+        attach(&mut data, &Recovery::Report);
+        assert_eq!(data, Some(json!({ "message": "m", "recovery": { "action": "check_balance" } })));
+    }
+
+    #[test]
+    fn doc_lists_every_action() {
+        let doc = doc();
+        for (hint, _) in Recovery::vocabulary() {
+            let action = serde_json::to_value(&hint).unwrap()["action"].as_str().unwrap().to_string();
+            assert!(doc.contains(&format!("\"action\":\"{}\"", action)), "{}", action);
+        }
+        assert_eq!(Recovery::vocabulary().len(), 9);
+    }
+}
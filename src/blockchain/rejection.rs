@@ -0,0 +1,145 @@
+use super::types::SubmitResponse;
+use serde::{Deserialize, Serialize};
+
+/// Why the node refused a submitted transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SubmitRejection {
+    BadSignature,
+    InsufficientFunds,
+    NonceConflict,
+    TooLarge,
+    Expired,
+    Unknown { raw: String },
+}
+
+/// Node error strings, normalized to lowercase snake_case, matched as substrings in order.
+/// Nonce patterns come first so "nonce_too_old" is not read as an expiry.
+const REJECTIONS: &[(&str, SubmitRejection)] = &[
+    ("nonce", SubmitRejection::NonceConflict),
+    ("signature", SubmitRejection::BadSignature),
+    ("insufficient", SubmitRejection::InsufficientFunds),
+    ("not_enough", SubmitRejection::InsufficientFunds),
+    ("too_large", SubmitRejection::TooLarge),
+    ("too_big", SubmitRejection::TooLarge),
+    ("tx_size", SubmitRejection::TooLarge),
+    ("expired", SubmitRejection::Expired),
+    ("too_old", SubmitRejection::Expired),
+];
+
+impl SubmitRejection {
+    /// Classifies a node error string; `None` when the node accepted the transaction.
+    pub fn classify(raw: &str) -> Option<Self> {
+        let normalized: String = raw
+            .trim()
+            .to_ascii_lowercase()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        if normalized == "ok" {
+            return None;
+        }
+
+        let rejection = REJECTIONS
+            .iter()
+            .find(|(pattern, _)| normalized.contains(pattern))
+            .map(|(_, rejection)| rejection.clone())
+            .unwrap_or_else(|| Self::Unknown { raw: raw.to_string() });
+        Some(rejection)
+    }
+
+    /// What the agent should do before retrying.
+    pub fn hint(&self) -> &'static str {
+        match self {
+            Self::BadSignature => "sign the signing_payload bytes from create_transaction with the signer's BLS key and the Amadeus transaction DST; use test_signing_flow to find the mismatch",
+            Self::InsufficientFunds => "the signer cannot cover the amount plus fee; check get_account_balance and lower the amount",
+            Self::NonceConflict => "the nonce was already used or is out of range; rebuild with create_transaction to get a fresh nonce",
+            Self::TooLarge => "the transaction exceeds the node's size limit; reduce argument sizes or split the call",
+            Self::Expired => "the transaction is too old to be accepted; rebuild, re-sign and submit promptly",
+            Self::Unknown { .. } => "the node returned an unrecognized error; see raw for the original message",
+        }
+    }
+}
+
+impl SubmitResponse {
+    /// Builds the response from a submit body `{"error": "ok" | <reason>, ...}`.
+    pub fn from_node(body: &serde_json::Value, tx_hash: String) -> Self {
+        let error = body.get("error").and_then(|e| e.as_str()).unwrap_or("unknown");
+        let rejection = SubmitRejection::classify(error);
+
+        Self {
+            error: error.to_string(),
+            tx_hash: if rejection.is_none() { Some(tx_hash) } else { None },
+            hint: rejection.as_ref().map(|r| r.hint().to_string()),
+            rejection,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn classifies_node_error_strings() {
+        let cases: &[(&str, Option<SubmitRejection>)] = &[
+            ("ok", None),
+            ("OK", None),
+            (" ok\n", None),
+            ("invalid_signature", Some(SubmitRejection::BadSignature)),
+            ("Invalid Signature", Some(SubmitRejection::BadSignature)),
+            ("bls signature verification failed", Some(SubmitRejection::BadSignature)),
+            ("insufficient_balance", Some(SubmitRejection::InsufficientFunds)),
+            ("Insufficient funds for fee", Some(SubmitRejection::InsufficientFunds)),
+            ("not_enough_ama", Some(SubmitRejection::InsufficientFunds)),
+            ("invalid_nonce", Some(SubmitRejection::NonceConflict)),
+            ("nonce_too_old", Some(SubmitRejection::NonceConflict)),
+            ("Nonce already used", Some(SubmitRejection::NonceConflict)),
+            ("tx_too_large", Some(SubmitRejection::TooLarge)),
+            ("Payload too big", Some(SubmitRejection::TooLarge)),
+            ("tx_size_exceeded", Some(SubmitRejection::TooLarge)),
+            ("tx_expired", Some(SubmitRejection::Expired)),
+            ("transaction too old", Some(SubmitRejection::Expired)),
+            ("", Some(SubmitRejection::Unknown { raw: String::new() })),
+            ("okay", Some(SubmitRejection::Unknown { raw: "okay".to_string() })),
+            ("invalid_contract", Some(SubmitRejection::Unknown { raw: "invalid_contract".to_string() })),
+        ];
+        for (raw, expected) in cases {
+            assert_eq!(&SubmitRejection::classify(raw), expected, "{:?}", raw);
+        }
+    }
+
+    #[test]
+    fn every_rejection_has_a_hint() {
+        for (_, rejection) in REJECTIONS {
+            assert!(!rejection.hint().is_empty());
+        }
+        assert!(SubmitRejection::Unknown { raw: "x".to_string() }.hint().contains("raw"));
+    }
+
+    #[test]
+    fn rejection_serializes_as_a_tagged_kind() {
+        assert_eq!(serde_json::to_value(SubmitRejection::NonceConflict).unwrap(), json!({ "kind": "nonce_conflict" }));
+        assert_eq!(
+            serde_json::to_value(SubmitRejection::Unknown { raw: "weird".to_string() }).unwrap(),
+            json!({ "kind": "unknown", "raw": "weird" })
+        );
+    }
+
+    #[test]
+    fn submit_response_from_node() {
+        let accepted = SubmitResponse::from_node(&json!({ "error": "ok" }), "hash".to_string());
+        assert_eq!(accepted.tx_hash.as_deref(), Some("hash"));
+        assert!(accepted.rejection.is_none() && accepted.hint.is_none());
+
+        let rejected = SubmitResponse::from_node(&json!({ "error": "invalid_signature" }), "hash".to_string());
+        assert_eq!(rejected.tx_hash, None);
+        assert_eq!(rejected.rejection, Some(SubmitRejection::BadSignature));
+        assert_eq!(rejected.hint.as_deref(), Some(SubmitRejection::BadSignature.hint()));
+
+        let missing = SubmitResponse::from_node(&json!({}), "hash".to_string());
+        assert_eq!(missing.error, "unknown");
+        assert_eq!(missing.rejection, Some(SubmitRejection::Unknown { raw: "unknown".to_string() }));
+    }
+}
@@ -0,0 +1,189 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Tip hashes remembered per follower; older heights are beyond any plausible reorg.
+pub const MAX_TIPS: usize = 512;
+
+/// How many recorded tips below a mismatch are re-checked to find the fork point.
+pub const MAX_FORK_WALK: usize = 8;
+
+/// How long tool outputs carry a warning after a reorg.
+pub const REORG_WARNING_SECS: u64 = 300;
+
+/// The chain replaced entries the follower had seen as the tip.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReorgEvent {
+    /// Lowest recorded height found off the current chain; data at or above it may be stale
+    pub fork_height: u64,
+    /// Recorded tips dropped, from `fork_height` to the last tip seen before the reorg
+    pub depth: u64,
+    /// Hash previously recorded at `fork_height`
+    pub orphaned_hash: String,
+    /// Unix seconds when the reorg was noticed
+    pub detected_at: u64,
+}
+
+impl ReorgEvent {
+    pub fn is_recent(&self, now: u64) -> bool {
+        now.saturating_sub(self.detected_at) < REORG_WARNING_SECS
+    }
+}
+
+/// Tip hashes by height as seen by a follower.
+#[derive(Debug, Clone, Default)]
+pub struct TipHistory {
+    hashes: BTreeMap<u64, String>,
+}
+
+impl TipHistory {
+    pub fn record(&mut self, height: u64, hash: String) {
+        self.hashes.insert(height, hash);
+        while self.hashes.len() > MAX_TIPS {
+            self.hashes.pop_first();
+        }
+    }
+
+    pub fn latest(&self) -> Option<(u64, &str)> {
+        self.hashes.last_key_value().map(|(h, hash)| (*h, hash.as_str()))
+    }
+
+    pub fn hash_at(&self, height: u64) -> Option<&str> {
+        self.hashes.get(&height).map(String::as_str)
+    }
+
+    /// Recorded heights to check for a reorg, newest first.
+    pub fn recent_heights(&self) -> Vec<u64> {
+        self.hashes.keys().rev().take(MAX_FORK_WALK).copied().collect()
+    }
+
+    /// Finds the fork point from the hashes the node now has at some recorded heights.
+    /// Walks down from the newest recorded tip and stops at the first one still on the
+    /// chain; returns the lowest height found off it, or `None` when the newest tip is
+    /// still canonical. Heights missing from `canonical` end the walk.
+    pub fn fork_height(&self, canonical: &BTreeMap<u64, BTreeSet<String>>) -> Option<u64> {
+        let mut fork = None;
+        for (height, hash) in self.hashes.iter().rev() {
+            match canonical.get(height) {
+                Some(hashes) if !hashes.contains(hash) => fork = Some(*height),
+                _ => break,
+            }
+        }
+        fork
+    }
+
+    /// Drops the tips from `fork_height` up and describes what was lost.
+    pub fn rewind(&mut self, fork_height: u64, detected_at: u64) -> Option<ReorgEvent> {
+        let dropped = self.hashes.split_off(&fork_height);
+        let orphaned_hash = dropped.get(&fork_height)?.clone();
+        Some(ReorgEvent {
+            fork_height,
+            depth: dropped.len() as u64,
+            orphaned_hash,
+            detected_at,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A chain as the node reports it: one hash per height, `{branch}{height}`.
+    fn chain(branches: &[(&str, std::ops::RangeInclusive<u64>)]) -> BTreeMap<u64, String> {
+        branches
+            .iter()
+            .flat_map(|(branch, heights)| heights.clone().map(move |h| (h, format!("{}{}", branch, h))))
+            .collect()
+    }
+
+    fn follow(chain: &BTreeMap<u64, String>) -> TipHistory {
+        let mut history = TipHistory::default();
+        for (height, hash) in chain {
+            history.record(*height, hash.clone());
+        }
+        history
+    }
+
+    /// What the tip follower does: re-read recent heights newest first until one of the
+    /// recorded tips is still on `now`, then look for the fork point.
+    fn detect(history: &mut TipHistory, now: &BTreeMap<u64, String>) -> Option<ReorgEvent> {
+        let mut canonical = BTreeMap::new();
+        for height in history.recent_heights() {
+            let Some(hash) = now.get(&height) else { break };
+            let still_canonical = history.hash_at(height) == Some(hash.as_str());
+            canonical.insert(height, BTreeSet::from([hash.clone()]));
+            if still_canonical {
+                break;
+            }
+        }
+        let fork = history.fork_height(&canonical)?;
+        history.rewind(fork, 1_000)
+    }
+
+    #[test]
+    fn an_extended_chain_is_not_a_reorg() {
+        let mut history = follow(&chain(&[("a", 1..=10)]));
+        assert_eq!(detect(&mut history, &chain(&[("a", 1..=12)])), None);
+        assert_eq!(history.latest(), Some((10, "a10")));
+    }
+
+    #[test]
+    fn a_replaced_tip_is_a_reorg_of_depth_one() {
+        let mut history = follow(&chain(&[("a", 1..=10)]));
+        let event = detect(&mut history, &chain(&[("a", 1..=9), ("b", 10..=11)])).unwrap();
+        assert_eq!(
+            event,
+            ReorgEvent { fork_height: 10, depth: 1, orphaned_hash: "a10".into(), detected_at: 1_000 }
+        );
+        assert_eq!(history.latest(), Some((9, "a9")));
+    }
+
+    #[test]
+    fn a_deeper_fork_reports_the_lowest_orphaned_height() {
+        let mut history = follow(&chain(&[("a", 1..=20)]));
+        let event = detect(&mut history, &chain(&[("a", 1..=16), ("b", 17..=21)])).unwrap();
+        assert_eq!((event.fork_height, event.depth, event.orphaned_hash.as_str()), (17, 4, "a17"));
+        assert_eq!(history.hash_at(17), None);
+        assert_eq!(history.hash_at(16), Some("a16"));
+
+        // Following the new branch afterwards raises nothing
+        history.record(21, "b21".into());
+        assert_eq!(detect(&mut history, &chain(&[("a", 1..=16), ("b", 17..=22)])), None);
+    }
+
+    #[test]
+    fn a_fork_deeper_than_the_walk_is_reported_at_the_walk_limit() {
+        let mut history = follow(&chain(&[("a", 1..=30)]));
+        let event = detect(&mut history, &chain(&[("a", 1..=10), ("b", 11..=30)])).unwrap();
+        assert_eq!(event.fork_height, 30 - MAX_FORK_WALK as u64 + 1);
+        assert_eq!(event.depth, MAX_FORK_WALK as u64);
+    }
+
+    #[test]
+    fn heights_the_node_cannot_serve_end_the_walk() {
+        let history = follow(&chain(&[("a", 1..=10)]));
+        // The node now lacks height 9, so only 10 is known to be off the chain
+        let canonical = BTreeMap::from([(10, BTreeSet::from(["b10".to_string()]))]);
+        assert_eq!(history.fork_height(&canonical), Some(10));
+        assert_eq!(history.fork_height(&BTreeMap::new()), None);
+    }
+
+    #[test]
+    fn any_of_several_entries_at_a_height_keeps_the_tip_canonical() {
+        let history = follow(&chain(&[("a", 1..=5)]));
+        let canonical = BTreeMap::from([(5, BTreeSet::from(["other5".to_string(), "a5".to_string()]))]);
+        assert_eq!(history.fork_height(&canonical), None);
+    }
+
+    #[test]
+    fn history_is_bounded_and_warnings_expire() {
+        let history = follow(&chain(&[("a", 1..=(MAX_TIPS as u64 + 5))]));
+        assert_eq!(history.hash_at(5), None);
+        assert_eq!(history.hash_at(6), Some("a6"));
+        assert_eq!(history.recent_heights().len(), MAX_FORK_WALK);
+
+        let event = ReorgEvent { fork_height: 1, depth: 1, orphaned_hash: "x".into(), detected_at: 1_000 };
+        assert!(event.is_recent(1_000 + REORG_WARNING_SECS - 1));
+        assert!(!event.is_recent(1_000 + REORG_WARNING_SECS));
+    }
+}
@@ -0,0 +1,239 @@
+use super::error::{BlockchainError, Result};
+use super::types::RichlistEntry;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+pub const DEFAULT_RICHLIST_LIMIT: u64 = 20;
+pub const MAX_RICHLIST_LIMIT: u64 = 500;
+
+/// The holders in a `contract/richlist` response, largest balance first.
+pub fn holders_from(response: &Value) -> Result<Vec<RichlistEntry>> {
+    if response.get("error").and_then(Value::as_str) != Some("ok") {
+        return Err(BlockchainError::InvalidResponse("failed to get richlist".to_string()));
+    }
+    let list = response
+        .get("richlist")
+        .ok_or_else(|| BlockchainError::InvalidResponse("missing richlist field".to_string()))?;
+    let mut holders: Vec<RichlistEntry> = serde_json::from_value(list.clone())
+        .map_err(|e| BlockchainError::InvalidResponse(format!("failed to parse richlist: {}", e)))?;
+    // `window` relies on the order; nodes already send it sorted, so this is normally a no-op
+    holders.sort_by_key(|h| std::cmp::Reverse(h.flat));
+    Ok(holders)
+}
+
+/// A holder with its 1-based position in the richlist.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ranked<T> {
+    pub rank: usize,
+    #[serde(flatten)]
+    pub entry: T,
+}
+
+/// Where a queried address sits in the richlist.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddressRank {
+    pub address: String,
+    pub rank: usize,
+    /// False when the address is not a listed holder and `rank` is where it would be inserted
+    pub in_list: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RichlistWindow<T> {
+    pub total: usize,
+    pub entries: Vec<Ranked<T>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub around: Option<AddressRank>,
+}
+
+/// Which slice of the richlist to return.
+#[derive(Debug, Clone, Default)]
+pub struct WindowRequest<'a> {
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+    pub around_address: Option<&'a str>,
+    /// Balance of `around_address`, used to find its insertion rank when it isn't listed
+    pub around_balance: Option<f64>,
+}
+
+/// Slices a richlist sorted by descending balance. With `around_address`, the window is
+/// centred on the address (or on its insertion point) and `offset` is ignored.
+pub fn window<T, A, B>(entries: &[T], address: A, balance: B, req: &WindowRequest) -> RichlistWindow<T>
+where
+    T: Clone,
+    A: Fn(&T) -> &str,
+    B: Fn(&T) -> f64,
+{
+    let limit = req.limit.unwrap_or(DEFAULT_RICHLIST_LIMIT as usize).clamp(1, MAX_RICHLIST_LIMIT as usize);
+    let total = entries.len();
+
+    let (start, around) = match req.around_address {
+        Some(target) => {
+            let (idx, in_list) = match entries.iter().position(|e| address(e) == target) {
+                Some(idx) => (idx, true),
+                None => {
+                    let held = req.around_balance.unwrap_or(0.0);
+                    (entries.partition_point(|e| balance(e) >= held), false)
+                }
+            };
+            // Listed address: half the window above it, the rest (itself included) below.
+            // Unlisted: split the window evenly around the insertion point.
+            let above = if in_list { (limit - 1) / 2 } else { limit / 2 };
+            let start = idx.saturating_sub(above).min(total.saturating_sub(limit));
+            let rank = AddressRank {
+                address: target.to_string(),
+                rank: idx + 1,
+                in_list,
+            };
+            (start, Some(rank))
+        }
+        None => (req.offset.unwrap_or(0).min(total), None),
+    };
+
+    let entries = entries
+        .iter()
+        .enumerate()
+        .skip(start)
+        .take(limit)
+        .map(|(i, e)| Ranked {
+            rank: i + 1,
+            entry: e.clone(),
+        })
+        .collect();
+
+    RichlistWindow {
+        total,
+        entries,
+        around,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// `a1` holds 100, `a2` 99, ... `a10` 91.
+    fn holders(n: u64) -> Vec<(String, f64)> {
+        (1..=n).map(|i| (format!("a{}", i), 101.0 - i as f64)).collect()
+    }
+
+    fn slice(entries: &[(String, f64)], req: &WindowRequest) -> RichlistWindow<(String, f64)> {
+        window(entries, |e| e.0.as_str(), |e| e.1, req)
+    }
+
+    fn ranks<T>(window: &RichlistWindow<T>) -> Vec<usize> {
+        window.entries.iter().map(|e| e.rank).collect()
+    }
+
+    fn around<'a>(address: &'a str, limit: usize, balance: Option<f64>) -> WindowRequest<'a> {
+        WindowRequest { limit: Some(limit), around_address: Some(address), around_balance: balance, ..Default::default() }
+    }
+
+    #[test]
+    fn top_and_offset_windows() {
+        let list = holders(10);
+        let top = slice(&list, &WindowRequest { limit: Some(3), ..Default::default() });
+        assert_eq!(top.total, 10);
+        assert_eq!(ranks(&top), [1, 2, 3]);
+        assert_eq!(top.entries[0].entry.0, "a1");
+        assert!(top.around.is_none());
+
+        let paged = slice(&list, &WindowRequest { limit: Some(3), offset: Some(8), ..Default::default() });
+        assert_eq!(ranks(&paged), [9, 10]);
+        let past_end = slice(&list, &WindowRequest { limit: Some(3), offset: Some(50), ..Default::default() });
+        assert!(past_end.entries.is_empty());
+        assert_eq!(past_end.total, 10);
+    }
+
+    #[test]
+    fn limit_defaults_and_is_clamped() {
+        let list = holders(30);
+        assert_eq!(slice(&list, &WindowRequest::default()).entries.len(), DEFAULT_RICHLIST_LIMIT as usize);
+        assert_eq!(ranks(&slice(&list, &WindowRequest { limit: Some(0), ..Default::default() })), [1]);
+        let big = holders(MAX_RICHLIST_LIMIT + 10);
+        let clamped = slice(&big, &WindowRequest { limit: Some(10_000), ..Default::default() });
+        assert_eq!(clamped.entries.len(), MAX_RICHLIST_LIMIT as usize);
+    }
+
+    #[test]
+    fn listed_address_gets_its_rank_and_neighbours() {
+        let list = holders(10);
+        let window = slice(&list, &around("a5", 3, None));
+        let rank = window.around.as_ref().unwrap();
+        assert_eq!((rank.address.as_str(), rank.rank, rank.in_list), ("a5", 5, true));
+        assert_eq!(ranks(&window), [4, 5, 6]);
+
+        // Even limit: the extra slot goes below the address
+        assert_eq!(ranks(&slice(&list, &around("a5", 4, None))), [4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn window_is_shifted_inside_the_list_at_the_edges() {
+        let list = holders(10);
+        let first = slice(&list, &around("a1", 3, None));
+        assert_eq!(first.around.as_ref().unwrap().rank, 1);
+        assert_eq!(ranks(&first), [1, 2, 3]);
+
+        let last = slice(&list, &around("a10", 3, None));
+        assert_eq!(last.around.as_ref().unwrap().rank, 10);
+        assert_eq!(ranks(&last), [8, 9, 10]);
+
+        let wider_than_list = slice(&list, &around("a4", 50, None));
+        assert_eq!(ranks(&wider_than_list), (1..=10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn around_ignores_offset() {
+        let list = holders(10);
+        let req = WindowRequest { offset: Some(8), ..around("a2", 3, None) };
+        assert_eq!(ranks(&slice(&list, &req)), [1, 2, 3]);
+    }
+
+    #[test]
+    fn unlisted_address_gets_nearest_insertion_rank() {
+        let list = holders(10);
+        // 95.5 sits between a5 (96) and a6 (95)
+        let window = slice(&list, &around("outsider", 4, Some(95.5)));
+        let rank = window.around.as_ref().unwrap();
+        assert_eq!((rank.rank, rank.in_list), (6, false));
+        assert_eq!(ranks(&window), [4, 5, 6, 7]);
+
+        // A tie ranks after the listed holders with the same balance
+        assert_eq!(slice(&list, &around("outsider", 3, Some(96.0))).around.unwrap().rank, 6);
+        // Richer than everyone
+        let top = slice(&list, &around("whale", 3, Some(1_000.0)));
+        assert_eq!(top.around.as_ref().unwrap().rank, 1);
+        assert_eq!(ranks(&top), [1, 2, 3]);
+    }
+
+    #[test]
+    fn unlisted_address_without_balance_ranks_last() {
+        let list = holders(10);
+        let window = slice(&list, &around("outsider", 4, None));
+        let rank = window.around.as_ref().unwrap();
+        assert_eq!((rank.rank, rank.in_list), (11, false));
+        assert_eq!(ranks(&window), [7, 8, 9, 10]);
+    }
+
+    #[test]
+    fn empty_list() {
+        let window = slice(&[], &around("outsider", 5, Some(1.0)));
+        assert_eq!(window.total, 0);
+        assert!(window.entries.is_empty());
+        assert_eq!(window.around.as_ref().unwrap().rank, 1);
+    }
+
+    #[test]
+    fn holders_are_sorted_and_errors_surface() {
+        let response = json!({"error": "ok", "richlist": [
+            {"address": "small", "flat": 5, "float": 0.000000005},
+            {"address": "big", "flat": 9000000000u64, "float": 9.0},
+        ]});
+        let holders = holders_from(&response).unwrap();
+        assert_eq!(holders.iter().map(|h| h.address.as_str()).collect::<Vec<_>>(), ["big", "small"]);
+
+        assert!(holders_from(&json!({"error": "not_found"})).is_err());
+        assert!(holders_from(&json!({"error": "ok"})).is_err());
+    }
+}
@@ -0,0 +1,92 @@
+//! A BLS key the native server holds to sign Coin transfers itself, for automation that
+//! has no external signer (`AMADEUS_MCP_SIGNING_KEY`). The seed never leaves this type:
+//! errors are fixed strings and Debug shows only the public key.
+
+use crate::secret::Secret;
+use crate::wasm::tx::{self, BuiltTx, SigningKey};
+use std::fmt;
+use zeroize::Zeroizing;
+
+/// Setting holding the base58 64-byte BLS secret; the tool is hidden without it.
+pub const SERVER_KEY_VAR: &str = "AMADEUS_MCP_SIGNING_KEY";
+pub const SERVER_KEY_TRANSFER_TOOL: &str = "transfer_from_server_key";
+
+pub struct ServerKey {
+    seed: Zeroizing<Vec<u8>>,
+    public_key: String,
+}
+
+impl ServerKey {
+    pub fn from_secret(secret: &Secret) -> Result<Self, &'static str> {
+        let seed = Zeroizing::new(
+            bs58::decode(secret.expose().trim())
+                .into_vec()
+                .map_err(|_| "expected a base58 secret key")?,
+        );
+        let key = SigningKey::from_seed(&seed)?;
+        let public_key = bs58::encode(&key.public_key).into_string();
+        Ok(Self { seed, public_key })
+    }
+
+    /// The key's account, base58.
+    pub fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    /// Builds and signs a transfer of `amount` atoms of `symbol` to `receiver`.
+    pub fn transfer(&self, receiver: &[u8], symbol: &str, amount: u64, nonce: Option<i128>) -> Result<BuiltTx, &'static str> {
+        tx::build_transfer_tx(&self.seed, receiver, symbol, amount.into(), nonce)
+    }
+}
+
+impl fmt::Debug for ServerKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ServerKey").field("public_key", &self.public_key).finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::decode::decode_blob;
+
+    fn secret(seed: &[u8]) -> Secret {
+        Secret::new(bs58::encode(seed).into_string())
+    }
+
+    #[test]
+    fn transfers_are_signed_by_the_held_key() {
+        let key = ServerKey::from_secret(&secret(&[7u8; 64])).unwrap();
+        let receiver = [9u8; 48];
+        let built = key.transfer(&receiver, "AMA", 1_500_000_000, Some(42)).unwrap();
+
+        let decoded = decode_blob(&bs58::encode(&built.packed).into_string()).unwrap();
+        assert_eq!(decoded.signer, key.public_key());
+        assert_eq!((decoded.contract.as_str(), decoded.function.as_str()), ("Coin", "transfer"));
+        assert_eq!((decoded.nonce.as_str(), built.nonce), ("42", 42));
+        assert_eq!(decoded.hash_matches, Some(true));
+
+        let txu = tx::decode_signed(&built.packed).unwrap();
+        let public_key = bs58::decode(key.public_key()).into_vec().unwrap();
+        assert!(tx::verify_signature(&public_key, &built.hash, &txu.signature).is_ok());
+    }
+
+    #[test]
+    fn bad_secrets_fail_without_echoing_them() {
+        for value in ["0OIl-not-base58", &bs58::encode([7u8; 32]).into_string()] {
+            let err = ServerKey::from_secret(&Secret::new(value.to_string())).unwrap_err();
+            assert!(!err.contains(value), "{}", err);
+        }
+        assert_eq!(ServerKey::from_secret(&Secret::new("0OIl".into())).unwrap_err(), "expected a base58 secret key");
+        assert_eq!(ServerKey::from_secret(&secret(&[7u8; 32])).unwrap_err(), "secret key must be 64 bytes");
+    }
+
+    #[test]
+    fn debug_shows_only_the_public_key() {
+        let value = secret(&[7u8; 64]);
+        let key = ServerKey::from_secret(&value).unwrap();
+        let shown = format!("{:?}", key);
+        assert!(shown.contains(key.public_key()), "{}", shown);
+        assert!(!shown.contains(value.expose()) && !shown.contains("seed"), "{}", shown);
+    }
+}
@@ -0,0 +1,224 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Idle time after which a worker session and its buffered messages are dropped.
+pub const SESSION_TTL_SECS: u64 = 3600;
+/// Messages kept for replay per session; older ones are dropped first.
+pub const MAX_BUFFERED_EVENTS: usize = 256;
+/// Header carrying the session id on requests and on the initialize response.
+pub const SESSION_HEADER: &str = "mcp-session-id";
+/// Header a reconnecting stream sends with the last event id it received.
+pub const LAST_EVENT_ID_HEADER: &str = "last-event-id";
+
+/// One server-to-client message with the id it was streamed under.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionEvent {
+    pub id: u64,
+    pub message: Value,
+}
+
+/// Why a reconnect can't be served from the buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayError {
+    /// The client asked for events after one this session never issued.
+    UnknownEvent(u64),
+    /// Events after the client's last one were already dropped from the buffer.
+    Evicted { oldest_retained: u64 },
+}
+
+impl std::fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplayError::UnknownEvent(id) => write!(f, "event {} was never issued by this session", id),
+            ReplayError::Evicted { oldest_retained } => {
+                write!(f, "events before {} are no longer buffered; re-initialize the session", oldest_retained)
+            }
+        }
+    }
+}
+
+/// State of one Streamable HTTP session: the negotiated protocol version, a monotonic event
+/// counter and the recent server-to-client messages kept for `Last-Event-ID` replay.
+/// Serializable so the worker's session Durable Object can keep it in storage; the
+/// transport itself lives in the worker glue.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionState {
+    protocol_version: Option<String>,
+    /// Id of the last event issued; 0 before the first one
+    last_event_id: u64,
+    /// Oldest first, at most `MAX_BUFFERED_EVENTS`
+    events: Vec<SessionEvent>,
+    /// Unix seconds of the last request on this session
+    last_seen: u64,
+}
+
+impl SessionState {
+    /// A session opened by an initialize request answered with `protocol_version`.
+    pub fn open(protocol_version: impl Into<String>, now: u64) -> Self {
+        Self { protocol_version: Some(protocol_version.into()), last_seen: now, ..Self::default() }
+    }
+
+    /// Whether initialize has completed; requests on a session that isn't open are rejected.
+    pub fn is_open(&self) -> bool {
+        self.protocol_version.is_some()
+    }
+
+    pub fn protocol_version(&self) -> Option<&str> {
+        self.protocol_version.as_deref()
+    }
+
+    pub fn last_event_id(&self) -> u64 {
+        self.last_event_id
+    }
+
+    pub fn touch(&mut self, now: u64) {
+        self.last_seen = self.last_seen.max(now);
+    }
+
+    pub fn is_expired(&self, now: u64, ttl_secs: u64) -> bool {
+        now.saturating_sub(self.last_seen) >= ttl_secs
+    }
+
+    /// Unix seconds at which the session expires if it stays idle.
+    pub fn expires_at(&self, ttl_secs: u64) -> u64 {
+        self.last_seen.saturating_add(ttl_secs)
+    }
+
+    /// Buffers `message` under the next event id and returns that id.
+    pub fn push(&mut self, message: Value) -> u64 {
+        self.last_event_id += 1;
+        self.events.push(SessionEvent { id: self.last_event_id, message });
+        if self.events.len() > MAX_BUFFERED_EVENTS {
+            let excess = self.events.len() - MAX_BUFFERED_EVENTS;
+            self.events.drain(..excess);
+        }
+        self.last_event_id
+    }
+
+    /// The buffered events after `last_event_id`, oldest first. A fresh stream (no id) gets
+    /// nothing, since every event was already sent on the POST that produced it. Errors when
+    /// the id is from the future or when events after it were evicted, since replaying with
+    /// a gap would silently lose messages.
+    pub fn replay_after(&self, last_event_id: Option<u64>) -> Result<&[SessionEvent], ReplayError> {
+        let Some(after) = last_event_id else {
+            return Ok(&[]);
+        };
+        if after > self.last_event_id {
+            return Err(ReplayError::UnknownEvent(after));
+        }
+        let oldest_retained = self.events.first().map_or(self.last_event_id + 1, |e| e.id);
+        if after + 1 < oldest_retained {
+            return Err(ReplayError::Evicted { oldest_retained });
+        }
+        let start = self.events.partition_point(|e| e.id <= after);
+        Ok(&self.events[start..])
+    }
+}
+
+/// `Last-Event-ID` as sent by the client; unparsable values are treated as absent.
+pub fn parse_last_event_id(header: Option<&str>) -> Option<u64> {
+    header.and_then(|h| h.trim().parse().ok())
+}
+
+/// `events` as a `text/event-stream` body.
+pub fn to_sse(events: &[SessionEvent]) -> String {
+    events
+        .iter()
+        .map(|e| format!("id: {}\nevent: message\ndata: {}\n\n", e.id, e.message))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn session_with(messages: u64) -> SessionState {
+        let mut session = SessionState::open("2025-06-18", NOW);
+        for i in 1..=messages {
+            assert_eq!(session.push(json!({ "id": i })), i);
+        }
+        session
+    }
+
+    fn ids(events: &[SessionEvent]) -> Vec<u64> {
+        events.iter().map(|e| e.id).collect()
+    }
+
+    #[test]
+    fn a_reconnect_replays_what_it_missed() {
+        let session = session_with(5);
+        assert_eq!(ids(session.replay_after(Some(2)).unwrap()), [3, 4, 5]);
+        assert_eq!(session.replay_after(Some(3)).unwrap()[0].message, json!({ "id": 4 }));
+        assert!(session.replay_after(Some(5)).unwrap().is_empty());
+        assert_eq!(ids(session.replay_after(Some(0)).unwrap()), [1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn a_fresh_stream_replays_nothing() {
+        assert!(session_with(3).replay_after(None).unwrap().is_empty());
+        assert!(SessionState::open("2025-06-18", NOW).replay_after(Some(0)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn an_id_never_issued_is_refused() {
+        let session = session_with(2);
+        assert_eq!(session.replay_after(Some(3)).unwrap_err(), ReplayError::UnknownEvent(3));
+        assert_eq!(ReplayError::UnknownEvent(3).to_string(), "event 3 was never issued by this session");
+    }
+
+    #[test]
+    fn evicted_events_are_not_replayed_with_a_gap() {
+        let total = MAX_BUFFERED_EVENTS as u64 + 10;
+        let session = session_with(total);
+        assert_eq!(session.last_event_id(), total);
+        // The oldest retained event is 11, so a client that saw 10 still gets everything
+        assert_eq!(session.replay_after(Some(10)).unwrap().len(), MAX_BUFFERED_EVENTS);
+        assert_eq!(session.replay_after(Some(9)).unwrap_err(), ReplayError::Evicted { oldest_retained: 11 });
+        assert_eq!(
+            ReplayError::Evicted { oldest_retained: 11 }.to_string(),
+            "events before 11 are no longer buffered; re-initialize the session"
+        );
+    }
+
+    #[test]
+    fn idle_sessions_expire_and_requests_keep_them_alive() {
+        let mut session = session_with(1);
+        assert_eq!(session.expires_at(SESSION_TTL_SECS), NOW + SESSION_TTL_SECS);
+        assert!(!session.is_expired(NOW + SESSION_TTL_SECS - 1, SESSION_TTL_SECS));
+        assert!(session.is_expired(NOW + SESSION_TTL_SECS, SESSION_TTL_SECS));
+
+        session.touch(NOW + 1800);
+        assert!(!session.is_expired(NOW + SESSION_TTL_SECS, SESSION_TTL_SECS));
+        // A late-arriving request with an older clock does not shorten the session
+        session.touch(NOW);
+        assert_eq!(session.expires_at(SESSION_TTL_SECS), NOW + 1800 + SESSION_TTL_SECS);
+    }
+
+    #[test]
+    fn only_initialized_sessions_are_open() {
+        assert!(!SessionState::default().is_open());
+        let session = SessionState::open("2025-06-18", NOW);
+        assert!(session.is_open());
+        assert_eq!(session.protocol_version(), Some("2025-06-18"));
+    }
+
+    #[test]
+    fn stored_state_resumes_the_event_sequence() {
+        let stored = serde_json::to_string(&session_with(3)).unwrap();
+        let mut restored: SessionState = serde_json::from_str(&stored).unwrap();
+        assert_eq!(ids(restored.replay_after(Some(1)).unwrap()), [2, 3]);
+        assert_eq!(restored.push(json!("next")), 4);
+    }
+
+    #[test]
+    fn last_event_id_header_and_sse_body() {
+        assert_eq!(parse_last_event_id(Some(" 42 ")), Some(42));
+        assert_eq!(parse_last_event_id(Some("abc")), None);
+        assert_eq!(parse_last_event_id(None), None);
+        let events = [SessionEvent { id: 7, message: json!({ "a": 1 }) }];
+        assert_eq!(to_sse(&events), "id: 7\nevent: message\ndata: {\"a\":1}\n\n");
+    }
+}
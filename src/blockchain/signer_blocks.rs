@@ -0,0 +1,54 @@
+//! The entries one validator key produced over a range of heights.
+
+use super::block_range::FailedHeight;
+use super::types::BlockEntry;
+use serde::Serialize;
+
+/// Most heights one get_blocks_by_signer call may span.
+pub const MAX_SIGNER_RANGE: u64 = 500;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SignedEntries {
+    pub signer: String,
+    pub start_height: u64,
+    pub end_height: u64,
+    /// Heights fetched, failed ones excluded
+    pub heights_scanned: u64,
+    /// Entries at those heights, by any signer
+    pub entries_scanned: usize,
+    /// Entries `signer` signed
+    pub count: usize,
+    /// Heights with at least one entry `signer` signed
+    pub heights_signed: usize,
+    /// count / entries_scanned, in percent
+    pub share_percent: f64,
+    /// The signed entries, in height order
+    pub entries: Vec<BlockEntry>,
+    /// Heights that could not be fetched; the share leaves them out
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub failed: Vec<FailedHeight>,
+}
+
+impl SignedEntries {
+    /// Keeps the entries of `start..=end` that `signer` signed.
+    pub fn new(signer: &str, start: u64, end: u64, entries: Vec<BlockEntry>, mut failed: Vec<FailedHeight>) -> Self {
+        failed.sort_by_key(|f| f.height);
+        let entries_scanned = entries.len();
+        let mut signed: Vec<BlockEntry> = entries.into_iter().filter(|e| e.header.signer == signer).collect();
+        signed.sort_by_key(|e| e.header.height);
+        let mut heights: Vec<u64> = signed.iter().map(|e| e.header.height).collect();
+        heights.dedup();
+        Self {
+            signer: signer.to_string(),
+            start_height: start,
+            end_height: end,
+            heights_scanned: (end - start + 1).saturating_sub(failed.len() as u64),
+            entries_scanned,
+            count: signed.len(),
+            heights_signed: heights.len(),
+            share_percent: if entries_scanned == 0 { 0.0 } else { signed.len() as f64 * 100.0 / entries_scanned as f64 },
+            entries: signed,
+            failed,
+        }
+    }
+}
@@ -0,0 +1,214 @@
+use super::types::{BlockEntry, ValidatorInfo};
+use serde::Serialize;
+use std::collections::HashMap;
+
+pub const DEFAULT_SIGNER_WINDOW: u64 = 100;
+pub const MAX_SIGNER_WINDOW: u64 = 1000;
+/// A signer whose share is this many times its expected share, or this fraction of it,
+/// is flagged as skewed.
+const SKEW_FACTOR: f64 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Skew {
+    Normal,
+    /// Signed at least `SKEW_FACTOR` times its expected share
+    Over,
+    /// Signed at most 1/`SKEW_FACTOR` of its expected share, or nothing at all
+    Under,
+    /// Signed entries without being in the current trainer set
+    NotTrainer,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SignerShare {
+    pub signer: String,
+    pub entries: u64,
+    /// Fraction of the window's entries
+    pub share: f64,
+    /// 1 / number of trainers; 0 for signers outside the trainer set
+    pub expected_share: f64,
+    /// share / expected_share, `None` outside the trainer set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ratio: Option<f64>,
+    pub skew: Skew,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rank: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SignerDistribution {
+    pub from_height: u64,
+    pub to_height: u64,
+    pub entries: u64,
+    pub trainers: usize,
+    /// Every signer seen in the window and every trainer, most entries first
+    pub signers: Vec<SignerShare>,
+    /// Trainers that signed nothing in the window
+    pub silent_trainers: Vec<String>,
+    /// Signers of the window that are not in the current trainer set; the set may have
+    /// changed within the window
+    pub unknown_signers: Vec<String>,
+}
+
+/// Counts `entries` per signer and joins the counts with the trainer set.
+pub fn distribution(entries: &[BlockEntry], trainers: &[ValidatorInfo], from_height: u64, to_height: u64) -> SignerDistribution {
+    let mut counts: HashMap<&str, u64> = HashMap::new();
+    for entry in entries {
+        *counts.entry(entry.header.signer.as_str()).or_default() += 1;
+    }
+    let total = entries.len() as u64;
+    let share_of = |n: u64| if total == 0 { 0.0 } else { n as f64 / total as f64 };
+    let expected = if trainers.is_empty() { 0.0 } else { 1.0 / trainers.len() as f64 };
+
+    let mut signers: Vec<SignerShare> = trainers
+        .iter()
+        .map(|trainer| {
+            let n = counts.get(trainer.pk.as_str()).copied().unwrap_or(0);
+            let ratio = share_of(n) / expected;
+            SignerShare {
+                signer: trainer.pk.clone(),
+                entries: n,
+                share: share_of(n),
+                expected_share: expected,
+                ratio: Some(ratio),
+                skew: if total == 0 {
+                    Skew::Normal
+                } else if ratio >= SKEW_FACTOR {
+                    Skew::Over
+                } else if ratio <= 1.0 / SKEW_FACTOR {
+                    Skew::Under
+                } else {
+                    Skew::Normal
+                },
+                score: trainer.score,
+                rank: trainer.rank,
+            }
+        })
+        .collect();
+
+    let mut unknown_signers = Vec::new();
+    for (signer, n) in &counts {
+        if !trainers.iter().any(|t| t.pk == *signer) {
+            unknown_signers.push(signer.to_string());
+            signers.push(SignerShare {
+                signer: signer.to_string(),
+                entries: *n,
+                share: share_of(*n),
+                expected_share: 0.0,
+                ratio: None,
+                skew: Skew::NotTrainer,
+                score: None,
+                rank: None,
+            });
+        }
+    }
+    unknown_signers.sort();
+    signers.sort_by(|a, b| b.entries.cmp(&a.entries).then_with(|| a.signer.cmp(&b.signer)));
+
+    SignerDistribution {
+        from_height,
+        to_height,
+        entries: total,
+        trainers: trainers.len(),
+        silent_trainers: signers
+            .iter()
+            .filter(|s| s.entries == 0 && s.skew != Skew::NotTrainer)
+            .map(|s| s.signer.clone())
+            .collect(),
+        signers,
+        unknown_signers,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn entry(height: u64, signer: &str) -> BlockEntry {
+        serde_json::from_value(json!({
+            "hash": format!("entry-{}", height),
+            "header": {
+                "slot": height, "height": height, "dr": "dr", "vr": "vr", "prev_hash": "prev", "signer": signer,
+                "root_tx": "rtx", "root_validator": "rv", "prev_slot": height
+            },
+            "tx_count": 0
+        }))
+        .unwrap()
+    }
+
+    fn trainer(pk: &str, rank: u64) -> ValidatorInfo {
+        ValidatorInfo { pk: pk.into(), score: Some(100.0 / rank as f64), rank: Some(rank), epoch: Some(7), emission_address: None }
+    }
+
+    /// Ten entries: A signs six, B two, D and the non-trainer X one each, C none.
+    fn window() -> Vec<BlockEntry> {
+        ["A", "A", "B", "A", "D", "A", "X", "A", "B", "A"].iter().enumerate().map(|(i, s)| entry(100 + i as u64, s)).collect()
+    }
+
+    fn trainers() -> Vec<ValidatorInfo> {
+        vec![trainer("A", 1), trainer("B", 2), trainer("C", 3), trainer("D", 4)]
+    }
+
+    fn share<'a>(distribution: &'a SignerDistribution, signer: &str) -> &'a SignerShare {
+        distribution.signers.iter().find(|s| s.signer == signer).unwrap()
+    }
+
+    #[test]
+    fn shares_are_compared_with_an_even_split() {
+        let d = distribution(&window(), &trainers(), 100, 109);
+        assert_eq!((d.from_height, d.to_height, d.entries, d.trainers), (100, 109, 10, 4));
+
+        let a = share(&d, "A");
+        assert_eq!((a.entries, a.share, a.expected_share, a.skew), (6, 0.6, 0.25, Skew::Over));
+        assert!((a.ratio.unwrap() - 2.4).abs() < 1e-9);
+        assert_eq!((a.score, a.rank), (Some(100.0), Some(1)));
+
+        assert_eq!(share(&d, "B").skew, Skew::Normal);
+        assert_eq!((share(&d, "D").entries, share(&d, "D").skew), (1, Skew::Under));
+        assert_eq!((share(&d, "C").entries, share(&d, "C").skew), (0, Skew::Under));
+    }
+
+    #[test]
+    fn trainers_that_signed_nothing_are_silent() {
+        let d = distribution(&window(), &trainers(), 100, 109);
+        assert_eq!(d.silent_trainers, vec!["C"]);
+    }
+
+    #[test]
+    fn signers_outside_the_trainer_set_are_reported_apart() {
+        let d = distribution(&window(), &trainers(), 100, 109);
+        assert_eq!(d.unknown_signers, vec!["X"]);
+        let x = share(&d, "X");
+        assert_eq!((x.entries, x.share, x.expected_share, x.ratio, x.skew), (1, 0.1, 0.0, None, Skew::NotTrainer));
+        assert_eq!((x.score, x.rank), (None, None));
+        assert!(serde_json::to_value(x).unwrap().get("ratio").is_none());
+    }
+
+    #[test]
+    fn signers_are_ordered_by_entries_then_key() {
+        let d = distribution(&window(), &trainers(), 100, 109);
+        let order: Vec<&str> = d.signers.iter().map(|s| s.signer.as_str()).collect();
+        assert_eq!(order, vec!["A", "B", "D", "X", "C"]);
+    }
+
+    #[test]
+    fn an_empty_window_flags_no_skew() {
+        let d = distribution(&[], &trainers(), 100, 99);
+        assert_eq!(d.entries, 0);
+        assert!(d.signers.iter().all(|s| s.skew == Skew::Normal && s.share == 0.0));
+        assert_eq!(d.silent_trainers, vec!["A", "B", "C", "D"]);
+    }
+
+    #[test]
+    fn without_a_trainer_set_every_signer_is_unknown() {
+        let d = distribution(&window(), &[], 100, 109);
+        assert_eq!(d.trainers, 0);
+        assert_eq!(d.unknown_signers, vec!["A", "B", "D", "X"]);
+        assert!(d.signers.iter().all(|s| s.skew == Skew::NotTrainer));
+        assert!(d.silent_trainers.is_empty());
+    }
+}
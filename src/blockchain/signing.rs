@@ -0,0 +1,538 @@
+use crate::secret::Secret;
+use crate::wasm::tx::{self, SigningKey, SIGNING_DST};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+const FLOW_AMOUNT: &str = "1";
+const FLOW_SYMBOL: &str = "AMA";
+const FLOW_NONCE: i64 = 1;
+
+/// The all-zero public key: not a point anyone holds the secret for, so the workflow
+/// doc's example can never be signed or submitted.
+const BURN_ADDRESS: [u8; 48] = [0; 48];
+
+/// DSTs commonly used by generic BLS tooling, tried when an external signature fails
+/// so the report can name the mismatch.
+const FOREIGN_DSTS: &[&[u8]] = &[
+    b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_NUL_",
+    b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SigningFlowStep {
+    pub step: String,
+    pub passed: bool,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SigningFlowReport {
+    pub passed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failed_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub public_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blob: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signing_payload: Option<String>,
+    /// Whether the server generated the key because none was passed
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub generated_key: bool,
+    pub steps: Vec<SigningFlowStep>,
+}
+
+#[derive(Default)]
+struct Report {
+    steps: Vec<SigningFlowStep>,
+    public_key: Option<String>,
+    blob: Option<String>,
+    signing_payload: Option<String>,
+}
+
+impl Report {
+    fn pass(&mut self, step: &str, detail: impl Into<String>) {
+        self.push(step, true, detail.into());
+    }
+
+    fn fail(mut self, step: &str, detail: impl Into<String>) -> SigningFlowReport {
+        self.push(step, false, detail.into());
+        self.finish()
+    }
+
+    fn push(&mut self, step: &str, passed: bool, detail: String) {
+        self.steps.push(SigningFlowStep {
+            step: step.to_string(),
+            passed,
+            detail,
+        });
+    }
+
+    fn finish(self) -> SigningFlowReport {
+        let failed_at = self.steps.iter().find(|s| !s.passed).map(|s| s.step.clone());
+        SigningFlowReport {
+            passed: failed_at.is_none(),
+            failed_at,
+            public_key: self.public_key,
+            blob: self.blob,
+            signing_payload: self.signing_payload,
+            generated_key: false,
+            steps: self.steps,
+        }
+    }
+}
+
+/// Outcome of verify_signature. `failed_check` names the first check that failed, with
+/// the same codes test_signing_flow reports.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignatureCheck {
+    pub valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failed_check: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl SignatureCheck {
+    fn failed(check: &str, detail: impl Into<String>) -> Self {
+        Self { valid: false, failed_check: Some(check.to_string()), detail: Some(detail.into()) }
+    }
+}
+
+/// Reads a 32-byte signing hash given as hex (signing_payload) or base58 (transaction_hash).
+fn parse_hash(payload: &str) -> Option<Vec<u8>> {
+    let payload = payload.trim();
+    hex::decode(payload)
+        .ok()
+        .filter(|bytes| bytes.len() == 32)
+        .or_else(|| bs58::decode(payload).into_vec().ok().filter(|bytes| bytes.len() == 32))
+}
+
+/// Verifies a BLS signature over a transaction signing hash with the Amadeus transaction
+/// DST. Nothing about the transaction itself is needed.
+pub fn verify_external(signing_payload: &str, signature_b58: &str, public_key_b58: &str) -> SignatureCheck {
+    let Some(hash) = parse_hash(signing_payload) else {
+        return SignatureCheck::failed("bad_payload_encoding", "signing_payload must be a 32-byte hash in hex or base58");
+    };
+    let Ok(public_key) = bs58::decode(public_key_b58.trim()).into_vec() else {
+        return SignatureCheck::failed(tx::SignatureError::BadPublicKey.as_str(), "public_key is not valid base58");
+    };
+    let Ok(signature) = bs58::decode(signature_b58.trim()).into_vec() else {
+        return SignatureCheck::failed(tx::SignatureError::BadSignatureEncoding.as_str(), "signature is not valid base58");
+    };
+
+    match tx::verify_signature(&public_key, &hash, &signature) {
+        Ok(()) => SignatureCheck { valid: true, failed_check: None, detail: None },
+        Err(e @ tx::SignatureError::Mismatch) => SignatureCheck::failed(
+            e.as_str(),
+            likely_mistake(&public_key, &hash, &signature)
+                .unwrap_or("signature does not verify for this key and payload"),
+        ),
+        Err(e @ tx::SignatureError::BadPublicKey) => {
+            SignatureCheck::failed(e.as_str(), "public_key is not a valid 48-byte compressed G1 point")
+        }
+        Err(e @ tx::SignatureError::BadSignatureEncoding) => {
+            SignatureCheck::failed(e.as_str(), "signature is not a 96-byte compressed G2 point")
+        }
+        Err(e @ tx::SignatureError::NotInSubgroup) => {
+            SignatureCheck::failed(e.as_str(), "signature point is not in the G2 subgroup")
+        }
+    }
+}
+
+/// Names a common signer mistake when a signature verifies under something other than
+/// the Amadeus TX tag over the raw hash.
+fn likely_mistake(public_key: &[u8], hash: &[u8], signature: &[u8]) -> Option<&'static str> {
+    if FOREIGN_DSTS
+        .iter()
+        .any(|dst| tx::verify_with_dst(public_key, hash, signature, dst).is_ok())
+    {
+        return Some("DST mismatch: signer used a generic BLS domain separation tag instead of the Amadeus TX tag");
+    }
+    let hex_payload = hex::encode(hash);
+    if tx::verify_signature(public_key, hex_payload.as_bytes(), signature).is_ok() {
+        return Some("hash mismatch: signer signed the hex string of the payload instead of its 32 raw bytes");
+    }
+    None
+}
+
+/// A fresh random 64-byte seed in base58, for a flow run without a caller's key. It holds
+/// no funds and is dropped with the call.
+pub fn throwaway_key() -> Result<Secret, String> {
+    let mut seed = Zeroizing::new([0u8; 64]);
+    getrandom::getrandom(seed.as_mut()).map_err(|e| format!("random source unavailable: {}", e))?;
+    Ok(Secret::new(bs58::encode(seed.as_ref()).into_string()))
+}
+
+/// Runs build → sign → verify → finalize → decode locally for a throwaway key and
+/// reports the first step that disagrees. Nothing is submitted.
+///
+/// When `external_signature` is given it must be the signer's output for the blob this
+/// flow builds (same key, fixed nonce), and is checked alongside the local signature.
+pub fn run_signing_flow(secret_key_b58: &str, external_signature: Option<&str>) -> SigningFlowReport {
+    let mut report = Report::default();
+
+    let seed = match bs58::decode(secret_key_b58.trim()).into_vec() {
+        Ok(seed) => Zeroizing::new(seed),
+        Err(_) => return report.fail("derive_key", "secret key is not valid base58"),
+    };
+    let key = match SigningKey::from_seed(&seed) {
+        Ok(key) => key,
+        Err(e) => return report.fail("derive_key", e),
+    };
+    let pk_b58 = bs58::encode(&key.public_key).into_string();
+    report.public_key = Some(pk_b58.clone());
+    report.pass("derive_key", format!("derived {}-byte public key", key.public_key.len()));
+
+    let args = vec![
+        key.public_key.clone(),
+        FLOW_AMOUNT.as_bytes().to_vec(),
+        FLOW_SYMBOL.as_bytes().to_vec(),
+    ];
+    let unsigned = match tx::build_unsigned(&key.public_key, "Coin", "transfer", &args, None, None, Some(FLOW_NONCE)) {
+        Ok(unsigned) => unsigned,
+        Err(e) => return report.fail("build_blob", e),
+    };
+    let blob_b58 = bs58::encode(&unsigned.tx_blob).into_string();
+    report.blob = Some(blob_b58.clone());
+    report.signing_payload = Some(hex::encode(unsigned.signing_hash));
+    report.pass("build_blob", format!("self-transfer of {} atom {}, {} bytes", FLOW_AMOUNT, FLOW_SYMBOL, unsigned.tx_blob.len()));
+
+    let rehash: [u8; 32] = Sha256::digest(&unsigned.tx_blob).into();
+    if rehash != unsigned.signing_hash {
+        return report.fail("hash", "hash mismatch: signing hash is not SHA-256 of the blob");
+    }
+    report.pass("hash", "signing hash is SHA-256 of the blob");
+
+    let signature = key.sign(&unsigned.signing_hash);
+    report.pass("sign", format!("signed with DST {}", String::from_utf8_lossy(SIGNING_DST)));
+
+    if let Err(e) = tx::verify_signature(&key.public_key, &unsigned.signing_hash, &signature) {
+        return report.fail("verify", format!("local signature failed verification: {}", e.as_str()));
+    }
+    report.pass("verify", "local signature verifies against the public key");
+
+    let submit_signature = match external_signature {
+        Some(ext) => match check_external(&key.public_key, &unsigned.tx_blob, &unsigned.signing_hash, ext) {
+            Ok(bytes) => {
+                report.pass("external_signature", "external signature verifies with the Amadeus DST");
+                bytes
+            }
+            Err(detail) => return report.fail("external_signature", detail),
+        },
+        None => signature,
+    };
+
+    let signature_b58 = bs58::encode(&submit_signature).into_string();
+    let finalized = match tx::finalize_transaction(&blob_b58, &signature_b58) {
+        Ok(finalized) => finalized,
+        Err(e) => return report.fail("finalize", format!("encoding mismatch: {}", e)),
+    };
+    report.pass("finalize", format!("packed signed transaction, {} bytes", finalized.packed.len()));
+
+    let decoded = match tx::decode_signed(&finalized.packed) {
+        Ok(decoded) => decoded,
+        Err(e) => return report.fail("decode", format!("encoding mismatch: {}", e)),
+    };
+
+    let mut mismatches = vec![];
+    if decoded.hash != unsigned.signing_hash {
+        mismatches.push("hash");
+    }
+    if decoded.signature != submit_signature {
+        mismatches.push("signature");
+    }
+    if decoded.tx.signer != key.public_key {
+        mismatches.push("signer");
+    }
+    if decoded.tx.nonce != FLOW_NONCE as i128 {
+        mismatches.push("nonce");
+    }
+    let action = &decoded.tx.action;
+    if action.contract != "Coin" || action.function != "transfer" || action.op != "call" {
+        mismatches.push("action");
+    }
+    if action.args != args {
+        mismatches.push("args");
+    }
+
+    if !mismatches.is_empty() {
+        return report.fail("decode", format!("decoded fields differ: {}", mismatches.join(", ")));
+    }
+    report.pass("decode", "decoded transaction matches the built fields");
+
+    report.finish()
+}
+
+/// The create → sign → submit walkthrough served at `catalog::SIGNING_WORKFLOW_URI`.
+/// Its constants and example blob come from the transaction builder itself, and the
+/// blob is decoded back before it is shown, so the doc cannot drift from the code.
+pub fn workflow_doc() -> Result<String, &'static str> {
+    let burn = bs58::encode(BURN_ADDRESS).into_string();
+    let args = vec![BURN_ADDRESS.to_vec(), b"0".to_vec(), FLOW_SYMBOL.as_bytes().to_vec()];
+    let unsigned = tx::build_unsigned(&BURN_ADDRESS, "Coin", "transfer", &args, None, None, Some(FLOW_NONCE))?;
+    let blob = bs58::encode(&unsigned.tx_blob).into_string();
+
+    let decoded: tx::Tx = vecpak::from_slice(&unsigned.tx_blob).map_err(|_| "failed to decode tx")?;
+    let rehash: [u8; 32] = Sha256::digest(&unsigned.tx_blob).into();
+    if decoded.signer != BURN_ADDRESS || decoded.nonce != FLOW_NONCE as i128 || decoded.action.args != args || rehash != unsigned.signing_hash {
+        return Err("example blob does not round-trip");
+    }
+
+    Ok(format!(
+        r#"Signing workflow: create_transaction -> sign locally -> submit_transaction
+
+The server never sees a secret key. It builds the unsigned transaction, you sign its
+hash with your own key, and it packs and broadcasts the signed result.
+
+1. create_transaction
+   Arguments: {{"signer":"<your public key, base58>","contract":"Coin","function":"transfer","args":[{{"b58":"<recipient>"}},"<amount in atoms>","AMA"]}}
+   Returns blob (base58 unsigned transaction) and signing_payload (hex).
+
+   Example, built by this server for a zero-amount transfer from and to the burn
+   address {burn}, which nobody can sign for:
+     blob:            {blob}
+     signing_payload: {payload}
+     nonce:           {nonce}
+   The blob decodes to signer {burn}, Coin.transfer({burn}, 0, {symbol}), nonce {nonce}.
+
+2. Sign
+   Scheme:  BLS12-381 min-pk (48-byte G1 public key, 96-byte G2 signature)
+   DST:     {dst}
+   Message: the 32 raw bytes of signing_payload, i.e. hex-decode it first. The payload
+            is SHA-256 of the blob bytes. Do not sign the hex string or the blob itself.
+   Key:     the 64-byte secret seed reduced modulo the curve order (from_bytes_wide),
+            as a 32-byte big-endian scalar.
+
+   Rust (blst):
+     let sk = blst::min_pk::SecretKey::from_bytes(&scalar_be)?;
+     let signature = sk.sign(&hex::decode(signing_payload)?, b"{dst}", &[]).to_bytes();
+   Any library works if it lets you set the DST; the default tags of generic BLS tools
+   ({foreign}) produce signatures the chain rejects.
+
+   On the stdio server, test_signing_flow runs these steps for a throwaway key and
+   names the step that disagrees; pass it an external signature to check your signer.
+
+3. submit_transaction
+   Arguments: {{"transaction":"<blob from step 1>","signature":"<signature, base58>"}}
+   Optional: network ("mainnet" or "testnet") and idempotency_key. A blob is
+   single-use: once its nonce is consumed, rebuild it with create_transaction.
+"#,
+        burn = burn,
+        blob = blob,
+        payload = hex::encode(unsigned.signing_hash),
+        nonce = unsigned.nonce,
+        symbol = FLOW_SYMBOL,
+        dst = String::from_utf8_lossy(SIGNING_DST),
+        foreign = FOREIGN_DSTS.iter().map(|d| String::from_utf8_lossy(d)).collect::<Vec<_>>().join(", "),
+    ))
+}
+
+/// Checks a signature produced by an external signer and names the likely mistake
+/// when it doesn't verify.
+fn check_external(public_key: &[u8], blob: &[u8], hash: &[u8; 32], signature_b58: &str) -> Result<Vec<u8>, String> {
+    let signature = bs58::decode(signature_b58.trim())
+        .into_vec()
+        .map_err(|_| "encoding mismatch: signature is not valid base58".to_string())?;
+
+    match tx::verify_signature(public_key, hash, &signature) {
+        Ok(()) => return Ok(signature),
+        Err(tx::SignatureError::Mismatch) => {}
+        Err(e) => return Err(format!("external signature rejected: {}", e.as_str())),
+    }
+
+    if let Some(mistake) = likely_mistake(public_key, hash, &signature) {
+        return Err(mistake.into());
+    }
+    if tx::verify_signature(public_key, blob, &signature).is_ok() {
+        return Err("hash mismatch: signer signed the raw blob instead of its SHA-256 hash".into());
+    }
+
+    Err("signature mismatch: signature does not verify for this key and payload".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fixed throwaway seed, so failures reproduce.
+    fn seed() -> [u8; 64] {
+        let mut seed = [0u8; 64];
+        for (i, byte) in seed.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        seed
+    }
+
+    fn secret_b58() -> String {
+        bs58::encode(seed()).into_string()
+    }
+
+    /// The flow's key as a blst key, to sign the way a misconfigured external signer would.
+    fn blst_key() -> blst::min_pk::SecretKey {
+        let scalar = bls12_381::Scalar::from_bytes_wide(&seed());
+        let mut be = scalar.to_bytes();
+        be.reverse();
+        blst::min_pk::SecretKey::from_bytes(&be).unwrap()
+    }
+
+    fn payload() -> (Vec<u8>, Vec<u8>) {
+        let report = run_signing_flow(&secret_b58(), None);
+        let blob = bs58::decode(report.blob.unwrap()).into_vec().unwrap();
+        (blob, hex::decode(report.signing_payload.unwrap()).unwrap())
+    }
+
+    fn with_external(signature: &[u8]) -> SigningFlowReport {
+        run_signing_flow(&secret_b58(), Some(&bs58::encode(signature).into_string()))
+    }
+
+    fn failed_step(report: &SigningFlowReport) -> &SigningFlowStep {
+        report.steps.iter().find(|s| !s.passed).expect("a failed step")
+    }
+
+    #[test]
+    fn known_good_key_passes_every_step() {
+        let report = run_signing_flow(&secret_b58(), None);
+        assert!(report.passed, "{:?}", report.steps);
+        assert_eq!(report.failed_at, None);
+        let steps: Vec<&str> = report.steps.iter().map(|s| s.step.as_str()).collect();
+        assert_eq!(steps, ["derive_key", "build_blob", "hash", "sign", "verify", "finalize", "decode"]);
+        assert_eq!(report.public_key.unwrap(), bs58::encode(SigningKey::from_seed(&seed()).unwrap().public_key).into_string());
+    }
+
+    #[test]
+    fn flow_is_deterministic_for_a_key() {
+        let (a, b) = (run_signing_flow(&secret_b58(), None), run_signing_flow(&secret_b58(), None));
+        assert_eq!(a.blob, b.blob);
+        assert_eq!(a.signing_payload, b.signing_payload);
+    }
+
+    #[test]
+    fn correct_external_signature_passes() {
+        let (_, hash) = payload();
+        let report = with_external(&SigningKey::from_seed(&seed()).unwrap().sign(&hash));
+        assert!(report.passed, "{:?}", report.steps);
+        assert!(report.steps.iter().any(|s| s.step == "external_signature" && s.passed));
+    }
+
+    #[test]
+    fn bad_secret_key_fails_at_derive_key() {
+        for secret in ["0OIl", &bs58::encode([1u8; 32]).into_string()] {
+            let report = run_signing_flow(secret, None);
+            assert!(!report.passed);
+            assert_eq!(report.failed_at.as_deref(), Some("derive_key"));
+            assert_eq!(report.steps.len(), 1);
+        }
+    }
+
+    #[test]
+    fn generic_dst_is_reported_as_dst_mismatch() {
+        let (_, hash) = payload();
+        let signature = blst_key().sign(&hash, FOREIGN_DSTS[0], &[]).to_bytes();
+        let report = with_external(&signature);
+        assert_eq!(report.failed_at.as_deref(), Some("external_signature"));
+        assert!(failed_step(&report).detail.starts_with("DST mismatch"), "{}", failed_step(&report).detail);
+    }
+
+    #[test]
+    fn signing_the_hex_payload_is_reported_as_hash_mismatch() {
+        let (_, hash) = payload();
+        let signature = blst_key().sign(hex::encode(&hash).as_bytes(), SIGNING_DST, &[]).to_bytes();
+        let report = with_external(&signature);
+        assert_eq!(report.failed_at.as_deref(), Some("external_signature"));
+        assert!(failed_step(&report).detail.contains("hex string"), "{}", failed_step(&report).detail);
+    }
+
+    #[test]
+    fn signing_the_raw_blob_is_reported_as_hash_mismatch() {
+        let (blob, _) = payload();
+        let signature = blst_key().sign(&blob, SIGNING_DST, &[]).to_bytes();
+        let report = with_external(&signature);
+        assert_eq!(report.failed_at.as_deref(), Some("external_signature"));
+        assert!(failed_step(&report).detail.contains("raw blob"), "{}", failed_step(&report).detail);
+    }
+
+    #[test]
+    fn corrupted_signatures_fail_at_external_signature() {
+        let (_, hash) = payload();
+        let good = SigningKey::from_seed(&seed()).unwrap().sign(&hash);
+        let other_key = SigningKey::from_seed(&[9u8; 64]).unwrap().sign(&hash);
+        let cases: [(&str, Vec<u8>, &str); 3] = [
+            ("truncated", good[..95].to_vec(), "external signature rejected: bad_signature_encoding"),
+            ("other key", other_key, "signature mismatch"),
+            ("not a point", vec![0xff; 96], "external signature rejected"),
+        ];
+        for (name, signature, expected) in cases {
+            let report = with_external(&signature);
+            assert_eq!(report.failed_at.as_deref(), Some("external_signature"), "{}", name);
+            assert!(failed_step(&report).detail.starts_with(expected), "{}: {}", name, failed_step(&report).detail);
+            // The flow stops at the broken step: nothing is finalized from a bad signature
+            assert!(!report.steps.iter().any(|s| s.step == "finalize"), "{}", name);
+        }
+
+        let report = run_signing_flow(&secret_b58(), Some("0OIl"));
+        assert_eq!(report.failed_at.as_deref(), Some("external_signature"));
+        assert!(failed_step(&report).detail.starts_with("encoding mismatch"));
+    }
+
+    #[test]
+    fn verify_external_names_the_failed_check() {
+        let (_, hash) = payload();
+        let key = SigningKey::from_seed(&seed()).unwrap();
+        let (pk, payload) = (bs58::encode(&key.public_key).into_string(), hex::encode(&hash));
+        let sign = |sig: &[u8]| bs58::encode(sig).into_string();
+
+        assert!(verify_external(&payload, &sign(&key.sign(&hash)), &pk).valid);
+        let cases = [
+            ("zz", sign(&key.sign(&hash)), pk.clone(), "bad_payload_encoding"),
+            (payload.as_str(), sign(&key.sign(&hash)), sign(&[1; 48]), "bad_public_key"),
+            (payload.as_str(), sign(&[1; 10]), pk.clone(), "bad_signature_encoding"),
+            (payload.as_str(), sign(&key.sign(&[0; 32])), pk.clone(), "signature_mismatch"),
+        ];
+        for (payload, signature, pk, check) in cases {
+            let result = verify_external(payload, &signature, &pk);
+            assert!(!result.valid);
+            assert_eq!(result.failed_check.as_deref(), Some(check));
+        }
+    }
+
+    #[test]
+    fn throwaway_keys_are_fresh_and_usable() {
+        let (a, b) = (throwaway_key().unwrap(), throwaway_key().unwrap());
+        assert_ne!(a.expose(), b.expose());
+        assert_eq!(bs58::decode(a.expose()).into_vec().unwrap().len(), 64);
+        assert!(run_signing_flow(a.expose(), None).passed);
+    }
+
+    /// The value after `label` on its line of the workflow doc.
+    fn doc_field<'a>(doc: &'a str, label: &str) -> &'a str {
+        doc.lines().find_map(|line| line.trim().strip_prefix(label)).unwrap_or_else(|| panic!("no {} in the doc", label)).trim()
+    }
+
+    #[test]
+    fn the_workflow_example_blob_round_trips_through_the_decoder() {
+        let doc = workflow_doc().unwrap();
+        let decoded = crate::blockchain::decode::decode_blob(doc_field(&doc, "blob:")).unwrap();
+        let burn = bs58::encode(BURN_ADDRESS).into_string();
+
+        assert_eq!(decoded.kind, crate::blockchain::decode::BlobKind::Unsigned);
+        assert_eq!((decoded.signer.as_str(), decoded.contract.as_str(), decoded.function.as_str()), (burn.as_str(), "Coin", "transfer"));
+        let args: Vec<&str> = decoded.args.iter().map(|a| a.value.as_str()).collect();
+        assert_eq!(args, vec![hex::encode(BURN_ADDRESS).as_str(), "0", FLOW_SYMBOL]);
+        assert_eq!(decoded.nonce, doc_field(&doc, "nonce:"));
+        assert_eq!(decoded.signing_hash, doc_field(&doc, "signing_payload:"));
+    }
+
+    #[test]
+    fn the_workflow_doc_names_the_real_dst() {
+        let doc = workflow_doc().unwrap();
+        assert_eq!(doc_field(&doc, "DST:"), String::from_utf8_lossy(SIGNING_DST));
+        assert!(doc.contains(&format!("b\"{}\"", String::from_utf8_lossy(SIGNING_DST))));
+        for foreign in FOREIGN_DSTS {
+            assert!(doc.contains(&*String::from_utf8_lossy(foreign)));
+        }
+        // Built fresh on each read, but always the same
+        assert_eq!(workflow_doc().unwrap(), doc);
+    }
+}
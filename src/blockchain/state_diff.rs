@@ -0,0 +1,193 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Mutex;
+
+/// Keys one `diff_contract_state` call may compare.
+pub const MAX_DIFF_KEYS: u64 = 50;
+
+/// Observations kept per contract key.
+const MAX_OBSERVATIONS: usize = 256;
+
+/// Where the before/after values of a diff came from.
+pub const SOURCE_OBSERVED: &str = "recorded_observations";
+
+/// A contract storage value as seen at a chain height.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateObservation {
+    pub height: u64,
+    /// Unix seconds when the value was read
+    pub recorded_at: u64,
+    pub value: Value,
+}
+
+/// Contract values read by this process, keyed by node URL, contract and key. The node
+/// has no height-parameterized contract get, so this is the only history available.
+#[derive(Debug, Default)]
+pub struct ContractStateLog {
+    inner: Mutex<HashMap<(String, String, String), VecDeque<StateObservation>>>,
+}
+
+impl ContractStateLog {
+    pub fn record(&self, url: &str, contract: &str, key: &str, observation: StateObservation) {
+        let mut inner = self.inner.lock().unwrap();
+        let log = inner
+            .entry((url.to_string(), contract.to_string(), key.to_string()))
+            .or_default();
+        if log.back().is_some_and(|last| last.height > observation.height) {
+            return;
+        }
+        if log.back().is_some_and(|last| last.height == observation.height) {
+            log.pop_back();
+        }
+        if log.len() == MAX_OBSERVATIONS {
+            log.pop_front();
+        }
+        log.push_back(observation);
+    }
+
+    /// Drops observations made at or above `height` on `url`, e.g. after a reorg.
+    pub fn discard_from(&self, url: &str, height: u64) {
+        let mut inner = self.inner.lock().unwrap();
+        for ((log_url, _, _), log) in inner.iter_mut() {
+            if log_url == url {
+                log.retain(|o| o.height < height);
+            }
+        }
+    }
+
+    /// The latest observation at or before `height`.
+    pub fn at_or_before(&self, url: &str, contract: &str, key: &str, height: u64) -> Option<StateObservation> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .get(&(url.to_string(), contract.to_string(), key.to_string()))?
+            .iter()
+            .rev()
+            .find(|o| o.height <= height)
+            .cloned()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChangedKey {
+    pub key: String,
+    pub before: Value,
+    pub after: Value,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StateDiff {
+    /// Keys only present after, with their value
+    pub added: BTreeMap<String, Value>,
+    /// Keys only present before, with their value
+    pub removed: BTreeMap<String, Value>,
+    pub changed: Vec<ChangedKey>,
+    pub unchanged: usize,
+}
+
+/// Compares two key/value views of contract storage. Values are compared structurally,
+/// so binary values encoded as strings or byte arrays diff like any other JSON. A `null`
+/// value counts as absent.
+pub fn diff(before: &BTreeMap<String, Value>, after: &BTreeMap<String, Value>) -> StateDiff {
+    let present = |map: &BTreeMap<String, Value>, key: &str| map.get(key).filter(|v| !v.is_null()).cloned();
+    let mut result = StateDiff::default();
+
+    let keys: std::collections::BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    for key in keys {
+        match (present(before, key), present(after, key)) {
+            (None, Some(value)) => {
+                result.added.insert(key.clone(), value);
+            }
+            (Some(value), None) => {
+                result.removed.insert(key.clone(), value);
+            }
+            (Some(b), Some(a)) if b != a => result.changed.push(ChangedKey { key: key.clone(), before: b, after: a }),
+            (Some(_), Some(_)) => result.unchanged += 1,
+            (None, None) => {}
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn storage(entries: &[(&str, Value)]) -> BTreeMap<String, Value> {
+        entries.iter().map(|(k, v)| (k.to_string(), v.clone())).collect()
+    }
+
+    fn seen(height: u64, value: Value) -> StateObservation {
+        StateObservation { height, recorded_at: 1_700_000_000 + height, value }
+    }
+
+    #[test]
+    fn keys_present_on_one_side_are_added_or_removed() {
+        let before = storage(&[("owner", json!("alice")), ("old", json!(1)), ("cleared", Value::Null)]);
+        let after = storage(&[("owner", json!("alice")), ("new", json!(2)), ("cleared", json!("set"))]);
+        let diff = diff(&before, &after);
+        assert_eq!(diff.added, storage(&[("new", json!(2)), ("cleared", json!("set"))]));
+        assert_eq!(diff.removed, storage(&[("old", json!(1))]));
+        assert!(diff.changed.is_empty());
+        assert_eq!(diff.unchanged, 1);
+    }
+
+    #[test]
+    fn binary_values_are_compared_byte_for_byte() {
+        let before = storage(&[
+            ("blob", json!([0, 255, 16, 1])),
+            ("hex", json!("00ff1001")),
+            ("same", json!([0, 0, 0])),
+        ]);
+        let after = storage(&[
+            ("blob", json!([0, 255, 16, 2])),
+            ("hex", json!("00FF1001")),
+            ("same", json!([0, 0, 0])),
+        ]);
+        let diff = diff(&before, &after);
+        assert_eq!(
+            diff.changed,
+            vec![
+                ChangedKey { key: "blob".into(), before: json!([0, 255, 16, 1]), after: json!([0, 255, 16, 2]) },
+                ChangedKey { key: "hex".into(), before: json!("00ff1001"), after: json!("00FF1001") },
+            ]
+        );
+        assert_eq!(diff.unchanged, 1);
+    }
+
+    #[test]
+    fn a_null_on_both_sides_or_empty_views_diff_to_nothing() {
+        assert_eq!(diff(&BTreeMap::new(), &BTreeMap::new()), StateDiff::default());
+        let nulls = storage(&[("gone", Value::Null)]);
+        assert_eq!(diff(&nulls, &BTreeMap::new()), StateDiff::default());
+    }
+
+    #[test]
+    fn log_answers_the_latest_observation_at_or_before_a_height() {
+        let log = ContractStateLog::default();
+        log.record("node", "Coin", "supply", seen(10, json!(100)));
+        log.record("node", "Coin", "supply", seen(20, json!(150)));
+        log.record("node", "Coin", "supply", seen(20, json!(160)));
+        // Observations that go back in height are ignored
+        log.record("node", "Coin", "supply", seen(15, json!(0)));
+
+        assert!(log.at_or_before("node", "Coin", "supply", 9).is_none());
+        assert_eq!(log.at_or_before("node", "Coin", "supply", 19).unwrap().value, json!(100));
+        assert_eq!(log.at_or_before("node", "Coin", "supply", 99).unwrap().value, json!(160));
+        assert!(log.at_or_before("other", "Coin", "supply", 99).is_none());
+
+        log.discard_from("node", 20);
+        assert_eq!(log.at_or_before("node", "Coin", "supply", 99).unwrap().height, 10);
+    }
+
+    #[test]
+    fn log_keeps_a_bounded_window_per_key() {
+        let log = ContractStateLog::default();
+        for height in 0..(MAX_OBSERVATIONS as u64 + 10) {
+            log.record("node", "Coin", "supply", seen(height, json!(height)));
+        }
+        assert!(log.at_or_before("node", "Coin", "supply", 9).is_none());
+        assert_eq!(log.at_or_before("node", "Coin", "supply", 10).unwrap().value, json!(10));
+    }
+}
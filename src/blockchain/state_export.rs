@@ -0,0 +1,329 @@
+//! Paged export of a contract's storage under a key prefix, through the node's prefix
+//! listing endpoint. Each page ends with a cursor holding the last exported key, so an
+//! export resumes from any server instance without state kept between calls.
+
+use super::action::{self, DecodedArg};
+use super::decode::BlobBytes;
+use super::error::BlockchainError;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Node endpoint listing a contract's storage under a key prefix. Nodes without it are
+/// detected by the endpoint probe and the export tool marked unavailable.
+pub const PREFIX_PATH: &str = "contract/get_prefix";
+pub const DEFAULT_EXPORT_LIMIT: u64 = 200;
+/// Keys per page; larger exports continue through `next_cursor`.
+pub const MAX_EXPORT_LIMIT: u64 = 1000;
+pub const DEFAULT_LIST_LIMIT: u64 = 100;
+/// Keys per list_contract_keys call, kept small since values come back whole.
+pub const MAX_LIST_LIMIT: u64 = 200;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Json,
+    /// `key,type,value` rows with a header line, in a `csv` string field
+    Csv,
+}
+
+/// Node path for up to `limit` keys under `prefix` that sort after `after`.
+pub fn request_path(contract: &str, prefix: &str, after: Option<&str>, limit: u64) -> String {
+    let mut path = format!("{}/{}/{}?limit={}", PREFIX_PATH, contract, prefix, limit);
+    if let Some(after) = after {
+        path.push_str("&after=");
+        path.push_str(&encode_query(after));
+    }
+    path
+}
+
+/// Percent-encodes everything but unreserved characters, for a query value.
+fn encode_query(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportedKey {
+    pub key: String,
+    #[serde(flatten)]
+    pub value: DecodedArg,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportPage {
+    pub contract_address: String,
+    pub prefix: String,
+    pub keys: Vec<ExportedKey>,
+    pub count: usize,
+    /// Pass back as `cursor` for the next page; absent on the last page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+    pub complete: bool,
+}
+
+/// One key of a list_contract_keys page, its value as text when printable and hex otherwise.
+#[derive(Debug, Clone, Serialize)]
+pub struct ListedKey {
+    pub key: String,
+    #[serde(flatten)]
+    pub value: BlobBytes,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyListing {
+    pub contract_address: String,
+    pub prefix: String,
+    pub count: usize,
+    pub keys: Vec<ListedKey>,
+    /// Pass back as `cursor` for the next page; absent on the last page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// One page of raw key/value pairs, in key order.
+struct Window {
+    entries: Vec<(String, Value)>,
+    /// Set when another page follows
+    next_cursor: Option<String>,
+}
+
+/// Up to `limit` keys from the node's answer to a request for `limit + 1`: the extra key
+/// only says whether another page follows. Keys outside the prefix or not after `after`
+/// are dropped, so a node that ignores `after` cannot repeat keys across pages.
+fn window(
+    response: &Value, contract: &str, prefix: &str, after: Option<&str>, limit: u64,
+) -> Result<Window, BlockchainError> {
+    let mut entries: Vec<(String, Value)> = entries(response)?
+        .into_iter()
+        .filter(|(key, _)| key.starts_with(prefix) && after.is_none_or(|after| key.as_str() > after))
+        .collect();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    entries.dedup_by(|a, b| a.0 == b.0);
+
+    let more = entries.len() as u64 > limit;
+    entries.truncate(limit as usize);
+    let next_cursor = more.then(|| entries.last().map(|(key, _)| encode_cursor(contract, prefix, key))).flatten();
+    Ok(Window { entries, next_cursor })
+}
+
+/// Builds an export page from the node's answer to a request for `limit + 1` keys.
+pub fn page(
+    response: &Value, contract: &str, prefix: &str, after: Option<&str>, limit: u64,
+) -> Result<ExportPage, BlockchainError> {
+    let Window { entries, next_cursor } = window(response, contract, prefix, after, limit)?;
+    let keys: Vec<ExportedKey> = entries
+        .into_iter()
+        .map(|(key, value)| ExportedKey { key, value: action::decode_value(&value) })
+        .collect();
+    Ok(ExportPage {
+        contract_address: contract.to_string(),
+        prefix: prefix.to_string(),
+        count: keys.len(),
+        complete: next_cursor.is_none(),
+        next_cursor,
+        keys,
+    })
+}
+
+/// Builds a list_contract_keys page from the node's answer to a request for `limit + 1`
+/// keys. String values are shown as their bytes; other values as their JSON text.
+pub fn listing(
+    response: &Value, contract: &str, prefix: &str, after: Option<&str>, limit: u64,
+) -> Result<KeyListing, BlockchainError> {
+    let Window { entries, next_cursor } = window(response, contract, prefix, after, limit)?;
+    let keys: Vec<ListedKey> = entries
+        .into_iter()
+        .map(|(key, value)| {
+            let value = match &value {
+                Value::String(text) => BlobBytes::new(text.as_bytes()),
+                other => BlobBytes::new(other.to_string().as_bytes()),
+            };
+            ListedKey { key, value }
+        })
+        .collect();
+    Ok(KeyListing {
+        contract_address: contract.to_string(),
+        prefix: prefix.to_string(),
+        count: keys.len(),
+        keys,
+        next_cursor,
+    })
+}
+
+/// Key/value pairs from the node's answer: a `{key: value}` object, or a list of
+/// `{"key", "value"}` objects or `[key, value]` pairs, optionally under `result`.
+fn entries(response: &Value) -> Result<Vec<(String, Value)>, BlockchainError> {
+    let unexpected = || BlockchainError::InvalidResponse("unexpected contract prefix listing".to_string());
+    let listing = response.get("result").unwrap_or(response);
+    match listing {
+        Value::Object(map) => Ok(map.iter().map(|(k, v)| (k.clone(), v.clone())).collect()),
+        Value::Array(items) => items
+            .iter()
+            .map(|item| match item {
+                Value::Array(pair) if pair.len() == 2 => Some((pair[0].as_str()?.to_string(), pair[1].clone())),
+                Value::Object(entry) => Some((entry.get("key")?.as_str()?.to_string(), entry.get("value")?.clone())),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(unexpected),
+        _ => Err(unexpected()),
+    }
+}
+
+/// Opaque cursor: hex of the contract, prefix and last exported key.
+pub fn encode_cursor(contract: &str, prefix: &str, last_key: &str) -> String {
+    hex::encode(serde_json::json!([contract, prefix, last_key]).to_string())
+}
+
+/// The last exported key in `cursor`, which must come from an export of the same
+/// contract and prefix.
+pub fn decode_cursor(cursor: &str, contract: &str, prefix: &str) -> Result<String, String> {
+    let (cursor_contract, cursor_prefix, last_key) = hex::decode(cursor)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<(String, String, String)>(&bytes).ok())
+        .ok_or_else(|| "invalid cursor".to_string())?;
+    if cursor_contract != contract || cursor_prefix != prefix {
+        return Err("cursor belongs to an export of another contract or prefix".to_string());
+    }
+    Ok(last_key)
+}
+
+/// The page's keys as CSV with a `key,type,value` header.
+pub fn to_csv(keys: &[ExportedKey]) -> String {
+    let mut csv = String::from("key,type,value\n");
+    for entry in keys {
+        let kind = serde_json::to_value(entry.value.kind).ok();
+        let kind = kind.as_ref().and_then(Value::as_str).unwrap_or_default();
+        csv.push_str(&format!("{},{},{}\n", csv_field(&entry.key), kind, csv_field(&entry.value.value)));
+    }
+    csv
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const CONTRACT: &str = "Coin";
+
+    /// Fixture storage: `bal:00` to `bal:24`, plus keys outside the `bal:` prefix.
+    fn storage() -> Vec<(String, Value)> {
+        let mut keys: Vec<(String, Value)> = (0..25).map(|i| (format!("bal:{:02}", i), json!(i * 100))).collect();
+        keys.push(("supply".to_string(), json!("2500")));
+        keys.push(("ba".to_string(), json!("short")));
+        keys
+    }
+
+    /// What a node honouring `after` and `limit` answers, as a `{key: value}` object.
+    fn node_answer(prefix: &str, after: Option<&str>, limit: u64) -> Value {
+        let mut keys: Vec<_> =
+            storage().into_iter().filter(|(k, _)| k.starts_with(prefix) && after.is_none_or(|a| k.as_str() > a)).collect();
+        keys.sort_by(|a, b| a.0.cmp(&b.0));
+        keys.truncate(limit as usize);
+        Value::Object(keys.into_iter().collect())
+    }
+
+    /// Exports every page through cursors, as a client would, answering each request with `answer`.
+    fn export_all(prefix: &str, limit: u64, answer: impl Fn(Option<&str>) -> Value) -> Vec<ExportPage> {
+        let mut pages = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let after = cursor.as_deref().map(|c| decode_cursor(c, CONTRACT, prefix).unwrap());
+            let page = page(&answer(after.as_deref()), CONTRACT, prefix, after.as_deref(), limit).unwrap();
+            cursor = page.next_cursor.clone();
+            let complete = page.complete;
+            pages.push(page);
+            if complete {
+                return pages;
+            }
+            assert!(pages.len() < 100, "export never completed");
+        }
+    }
+
+    fn keys(pages: &[ExportPage]) -> Vec<String> {
+        pages.iter().flat_map(|p| p.keys.iter().map(|k| k.key.clone())).collect()
+    }
+
+    #[test]
+    fn cursor_resumption_has_no_gaps_or_duplicates() {
+        let expected: Vec<String> = (0..25).map(|i| format!("bal:{:02}", i)).collect();
+        for limit in [1, 7, 10, 24, 25, 26, 1000] {
+            let pages = export_all("bal:", limit, |after| node_answer("bal:", after, limit + 1));
+            assert_eq!(keys(&pages), expected, "limit {}", limit);
+            assert_eq!(pages.len() as u64, 25_u64.div_ceil(limit), "limit {}", limit);
+            assert!(pages.iter().all(|p| p.count == p.keys.len() && p.count as u64 <= limit));
+            assert!(pages[..pages.len() - 1].iter().all(|p| !p.complete && p.next_cursor.is_some()));
+        }
+    }
+
+    #[test]
+    fn a_node_that_ignores_after_still_pages_without_repeats() {
+        let expected: Vec<String> = (0..25).map(|i| format!("bal:{:02}", i)).collect();
+        let pages = export_all("bal:", 10, |_| node_answer("bal:", None, u64::MAX));
+        assert_eq!(keys(&pages), expected);
+    }
+
+    #[test]
+    fn keys_outside_the_prefix_are_dropped_and_values_tagged() {
+        let answer = json!({ "bal:01": 100, "supply": "2500", "bal:00": "hello" });
+        let page = page(&answer, CONTRACT, "bal:", None, 10).unwrap();
+        assert_eq!(
+            serde_json::to_value(&page.keys).unwrap(),
+            json!([{ "key": "bal:00", "type": "utf8", "value": "hello" }, { "key": "bal:01", "type": "integer", "value": "100" }])
+        );
+        assert!(page.complete && page.next_cursor.is_none());
+    }
+
+    #[test]
+    fn listings_come_as_objects_pairs_or_records() {
+        let shapes = [
+            json!({ "b": 2, "a": 1 }),
+            json!({ "result": [["a", 1], ["b", 2]] }),
+            json!([{ "key": "b", "value": 2 }, { "key": "a", "value": 1 }]),
+        ];
+        for shape in shapes {
+            let page = page(&shape, CONTRACT, "", None, 10).unwrap();
+            assert_eq!(page.keys.iter().map(|k| k.key.as_str()).collect::<Vec<_>>(), ["a", "b"], "{}", shape);
+        }
+        assert!(page(&json!([["a"]]), CONTRACT, "", None, 10).is_err());
+        assert!(page(&json!("no"), CONTRACT, "", None, 10).is_err());
+    }
+
+    #[test]
+    fn cursors_round_trip_and_are_bound_to_their_export() {
+        let cursor = encode_cursor(CONTRACT, "bal:", "bal:07");
+        assert_eq!(decode_cursor(&cursor, CONTRACT, "bal:").unwrap(), "bal:07");
+        assert_eq!(decode_cursor(&cursor, CONTRACT, "bal:0").unwrap_err(), "cursor belongs to an export of another contract or prefix");
+        assert_eq!(decode_cursor(&cursor, "Epoch", "bal:").unwrap_err(), "cursor belongs to an export of another contract or prefix");
+        assert_eq!(decode_cursor("zz", CONTRACT, "bal:").unwrap_err(), "invalid cursor");
+        assert_eq!(decode_cursor(&hex::encode("[1,2]"), CONTRACT, "bal:").unwrap_err(), "invalid cursor");
+    }
+
+    #[test]
+    fn request_path_encodes_the_resume_key() {
+        assert_eq!(request_path(CONTRACT, "bal:", None, 11), "contract/get_prefix/Coin/bal:?limit=11");
+        assert_eq!(request_path(CONTRACT, "", Some("bal:07 x/y"), 11), "contract/get_prefix/Coin/?limit=11&after=bal%3A07%20x%2Fy");
+    }
+
+    #[test]
+    fn csv_quotes_fields_that_need_it() {
+        let answer = json!({ "a,b": "say \"hi\"", "n": 5 });
+        let page = page(&answer, CONTRACT, "", None, 10).unwrap();
+        assert_eq!(to_csv(&page.keys), "key,type,value\n\"a,b\",utf8,\"say \"\"hi\"\"\"\nn,integer,5\n");
+    }
+}
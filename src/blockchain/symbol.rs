@@ -0,0 +1,128 @@
+use super::types::{Argument, Balance, TransactionRequest};
+
+/// The token a request moves: the symbol argument of `Coin.transfer`, otherwise the
+/// attached symbol.
+pub fn requested_symbol(req: &TransactionRequest) -> Option<&str> {
+    if req.contract == "Coin" && req.function == "transfer" {
+        match req.args.get(2) {
+            Some(Argument::String(symbol)) | Some(Argument::Utf8 { utf8: symbol }) => return Some(symbol),
+            _ => {}
+        }
+    }
+    req.attached_symbol.as_deref()
+}
+
+/// Held symbols `requested` was probably meant to be: the same letters in another case,
+/// or one edit away. Empty when `requested` is held or nothing is close.
+pub fn likely_symbols<'a>(requested: &str, held: &[&'a str]) -> Vec<&'a str> {
+    if held.contains(&requested) {
+        return Vec::new();
+    }
+    let folded = requested.to_ascii_uppercase();
+    held.iter()
+        .copied()
+        .filter(|symbol| edit_distance(&folded, &symbol.to_ascii_uppercase()) <= 1)
+        .collect()
+}
+
+/// Rejects a request for a symbol the signer does not hold when a held symbol is a near
+/// match, naming the candidates and the signer's holdings.
+pub fn check_held(requested: &str, balances: &[Balance]) -> Result<(), String> {
+    let held: Vec<&str> = balances.iter().filter(|b| b.flat > 0).map(|b| b.symbol.as_str()).collect();
+    let candidates = likely_symbols(requested, &held);
+    if candidates.is_empty() {
+        return Ok(());
+    }
+    Err(format!(
+        "the signer holds no {}; did you mean {}? Holdings: {}",
+        requested,
+        candidates.join(" or "),
+        held.join(", ")
+    ))
+}
+
+/// Levenshtein distance over chars.
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = (above + 1).min(row[j] + 1).min(diagonal + usize::from(ca != *cb));
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn balance(symbol: &str, flat: u64) -> Balance {
+        Balance { symbol: symbol.to_string(), flat, float: flat as f64 }
+    }
+
+    fn request(value: serde_json::Value) -> TransactionRequest {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn another_case_or_one_edit_is_a_match() {
+        assert_eq!(likely_symbols("ama", &["AMA", "USDX"]), vec!["AMA"]);
+        assert_eq!(likely_symbols("AMX", &["AMA", "USDX"]), vec!["AMA"]);
+        assert_eq!(likely_symbols("USD", &["AMA", "USDX"]), vec!["USDX"]);
+        assert_eq!(likely_symbols("usdxx", &["AMA", "USDX"]), vec!["USDX"]);
+    }
+
+    #[test]
+    fn held_or_distant_symbols_are_not_matched() {
+        assert!(likely_symbols("AMA", &["AMA", "AMB"]).is_empty());
+        assert!(likely_symbols("BTC", &["AMA", "USDX"]).is_empty());
+        assert!(likely_symbols("AM", &["AMAX"]).is_empty());
+        assert!(likely_symbols("ama", &[]).is_empty());
+    }
+
+    #[test]
+    fn every_close_symbol_is_a_candidate() {
+        assert_eq!(likely_symbols("AMC", &["AMA", "AMB", "USDX"]), vec!["AMA", "AMB"]);
+        let err = check_held("AMC", &[balance("AMA", 5), balance("AMB", 1), balance("USDX", 2)]).unwrap_err();
+        assert_eq!(err, "the signer holds no AMC; did you mean AMA or AMB? Holdings: AMA, AMB, USDX");
+    }
+
+    #[test]
+    fn only_positive_balances_count_as_held() {
+        assert_eq!(
+            check_held("ama", &[balance("AMA", 5), balance("USDX", 0)]).unwrap_err(),
+            "the signer holds no ama; did you mean AMA? Holdings: AMA"
+        );
+        // An empty balance is not worth suggesting, so the request goes through
+        assert!(check_held("ama", &[balance("AMA", 0)]).is_ok());
+        assert!(check_held("AMA", &[balance("AMA", 5)]).is_ok());
+    }
+
+    #[test]
+    fn the_requested_symbol_comes_from_the_transfer_or_the_attachment() {
+        let transfer = request(json!({ "signer": "s", "contract": "Coin", "function": "transfer", "args": ["to", "1", "ama"] }));
+        assert_eq!(requested_symbol(&transfer), Some("ama"));
+        let utf8 = request(json!({ "signer": "s", "contract": "Coin", "function": "transfer", "args": ["to", "1", { "utf8": "USDX" }] }));
+        assert_eq!(requested_symbol(&utf8), Some("USDX"));
+        let attached = request(json!({
+            "signer": "s", "contract": "Dex", "function": "swap", "args": [], "attached_symbol": "AMA", "attached_amount": "5"
+        }));
+        assert_eq!(requested_symbol(&attached), Some("AMA"));
+        let plain = request(json!({ "signer": "s", "contract": "Dex", "function": "swap", "args": ["AMA"] }));
+        assert_eq!(requested_symbol(&plain), None);
+    }
+
+    #[test]
+    fn edit_distance_counts_insertions_deletions_and_substitutions() {
+        for (a, b, expected) in [("", "", 0), ("AMA", "AMA", 0), ("AMA", "AMB", 1), ("AMA", "AM", 1), ("", "AMA", 3), ("kitten", "sitting", 3)] {
+            assert_eq!(edit_distance(a, b), expected, "{} {}", a, b);
+            assert_eq!(edit_distance(b, a), expected, "{} {}", b, a);
+        }
+    }
+}
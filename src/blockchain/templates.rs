@@ -0,0 +1,299 @@
+//! Named argument layouts of common contract calls. `list_tx_templates` and the
+//! `amadeus://templates/{name}` resource show them; `create_transaction_blob` checks calls
+//! to the same contract functions against them before building.
+
+use super::types::{Argument, TransactionRequest};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// Resource URI prefix for templates; append the template name.
+pub const TEMPLATES_URI: &str = "amadeus://templates/";
+
+/// Bytes of a public key.
+const ADDRESS_BYTES: usize = 48;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArgType {
+    /// A 48-byte public key, passed as `{"b58": ...}`
+    Address,
+    /// A non-negative integer of atoms, as a decimal string
+    Amount,
+    /// A token symbol such as `AMA`
+    Symbol,
+}
+
+impl ArgType {
+    /// How the argument is written in a create_transaction example.
+    fn placeholder(self, name: &str) -> Value {
+        match self {
+            Self::Address => json!({ "b58": format!("<{}>", name) }),
+            Self::Amount => json!(format!("<{} in atoms>", name)),
+            Self::Symbol => json!("AMA"),
+        }
+    }
+
+    fn check(self, bytes: &[u8]) -> Result<(), String> {
+        match self {
+            Self::Address if bytes.len() != ADDRESS_BYTES => Err(format!(
+                "must be a {}-byte public key passed as {{\"b58\": ...}}, got {} bytes",
+                ADDRESS_BYTES,
+                bytes.len()
+            )),
+            Self::Amount if bytes.is_empty() || !bytes.iter().all(u8::is_ascii_digit) => {
+                Err("must be a whole number of atoms, e.g. \"1000000000\"".to_string())
+            }
+            Self::Symbol if bytes.is_empty() || !bytes.iter().all(u8::is_ascii_alphanumeric) => {
+                Err("must be a token symbol such as \"AMA\"".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct TemplateArg {
+    pub name: &'static str,
+    #[serde(rename = "type")]
+    pub kind: ArgType,
+    pub description: &'static str,
+    /// Optional arguments may only be left off the end of the list
+    pub required: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct TxTemplate {
+    pub name: &'static str,
+    pub contract: &'static str,
+    pub function: &'static str,
+    pub description: &'static str,
+    pub args: &'static [TemplateArg],
+}
+
+pub const TEMPLATES: &[TxTemplate] = &[
+    TxTemplate {
+        name: "transfer",
+        contract: "Coin",
+        function: "transfer",
+        description: "Sends tokens from the signer to another address",
+        args: &[
+            TemplateArg { name: "recipient", kind: ArgType::Address, description: "Public key receiving the tokens", required: true },
+            TemplateArg { name: "amount", kind: ArgType::Amount, description: "Atoms to send; 1 AMA is 10^9 atoms", required: true },
+            TemplateArg { name: "symbol", kind: ArgType::Symbol, description: "Token to send; AMA when left off", required: false },
+        ],
+    },
+    TxTemplate {
+        name: "set_emission_address",
+        contract: "Epoch",
+        function: "set_emission_address",
+        description: "Directs the signing validator's emissions to another address",
+        args: &[TemplateArg {
+            name: "address",
+            kind: ArgType::Address,
+            description: "Public key that receives the validator's emissions",
+            required: true,
+        }],
+    },
+];
+
+pub fn template(name: &str) -> Option<&'static TxTemplate> {
+    TEMPLATES.iter().find(|t| t.name == name)
+}
+
+/// The template describing calls to `contract.function`, if any.
+pub fn for_call(contract: &str, function: &str) -> Option<&'static TxTemplate> {
+    TEMPLATES.iter().find(|t| t.contract == contract && t.function == function)
+}
+
+impl TxTemplate {
+    /// Arguments for create_transaction with every value a placeholder to fill in.
+    pub fn example(&self) -> Value {
+        let args: Vec<Value> = self.args.iter().map(|a| a.kind.placeholder(a.name)).collect();
+        json!({
+            "signer": "<signer public key>",
+            "contract": self.contract,
+            "function": self.function,
+            "args": args,
+        })
+    }
+
+    /// The template with its example, as listed and served.
+    pub fn describe(&self) -> Value {
+        let mut described = json!(self);
+        described["example"] = self.example();
+        described
+    }
+
+    /// Checks argument count and each argument's bytes against the template.
+    pub fn check(&self, args: &[Argument]) -> Result<(), String> {
+        let required = self.args.iter().filter(|a| a.required).count();
+        if args.len() < required || args.len() > self.args.len() {
+            let expected = self.args.iter().map(|a| a.name).collect::<Vec<_>>().join(", ");
+            return Err(format!(
+                "{}.{} takes ({}) but got {} argument(s); see template {}",
+                self.contract,
+                self.function,
+                expected,
+                args.len(),
+                self.name
+            ));
+        }
+        for (i, (arg, spec)) in args.iter().zip(self.args).enumerate() {
+            let bytes = arg.to_bytes().map_err(|e| e.to_string())?;
+            spec.kind
+                .check(&bytes)
+                .map_err(|e| format!("argument {} ({}) of {}.{} {}", i, spec.name, self.contract, self.function, e))?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks a request against its template when one describes the call; other calls pass.
+pub fn check_request(req: &TransactionRequest) -> Result<(), String> {
+    for_call(&req.contract, &req.function).map_or(Ok(()), |t| t.check(&req.args))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::{decode, BlockchainClient};
+    use crate::wasm::tx;
+    use std::collections::HashSet;
+
+    const SIGNER: &str = "29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw";
+    const RECEIVER: &str = "42KfUgyU2XHCPwDD6g9gryCvtj9vqJAvM6h3Yzs3VRbuyT2JCHwvuk5iAbzXrioerf";
+
+    /// The template's arguments with every placeholder replaced by a real value.
+    fn filled(t: &TxTemplate) -> Vec<Argument> {
+        t.args
+            .iter()
+            .map(|a| match a.kind {
+                ArgType::Address => Argument::Base58 { b58: RECEIVER.to_string() },
+                ArgType::Amount => Argument::String("1000000000".to_string()),
+                ArgType::Symbol => Argument::String("AMA".to_string()),
+            })
+            .collect()
+    }
+
+    fn request(t: &TxTemplate, args: Vec<Argument>) -> TransactionRequest {
+        TransactionRequest {
+            signer: SIGNER.to_string(),
+            contract: t.contract.to_string(),
+            function: t.function.to_string(),
+            args,
+            attached_symbol: None,
+            attached_amount: None,
+            nonce: Some(1),
+        }
+    }
+
+    #[test]
+    fn names_and_calls_are_unique_and_resolve_back() {
+        let names: HashSet<_> = TEMPLATES.iter().map(|t| t.name).collect();
+        let calls: HashSet<_> = TEMPLATES.iter().map(|t| (t.contract, t.function)).collect();
+        assert_eq!(names.len(), TEMPLATES.len());
+        assert_eq!(calls.len(), TEMPLATES.len());
+        for t in TEMPLATES {
+            assert_eq!(template(t.name).unwrap().name, t.name);
+            assert_eq!(for_call(t.contract, t.function).unwrap().name, t.name);
+        }
+        assert!(template("stake").is_none());
+        assert!(for_call("Coin", "mint").is_none());
+    }
+
+    #[test]
+    fn optional_arguments_only_trail() {
+        for t in TEMPLATES {
+            let first_optional = t.args.iter().position(|a| !a.required).unwrap_or(t.args.len());
+            assert!(t.args[first_optional..].iter().all(|a| !a.required), "{} has a required argument after an optional one", t.name);
+        }
+    }
+
+    #[test]
+    fn example_has_one_placeholder_per_argument() {
+        for t in TEMPLATES {
+            let example = t.example();
+            assert_eq!(example["contract"], t.contract);
+            assert_eq!(example["function"], t.function);
+            assert_eq!(example["args"].as_array().unwrap().len(), t.args.len());
+            assert_eq!(t.describe()["example"], example);
+        }
+    }
+
+    /// Every template must be buildable: its filled example passes the template and
+    /// create_transaction turns it into a blob that decodes back to the same call.
+    #[tokio::test]
+    async fn every_template_builds_through_create_transaction() {
+        let client = BlockchainClient::new(String::new()).unwrap();
+        for t in TEMPLATES {
+            let req = request(t, filled(t));
+            check_request(&req).unwrap_or_else(|e| panic!("template {}: {}", t.name, e));
+            let blob = client
+                .create_transaction_blob(req)
+                .await
+                .unwrap_or_else(|e| panic!("template {} does not build: {}", t.name, e));
+            let decoded = decode::decode_blob(&blob.blob).unwrap();
+            assert_eq!(for_call(&decoded.contract, &decoded.function).unwrap().name, t.name);
+            assert_eq!(decoded.args.len(), t.args.len());
+        }
+    }
+
+    /// Every call a dedicated builder assembles must have a template that accepts its arguments.
+    #[test]
+    fn dedicated_builders_match_a_template() {
+        let receiver = bs58::decode(RECEIVER).into_vec().unwrap();
+        let built = tx::build_transfer_tx(&[3u8; 64], &receiver, "AMA", 1_000_000_000, Some(1)).unwrap();
+        let action = tx::decode_signed(&built.packed).unwrap().tx.action;
+        let t = for_call(&action.contract, &action.function)
+            .unwrap_or_else(|| panic!("{}.{} is built but has no template", action.contract, action.function));
+        let args: Vec<Argument> = action.args.iter().map(|a| Argument::Hex { hex: hex::encode(a) }).collect();
+        t.check(&args).unwrap();
+    }
+
+    #[test]
+    fn optional_symbol_may_be_left_off() {
+        let t = template("transfer").unwrap();
+        let mut args = filled(t);
+        args.pop();
+        t.check(&args).unwrap();
+    }
+
+    #[test]
+    fn wrong_count_names_the_expected_arguments() {
+        let t = template("transfer").unwrap();
+        let err = t.check(&filled(t)[..1]).unwrap_err();
+        assert_eq!(err, "Coin.transfer takes (recipient, amount, symbol) but got 1 argument(s); see template transfer");
+        let mut too_many = filled(t);
+        too_many.push(Argument::String("extra".to_string()));
+        assert!(t.check(&too_many).unwrap_err().contains("got 4 argument(s)"));
+    }
+
+    #[test]
+    fn swapped_arguments_name_the_one_that_does_not_fit() {
+        let t = template("transfer").unwrap();
+        let mut args = filled(t);
+        args.swap(0, 1);
+        let err = t.check(&args).unwrap_err();
+        assert!(err.starts_with("argument 0 (recipient) of Coin.transfer must be a 48-byte public key"), "{}", err);
+
+        let mut args = filled(t);
+        args[0] = Argument::String(RECEIVER.to_string());
+        assert!(t.check(&args).unwrap_err().contains("got 66 bytes"));
+
+        let mut args = filled(t);
+        args[1] = Argument::String("1.5".to_string());
+        assert_eq!(t.check(&args).unwrap_err(), "argument 1 (amount) of Coin.transfer must be a whole number of atoms, e.g. \"1000000000\"");
+
+        let mut args = filled(t);
+        args[2] = Argument::String("A-M".to_string());
+        assert!(t.check(&args).unwrap_err().ends_with("must be a token symbol such as \"AMA\""));
+    }
+
+    #[test]
+    fn calls_without_a_template_pass() {
+        let t = template("transfer").unwrap();
+        let mut req = request(t, vec![Argument::String("anything".to_string())]);
+        req.function = "burn".to_string();
+        check_request(&req).unwrap();
+    }
+}
@@ -0,0 +1,329 @@
+use super::{
+    clock::ClockSkew,
+    reorg::{ReorgEvent, TipHistory},
+    types::ChainStats,
+    BlockchainClient,
+};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::watch;
+use tracing::{debug, warn};
+
+/// Longest wait between attempts while the node keeps failing.
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Heights this far past the last seen tip are still accepted, since the chain moves on
+/// between refreshes.
+const HEIGHT_SLACK: u64 = 64;
+
+/// Latest chain tip seen by the follower.
+#[derive(Debug, Clone)]
+pub struct TipState {
+    pub height: u64,
+    pub hash: Option<String>,
+    pub stats: ChainStats,
+    /// Unix seconds when this state was fetched
+    pub observed_at: u64,
+    /// Most recent reorg seen by the follower
+    pub last_reorg: Option<ReorgEvent>,
+    /// Local clock against the tip entry's timestamp, when the node sends one
+    pub clock_skew: Option<ClockSkew>,
+}
+
+impl TipState {
+    /// How long ago this state was fetched.
+    pub fn age(&self) -> Duration {
+        Duration::from_secs(now().saturating_sub(self.observed_at))
+    }
+
+    /// The last reorg, while it is recent enough to warn about.
+    pub fn recent_reorg(&self) -> Option<&ReorgEvent> {
+        self.last_reorg.as_ref().filter(|r| r.is_recent(now()))
+    }
+
+    /// Whether `height` is too far ahead of the tip to exist yet.
+    pub fn is_beyond(&self, height: u64) -> bool {
+        height > self.height.saturating_add(HEIGHT_SLACK)
+    }
+
+    /// Adds `as_of_height` to an object output, and `reorg_warning` while the last reorg
+    /// is recent. Other outputs are left as they are.
+    pub fn stamp(&self, output: &mut Value) {
+        let Some(fields) = output.as_object_mut() else {
+            return;
+        };
+        fields.insert("as_of_height".to_string(), self.height.into());
+        if let Some(reorg) = self.recent_reorg() {
+            fields.insert("reorg_warning".to_string(), json!(reorg));
+        }
+    }
+}
+
+/// Background task that keeps the tip entry and chain stats for one node fresh. Stops
+/// when dropped.
+pub struct TipFollower {
+    state: watch::Receiver<Option<TipState>>,
+    _stop: watch::Sender<()>,
+}
+
+impl TipFollower {
+    /// Spawns the refresh loop on the current tokio runtime. Clock skew past
+    /// `skew_threshold` is logged once each time it appears.
+    pub fn spawn(client: BlockchainClient, url: String, interval: Duration, skew_threshold: Duration) -> Self {
+        let (state_tx, state) = watch::channel(None);
+        let (stop, stop_rx) = watch::channel(());
+        tokio::spawn(follow(client, url, interval, skew_threshold, state_tx, stop_rx));
+        Self { state, _stop: stop }
+    }
+
+    /// The latest tip, or `None` before the first successful refresh.
+    pub fn current(&self) -> Option<TipState> {
+        self.state.borrow().clone()
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<Option<TipState>> {
+        self.state.clone()
+    }
+}
+
+async fn follow(
+    client: BlockchainClient,
+    url: String,
+    interval: Duration,
+    skew_threshold: Duration,
+    state: watch::Sender<Option<TipState>>,
+    mut stop: watch::Receiver<()>,
+) {
+    let mut delay = Duration::ZERO;
+    let mut history = TipHistory::default();
+    let mut last_reorg = None;
+    let mut skewed = false;
+    loop {
+        tokio::select! {
+            _ = stop.changed() => break,
+            _ = tokio::time::sleep(delay) => {}
+        }
+
+        delay = match refresh(&client, &url, &mut history, &mut last_reorg).await {
+            Ok(tip) => {
+                debug!(height = tip.height, "tip refreshed");
+                let now_skewed = tip.clock_skew.is_some_and(|s| s.exceeds(skew_threshold));
+                if now_skewed && !skewed {
+                    warn!(skew_secs = tip.clock_skew.map(|s| s.skew_secs), "local clock is skewed against the chain, deriving nonces from the chain");
+                }
+                skewed = now_skewed;
+                state.send_replace(Some(tip));
+                interval
+            }
+            Err(e) => {
+                let backoff = backoff(delay, interval);
+                warn!(error = %e, retry_in = ?backoff, "tip refresh failed");
+                backoff
+            }
+        };
+    }
+    debug!(url = %url, "tip follower stopped");
+}
+
+/// Wait after a failed refresh: double the last wait, at least `interval` and at most
+/// `MAX_BACKOFF` (or `interval` when that is longer).
+fn backoff(delay: Duration, interval: Duration) -> Duration {
+    (delay * 2).clamp(interval, MAX_BACKOFF.max(interval))
+}
+
+async fn refresh(
+    client: &BlockchainClient,
+    url: &str,
+    history: &mut TipHistory,
+    last_reorg: &mut Option<ReorgEvent>,
+) -> super::error::Result<TipState> {
+    let stats = client.get_chain_stats(url).await?;
+
+    if let Some(reorg) = detect_reorg(client, url, history).await {
+        warn!(fork_height = reorg.fork_height, depth = reorg.depth, "chain reorg detected");
+        client.invalidate_from(url, reorg.fork_height);
+        *last_reorg = Some(reorg);
+    }
+
+    let (hash, clock_skew) = match client.get_entry_tip(url).await {
+        Ok(entry) => {
+            history.record(entry.header.height, entry.hash.clone());
+            (Some(entry.hash), entry.header.timestamp.map(|ts| ClockSkew::measure(now(), ts)))
+        }
+        Err(e) => {
+            debug!(error = %e, "tip entry unavailable, keeping height from stats");
+            (None, None)
+        }
+    };
+
+    Ok(TipState {
+        height: stats.height,
+        hash,
+        stats,
+        observed_at: now(),
+        last_reorg: last_reorg.clone(),
+        clock_skew,
+    })
+}
+
+/// Re-reads the entries at recently recorded tip heights, newest first, until one of
+/// the recorded tips is found still on the chain.
+async fn detect_reorg(client: &BlockchainClient, url: &str, history: &mut TipHistory) -> Option<ReorgEvent> {
+    let mut canonical = BTreeMap::new();
+    for height in history.recent_heights() {
+        let Ok(entries) = client.get_block_by_height(height, url).await else {
+            break;
+        };
+        let hashes: BTreeSet<String> = entries.into_iter().map(|e| e.hash).collect();
+        let still_canonical = history.hash_at(height).is_some_and(|h| hashes.contains(h));
+        canonical.insert(height, hashes);
+        if still_canonical {
+            break;
+        }
+    }
+
+    let fork_height = history.fork_height(&canonical)?;
+    history.rewind(fork_height, now())
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::offline::ScratchNode;
+
+    fn tip(height: u64, last_reorg: Option<ReorgEvent>) -> TipState {
+        TipState {
+            height,
+            hash: Some("tiphash".to_string()),
+            stats: serde_json::from_value(json!({ "height": height })).unwrap(),
+            observed_at: now(),
+            last_reorg,
+            clock_skew: None,
+        }
+    }
+
+    fn reorg(detected_at: u64) -> ReorgEvent {
+        ReorgEvent { fork_height: 990, depth: 3, orphaned_hash: "orphan".to_string(), detected_at }
+    }
+
+    #[test]
+    fn stamp_adds_as_of_height_to_objects() {
+        let mut output = json!({ "address": "abc", "balances": [] });
+        tip(1000, None).stamp(&mut output);
+        assert_eq!(output, json!({ "address": "abc", "balances": [], "as_of_height": 1000 }));
+    }
+
+    #[test]
+    fn stamp_adds_reorg_warning_only_while_recent() {
+        let mut output = json!({ "height": 5 });
+        tip(1000, Some(reorg(now()))).stamp(&mut output);
+        assert_eq!(
+            output,
+            json!({
+                "height": 5,
+                "as_of_height": 1000,
+                "reorg_warning": { "fork_height": 990, "depth": 3, "orphaned_hash": "orphan", "detected_at": now() }
+            })
+        );
+
+        let mut output = json!({ "height": 5 });
+        tip(1000, Some(reorg(0))).stamp(&mut output);
+        assert_eq!(output, json!({ "height": 5, "as_of_height": 1000 }));
+    }
+
+    #[test]
+    fn stamp_leaves_other_outputs_alone() {
+        for mut output in [json!([1, 2]), json!("text"), json!(null)] {
+            let before = output.clone();
+            tip(1000, None).stamp(&mut output);
+            assert_eq!(output, before);
+        }
+    }
+
+    #[test]
+    fn heights_past_the_slack_are_beyond_the_tip() {
+        let state = tip(1000, None);
+        assert!(!state.is_beyond(1000));
+        assert!(!state.is_beyond(1000 + HEIGHT_SLACK));
+        assert!(state.is_beyond(1001 + HEIGHT_SLACK));
+        assert!(!tip(u64::MAX, None).is_beyond(u64::MAX));
+    }
+
+    fn set_height(node: &ScratchNode, height: u64) {
+        node.set("stats", json!({ "error": "ok", "stats": { "height": height } }));
+    }
+
+    fn spawn_follower(node: &ScratchNode) -> TipFollower {
+        TipFollower::spawn(node.client(), "http://node".to_string(), Duration::from_millis(10), Duration::from_secs(60))
+    }
+
+    async fn wait_for_height(state: &mut watch::Receiver<Option<TipState>>, height: u64) {
+        tokio::time::timeout(Duration::from_secs(5), state.wait_for(|tip| tip.as_ref().is_some_and(|t| t.height == height)))
+            .await
+            .expect("follower did not reach the height")
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn follower_publishes_each_new_tip() {
+        let node = ScratchNode::new();
+        set_height(&node, 1000);
+        let follower = spawn_follower(&node);
+        let mut state = follower.subscribe();
+
+        wait_for_height(&mut state, 1000).await;
+        let current = follower.current().unwrap();
+        assert_eq!(current.stats.height, 1000);
+        // No tip entry fixture, so the height comes from stats alone
+        assert_eq!(current.hash, None);
+
+        set_height(&node, 1001);
+        wait_for_height(&mut state, 1001).await;
+    }
+
+    #[tokio::test]
+    async fn follower_keeps_retrying_while_the_node_fails() {
+        let node = ScratchNode::new();
+        let follower = spawn_follower(&node);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(follower.current().is_none());
+
+        set_height(&node, 7);
+        let mut state = follower.subscribe();
+        wait_for_height(&mut state, 7).await;
+    }
+
+    #[tokio::test]
+    async fn follower_stops_when_dropped() {
+        let node = ScratchNode::new();
+        set_height(&node, 1000);
+        let follower = spawn_follower(&node);
+        let mut state = follower.subscribe();
+        wait_for_height(&mut state, 1000).await;
+
+        drop(follower);
+        // The loop owns the sender, so the channel closes once the loop has exited
+        tokio::time::timeout(Duration::from_secs(5), async { while state.changed().await.is_ok() {} })
+            .await
+            .expect("follower kept running after being dropped");
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let interval = Duration::from_secs(5);
+        let mut delay = Duration::ZERO;
+        let mut delays = Vec::new();
+        for _ in 0..6 {
+            delay = backoff(delay, interval);
+            delays.push(delay.as_secs());
+        }
+        assert_eq!(delays, [5, 10, 20, 40, 60, 60]);
+        // An interval above the cap is never shortened
+        assert_eq!(backoff(Duration::from_secs(90), Duration::from_secs(90)), Duration::from_secs(90));
+    }
+}
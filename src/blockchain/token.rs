@@ -0,0 +1,204 @@
+//! Token supply and decimals read from the Coin contract's storage.
+
+use super::decimals::{DecimalsRegistry, MAX_DECIMALS};
+use super::error::{BlockchainError, Result};
+use super::state_export::ExportPage;
+use serde::Serialize;
+use serde_json::Value;
+
+pub const COIN_CONTRACT: &str = "Coin";
+/// Coin storage prefixes; the symbol follows.
+pub const TOTAL_SUPPLY_PREFIX: &str = "totalSupply:";
+pub const DECIMALS_PREFIX: &str = "decimals:";
+/// Most tokens one list_tokens call reads.
+pub const MAX_LISTED_TOKENS: u64 = 100;
+
+/// Storage key of the burn address's balance of `symbol`. The burn address is the all-zero
+/// public key, which nobody holds the secret for.
+pub fn burned_key(symbol: &str) -> String {
+    format!("balance:{}:{}", bs58::encode([0u8; 48]).into_string(), symbol)
+}
+
+/// An integer contract value, as the node sends it: a number, a decimal string, or either
+/// under `value` or `result`. `None` for an absent key.
+pub fn state_integer(value: &Value) -> Option<u128> {
+    match value {
+        Value::Number(n) => n.as_u64().map(u128::from),
+        Value::String(s) => s.trim().parse().ok(),
+        Value::Object(map) => map.get("value").or_else(|| map.get("result")).and_then(state_integer),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DecimalsSource {
+    /// Read from the token's Coin storage
+    Onchain,
+    /// From the built-in defaults or `SYMBOL_DECIMALS`
+    Configured,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenInfo {
+    pub symbol: String,
+    /// Total supply in atoms; a string because it may not fit a JSON number exactly
+    pub total_supply_flat: String,
+    /// Total supply in whole tokens; absent when the decimals are unknown
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_supply_float: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decimals: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decimals_source: Option<DecimalsSource>,
+    /// Held by the burn address, in whole tokens when the decimals are known, else in atoms
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub burned: Option<f64>,
+}
+
+impl TokenInfo {
+    /// Assembles a token from its storage values. On-chain decimals win over `registry`;
+    /// without either, amounts stay in atoms.
+    pub fn new(
+        symbol: &str,
+        total_supply: u128,
+        onchain_decimals: Option<u128>,
+        burned: Option<u128>,
+        registry: &DecimalsRegistry,
+    ) -> Self {
+        let onchain = onchain_decimals
+            .and_then(|d| u32::try_from(d).ok())
+            .filter(|d| *d <= MAX_DECIMALS);
+        let (decimals, decimals_source) = match (onchain, registry.get(symbol)) {
+            (Some(d), _) => (Some(d), Some(DecimalsSource::Onchain)),
+            (None, Some(d)) => (Some(d), Some(DecimalsSource::Configured)),
+            (None, None) => (None, None),
+        };
+        let to_float = |atoms: u128| atoms as f64 / 10f64.powi(decimals.unwrap_or(0) as i32);
+        Self {
+            symbol: symbol.to_string(),
+            total_supply_flat: total_supply.to_string(),
+            total_supply_float: decimals.map(|_| to_float(total_supply)),
+            decimals,
+            decimals_source,
+            burned: burned.map(to_float),
+        }
+    }
+}
+
+/// The symbols with a total supply in a `totalSupply:` export page, with the supply.
+pub fn supplies(page: &ExportPage) -> Vec<(String, u128)> {
+    page.keys
+        .iter()
+        .filter_map(|key| {
+            let symbol = key.key.strip_prefix(TOTAL_SUPPLY_PREFIX)?;
+            let supply = key.value.value.parse().ok()?;
+            Some((symbol.to_string(), supply))
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenList {
+    pub count: usize,
+    pub tokens: Vec<TokenInfo>,
+    /// Set when the chain has more tokens than one call lists
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+impl TokenList {
+    pub fn new(tokens: Vec<TokenInfo>, complete: bool) -> Self {
+        Self {
+            count: tokens.len(),
+            note: (!complete).then(|| {
+                format!("only the first {} tokens are listed; pass symbol for any other", MAX_LISTED_TOKENS)
+            }),
+            tokens,
+        }
+    }
+}
+
+/// What an unknown symbol is reported as.
+pub fn unknown_symbol(symbol: &str) -> BlockchainError {
+    BlockchainError::NotFound { argument: "symbol".to_string(), value: symbol.to_string() }
+}
+
+/// The supply of `symbol` from its `totalSupply:` value; a token without one does not exist.
+pub fn total_supply(symbol: &str, value: Option<&Value>) -> Result<u128> {
+    value.and_then(state_integer).ok_or_else(|| unknown_symbol(symbol))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::action;
+    use crate::blockchain::state_export::ExportedKey;
+    use serde_json::json;
+
+    #[test]
+    fn integers_are_read_in_every_shape_the_node_sends() {
+        assert_eq!(state_integer(&json!(42)), Some(42));
+        assert_eq!(state_integer(&json!(" 340282366920938463463374607431768211455 ")), Some(u128::MAX));
+        assert_eq!(state_integer(&json!({ "value": "7" })), Some(7));
+        assert_eq!(state_integer(&json!({ "result": { "value": 8 } })), Some(8));
+        for absent in [json!(null), json!(-1), json!(1.5), json!("seven"), json!({ "other": 1 }), json!([1])] {
+            assert_eq!(state_integer(&absent), None, "{}", absent);
+        }
+    }
+
+    #[test]
+    fn onchain_decimals_win_over_configured_ones() {
+        let registry = DecimalsRegistry::default();
+        let onchain = TokenInfo::new("AMA", 5_000_000_000, Some(6), Some(1_000_000), &registry);
+        assert_eq!((onchain.decimals, onchain.decimals_source), (Some(6), Some(DecimalsSource::Onchain)));
+        assert_eq!((onchain.total_supply_float, onchain.burned), (Some(5000.0), Some(1.0)));
+
+        let configured = TokenInfo::new("AMA", 5_000_000_000, None, None, &registry);
+        assert_eq!((configured.decimals, configured.decimals_source), (Some(9), Some(DecimalsSource::Configured)));
+        assert_eq!(configured.total_supply_float, Some(5.0));
+
+        // Out-of-range on-chain decimals are ignored rather than trusted
+        let bogus = TokenInfo::new("AMA", 1, Some(u128::from(MAX_DECIMALS) + 1), None, &registry);
+        assert_eq!(bogus.decimals_source, Some(DecimalsSource::Configured));
+    }
+
+    #[test]
+    fn unknown_decimals_keep_amounts_in_atoms() {
+        let token = TokenInfo::new("NEW", u128::MAX, None, Some(250), &DecimalsRegistry::default());
+        assert_eq!(token.total_supply_flat, u128::MAX.to_string());
+        assert_eq!((token.decimals, token.total_supply_float, token.burned), (None, None, Some(250.0)));
+        let json = serde_json::to_value(&token).unwrap();
+        assert!(json.get("decimals").is_none() && json.get("total_supply_float").is_none());
+    }
+
+    #[test]
+    fn supplies_come_from_total_supply_keys() {
+        let key = |key: &str, value: &str| ExportedKey { key: key.into(), value: action::decode_value(&json!(value)) };
+        let page = ExportPage {
+            contract_address: COIN_CONTRACT.into(),
+            prefix: TOTAL_SUPPLY_PREFIX.into(),
+            keys: vec![key("totalSupply:AMA", "1000"), key("decimals:AMA", "9"), key("totalSupply:BAD", "lots")],
+            count: 3,
+            next_cursor: None,
+            complete: true,
+        };
+        assert_eq!(supplies(&page), vec![("AMA".to_string(), 1000)]);
+    }
+
+    #[test]
+    fn a_symbol_without_a_supply_does_not_exist() {
+        assert_eq!(total_supply("AMA", Some(&json!("12"))).unwrap(), 12);
+        let missing = total_supply("NOPE", None).unwrap_err();
+        assert!(matches!(missing, BlockchainError::NotFound { ref argument, ref value } if argument == "symbol" && value == "NOPE"));
+        assert!(total_supply("NOPE", Some(&json!(null))).is_err());
+    }
+
+    #[test]
+    fn lists_note_when_they_are_cut_short() {
+        assert_eq!(TokenList::new(Vec::new(), true).note, None);
+        let partial = TokenList::new(Vec::new(), false);
+        assert_eq!(partial.note.unwrap(), "only the first 100 tokens are listed; pass symbol for any other");
+        assert_eq!(burned_key("AMA"), format!("balance:{}:AMA", "1".repeat(48)));
+    }
+}
@@ -0,0 +1,301 @@
+use super::types::Transaction;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+pub const MAX_TRACE_HOPS: u32 = 3;
+pub const DEFAULT_TRACE_HOPS: u32 = 2;
+pub const MAX_TRACE_TXS_PER_ADDRESS: u32 = 100;
+pub const DEFAULT_TRACE_TXS_PER_ADDRESS: u32 = 25;
+pub const MAX_TRACE_BUDGET: u32 = 50;
+pub const DEFAULT_TRACE_BUDGET: u32 = 20;
+
+/// Which way funds are followed from the starting address.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TraceDirection {
+    /// Towards the senders that paid the address
+    In,
+    /// Towards the recipients the address paid
+    #[default]
+    Out,
+}
+
+/// A `Coin.transfer` reduced to what the graph needs.
+pub(crate) struct Transfer<'a> {
+    pub from: &'a str,
+    pub to: &'a str,
+    pub amount: u128,
+    pub symbol: &'a str,
+}
+
+pub(crate) fn transfer(tx: &Transaction) -> Option<Transfer<'_>> {
+    let action = &tx.tx.action;
+    if action.contract != "Coin" || action.function != "transfer" {
+        return None;
+    }
+    Some(Transfer {
+        from: &tx.tx.signer,
+        to: action.args.first()?,
+        amount: action.args.get(1)?.parse().ok()?,
+        symbol: action.args.get(2).map_or("AMA", String::as_str),
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphNode {
+    pub address: String,
+    /// Hops from the starting address
+    pub hop: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+    /// Total atoms moved along this edge per symbol, as decimal strings
+    pub amounts: BTreeMap<String, String>,
+    pub tx_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FundsGraph {
+    pub direction: TraceDirection,
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+    /// Addresses the trace stopped at: the outermost hop, or addresses with no further transfers
+    pub endpoints: Vec<String>,
+    /// Addresses reached again, through a cycle or a second path, and not expanded twice
+    pub revisited: Vec<String>,
+    /// Addresses the request budget left unexpanded
+    pub unexpanded: Vec<String>,
+}
+
+/// Builds the graph one expanded address at a time, so the walk over the node can stay a
+/// loop of fetches and the graph logic stays free of I/O.
+#[derive(Debug, Clone)]
+pub struct FundsTrace {
+    direction: TraceDirection,
+    hops: BTreeMap<String, u32>,
+    edges: BTreeMap<(String, String), (BTreeMap<String, u128>, usize)>,
+    expanded: BTreeSet<String>,
+    revisited: BTreeSet<String>,
+}
+
+impl FundsTrace {
+    pub fn new(start: &str, direction: TraceDirection) -> Self {
+        Self {
+            direction,
+            hops: BTreeMap::from([(start.to_string(), 0)]),
+            edges: BTreeMap::new(),
+            expanded: BTreeSet::new(),
+            revisited: BTreeSet::new(),
+        }
+    }
+
+    /// Records the transfers in `history` that move funds in the traced direction for
+    /// `address`, and returns the addresses reached for the first time, to expand next.
+    pub fn expand(&mut self, address: &str, history: &[Transaction]) -> Vec<String> {
+        let hop = self.hops.get(address).copied().unwrap_or(0) + 1;
+        self.expanded.insert(address.to_string());
+
+        let mut reached = Vec::new();
+        for t in history.iter().filter_map(transfer) {
+            let (edge, next) = match self.direction {
+                TraceDirection::Out if t.from == address => ((t.from, t.to), t.to),
+                TraceDirection::In if t.to == address => ((t.from, t.to), t.from),
+                _ => continue,
+            };
+
+            let (amounts, count) = self
+                .edges
+                .entry((edge.0.to_string(), edge.1.to_string()))
+                .or_default();
+            *amounts.entry(t.symbol.to_string()).or_default() += t.amount;
+            *count += 1;
+
+            if self.hops.contains_key(next) {
+                if next != address && !reached.iter().any(|r| r == next) {
+                    self.revisited.insert(next.to_string());
+                }
+            } else {
+                self.hops.insert(next.to_string(), hop);
+                reached.push(next.to_string());
+            }
+        }
+        reached
+    }
+
+    pub fn finish(self, max_hops: u32) -> FundsGraph {
+        let has_onward_edge = |address: &str| {
+            self.edges.keys().any(|(from, to)| match self.direction {
+                TraceDirection::Out => from == address,
+                TraceDirection::In => to == address,
+            })
+        };
+        let endpoints = self
+            .hops
+            .iter()
+            .filter(|(address, hop)| {
+                **hop == max_hops || (self.expanded.contains(*address) && !has_onward_edge(address))
+            })
+            .map(|(address, _)| address.clone())
+            .collect();
+        let unexpanded = self
+            .hops
+            .iter()
+            .filter(|(address, hop)| **hop < max_hops && !self.expanded.contains(*address))
+            .map(|(address, _)| address.clone())
+            .collect();
+
+        FundsGraph {
+            direction: self.direction,
+            nodes: self
+                .hops
+                .iter()
+                .map(|(address, hop)| GraphNode { address: address.clone(), hop: *hop })
+                .collect(),
+            edges: self
+                .edges
+                .into_iter()
+                .map(|((from, to), (amounts, tx_count))| GraphEdge {
+                    from,
+                    to,
+                    amounts: amounts.into_iter().map(|(symbol, atoms)| (symbol, atoms.to_string())).collect(),
+                    tx_count,
+                })
+                .collect(),
+            endpoints,
+            revisited: self.revisited.into_iter().collect(),
+            unexpanded,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn transfer_tx(hash: &str, from: &str, to: &str, amount: u64, symbol: &str) -> Transaction {
+        serde_json::from_value(json!({
+            "hash": hash,
+            "metadata": { "entry_hash": "entry", "entry_height": 1 },
+            "signature": "sig",
+            "result": { "error": "ok" },
+            "tx": {
+                "action": { "args": [to, amount.to_string(), symbol], "function": "transfer", "op": "call", "contract": "Coin" },
+                "nonce": 1,
+                "signer": from
+            },
+            "receipt": { "success": true, "result": "ok", "logs": [], "exec_used": "0" }
+        }))
+        .unwrap()
+    }
+
+    /// Three hops out of A: A pays B twice and C once, B and C both pay D (a second path),
+    /// D pays E and sends some back to A (a cycle). Each history holds both directions, as
+    /// the node returns them.
+    fn histories() -> HashMap<&'static str, Vec<Transaction>> {
+        let ab1 = transfer_tx("ab1", "A", "B", 60, "AMA");
+        let ab2 = transfer_tx("ab2", "A", "B", 40, "AMA");
+        let ac = transfer_tx("ac", "A", "C", 10, "AMA");
+        let xa = transfer_tx("xa", "X", "A", 5, "AMA");
+        let bd = transfer_tx("bd", "B", "D", 70, "AMA");
+        let cd = transfer_tx("cd", "C", "D", 5, "USDX");
+        let da = transfer_tx("da", "D", "A", 1, "AMA");
+        let de = transfer_tx("de", "D", "E", 30, "AMA");
+        HashMap::from([
+            ("A", vec![ab1.clone(), ab2.clone(), ac.clone(), xa, da.clone()]),
+            ("B", vec![ab1, ab2, bd.clone()]),
+            ("C", vec![ac, cd.clone()]),
+            ("D", vec![bd, cd, da, de.clone()]),
+            ("E", vec![de]),
+        ])
+    }
+
+    /// The client's walk: hop by hop, one history read per address, within `budget`.
+    fn walk(start: &str, direction: TraceDirection, max_hops: u32, budget: u32) -> FundsGraph {
+        let histories = histories();
+        let mut trace = FundsTrace::new(start, direction);
+        let mut frontier = vec![start.to_string()];
+        let mut used = 0;
+        for _ in 0..max_hops {
+            let mut next = Vec::new();
+            for address in frontier {
+                if used == budget {
+                    break;
+                }
+                used += 1;
+                next.extend(trace.expand(&address, histories.get(address.as_str()).map_or(&[][..], Vec::as_slice)));
+            }
+            frontier = next;
+        }
+        trace.finish(max_hops)
+    }
+
+    fn hops(graph: &FundsGraph) -> Vec<(&str, u32)> {
+        graph.nodes.iter().map(|n| (n.address.as_str(), n.hop)).collect()
+    }
+
+    fn edge<'a>(graph: &'a FundsGraph, from: &str, to: &str) -> &'a GraphEdge {
+        graph.edges.iter().find(|e| e.from == from && e.to == to).unwrap()
+    }
+
+    #[test]
+    fn three_hops_out_aggregate_edges_and_stop_at_cycles() {
+        let graph = walk("A", TraceDirection::Out, 3, 20);
+        assert_eq!(hops(&graph), vec![("A", 0), ("B", 1), ("C", 1), ("D", 2), ("E", 3)]);
+
+        let ab = edge(&graph, "A", "B");
+        assert_eq!((ab.amounts.get("AMA").map(String::as_str), ab.tx_count), (Some("100"), 2));
+        assert_eq!(edge(&graph, "C", "D").amounts, BTreeMap::from([("USDX".to_string(), "5".to_string())]));
+        assert!(graph.edges.iter().all(|e| e.from != "X"), "incoming transfers are not followed out");
+        assert_eq!(graph.edges.len(), 6);
+
+        // D is reached again through C, and A again through D; neither is expanded twice
+        assert_eq!(graph.revisited, vec!["A".to_string(), "D".to_string()]);
+        assert_eq!(graph.endpoints, vec!["E".to_string()]);
+        assert!(graph.unexpanded.is_empty());
+    }
+
+    #[test]
+    fn tracing_in_follows_senders() {
+        let graph = walk("E", TraceDirection::In, 3, 20);
+        assert_eq!(hops(&graph), vec![("A", 3), ("B", 2), ("C", 2), ("D", 1), ("E", 0)]);
+        assert_eq!(edge(&graph, "A", "B").tx_count, 2);
+        assert!(graph.edges.iter().all(|e| !(e.from == "D" && e.to == "A")), "outgoing transfers are not followed in");
+        assert_eq!(graph.revisited, vec!["A".to_string()]);
+        assert_eq!(graph.endpoints, vec!["A".to_string()]);
+    }
+
+    #[test]
+    fn the_request_budget_leaves_addresses_unexpanded() {
+        let graph = walk("A", TraceDirection::Out, 3, 2);
+        assert_eq!(hops(&graph), vec![("A", 0), ("B", 1), ("C", 1), ("D", 2)]);
+        assert_eq!(graph.unexpanded, vec!["C".to_string(), "D".to_string()]);
+        assert!(graph.endpoints.is_empty());
+    }
+
+    #[test]
+    fn an_address_without_onward_transfers_is_an_endpoint() {
+        let graph = walk("E", TraceDirection::Out, 3, 20);
+        assert_eq!(hops(&graph), vec![("E", 0)]);
+        assert_eq!(graph.endpoints, vec!["E".to_string()]);
+        assert!(graph.edges.is_empty());
+    }
+
+    #[test]
+    fn only_coin_transfers_with_an_amount_count() {
+        let mut call = transfer_tx("m", "A", "B", 1, "AMA");
+        call.tx.action.function = "mint".to_string();
+        assert!(transfer(&call).is_none());
+        let mut bad = transfer_tx("b", "A", "B", 1, "AMA");
+        bad.tx.action.args[1] = "lots".to_string();
+        assert!(transfer(&bad).is_none());
+        let mut default_symbol = transfer_tx("d", "A", "B", 7, "AMA");
+        default_symbol.tx.action.args.truncate(2);
+        assert_eq!(transfer(&default_symbol).map(|t| (t.symbol, t.amount)), Some(("AMA", 7)));
+    }
+}
@@ -0,0 +1,311 @@
+//! Dry-run checks of a Coin.transfer before it is built, so malformed addresses, bad
+//! amounts and amounts above the balance fail up front instead of as a vague node error
+//! at submit time.
+
+use super::address::Address;
+use super::decimals::DecimalsRegistry;
+use super::error::{BlockchainError, Result};
+use super::symbol;
+use super::types::{Argument, Balance, TransactionRequest};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+const COIN_CONTRACT: &str = "Coin";
+const TRANSFER_FUNCTION: &str = "transfer";
+/// Check that compares the amount with the source balance.
+pub const BALANCE_CHECK: &str = "balance";
+
+/// An amount as a client sends it: an integer is atoms, a decimal is whole tokens.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum TransferAmount {
+    Atoms(u64),
+    Whole(f64),
+    Text(String),
+}
+
+impl TransferAmount {
+    fn text(&self) -> String {
+        match self {
+            Self::Atoms(atoms) => atoms.to_string(),
+            // Keeps 5.0 a decimal, so it stays whole tokens rather than 5 atoms
+            Self::Whole(whole) if whole.fract() == 0.0 => format!("{:.1}", whole),
+            Self::Whole(whole) => whole.to_string(),
+            Self::Text(text) => text.trim().to_string(),
+        }
+    }
+}
+
+/// The fields of a transfer, from a validate_transfer call or a create_transaction request.
+#[derive(Debug, Clone)]
+pub struct TransferRequest {
+    pub source: String,
+    pub destination: String,
+    pub amount: String,
+    pub symbol: String,
+}
+
+impl TransferRequest {
+    /// Addresses given with whitespace or in hex are normalized to base58.
+    pub fn new(source: &str, destination: &str, amount: &TransferAmount, symbol: Option<&str>) -> Self {
+        let canonical = |address: &str| Address::parse(address).map_or_else(|_| address.trim().to_string(), |a| a.to_string());
+        Self {
+            source: canonical(source),
+            destination: canonical(destination),
+            amount: amount.text(),
+            symbol: symbol.unwrap_or("AMA").trim().to_string(),
+        }
+    }
+
+    /// The transfer a create_transaction request makes, `None` when it is not a Coin.transfer
+    /// with the recipient, amount and symbol arguments.
+    pub fn from_request(req: &TransactionRequest) -> Option<Self> {
+        if req.contract != COIN_CONTRACT || req.function != TRANSFER_FUNCTION {
+            return None;
+        }
+        let text = |arg: &Argument| match arg {
+            Argument::String(s) | Argument::Utf8 { utf8: s } | Argument::Base58 { b58: s } => Some(s.clone()),
+            Argument::Number(n) => Some(n.to_string()),
+            Argument::Hex { .. } => None,
+        };
+        match req.args.as_slice() {
+            [to, amount, symbol] => Some(Self {
+                source: req.signer.clone(),
+                destination: text(to)?,
+                amount: text(amount)?,
+                symbol: text(symbol)?,
+            }),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Passed,
+    Failed,
+    /// Could not be run, e.g. the balance lookup failed; does not make the transfer invalid
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TransferCheck {
+    pub check: &'static str,
+    pub status: CheckStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TransferValidation {
+    /// True when no check failed
+    pub valid: bool,
+    pub source: String,
+    pub destination: String,
+    pub symbol: String,
+    /// The amount in atoms, when it parsed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount_atoms: Option<String>,
+    /// The source's balance of the symbol in atoms, when it was read
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub available_atoms: Option<String>,
+    pub checks: Vec<TransferCheck>,
+    #[serde(skip)]
+    atoms: Option<u128>,
+}
+
+impl TransferValidation {
+    /// Runs the checks that need no node: both addresses, the symbol and the amount.
+    /// Without `decimals` the amount must be whole atoms, as in a create_transaction argument.
+    pub fn check(req: &TransferRequest, decimals: Option<&DecimalsRegistry>) -> Self {
+        let mut report = Self {
+            valid: true,
+            source: req.source.clone(),
+            destination: req.destination.clone(),
+            symbol: req.symbol.clone(),
+            amount_atoms: None,
+            available_atoms: None,
+            checks: Vec::new(),
+            atoms: None,
+        };
+        report.record("source_address", address_check(&req.source));
+        report.record("destination_address", address_check(&req.destination));
+        let symbol = if req.symbol.is_empty() { Err("symbol is empty".to_string()) } else { Ok(()) };
+        report.record("symbol", symbol);
+        match parse_amount(&req.amount, decimals.and_then(|d| d.get(&req.symbol))) {
+            Ok(atoms) => {
+                report.atoms = Some(atoms);
+                report.amount_atoms = Some(atoms.to_string());
+                report.record("amount", Ok(()));
+            }
+            Err(e) => report.record("amount", Err(e)),
+        }
+        report
+    }
+
+    /// Whether the balance check can run: the source is a valid key and the amount parsed.
+    pub fn needs_balance(&self) -> bool {
+        self.atoms.is_some() && self.passed("source_address")
+    }
+
+    /// Compares the amount with the source's `balances`; `Err` records why they could not be
+    /// read. A symbol the source does not hold but nearly matches a held one fails first, as
+    /// a likely typo.
+    pub fn check_balance(&mut self, balances: std::result::Result<&[Balance], String>) {
+        let Some(atoms) = self.atoms else {
+            return;
+        };
+        match balances {
+            Ok(balances) => {
+                let held = symbol::check_held(&self.symbol, balances);
+                self.record("symbol_held", held);
+                let available = balances.iter().find(|b| b.symbol == self.symbol).map_or(0, |b| b.flat as u128);
+                self.available_atoms = Some(available.to_string());
+                let covered = if atoms <= available {
+                    Ok(())
+                } else {
+                    Err(format!("{} atoms of {} is above the balance of {}", atoms, self.symbol, available))
+                };
+                self.record(BALANCE_CHECK, covered);
+            }
+            Err(e) => self.checks.push(TransferCheck { check: BALANCE_CHECK, status: CheckStatus::Skipped, detail: Some(e) }),
+        }
+    }
+
+    /// The first failed check as an error: `InsufficientBalance` for the balance, a
+    /// validation failure for anything else.
+    pub fn into_result(self) -> Result<Self> {
+        let Some(failed) = self.checks.iter().find(|c| c.status == CheckStatus::Failed) else {
+            return Ok(self);
+        };
+        if failed.check == BALANCE_CHECK {
+            return Err(BlockchainError::InsufficientBalance {
+                required: self.amount_atoms.clone().unwrap_or_default(),
+                available: self.available_atoms.clone().unwrap_or_default(),
+            });
+        }
+        Err(BlockchainError::ValidationFailed(format!(
+            "{}: {}",
+            failed.check,
+            failed.detail.as_deref().unwrap_or("failed")
+        )))
+    }
+
+    fn passed(&self, check: &str) -> bool {
+        self.checks.iter().any(|c| c.check == check && c.status == CheckStatus::Passed)
+    }
+
+    fn record(&mut self, check: &'static str, outcome: std::result::Result<(), String>) {
+        let (status, detail) = match outcome {
+            Ok(()) => (CheckStatus::Passed, None),
+            Err(e) => (CheckStatus::Failed, Some(e)),
+        };
+        self.valid &= status != CheckStatus::Failed;
+        self.checks.push(TransferCheck { check, status, detail });
+    }
+}
+
+fn address_check(address: &str) -> std::result::Result<(), String> {
+    Address::from_base58(address).map(|_| ()).map_err(|e| format!("{}: {}", address, e))
+}
+
+/// Atoms for an amount: an integer is taken as atoms, a decimal as whole tokens converted
+/// with `decimals`, which it may not have more fractional digits than.
+pub fn parse_amount(amount: &str, decimals: Option<u32>) -> std::result::Result<u128, String> {
+    let atoms = match amount.split_once('.') {
+        None => amount.parse::<u128>().map_err(|_| format!("amount {} is not a whole number of atoms", amount))?,
+        Some((whole, fraction)) => {
+            let places = decimals.ok_or_else(|| {
+                format!("amount {} is not a whole number of atoms and cannot be converted from whole tokens", amount)
+            })?;
+            if (whole.is_empty() && fraction.is_empty()) || !whole.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit()) {
+                return Err(format!("amount {} is not a number", amount));
+            }
+            let fraction = fraction.trim_end_matches('0');
+            if fraction.len() > places as usize {
+                return Err(format!("amount {} has more than {} decimal places", amount, places));
+            }
+            let scale = 10u128.pow(places);
+            let whole: u128 = if whole.is_empty() { 0 } else { whole.parse().map_err(|_| format!("amount {} is too large", amount))? };
+            let fraction: u128 = if fraction.is_empty() {
+                0
+            } else {
+                fraction.parse::<u128>().map_err(|_| format!("amount {} is not a number", amount))?
+                    * 10u128.pow(places - fraction.len() as u32)
+            };
+            whole
+                .checked_mul(scale)
+                .and_then(|atoms| atoms.checked_add(fraction))
+                .ok_or_else(|| format!("amount {} is too large", amount))?
+        }
+    };
+    if atoms == 0 {
+        return Err("amount must be above zero".to_string());
+    }
+    Ok(atoms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AMA: Option<u32> = Some(9);
+
+    #[test]
+    fn integers_are_atoms_and_decimals_whole_tokens() {
+        assert_eq!(parse_amount("42", AMA), Ok(42));
+        assert_eq!(parse_amount("1.5", AMA), Ok(1_500_000_000));
+        assert_eq!(parse_amount(".5", AMA), Ok(500_000_000));
+        assert_eq!(parse_amount("2.", AMA), Ok(2_000_000_000));
+        assert!(parse_amount("1.5", None).unwrap_err().contains("cannot be converted from whole tokens"));
+    }
+
+    #[test]
+    fn more_places_than_the_token_has_are_refused() {
+        assert_eq!(parse_amount("0.000000001", AMA), Ok(1));
+        assert_eq!(parse_amount("1.0000000010", AMA), Ok(1_000_000_001), "trailing zeros are not places");
+        assert_eq!(parse_amount("0.0000000001", AMA).unwrap_err(), "amount 0.0000000001 has more than 9 decimal places");
+        assert!(parse_amount("1.25", Some(1)).is_err());
+    }
+
+    #[test]
+    fn negative_empty_and_zero_amounts_are_refused() {
+        for (amount, error) in [
+            ("", "amount  is not a whole number of atoms"),
+            ("-1", "amount -1 is not a whole number of atoms"),
+            ("-1.5", "amount -1.5 is not a number"),
+            (".", "amount . is not a number"),
+            ("1e9", "amount 1e9 is not a whole number of atoms"),
+            ("0", "amount must be above zero"),
+            ("0.000", "amount must be above zero"),
+        ] {
+            assert_eq!(parse_amount(amount, AMA).unwrap_err(), error);
+        }
+    }
+
+    #[test]
+    fn amounts_past_u64_are_exact_until_u128_overflows() {
+        // u64::MAX + 1 atoms, and whole tokens whose atoms no longer fit a u64
+        assert_eq!(parse_amount("18446744073709551616", AMA), Ok(u64::MAX as u128 + 1));
+        assert_eq!(parse_amount("18446744073.709551616", AMA), Ok(u64::MAX as u128 + 1));
+        assert_eq!(parse_amount("20000000000.5", AMA), Ok(20_000_000_000_500_000_000));
+
+        let past_u128 = format!("{}0", u128::MAX);
+        assert!(parse_amount(&past_u128, AMA).unwrap_err().contains("is not a whole number of atoms"));
+        let whole_past_u128 = format!("{}.5", u128::MAX / 10);
+        assert_eq!(parse_amount(&whole_past_u128, AMA).unwrap_err(), format!("amount {} is too large", whole_past_u128));
+    }
+
+    #[test]
+    fn atoms_round_trip_through_whole_tokens() {
+        for atoms in [1u128, 999_999_999, 1_000_000_000, 123_456_789_012, u64::MAX as u128] {
+            let whole = format!("{}.{:09}", atoms / 1_000_000_000, atoms % 1_000_000_000);
+            assert_eq!(parse_amount(&whole, AMA), Ok(atoms), "{}", whole);
+            assert_eq!(parse_amount(&atoms.to_string(), AMA), Ok(atoms));
+        }
+        for (amount, atoms) in [(TransferAmount::Atoms(42), 42), (TransferAmount::Whole(5.0), 5_000_000_000), (TransferAmount::Whole(0.25), 250_000_000)] {
+            assert_eq!(parse_amount(&amount.text(), AMA), Ok(atoms));
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use super::action::{self, DecodedAction};
+use super::error::{BlockchainError, Result};
+use super::types::{TransactionAction, TransactionData};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Node path of the transaction pool, under the API prefix.
+pub const TX_POOL_PATH: &str = "chain/tx_pool";
+/// What a node without the pool endpoint is reported as.
+pub const TX_POOL_UNSUPPORTED: &str = "node does not expose tx pool";
+
+/// A transaction the node holds but has not included in an entry yet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingTransaction {
+    pub hash: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    pub tx: TransactionData,
+    /// Filled by the client, not the node
+    #[serde(default, skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub decoded_action: Option<DecodedAction>,
+}
+
+impl PendingTransaction {
+    /// The receiver of a `Coin.transfer`, the only call whose destination is known.
+    pub fn destination(&self) -> Option<&str> {
+        destination(&self.tx.action)
+    }
+
+    fn involves(&self, address: &str) -> bool {
+        self.tx.signer == address || self.destination() == Some(address)
+    }
+}
+
+fn destination(action: &TransactionAction) -> Option<&str> {
+    if action.contract != "Coin" || action.function != "transfer" {
+        return None;
+    }
+    action.args.first().map(String::as_str)
+}
+
+/// The node's transaction pool.
+#[derive(Debug, Clone, Default)]
+pub struct TxPool {
+    pub transactions: Vec<PendingTransaction>,
+    /// Pool items whose shape the typed form does not cover, as the node sent them
+    pub undecoded: Vec<Value>,
+}
+
+impl TxPool {
+    /// Reads a `chain/tx_pool` response. An item that does not fit `PendingTransaction` is
+    /// kept raw rather than failing the whole pool.
+    pub fn from_node(response: &Value) -> Result<Self> {
+        if response.get("error").and_then(Value::as_str) != Some("ok") {
+            return Err(BlockchainError::InvalidResponse("failed to get tx pool".to_string()));
+        }
+        let items = response
+            .get("txs")
+            .and_then(Value::as_array)
+            .ok_or_else(|| BlockchainError::InvalidResponse("missing txs field".to_string()))?;
+        let mut pool = Self::default();
+        for item in items {
+            match serde_json::from_value::<PendingTransaction>(item.clone()) {
+                Ok(mut tx) => {
+                    tx.decoded_action = action::decode(&tx.tx.action);
+                    pool.transactions.push(tx);
+                }
+                Err(_) => pool.undecoded.push(item.clone()),
+            }
+        }
+        Ok(pool)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PendingTransactions {
+    /// Transactions in the whole pool, before the address filter
+    pub pool_size: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    pub count: usize,
+    pub transactions: Vec<PendingTransaction>,
+    /// Unreadable pool items; never filtered, since their signer is unknown
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub undecoded: Vec<Value>,
+}
+
+impl PendingTransactions {
+    /// Keeps the transactions `address` signed or is the receiver of; all of them without one.
+    pub fn new(pool: TxPool, address: Option<&str>) -> Self {
+        let pool_size = pool.transactions.len() + pool.undecoded.len();
+        let transactions: Vec<PendingTransaction> = pool
+            .transactions
+            .into_iter()
+            .filter(|tx| address.is_none_or(|a| tx.involves(a)))
+            .collect();
+        Self {
+            pool_size,
+            address: address.map(str::to_string),
+            count: transactions.len(),
+            transactions,
+            undecoded: pool.undecoded,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SIGNER: &str = "29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw";
+    const RECEIVER: &str = "42KfUgyU2XHCPwDD6g9gryCvtj9vqJAvM6h3Yzs3VRbuyT2JCHwvuk5iAbzXrioerf";
+
+    fn pending(hash: &str, signer: &str, contract: &str, function: &str, args: Value) -> Value {
+        json!({
+            "hash": hash,
+            "tx": {
+                "action": { "args": args, "function": function, "op": "call", "contract": contract },
+                "nonce": 1,
+                "signer": signer
+            }
+        })
+    }
+
+    fn pool() -> TxPool {
+        TxPool::from_node(&json!({
+            "error": "ok",
+            "txs": [
+                pending("out", SIGNER, "Coin", "transfer", json!([RECEIVER, "5", "AMA"])),
+                pending("in", RECEIVER, "Coin", "transfer", json!([SIGNER, "5", "AMA"])),
+                pending("call", RECEIVER, "Epoch", "submit_sol", json!([SIGNER])),
+                { "hash": "odd", "tx": "not an object" }
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn unreadable_items_are_kept_raw() {
+        let pool = pool();
+        assert_eq!(pool.transactions.iter().map(|t| t.hash.as_str()).collect::<Vec<_>>(), vec!["out", "in", "call"]);
+        assert_eq!(pool.undecoded, vec![json!({ "hash": "odd", "tx": "not an object" })]);
+    }
+
+    #[test]
+    fn only_transfers_have_a_destination() {
+        let pool = pool();
+        assert_eq!(pool.transactions[0].destination(), Some(RECEIVER));
+        assert_eq!(pool.transactions[2].destination(), None);
+        assert!(pool.transactions[0].decoded_action.is_some());
+    }
+
+    #[test]
+    fn the_address_filter_matches_signer_or_receiver() {
+        let all = PendingTransactions::new(pool(), None);
+        assert_eq!((all.pool_size, all.count, all.undecoded.len()), (4, 3, 1));
+
+        // The Epoch call names SIGNER as an argument but is not a transfer to it
+        let mine = PendingTransactions::new(pool(), Some(SIGNER));
+        assert_eq!(mine.transactions.iter().map(|t| t.hash.as_str()).collect::<Vec<_>>(), vec!["out", "in"]);
+        assert_eq!((mine.pool_size, mine.count, mine.address.as_deref()), (4, 2, Some(SIGNER)));
+        assert_eq!(mine.undecoded.len(), 1, "undecoded items are never filtered");
+    }
+
+    #[test]
+    fn a_pool_response_needs_an_ok_envelope_and_txs() {
+        for bad in [json!({ "error": "not_found" }), json!({ "txs": [] }), json!({ "error": "ok" }), json!({ "error": "ok", "txs": {} })] {
+            assert!(matches!(TxPool::from_node(&bad), Err(BlockchainError::InvalidResponse(_))), "{}", bad);
+        }
+        let empty = TxPool::from_node(&json!({ "error": "ok", "txs": [] })).unwrap();
+        assert!(empty.transactions.is_empty() && empty.undecoded.is_empty());
+    }
+}
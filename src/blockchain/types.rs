@@ -1,3 +1,28 @@
+use super::action::{self, DecodedAction};
+use super::address::{Address, AddressError};
+use super::block_time::TimeTarget;
+use super::block_txs::MAX_BLOCK_TXS;
+use super::built::{BuiltStatus, MAX_BUILT_LIST_LIMIT};
+use super::error::BlockchainError;
+use super::history_filter::{Direction, HistoryFilter};
+use super::flexible::{de_flexible_opt_i64, de_flexible_opt_u32, de_flexible_opt_u64, de_flexible_u64};
+use super::finality::MAX_FINALITY_SAMPLE;
+use super::liveness::MAX_LIVENESS_SAMPLE;
+use super::investigate::MAX_INVESTIGATE_BUDGET;
+use super::labels::AddressLabel;
+use super::rejection::SubmitRejection;
+use super::decimals::{DecimalsRegistry, AMA_DECIMALS};
+use super::emission::EmissionWindow;
+use super::recent_blocks::MAX_RECENT_BLOCKS;
+use super::richlist::MAX_RICHLIST_LIMIT;
+use super::signers::MAX_SIGNER_WINDOW;
+use super::state_diff::MAX_DIFF_KEYS;
+use super::state_export::{ExportFormat, MAX_EXPORT_LIMIT, MAX_LIST_LIMIT};
+use super::wait::{MAX_WAIT_POLL_SECS, MAX_WAIT_TIMEOUT_SECS};
+use super::fee::FeeEstimate;
+use super::transfer_check::{TransferAmount, TransferRequest};
+use super::trace::{TraceDirection, MAX_TRACE_BUDGET, MAX_TRACE_HOPS, MAX_TRACE_TXS_PER_ADDRESS};
+use crate::secret::Secret;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use validator::Validate;
@@ -9,6 +34,8 @@ pub struct UnsignedTransactionBlob {
     pub transaction_hash: String,
     #[serde(skip)]
     pub tx_bytes: Vec<u8>,
+    #[serde(skip)]
+    pub nonce: i128,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
@@ -19,6 +46,19 @@ pub struct SignedTransaction {
     pub signature: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub network: Option<String>,
+    /// Retrying with the same key returns the first accepted result instead of broadcasting again
+    #[validate(length(min = 1, max = 128))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct BlobFreshnessQuery {
+    /// Unsigned blob from create_transaction
+    #[validate(length(min = 1))]
+    pub transaction: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,10 +66,40 @@ pub struct SubmitResponse {
     pub error: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tx_hash: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rejection: Option<SubmitRejection>,
+    /// Remediation for `rejection`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
 pub struct AccountQuery {
+    #[validate(length(min = 1))]
+    pub address: String,
+    /// Only this token, e.g. AMA, read with one cheaper lookup; omit for every token
+    #[validate(length(min = 1, max = 32))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+}
+
+impl AccountQuery {
+    /// The address normalized to base58, forgiving whitespace and hex.
+    pub fn address(&self) -> Result<Address, AddressError> {
+        Address::parse(&self.address)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ValidateAddressQuery {
+    /// Address to check, base58 or hex
+    pub address: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct NonceQuery {
     #[validate(length(min = 1))]
     pub address: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -40,9 +110,45 @@ pub struct AccountQuery {
 pub struct AccountBalance {
     pub address: String,
     pub balances: Vec<Balance>,
+    /// False when the chain has never seen the address (no balances, no transactions)
+    pub exists: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// `error` values of a `wallet/balance_all` envelope that mean the node has no record of
+/// the account, as opposed to a failed read.
+pub const UNKNOWN_ACCOUNT_ERRORS: &[&str] = &["not_found", "account_not_found", "unknown_account"];
+
+impl AccountBalance {
+    /// The balances in a `wallet/balance_all` response, `None` for the unknown-account
+    /// envelope. Any other non-ok envelope is an error, so a node failure is never
+    /// reported as an account that does not exist.
+    pub fn balances_from(response: &serde_json::Value) -> Result<Option<Vec<Balance>>, BlockchainError> {
+        match response.get("error").and_then(|e| e.as_str()) {
+            Some("ok") => {}
+            Some(error) if UNKNOWN_ACCOUNT_ERRORS.contains(&error) => return Ok(None),
+            Some(error) => return Err(BlockchainError::InvalidResponse(format!("balance_all failed: {}", error))),
+            None => return Err(BlockchainError::InvalidResponse("balance_all response has no error field".to_string())),
+        }
+        let balances = response
+            .get("balances")
+            .ok_or_else(|| BlockchainError::InvalidResponse("missing balances field".to_string()))?;
+        serde_json::from_value(balances.clone())
+            .map(Some)
+            .map_err(|e| BlockchainError::InvalidResponse(format!("failed to parse balances: {}", e)))
+    }
+}
+
+/// Length of a BLS12-381 G1 compressed public key, which is what account addresses are.
+pub const PUBLIC_KEY_LEN: usize = 48;
+
+/// Whether `address` decodes to a public key. Anything else cannot be an account.
+pub fn is_valid_address(address: &str) -> bool {
+    bs58::decode(address)
+        .into_vec()
+        .is_ok_and(|pk| pk.len() == PUBLIC_KEY_LEN)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Balance {
     pub symbol: String,
     /// Balance in smallest unit (atoms)
@@ -51,11 +157,158 @@ pub struct Balance {
     pub float: f64,
 }
 
+impl Balance {
+    pub fn zero(symbol: &str) -> Self {
+        Self { symbol: symbol.to_string(), flat: 0, float: 0.0 }
+    }
+}
+
+/// One AMA holder from the node's richlist. Nodes send the balance as `flat` atoms and a
+/// `float`, or only as `balance` in whole AMA, as numbers or strings; all of those parse.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "RawRichlistEntry")]
+pub struct RichlistEntry {
+    pub address: String,
+    /// Balance in atoms
+    pub flat: u64,
+    /// Balance in AMA
+    pub float: f64,
+}
+
+#[derive(Deserialize)]
+struct RawRichlistEntry {
+    address: String,
+    #[serde(default)]
+    flat: Option<serde_json::Value>,
+    #[serde(default)]
+    float: Option<serde_json::Value>,
+    #[serde(default)]
+    balance: Option<serde_json::Value>,
+}
+
+impl TryFrom<RawRichlistEntry> for RichlistEntry {
+    type Error = String;
+
+    fn try_from(raw: RawRichlistEntry) -> Result<Self, Self::Error> {
+        let number = |value: &Option<serde_json::Value>| match value {
+            Some(serde_json::Value::Number(n)) => n.as_f64(),
+            Some(serde_json::Value::String(s)) => s.trim().parse::<f64>().ok(),
+            _ => None,
+        };
+        let atoms = |value: &Option<serde_json::Value>| match value {
+            Some(serde_json::Value::Number(n)) => n.as_u64(),
+            Some(serde_json::Value::String(s)) => s.trim().parse::<u64>().ok(),
+            _ => None,
+        };
+        let scale = 10f64.powi(AMA_DECIMALS as i32);
+        let (flat, float) = match (atoms(&raw.flat), number(&raw.float).or_else(|| number(&raw.balance))) {
+            (Some(flat), Some(float)) => (flat, float),
+            (Some(flat), None) => (flat, flat as f64 / scale),
+            (None, Some(float)) => ((float * scale).round() as u64, float),
+            (None, None) => return Err(format!("richlist entry for {} has no readable balance", raw.address)),
+        };
+        Ok(Self { address: raw.address, flat, float })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct EpochScoreQuery {
+    /// Only this validator's score; all validators when omitted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct RichlistQuery {
+    /// Holders returned (default 20, max 500)
+    #[validate(range(min = 1, max = MAX_RICHLIST_LIMIT))]
+    #[serde(default, deserialize_with = "de_flexible_opt_u64", skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>,
+    /// Holders skipped from the top; ignored with around_address
+    #[serde(default, deserialize_with = "de_flexible_opt_u64", skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u64>,
+    /// Centre the page on this address and report its rank
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub around_address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<OutputDetail>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
 pub struct HeightQuery {
+    #[serde(deserialize_with = "de_flexible_u64")]
     pub height: u64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub network: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<OutputDetail>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct BlockWithTxsQuery {
+    #[serde(deserialize_with = "de_flexible_u64")]
+    pub height: u64,
+    /// Transactions skipped in each entry (default 0)
+    #[serde(default, deserialize_with = "de_flexible_opt_u64", skip_serializing_if = "Option::is_none")]
+    pub tx_offset: Option<u64>,
+    /// Transactions returned per entry (default 100, max 500)
+    #[validate(range(min = 1, max = MAX_BLOCK_TXS))]
+    #[serde(default, deserialize_with = "de_flexible_opt_u64", skip_serializing_if = "Option::is_none")]
+    pub tx_limit: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<OutputDetail>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct EntryHashQuery {
+    /// Base58 entry hash
+    #[validate(length(min = 1))]
+    pub hash: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<OutputDetail>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct WaitForTransactionQuery {
+    #[validate(length(min = 1))]
+    pub tx_hash: String,
+    /// Seconds to wait before returning a pending result (default 60, max 300)
+    #[validate(range(min = 1, max = MAX_WAIT_TIMEOUT_SECS))]
+    #[serde(default, deserialize_with = "de_flexible_opt_u64", skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    /// Seconds between polls (default 2, max 60)
+    #[validate(range(min = 1, max = MAX_WAIT_POLL_SECS))]
+    #[serde(default, deserialize_with = "de_flexible_opt_u64", skip_serializing_if = "Option::is_none")]
+    pub poll_interval_secs: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct PendingTransactionsQuery {
+    /// Only transactions this address signed or receives a transfer in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct TxsInEntryQuery {
+    #[validate(length(min = 1))]
+    pub entry_hash: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<OutputDetail>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
@@ -70,14 +323,66 @@ pub struct TransactionQuery {
 pub struct TransactionHistoryQuery {
     #[validate(length(min = 1))]
     pub address: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, deserialize_with = "de_flexible_opt_u32", skip_serializing_if = "Option::is_none")]
     pub limit: Option<u32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, deserialize_with = "de_flexible_opt_u32", skip_serializing_if = "Option::is_none")]
     pub offset: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sort: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub network: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<OutputDetail>,
+    /// Only transactions the account signed ('sent') or transfers it received ('received')
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub direction: Option<Direction>,
+    /// Only transfers of this symbol, or calls attaching it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    /// Only transactions in entries at or after this unix time (seconds)
+    #[serde(default, deserialize_with = "de_flexible_opt_u64", skip_serializing_if = "Option::is_none")]
+    pub after_timestamp: Option<u64>,
+    /// Only transactions in entries before this unix time (seconds)
+    #[serde(default, deserialize_with = "de_flexible_opt_u64", skip_serializing_if = "Option::is_none")]
+    pub before_timestamp: Option<u64>,
+    /// Only transfers between the account and this address
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub counterparty: Option<String>,
+}
+
+impl TransactionHistoryQuery {
+    pub fn filter(&self) -> HistoryFilter {
+        HistoryFilter {
+            direction: self.direction,
+            symbol: self.symbol.clone(),
+            after_timestamp: self.after_timestamp,
+            before_timestamp: self.before_timestamp,
+            counterparty: self.counterparty.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct FullHistoryQuery {
+    #[validate(length(min = 1))]
+    pub address: String,
+    /// Transactions per page (default 100)
+    #[validate(range(min = 1))]
+    #[serde(default, deserialize_with = "de_flexible_opt_u32", skip_serializing_if = "Option::is_none")]
+    pub page_size: Option<u32>,
+    /// Stop after this many transactions (default and max: the server's full history limit, 10000 unless configured)
+    #[validate(range(min = 1))]
+    #[serde(default, deserialize_with = "de_flexible_opt_u64", skip_serializing_if = "Option::is_none")]
+    pub max_transactions: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+}
+
+impl FullHistoryQuery {
+    /// The address normalized to base58, forgiving whitespace and hex.
+    pub fn address(&self) -> Result<Address, AddressError> {
+        Address::parse(&self.address)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
@@ -90,6 +395,132 @@ pub struct ContractStateQuery {
     pub network: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct TokenInfoQuery {
+    /// Token symbol, e.g. AMA; omit to list every token
+    #[validate(length(min = 1, max = 32))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct ContractExportQuery {
+    #[validate(length(min = 1))]
+    pub contract_address: String,
+    /// Only keys starting with this are exported; all keys when empty
+    #[serde(default)]
+    pub prefix: String,
+    /// 'json' (default) or 'csv'
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<ExportFormat>,
+    /// Keys per page (default 200, max 1000)
+    #[validate(range(min = 1, max = MAX_EXPORT_LIMIT))]
+    #[serde(default, deserialize_with = "de_flexible_opt_u64", skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>,
+    /// next_cursor of the previous page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct ContractKeysQuery {
+    #[validate(length(min = 1))]
+    pub contract_address: String,
+    /// Only keys starting with this are listed; all keys when empty
+    #[serde(default)]
+    pub prefix: String,
+    /// Keys per page (default 100, max 200)
+    #[validate(range(min = 1, max = MAX_LIST_LIMIT))]
+    #[serde(default, deserialize_with = "de_flexible_opt_u64", skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>,
+    /// next_cursor of the previous page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct ContractCallPreviewQuery {
+    #[validate(length(min = 1))]
+    pub contract: String,
+    #[validate(length(min = 1))]
+    pub function: String,
+    /// Same conventions as create_transaction args
+    #[serde(default)]
+    pub args: Vec<Argument>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct ContractDiffQuery {
+    #[validate(length(min = 1))]
+    pub contract_address: String,
+    /// Storage keys to compare
+    #[validate(length(min = 1, max = MAX_DIFF_KEYS))]
+    pub keys: Vec<String>,
+    #[serde(deserialize_with = "de_flexible_u64")]
+    pub height_a: u64,
+    #[serde(deserialize_with = "de_flexible_u64")]
+    pub height_b: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct InvestigateQuery {
+    #[validate(length(min = 1))]
+    pub address_a: String,
+    #[validate(length(min = 1))]
+    pub address_b: String,
+    /// Upper bound on upstream requests, 4 to 50 (default 10)
+    #[validate(range(min = 4, max = MAX_INVESTIGATE_BUDGET))]
+    #[serde(default, deserialize_with = "de_flexible_opt_u32", skip_serializing_if = "Option::is_none")]
+    pub max_requests: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct TraceFundsQuery {
+    #[validate(length(min = 1))]
+    pub address: String,
+    /// `out` follows recipients (default), `in` follows senders
+    #[serde(default)]
+    pub direction: TraceDirection,
+    /// Hops to follow, 1 to 3 (default 2)
+    #[validate(range(min = 1, max = MAX_TRACE_HOPS))]
+    #[serde(default, deserialize_with = "de_flexible_opt_u32", skip_serializing_if = "Option::is_none")]
+    pub max_hops: Option<u32>,
+    /// Most recent transactions read per address, up to 100 (default 25)
+    #[validate(range(min = 1, max = MAX_TRACE_TXS_PER_ADDRESS))]
+    #[serde(default, deserialize_with = "de_flexible_opt_u32", skip_serializing_if = "Option::is_none")]
+    pub txs_per_address: Option<u32>,
+    /// Upper bound on upstream requests, up to 50 (default 20)
+    #[validate(range(min = 1, max = MAX_TRACE_BUDGET))]
+    #[serde(default, deserialize_with = "de_flexible_opt_u32", skip_serializing_if = "Option::is_none")]
+    pub max_requests: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct RawNodeQuery {
+    /// Node path with optional query string, e.g. `/api/chain/tip`
+    #[validate(length(min = 1, max = 512))]
+    pub path: String,
+    /// Only GET is supported
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
 pub struct TransactionRequest {
     #[validate(length(min = 1))]
@@ -103,10 +534,59 @@ pub struct TransactionRequest {
     pub attached_symbol: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub attached_amount: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, deserialize_with = "de_flexible_opt_i64", skip_serializing_if = "Option::is_none")]
     pub nonce: Option<i64>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct BuiltTransactionsQuery {
+    /// Only transactions this signer built
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signer: Option<String>,
+    /// 'pending', 'observed' or 'abandoned'
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<BuiltStatus>,
+    /// Newest transactions listed (default 50, max 1000)
+    #[validate(range(min = 1, max = MAX_BUILT_LIST_LIMIT))]
+    #[serde(default, deserialize_with = "de_flexible_opt_u64", skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct AddressLabelQuery {
+    #[validate(length(min = 1))]
+    pub address: String,
+    /// Name shown next to the address in outputs, e.g. 'exchange hot wallet' (at most 64 characters)
+    #[validate(length(min = 1))]
+    pub label: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct ImportLabelsQuery {
+    /// Labels as export_address_labels returns them; added_at may be omitted
+    pub labels: Vec<AddressLabel>,
+    /// Drop every existing label first (default false: merge, replacing labels of the same address)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replace: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct DecodeTransactionQuery {
+    /// Base58 unsigned blob from create_transaction, or a signed transaction
+    #[validate(length(min = 1))]
+    pub blob: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct RebuildQuery {
+    /// The request the blob was built from; `nonce` is required
+    #[validate(nested)]
+    pub request: TransactionRequest,
+    /// Base58 blob to compare against
+    #[validate(length(min = 1))]
+    pub blob: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(untagged)]
 pub enum Argument {
@@ -117,13 +597,244 @@ pub enum Argument {
     Utf8 { utf8: String },
 }
 
+impl Argument {
+    /// The bytes the argument stands for in a contract call.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, BlockchainError> {
+        match self {
+            Argument::String(s) => Ok(s.as_bytes().to_vec()),
+            Argument::Number(n) => Ok(n.to_string().as_bytes().to_vec()),
+            Argument::Base58 { b58 } => bs58::decode(b58)
+                .into_vec()
+                .map_err(|_| BlockchainError::ValidationFailed("invalid base58 arg".into())),
+            Argument::Hex { hex } => hex::decode(hex.trim_start_matches("0x"))
+                .map_err(|_| BlockchainError::ValidationFailed("invalid hex arg".into())),
+            Argument::Utf8 { utf8 } => Ok(utf8.as_bytes().to_vec()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct VerifySignatureQuery {
+    /// Signing hash: create_transaction's hex signing_payload or its base58 transaction_hash
+    #[validate(length(min = 1))]
+    pub signing_payload: String,
+    /// Base58 96-byte BLS signature
+    #[validate(length(min = 1))]
+    pub signature: String,
+    /// Base58 48-byte public key of the signer
+    #[validate(length(min = 1))]
+    pub public_key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct SigningFlowQuery {
+    /// Throwaway base58 64-byte secret key; never pass a key that holds funds. Generated
+    /// when omitted if the server enables key tools
+    #[validate(length(min = 1))]
+    #[schemars(with = "Option<String>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret_key: Option<Secret>,
+    /// Optional signature from an external signer over the flow's signing_payload
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
 pub struct ChainStatsQuery {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub network: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct EmissionStatsQuery {
+    /// Number of entries in the window, ending at the current height; give this or since
+    #[validate(range(min = 1))]
+    #[serde(default, deserialize_with = "de_flexible_opt_u64", skip_serializing_if = "Option::is_none")]
+    pub blocks: Option<u64>,
+    /// Start of the window as unix seconds or RFC3339, placed at a height by the same
+    /// search as get_block_by_timestamp; give this or blocks
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<TimeTarget>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+}
+
+impl EmissionStatsQuery {
+    /// The window asked for; exactly one of blocks and since must be given.
+    pub fn window(&self) -> Result<EmissionWindow, String> {
+        match (self.blocks, &self.since) {
+            (Some(blocks), None) => Ok(EmissionWindow::Blocks(blocks)),
+            (None, Some(since)) => since.unix_secs().map(EmissionWindow::Since),
+            (Some(_), Some(_)) => Err("give either blocks or since, not both".to_string()),
+            (None, None) => Err("give blocks or since".to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct FinalityQuery {
+    /// Most recent entries to walk back through looking for a finalized one (default 32, max 128)
+    #[validate(range(min = 1, max = MAX_FINALITY_SAMPLE))]
+    #[serde(default, deserialize_with = "de_flexible_opt_u32", skip_serializing_if = "Option::is_none")]
+    pub sample_entries: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct BlockRangeQuery {
+    /// First height of the range
+    #[serde(deserialize_with = "de_flexible_u64")]
+    pub start_height: u64,
+    /// Last height of the range, inclusive; at most 100 heights after start_height - 1
+    #[serde(deserialize_with = "de_flexible_u64")]
+    pub end_height: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct ServerKeyTransferQuery {
+    /// Base58 public key that receives the tokens
+    #[validate(length(min = 1))]
+    pub destination: String,
+    /// Amount in atoms (10^-9 AMA for AMA)
+    #[validate(range(min = 1))]
+    #[serde(deserialize_with = "de_flexible_u64")]
+    pub amount: u64,
+    /// Token symbol (default AMA)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct ValidateTransferQuery {
+    /// Base58 public key the tokens would leave
+    pub source: String,
+    /// Base58 public key that would receive them
+    pub destination: String,
+    /// An integer is atoms; a decimal such as "1.5" is whole tokens, converted with the
+    /// token's decimals
+    pub amount: TransferAmount,
+    /// Token symbol (default AMA)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct EstimateFeeQuery {
+    /// Base58 unsigned blob from create_transaction, or a signed transaction; when set the
+    /// transfer fields are ignored
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blob: Option<String>,
+    /// Base58 public key the tokens would leave
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Base58 public key that would receive them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub destination: Option<String>,
+    /// An integer is atoms; a decimal such as "1.5" is whole tokens
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<TransferAmount>,
+    /// Token symbol (default AMA)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+}
+
+impl EstimateFeeQuery {
+    /// The estimate for `blob`, or else for the transfer the other fields draft.
+    pub fn estimate(&self, decimals: &DecimalsRegistry) -> Result<FeeEstimate, String> {
+        if let Some(blob) = &self.blob {
+            return FeeEstimate::for_blob(blob);
+        }
+        match (&self.source, &self.destination, &self.amount) {
+            (Some(source), Some(destination), Some(amount)) => {
+                FeeEstimate::for_transfer(&TransferRequest::new(source, destination, amount, self.symbol.as_deref()), decimals)
+            }
+            _ => Err("give either blob, or source, destination and amount of a transfer".to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct BlocksBySignerQuery {
+    /// Base58 public key of the validator
+    #[validate(length(min = 1))]
+    pub signer: String,
+    /// First height of the range
+    #[serde(deserialize_with = "de_flexible_u64")]
+    pub start_height: u64,
+    /// Last height of the range, inclusive; at most 500 heights after start_height - 1
+    #[serde(deserialize_with = "de_flexible_u64")]
+    pub end_height: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct BlockAtTimeQuery {
+    /// Unix seconds, or RFC3339 such as "2024-06-01T12:00:00Z"
+    pub timestamp: TimeTarget,
+    /// Entry fetches the search may make (default and max: the server's limit, 40 unless configured)
+    #[validate(range(min = 2))]
+    #[serde(default, deserialize_with = "de_flexible_opt_u64", skip_serializing_if = "Option::is_none")]
+    pub max_probes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct RecentBlocksQuery {
+    /// Heights to cover, ending at the tip (default 10, max 50)
+    #[validate(range(min = 1, max = MAX_RECENT_BLOCKS))]
+    #[serde(default, deserialize_with = "de_flexible_opt_u64", skip_serializing_if = "Option::is_none")]
+    pub count: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct SignerDistributionQuery {
+    /// Number of entries in the window, ending at the tip (default 100, max 1000)
+    #[validate(range(min = 1, max = MAX_SIGNER_WINDOW))]
+    #[serde(default, deserialize_with = "de_flexible_opt_u64", skip_serializing_if = "Option::is_none")]
+    pub blocks: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct EpochInfoQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct LivenessQuery {
+    /// Entries sampled for the longest gap, ending at the tip (default 50, max 500)
+    #[validate(range(min = 2, max = MAX_LIVENESS_SAMPLE))]
+    #[serde(default, deserialize_with = "de_flexible_opt_u64", skip_serializing_if = "Option::is_none")]
+    pub blocks: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct CorrelateValidatorsQuery {
+    /// Entries searched for each validator's last signature, ending at the tip (default
+    /// 100, max 1000)
+    #[validate(range(min = 1, max = MAX_SIGNER_WINDOW))]
+    #[serde(default, deserialize_with = "de_flexible_opt_u64", skip_serializing_if = "Option::is_none")]
+    pub blocks: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ChainStats {
     pub height: u64,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -138,11 +849,12 @@ pub struct ChainStats {
     pub tx_pool_size: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub txs_per_sec: Option<f64>,
+    /// Fields the node sends beyond the known ones; sorted so the output is byte-stable
     #[serde(flatten)]
-    pub extra: std::collections::HashMap<String, serde_json::Value>,
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct BlockEntry {
     pub hash: String,
     pub header: Header,
@@ -151,7 +863,30 @@ pub struct BlockEntry {
     pub consensus: Option<Consensus>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl BlockEntry {
+    /// The entry in a `chain/hash/{hash}` response; a `not_found` envelope is `NotFound`.
+    pub fn from_hash_response(hash: &str, response: &serde_json::Value) -> Result<Self, BlockchainError> {
+        match response.get("error").and_then(|e| e.as_str()) {
+            Some("ok") => {}
+            Some("not_found") => {
+                return Err(BlockchainError::NotFound { argument: "hash".to_string(), value: hash.to_string() });
+            }
+            _ => return Err(BlockchainError::InvalidResponse("failed to get entry".to_string())),
+        }
+        let entry = response
+            .get("entry")
+            .ok_or_else(|| BlockchainError::InvalidResponse("missing entry field".to_string()))?;
+        serde_json::from_value(entry.clone())
+            .map_err(|e| BlockchainError::InvalidResponse(format!("failed to parse entry: {}", e)))
+    }
+}
+
+/// Whether `hash` decodes as base58, checked before a hash goes into a node path.
+pub fn is_base58(hash: &str) -> bool {
+    !hash.is_empty() && bs58::decode(hash).into_vec().is_ok()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Header {
     pub slot: u64,
     pub height: u64,
@@ -162,16 +897,19 @@ pub struct Header {
     pub root_tx: String,
     pub root_validator: String,
     pub prev_slot: u64,
+    /// Seconds or milliseconds, depending on the node; see `clock::entry_secs`
+    #[serde(default, deserialize_with = "de_flexible_opt_u64", skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<u64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Consensus {
     pub score: f64,
     pub finality_reached: bool,
     pub mut_hash: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Transaction {
     pub hash: String,
     pub metadata: TransactionMetadata,
@@ -179,9 +917,19 @@ pub struct Transaction {
     pub result: TransactionResult,
     pub tx: TransactionData,
     pub receipt: TransactionReceipt,
+    /// Readable form of a contract call's arguments; filled by the client, not the node
+    #[serde(default, skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub decoded_action: Option<DecodedAction>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl Transaction {
+    pub fn with_decoded_action(mut self) -> Self {
+        self.decoded_action = action::decode(&self.tx.action);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TransactionMetadata {
     pub entry_hash: String,
     pub entry_height: u64,
@@ -189,27 +937,31 @@ pub struct TransactionMetadata {
     pub tx_event: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TransactionResult {
     pub error: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TransactionData {
     pub action: TransactionAction,
     pub nonce: u64,
     pub signer: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TransactionAction {
     pub args: Vec<String>,
     pub function: String,
     pub op: String,
     pub contract: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attached_symbol: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attached_amount: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TransactionReceipt {
     pub success: bool,
     pub result: String,
@@ -217,21 +969,538 @@ pub struct TransactionReceipt {
     pub exec_used: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct ValidatorDetailsQuery {
+    /// Validator public key (base58); all validators when omitted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub public_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
 pub struct ValidatorsQuery {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub network: Option<String>,
+    /// Skip the epoch score lookup and return public keys only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keys_only: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct RemovedValidatorsQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+}
+
+/// Trainers removed from the set during the current epoch.
+#[derive(Debug, Clone, Serialize)]
+pub struct RemovedTrainers {
+    /// Epoch the removals belong to, when the node says
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub epoch: Option<u64>,
+    pub keys: Vec<String>,
+}
+
+impl RemovedTrainers {
+    /// Reads a `/api/peer/removed_trainers` response; the keys may come as
+    /// `removed_trainers` or `trainers`.
+    pub fn from_node(response: &serde_json::Value) -> Result<Self, BlockchainError> {
+        if response.get("error").and_then(|e| e.as_str()) != Some("ok") {
+            return Err(BlockchainError::InvalidResponse("failed to get removed trainers".to_string()));
+        }
+        let keys = response
+            .get("removed_trainers")
+            .or_else(|| response.get("trainers"))
+            .ok_or_else(|| BlockchainError::InvalidResponse("missing removed_trainers field".to_string()))?;
+        let keys = serde_json::from_value(keys.clone())
+            .map_err(|e| BlockchainError::InvalidResponse(format!("failed to parse removed trainers: {}", e)))?;
+        let epoch = response
+            .get("epoch")
+            .and_then(|e| e.as_u64().or_else(|| e.as_str().and_then(|s| s.parse().ok())));
+        Ok(Self { epoch, keys })
+    }
+
+    /// Cross-checks the removals with the current trainer set.
+    pub fn against(self, current: &[String]) -> RemovedValidators {
+        let current: std::collections::HashSet<&str> = current.iter().map(String::as_str).collect();
+        let still_listed = self.keys.iter().filter(|k| current.contains(k.as_str())).cloned().collect();
+        RemovedValidators { epoch: self.epoch, count: self.keys.len(), removed: self.keys, still_listed }
+    }
+}
+
+/// Removed trainers checked against the trainer set.
+#[derive(Debug, Clone, Serialize)]
+pub struct RemovedValidators {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub epoch: Option<u64>,
+    pub count: usize,
+    pub removed: Vec<String>,
+    /// Removed keys the trainer list still holds; the node's two lists disagree
+    pub still_listed: Vec<String>,
+}
+
+/// A node from `/api/peer/nodes`. Nodes may leave out any field, the public key included.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct PeerNode {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pk: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ip4: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, deserialize_with = "de_flexible_opt_u64", skip_serializing_if = "Option::is_none")]
+    pub latency: Option<u64>,
+    #[serde(default, deserialize_with = "de_flexible_opt_u64", skip_serializing_if = "Option::is_none")]
+    pub last_message: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub online: Option<bool>,
+    /// Fields the node sends beyond the known ones; sorted so the output is byte-stable
+    #[serde(flatten)]
+    pub extra: std::collections::BTreeMap<String, serde_json::Value>,
+}
+
+impl PeerNode {
+    /// Whether the node's IP starts with `filter` or its public key contains it.
+    pub fn matches(&self, filter: &str) -> bool {
+        self.ip4.as_deref().is_some_and(|ip| ip.starts_with(filter))
+            || self.pk.as_deref().is_some_and(|pk| pk.contains(filter))
+    }
+}
+
+/// The peers a get_nodes call returns: `count` after the filter, `total` before it.
+#[derive(Debug, Clone, Serialize)]
+pub struct PeerList {
+    pub count: usize,
+    pub total: usize,
+    pub nodes: Vec<PeerNode>,
+}
+
+impl PeerList {
+    pub fn new(nodes: Vec<PeerNode>, filter: Option<&str>) -> Self {
+        let total = nodes.len();
+        let nodes: Vec<PeerNode> = match filter.map(str::trim).filter(|f| !f.is_empty()) {
+            Some(filter) => nodes.into_iter().filter(|node| node.matches(filter)).collect(),
+            None => nodes,
+        };
+        Self { count: nodes.len(), total, nodes }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct NodesQuery {
+    /// Only peers whose IP starts with this prefix or whose public key contains it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValidatorInfo {
-    pub version: String,
     pub pk: String,
-    pub latency: u64,
-    pub slot_speed: u64,
-    pub is_trainer: bool,
-    pub temporal_height: u64,
-    pub rooted_hash: String,
-    pub rooted_height: u64,
-    pub temporal_hash: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rank: Option<u64>,
+    /// Epoch the score belongs to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub epoch: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub emission_address: Option<String>,
+}
+
+impl ValidatorInfo {
+    /// Joins trainer keys with epoch scores; keys without a score keep `None`.
+    pub fn join(keys: Vec<String>, scores: Option<&[EpochScore]>) -> Vec<ValidatorInfo> {
+        keys.into_iter()
+            .map(|pk| {
+                let score = scores.and_then(|s| s.iter().find(|e| e.public_key == pk));
+                ValidatorInfo {
+                    score: score.map(|s| s.score),
+                    rank: score.map(|s| s.rank),
+                    pk,
+                    epoch: None,
+                    emission_address: None,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Where a validator's emissions are paid.
+#[derive(Debug, Clone, Serialize)]
+pub struct EmissionAddress {
+    pub public_key: String,
+    /// False when the validator never set an emission address
+    pub configured: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub emission_address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<&'static str>,
+}
+
+impl EmissionAddress {
+    /// Reads a `/api/epoch/get_emission_address/{pk}` response. A non-ok envelope or an
+    /// empty address means none is configured, which is an answer rather than an error.
+    pub fn from_node(public_key: &str, response: &serde_json::Value) -> Result<Self, BlockchainError> {
+        let status = response
+            .get("error")
+            .and_then(|e| e.as_str())
+            .ok_or_else(|| BlockchainError::InvalidResponse("missing error field".to_string()))?;
+        let address = response
+            .get("emission_address")
+            .or_else(|| response.get("address"))
+            .and_then(|a| a.as_str())
+            .filter(|a| status == "ok" && !a.is_empty());
+        Ok(match address {
+            Some(address) => Self {
+                public_key: public_key.to_string(),
+                configured: true,
+                emission_address: Some(address.to_string()),
+                note: None,
+            },
+            None => Self {
+                public_key: public_key.to_string(),
+                configured: false,
+                emission_address: None,
+                note: Some("no emission address is set; use the set_emission_address template to set one"),
+            },
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Validate)]
+pub struct EmissionAddressQuery {
+    /// Validator public key (base58)
+    pub address: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EpochScore {
+    pub public_key: String,
+    pub score: f64,
+    /// 1-based position by descending score
+    pub rank: u64,
+}
+
+impl EpochScore {
+    /// Parses the `scores` payload of `/api/epoch/score`, accepting `[pk, score]` pairs,
+    /// `{pk, score}` objects or a `{pk: score}` map, and ranks by descending score.
+    pub fn parse_list(scores: &serde_json::Value) -> Option<Vec<EpochScore>> {
+        let pairs: Vec<(String, f64)> = match scores {
+            serde_json::Value::Array(items) => items
+                .iter()
+                .map(|item| match item {
+                    serde_json::Value::Array(pair) => Some((
+                        pair.first()?.as_str()?.to_string(),
+                        number(pair.get(1)?)?,
+                    )),
+                    serde_json::Value::Object(obj) => Some((
+                        obj.get("pk")
+                            .or_else(|| obj.get("public_key"))?
+                            .as_str()?
+                            .to_string(),
+                        number(obj.get("score")?)?,
+                    )),
+                    _ => None,
+                })
+                .collect::<Option<_>>()?,
+            serde_json::Value::Object(map) => map
+                .iter()
+                .map(|(pk, score)| Some((pk.clone(), number(score)?)))
+                .collect::<Option<_>>()?,
+            _ => return None,
+        };
+        Some(Self::rank(pairs))
+    }
+
+    /// The scores in an `/api/epoch/score` response; a non-ok envelope is an error rather
+    /// than an empty set.
+    pub fn from_response(response: &serde_json::Value) -> Result<Vec<EpochScore>, BlockchainError> {
+        if response.get("error").and_then(|e| e.as_str()) != Some("ok") {
+            return Err(BlockchainError::InvalidResponse("failed to get epoch scores".to_string()));
+        }
+        response
+            .get("scores")
+            .and_then(Self::parse_list)
+            .ok_or_else(|| BlockchainError::InvalidResponse("failed to parse epoch scores".to_string()))
+    }
+
+    /// The score of `public_key` from its `/api/epoch/score/{pk}` response (a bare `score`,
+    /// or `scores` in any shape `parse_list` accepts), ranked among `all`. A non-ok
+    /// envelope or a response without a score means the key is not a validator this epoch.
+    pub fn for_validator(
+        public_key: &str,
+        response: &serde_json::Value,
+        all: &[EpochScore],
+    ) -> Result<EpochScore, BlockchainError> {
+        let not_a_validator =
+            || BlockchainError::ValidationFailed(format!("{} has no score in the current epoch", public_key));
+        if response.get("error").and_then(|e| e.as_str()).is_some_and(|e| e != "ok") {
+            return Err(not_a_validator());
+        }
+        let score = response
+            .get("score")
+            .and_then(number)
+            .or_else(|| {
+                Self::parse_list(response.get("scores")?)?
+                    .into_iter()
+                    .find(|s| s.public_key == public_key)
+                    .map(|s| s.score)
+            })
+            .ok_or_else(not_a_validator)?;
+        let rank = all.iter().filter(|s| s.public_key != public_key && s.score > score).count() as u64 + 1;
+        Ok(EpochScore { public_key: public_key.to_string(), score, rank })
+    }
+
+    pub fn rank(mut pairs: Vec<(String, f64)>) -> Vec<EpochScore> {
+        pairs.sort_by(|a, b| b.1.total_cmp(&a.1));
+        pairs
+            .into_iter()
+            .enumerate()
+            .map(|(i, (public_key, score))| EpochScore {
+                public_key,
+                score,
+                rank: i as u64 + 1,
+            })
+            .collect()
+    }
+}
+
+fn number(value: &serde_json::Value) -> Option<f64> {
+    value
+        .as_f64()
+        .or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+}
+
+/// How much of a heavy result to return: `full` records or `compact` summaries
+/// sized for small context windows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputDetail {
+    #[default]
+    Full,
+    Compact,
+}
+
+impl std::str::FromStr for OutputDetail {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "full" => Ok(Self::Full),
+            "compact" => Ok(Self::Compact),
+            other => Err(format!("unknown output detail: {}", other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::HashMap;
+    use std::hash::{BuildHasher, Hasher};
+
+    /// A hasher whose seed picks the iteration order of a `HashMap`, standing in for the
+    /// random seed a separate process run would get.
+    #[derive(Clone, Copy)]
+    struct Seeded(u64);
+
+    impl BuildHasher for Seeded {
+        type Hasher = DefaultHasher;
+
+        fn build_hasher(&self) -> DefaultHasher {
+            let mut hasher = DefaultHasher::new();
+            hasher.write_u64(self.0);
+            hasher
+        }
+    }
+
+    const EXTRA_KEYS: [&str; 12] = [
+        "zeta", "alpha", "validators", "epoch", "tx_pool", "mid", "b", "a", "peers", "uptime", "nested", "version",
+    ];
+
+    fn fixture(seed: u64) -> ChainStats {
+        let mut extra: HashMap<String, Value, Seeded> = HashMap::with_hasher(Seeded(seed));
+        for (i, key) in EXTRA_KEYS.iter().enumerate() {
+            let value = match *key {
+                "nested" => json!({ "z": 1, "a": [{ "y": true, "b": null }], "m": "x" }),
+                _ => json!(i),
+            };
+            extra.insert(key.to_string(), value);
+        }
+        ChainStats {
+            height: 1234,
+            pflops: Some(1.5),
+            burned: None,
+            circulating: Some(1_000_000.25),
+            diff_bits: Some(24),
+            tx_pool_size: Some(3),
+            txs_per_sec: None,
+            extra: extra.into_iter().collect(),
+        }
+    }
+
+    fn insertion_order(seed: u64) -> Vec<String> {
+        let mut map: HashMap<&str, (), Seeded> = HashMap::with_hasher(Seeded(seed));
+        map.extend(EXTRA_KEYS.iter().map(|key| (*key, ())));
+        map.into_keys().map(String::from).collect()
+    }
+
+    #[test]
+    fn balances_from_active_account() {
+        let response = json!({
+            "error": "ok",
+            "balances": [{ "symbol": "AMA", "flat": 2_500_000_000u64, "float": 2.5 }, { "symbol": "USDX", "flat": 7, "float": 0.000000007 }]
+        });
+        let balances = AccountBalance::balances_from(&response).unwrap().unwrap();
+        assert_eq!(balances.len(), 2);
+        assert_eq!((balances[0].symbol.as_str(), balances[0].flat), ("AMA", 2_500_000_000));
+    }
+
+    #[test]
+    fn balances_from_known_empty_account() {
+        let response = json!({ "error": "ok", "balances": [] });
+        assert_eq!(AccountBalance::balances_from(&response).unwrap().map(|b| b.len()), Some(0));
+    }
+
+    #[test]
+    fn balances_from_unknown_account_envelope() {
+        for error in UNKNOWN_ACCOUNT_ERRORS {
+            let response = json!({ "error": error });
+            assert!(AccountBalance::balances_from(&response).unwrap().is_none(), "{}", error);
+        }
+    }
+
+    #[test]
+    fn balances_from_other_envelopes_are_errors() {
+        for response in [
+            json!({ "error": "internal_error" }),
+            json!({ "error": "rate_limited" }),
+            json!({ "error": "invalid_pk" }),
+            json!({ "error": 500 }),
+            json!({ "balances": [] }),
+            json!({ "error": "ok" }),
+            json!({ "error": "ok", "balances": "none" }),
+        ] {
+            let result = AccountBalance::balances_from(&response);
+            assert!(matches!(result, Err(BlockchainError::InvalidResponse(_))), "{}: {:?}", response, result);
+        }
+    }
+
+    #[test]
+    fn seeds_give_different_hash_orders() {
+        // Guards the test below: if every seed iterated alike it would prove nothing
+        let orders: Vec<_> = (0..8).map(insertion_order).collect();
+        assert!(orders.iter().any(|order| order != &orders[0]));
+    }
+
+    #[test]
+    fn chain_stats_serialize_byte_identical_across_hash_seeds() {
+        let compact = serde_json::to_string(&fixture(0)).unwrap();
+        let pretty = serde_json::to_string_pretty(&fixture(0)).unwrap();
+        for seed in 1..32 {
+            assert_eq!(serde_json::to_string(&fixture(seed)).unwrap(), compact, "seed {}", seed);
+            assert_eq!(serde_json::to_string_pretty(&fixture(seed)).unwrap(), pretty, "seed {}", seed);
+        }
+
+        let parsed: Value = serde_json::from_str(&compact).unwrap();
+        let keys: Vec<_> = parsed.as_object().unwrap().keys().cloned().collect();
+        let mut sorted = keys.clone();
+        sorted.sort();
+        assert_eq!(keys, sorted);
+        assert!(pretty.find("\"a\": [").unwrap() < pretty.find("\"m\": \"x\"").unwrap());
+    }
+
+    #[test]
+    fn chain_stats_round_trip_is_byte_identical_whatever_the_node_key_order() {
+        let stats = fixture(0);
+        let mut fields: Vec<(String, Value)> =
+            serde_json::to_value(&stats).unwrap().as_object().unwrap().clone().into_iter().collect();
+        let expected = serde_json::to_string(&stats).unwrap();
+
+        for reversed in [false, true] {
+            if reversed {
+                fields.reverse();
+            }
+            // Writes the object by hand so the text keeps the node's key order
+            let body = fields
+                .iter()
+                .map(|(key, value)| format!("{}:{}", json!(key), value))
+                .collect::<Vec<_>>()
+                .join(",");
+            let parsed: ChainStats = serde_json::from_str(&format!("{{{}}}", body)).unwrap();
+            assert_eq!(serde_json::to_string(&parsed).unwrap(), expected);
+            assert_eq!(parsed.extra.len(), EXTRA_KEYS.len());
+        }
+    }
+
+    fn ranked(scores: &[EpochScore]) -> Vec<(&str, f64, u64)> {
+        scores.iter().map(|s| (s.public_key.as_str(), s.score, s.rank)).collect()
+    }
+
+    #[test]
+    fn epoch_scores_parse_from_every_shape_highest_first() {
+        let expected = vec![("pk_b", 9.0, 1), ("pk_a", 4.5, 2)];
+        for scores in [
+            json!([["pk_a", 4.5], ["pk_b", "9"]]),
+            json!([{ "pk": "pk_a", "score": "4.5" }, { "public_key": "pk_b", "score": 9 }]),
+            json!({ "pk_a": 4.5, "pk_b": 9.0 }),
+        ] {
+            assert_eq!(ranked(&EpochScore::parse_list(&scores).unwrap()), expected, "{}", scores);
+        }
+        assert!(EpochScore::parse_list(&json!([["pk_a", "high"]])).is_none());
+        assert!(EpochScore::parse_list(&json!("pk_a")).is_none());
+    }
+
+    #[test]
+    fn a_score_response_needs_an_ok_envelope() {
+        let scores = EpochScore::from_response(&json!({ "error": "ok", "scores": { "pk_a": 1 } })).unwrap();
+        assert_eq!(ranked(&scores), vec![("pk_a", 1.0, 1)]);
+        for response in [json!({ "error": "not_ready" }), json!({ "scores": { "pk_a": 1 } }), json!({ "error": "ok", "scores": "x" })] {
+            assert!(matches!(EpochScore::from_response(&response), Err(BlockchainError::InvalidResponse(_))), "{}", response);
+        }
+    }
+
+    #[test]
+    fn a_validator_score_is_ranked_against_the_full_list() {
+        let all = EpochScore::parse_list(&json!({ "pk_a": 4.5, "pk_b": 9.0, "pk_c": 1.0 })).unwrap();
+        let bare = EpochScore::for_validator("pk_a", &json!({ "error": "ok", "score": 4.5 }), &all).unwrap();
+        assert_eq!(ranked(&[bare]), vec![("pk_a", 4.5, 2)]);
+        let listed = EpochScore::for_validator("pk_c", &json!({ "scores": [["pk_c", "1"]] }), &all).unwrap();
+        assert_eq!(ranked(&[listed]), vec![("pk_c", 1.0, 3)]);
+    }
+
+    #[test]
+    fn a_key_without_a_score_is_not_a_validator() {
+        let all = EpochScore::parse_list(&json!({ "pk_a": 4.5 })).unwrap();
+        for response in [json!({ "error": "not_found" }), json!({ "error": "ok" }), json!({}), json!({ "scores": [] })] {
+            match EpochScore::for_validator("pk_z", &response, &all) {
+                Err(BlockchainError::ValidationFailed(message)) => {
+                    assert_eq!(message, "pk_z has no score in the current epoch")
+                }
+                other => panic!("{}: {:?}", response, other),
+            }
+        }
+    }
+
+    #[test]
+    fn numeric_query_arguments_accept_strings() {
+        let block: BlockWithTxsQuery = serde_json::from_value(json!({ "height": "20", "tx_offset": "5", "tx_limit": 7 })).unwrap();
+        assert_eq!((block.height, block.tx_offset, block.tx_limit), (20, Some(5), Some(7)));
+        let richlist: RichlistQuery = serde_json::from_value(json!({ "limit": "20", "offset": " 40 " })).unwrap();
+        assert_eq!((richlist.limit, richlist.offset), (Some(20), Some(40)));
+        let built: BuiltTransactionsQuery = serde_json::from_value(json!({ "signer": "S", "limit": "25" })).unwrap();
+        assert_eq!((built.signer.as_deref(), built.limit), (Some("S"), Some(25)));
+
+        assert!(serde_json::from_value::<BlockWithTxsQuery>(json!({ "height": "twenty" })).is_err());
+        assert!(serde_json::from_value::<BlockWithTxsQuery>(json!({})).is_err());
+        assert!(serde_json::from_value::<RichlistQuery>(json!({ "limit": "-1" })).is_err());
+        assert!(serde_json::from_value::<BuiltTransactionsQuery>(json!({ "signer": "S", "limit": "lots" })).is_err());
+    }
 }
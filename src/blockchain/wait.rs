@@ -0,0 +1,43 @@
+//! Waiting for a submitted transaction to show up on the node. The polling loop lives in
+//! the stdio server, which owns the cancellation token; this holds the limits, what a
+//! failed poll means and the result shapes.
+
+use super::error::BlockchainError;
+use super::recovery::{self, Recovery};
+use super::types::Transaction;
+use serde::Serialize;
+
+pub const DEFAULT_WAIT_TIMEOUT_SECS: u64 = 60;
+pub const MAX_WAIT_TIMEOUT_SECS: u64 = 300;
+pub const DEFAULT_WAIT_POLL_SECS: u64 = 2;
+pub const MAX_WAIT_POLL_SECS: u64 = 60;
+
+/// Whether a poll that failed with `error` leaves the transaction worth polling for: it is
+/// not known yet, or the node is briefly failing. Anything else will not change by waiting.
+pub fn keeps_polling(error: &BlockchainError) -> bool {
+    matches!(error, BlockchainError::NotFound { .. })
+        || matches!(recovery::for_error(error), Recovery::RetryAfter { .. } | Recovery::RetryLater { .. })
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum WaitOutcome {
+    Found {
+        polls: u32,
+        elapsed_ms: u64,
+        transaction: Box<Transaction>,
+    },
+    /// The timeout passed without the node knowing the transaction
+    Pending {
+        polls: u32,
+        elapsed_ms: u64,
+        timeout_secs: u64,
+        /// The last poll's failure when it was not a plain "not found yet"
+        #[serde(skip_serializing_if = "Option::is_none")]
+        last_error: Option<String>,
+        hint: &'static str,
+    },
+}
+
+pub const PENDING_HINT: &str =
+    "not included yet; call wait_for_transaction again, or rebuild with create_transaction if the blob may have expired";
@@ -0,0 +1,1097 @@
+//! Tool catalog metadata shared by the native server and the worker.
+
+use crate::blockchain::symbol::edit_distance;
+use crate::blockchain::limits::QueryLimits;
+use crate::blockchain::server_key::{SERVER_KEY_TRANSFER_TOOL, SERVER_KEY_VAR};
+use crate::config::{RedactMode, ServerConfig};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+
+/// Bumped whenever a tool is added or removed, or an argument or result shape changes.
+/// Keep in step with the last `CHANGELOG` entry.
+pub const TOOLS_VERSION: u64 = 72;
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct CatalogChange {
+    pub version: u64,
+    pub summary: &'static str,
+}
+
+pub const CHANGELOG: &[CatalogChange] = &[
+    CatalogChange { version: 1, summary: "initial catalog" },
+    CatalogChange { version: 2, summary: "detail=full|compact on get_block_by_height, get_transaction_history, get_txs_in_entry" },
+    CatalogChange { version: 3, summary: "get_validators returns {pk, score, rank} entries; keys_only argument" },
+    CatalogChange { version: 4, summary: "test_signing_flow added" },
+    CatalogChange { version: 5, summary: "get_richlist limit, offset and around_address arguments" },
+    CatalogChange { version: 6, summary: "get_emission_stats added" },
+    CatalogChange { version: 7, summary: "numeric arguments accept numeric strings and whole floats" },
+    CatalogChange { version: 8, summary: "get_capabilities added" },
+    CatalogChange { version: 9, summary: "raw_node_query added (only with MCP_RAW_QUERY_ALLOW)" },
+    CatalogChange { version: 10, summary: "rebuild_and_compare added" },
+    CatalogChange { version: 11, summary: "diff_contract_state added (stdio only)" },
+    CatalogChange { version: 12, summary: "investigate_addresses added (stdio only)" },
+    CatalogChange { version: 13, summary: "submit_transaction idempotency_key argument" },
+    CatalogChange { version: 14, summary: "estimate_finality_time added (stdio only)" },
+    CatalogChange { version: 15, summary: "example arguments in tool descriptions (stdio), tools/list examples (worker) and tool docs" },
+    CatalogChange { version: 16, summary: "trace_funds added (stdio only)" },
+    CatalogChange { version: 17, summary: "create_watch, list_watches and delete_watch added (worker only)" },
+    CatalogChange { version: 18, summary: "check_blob_freshness added; create_transaction returns nonce, nonce_basis and built_at" },
+    CatalogChange { version: 19, summary: "timing block (duration_ms, upstream_requests, retries, cache_hits) on tool outputs" },
+    CatalogChange { version: 20, summary: "get_signer_distribution added (stdio only)" },
+    CatalogChange { version: 21, summary: "preview_contract_call added" },
+    CatalogChange { version: 22, summary: "claim_testnet_ama takes an optional symbol" },
+    CatalogChange { version: 23, summary: "get_transaction and full get_transaction_history add decoded_action to contract calls" },
+    CatalogChange { version: 24, summary: "correlate_validator_nodes added" },
+    CatalogChange { version: 25, summary: "create_transaction and claim_testnet_ama report clock_skew_warning; nonce_basis may be 'chain'" },
+    CatalogChange { version: 26, summary: "get_capabilities returns a features map with the reason each disabled feature is off" },
+    CatalogChange { version: 27, summary: "list_tx_templates added; create_transaction checks calls that match a template" },
+    CatalogChange { version: 28, summary: "get_liveness added (stdio only)" },
+    CatalogChange { version: 29, summary: "get_signing_public_key added; results carry a signature block when MCP_RESPONSE_SIGNING_SK is set (stdio only)" },
+    CatalogChange { version: 30, summary: "export_contract_state added" },
+    CatalogChange { version: 31, summary: "list_built_transactions added; get_transaction and get_transaction_history mark transactions built by this server" },
+    CatalogChange { version: 32, summary: "get_capabilities reports queryLimits; arguments above them fail with limit_exceeded" },
+    CatalogChange { version: 33, summary: "add_address_label, export_address_labels and import_address_labels added; outputs label known addresses and investigate_addresses reports frequent_counterparties" },
+    CatalogChange { version: 34, summary: "get_entry_tip added to the stdio server" },
+    CatalogChange { version: 35, summary: "get_richlist added to the stdio server; entries carry rank, address, flat and float on both servers" },
+    CatalogChange { version: 36, summary: "get_nodes added to the stdio server; both servers return count, total and nodes and accept a filter" },
+    CatalogChange { version: 37, summary: "get_epoch_score added to the stdio server; both servers return ranked {public_key, score, rank} entries" },
+    CatalogChange { version: 38, summary: "get_emission_address added to the stdio server; both servers report a validator without one as configured: false" },
+    CatalogChange { version: 39, summary: "get_block_with_txs added to the stdio server; entries page their transactions with tx_offset and tx_limit" },
+    CatalogChange { version: 40, summary: "get_txs_in_entry added to the stdio server; both servers return typed transactions and fail unknown entry hashes with not_found" },
+    CatalogChange { version: 41, summary: "get_entry_by_hash added to the stdio server, also as the amadeus://entry/{hash} resource; both servers return a typed entry" },
+    CatalogChange { version: 42, summary: "get_removed_validators added to the stdio server; both servers flag removed keys still in the validator list" },
+    CatalogChange { version: 43, summary: "get_block_range added to the stdio server" },
+    CatalogChange { version: 44, summary: "wait_for_transaction added to the stdio server; get_transaction fails unknown hashes with not_found" },
+    CatalogChange { version: 45, summary: "get_account_nonce added" },
+    CatalogChange { version: 46, summary: "decode_transaction added" },
+    CatalogChange { version: 47, summary: "verify_signature added" },
+    CatalogChange { version: 48, summary: "get_pending_transactions added" },
+    CatalogChange { version: 49, summary: "get_token_info added" },
+    CatalogChange { version: 50, summary: "list_contract_keys added" },
+    CatalogChange { version: 51, summary: "get_epoch_info added (stdio only)" },
+    CatalogChange { version: 52, summary: "get_validator_details added (stdio only)" },
+    CatalogChange { version: 53, summary: "get_transaction_history filters: direction, symbol, counterparty, after_timestamp, before_timestamp" },
+    CatalogChange { version: 54, summary: "get_full_transaction_history added; queryLimits gains max_full_history" },
+    CatalogChange { version: 55, summary: "get_blocks_by_signer added (stdio only)" },
+    CatalogChange { version: 56, summary: "transfer_from_server_key added (stdio only, requires AMADEUS_MCP_SIGNING_KEY)" },
+    CatalogChange { version: 57, summary: "validate_transfer added; create_transaction runs its checks on Coin transfers" },
+    CatalogChange { version: 58, summary: "validate_address added; get_account_balance accepts hex and whitespace in addresses" },
+    CatalogChange { version: 59, summary: "check_node_health added" },
+    CatalogChange { version: 60, summary: "get_recent_blocks added (stdio only)" },
+    CatalogChange { version: 61, summary: "get_block_by_timestamp added; queryLimits gains max_time_probes" },
+    CatalogChange { version: 62, summary: "analyze_account added" },
+    CatalogChange { version: 63, summary: "get_account_balance takes an optional symbol" },
+    CatalogChange { version: 64, summary: "estimate_fee added; create_transaction returns estimated_fee" },
+    CatalogChange { version: 65, summary: "detail=full|compact on get_block_with_txs and get_richlist" },
+    CatalogChange { version: 66, summary: "test_signing_flow: secret_key optional, generated with MCP_KEY_TOOLS=1; report carries generated_key" },
+    CatalogChange { version: 67, summary: "get_emission_stats: since as an alternative to blocks; blocks optional; output reports direct as unavailable" },
+    CatalogChange { version: 68, summary: "get_block_by_timestamp steps around heights without a timestamp and lists them in skipped" },
+    CatalogChange { version: 69, summary: "submit_transaction: rejections carry rejection {kind, raw} and hint, in the submission_failed error data and the worker result" },
+    CatalogChange { version: 70, summary: "get_account_balance: only the node's unknown-account answer is exists=false; other node errors fail the call" },
+    CatalogChange { version: 71, summary: "stdio server: object outputs of mainnet queries carry as_of_height, the tip height the answer is consistent with" },
+    CatalogChange { version: 72, summary: "errors carry a recovery hint {action, ...} in their data; vocabulary at amadeus://docs/error-recovery" },
+];
+
+/// Resource URI prefix for long-form tool docs; append the tool name.
+pub const TOOL_DOCS_URI: &str = "amadeus://docs/tools/";
+/// Resource URI of the create → sign → submit walkthrough.
+pub const SIGNING_WORKFLOW_URI: &str = "amadeus://docs/signing-workflow";
+/// Resource URI of the error recovery hint vocabulary.
+pub const ERROR_RECOVERY_URI: &str = "amadeus://docs/error-recovery";
+
+/// `tools/list` page size when the client does not ask for one.
+pub const DEFAULT_PAGE_SIZE: usize = 20;
+pub const MAX_PAGE_SIZE: usize = 100;
+
+/// Short description for `tools/list` plus the long prose served at `TOOL_DOCS_URI`.
+#[derive(Debug, Clone, Copy)]
+pub struct ToolDoc {
+    pub name: &'static str,
+    pub summary: &'static str,
+    pub doc: &'static str,
+    /// Canonical argument objects, as JSON; every tool has at least one
+    pub examples: &'static [&'static str],
+}
+
+impl ToolDoc {
+    /// The example argument objects, parsed.
+    pub fn examples(&self) -> Vec<serde_json::Value> {
+        self.examples.iter().filter_map(|e| serde_json::from_str(e).ok()).collect()
+    }
+
+    /// The long-form doc with its examples, as served at `TOOL_DOCS_URI`.
+    pub fn render(&self) -> String {
+        let mut text = format!("{}\n\nExample arguments:", self.doc);
+        for example in self.examples {
+            text.push_str("\n  ");
+            text.push_str(example);
+        }
+        text
+    }
+}
+
+pub const TOOL_DOCS: &[ToolDoc] = &[
+    ToolDoc {
+        name: "get_capabilities",
+        summary: "Returns the tool catalog version (toolsVersion), the exposed tools, the deployment's optional features and a changelog of catalog changes",
+        doc: "Returns the tool catalog version (toolsVersion), the tools exposed by this deployment, its optional features and a changelog of catalog changes. features maps each optional feature (submit_transaction, raw_node_query, server_key_transfer, faucet, watches, address_labels, offline, tip_follower, disk_cache, address_redaction, submit_confirmation, strict_args, timing, session_budget) to {enabled, reason}; reason says why a disabled feature is off, e.g. read-only mode, a missing setting or a feature the other server type provides. queryLimits holds the largest arguments this server accepts (max_history_limit, max_block_span, max_batch_calls, max_contract_keys, max_richlist_limit, max_full_history, max_time_probes); a call above one fails with limit_exceeded. The same map is included in the initialize instructions. Clients that cache tool schemas should re-fetch them when toolsVersion differs from the cached one.",
+        examples: &[r#"{}"#],
+    },
+    ToolDoc {
+        name: "list_tx_templates",
+        summary: "Lists transaction templates: contract, function and ordered, typed arguments of common calls, with a fill-in example",
+        doc: "Lists named templates for common transactions (transfer, set_emission_address). Each gives the contract and function, the arguments in order with their type (address, amount or symbol), a description and whether they are required, and an example of create_transaction arguments with placeholders to fill in. Each template is also served as the resource amadeus://templates/{name}. create_transaction and rebuild_and_compare check calls to a templated function against its template and fail naming the argument that does not fit, e.g. a recipient passed as a plain string instead of {b58}.",
+        examples: &[r#"{}"#],
+    },
+    ToolDoc {
+        name: "create_transaction",
+        summary: "Creates unsigned transaction for any contract call",
+        doc: "Creates an unsigned transaction for any contract call. Takes the signer public key (base58), contract name, function name and arguments; arguments may be plain strings, numbers, or {b58}, {hex} and {utf8} objects. attached_symbol and attached_amount attach tokens to the call. Returns a base58 blob and a hex signing_payload: sign the payload with the signer's BLS12-381 key and the Amadeus transaction DST, then call submit_transaction. With an explicit nonce the blob is reproducible. The output also carries the nonce (decimal string), nonce_basis ('clock', 'explicit' or 'chain') and built_at (unix seconds), for checking later whether the blob went stale. When the server clock is off from the chain tip's timestamp by more than the configured threshold, a nonce left to the clock is derived from the signer's latest nonce and the chain's time instead (nonce_basis 'chain') and clock_skew_warning explains why. When the signer does not hold the transferred or attached symbol but holds a near match (other case, or one letter off, e.g. 'ama' for 'AMA'), the call fails naming the likely symbol and the signer's holdings. A Coin transfer is first run through the validate_transfer checks with the amount taken as atoms; an amount above the signer's balance fails with insufficient_balance. estimated_fee is the estimate_fee result for the blob.",
+        examples: &[r#"{"signer":"29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw","contract":"Coin","function":"transfer","args":[{"b58":"42KfUgyU2XHCPwDD6g9gryCvtj9vqJAvM6h3Yzs3VRbuyT2JCHwvuk5iAbzXrioerf"},"1000000000","AMA"]}"#, r#"{"signer":"29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw","contract":"Coin","function":"transfer","args":[{"b58":"42KfUgyU2XHCPwDD6g9gryCvtj9vqJAvM6h3Yzs3VRbuyT2JCHwvuk5iAbzXrioerf"},"2500000000","AMA"],"nonce":1718000000000000000}"#],
+    },
+    ToolDoc {
+        name: "validate_address",
+        summary: "Checks an address and returns its base58, hex and short forms",
+        doc: "Diagnoses a pasted address locally, without a node call. The input may be base58 or hex, with or without a 0x prefix; whitespace anywhere is ignored. It must decode to 48 bytes that are a compressed BLS12-381 G1 point in the prime-order subgroup, which is what an account public key is. Returns valid, verdict ('valid', or the first failed check: empty, bad_encoding, wrong_length, not_on_curve, not_in_subgroup, infinity), detail naming the problem (e.g. the offending character and its position), the encoding it decoded from, and, whenever the input decodes to 48 bytes, the canonical base58, hex and a shortened display form. normalized is true when the input was not already canonical base58; pass the base58 form to other tools. get_account_balance, validate_transfer and claim_testnet_ama parse addresses the same way; of them only claim_testnet_ama also requires the point checks.",
+        examples: &[r#"{"address":"7gifEnyJbAwSa1Hb94ToW7rPyA5pJH4E74Dkchkvhan1RStQz9w4cj2NFBLoTZx5c2"}"#, r#"{"address":"0xb63be9538e27ecd476bb75fdd5ce5f0016f1b42872f4bfda0eb4c05ad54bf2f8f2c6807ca7f18782403aed15e86a6857"}"#],
+    },
+    ToolDoc {
+        name: "estimate_fee",
+        summary: "Estimates a transaction's fee from its signed size, before signing",
+        doc: "Works out what a transaction will cost without a node, so the cost is known before signing. Give blob (base58, unsigned from create_transaction or already signed), or source, destination, amount and optionally symbol (default AMA) to draft a Coin transfer; blob wins when both are given. A transfer amount that is an integer is atoms, a decimal such as \"1.5\" is whole tokens converted with the token's decimals; the draft takes a clock nonce, as create_transaction would. The fee follows the protocol's byte rule on the signed transaction (the blob plus its 32-byte hash and 96-byte signature): 0.01 AMA, plus 0.01 AMA per full 1024 signed bytes. Nodes quote no fees, so basis is always 'byte_rule'; the receipt's exec_used is what was actually charged. Returns {kind, tx_bytes, signed_bytes, fee_atoms, fee, basis}, with kind 'unsigned' or 'signed' for a blob. A blob that does not decode or a draft with a bad address or amount fails with invalid_transaction.",
+        examples: &[r#"{"source":"29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw","destination":"42KfUgyU2XHCPwDD6g9gryCvtj9vqJAvM6h3Yzs3VRbuyT2JCHwvuk5iAbzXrioerf","amount":"1.5"}"#, r#"{"blob":"<blob from create_transaction>"}"#],
+    },
+    ToolDoc {
+        name: "validate_transfer",
+        summary: "Dry-runs a Coin transfer and reports each check that passed or failed",
+        doc: "Checks a transfer before it is built, so a bad address or an amount above the balance shows up here rather than as a node error at submit time. Checks, in order: source_address and destination_address (base58 48-byte public keys), symbol (set; default AMA), amount (an integer is atoms, a decimal such as \"1.5\" is whole tokens converted with the token's decimals, and it must be above zero), then symbol_held and balance against the source's balances. Returns {valid, source, destination, symbol, amount_atoms, available_atoms, checks: [{check, status, detail}]} with status 'passed', 'failed' or 'skipped'; the balance check is skipped when the balances cannot be read and does not make the transfer invalid. Nothing is built or signed. create_transaction runs the same checks on Coin transfers. Optional network parameter: 'mainnet' (default) or 'testnet'.",
+        examples: &[r#"{"source":"29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw","destination":"42KfUgyU2XHCPwDD6g9gryCvtj9vqJAvM6h3Yzs3VRbuyT2JCHwvuk5iAbzXrioerf","amount":"1.5"}"#, r#"{"source":"29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw","destination":"42KfUgyU2XHCPwDD6g9gryCvtj9vqJAvM6h3Yzs3VRbuyT2JCHwvuk5iAbzXrioerf","amount":1000000000,"symbol":"AMA","network":"testnet"}"#],
+    },
+    ToolDoc {
+        name: "preview_contract_call",
+        summary: "Runs a contract call read-only on the node and returns the result or revert reason",
+        doc: "Executes a contract call on the node without building or signing a transaction, through the node's read-only call endpoint (POST {api}/contract/view/{contract}/{function}). Arguments use the same conventions as create_transaction (plain strings, numbers, {b58}, {hex}, {utf8}) and are encoded to the same bytes, so the preview runs what would be submitted. Returns status 'ok' with result, or 'reverted' with reason. Nodes without the endpoint are detected by the startup probe: the tool is marked unavailable in tools/list and fails with endpoint_unsupported.",
+        examples: &[r#"{"contract":"Coin","function":"balance","args":[{"b58":"29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw"},"AMA"]}"#],
+    },
+    ToolDoc {
+        name: "check_blob_freshness",
+        summary: "Checks whether an unsigned blob's nonce is still usable",
+        doc: "Decodes an unsigned blob and compares its nonce with the signer's latest nonce on chain and with the clock. Status is 'fresh', 'consumed' (the signer already used this nonce or a higher one, so the node will reject the blob; rebuild and sign again) or 'gapped' (the nonce is far ahead of the clock). For blobs built by the same stdio server, origin is 'this_server' with built_at and age_secs; otherwise origin is 'unknown'. The stdio server's submit_transaction runs this check on blobs it built, refuses consumed ones with stale_blob and adds freshness_warning to the result otherwise. Optional network parameter: 'mainnet' (default) or 'testnet'.",
+        examples: &[r#"{"transaction":"<blob from create_transaction>"}"#, r#"{"transaction":"<blob from create_transaction>","network":"testnet"}"#],
+    },
+    ToolDoc {
+        name: "decode_transaction",
+        summary: "Unpacks a base58 transaction blob locally: signer, nonce, call, arguments and signing hash",
+        doc: "Decodes a base58 vecpak blob without contacting a node, to check what a blob does before signing or submitting it. kind is 'unsigned' for a blob from create_transaction (Tx) or 'signed' for a finalized transaction (TxU). Returns the signer (base58), nonce (decimal string), op, contract, function, args, attached_symbol and attached_amount, the signing_hash (hex SHA-256 of the unsigned transaction, what the signer signs) and the same hash in base58 as transaction_hash. Each argument is {encoding, value}: the text when its bytes are valid UTF-8, hex otherwise. A signed blob also returns its signature and hash_matches, whether the hash it carries is the one computed here. A blob that is not base58 or not a transaction fails with invalid_blob.",
+        examples: &[r#"{"blob":"<blob from create_transaction>"}"#],
+    },
+    ToolDoc {
+        name: "verify_signature",
+        summary: "Checks a BLS signature over a transaction signing hash before submitting it",
+        doc: "Verifies that a signature from an external signer matches a signing payload and public key, using the Amadeus transaction DST (AMADEUS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_TX_), without contacting a node. signing_payload is the 32-byte hash as create_transaction returns it, hex signing_payload or base58 transaction_hash; signature and public_key are base58. Returns valid and, when false, failed_check: 'bad_payload_encoding', 'bad_public_key', 'bad_signature_encoding', 'signature_not_in_subgroup' or 'signature_mismatch', with a detail. A mismatch names the likely mistake when the signer used a generic BLS DST or signed the hex string instead of the raw bytes.",
+        examples: &[r#"{"signing_payload":"<signing_payload from create_transaction>","signature":"<base58 signature>","public_key":"29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw"}"#],
+    },
+    ToolDoc {
+        name: "transfer_from_server_key",
+        summary: "Signs and submits a Coin.transfer from the key this server holds",
+        doc: "Spends from the server's own key, not the caller's: builds a Coin.transfer of amount atoms of symbol (default AMA) to destination, signs it with the BLS key in AMADEUS_MCP_SIGNING_KEY (base58, 64 bytes) and broadcasts it. Returns status, tx_hash, from (the server key's public key), destination, amount, symbol and nonce. The tool is only listed when the key is set, and hidden in read-only mode like submit_transaction. The nonce comes from the clock, or from the chain with a clock_skew_warning when the clock is off from the mainnet tip, as in create_transaction. With MCP_ELICIT_CONFIRM the user is asked to confirm the transfer first. Rejections fail with submission_failed and the same hint and recovery as submit_transaction. The key never appears in outputs, logs or errors. Stdio only; meant for automation such as CI bots funding test accounts or monitoring canaries. Optional network parameter: 'mainnet' (default) or 'testnet'.",
+        examples: &[r#"{"destination":"42KfUgyU2XHCPwDD6g9gryCvtj9vqJAvM6h3Yzs3VRbuyT2JCHwvuk5iAbzXrioerf","amount":1000000000,"network":"testnet"}"#],
+    },
+    ToolDoc {
+        name: "rebuild_and_compare",
+        summary: "Rebuilds an unsigned transaction from its create_transaction request (nonce required) and compares it byte for byte with a blob",
+        doc: "Rebuilds an unsigned transaction from the original create_transaction request and compares it byte for byte with a blob, for example one built on an air-gapped machine. The request must carry the nonce the blob was built with. Reports equality, the first differing byte offset, both lengths and the rebuilt blob and hash.",
+        examples: &[r#"{"request":{"signer":"29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw","contract":"Coin","function":"transfer","args":[{"b58":"42KfUgyU2XHCPwDD6g9gryCvtj9vqJAvM6h3Yzs3VRbuyT2JCHwvuk5iAbzXrioerf"},"1000000000","AMA"],"nonce":1718000000000000000},"blob":"<blob from create_transaction>"}"#],
+    },
+    ToolDoc {
+        name: "submit_transaction",
+        summary: "Submits a signed transaction to the blockchain network",
+        doc: "Submits a signed transaction to the network. Takes the blob from create_transaction and the base58 signature over its signing_payload. Rejections are classified (bad_signature, insufficient_funds, nonce_conflict, too_large, expired) and come with a hint on what to change before retrying. Pass an idempotency_key to make retries safe: a repeated submit with the same key returns the first accepted result with replayed=true instead of broadcasting again, while a failed or rejected submit is attempted again, and reusing the key for a different blob fails with idempotency_key_reused. Keys are scoped to the MCP session (or client address) and remembered for 24 hours. On the stdio server, clients that support elicitation are asked to confirm a summary of the decoded transaction first; declining, cancelling or not answering within two minutes fails with submission_not_confirmed and nothing is broadcast. Optional network parameter: 'mainnet' (default) or 'testnet'.",
+        examples: &[r#"{"transaction":"<blob from create_transaction>","signature":"<base58 BLS signature over signing_payload>","idempotency_key":"transfer-2024-06-10-001"}"#],
+    },
+    ToolDoc {
+        name: "get_account_balance",
+        summary: "Queries the balance of an account across all supported assets",
+        doc: "Queries the balance of an account across all supported assets. The address may be base58 or hex with stray whitespace, as validate_address accepts; anything that does not decode to 48 bytes fails with invalid_address naming the failed check. Well-formed addresses the chain has never seen return empty balances with exists=false; any other error from the node fails the call rather than reading as a missing account. With symbol set (e.g. 'AMA'), only that token is read, through the node's per-symbol balance endpoint instead of balance_all; balances then holds that one {symbol, flat, float}, zero when the account does not hold it, and a symbol that is not alphanumeric fails validation. Optional network parameter: 'mainnet' (default) or 'testnet'.",
+        examples: &[r#"{"address":"29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw"}"#, r#"{"address":"29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw","symbol":"AMA"}"#, r#"{"address":"29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw","network":"testnet"}"#],
+    },
+    ToolDoc {
+        name: "check_node_health",
+        summary: "Reports whether the node answers, how fast, and whether it keeps up with a reference node",
+        doc: "Checks the configured node before its answers are trusted. Times one chain/stats call (latency_ms, retries included) and reports the node's height and tx_pool_size. On mainnet, when BLOCKCHAIN_FALLBACK_URLS is set, the first fallback node is read as a reference: height_lag is its height minus the node's (negative when the node is ahead) and stale is true above 10 entries. An unreachable node gives reachable=false with error instead of failing the call; a failing reference gives reference_error. The stdio server runs the same check once at startup and logs a warning when the node is unreachable or stale. Optional network parameter: 'mainnet' (default) or 'testnet' (no reference).",
+        examples: &[r#"{}"#, r#"{"network":"testnet"}"#],
+    },
+    ToolDoc {
+        name: "get_chain_stats",
+        summary: "Retrieves current blockchain statistics",
+        doc: "Retrieves current blockchain statistics including height, total transactions, total accounts, circulating supply and burned amount. Optional network parameter: 'mainnet' (default) or 'testnet'.",
+        examples: &[r#"{}"#],
+    },
+    ToolDoc {
+        name: "get_emission_stats",
+        summary: "Reports AMA emitted and burned over the last `blocks` entries or since a moment by diffing chain stats snapshots, with methodology and confidence",
+        doc: "Reports AMA emitted and burned over the last `blocks` entries by diffing chain stats snapshots taken at the window edges (emitted = change in circulating supply + change in burned). Instead of blocks, since (unix seconds or RFC3339) starts the window at the height get_block_by_timestamp places that moment at, and is echoed back; give one of the two. The worker diffs the snapshots its scheduled trigger records, the stdio server those its tip follower records. States methodology and confidence; direct reports that summing per-entry emission is unavailable, as nodes expose no per-entry figures. Returns insufficient_historical_data when no earlier snapshot exists. Optional network parameter: 'mainnet' (default) or 'testnet'.",
+        examples: &[r#"{"blocks":1000}"#, r#"{"since":"2024-06-01T00:00:00Z"}"#],
+    },
+    ToolDoc {
+        name: "get_block_by_height",
+        summary: "Retrieves blockchain entries at a specific height",
+        doc: "Retrieves all blockchain entries at a specific height. Optional detail parameter: 'full' or 'compact' (abbreviated entries). Optional network parameter: 'mainnet' (default) or 'testnet'.",
+        examples: &[r#"{"height":1250000}"#, r#"{"height":1250000,"detail":"compact"}"#],
+    },
+    ToolDoc {
+        name: "get_block_by_timestamp",
+        summary: "Finds the entries just before and after a moment, by binary search over heights",
+        doc: "Answers \"which entry was produced around this time\". timestamp is unix seconds or RFC3339 with an offset (e.g. '2024-06-01T12:00:00Z' or '2024-06-01T14:00:00+02:00'); fractional seconds are dropped. Reads the tip height from chain stats, probes the genesis entry and the tip entry, then halves the bracket around the target until adjacent heights remain, using each height's first entry timestamp. A height that is empty or has no timestamp is stepped around rather than failing the search: the next height up from genesis or down from the tip, or the nearest unprobed neighbour of the middle; such heights are listed in skipped and count against the budget. Each probe is one entry fetch, at most max_probes (default and max: the server's max_time_probes, 40 unless MCP_MAX_TIME_PROBES or the small preset changes it); a search of the whole chain needs about log2(tip height) + 2. Returns target, position, below (latest entry at or before the target), above (earliest entry after it), probes and converged, false when the budget ran out before the bracket closed. below and above are {height, hash, timestamp, timestamp_ms, offset_secs}. A target before genesis returns the genesis entry as above with position before_genesis; one after the tip returns the tip entry as below with position after_tip, or future when it is also later than now; note explains these. Optional network parameter: 'mainnet' (default) or 'testnet'.",
+        examples: &[r#"{"timestamp":"2024-06-01T12:00:00Z"}"#, r#"{"timestamp":1717243200,"network":"testnet"}"#],
+    },
+    ToolDoc {
+        name: "get_account_nonce",
+        summary: "Latest nonce of an account and the next one to use",
+        doc: "Returns {address, nonce, has_transacted, next_nonce} for building transactions outside this server. nonce is the highest nonce the account signed with among its newest transactions, '0' when it never sent one; next_nonce is above it and no earlier than the server's clock, in nanoseconds like create_transaction's clock nonces. Both are strings because they do not fit a JSON number exactly. Pass next_nonce as create_transaction's nonce for a reproducible blob. An address that is not base58 48 bytes fails with invalid_address. Optional network parameter: 'mainnet' (default) or 'testnet'.",
+        examples: &[r#"{"address":"29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw"}"#],
+    },
+    ToolDoc {
+        name: "get_pending_transactions",
+        summary: "Transactions waiting in the node's pool, optionally only those involving an address",
+        doc: "Lists the transactions the node holds but has not included in an entry yet, to answer whether a submitted transfer is stuck. Each has its hash, signature, tx (signer, nonce, action) and decoded_action for contract calls. With address, only transactions it signed or receives a Coin transfer in are returned; pool_size still counts the whole pool. An empty pool is an empty list, not an error. Pool items the server cannot read are returned raw under undecoded. A node without the pool endpoint fails with endpoint_unsupported and the message 'node does not expose tx pool'. Optional network parameter: 'mainnet' (default) or 'testnet'.",
+        examples: &[r#"{}"#, r#"{"address":"29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw"}"#],
+    },
+    ToolDoc {
+        name: "get_transaction",
+        summary: "Retrieves a specific transaction by its hash",
+        doc: "Retrieves a specific transaction by its hash, with signer, action, arguments and the entry it was included in. Contract calls also carry decoded_action: contract, function, each argument tagged as integer, address, base58, utf8 or raw, and the attached symbol and amount; parts that cannot be decoded are shown raw and explained in decode_error. A hash the node does not know fails with not_found; to wait for a just-submitted transaction use wait_for_transaction. Optional network parameter: 'mainnet' (default) or 'testnet'.",
+        examples: &[r#"{"tx_hash":"2qo2mC7GvBBEPZTmZeYXsc5KhzboKSen6DekBytar1fy"}"#],
+    },
+    ToolDoc {
+        name: "wait_for_transaction",
+        summary: "Poll until a transaction is known to the node, or time out",
+        doc: "Polls get_transaction for tx_hash every poll_interval_secs (default 2, max 60) until the node knows the transaction or timeout_secs (default 60, max 300) pass. Returns {status: 'found', polls, elapsed_ms, transaction}, or {status: 'pending', polls, elapsed_ms, timeout_secs, hint} on timeout, which is not an error; last_error is added when the last poll failed for a reason other than the transaction not being known yet, such as the node being unreachable. Errors that waiting cannot fix end the call at once. Cancelling the call stops the polling. Stdio only.",
+        examples: &[r#"{"tx_hash":"2qo2mC7GvBBEPZTmZeYXsc5KhzboKSen6DekBytar1fy"}"#, r#"{"tx_hash":"2qo2mC7GvBBEPZTmZeYXsc5KhzboKSen6DekBytar1fy","timeout_secs":120,"poll_interval_secs":5}"#],
+    },
+    ToolDoc {
+        name: "get_full_transaction_history",
+        summary: "Collects an account's whole transaction history for exports",
+        doc: "Collects an account's whole transaction history, paging oldest first with page_size transactions a page (default 100, at most the server's max_history_limit) until the node returns a short page or max_transactions transactions are collected. max_transactions defaults to and may not exceed the server's max_full_history limit (10000 unless MCP_MAX_FULL_HISTORY or the small preset lowers it). Pages are fetched one after another and transactions are deduplicated by hash, since a page can shift when transactions arrive mid-fetch; duplicates counts the rows dropped. Returns {address, count, pages, duplicates, complete, max_transactions, first_height, last_height, first_timestamp, last_timestamp, transactions}, with the timestamps in unix seconds of the oldest and newest transactions' entries; complete is false when the cap stopped the fetch. The stdio server sends a progress notification after each page when the request has a progress token and stops fetching as soon as the call is cancelled. Optional network parameter: 'mainnet' (default) or 'testnet'.",
+        examples: &[r#"{"address":"29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw"}"#, r#"{"address":"29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw","page_size":50,"max_transactions":2000}"#],
+    },
+    ToolDoc {
+        name: "analyze_account",
+        summary: "Totals, top counterparties, fees and activity span of an account",
+        doc: "Collects the account's history as get_full_transaction_history does (same page_size and max_transactions, and the same progress notifications and cancellation on stdio) and summarizes it instead of returning it. totals has, per symbol, the atoms sent and received (decimal strings) and transfers_out and transfers_in. top_by_count ranks the 10 counterparties with the most Coin transfers, with sent and received counts; top_by_volume ranks, per symbol, the 10 with the most atoms moved either way, since amounts of different symbols do not compare. fees sums the receipts' exec_used over the transactions the address signed. Failed transactions and transfers to itself are counted in failed and self_transfers and left out of the totals. first_height, last_height, first_timestamp and last_timestamp (unix seconds) span the history; complete is false when max_transactions cut it short, so the statistics cover only the oldest transactions. The address may be base58 or hex; one that is neither fails with invalid_address. Optional network parameter: 'mainnet' (default) or 'testnet'.",
+        examples: &[r#"{"address":"29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw"}"#, r#"{"address":"29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw","max_transactions":2000,"network":"testnet"}"#],
+    },
+    ToolDoc {
+        name: "get_transaction_history",
+        summary: "Retrieves transaction history for a specific account",
+        doc: "Retrieves transaction history for a specific account. Supports pagination with limit, offset and sort (asc or desc). Optional detail parameter: 'full' or 'compact' (short hashes, only from/to/amount/symbol/height, long lists collapsed). Full detail adds decoded_action to contract calls, as in get_transaction. Optional filters: direction 'sent' (signed by the account) or 'received' (transfers to it signed by others), symbol (the transferred or attached token), counterparty (the other side of a transfer), and after_timestamp / before_timestamp (unix seconds of the transaction's entry, after inclusive, before exclusive). With any filter set, the server pulls history pages from offset until limit transactions match (default 20) or 2000 have been scanned, and wraps them as {count, scanned, pages, complete, next_offset, transactions}; pass next_offset back as offset with the same filters to keep scanning. A time range costs one entry lookup per distinct height among the matches. Optional network parameter: 'mainnet' (default) or 'testnet'.",
+        examples: &[r#"{"address":"29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw","limit":20,"sort":"desc"}"#, r#"{"address":"29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw","limit":50,"offset":50,"detail":"compact"}"#, r#"{"address":"29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw","direction":"received","symbol":"AMA","after_timestamp":1735689600}"#],
+    },
+    ToolDoc {
+        name: "get_validator_details",
+        summary: "Validators with score, rank, epoch and emission address, or one validator by public key",
+        doc: "Combines the trainer list with each validator's epoch score (/api/epoch/score/{pk}) and emission address (/api/epoch/get_emission_address/{pk}). Returns {validators, count, scores_available}; each validator has pk, score, rank, epoch (derived from the tip height) and emission_address. Pass public_key for one validator; a key that is not a current trainer fails with not_found. Lookups that fail for a validator leave its fields out rather than failing the call, so check which fields are present. Slower than get_validators for the whole set, since it makes two requests per validator. Optional network parameter: 'mainnet' (default) or 'testnet'.",
+        examples: &[r#"{"public_key":"29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw"}"#, r#"{}"#],
+    },
+    ToolDoc {
+        name: "get_validators",
+        summary: "Retrieves the list of current validator nodes with epoch score and rank",
+        doc: "Retrieves the current validator nodes (trainers) with their epoch score and rank. Set keys_only to skip the score lookup. Optional network parameter: 'mainnet' (default) or 'testnet'.",
+        examples: &[r#"{}"#, r#"{"keys_only":true}"#],
+    },
+    ToolDoc {
+        name: "list_contract_keys",
+        summary: "Lists a contract's storage keys under a prefix with their values, a page at a time",
+        doc: "Lists the storage keys of a contract that start with prefix (all keys when empty), in key order, with each value as {encoding, value}: the text when it is printable UTF-8, hex otherwise. Returns up to limit keys (default 100, max 200); when more remain, next_cursor is set: pass it back as cursor with the same contract_address and prefix for the next page. Use it to look around a contract's storage while debugging; export_contract_state exports larger pages with typed values and CSV. Needs the node's contract prefix listing. Optional network parameter: 'mainnet' (default) or 'testnet'.",
+        examples: &[r#"{"contract_address":"Coin","prefix":"totalSupply:"}"#, r#"{"contract_address":"Coin","prefix":"balance:","cursor":"<next_cursor>"}"#],
+    },
+    ToolDoc {
+        name: "get_token_info",
+        summary: "Total supply, decimals and burned amount of a token, or of every token when no symbol is given",
+        doc: "Reads a token's Coin contract storage and returns {symbol, total_supply_flat, total_supply_float, decimals, decimals_source, burned}. total_supply_flat is the supply in atoms as a string; total_supply_float and burned (the burn address's balance) are in whole tokens using the token's decimals: its on-chain decimals when set (decimals_source 'onchain'), otherwise the built-in or SYMBOL_DECIMALS value ('configured'). When neither is known, decimals is absent and burned is in atoms. Without symbol, lists every token with a supply as {count, tokens}, up to 100 (needs the node's contract prefix listing). An unknown symbol fails with not_found. Optional network parameter: 'mainnet' (default) or 'testnet'.",
+        examples: &[r#"{"symbol":"AMA"}"#, r#"{}"#],
+    },
+    ToolDoc {
+        name: "get_contract_state",
+        summary: "Retrieves a specific value from smart contract storage",
+        doc: "Retrieves a specific value from smart contract storage by contract address and key. Optional network parameter: 'mainnet' (default) or 'testnet'.",
+        examples: &[r#"{"contract_address":"Coin","key":"<storage key>"}"#],
+    },
+    ToolDoc {
+        name: "raw_node_query",
+        summary: "GETs a node API path without a dedicated tool; only allowed path prefixes are accepted",
+        doc: "GETs a node API path that has no dedicated tool yet and returns the JSON body. Only exposed when the deployment sets MCP_RAW_QUERY_ALLOW; paths must start with one of its prefixes and may not contain traversal segments, encoded separators or absolute URLs. Only GET is supported. Optional network parameter: 'mainnet' (default) or 'testnet'.",
+        examples: &[r#"{"path":"/api/chain/tip"}"#],
+    },
+    ToolDoc {
+        name: "claim_testnet_ama",
+        summary: "Claims testnet tokens to the specified address (once per 24 hours per IP and symbol)",
+        doc: "Sends testnet tokens from the faucet to the given address. Optional symbol parameter picks the token, AMA by default; the deployment configures which symbols are available and how much each claim sends. Limited to one claim per 24 hours per client IP and symbol, so claiming one token does not block another. The output carries clock_skew_warning when the worker clock is off from the testnet tip; the nonce then comes from the chain's time. Testnet tokens have no value.",
+        examples: &[
+            r#"{"address":"29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw"}"#,
+            r#"{"address":"29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw","symbol":"AMA"}"#,
+        ],
+    },
+    ToolDoc {
+        name: "create_watch",
+        summary: "Posts new transactions of an address to a webhook, checked every scheduled run",
+        doc: "Watches a mainnet address and POSTs its new transactions to an https webhook after each scheduled run (every 10 minutes), starting from the current tip. Requires an Authorization: Bearer token the server accepts; each token may hold a limited number of watches. The filter narrows what is reported: direction 'in' (transfers received), 'out' (transactions signed) or 'any' (default), symbol, and min_amount in atoms; symbol and min_amount only match transfers. The response includes a secret that is shown only once: every webhook body is signed with HMAC-SHA256 under it, sent as 'X-Amadeus-Signature: sha256=<hex>'. Failed deliveries are retried with backoff and again on the next run. Webhook URLs must be https and may not point at internal addresses.",
+        examples: &[r#"{"address":"29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw","webhook_url":"https://example.com/hooks/amadeus","filter":{"direction":"in","symbol":"AMA","min_amount":"1000000000"}}"#],
+    },
+    ToolDoc {
+        name: "list_watches",
+        summary: "Lists the watches held by the caller's bearer token",
+        doc: "Lists the watches created with the caller's bearer token, with their filters and the entry height each has been dispatched up to. Secrets are not shown again.",
+        examples: &[r#"{}"#],
+    },
+    ToolDoc {
+        name: "delete_watch",
+        summary: "Deletes one of the caller's watches",
+        doc: "Deletes a watch by the id create_watch returned. Only watches created with the caller's bearer token can be deleted.",
+        examples: &[r#"{"id":"5f0c2a9e41d8b7c36e1f4a2b9d0c8e71"}"#],
+    },
+    ToolDoc {
+        name: "add_address_label",
+        summary: "Labels an address so outputs show the label next to it",
+        doc: "Stores a label for an address, e.g. 'exchange hot wallet', replacing its previous one. Afterwards outputs that contain the address add the label beside it as <field>_label (address_label, signer_label, from_label, to_label, destination_label, counterparty_label), and investigate_addresses suggests labelling unlabelled frequent counterparties. Labels are trimmed and at most 64 characters. The burn address has a built-in label that cannot be changed, and built-in names (burn, Coin, Epoch, Contract, Nft) cannot be used in any case; such labels fail with invalid_label naming the field to fix. The stdio server keeps up to 10000 labels, in MCP_DISK_CACHE_DIR when set and in memory otherwise. The worker keeps them in D1, shared by all callers, and requires an Authorization: Bearer token from MCP_LABEL_TOKENS.",
+        examples: &[r#"{"address":"29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw","label":"exchange hot wallet"}"#],
+    },
+    ToolDoc {
+        name: "export_address_labels",
+        summary: "Returns every user address label, for backup or import elsewhere",
+        doc: "Returns the user address labels as {address, label, added_at} objects, in the form import_address_labels accepts. Built-in labels are not included.",
+        examples: &[r#"{}"#],
+    },
+    ToolDoc {
+        name: "import_address_labels",
+        summary: "Adds address labels exported from this or another server",
+        doc: "Adds the labels in the form export_address_labels returns; added_at may be omitted. A label for an address that already has one replaces it, and replace: true drops every existing label first. Entries failing the add_address_label rules are listed in rejected with the reason while the rest still apply; the output counts the labels added, updated and unchanged. On the worker it requires an Authorization: Bearer token from MCP_LABEL_TOKENS.",
+        examples: &[r#"{"labels":[{"address":"29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw","label":"exchange hot wallet"}]}"#, r#"{"labels":[],"replace":true}"#],
+    },
+    ToolDoc {
+        name: "get_entry_tip",
+        summary: "Get the latest blockchain entry",
+        doc: "Returns the latest entry at the chain tip, including its hash, height and header. The stdio server returns hash, height, slot, signer, tx_count and finalized for the mainnet tip; the worker returns the node's entry as is.",
+        examples: &[r#"{}"#],
+    },
+    ToolDoc {
+        name: "get_entry_by_hash",
+        summary: "Get entry by hash",
+        doc: "Returns a blockchain entry by its base58 hash, in the same shape as the entries of get_block_by_height. A hash that is not base58 fails with validation_failed before the node is asked; an unknown one fails with not_found. The stdio server also serves entries as the resource amadeus://entry/{hash} (mainnet). Optional detail parameter: 'full' or 'compact'.",
+        examples: &[r#"{"hash":"2qo2mC7GvBBEPZTmZeYXsc5KhzboKSen6DekBytar1fy"}"#],
+    },
+    ToolDoc {
+        name: "get_block_range",
+        summary: "Entries of up to 100 consecutive heights with per-height counts",
+        doc: "Returns the entries of every height from start_height to end_height, inclusive, at most 100 heights (and no more than the server's max_block_span). Heights come back in order as {height, entry_count, tx_count, entries}, with entry_count and tx_count totals for the range, so a range can be summarized without further calls. A height that could not be fetched is listed under failed with its error while the others are still returned. end_height past the tip fails with height_beyond_tip. Stdio only; heights are batched when the node has a batch endpoint and otherwise fetched eight at a time.",
+        examples: &[r#"{"start_height":1250000,"end_height":1250049}"#],
+    },
+    ToolDoc {
+        name: "get_block_with_txs",
+        summary: "Get block at height with full transactions",
+        doc: "Returns the entries at a height together with their full transactions. Each entry carries txs_total and up to tx_limit (default 100, max 500) transactions from tx_offset; when some were left out it also carries next_tx_offset and a note, so call again with that offset for the rest. Prefer get_block_by_height when transaction bodies are not needed. Optional detail parameter: 'full' or 'compact' (abbreviated entry headers and transactions, each page's txs collapsed to a count and the first 10).",
+        examples: &[r#"{"height":1250000}"#, r#"{"height":1250000,"tx_offset":100}"#, r#"{"height":1250000,"detail":"compact"}"#],
+    },
+    ToolDoc {
+        name: "get_txs_in_entry",
+        summary: "Get all transactions in an entry",
+        doc: "Returns every transaction included in an entry, by entry hash, as {entry_hash, count, txs}; contract calls carry decoded_action. A transaction the typed form does not cover is returned as the node sent it under undecoded instead of failing the call. An unknown entry hash fails with not_found. Optional detail parameter: 'full' or 'compact'.",
+        examples: &[r#"{"entry_hash":"2qo2mC7GvBBEPZTmZeYXsc5KhzboKSen6DekBytar1fy","detail":"compact"}"#],
+    },
+    ToolDoc {
+        name: "get_epoch_score",
+        summary: "Get validator mining scores (optionally for specific address)",
+        doc: "Returns the validators' mining scores for the current epoch as {count, scores}, each entry {public_key, score, rank} with rank 1 the highest score, highest first. With address, returns only that validator's score, ranked against the full list; an address with no score this epoch fails with invalid_params.",
+        examples: &[r#"{}"#, r#"{"address":"29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw"}"#],
+    },
+    ToolDoc {
+        name: "get_emission_address",
+        summary: "Get emission address for a validator",
+        doc: "Returns the address a validator's emissions are paid to, by validator public key, as {public_key, configured, emission_address}. A validator that never set one gets configured: false and a note instead of an error; a key that is not base58 48 bytes fails with invalid_address before any request.",
+        examples: &[r#"{"address":"29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw"}"#],
+    },
+    ToolDoc {
+        name: "get_richlist",
+        summary: "Get top AMA token holders. Use limit/offset to page, or around_address for the holders ranked just above and below an address",
+        doc: "Returns the top AMA holders ranked by balance, each entry with rank, address, flat (atoms) and float (AMA), plus total, the number of holders the node lists. Use limit (default 20, max 500) and offset to page through the list, or around_address to get the holders ranked just above and below a given address together with its own rank (around.in_list is false when the address is not listed, and rank is where its balance would place it). Nodes that send balances as strings or only as a balance field are read the same way. The stdio server caches the full list for 60 seconds, so successive pages come from one fetch. Optional detail parameter: 'full' or 'compact' (rank, address prefix and float per holder, the page collapsed to a count and the first 10).",
+        examples: &[r#"{"limit":10}"#, r#"{"limit":100,"detail":"compact"}"#, r#"{"around_address":"29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw"}"#],
+    },
+    ToolDoc {
+        name: "get_nodes",
+        summary: "Get connected peer nodes",
+        doc: "Returns the peer nodes the upstream node is connected to as {count, total, nodes}. Each node has ip4, pk, version, latency, last_message and online when the node reports them, plus any other fields it sends. filter keeps the peers whose IP starts with it (\"72.9.\") or whose public key contains it; count is then the matches and total the unfiltered peers.",
+        examples: &[r#"{}"#, r#"{"filter": "72.9."}"#],
+    },
+    ToolDoc {
+        name: "get_removed_validators",
+        summary: "Get validators removed this epoch",
+        doc: "Returns the validators removed from the set during the current epoch as {epoch, count, removed, still_listed}. still_listed holds removed keys that get_validators still lists, an inconsistency between the node's two lists worth reporting; it is normally empty. epoch is present when the node reports it.",
+        examples: &[r#"{}"#],
+    },
+    ToolDoc {
+        name: "test_signing_flow",
+        summary: "Diagnoses the create/sign/submit loop locally with a throwaway secret key; never submits anything",
+        doc: "Diagnoses the create/sign/submit loop locally with a THROWAWAY secret key (never a funded key): builds a self-transfer blob with a fixed nonce, signs and verifies it with the Amadeus BLS DST, finalizes and decodes it, and reports which step fails (hash, DST or encoding mismatch). Pass signature to check an external signer's output for the returned signing_payload. Without secret_key the server generates a throwaway key when MCP_KEY_TOOLS=1 is set, reporting generated_key: true, and rejects the call with secret_key_required otherwise. signature cannot be combined with a generated key, which only the server holds. Stdio only.",
+        examples: &[r#"{"secret_key":"<throwaway base58 64-byte secret key>"}"#, r#"{}"#],
+    },
+    ToolDoc {
+        name: "get_signer_distribution",
+        summary: "Entries per signer over a window, against the trainer set, with skew flags",
+        doc: "Counts the signer of each entry over the last `blocks` heights (default 100, max 1000) and joins the counts with the current trainer set and epoch scores. Each signer gets entries, share of the window, expected_share (1 / number of trainers), ratio (share / expected_share) and skew: 'over' at twice the expected share or more, 'under' at half or less, 'not_trainer' for signers outside the current trainer set. silent_trainers lists trainers that signed nothing in the window; unknown_signers lists signers outside the set, which can happen when the set changed within the window. Stdio only; the window is fetched eight heights at a time.",
+        examples: &[r#"{}"#, r#"{"blocks":500}"#],
+    },
+    ToolDoc {
+        name: "get_recent_blocks",
+        summary: "Digest of the newest heights: hashes, signers, tx counts, intervals and totals",
+        doc: "Answers \"what happened on chain lately\" without a get_block_by_height loop. Reads the tip height from chain stats and fetches the last count heights (default 10, max 50, and at most max_block_span) like get_block_range. Each height, oldest first, lists entry_hashes, signers (one unless the height has several entries), tx_count, timestamp_ms of its first entry and interval_ms since the height below it; the oldest height and any height above a failed one have no interval. Totals: tx_count, unique_signers and avg_interval_ms over the known intervals. Heights that could not be fetched are listed under failed with the error. Stdio only; heights are fetched through the batch endpoint when the node has one, eight at a time otherwise. Optional network parameter: 'mainnet' (default) or 'testnet'.",
+        examples: &[r#"{}"#, r#"{"count":50,"network":"testnet"}"#],
+    },
+    ToolDoc {
+        name: "get_blocks_by_signer",
+        summary: "Entries a validator key signed over a height range, with its share of the range",
+        doc: "Fetches every height from start_height to end_height (inclusive, at most 500 heights and at most max_block_span) like get_block_range and keeps the entries whose header signer is the given public key. Returns signer, the range, heights_scanned, entries_scanned (entries by any signer), count (entries signed), heights_signed, share_percent (count / entries_scanned, in percent) and the signed entries in height order. Heights that could not be fetched are listed under failed with the error and are left out of heights_scanned and the share rather than counted as missed. Stdio only; heights are fetched through the batch endpoint when the node has one, eight at a time otherwise. Optional network parameter: 'mainnet' (default) or 'testnet'.",
+        examples: &[r#"{"signer":"29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw","start_height":35000000,"end_height":35000499}"#],
+    },
+    ToolDoc {
+        name: "get_epoch_info",
+        summary: "Current epoch, progress through it and estimated time to the next",
+        doc: "Derives the current epoch from the tip height (epochs are 100000 heights) and returns epoch, height, epoch_start_height, next_epoch_height, height_in_epoch, blocks_remaining and progress (0 to 1). The time to the next epoch is projected from the entry timestamps of five heights over the last 1000: seconds_per_entry, estimated_secs_to_next and estimated_next_epoch_at (unix seconds). The estimate fields are absent when the sampled entries carry no usable timestamps; pace_samples says how many were used. The estimate assumes the recent pace holds for the rest of the epoch. Optional network parameter: 'mainnet' (default) or 'testnet'.",
+        examples: &[r#"{}"#, r#"{"network":"testnet"}"#],
+    },
+    ToolDoc {
+        name: "get_liveness",
+        summary: "Reports whether the chain is alive: time since the latest entry, the longest recent gap, txs_per_sec and a healthy/degraded/stalled verdict",
+        doc: "Reports whether the chain is producing entries. since_last_entry_secs is the time since the newest entry's timestamp, taken from the sample or the tip follower, whichever is newer. longest_gap_secs and longest_gap_at are the longest wait between consecutive entries over the last `blocks` entries (default 50, max 500) and the height that ended it. txs_per_sec comes from chain stats. verdict is 'stalled' when the silence reaches the stalled threshold, 'degraded' when the silence or the longest gap reaches the degraded threshold, 'healthy' otherwise, and 'unknown' when the node sends no entry timestamps; the thresholds (MCP_LIVENESS_DEGRADED_SECS, MCP_LIVENESS_STALLED_SECS, default 15 and 60) are echoed in the output. The amadeus://status resource carries the same verdict for the mainnet tip. Optional network parameter: 'mainnet' (default) or 'testnet'.",
+        examples: &[r#"{}"#, r#"{"blocks":200,"network":"testnet"}"#],
+    },
+    ToolDoc {
+        name: "get_signing_public_key",
+        summary: "Returns the ed25519 key that signs tool results, when response signing is on",
+        doc: "When the server runs with MCP_RESPONSE_SIGNING_SK, every tool result gains a signature block: algorithm ('ed25519'), public_key, timestamp (unix seconds), request_id (the JSON-RPC id of the call) and value (base58 signature). The signed bytes are the compact JSON, keys sorted, of {payload, request_id, timestamp}, where payload is the result with the signature field removed. This tool returns the public key (base58) to check them against, or enabled=false when results are not signed. Pin the key out of band; a relay that can rewrite results can also rewrite this tool's answer. Stdio only.",
+        examples: &[r#"{}"#],
+    },
+    ToolDoc {
+        name: "correlate_validator_nodes",
+        summary: "Matches each validator key to the peer node carrying it, with version and last signed entry",
+        doc: "Joins the current trainer set with the node's peer list by public key and with the signers of the last `blocks` entries (default 100, max 1000). Each validator gets a status: 'connected' (one online peer carries its key), 'offline' (one peer, reporting itself offline), 'not_connected' (no peer), 'duplicate' (several peers carry the key; all are listed and no version is picked) or 'unavailable' when the peer list exposes no public keys at all, in which case nothing is guessed and note explains why. The matched peer's ip4, version, latency and last_message are included, plus last_signed_height within the window. Stdio only.",
+        examples: &[r#"{}"#, r#"{"blocks":500}"#],
+    },
+    ToolDoc {
+        name: "estimate_finality_time",
+        summary: "Estimates seconds until a transaction submitted now is finalized, with a confidence range",
+        doc: "Estimates how many seconds until a transaction submitted now is in a finalized entry. Walks back from the tip (up to sample_entries, default 32) to find how far finality trails it, and measures the entry production rate from chain stats the server recorded over the last 10 minutes. Returns insufficient_data rather than a guess when either is missing. Stdio only.",
+        examples: &[r#"{}"#, r#"{"sample_entries":64}"#],
+    },
+    ToolDoc {
+        name: "list_built_transactions",
+        summary: "Lists transactions this server built, with whether each was seen on chain or looks abandoned",
+        doc: "Lists the transactions create_transaction built, newest first: transaction_hash, signer, contract, function, destination and amount (for Coin.transfer; the attached amount otherwise), symbol, built_at (unix seconds) and status. A transaction is 'observed' once a get_transaction or get_transaction_history lookup returned it from the chain (observed_height is the entry it was in), 'abandoned' when it has not been seen an hour after it was built, and 'pending' before that. Those lookups also mark each transaction built here with built_by_this_server: true and its built_at. Filters: signer, status and limit (default 50, max 1000). The stdio server keeps up to 1000 transactions, in MCP_DISK_CACHE_DIR when set and in memory otherwise; the worker keeps them in D1 per signer and requires the signer argument. With MCP_RECORD_BUILT_TXS=0 nothing is recorded or marked.",
+        examples: &[r#"{}"#, r#"{"signer":"29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw","status":"abandoned"}"#],
+    },
+    ToolDoc {
+        name: "export_contract_state",
+        summary: "Exports a contract's storage under a key prefix as JSON or CSV, paged with a resumable cursor",
+        doc: "Dumps a contract's storage for migration or audit, through the node's prefix listing endpoint (GET {api}/contract/get_prefix/{contract}/{prefix}). Keys come in key order, each with its value tagged as in decoded_action: integer, address, base58, utf8, or raw when it is none of those. format 'json' (default) returns keys as {key, type, value} objects; 'csv' returns a csv string with a key,type,value header. A page holds at most limit keys (default 200, max 1000); when more remain, complete is false and next_cursor is set. Pass next_cursor back as cursor, with the same contract_address and prefix, to continue. The cursor holds the last exported key, so it works across server restarts and instances, and a page never repeats or skips keys that did not change between calls. Nodes without the endpoint are detected by the startup probe: the tool is marked unavailable in tools/list and fails with endpoint_unsupported. Optional network parameter: 'mainnet' (default) or 'testnet'.",
+        examples: &[r#"{"contract_address":"Coin","prefix":"balance:"}"#, r#"{"contract_address":"Coin","prefix":"balance:","format":"csv","limit":1000,"cursor":"<next_cursor>"}"#],
+    },
+    ToolDoc {
+        name: "diff_contract_state",
+        summary: "Compares contract storage keys between two heights from values this server has observed",
+        doc: "Compares contract storage keys between height_a and height_b, returning added, removed and changed keys with before/after values. The node has no historical state queries, so values come from reads this server recorded; keys without an observation on both sides are listed as unobserved. Mainnet only, up to 50 keys. Stdio only.",
+        examples: &[r#"{"contract_address":"Coin","keys":["<storage key>"],"height_a":1250000,"height_b":1250500}"#],
+    },
+    ToolDoc {
+        name: "investigate_addresses",
+        summary: "Balances, direct transfers, shared counterparties and interaction heights for two accounts within a request budget",
+        doc: "Answers how two accounts relate in one call: both balances, direct transfers between them, counterparties both have transferred with, and the heights of their first and last interaction. Reads history pages until they end or max_requests upstream requests (4-50, default 10) are spent, and reports coverage per account, whether the budget ran out, and whether the tip moved while reading. Each account also lists its frequent_counterparties: accounts in at least 25% of its transfers (and 3 or more), with share_percent; those without a label carry a suggestion to add one with add_address_label. Stdio only.",
+        examples: &[r#"{"address_a":"29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw","address_b":"42KfUgyU2XHCPwDD6g9gryCvtj9vqJAvM6h3Yzs3VRbuyT2JCHwvuk5iAbzXrioerf"}"#, r#"{"address_a":"29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw","address_b":"42KfUgyU2XHCPwDD6g9gryCvtj9vqJAvM6h3Yzs3VRbuyT2JCHwvuk5iAbzXrioerf","max_requests":20}"#],
+    },
+    ToolDoc {
+        name: "get_amadeus_docs",
+        summary: "Returns documentation about the Amadeus blockchain",
+        doc: "Returns documentation about the Amadeus blockchain: overview, key concepts, RPC API endpoints, wallet operations and ecosystem information. Stdio only.",
+        examples: &[r#"{}"#],
+    },
+    ToolDoc {
+        name: "trace_funds",
+        summary: "Follows transfers out of or into an address for up to three hops and returns the resulting graph",
+        doc: "Follows Coin transfers out of (direction 'out', default) or into ('in') an address, hop by hop, for up to max_hops hops (1-3). Each address reached has its latest txs_per_address transactions read. Returns nodes with their hop, edges with per-symbol atom totals and transaction counts, and the endpoints the trace stopped at. Addresses reached twice are expanded once and listed in revisited; once max_requests history reads are spent the remaining addresses are listed in unexpanded. Stdio only.",
+        examples: &[r#"{"address":"29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw"}"#, r#"{"address":"29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw","direction":"in","max_hops":3,"txs_per_address":50,"max_requests":40}"#],
+    },
+];
+
+/// Looks up a tool's catalog entry.
+pub fn tool_doc(name: &str) -> Option<&'static ToolDoc> {
+    TOOL_DOCS.iter().find(|d| d.name == name)
+}
+
+/// Returns one page of `items` starting at `cursor` and the cursor for the next page.
+/// Cursors are opaque to clients and tied to `version`, so a listing that spans a
+/// catalog change fails instead of skipping or repeating tools.
+pub fn page<T: Clone>(
+    items: &[T],
+    cursor: Option<&str>,
+    page_size: usize,
+    version: u64,
+) -> Result<(Vec<T>, Option<String>), String> {
+    let offset = match cursor {
+        None => 0,
+        Some(cursor) => {
+            let decoded = hex::decode(cursor)
+                .ok()
+                .and_then(|bytes| String::from_utf8(bytes).ok())
+                .ok_or_else(|| "invalid cursor".to_string())?;
+            let (cursor_version, offset) = decoded
+                .split_once(':')
+                .and_then(|(v, o)| Some((v.parse::<u64>().ok()?, o.parse::<usize>().ok()?)))
+                .ok_or_else(|| "invalid cursor".to_string())?;
+            if cursor_version != version {
+                return Err("cursor is stale, the tool catalog changed; list again from the start".to_string());
+            }
+            offset
+        }
+    };
+
+    let page_size = page_size.clamp(1, MAX_PAGE_SIZE);
+    let end = (offset + page_size).min(items.len());
+    let page = items.get(offset..end).unwrap_or_default().to_vec();
+    let next = (end < items.len()).then(|| hex::encode(format!("{}:{}", version, end)));
+    Ok((page, next))
+}
+
+/// Tools that broadcast or move funds; hidden in read-only mode.
+pub const WRITE_TOOLS: &[&str] = &["submit_transaction", "claim_testnet_ama", SERVER_KEY_TRANSFER_TOOL];
+
+/// A node endpoint that older or self-hosted nodes may lack, and the tools that need it.
+#[derive(Debug, Clone, Copy)]
+pub struct EndpointRequirement {
+    /// Path, or path prefix for endpoints that take a parameter
+    pub endpoint: &'static str,
+    /// Cheap request (sent as HEAD) whose 404 means the node lacks the endpoint
+    pub probe: &'static str,
+    pub tools: &'static [&'static str],
+}
+
+pub const ENDPOINT_REQUIREMENTS: &[EndpointRequirement] = &[
+    EndpointRequirement {
+        endpoint: "/api/contract/view",
+        probe: "/api/contract/view",
+        tools: &["preview_contract_call"],
+    },
+    EndpointRequirement {
+        endpoint: "/api/contract/get_prefix",
+        probe: "/api/contract/get_prefix",
+        tools: &["export_contract_state", "list_contract_keys"],
+    },
+    EndpointRequirement {
+        endpoint: "/api/chain/tip",
+        probe: "/api/chain/tip",
+        tools: &["get_entry_tip", "estimate_finality_time", "investigate_addresses", "get_signer_distribution", "correlate_validator_nodes", "get_liveness", "get_epoch_info"],
+    },
+    EndpointRequirement {
+        endpoint: "/api/chain/height_with_txs/",
+        probe: "/api/chain/height_with_txs/1",
+        tools: &["get_block_with_txs"],
+    },
+    EndpointRequirement {
+        endpoint: "/api/contract/richlist",
+        probe: "/api/contract/richlist",
+        tools: &["get_richlist"],
+    },
+    EndpointRequirement {
+        endpoint: "/api/epoch/score",
+        probe: "/api/epoch/score",
+        tools: &["get_epoch_score"],
+    },
+    EndpointRequirement {
+        endpoint: "/api/peer/nodes",
+        probe: "/api/peer/nodes",
+        tools: &["get_nodes", "correlate_validator_nodes"],
+    },
+    EndpointRequirement {
+        endpoint: "/api/peer/removed_trainers",
+        probe: "/api/peer/removed_trainers",
+        tools: &["get_removed_validators"],
+    },
+    EndpointRequirement {
+        endpoint: "/api/chain/tx_pool",
+        probe: "/api/chain/tx_pool",
+        tools: &["get_pending_transactions"],
+    },
+];
+
+/// The tracked endpoint a request path hits, if any.
+pub fn endpoint_requirement(path: &str) -> Option<&'static EndpointRequirement> {
+    let path = path.split('?').next().unwrap_or(path);
+    ENDPOINT_REQUIREMENTS.iter().find(|r| {
+        if r.endpoint.ends_with('/') {
+            path.starts_with(r.endpoint)
+        } else {
+            path == r.endpoint || path.starts_with(&format!("{}/", r.endpoint))
+        }
+    })
+}
+
+/// Node passthrough tool; exposed only when `MCP_RAW_QUERY_ALLOW` lists path prefixes.
+pub const RAW_QUERY_TOOL: &str = "raw_node_query";
+
+/// Which tools a deployment exposes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ToolFilter {
+    pub read_only: bool,
+    /// When set, only these tools are exposed
+    pub allow: Option<Vec<String>>,
+    pub deny: Vec<String>,
+    /// Node path prefixes `raw_node_query` may GET
+    pub raw_query_allow: Vec<String>,
+    /// Whether `AMADEUS_MCP_SIGNING_KEY` is set, which `transfer_from_server_key` needs
+    pub server_key: bool,
+}
+
+impl ToolFilter {
+    /// Reads `MCP_READ_ONLY`, `MCP_TOOLS_ALLOW` and `MCP_TOOLS_DENY` (comma-separated names)
+    /// and `MCP_RAW_QUERY_ALLOW` (comma-separated path prefixes), and whether
+    /// `AMADEUS_MCP_SIGNING_KEY` is set.
+    pub fn from_lookup<F: Fn(&str) -> Option<String>>(lookup: F) -> Self {
+        let list = |key: &str| {
+            lookup(key).map(|v| {
+                v.split(',')
+                    .map(str::trim)
+                    .filter(|name| !name.is_empty())
+                    .map(String::from)
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        Self {
+            read_only: lookup("MCP_READ_ONLY").is_some_and(|v| v == "1" || v.eq_ignore_ascii_case("true")),
+            allow: list("MCP_TOOLS_ALLOW"),
+            deny: list("MCP_TOOLS_DENY").unwrap_or_default(),
+            raw_query_allow: list("MCP_RAW_QUERY_ALLOW")
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|prefix| prefix.starts_with('/'))
+                .collect(),
+            server_key: lookup(SERVER_KEY_VAR).is_some_and(|v| !v.trim().is_empty()),
+        }
+    }
+
+    pub fn allows(&self, name: &str) -> bool {
+        self.denial(name).is_none()
+    }
+
+    /// Why a tool is hidden, `None` when it is exposed.
+    pub fn denial(&self, name: &str) -> Option<&'static str> {
+        if name == RAW_QUERY_TOOL && self.raw_query_allow.is_empty() {
+            return Some("MCP_RAW_QUERY_ALLOW is not set");
+        }
+        if name == SERVER_KEY_TRANSFER_TOOL && !self.server_key {
+            return Some("AMADEUS_MCP_SIGNING_KEY is not set");
+        }
+        if self.read_only && WRITE_TOOLS.contains(&name) {
+            return Some("read-only mode (MCP_READ_ONLY)");
+        }
+        if self.deny.iter().any(|d| d == name) {
+            return Some("listed in MCP_TOOLS_DENY");
+        }
+        if self.allow.as_ref().is_some_and(|allow| !allow.iter().any(|a| a == name)) {
+            return Some("not listed in MCP_TOOLS_ALLOW");
+        }
+        None
+    }
+
+    /// Checks a `raw_node_query` path: relative, no traversal or encoded separators, and
+    /// under one of the allowed prefixes. A prefix without a trailing `/` matches that
+    /// exact path or anything below it.
+    pub fn check_raw_path(&self, path: &str) -> Result<(), String> {
+        if !path.starts_with('/') || path.starts_with("//") {
+            return Err("path must be relative to the node and start with a single /".to_string());
+        }
+        if path.contains("://") || path.contains('\\') || path.contains('#') {
+            return Err("path must not contain a scheme, backslash or fragment".to_string());
+        }
+        if path.chars().any(|c| c.is_control() || c.is_whitespace()) {
+            return Err("path must not contain whitespace or control characters".to_string());
+        }
+        let route = path.split('?').next().unwrap_or_default();
+        let lowered = route.to_ascii_lowercase();
+        if ["%2e", "%2f", "%5c"].iter().any(|encoded| lowered.contains(encoded)) {
+            return Err("path must not contain encoded dots or separators".to_string());
+        }
+        if route.split('/').any(|segment| segment == "." || segment == "..") {
+            return Err("path must not contain . or .. segments".to_string());
+        }
+
+        let allowed = self.raw_query_allow.iter().any(|prefix| {
+            if prefix.ends_with('/') {
+                route.starts_with(prefix.as_str())
+            } else {
+                route == prefix || route.starts_with(&format!("{}/", prefix))
+            }
+        });
+        if allowed {
+            Ok(())
+        } else {
+            Err("path is outside MCP_RAW_QUERY_ALLOW".to_string())
+        }
+    }
+}
+
+/// Body of the `get_capabilities` tool.
+pub fn capabilities(
+    tools_version: u64, tools: &[String], features: &BTreeMap<&'static str, Feature>, limits: &QueryLimits,
+) -> Value {
+    json!({
+        "toolsVersion": tools_version,
+        "tools": tools,
+        "features": features,
+        "queryLimits": limits,
+        "changelog": CHANGELOG,
+    })
+}
+
+/// Whether an optional feature is on in this deployment, and why not when it is off.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Feature {
+    pub enabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl Feature {
+    fn when(enabled: bool, reason: &str) -> Self {
+        Self { enabled, reason: (!enabled).then(|| reason.to_string()) }
+    }
+
+    fn off(reason: &str) -> Self {
+        Self::when(false, reason)
+    }
+
+    /// On exactly when `filter` exposes `tool`, which is what tools/list shows.
+    fn tool(filter: &ToolFilter, tool: &str) -> Self {
+        Self { enabled: filter.allows(tool), reason: filter.denial(tool).map(String::from) }
+    }
+}
+
+/// Which server reports the features; each supports a different subset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Deployment {
+    Stdio,
+    /// The worker, with whether the faucet key and the watch and label tokens are bound
+    Worker { faucet_key: bool, watch_tokens: bool, label_tokens: bool },
+}
+
+/// The deployment's optional features, derived from the config and the tool filter that
+/// also decide tools/list so the two cannot disagree.
+pub fn features(config: &ServerConfig, filter: &ToolFilter, deployment: Deployment) -> BTreeMap<&'static str, Feature> {
+    const STDIO_ONLY: &str = "stdio server only";
+    const WORKER_ONLY: &str = "worker only";
+    let stdio = deployment == Deployment::Stdio;
+    let stdio_setting = |enabled: bool, reason: &str| if stdio { Feature::when(enabled, reason) } else { Feature::off(STDIO_ONLY) };
+
+    let mut features = BTreeMap::new();
+    features.insert("submit_transaction", Feature::tool(filter, "submit_transaction"));
+    features.insert("raw_node_query", Feature::tool(filter, RAW_QUERY_TOOL));
+    features.insert(
+        "server_key_transfer",
+        if stdio { Feature::tool(filter, SERVER_KEY_TRANSFER_TOOL) } else { Feature::off(STDIO_ONLY) },
+    );
+    features.insert(
+        "faucet",
+        match deployment {
+            Deployment::Stdio => Feature::off(WORKER_ONLY),
+            Deployment::Worker { faucet_key: false, .. } => Feature::off("AMADEUS_TESTNET_SK is not set"),
+            Deployment::Worker { .. } => Feature::tool(filter, "claim_testnet_ama"),
+        },
+    );
+    features.insert(
+        "watches",
+        match deployment {
+            Deployment::Stdio => Feature::off(WORKER_ONLY),
+            Deployment::Worker { watch_tokens: false, .. } => Feature::off("MCP_WATCH_TOKENS is not set"),
+            Deployment::Worker { .. } => Feature::tool(filter, "create_watch"),
+        },
+    );
+    features.insert(
+        "address_labels",
+        match deployment {
+            Deployment::Stdio => Feature::tool(filter, "add_address_label"),
+            Deployment::Worker { label_tokens: false, .. } => Feature::off("MCP_LABEL_TOKENS is not set"),
+            Deployment::Worker { .. } => Feature::tool(filter, "add_address_label"),
+        },
+    );
+    features.insert("offline", stdio_setting(config.offline, "MCP_OFFLINE is not set"));
+    features.insert("tip_follower", stdio_setting(config.tip_refresh.is_some(), "MCP_TIP_REFRESH_SECS=0"));
+    features.insert("disk_cache", stdio_setting(config.disk_cache_dir.is_some(), "MCP_DISK_CACHE_DIR is not set"));
+    features.insert(
+        "address_redaction",
+        stdio_setting(config.redact_addresses != RedactMode::Off, "MCP_REDACT_ADDRESSES is not set"),
+    );
+    features.insert("submit_confirmation", stdio_setting(config.elicit_confirm, "MCP_ELICIT_CONFIRM=0"));
+    features.insert(
+        "response_signing",
+        stdio_setting(config.response_signing_key.is_some(), "MCP_RESPONSE_SIGNING_SK is not set"),
+    );
+    features.insert("drift_check", stdio_setting(config.drift_check_interval.is_some(), "MCP_DRIFT_CHECK_SECS is not set"));
+    features.insert(
+        "built_transaction_log",
+        Feature::when(config.record_built_transactions, "MCP_RECORD_BUILT_TXS=0"),
+    );
+    features.insert("strict_args", Feature::when(config.strict_args, "MCP_STRICT_ARGS is not set"));
+    features.insert("key_tools", stdio_setting(config.key_tools, "MCP_KEY_TOOLS is not set"));
+    features.insert("timing", Feature::when(config.include_timing, "MCP_INCLUDE_TIMING=0"));
+    features.insert(
+        "session_budget",
+        Feature::when(!config.session_budget.is_unlimited(), "neither MCP_SESSION_REQUEST_LIMIT nor MCP_SESSION_REQUEST_LIMIT_PER_HOUR is set"),
+    );
+    features
+}
+
+/// Edits within which a declared argument name is offered for an unknown one.
+const ARGUMENT_SUGGESTION_DISTANCE: usize = 2;
+
+/// An argument a tool's schema does not declare (`MCP_STRICT_ARGS`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UnknownArgument {
+    pub name: String,
+    /// The declared argument closest to `name`, when one is only a typo away
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub did_you_mean: Option<String>,
+}
+
+/// Top-level keys of `args` missing from the `properties` of `input_schema`.
+pub fn unknown_arguments(input_schema: &Map<String, Value>, args: &Map<String, Value>) -> Vec<UnknownArgument> {
+    let declared: Vec<&str> = input_schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .map(|properties| properties.keys().map(String::as_str).collect())
+        .unwrap_or_default();
+    args.keys()
+        .filter(|key| !declared.contains(&key.as_str()))
+        .map(|key| UnknownArgument {
+            name: key.clone(),
+            did_you_mean: declared
+                .iter()
+                .map(|name| (edit_distance(&key.to_ascii_lowercase(), name), *name))
+                .filter(|(distance, _)| *distance <= ARGUMENT_SUGGESTION_DISTANCE)
+                .min_by_key(|(distance, _)| *distance)
+                .map(|(_, name)| name.to_string()),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema() -> Map<String, Value> {
+        serde_json::json!({
+            "type": "object",
+            "properties": { "address": { "type": "string" }, "limit": { "type": "integer" }, "network": { "type": "string" } },
+            "required": ["address"]
+        })
+        .as_object()
+        .cloned()
+        .unwrap()
+    }
+
+    fn args(value: Value) -> Map<String, Value> {
+        value.as_object().cloned().unwrap()
+    }
+
+    #[test]
+    fn declared_arguments_are_not_unknown() {
+        assert!(unknown_arguments(&schema(), &args(serde_json::json!({ "address": "a", "limit": 5, "network": "testnet" }))).is_empty());
+        assert!(unknown_arguments(&schema(), &Map::new()).is_empty());
+    }
+
+    #[test]
+    fn typoed_arguments_name_the_closest_declared_one() {
+        let unknown = unknown_arguments(&schema(), &args(serde_json::json!({ "address": "a", "limt": 5, "Network": "testnet" })));
+        assert_eq!(
+            unknown,
+            vec![
+                UnknownArgument { name: "Network".into(), did_you_mean: Some("network".into()) },
+                UnknownArgument { name: "limt".into(), did_you_mean: Some("limit".into()) },
+            ]
+        );
+    }
+
+    #[test]
+    fn extra_arguments_get_no_suggestion() {
+        let unknown = unknown_arguments(&schema(), &args(serde_json::json!({ "address": "a", "denomination": "AMA" })));
+        assert_eq!(unknown, vec![UnknownArgument { name: "denomination".into(), did_you_mean: None }]);
+        assert_eq!(serde_json::to_value(&unknown[0]).unwrap(), serde_json::json!({ "name": "denomination" }));
+
+        // A tool without declared properties accepts nothing
+        let empty = serde_json::json!({ "type": "object" }).as_object().cloned().unwrap();
+        assert_eq!(unknown_arguments(&empty, &args(serde_json::json!({ "limit": 1 })))[0].did_you_mean, None);
+    }
+
+    fn filter(allow: &str) -> ToolFilter {
+        ToolFilter::from_lookup(|key| (key == "MCP_RAW_QUERY_ALLOW").then(|| allow.to_string()))
+    }
+
+    #[test]
+    fn raw_path_allowlist_accepts_paths_under_a_prefix() {
+        let filter = filter("/api/chain, /api/peer/");
+        for path in [
+            "/api/chain",
+            "/api/chain/",
+            "/api/chain/tip",
+            "/api/chain/height/100?limit=5",
+            "/api/chain?x=..",
+            "/api/peer/",
+            "/api/peer/nodes",
+        ] {
+            assert_eq!(filter.check_raw_path(path), Ok(()), "{}", path);
+        }
+    }
+
+    #[test]
+    fn raw_path_allowlist_rejects_escapes_and_lookalikes() {
+        let filter = filter("/api/chain,/api/peer/");
+        for (path, reason) in [
+            ("/api/chainx", "outside MCP_RAW_QUERY_ALLOW"),
+            ("/api/chain_stats", "outside MCP_RAW_QUERY_ALLOW"),
+            ("/api/peer", "outside MCP_RAW_QUERY_ALLOW"),
+            ("/api/peers/nodes", "outside MCP_RAW_QUERY_ALLOW"),
+            ("/API/chain", "outside MCP_RAW_QUERY_ALLOW"),
+            ("/api", "outside MCP_RAW_QUERY_ALLOW"),
+            ("/api/chain/../wallet/keys", ". or .. segments"),
+            ("/api/chain/..", ". or .. segments"),
+            ("/api/chain/./tip", ". or .. segments"),
+            ("/api/chain/%2e%2e/wallet", "encoded dots or separators"),
+            ("/api/chain/%2E%2E/wallet", "encoded dots or separators"),
+            ("/api/chain/.%2e/wallet", "encoded dots or separators"),
+            ("/api/chain%2f..%2fwallet", "encoded dots or separators"),
+            ("/api/chain/%5c..%5cwallet", "encoded dots or separators"),
+            ("/api/chain\\..\\wallet", "scheme, backslash or fragment"),
+            ("/api/chain#/../wallet", "scheme, backslash or fragment"),
+            ("/api/chain/http://evil.example/", "scheme, backslash or fragment"),
+            ("http://evil.example/api/chain", "single /"),
+            ("//evil.example/api/chain", "single /"),
+            ("api/chain", "single /"),
+            ("", "single /"),
+            ("/api/chain/ tip", "whitespace or control"),
+            ("/api/chain/\ntip", "whitespace or control"),
+        ] {
+            let err = filter.check_raw_path(path).expect_err(path);
+            assert!(err.contains(reason), "{:?}: {}", path, err);
+        }
+    }
+
+    #[test]
+    fn raw_path_allowlist_ignores_relative_prefixes() {
+        let filter = filter("api/chain, ,/api/chain");
+        assert_eq!(filter.raw_query_allow, vec!["/api/chain".to_string()]);
+        assert!(filter.allows(RAW_QUERY_TOOL));
+    }
+
+    #[test]
+    fn raw_path_allowlist_unset_hides_the_tool_and_rejects_everything() {
+        let filter = ToolFilter::from_lookup(|_| None);
+        assert_eq!(filter.denial(RAW_QUERY_TOOL), Some("MCP_RAW_QUERY_ALLOW is not set"));
+        assert!(filter.check_raw_path("/api/chain").is_err());
+    }
+
+    #[test]
+    fn pages_cover_every_item_once_in_order() {
+        let items: Vec<u32> = (0..45).collect();
+        let mut seen = Vec::new();
+        let mut cursor = None;
+        let mut pages = 0;
+        loop {
+            let (page, next) = page(&items, cursor.as_deref(), 20, 7).unwrap();
+            assert!(page.len() <= 20);
+            seen.extend(page);
+            pages += 1;
+            match next {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        assert_eq!(pages, 3);
+        assert_eq!(seen, items);
+    }
+
+    #[test]
+    fn page_size_is_clamped() {
+        let items: Vec<u32> = (0..250).collect();
+        let (first, _) = page(&items, None, 0, 1).unwrap();
+        assert_eq!(first.len(), 1);
+        let (big, next) = page(&items, None, 10_000, 1).unwrap();
+        assert_eq!(big.len(), MAX_PAGE_SIZE);
+        assert!(next.is_some());
+    }
+
+    #[test]
+    fn cursors_from_another_catalog_version_or_garbage_are_rejected() {
+        let items: Vec<u32> = (0..30).collect();
+        let (_, next) = page(&items, None, 10, 3).unwrap();
+        let next = next.unwrap();
+        assert!(page(&items, Some(&next), 10, 4).unwrap_err().contains("stale"));
+        for cursor in ["", "zz", &hex::encode("3"), &hex::encode("x:10")] {
+            assert_eq!(page(&items, Some(cursor), 10, 3).unwrap_err(), "invalid cursor", "{:?}", cursor);
+        }
+        // A cursor past the end is an empty last page
+        let (page, next) = page(&items, Some(&hex::encode("3:99")), 10, 3).unwrap();
+        assert!(page.is_empty() && next.is_none());
+    }
+
+    #[test]
+    fn tool_docs_keep_short_summaries_and_the_long_prose() {
+        for doc in TOOL_DOCS {
+            assert!(doc.summary.chars().count() <= 200, "{} summary is {} chars", doc.name, doc.summary.len());
+            assert!(!doc.doc.is_empty(), "{} has no long doc", doc.name);
+            assert_eq!(tool_doc(doc.name).unwrap().name, doc.name);
+            let rendered = doc.render();
+            assert!(rendered.starts_with(doc.doc));
+            for example in doc.examples {
+                assert!(rendered.contains(example));
+            }
+        }
+        assert!(tool_doc("no_such_tool").is_none());
+    }
+
+    fn worker(faucet_key: bool, watch_tokens: bool, label_tokens: bool) -> Deployment {
+        Deployment::Worker { faucet_key, watch_tokens, label_tokens }
+    }
+
+    #[test]
+    fn worker_features_need_their_secrets_and_the_filter() {
+        let config = ServerConfig::from_lookup(|_| None);
+        let features = features(&config, &ToolFilter::default(), worker(false, false, true));
+        assert_eq!(features["faucet"], Feature::off("AMADEUS_TESTNET_SK is not set"));
+        assert_eq!(features["watches"], Feature::off("MCP_WATCH_TOKENS is not set"));
+        assert_eq!(features["address_labels"], Feature { enabled: true, reason: None });
+        assert_eq!(features["disk_cache"], Feature::off("stdio server only"));
+        assert_eq!(features["server_key_transfer"], Feature::off("stdio server only"));
+
+        let read_only = ToolFilter { read_only: true, ..ToolFilter::default() };
+        let features = self::features(&config, &read_only, worker(true, true, false));
+        // With the key bound the faucet follows the filter, which hides write tools
+        assert_eq!(features["faucet"], Feature::tool(&read_only, "claim_testnet_ama"));
+        assert!(!features["faucet"].enabled && features["faucet"].reason.is_some());
+        assert!(features["watches"].enabled);
+        assert_eq!(features["address_labels"], Feature::off("MCP_LABEL_TOKENS is not set"));
+    }
+
+    #[test]
+    fn tool_backed_features_follow_the_filter() {
+        let config = ServerConfig::from_lookup(|_| None);
+        for filter in [
+            ToolFilter::default(),
+            ToolFilter { read_only: true, ..ToolFilter::default() },
+            ToolFilter { deny: vec!["submit_transaction".into()], ..ToolFilter::default() },
+            ToolFilter { allow: Some(vec![RAW_QUERY_TOOL.into()]), raw_query_allow: vec!["/api".into()], ..ToolFilter::default() },
+        ] {
+            let features = features(&config, &filter, Deployment::Stdio);
+            for (feature, tool) in [("submit_transaction", "submit_transaction"), ("raw_node_query", RAW_QUERY_TOOL)] {
+                assert_eq!(features[feature].enabled, filter.allows(tool), "{} with {:?}", feature, filter);
+                assert_eq!(features[feature].reason.as_deref(), filter.denial(tool), "{}", feature);
+            }
+        }
+    }
+}
@@ -0,0 +1,505 @@
+use crate::blockchain::api_path::{self, ApiPaths, DEFAULT_API_PREFIX, DEFAULT_TX_API_PREFIX};
+use crate::blockchain::budget::BudgetLimits;
+use crate::blockchain::clock::DEFAULT_CLOCK_SKEW_THRESHOLD;
+use crate::blockchain::decimals::DecimalsRegistry;
+use crate::blockchain::faucet::FaucetTokens;
+use crate::blockchain::limits::{LimitsPreset, QueryLimits};
+use crate::blockchain::liveness::LivenessThresholds;
+use crate::blockchain::server_key::{ServerKey, SERVER_KEY_VAR};
+use crate::blockchain::OutputDetail;
+use crate::catalog::ToolFilter;
+use crate::locale::Locale;
+use crate::secret::Secret;
+use std::str::FromStr;
+use zeroize::Zeroize;
+use std::time::Duration;
+
+/// Name of the worker service binding that fronts the mainnet node.
+pub const BLOCKCHAIN_SERVICE_BINDING: &str = "BLOCKCHAIN_SERVICE";
+
+/// How the worker reaches the upstream node.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UpstreamTransport {
+    /// Use the service binding when it is bound, public fetch otherwise
+    #[default]
+    Auto,
+    /// Always use public fetch
+    Fetch,
+    /// Prefer the service binding; still falls back to public fetch if it is missing
+    Service,
+}
+
+impl UpstreamTransport {
+    /// Picks the binding to route upstream calls through, if any; `None` means public fetch.
+    pub fn select<T>(self, binding: Option<T>) -> Option<T> {
+        match self {
+            Self::Fetch => None,
+            Self::Auto | Self::Service => binding,
+        }
+    }
+
+    /// Whether a request to `url` goes through the binding fronting `base_url`: the same
+    /// origin and path prefix, so a look-alike host or a sibling path does not.
+    pub fn is_bound_url(base_url: &str, url: &str) -> bool {
+        let base_url = base_url.trim_end_matches('/');
+        url.strip_prefix(base_url).is_some_and(|rest| rest.is_empty() || rest.starts_with(['/', '?']))
+    }
+}
+
+impl FromStr for UpstreamTransport {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "fetch" => Ok(Self::Fetch),
+            "service" => Ok(Self::Service),
+            other => Err(format!("unknown upstream transport: {}", other)),
+        }
+    }
+}
+
+/// How addresses in tool outputs are shown (`MCP_REDACT_ADDRESSES`). Stdio server only;
+/// the worker keeps no session to map tokens back.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RedactMode {
+    /// Addresses are returned as-is
+    #[default]
+    Off,
+    /// First and last six characters, e.g. `6Y2iRx…Pq9wZt`
+    Partial,
+    /// Stable per-session token derived with HMAC, e.g. `addr:3f9a0c1e7b24d5a6`
+    Hash,
+}
+
+impl FromStr for RedactMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "off" | "none" | "" => Ok(Self::Off),
+            "partial" => Ok(Self::Partial),
+            "hash" => Ok(Self::Hash),
+            other => Err(format!("unknown redaction mode: {}", other)),
+        }
+    }
+}
+
+pub const DEFAULT_TIP_REFRESH: Duration = Duration::from_secs(5);
+pub const DEFAULT_DISK_CACHE_MAX_BYTES: u64 = 256 * 1024 * 1024;
+/// Fixture directory read by `MCP_OFFLINE=1` unless `MCP_OFFLINE_DIR` is set; the one
+/// `fixtures refresh` writes.
+pub const DEFAULT_OFFLINE_DIR: &str = "tests/fixtures";
+pub const DEFAULT_MAINNET_URL: &str = "https://nodes.amadeus.bot";
+pub const DEFAULT_TESTNET_URL: &str = "https://testnet.amadeus.bot";
+
+/// Deployment settings shared by the native server and the worker.
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    /// Node URL for mainnet calls (`BLOCKCHAIN_URL`)
+    pub mainnet_url: String,
+    /// Node URL for testnet calls (`AMADEUS_TESTNET_RPC`)
+    pub testnet_url: String,
+    /// Worker: backup mainnet nodes tried in order when `mainnet_url` is unavailable. Both
+    /// servers compare `mainnet_url`'s height with the first in check_node_health
+    /// (`BLOCKCHAIN_FALLBACK_URLS`, comma-separated)
+    pub mainnet_fallback_urls: Vec<String>,
+    /// Default shape of heavy tool outputs, overridable per call with `detail`
+    pub output_detail: OutputDetail,
+    /// Worker only: whether to route mainnet calls through the service binding
+    pub upstream_transport: UpstreamTransport,
+    /// Node API mount points: `BLOCKCHAIN_API_PREFIX` (default `/api`) for reads and
+    /// `BLOCKCHAIN_TX_API_PREFIX` (default `/api`) for transaction submission
+    pub api_paths: ApiPaths,
+    /// Read-only mode and tool allow/deny lists
+    pub tool_filter: ToolFilter,
+    /// Stdio only: redaction of addresses in tool outputs
+    pub redact_addresses: RedactMode,
+    /// Stdio only: how often the mainnet tip is refreshed in the background, `None` when
+    /// `MCP_TIP_REFRESH_SECS=0`
+    pub tip_refresh: Option<Duration>,
+    /// Stdio only: directory keeping finalized entries and transactions across restarts
+    /// (`MCP_DISK_CACHE_DIR`); off when unset
+    pub disk_cache_dir: Option<String>,
+    /// Stdio only: size cap of the disk cache (`MCP_DISK_CACHE_MAX_MB`, default 256)
+    pub disk_cache_max_bytes: u64,
+    /// Stdio only: answer read tools from recorded fixtures and never contact a node
+    /// (`MCP_OFFLINE=1`); tools that would change the chain fail
+    pub offline: bool,
+    /// Fixture directory for offline mode (`MCP_OFFLINE_DIR`, default `tests/fixtures`)
+    pub offline_dir: String,
+    /// Per-symbol decimals: built-in defaults overridden by `SYMBOL_DECIMALS`
+    /// (`AMA=9,USDX=6`)
+    pub symbol_decimals: DecimalsRegistry,
+    /// Worker only: tokens claim_testnet_ama hands out and the whole units per claim
+    /// (`FAUCET_TOKENS`, JSON); 100 AMA by default
+    pub faucet_tokens: FaucetTokens,
+    /// Stdio only: ask the user to confirm each submit when the client supports
+    /// elicitation; off with `MCP_ELICIT_CONFIRM=0`
+    pub elicit_confirm: bool,
+    /// Reject tool calls with arguments the tool's schema does not declare
+    /// (`MCP_STRICT_ARGS=1`); off by default, unknown arguments are ignored
+    pub strict_args: bool,
+    /// Stdio only: let test_signing_flow generate a throwaway secret key when none is
+    /// passed (`MCP_KEY_TOOLS=1`); off by default, the key is then required
+    pub key_tools: bool,
+    /// Add a `timing` block (duration, upstream requests, retries, cache hits) to tool
+    /// outputs; off with `MCP_INCLUDE_TIMING=0`
+    pub include_timing: bool,
+    /// Upstream requests a session may cause before its tool calls are refused
+    /// (`MCP_SESSION_REQUEST_LIMIT`, `MCP_SESSION_REQUEST_LIMIT_PER_HOUR`); unlimited by default
+    pub session_budget: BudgetLimits,
+    /// Local clock difference from the chain tolerated before clock-based nonces are
+    /// replaced with chain-derived ones (`MCP_CLOCK_SKEW_SECS`, default 30)
+    pub clock_skew_threshold: Duration,
+    /// Silence after which get_liveness reports the chain degraded or stalled
+    /// (`MCP_LIVENESS_DEGRADED_SECS`, default 15; `MCP_LIVENESS_STALLED_SECS`, default 60)
+    pub liveness: LivenessThresholds,
+    /// Largest arguments a tool call may ask of the node: `MCP_QUERY_LIMITS` picks a preset
+    /// (default, or small for nodes on modest hardware), and `MCP_MAX_HISTORY_LIMIT`,
+    /// `MCP_MAX_BLOCK_SPAN`, `MCP_MAX_BATCH_CALLS`, `MCP_MAX_CONTRACT_KEYS`,
+    /// `MCP_MAX_RICHLIST_LIMIT`, `MCP_MAX_FULL_HISTORY` and `MCP_MAX_TIME_PROBES` override
+    /// single limits
+    pub query_limits: QueryLimits,
+    /// Stdio only: how often node responses are compared with the parsed types in the
+    /// background (`MCP_DRIFT_CHECK_SECS`); off when unset
+    pub drift_check_interval: Option<Duration>,
+    /// Record the transactions create_transaction builds and mark them in lookups; off
+    /// with `MCP_RECORD_BUILT_TXS=0` for deployments that must not keep them
+    pub record_built_transactions: bool,
+    /// Stdio only: ed25519 seed, 32 bytes in base58 or hex, that signs every tool result
+    /// (`MCP_RESPONSE_SIGNING_SK`); results are unsigned when unset
+    pub response_signing_key: Option<Secret>,
+    /// Stdio only: base58 64-byte BLS secret that transfer_from_server_key spends from
+    /// (`AMADEUS_MCP_SIGNING_KEY`); the tool is hidden when unset
+    pub server_signing_key: Option<Secret>,
+    /// Number and date formatting of display text (`MCP_LOCALE`: en, de, fr, ja, uk)
+    pub locale: Locale,
+    /// Settings that were invalid but fell back to a default instead of failing, to be
+    /// logged at startup
+    pub warnings: Vec<String>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self::from_lookup(|_| None)
+    }
+}
+
+impl ServerConfig {
+    /// Builds the config from a key lookup so both the process environment and
+    /// worker bindings can feed it. Invalid values fall back to their defaults.
+    pub fn from_lookup<F: Fn(&str) -> Option<String>>(lookup: F) -> Self {
+        Self::parse(lookup).0
+    }
+
+    /// Like `from_lookup`, but rejects the whole config if any value is invalid.
+    pub fn try_from_lookup<F: Fn(&str) -> Option<String>>(lookup: F) -> Result<Self, String> {
+        match Self::parse(lookup) {
+            (config, errors) if errors.is_empty() => Ok(config),
+            (_, errors) => Err(errors.join("; ")),
+        }
+    }
+
+    fn parse<F: Fn(&str) -> Option<String>>(lookup: F) -> (Self, Vec<String>) {
+        let mut errors = Vec::new();
+        let mut warnings = Vec::new();
+        // An unknown locale only affects display text, so it falls back instead of failing
+        let locale = setting(&lookup, &mut warnings, "MCP_LOCALE", |v| v.parse()).unwrap_or_default();
+        let config = Self {
+            mainnet_url: setting(&lookup, &mut errors, "BLOCKCHAIN_URL", node_url)
+                .unwrap_or_else(|| DEFAULT_MAINNET_URL.to_string()),
+            testnet_url: setting(&lookup, &mut errors, "AMADEUS_TESTNET_RPC", node_url)
+                .unwrap_or_else(|| DEFAULT_TESTNET_URL.to_string()),
+            mainnet_fallback_urls: setting(&lookup, &mut errors, "BLOCKCHAIN_FALLBACK_URLS", |v| {
+                v.split(',').map(str::trim).filter(|u| !u.is_empty()).map(|u| node_url(u.to_string())).collect()
+            })
+            .unwrap_or_default(),
+            output_detail: setting(&lookup, &mut errors, "MCP_OUTPUT_DETAIL", |v| v.parse())
+                .unwrap_or_default(),
+            upstream_transport: setting(&lookup, &mut errors, "MCP_UPSTREAM_TRANSPORT", |v| v.parse())
+                .unwrap_or_default(),
+            api_paths: ApiPaths::new(
+                setting(&lookup, &mut errors, "BLOCKCHAIN_API_PREFIX", api_path::validate_prefix)
+                    .unwrap_or_else(|| DEFAULT_API_PREFIX.to_string()),
+                setting(&lookup, &mut errors, "BLOCKCHAIN_TX_API_PREFIX", api_path::validate_prefix)
+                    .unwrap_or_else(|| DEFAULT_TX_API_PREFIX.to_string()),
+            )
+            .unwrap_or_default(),
+            tool_filter: ToolFilter::from_lookup(&lookup),
+            redact_addresses: setting(&lookup, &mut errors, "MCP_REDACT_ADDRESSES", |v| v.parse())
+                .unwrap_or_default(),
+            tip_refresh: setting(&lookup, &mut errors, "MCP_TIP_REFRESH_SECS", |v| {
+                v.trim().parse::<u64>().map_err(|e| e.to_string())
+            })
+            .map_or(Some(DEFAULT_TIP_REFRESH), |secs| (secs > 0).then(|| Duration::from_secs(secs))),
+            disk_cache_dir: lookup("MCP_DISK_CACHE_DIR").map(|v| v.trim().to_string()).filter(|v| !v.is_empty()),
+            disk_cache_max_bytes: setting(&lookup, &mut errors, "MCP_DISK_CACHE_MAX_MB", |v| {
+                match v.trim().parse::<u64>() {
+                    Ok(0) => Err("must be at least 1".to_string()),
+                    Ok(mb) => Ok(mb * 1024 * 1024),
+                    Err(e) => Err(e.to_string()),
+                }
+            })
+            .unwrap_or(DEFAULT_DISK_CACHE_MAX_BYTES),
+            offline: lookup("MCP_OFFLINE")
+                .is_some_and(|v| v.trim() == "1" || v.trim().eq_ignore_ascii_case("true")),
+            offline_dir: lookup("MCP_OFFLINE_DIR")
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+                .unwrap_or_else(|| DEFAULT_OFFLINE_DIR.to_string()),
+            symbol_decimals: setting(&lookup, &mut errors, "SYMBOL_DECIMALS", |v| v.parse())
+                .unwrap_or_default(),
+            faucet_tokens: setting(&lookup, &mut errors, "FAUCET_TOKENS", |v| v.parse())
+                .unwrap_or_default(),
+            elicit_confirm: lookup("MCP_ELICIT_CONFIRM")
+                .is_none_or(|v| !(v.trim() == "0" || v.trim().eq_ignore_ascii_case("false"))),
+            strict_args: lookup("MCP_STRICT_ARGS")
+                .is_some_and(|v| v.trim() == "1" || v.trim().eq_ignore_ascii_case("true")),
+            key_tools: lookup("MCP_KEY_TOOLS")
+                .is_some_and(|v| v.trim() == "1" || v.trim().eq_ignore_ascii_case("true")),
+            include_timing: lookup("MCP_INCLUDE_TIMING")
+                .is_none_or(|v| !(v.trim() == "0" || v.trim().eq_ignore_ascii_case("false"))),
+            drift_check_interval: setting(&lookup, &mut errors, "MCP_DRIFT_CHECK_SECS", seconds),
+            record_built_transactions: lookup("MCP_RECORD_BUILT_TXS")
+                .is_none_or(|v| !(v.trim() == "0" || v.trim().eq_ignore_ascii_case("false"))),
+            clock_skew_threshold: setting(&lookup, &mut errors, "MCP_CLOCK_SKEW_SECS", seconds)
+                .unwrap_or(DEFAULT_CLOCK_SKEW_THRESHOLD),
+            liveness: {
+                let defaults = LivenessThresholds::default();
+                let degraded = setting(&lookup, &mut errors, "MCP_LIVENESS_DEGRADED_SECS", seconds).unwrap_or(defaults.degraded);
+                let stalled = setting(&lookup, &mut errors, "MCP_LIVENESS_STALLED_SECS", seconds).unwrap_or(defaults.stalled);
+                if degraded >= stalled {
+                    errors.push("MCP_LIVENESS_DEGRADED_SECS: must be below MCP_LIVENESS_STALLED_SECS".to_string());
+                }
+                LivenessThresholds { degraded, stalled }
+            },
+            query_limits: {
+                let preset = setting(&lookup, &mut errors, "MCP_QUERY_LIMITS", |v| v.parse::<LimitsPreset>())
+                    .unwrap_or_default()
+                    .limits();
+                let limit = |errors: &mut Vec<String>, key| setting(&lookup, errors, key, request_limit);
+                QueryLimits {
+                    max_history_limit: limit(&mut errors, "MCP_MAX_HISTORY_LIMIT")
+                        .map(|v| u32::try_from(v).unwrap_or(u32::MAX))
+                        .or(preset.max_history_limit),
+                    max_block_span: limit(&mut errors, "MCP_MAX_BLOCK_SPAN").unwrap_or(preset.max_block_span),
+                    max_batch_calls: limit(&mut errors, "MCP_MAX_BATCH_CALLS")
+                        .map_or(preset.max_batch_calls, |v| usize::try_from(v).unwrap_or(usize::MAX)),
+                    max_contract_keys: limit(&mut errors, "MCP_MAX_CONTRACT_KEYS").unwrap_or(preset.max_contract_keys),
+                    max_richlist_limit: limit(&mut errors, "MCP_MAX_RICHLIST_LIMIT").unwrap_or(preset.max_richlist_limit),
+                    max_full_history: limit(&mut errors, "MCP_MAX_FULL_HISTORY").unwrap_or(preset.max_full_history),
+                    max_time_probes: limit(&mut errors, "MCP_MAX_TIME_PROBES").unwrap_or(preset.max_time_probes),
+                }
+            },
+            session_budget: BudgetLimits {
+                total: setting(&lookup, &mut errors, "MCP_SESSION_REQUEST_LIMIT", request_limit),
+                per_hour: setting(&lookup, &mut errors, "MCP_SESSION_REQUEST_LIMIT_PER_HOUR", request_limit),
+            },
+            response_signing_key: setting(&lookup, &mut errors, "MCP_RESPONSE_SIGNING_SK", signing_seed),
+            server_signing_key: setting(&lookup, &mut errors, SERVER_KEY_VAR, server_key),
+            locale,
+            warnings: warnings.into_iter().map(|w| format!("{}; using en", w)).collect(),
+        };
+        (config, errors)
+    }
+
+    /// Reads the process environment, overlaid by the `KEY=VALUE` file at
+    /// `MCP_CONFIG_FILE` when set. Fails on unreadable files or invalid values.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load() -> Result<Self, String> {
+        let file = match std::env::var("MCP_CONFIG_FILE") {
+            Ok(path) => read_config_file(&path)?,
+            Err(_) => std::collections::HashMap::new(),
+        };
+        Self::try_from_lookup(|key| file.get(key).cloned().or_else(|| std::env::var(key).ok()))
+    }
+}
+
+/// Looks up and parses one setting, recording a parse failure in `errors`.
+fn setting<T, F, P>(lookup: &F, errors: &mut Vec<String>, key: &str, parse: P) -> Option<T>
+where
+    F: Fn(&str) -> Option<String>,
+    P: FnOnce(String) -> Result<T, String>,
+{
+    match lookup(key).map(parse) {
+        Some(Ok(value)) => Some(value),
+        Some(Err(e)) => {
+            errors.push(format!("{}: {}", key, e));
+            None
+        }
+        None => None,
+    }
+}
+
+fn request_limit(value: String) -> Result<u64, String> {
+    match value.trim().parse::<u64>() {
+        Ok(0) => Err("must be at least 1".to_string()),
+        Ok(limit) => Ok(limit),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn seconds(value: String) -> Result<Duration, String> {
+    match value.trim().parse::<u64>() {
+        Ok(0) => Err("must be at least 1".to_string()),
+        Ok(secs) => Ok(Duration::from_secs(secs)),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Bytes of an ed25519 seed.
+pub const SIGNING_SEED_BYTES: usize = 32;
+
+/// Decodes an ed25519 seed given in hex (64 digits) or base58.
+pub fn decode_signing_seed(value: &str) -> Result<Vec<u8>, String> {
+    let value = value.trim();
+    let bytes = match hex::decode(value) {
+        Ok(bytes) if value.len() == SIGNING_SEED_BYTES * 2 => bytes,
+        _ => bs58::decode(value).into_vec().map_err(|_| "expected a hex or base58 seed".to_string())?,
+    };
+    if bytes.len() != SIGNING_SEED_BYTES {
+        return Err(format!("expected a {}-byte ed25519 seed, got {} bytes", SIGNING_SEED_BYTES, bytes.len()));
+    }
+    Ok(bytes)
+}
+
+fn signing_seed(value: String) -> Result<Secret, String> {
+    let mut seed = decode_signing_seed(&value)?;
+    seed.zeroize();
+    Ok(Secret::new(value.trim().to_string()))
+}
+
+/// Checks the key derives without keeping any of it in the error.
+fn server_key(value: String) -> Result<Secret, String> {
+    let secret = Secret::new(value);
+    ServerKey::from_secret(&secret)?;
+    Ok(secret)
+}
+
+fn node_url(value: String) -> Result<String, String> {
+    let value = value.trim().trim_end_matches('/').to_string();
+    if value.starts_with("http://") || value.starts_with("https://") {
+        Ok(value)
+    } else {
+        Err(format!("expected an http(s) URL, got {}", value))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_config_file(path: &str) -> Result<std::collections::HashMap<String, String>, String> {
+    let contents = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    contents
+        .lines()
+        .map(str::trim)
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(i, line)| {
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("{}:{}: expected KEY=VALUE", path, i + 1))?;
+            Ok((key.trim().to_string(), value.trim().trim_matches('"').to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn config(settings: &[(&str, &str)]) -> Result<ServerConfig, String> {
+        let settings: HashMap<String, String> =
+            settings.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect();
+        ServerConfig::try_from_lookup(|key| settings.get(key).cloned())
+    }
+
+    #[test]
+    fn transport_selection_falls_back_to_fetch_without_a_binding() {
+        for (transport, binding, expected) in [
+            (UpstreamTransport::Auto, Some("svc"), Some("svc")),
+            (UpstreamTransport::Auto, None, None),
+            (UpstreamTransport::Service, Some("svc"), Some("svc")),
+            (UpstreamTransport::Service, None, None),
+            (UpstreamTransport::Fetch, Some("svc"), None),
+            (UpstreamTransport::Fetch, None, None),
+        ] {
+            assert_eq!(transport.select(binding), expected, "{:?} {:?}", transport, binding);
+        }
+    }
+
+    #[test]
+    fn transport_parses_from_the_environment() {
+        assert_eq!(config(&[]).unwrap().upstream_transport, UpstreamTransport::Auto);
+        for (value, expected) in
+            [("auto", UpstreamTransport::Auto), ("FETCH", UpstreamTransport::Fetch), ("Service", UpstreamTransport::Service)]
+        {
+            let config = config(&[("MCP_UPSTREAM_TRANSPORT", value)]).unwrap();
+            assert_eq!(config.upstream_transport, expected, "{}", value);
+        }
+        let err = config(&[("MCP_UPSTREAM_TRANSPORT", "binding")]).unwrap_err();
+        assert!(err.contains("MCP_UPSTREAM_TRANSPORT"), "{}", err);
+    }
+
+    #[test]
+    fn only_urls_under_the_base_go_through_the_binding() {
+        let base = "https://nodes.amadeus.bot";
+        for url in [
+            "https://nodes.amadeus.bot",
+            "https://nodes.amadeus.bot/",
+            "https://nodes.amadeus.bot/api/chain/tip",
+            "https://nodes.amadeus.bot?x=1",
+        ] {
+            assert!(UpstreamTransport::is_bound_url(base, url), "{}", url);
+            assert!(UpstreamTransport::is_bound_url(&format!("{}/", base), url), "{}", url);
+        }
+        for url in [
+            "https://nodes.amadeus.bot.evil.example/api/chain/tip",
+            "https://nodes.amadeus.botnet/api",
+            "https://testnet.amadeus.bot/api/chain/tip",
+            "http://nodes.amadeus.bot/api/chain/tip",
+        ] {
+            assert!(!UpstreamTransport::is_bound_url(base, url), "{}", url);
+        }
+
+        let prefixed = "https://gateway.example/amadeus";
+        assert!(UpstreamTransport::is_bound_url(prefixed, "https://gateway.example/amadeus/api/chain/tip"));
+        assert!(!UpstreamTransport::is_bound_url(prefixed, "https://gateway.example/amadeus-testnet/api/chain/tip"));
+    }
+
+    #[test]
+    fn api_prefixes_are_validated_at_startup() {
+        let paths = config(&[("BLOCKCHAIN_API_PREFIX", "/node/v2"), ("BLOCKCHAIN_TX_API_PREFIX", "/node/v2/tx")]).unwrap().api_paths;
+        assert_eq!((paths.api("chain/tip"), paths.tx("tx/submit")), ("/node/v2/chain/tip".into(), "/node/v2/tx/tx/submit".into()));
+        assert_eq!(config(&[]).unwrap().api_paths, ApiPaths::default());
+
+        for (key, value) in [("BLOCKCHAIN_API_PREFIX", "api"), ("BLOCKCHAIN_TX_API_PREFIX", "/api/v1/")] {
+            let err = config(&[(key, value)]).unwrap_err();
+            assert!(err.starts_with(key), "{}", err);
+        }
+    }
+
+    #[test]
+    fn an_unsupported_locale_falls_back_to_en_with_a_warning() {
+        assert_eq!(config(&[("MCP_LOCALE", "de-DE")]).unwrap().locale, Locale::De);
+        assert!(config(&[]).unwrap().warnings.is_empty());
+
+        let config = config(&[("MCP_LOCALE", "es")]).unwrap();
+        assert_eq!(config.locale, Locale::En);
+        assert_eq!(
+            config.warnings,
+            vec!["MCP_LOCALE: unsupported locale es, expected one of en, de, fr, ja, uk; using en".to_string()]
+        );
+    }
+
+    #[test]
+    fn a_bad_server_signing_key_is_reported_without_its_value() {
+        let value = bs58::encode([7u8; 32]).into_string();
+        let err = config(&[(SERVER_KEY_VAR, &value)]).unwrap_err();
+        assert_eq!(err, "AMADEUS_MCP_SIGNING_KEY: secret key must be 64 bytes");
+        assert!(!err.contains(&value));
+
+        let valid = bs58::encode([7u8; 64]).into_string();
+        assert!(config(&[(SERVER_KEY_VAR, &valid)]).unwrap().server_signing_key.is_some());
+    }
+}
@@ -0,0 +1,317 @@
+//! `amadeus-mcp conformance`: drives the server through a whole MCP session the way a
+//! client would (initialize, tools/list with its cursors, tools/call, resources, resource
+//! templates and prompts) and checks the protocol framing of every answer. Dev builds only
+//! (`--features dev-tools`).
+//!
+//! The checks talk to the server through [`Exchange`], one JSON-RPC message at a time, so
+//! another transport (HTTP) only needs its own `Exchange`. [`in_process`] runs them over an
+//! in-memory pipe against a server in offline mode with an empty fixture directory, so
+//! nothing reaches the network and node-backed tools fail with `offline_mode`.
+
+use crate::blockchain::BlockchainClient;
+use crate::catalog;
+use crate::config::ServerConfig;
+use crate::server::BlockchainMcpServer;
+use rmcp::ServiceExt;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, DuplexStream, Lines, ReadHalf, WriteHalf};
+
+const PROTOCOL_VERSION: &str = "2024-11-05";
+/// Upper bound on tools/list pages followed, in case the cursor never ends.
+const MAX_PAGES: usize = 50;
+const PIPE_BUFFER_BYTES: usize = 1 << 20;
+
+/// One side of an MCP session.
+pub trait Exchange {
+    /// Sends a request and waits for the response with the same id.
+    fn request(&mut self, message: Value) -> impl std::future::Future<Output = Result<Value, String>> + Send;
+    fn notify(&mut self, message: Value) -> impl std::future::Future<Output = Result<(), String>> + Send;
+}
+
+/// Newline-delimited JSON-RPC over an in-memory pipe, as the stdio transport frames it.
+pub struct DuplexExchange {
+    lines: Lines<BufReader<ReadHalf<DuplexStream>>>,
+    writer: WriteHalf<DuplexStream>,
+}
+
+impl DuplexExchange {
+    async fn send(&mut self, message: &Value) -> Result<(), String> {
+        let mut line = message.to_string();
+        line.push('\n');
+        self.writer.write_all(line.as_bytes()).await.map_err(|e| e.to_string())?;
+        self.writer.flush().await.map_err(|e| e.to_string())
+    }
+}
+
+impl Exchange for DuplexExchange {
+    async fn request(&mut self, message: Value) -> Result<Value, String> {
+        self.send(&message).await?;
+        // Notifications and server-initiated requests in between are skipped
+        loop {
+            let line = self
+                .lines
+                .next_line()
+                .await
+                .map_err(|e| e.to_string())?
+                .ok_or("server closed the session")?;
+            let reply: Value = serde_json::from_str(&line).map_err(|e| format!("unparsable frame {}: {}", line, e))?;
+            if reply.get("id") == message.get("id") && reply.get("method").is_none() {
+                return Ok(reply);
+            }
+        }
+    }
+
+    async fn notify(&mut self, message: Value) -> Result<(), String> {
+        self.send(&message).await
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConformanceStep {
+    pub step: String,
+    pub passed: bool,
+    pub detail: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConformanceReport {
+    pub passed: bool,
+    pub steps: Vec<ConformanceStep>,
+}
+
+struct Session<'a, E> {
+    exchange: &'a mut E,
+    next_id: u64,
+    steps: Vec<ConformanceStep>,
+}
+
+impl<E: Exchange> Session<'_, E> {
+    async fn call(&mut self, method: &str, params: Value) -> Result<Value, String> {
+        self.next_id += 1;
+        let reply = self
+            .exchange
+            .request(json!({ "jsonrpc": "2.0", "id": self.next_id, "method": method, "params": params }))
+            .await?;
+        if reply["jsonrpc"] != "2.0" {
+            return Err(format!("{} reply is not JSON-RPC 2.0: {}", method, reply));
+        }
+        Ok(reply)
+    }
+
+    /// The `result` of a successful call.
+    async fn result(&mut self, method: &str, params: Value) -> Result<Value, String> {
+        let reply = self.call(method, params).await?;
+        match (reply.get("result"), reply.get("error")) {
+            (Some(result), None) => Ok(result.clone()),
+            _ => Err(format!("{} failed: {}", method, reply["error"])),
+        }
+    }
+
+    /// The `error` of a call that must fail.
+    async fn error(&mut self, method: &str, params: Value) -> Result<Value, String> {
+        let reply = self.call(method, params).await?;
+        match (reply.get("result"), reply.get("error")) {
+            (None, Some(error)) if error["code"].is_i64() && error["message"].is_string() => Ok(error.clone()),
+            _ => Err(format!("{} should fail with a JSON-RPC error, got {}", method, reply)),
+        }
+    }
+
+    fn record(&mut self, step: &str, outcome: Result<String, String>) -> bool {
+        let passed = outcome.is_ok();
+        let detail = outcome.unwrap_or_else(|e| e);
+        self.steps.push(ConformanceStep { step: step.to_string(), passed, detail });
+        passed
+    }
+}
+
+/// Runs every check over `exchange`. Stops after a failed initialize, since nothing else
+/// is meaningful without a session; otherwise every step runs and is reported.
+pub async fn run<E: Exchange>(exchange: &mut E) -> ConformanceReport {
+    let mut session = Session { exchange, next_id: 0, steps: Vec::new() };
+
+    let initialized = initialize(&mut session).await;
+    if session.record("initialize", initialized) {
+        let outcome = list_tools(&mut session).await;
+        session.record("tools/list", outcome);
+        let outcome = bad_cursor(&mut session).await;
+        session.record("tools/list bad cursor", outcome);
+        let outcome = call_tool(&mut session).await;
+        session.record("tools/call", outcome);
+        let outcome = failing_tool(&mut session).await;
+        session.record("tools/call failing", outcome);
+        let outcome = unknown_tool(&mut session).await;
+        session.record("tools/call unknown tool", outcome);
+        let outcome = resources(&mut session).await;
+        session.record("resources", outcome);
+        let outcome = resource_templates(&mut session).await;
+        session.record("resource templates", outcome);
+        let outcome = prompts(&mut session).await;
+        session.record("prompts", outcome);
+    }
+
+    let steps = session.steps;
+    ConformanceReport { passed: steps.iter().all(|s| s.passed), steps }
+}
+
+async fn initialize<E: Exchange>(session: &mut Session<'_, E>) -> Result<String, String> {
+    let result = session
+        .result(
+            "initialize",
+            json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": {},
+                "clientInfo": { "name": "amadeus-mcp-conformance", "version": env!("CARGO_PKG_VERSION") }
+            }),
+        )
+        .await?;
+    let version = result["protocolVersion"].as_str().ok_or("no protocolVersion")?;
+    for capability in ["tools", "resources", "prompts"] {
+        if !result["capabilities"][capability].is_object() {
+            return Err(format!("capability {} not advertised: {}", capability, result["capabilities"]));
+        }
+    }
+    if result["serverInfo"]["name"].as_str().is_none() {
+        return Err("no serverInfo.name".to_string());
+    }
+    session
+        .exchange
+        .notify(json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }))
+        .await?;
+    Ok(format!("protocol {}", version))
+}
+
+async fn list_tools<E: Exchange>(session: &mut Session<'_, E>) -> Result<String, String> {
+    let mut names = HashSet::new();
+    let mut cursor: Option<String> = None;
+    for page in 1..=MAX_PAGES {
+        let params = cursor.as_ref().map_or_else(|| json!({}), |c| json!({ "cursor": c }));
+        let result = session.result("tools/list", params).await?;
+        let tools = result["tools"].as_array().ok_or("tools is not an array")?;
+        if tools.len() > catalog::MAX_PAGE_SIZE {
+            return Err(format!("page {} has {} tools, above the page size", page, tools.len()));
+        }
+        for tool in tools {
+            let name = tool["name"].as_str().ok_or("tool without a name")?;
+            if tool["inputSchema"]["type"] != "object" {
+                return Err(format!("{} has no object inputSchema", name));
+            }
+            if !names.insert(name.to_string()) {
+                return Err(format!("{} listed twice", name));
+            }
+        }
+        match result.get("nextCursor").and_then(Value::as_str) {
+            Some(next) => cursor = Some(next.to_string()),
+            None if page == 1 && names.len() > catalog::DEFAULT_PAGE_SIZE => {
+                return Err(format!("{} tools on one page without a cursor", names.len()))
+            }
+            None => return Ok(format!("{} tools over {} pages", names.len(), page)),
+        }
+    }
+    Err(format!("cursor did not end after {} pages", MAX_PAGES))
+}
+
+async fn bad_cursor<E: Exchange>(session: &mut Session<'_, E>) -> Result<String, String> {
+    let error = session.error("tools/list", json!({ "cursor": "not-a-cursor" })).await?;
+    expect_code(&error, -32602)?;
+    Ok(format!("rejected with {}", error["message"]))
+}
+
+async fn call_tool<E: Exchange>(session: &mut Session<'_, E>) -> Result<String, String> {
+    let result = session.result("tools/call", json!({ "name": "get_amadeus_docs", "arguments": {} })).await?;
+    if result["isError"] == true {
+        return Err(format!("tool reported an error: {}", result));
+    }
+    let content = result["content"].as_array().filter(|c| !c.is_empty()).ok_or("no content")?;
+    let text = content[0]["text"].as_str().filter(|_| content[0]["type"] == "text").ok_or("first content is not text")?;
+    serde_json::from_str::<Value>(text).map_err(|e| format!("text content is not JSON: {}", e))?;
+    Ok(format!("{} content item(s)", content.len()))
+}
+
+async fn failing_tool<E: Exchange>(session: &mut Session<'_, E>) -> Result<String, String> {
+    let error = session
+        .error("tools/call", json!({ "name": "get_account_balance", "arguments": { "address": "not-an-address" } }))
+        .await?;
+    expect_code(&error, -32602)?;
+    let action = error["data"]["recovery"]["action"].as_str().ok_or("error data has no recovery hint")?;
+    Ok(format!("{} with recovery {}", error["message"], action))
+}
+
+async fn unknown_tool<E: Exchange>(session: &mut Session<'_, E>) -> Result<String, String> {
+    let error = session.error("tools/call", json!({ "name": "no_such_tool", "arguments": {} })).await?;
+    Ok(format!("rejected with {}", error["message"]))
+}
+
+async fn resources<E: Exchange>(session: &mut Session<'_, E>) -> Result<String, String> {
+    let result = session.result("resources/list", json!({})).await?;
+    let uris: Vec<&str> = result["resources"]
+        .as_array()
+        .ok_or("resources is not an array")?
+        .iter()
+        .filter_map(|r| r["uri"].as_str())
+        .collect();
+    if !uris.contains(&catalog::ERROR_RECOVERY_URI) {
+        return Err(format!("{} not listed", catalog::ERROR_RECOVERY_URI));
+    }
+    let read = session.result("resources/read", json!({ "uri": catalog::ERROR_RECOVERY_URI })).await?;
+    read["contents"][0]["text"].as_str().ok_or("resource has no text contents")?;
+    let error = session.error("resources/read", json!({ "uri": "amadeus://no-such-resource" })).await?;
+    Ok(format!("{} listed, unknown URI rejected with {}", uris.len(), error["message"]))
+}
+
+async fn resource_templates<E: Exchange>(session: &mut Session<'_, E>) -> Result<String, String> {
+    let result = session.result("resources/templates/list", json!({})).await?;
+    let templates = result["resourceTemplates"].as_array().ok_or("resourceTemplates is not an array")?;
+    let docs = format!("{}{{name}}", catalog::TOOL_DOCS_URI);
+    if !templates.iter().any(|t| t["uriTemplate"] == docs.as_str()) {
+        return Err(format!("{} not listed", docs));
+    }
+    let uri = format!("{}get_chain_stats", catalog::TOOL_DOCS_URI);
+    let read = session.result("resources/read", json!({ "uri": uri })).await?;
+    read["contents"][0]["text"].as_str().ok_or("tool doc has no text contents")?;
+    Ok(format!("{} template(s), tool doc read", templates.len()))
+}
+
+async fn prompts<E: Exchange>(session: &mut Session<'_, E>) -> Result<String, String> {
+    let result = session.result("prompts/list", json!({})).await?;
+    let prompts = result["prompts"].as_array().ok_or("prompts is not an array")?;
+    for prompt in prompts {
+        let name = prompt["name"].as_str().ok_or("prompt without a name")?;
+        let got = session.result("prompts/get", json!({ "name": name })).await?;
+        got["messages"].as_array().ok_or_else(|| format!("prompt {} has no messages", name))?;
+    }
+    let error = session.error("prompts/get", json!({ "name": "no_such_prompt" })).await?;
+    expect_code(&error, -32602)?;
+    Ok(format!("{} prompt(s), unknown prompt rejected", prompts.len()))
+}
+
+fn expect_code(error: &Value, code: i64) -> Result<(), String> {
+    match error["code"].as_i64() {
+        Some(c) if c == code => Ok(()),
+        _ => Err(format!("expected error code {}, got {}", code, error)),
+    }
+}
+
+/// Runs the checks against a server in this process. `config` is switched to offline
+/// mode over an empty fixture directory so the run never touches the network.
+pub async fn in_process(mut config: ServerConfig) -> Result<ConformanceReport, String> {
+    let fixtures = std::env::temp_dir().join(format!("amadeus-mcp-conformance-{}", std::process::id()));
+    std::fs::create_dir_all(&fixtures).map_err(|e| e.to_string())?;
+    config.offline = true;
+    config.offline_dir = fixtures.display().to_string();
+    let client = BlockchainClient::from_config(&config).map_err(|e| e.to_string())?;
+
+    let (client_side, server_side) = tokio::io::duplex(PIPE_BUFFER_BYTES);
+    let service = tokio::spawn(BlockchainMcpServer::new(client, config).serve(tokio::io::split(server_side)));
+    let (reader, writer) = tokio::io::split(client_side);
+    let mut exchange = DuplexExchange { lines: BufReader::new(reader).lines(), writer };
+    let report = run(&mut exchange).await;
+
+    drop(exchange);
+    if let Ok(Ok(service)) = service.await {
+        let _ = service.cancel().await;
+    }
+    let _ = std::fs::remove_dir(&fixtures);
+    Ok(report)
+}
@@ -0,0 +1,294 @@
+//! `amadeus-mcp fixtures refresh`: re-records the node responses under `tests/fixtures`
+//! from the configured node. Dev builds only (`--features dev-tools`).
+//!
+//! Responses are normalized before they are written so a refresh only shows a diff when
+//! the node API changed shape, not because the chain moved on. The rules in [`RULES`] are
+//! applied to every object key at any depth:
+//!
+//! - heights and slots become `"<height>"`
+//! - timestamps become `"<timestamp>"`
+//! - chain-wide metrics (supply, hash rate, pool size) become `"<metric>"`
+//! - in the `tip` scenario only, hashes and signatures become `"<hash>"`, since the tip
+//!   is a different entry on every refresh
+//!
+//! A key is only replaced when its value is a number or a string; objects and arrays
+//! under a matching key are normalized recursively instead.
+
+use crate::blockchain::BlockchainClient;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+pub const FIXTURES_DIR: &str = "tests/fixtures";
+/// Account whose balances and history are recorded, unless `FIXTURE_ACCOUNT` is set.
+pub const DEFAULT_FIXTURE_ACCOUNT: &str = "29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw";
+/// Entries below the tip the search for a finalized entry with transactions starts at.
+const FINALIZED_DEPTH: u64 = 32;
+/// Heights tried, walking down, before giving up on finding an entry with transactions.
+const TXS_SEARCH_LIMIT: u64 = 50;
+/// Differing paths listed per fixture in the summary; the rest are counted.
+const MAX_LISTED_DIFFS: usize = 10;
+
+/// Replaces the value of any of `keys` with `placeholder`, in the `only` scenarios or in
+/// all of them when `only` is empty.
+#[derive(Debug, Clone, Copy)]
+pub struct NormalizeRule {
+    pub keys: &'static [&'static str],
+    pub placeholder: &'static str,
+    pub only: &'static [&'static str],
+}
+
+pub const RULES: &[NormalizeRule] = &[
+    NormalizeRule { keys: &["height", "entry_height", "slot", "prev_slot"], placeholder: "<height>", only: &[] },
+    NormalizeRule { keys: &["timestamp", "ts", "ts_m", "recorded_at", "created_at"], placeholder: "<timestamp>", only: &[] },
+    NormalizeRule {
+        keys: &["pflops", "burned", "circulating", "tx_pool_size", "txs_per_sec", "diff_bits", "score"],
+        placeholder: "<metric>",
+        only: &[],
+    },
+    NormalizeRule {
+        keys: &["hash", "prev_hash", "dr", "vr", "signer", "root_tx", "root_validator", "mut_hash", "signature"],
+        placeholder: "<hash>",
+        only: &["tip"],
+    },
+];
+
+/// Applies [`RULES`] for `scenario` to `value` in place.
+pub fn normalize(scenario: &str, value: &mut Value) {
+    match value {
+        Value::Object(fields) => {
+            for (key, field) in fields.iter_mut() {
+                let rule = RULES
+                    .iter()
+                    .filter(|r| r.only.is_empty() || r.only.contains(&scenario))
+                    .find(|r| r.keys.contains(&key.as_str()));
+                match rule {
+                    Some(rule) if field.is_number() || field.is_string() => *field = Value::from(rule.placeholder),
+                    _ => normalize(scenario, field),
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| normalize(scenario, item)),
+        _ => {}
+    }
+}
+
+/// JSON paths (`$.entry.header.hash`) where `old` and `new` differ.
+pub fn diff_paths(old: &Value, new: &Value) -> Vec<String> {
+    let mut paths = Vec::new();
+    collect_diffs("$", old, new, &mut paths);
+    paths
+}
+
+fn collect_diffs(path: &str, old: &Value, new: &Value, paths: &mut Vec<String>) {
+    match (old, new) {
+        (Value::Object(a), Value::Object(b)) => {
+            for (key, value) in a {
+                match b.get(key) {
+                    Some(other) => collect_diffs(&format!("{}.{}", path, key), value, other, paths),
+                    None => paths.push(format!("{}.{} (removed)", path, key)),
+                }
+            }
+            paths.extend(b.keys().filter(|k| !a.contains_key(*k)).map(|k| format!("{}.{} (added)", path, k)));
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            for (i, (x, y)) in a.iter().zip(b).enumerate() {
+                collect_diffs(&format!("{}[{}]", path, i), x, y, paths);
+            }
+            if a.len() != b.len() {
+                paths.push(format!("{} (length {} -> {})", path, a.len(), b.len()));
+            }
+        }
+        (a, b) if a != b => paths.push(path.to_string()),
+        _ => {}
+    }
+}
+
+/// Runs `fixtures <subcommand>` and returns the text to print.
+pub async fn command(args: &[String], client: &BlockchainClient, url: &str) -> anyhow::Result<String> {
+    match args.first().map(String::as_str) {
+        Some("refresh") => refresh(client, url, Path::new(FIXTURES_DIR)).await,
+        _ => anyhow::bail!("usage: amadeus-mcp fixtures refresh"),
+    }
+}
+
+/// Fixture changes not yet committed would be silently overwritten, so they stop the refresh.
+fn ensure_clean(dir: &Path) -> anyhow::Result<()> {
+    let output = std::process::Command::new("git")
+        .args(["status", "--porcelain", "--"])
+        .arg(dir)
+        .output()
+        .map_err(|e| anyhow::anyhow!("cannot check {} with git: {}", dir.display(), e))?;
+    if !output.status.success() {
+        anyhow::bail!("git status failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    let changes = String::from_utf8_lossy(&output.stdout);
+    if !changes.trim().is_empty() {
+        anyhow::bail!("{} has uncommitted changes, commit or discard them first:\n{}", dir.display(), changes.trim_end());
+    }
+    Ok(())
+}
+
+async fn refresh(client: &BlockchainClient, url: &str, dir: &Path) -> anyhow::Result<String> {
+    ensure_clean(dir)?;
+    std::fs::create_dir_all(dir)?;
+
+    let paths = client.api_paths();
+    let account = std::env::var("FIXTURE_ACCOUNT").unwrap_or_else(|_| DEFAULT_FIXTURE_ACCOUNT.to_string());
+    let tip = client.raw_query(&paths.api("chain/tip"), url).await?;
+    let tip_height = tip["entry"]["header"]["height"]
+        .as_u64()
+        .ok_or_else(|| anyhow::anyhow!("tip entry has no height"))?;
+
+    let mut scenarios: Vec<(&str, Value)> = vec![
+        ("stats", client.raw_query(&paths.api("chain/stats"), url).await?),
+        ("tip", tip),
+        ("block_with_txs", finalized_block_with_txs(client, url, tip_height).await?),
+        ("balances", client.raw_query(&paths.api(&format!("wallet/balance_all/{}", account)), url).await?),
+        ("history", client.raw_query(&paths.api(&format!("chain/tx_events_by_account/{}?limit=10", account)), url).await?),
+        ("trainers", client.raw_query(&paths.api("peer/trainers"), url).await?),
+        ("richlist", client.raw_query(&paths.api("contract/richlist"), url).await?),
+    ];
+    // Contract storage layout is deployment-specific, so this one is opt-in
+    if let Ok(contract_key) = std::env::var("FIXTURE_CONTRACT_KEY") {
+        scenarios.push(("contract_state", client.raw_query(&paths.api(&format!("contract/get/{}", contract_key)), url).await?));
+    }
+
+    let mut summary = Vec::new();
+    for (name, mut value) in scenarios {
+        normalize(name, &mut value);
+        let path: PathBuf = dir.join(format!("{}.json", name));
+        let previous = std::fs::read_to_string(&path).ok().and_then(|s| serde_json::from_str::<Value>(&s).ok());
+        std::fs::write(&path, serde_json::to_string_pretty(&value)? + "\n")?;
+
+        summary.push(match previous {
+            None => format!("{}: new", name),
+            Some(previous) => {
+                let diffs = diff_paths(&previous, &value);
+                if diffs.is_empty() {
+                    format!("{}: unchanged", name)
+                } else {
+                    let mut line = format!("{}: {} change(s)", name, diffs.len());
+                    for path in diffs.iter().take(MAX_LISTED_DIFFS) {
+                        line.push_str(&format!("\n  {}", path));
+                    }
+                    if diffs.len() > MAX_LISTED_DIFFS {
+                        line.push_str(&format!("\n  ... and {} more", diffs.len() - MAX_LISTED_DIFFS));
+                    }
+                    line
+                }
+            }
+        });
+    }
+    Ok(summary.join("\n"))
+}
+
+/// The first entry with transactions at or below `FINALIZED_DEPTH` under the tip.
+async fn finalized_block_with_txs(client: &BlockchainClient, url: &str, tip_height: u64) -> anyhow::Result<Value> {
+    let paths = client.api_paths();
+    let start = tip_height.saturating_sub(FINALIZED_DEPTH);
+    for height in (start.saturating_sub(TXS_SEARCH_LIMIT)..=start).rev() {
+        let block = client.raw_query(&paths.api(&format!("chain/height_with_txs/{}", height)), url).await?;
+        if has_txs(&block) {
+            return Ok(block);
+        }
+    }
+    anyhow::bail!("no entry with transactions within {} heights below {}", TXS_SEARCH_LIMIT, start)
+}
+
+fn has_txs(value: &Value) -> bool {
+    match value {
+        Value::Object(fields) => fields
+            .iter()
+            .any(|(key, field)| (key == "txs" && field.as_array().is_some_and(|t| !t.is_empty())) || has_txs(field)),
+        Value::Array(items) => items.iter().any(has_txs),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tip(height: u64, hash: &str, slot: u64) -> Value {
+        json!({
+            "error": "ok",
+            "entry": {
+                "hash": hash,
+                "header": { "height": height, "slot": slot, "prev_hash": format!("prev-{}", hash), "signer": "validator" },
+                "tx_count": 3
+            }
+        })
+    }
+
+    #[test]
+    fn volatile_fields_become_placeholders() {
+        let mut stats = json!({
+            "stats": { "height": 123, "pflops": 4.2, "circulating": "1000", "timestamp": 1_718_000_000, "name": "mainnet" },
+            "txs": [{ "entry_height": 9, "ts_m": 1_718_000_000_000u64, "hash": "keep" }]
+        });
+        normalize("stats", &mut stats);
+        assert_eq!(
+            stats,
+            json!({
+                "stats": { "height": "<height>", "pflops": "<metric>", "circulating": "<metric>", "timestamp": "<timestamp>", "name": "mainnet" },
+                "txs": [{ "entry_height": "<height>", "ts_m": "<timestamp>", "hash": "keep" }]
+            })
+        );
+    }
+
+    #[test]
+    fn hashes_are_only_replaced_in_the_tip_scenario() {
+        let mut at_tip = tip(10, "abc", 20);
+        normalize("tip", &mut at_tip);
+        assert_eq!(at_tip["entry"]["hash"], "<hash>");
+        assert_eq!(at_tip["entry"]["header"]["prev_hash"], "<hash>");
+        assert_eq!(at_tip["entry"]["header"]["signer"], "<hash>");
+        assert_eq!(at_tip["entry"]["tx_count"], 3);
+
+        let mut block = tip(10, "abc", 20);
+        normalize("block_with_txs", &mut block);
+        assert_eq!(block["entry"]["hash"], "abc");
+        assert_eq!(block["entry"]["header"]["height"], "<height>");
+    }
+
+    #[test]
+    fn structured_values_under_a_matching_key_are_walked_not_replaced() {
+        let mut value = json!({ "height": { "height": 5, "label": "x" }, "timestamp": null, "slot": [1, 2] });
+        normalize("stats", &mut value);
+        assert_eq!(value, json!({ "height": { "height": "<height>", "label": "x" }, "timestamp": null, "slot": [1, 2] }));
+    }
+
+    #[test]
+    fn refreshes_of_a_moving_chain_normalize_to_the_same_fixture() {
+        let (mut earlier, mut later) = (tip(10, "abc", 20), tip(11, "def", 22));
+        normalize("tip", &mut earlier);
+        normalize("tip", &mut later);
+        assert_eq!(earlier, later);
+        assert!(diff_paths(&earlier, &later).is_empty());
+
+        // Normalizing again changes nothing
+        let normalized = earlier.clone();
+        normalize("tip", &mut earlier);
+        assert_eq!(earlier, normalized);
+    }
+
+    #[test]
+    fn diffs_name_changed_added_and_removed_paths() {
+        let old = json!({ "entry": { "hash": "a", "gone": 1 }, "txs": [1, 2], "same": true });
+        let new = json!({ "entry": { "hash": "b", "new": 1 }, "txs": [1, 3, 4], "same": true });
+        assert_eq!(
+            diff_paths(&old, &new),
+            vec!["$.entry.gone (removed)", "$.entry.hash", "$.entry.new (added)", "$.txs[1]", "$.txs (length 2 -> 3)"]
+        );
+        assert!(diff_paths(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn entries_with_transactions_are_found_at_any_depth() {
+        assert!(has_txs(&json!({ "entry": { "txs": [{ "hash": "a" }] } })));
+        assert!(has_txs(&json!([{ "txs": [1] }])));
+        assert!(!has_txs(&json!({ "entry": { "txs": [] } })));
+        assert!(!has_txs(&json!({ "entry": { "txs": "none" } })));
+    }
+}
@@ -1,10 +1,91 @@
+//! Amadeus blockchain tools for MCP, as a stdio server binary, a Cloudflare Worker, or a
+//! component embedded in another rmcp server.
+//!
+//! To embed, keep a [`BlockchainMcpServer`] next to your own tools and delegate to it from
+//! your `ServerHandler`. It is cheap to clone and shares its state between clones.
+//!
+//! ```no_run
+//! use amadeus_mcp::{BlockchainClient, BlockchainMcpServer, ServerConfig};
+//! use rmcp::{model::*, service::RequestContext, ErrorData as McpError, RoleServer, ServerHandler};
+//!
+//! #[derive(Clone)]
+//! struct Host {
+//!     amadeus: BlockchainMcpServer,
+//! }
+//!
+//! impl ServerHandler for Host {
+//!     async fn list_tools(
+//!         &self,
+//!         _request: Option<PaginatedRequestParam>,
+//!         _context: RequestContext<RoleServer>,
+//!     ) -> Result<ListToolsResult, McpError> {
+//!         let mut tools = self.amadeus.tools();
+//!         tools.push(Tool::new("ping", "Replies with pong", std::sync::Arc::new(Default::default())));
+//!         Ok(ListToolsResult::with_all_items(tools))
+//!     }
+//!
+//!     async fn call_tool(
+//!         &self,
+//!         request: CallToolRequestParam,
+//!         context: RequestContext<RoleServer>,
+//!     ) -> Result<CallToolResult, McpError> {
+//!         if self.amadeus.has_tool(&request.name) {
+//!             return self.amadeus.call_tool(request, context).await;
+//!         }
+//!         match request.name.as_ref() {
+//!             "ping" => Ok(CallToolResult::success(vec![Content::text("pong")])),
+//!             _ => Err(McpError::invalid_params("unknown_tool", None)),
+//!         }
+//!     }
+//!
+//!     async fn read_resource(
+//!         &self,
+//!         request: ReadResourceRequestParam,
+//!         context: RequestContext<RoleServer>,
+//!     ) -> Result<ReadResourceResult, McpError> {
+//!         if BlockchainMcpServer::has_resource(&request.uri) {
+//!             return self.amadeus.read_resource(request, context).await;
+//!         }
+//!         Err(McpError::invalid_params("unknown_resource", None))
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! let config = ServerConfig::load().map_err(anyhow::Error::msg)?;
+//! let client = BlockchainClient::from_config(&config)?;
+//! let host = Host { amadeus: BlockchainMcpServer::new(client, config) };
+//! # let _ = host;
+//! # Ok(())
+//! # }
+//! ```
+
 pub mod blockchain;
+pub mod catalog;
+pub mod config;
+pub mod locale;
+pub mod secret;
 pub mod wasm;
 
+#[cfg(not(target_arch = "wasm32"))]
+pub mod attest;
+#[cfg(all(feature = "dev-tools", not(target_arch = "wasm32")))]
+pub mod conformance;
+#[cfg(all(feature = "dev-tools", not(target_arch = "wasm32")))]
+pub mod fixtures;
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+pub mod push;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod redact;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod render;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod server;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod telemetry;
 
 pub use blockchain::{BlockchainClient, BlockchainError};
+pub use config::ServerConfig;
 
 #[cfg(not(target_arch = "wasm32"))]
 pub use server::BlockchainMcpServer;
@@ -0,0 +1,208 @@
+//! Locale-dependent formatting for text meant for people (confirmation prompts and other
+//! display strings). Machine-readable output fields are never localized.
+
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Locale {
+    #[default]
+    En,
+    De,
+    Fr,
+    Ja,
+    Uk,
+}
+
+/// Separators and date layout for one locale.
+struct Format {
+    thousands: &'static str,
+    decimal: &'static str,
+    /// Order of day, month and year, and the separator between them
+    date: DateLayout,
+}
+
+#[derive(Clone, Copy)]
+enum DateLayout {
+    /// 06/10/2024
+    MonthDayYear(&'static str),
+    /// 10.06.2024
+    DayMonthYear(&'static str),
+    /// 2024/06/10
+    YearMonthDay(&'static str),
+}
+
+impl Locale {
+    pub const ALL: [Locale; 5] = [Locale::En, Locale::De, Locale::Fr, Locale::Ja, Locale::Uk];
+
+    pub fn tag(self) -> &'static str {
+        match self {
+            Self::En => "en",
+            Self::De => "de",
+            Self::Fr => "fr",
+            Self::Ja => "ja",
+            Self::Uk => "uk",
+        }
+    }
+
+    fn format(self) -> Format {
+        match self {
+            Self::En => Format { thousands: ",", decimal: ".", date: DateLayout::MonthDayYear("/") },
+            Self::De => Format { thousands: ".", decimal: ",", date: DateLayout::DayMonthYear(".") },
+            Self::Fr => Format { thousands: "\u{202f}", decimal: ",", date: DateLayout::DayMonthYear("/") },
+            Self::Ja => Format { thousands: ",", decimal: ".", date: DateLayout::YearMonthDay("/") },
+            Self::Uk => Format { thousands: "\u{a0}", decimal: ",", date: DateLayout::DayMonthYear(".") },
+        }
+    }
+
+    /// `value` with the locale's thousands separator.
+    pub fn format_integer(self, value: u128) -> String {
+        group_digits(&value.to_string(), self.format().thousands)
+    }
+
+    /// `atoms` as a decimal amount of `symbol` with `decimals` places, trailing zeros
+    /// dropped, e.g. `1,234.5 AMA` in en and `1.234,5 AMA` in de.
+    pub fn format_amount(self, atoms: u128, decimals: u32, symbol: &str) -> String {
+        let format = self.format();
+        let Some(unit) = 10u128.checked_pow(decimals) else {
+            return format!("{} atoms of {}", atoms, symbol);
+        };
+        let whole = group_digits(&(atoms / unit).to_string(), format.thousands);
+        let fraction = atoms % unit;
+        if fraction == 0 {
+            return format!("{} {}", whole, symbol);
+        }
+        let fraction = format!("{:0width$}", fraction, width = decimals as usize);
+        format!("{}{}{} {}", whole, format.decimal, fraction.trim_end_matches('0'), symbol)
+    }
+
+    /// Date and 24-hour UTC time of a unix timestamp, e.g. `06/10/2024 14:05:09 UTC` in en.
+    pub fn format_timestamp(self, unix_secs: u64) -> String {
+        let (year, month, day) = civil_date(unix_secs / 86_400);
+        let secs = unix_secs % 86_400;
+        let date = match self.format().date {
+            DateLayout::MonthDayYear(sep) => format!("{:02}{sep}{:02}{sep}{}", month, day, year),
+            DateLayout::DayMonthYear(sep) => format!("{:02}{sep}{:02}{sep}{}", day, month, year),
+            DateLayout::YearMonthDay(sep) => format!("{}{sep}{:02}{sep}{:02}", year, month, day),
+        };
+        format!("{} {:02}:{:02}:{:02} UTC", date, secs / 3600, secs % 3600 / 60, secs % 60)
+    }
+}
+
+impl FromStr for Locale {
+    type Err = String;
+
+    /// Accepts a language tag (`de`, `de-DE`, `de_DE.UTF-8`); only the language is used.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let language = s.trim().split(['-', '_', '.']).next().unwrap_or_default();
+        Self::ALL
+            .into_iter()
+            .find(|l| l.tag().eq_ignore_ascii_case(language))
+            .ok_or_else(|| {
+                let supported: Vec<&str> = Self::ALL.iter().map(|l| l.tag()).collect();
+                format!("unsupported locale {}, expected one of {}", s.trim(), supported.join(", "))
+            })
+    }
+}
+
+fn group_digits(digits: &str, separator: &str) -> String {
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3 * separator.len());
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push_str(separator);
+        }
+        grouped.push(c);
+    }
+    grouped
+}
+
+/// Year, month and day of the proleptic Gregorian calendar for days since 1970-01-01.
+fn civil_date(days: u64) -> (i64, u32, u32) {
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2024-06-10 14:05:09 UTC
+    const TIMESTAMP: u64 = 1_718_028_309;
+
+    #[test]
+    fn integers_use_the_thousands_separator() {
+        for (locale, expected) in [
+            (Locale::En, "1,234,567"),
+            (Locale::De, "1.234.567"),
+            (Locale::Fr, "1\u{202f}234\u{202f}567"),
+            (Locale::Ja, "1,234,567"),
+            (Locale::Uk, "1\u{a0}234\u{a0}567"),
+        ] {
+            assert_eq!(locale.format_integer(1_234_567), expected, "{:?}", locale);
+            assert_eq!(locale.format_integer(999), "999", "{:?}", locale);
+            assert_eq!(locale.format_integer(0), "0", "{:?}", locale);
+        }
+    }
+
+    #[test]
+    fn amounts_use_the_decimal_mark() {
+        for (locale, expected) in [
+            (Locale::En, "1,234.5 AMA"),
+            (Locale::De, "1.234,5 AMA"),
+            (Locale::Fr, "1\u{202f}234,5 AMA"),
+            (Locale::Ja, "1,234.5 AMA"),
+            (Locale::Uk, "1\u{a0}234,5 AMA"),
+        ] {
+            assert_eq!(locale.format_amount(1_234_500_000_000, 9, "AMA"), expected, "{:?}", locale);
+        }
+    }
+
+    #[test]
+    fn amounts_keep_leading_fraction_zeros_and_drop_trailing_ones() {
+        assert_eq!(Locale::En.format_amount(1_000_000_001, 9, "AMA"), "1.000000001 AMA");
+        assert_eq!(Locale::De.format_amount(50_000_000, 9, "AMA"), "0,05 AMA");
+        assert_eq!(Locale::En.format_amount(2_000_000_000, 9, "AMA"), "2 AMA");
+        assert_eq!(Locale::Fr.format_amount(1_500, 0, "PTS"), "1\u{202f}500 PTS");
+        assert_eq!(Locale::En.format_amount(7, 40, "BIG"), "7 atoms of BIG");
+    }
+
+    #[test]
+    fn timestamps_follow_the_date_layout() {
+        for (locale, expected) in [
+            (Locale::En, "06/10/2024 14:05:09 UTC"),
+            (Locale::De, "10.06.2024 14:05:09 UTC"),
+            (Locale::Fr, "10/06/2024 14:05:09 UTC"),
+            (Locale::Ja, "2024/06/10 14:05:09 UTC"),
+            (Locale::Uk, "10.06.2024 14:05:09 UTC"),
+        ] {
+            assert_eq!(locale.format_timestamp(TIMESTAMP), expected, "{:?}", locale);
+        }
+    }
+
+    #[test]
+    fn calendar_edges() {
+        assert_eq!(Locale::Ja.format_timestamp(0), "1970/01/01 00:00:00 UTC");
+        assert_eq!(Locale::Ja.format_timestamp(951_782_400), "2000/02/29 00:00:00 UTC");
+        assert_eq!(Locale::Ja.format_timestamp(1_709_251_199), "2024/02/29 23:59:59 UTC");
+        assert_eq!(Locale::Ja.format_timestamp(4_107_542_400), "2100/03/01 00:00:00 UTC");
+    }
+
+    #[test]
+    fn tags_parse_by_language() {
+        for locale in Locale::ALL {
+            assert_eq!(locale.tag().parse::<Locale>(), Ok(locale));
+        }
+        assert_eq!("de-DE".parse::<Locale>(), Ok(Locale::De));
+        assert_eq!("uk_UA.UTF-8".parse::<Locale>(), Ok(Locale::Uk));
+        assert_eq!(" JA ".parse::<Locale>(), Ok(Locale::Ja));
+        assert_eq!("es".parse::<Locale>().unwrap_err(), "unsupported locale es, expected one of en, de, fr, ja, uk");
+        assert!("".parse::<Locale>().is_err());
+    }
+}
@@ -1,38 +1,109 @@
-use amadeus_mcp::{BlockchainClient, BlockchainMcpServer};
+use amadeus_mcp::{BlockchainClient, BlockchainMcpServer, ServerConfig};
 use rmcp::ServiceExt;
-use std::env;
-use tracing::info;
+use tracing::{info, warn};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    tracing_subscriber::registry()
+    let registry = tracing_subscriber::registry()
         .with(
             EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| EnvFilter::new("info,amadeus_mcp=debug")),
         )
-        .with(tracing_subscriber::fmt::layer().with_target(true))
-        .init();
+        .with(tracing_subscriber::fmt::layer().with_target(true));
+    #[cfg(feature = "otel")]
+    let registry = registry.with(amadeus_mcp::telemetry::otel_layer());
+    registry.init();
 
-    let mainnet_url =
-        env::var("BLOCKCHAIN_URL").unwrap_or_else(|_| "https://nodes.amadeus.bot".to_string());
-    let testnet_url =
-        env::var("AMADEUS_TESTNET_RPC").unwrap_or_else(|_| "https://testnet.amadeus.bot".to_string());
+    let config = ServerConfig::load().map_err(|e| anyhow::anyhow!("invalid configuration: {}", e))?;
+    for warning in &config.warnings {
+        warn!("{}", warning);
+    }
 
-    info!(mainnet_url = %mainnet_url, testnet_url = %testnet_url, "initializing blockchain client");
+    info!(mainnet_url = %config.mainnet_url, testnet_url = %config.testnet_url, "initializing blockchain client");
 
-    let client = BlockchainClient::new(mainnet_url.clone())?;
-    let server = BlockchainMcpServer::new(client, mainnet_url, testnet_url);
+    let client = BlockchainClient::from_config(&config)?;
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("check-drift") {
+        let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?.as_secs();
+        let report = amadeus_mcp::blockchain::drift::check(&client, &config.mainnet_url, now).await;
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        if report.is_broken() {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    #[cfg(feature = "dev-tools")]
+    if args.first().map(String::as_str) == Some("fixtures") {
+        println!("{}", amadeus_mcp::fixtures::command(&args[1..], &client, &config.mainnet_url).await?);
+        return Ok(());
+    }
+
+    #[cfg(feature = "dev-tools")]
+    if args.first().map(String::as_str) == Some("conformance") {
+        let report = amadeus_mcp::conformance::in_process(config).await.map_err(|e| anyhow::anyhow!(e))?;
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        if !report.passed {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    // Off the startup path: a down node must not delay the handshake
+    let health_client = client.clone();
+    let mainnet_url = config.mainnet_url.clone();
+    let reference_url = config.mainnet_fallback_urls.first().cloned();
+    tokio::spawn(async move {
+        let health = health_client.health_check(&mainnet_url, reference_url.as_deref()).await;
+        match health.warning() {
+            Some(warning) => warn!("{}", warning),
+            None => info!(latency_ms = ?health.latency_ms, height = ?health.height, "node reachable"),
+        }
+    });
+
+    let server = BlockchainMcpServer::new(client, config);
 
     let service = server
+        .clone()
         .serve(rmcp::transport::stdio())
         .await
         .map_err(|e| anyhow::anyhow!("failed to initialize server: {}", e))?;
 
+    #[cfg(unix)]
+    tokio::spawn(reload_on_sighup(server, service.peer().clone()));
+
     service
         .waiting()
         .await
         .map_err(|e| anyhow::anyhow!("server error: {}", e))?;
 
+    #[cfg(feature = "otel")]
+    amadeus_mcp::telemetry::shutdown();
+
     Ok(())
 }
+
+/// Re-reads the configuration on SIGHUP and applies it without restarting the stdio
+/// session. An invalid configuration is logged and the current one kept.
+#[cfg(unix)]
+async fn reload_on_sighup(server: BlockchainMcpServer, peer: rmcp::Peer<rmcp::RoleServer>) {
+    use tokio::signal::unix::{signal, SignalKind};
+    use tracing::error;
+
+    let mut hangup = match signal(SignalKind::hangup()) {
+        Ok(hangup) => hangup,
+        Err(e) => {
+            error!(error = %e, "failed to install SIGHUP handler");
+            return;
+        }
+    };
+
+    while hangup.recv().await.is_some() {
+        match server.reload_with(ServerConfig::load, Some(&peer)).await {
+            Ok(()) => info!(mainnet_url = %server.config().mainnet_url, "configuration reloaded"),
+            Err(e) => error!(error = %e, "keeping the current configuration"),
+        }
+    }
+}
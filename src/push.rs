@@ -0,0 +1,184 @@
+//! Protocol of the `/ws` push bridge for the native HTTP transport: what a socket may send,
+//! what it is pushed, and the per-socket subscription set. The socket itself belongs to
+//! the transport; this module only decides what goes over it.
+//!
+//! Sockets send `{"subscribe": "amadeus://..."}` or `{"unsubscribe": "amadeus://..."}`.
+//! Every socket gets tip and reorg events; resource updates only for subscribed URIs.
+
+use crate::blockchain::reorg::ReorgEvent;
+use crate::blockchain::tip::TipState;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Subscriptions one socket may hold at a time.
+pub const MAX_SOCKET_SUBSCRIPTIONS: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SocketRequest {
+    Subscribe(String),
+    Unsubscribe(String),
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PushMessage {
+    Tip {
+        height: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        hash: Option<String>,
+    },
+    Reorg(ReorgEvent),
+    Resource { uri: String, contents: serde_json::Value },
+    Subscribed { uri: String },
+    Unsubscribed { uri: String },
+    Error { message: String },
+}
+
+/// The URIs one socket is subscribed to. Dropped with the socket, which is all the
+/// cleanup a disconnect needs.
+#[derive(Debug, Default)]
+pub struct SocketSubscriptions {
+    uris: BTreeSet<String>,
+}
+
+impl SocketSubscriptions {
+    pub fn uris(&self) -> impl Iterator<Item = &str> {
+        self.uris.iter().map(String::as_str)
+    }
+
+    pub fn is_subscribed(&self, uri: &str) -> bool {
+        self.uris.contains(uri)
+    }
+
+    /// Applies one text frame from the socket; `is_resource` says whether the server
+    /// serves a URI. Returns the reply to send back.
+    pub fn handle(&mut self, frame: &str, is_resource: impl Fn(&str) -> bool) -> PushMessage {
+        let error = |message: String| PushMessage::Error { message };
+        match serde_json::from_str::<SocketRequest>(frame) {
+            Err(_) => error(r#"expected {"subscribe": "<uri>"} or {"unsubscribe": "<uri>"}"#.to_string()),
+            Ok(SocketRequest::Subscribe(uri)) if !is_resource(&uri) => error(format!("unknown resource {}", uri)),
+            Ok(SocketRequest::Subscribe(uri))
+                if !self.uris.contains(&uri) && self.uris.len() >= MAX_SOCKET_SUBSCRIPTIONS =>
+            {
+                error(format!("at most {} subscriptions per socket", MAX_SOCKET_SUBSCRIPTIONS))
+            }
+            Ok(SocketRequest::Subscribe(uri)) => {
+                self.uris.insert(uri.clone());
+                PushMessage::Subscribed { uri }
+            }
+            Ok(SocketRequest::Unsubscribe(uri)) => {
+                self.uris.remove(&uri);
+                PushMessage::Unsubscribed { uri }
+            }
+        }
+    }
+}
+
+/// Events to push when the tip follower moves from `previous` to `current`: the new tip
+/// when height or hash changed, and a reorg the previous state did not know about yet.
+pub fn tip_events(previous: Option<&TipState>, current: &TipState) -> Vec<PushMessage> {
+    let mut events = Vec::new();
+    let moved = previous.is_none_or(|p| p.height != current.height || p.hash != current.hash);
+    if moved {
+        events.push(PushMessage::Tip { height: current.height, hash: current.hash.clone() });
+    }
+    let known = previous.and_then(|p| p.last_reorg.as_ref()).map(|r| r.detected_at);
+    if let Some(reorg) = current.last_reorg.as_ref().filter(|r| Some(r.detected_at) != known) {
+        events.push(PushMessage::Reorg(reorg.clone()));
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn is_resource(uri: &str) -> bool {
+        uri.starts_with("amadeus://")
+    }
+
+    fn tip(height: u64, hash: &str, last_reorg: Option<ReorgEvent>) -> TipState {
+        TipState {
+            height,
+            hash: Some(hash.to_string()),
+            stats: serde_json::from_value(json!({ "height": height })).unwrap(),
+            observed_at: 0,
+            last_reorg,
+            clock_skew: None,
+        }
+    }
+
+    fn reorg(detected_at: u64) -> ReorgEvent {
+        ReorgEvent { fork_height: 990, depth: 3, orphaned_hash: "orphan".to_string(), detected_at }
+    }
+
+    fn pushed(message: PushMessage) -> serde_json::Value {
+        serde_json::to_value(message).unwrap()
+    }
+
+    #[test]
+    fn sockets_subscribe_and_unsubscribe() {
+        let mut socket = SocketSubscriptions::default();
+        assert_eq!(
+            pushed(socket.handle(r#"{"subscribe":"amadeus://status"}"#, is_resource)),
+            json!({ "type": "subscribed", "uri": "amadeus://status" })
+        );
+        assert!(socket.is_subscribed("amadeus://status"));
+        assert_eq!(
+            pushed(socket.handle(r#"{"unsubscribe":"amadeus://status"}"#, is_resource)),
+            json!({ "type": "unsubscribed", "uri": "amadeus://status" })
+        );
+        assert_eq!(socket.uris().count(), 0);
+    }
+
+    #[test]
+    fn bad_frames_and_unknown_resources_are_refused() {
+        let mut socket = SocketSubscriptions::default();
+        for (frame, message) in [
+            ("not json", r#"expected {"subscribe": "<uri>"} or {"unsubscribe": "<uri>"}"#),
+            (r#"{"watch":"amadeus://status"}"#, r#"expected {"subscribe": "<uri>"} or {"unsubscribe": "<uri>"}"#),
+            (r#"{"subscribe":"https://elsewhere"}"#, "unknown resource https://elsewhere"),
+        ] {
+            assert_eq!(pushed(socket.handle(frame, is_resource)), json!({ "type": "error", "message": message }), "{}", frame);
+        }
+        assert_eq!(socket.uris().count(), 0);
+    }
+
+    #[test]
+    fn subscriptions_per_socket_are_capped() {
+        let mut socket = SocketSubscriptions::default();
+        for i in 0..MAX_SOCKET_SUBSCRIPTIONS {
+            let frame = json!({ "subscribe": format!("amadeus://validators?offset={}", i) }).to_string();
+            assert!(matches!(socket.handle(&frame, is_resource), PushMessage::Subscribed { .. }));
+        }
+        let over = socket.handle(r#"{"subscribe":"amadeus://status"}"#, is_resource);
+        assert_eq!(pushed(over), json!({ "type": "error", "message": "at most 16 subscriptions per socket" }));
+        // Subscribing again to a held URI is not a new subscription
+        let again = socket.handle(r#"{"subscribe":"amadeus://validators?offset=0"}"#, is_resource);
+        assert!(matches!(again, PushMessage::Subscribed { .. }));
+        assert_eq!(socket.uris().count(), MAX_SOCKET_SUBSCRIPTIONS);
+    }
+
+    #[test]
+    fn a_moved_tip_is_pushed_once() {
+        let first = tip(100, "a", None);
+        assert_eq!(tip_events(None, &first).into_iter().map(pushed).collect::<Vec<_>>(), vec![json!({ "type": "tip", "height": 100, "hash": "a" })]);
+        assert!(tip_events(Some(&first), &tip(100, "a", None)).is_empty());
+        // Same height, new hash: the tip was replaced
+        assert_eq!(tip_events(Some(&first), &tip(100, "b", None)).len(), 1);
+        assert_eq!(pushed(tip_events(Some(&first), &tip(101, "c", None)).remove(0))["height"], 101);
+    }
+
+    #[test]
+    fn a_reorg_is_pushed_when_first_seen() {
+        let before = tip(1000, "a", None);
+        let after = tip(998, "b", Some(reorg(50)));
+        let events: Vec<_> = tip_events(Some(&before), &after).into_iter().map(pushed).collect();
+        assert_eq!(events[1], json!({ "type": "reorg", "fork_height": 990, "depth": 3, "orphaned_hash": "orphan", "detected_at": 50 }));
+        // The follower keeps reporting its last reorg; it is not pushed again
+        assert!(tip_events(Some(&after), &tip(998, "b", Some(reorg(50)))).is_empty());
+        assert_eq!(tip_events(Some(&after), &tip(998, "b", Some(reorg(90)))).len(), 1);
+    }
+}
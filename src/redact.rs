@@ -0,0 +1,274 @@
+//! Address redaction for tool outputs (`MCP_REDACT_ADDRESSES`). Addresses are found
+//! structurally: any JSON string that decodes to a 48-byte public key. Tokens handed to
+//! the agent are remembered, up to `MAX_TOKENS` most recently used, so later calls that
+//! pass them back resolve to the real address.
+
+use crate::blockchain::is_valid_address;
+use crate::config::RedactMode;
+use crate::secret::hmac_sha256;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Mutex, RwLock};
+
+const PARTIAL_CHARS: usize = 6;
+const TOKEN_PREFIX: &str = "addr:";
+/// Tokens remembered for resolving; the least recently used is forgotten past this.
+pub const MAX_TOKENS: usize = 4096;
+
+pub struct Redactor {
+    mode: RwLock<RedactMode>,
+    /// Per-session HMAC key, so tokens are stable within a session only
+    key: [u8; 32],
+    /// Token -> real address
+    tokens: Mutex<TokenMap>,
+}
+
+/// Token -> address, dropping the least recently used token past `capacity`.
+struct TokenMap {
+    capacity: usize,
+    /// Token -> (address, last use)
+    entries: HashMap<String, (String, u64)>,
+    /// Last use -> token, oldest first
+    order: BTreeMap<u64, String>,
+    clock: u64,
+}
+
+impl TokenMap {
+    fn new(capacity: usize) -> Self {
+        Self { capacity, entries: HashMap::new(), order: BTreeMap::new(), clock: 0 }
+    }
+
+    fn touch(&mut self, token: &str) -> Option<u64> {
+        let (_, used) = self.entries.get_mut(token)?;
+        let previous = std::mem::replace(used, self.clock);
+        self.order.remove(&previous);
+        self.order.insert(self.clock, token.to_string());
+        self.clock += 1;
+        Some(previous)
+    }
+
+    /// The address of `token`, marking it used.
+    fn get(&mut self, token: &str) -> Option<String> {
+        self.touch(token)?;
+        self.entries.get(token).map(|(address, _)| address.clone())
+    }
+
+    fn insert(&mut self, token: String, address: String) {
+        if self.touch(&token).is_some() {
+            return;
+        }
+        if self.entries.len() >= self.capacity {
+            if let Some((_, oldest)) = self.order.pop_first() {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.order.insert(self.clock, token.clone());
+        self.entries.insert(token, (address, self.clock));
+        self.clock += 1;
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+impl Redactor {
+    pub fn new(mode: RedactMode) -> Self {
+        let mut key = [0u8; 32];
+        getrandom::getrandom(&mut key).expect("OS random source unavailable");
+        Self {
+            mode: RwLock::new(mode),
+            key,
+            tokens: Mutex::new(TokenMap::new(MAX_TOKENS)),
+        }
+    }
+
+    pub fn set_mode(&self, mode: RedactMode) {
+        *self.mode.write().unwrap() = mode;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        *self.mode.read().unwrap() != RedactMode::Off
+    }
+
+    /// Replaces tokens in tool arguments with the addresses they stand for, and returns
+    /// the addresses the caller supplied; those are never redacted in the reply.
+    pub fn resolve(&self, arguments: &mut Value) -> HashSet<String> {
+        let mut tokens = self.tokens.lock().unwrap();
+        let mut supplied = HashSet::new();
+        walk_strings(arguments, &mut |s| {
+            if let Some(address) = tokens.get(s.as_str()) {
+                *s = address;
+            }
+            if is_valid_address(s) {
+                supplied.insert(s.clone());
+            }
+        });
+        supplied
+    }
+
+    /// Redacts every address in `value` except the exempt ones.
+    pub fn redact(&self, value: &mut Value, exempt: &HashSet<String>) {
+        let mode = *self.mode.read().unwrap();
+        if mode == RedactMode::Off {
+            return;
+        }
+        let mut tokens = self.tokens.lock().unwrap();
+        walk_strings(value, &mut |s| {
+            if exempt.contains(s.as_str()) || !is_valid_address(s) {
+                return;
+            }
+            let token = self.token(mode, s);
+            tokens.insert(token.clone(), std::mem::take(s));
+            *s = token;
+        });
+    }
+
+    fn token(&self, mode: RedactMode, address: &str) -> String {
+        match mode {
+            RedactMode::Off => address.to_string(),
+            RedactMode::Partial => {
+                let head: String = address.chars().take(PARTIAL_CHARS).collect();
+                let tail: String = address.chars().skip(address.chars().count() - PARTIAL_CHARS).collect();
+                format!("{}…{}", head, tail)
+            }
+            RedactMode::Hash => {
+                let mac = hmac_sha256(&self.key, address.as_bytes());
+                format!("{}{}", TOKEN_PREFIX, hex::encode(&mac[..8]))
+            }
+        }
+    }
+}
+
+fn walk_strings<F: FnMut(&mut String)>(value: &mut Value, f: &mut F) {
+    match value {
+        Value::String(s) => f(s),
+        Value::Array(items) => items.iter_mut().for_each(|v| walk_strings(v, f)),
+        Value::Object(fields) => fields.values_mut().for_each(|v| walk_strings(v, f)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn address(byte: u8) -> String {
+        bs58::encode([byte; 48]).into_string()
+    }
+
+    fn output() -> Value {
+        json!({ "signer": address(1), "args": [address(2), "1000", "AMA"], "nested": { "to": address(2) }, "hash": "abc" })
+    }
+
+    #[test]
+    fn off_leaves_addresses_alone() {
+        let mut value = output();
+        Redactor::new(RedactMode::Off).redact(&mut value, &HashSet::new());
+        assert_eq!(value, output());
+    }
+
+    #[test]
+    fn hash_tokens_are_stable_within_a_session_and_differ_across_sessions() {
+        let redactor = Redactor::new(RedactMode::Hash);
+        let mut value = output();
+        redactor.redact(&mut value, &HashSet::new());
+        let token = value["args"][0].as_str().unwrap().to_string();
+        assert!(token.starts_with(TOKEN_PREFIX), "{}", token);
+        assert_eq!(value["nested"]["to"], token.as_str());
+        assert_ne!(value["signer"], token.as_str());
+        assert_eq!(value["args"][1], "1000");
+        assert_eq!(value["hash"], "abc");
+
+        let mut again = output();
+        redactor.redact(&mut again, &HashSet::new());
+        assert_eq!(again, value);
+
+        let mut other = output();
+        Redactor::new(RedactMode::Hash).redact(&mut other, &HashSet::new());
+        assert_ne!(other["args"][0], token.as_str());
+    }
+
+    #[test]
+    fn partial_keeps_the_edges() {
+        let mut value = output();
+        Redactor::new(RedactMode::Partial).redact(&mut value, &HashSet::new());
+        let full = address(2);
+        let token = value["args"][0].as_str().unwrap();
+        assert!(token.starts_with(&full[..PARTIAL_CHARS]) && token.ends_with(&full[full.len() - PARTIAL_CHARS..]));
+        assert!(token.len() < full.len());
+    }
+
+    #[test]
+    fn supplied_addresses_are_exempt() {
+        let redactor = Redactor::new(RedactMode::Hash);
+        let mut arguments = json!({ "address": address(1) });
+        let exempt = redactor.resolve(&mut arguments);
+        let mut value = output();
+        redactor.redact(&mut value, &exempt);
+        assert_eq!(value["signer"], address(1));
+        assert_ne!(value["args"][0], address(2));
+    }
+
+    #[test]
+    fn tokens_resolve_back_and_count_as_supplied() {
+        let redactor = Redactor::new(RedactMode::Hash);
+        let mut value = output();
+        redactor.redact(&mut value, &HashSet::new());
+
+        let mut arguments = json!({ "address": value["args"][0].clone(), "limit": 5, "other": "addr:unknown" });
+        let exempt = redactor.resolve(&mut arguments);
+        assert_eq!(arguments, json!({ "address": address(2), "limit": 5, "other": "addr:unknown" }));
+        assert_eq!(exempt, HashSet::from([address(2)]));
+    }
+
+    #[test]
+    fn mode_can_change_at_runtime() {
+        let redactor = Redactor::new(RedactMode::Off);
+        assert!(!redactor.is_enabled());
+        redactor.set_mode(RedactMode::Partial);
+        assert!(redactor.is_enabled());
+        let mut value = output();
+        redactor.redact(&mut value, &HashSet::new());
+        assert_ne!(value["signer"], address(1));
+    }
+
+    #[test]
+    fn remembered_tokens_are_capped() {
+        let redactor = Redactor::new(RedactMode::Hash);
+        let addresses: Vec<Value> = (0..MAX_TOKENS as u32 + 10)
+            .map(|n| {
+                let mut pk = [3u8; 48];
+                pk[..4].copy_from_slice(&n.to_be_bytes());
+                Value::String(bs58::encode(pk).into_string())
+            })
+            .collect();
+        let mut value = Value::Array(addresses.clone());
+        redactor.redact(&mut value, &HashSet::new());
+        assert_eq!(redactor.tokens.lock().unwrap().len(), MAX_TOKENS);
+
+        // The newest tokens still resolve, the oldest are forgotten
+        let mut newest = value[MAX_TOKENS + 9].clone();
+        redactor.resolve(&mut newest);
+        assert_eq!(newest, addresses[MAX_TOKENS + 9]);
+        let mut oldest = value[0].clone();
+        redactor.resolve(&mut oldest);
+        assert_eq!(oldest, value[0]);
+    }
+
+    #[test]
+    fn the_least_recently_used_token_goes_first() {
+        let mut tokens = TokenMap::new(2);
+        tokens.insert("a".into(), "A".into());
+        tokens.insert("b".into(), "B".into());
+        assert_eq!(tokens.get("a").as_deref(), Some("A"));
+        tokens.insert("c".into(), "C".into());
+        assert_eq!((tokens.get("a").as_deref(), tokens.get("b"), tokens.get("c").as_deref()), (Some("A"), None, Some("C")));
+
+        tokens.insert("a".into(), "A".into());
+        tokens.insert("d".into(), "D".into());
+        assert_eq!((tokens.len(), tokens.get("c"), tokens.get("a").as_deref()), (2, None, Some("A")));
+    }
+}
@@ -0,0 +1,143 @@
+//! JSON rendering of tool and resource outputs. Small outputs are rendered inline; large
+//! ones (multi-megabyte blocks and histories) go to the blocking pool so they do not hold
+//! a runtime worker while other calls wait.
+
+use serde_json::Value;
+use std::cell::RefCell;
+
+/// Text outputs at least this long are post-processed on the blocking pool.
+pub const LARGE_TEXT_BYTES: usize = 256 * 1024;
+/// Values with more nodes than this are rendered on the blocking pool; a node is a few
+/// dozen bytes of output, so this is about the same size as `LARGE_TEXT_BYTES`.
+const LARGE_VALUE_NODES: usize = 10_000;
+/// A render buffer that grew past this is dropped instead of kept for the next call.
+const MAX_KEPT_BUFFER: usize = 8 * 1024 * 1024;
+
+thread_local! {
+    static BUFFER: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Whether `value` has more than `LARGE_VALUE_NODES` nodes; stops counting there.
+pub fn is_large(value: &Value) -> bool {
+    let mut stack = vec![value];
+    let mut nodes = 0;
+    while let Some(value) = stack.pop() {
+        nodes += 1;
+        if nodes > LARGE_VALUE_NODES {
+            return true;
+        }
+        match value {
+            Value::Array(items) => stack.extend(items),
+            Value::Object(fields) => stack.extend(fields.values()),
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Renders `value` through the thread's reusable buffer, so repeated large renders do not
+/// regrow a fresh one each time.
+pub fn to_string(value: &Value, pretty: bool) -> Result<String, serde_json::Error> {
+    BUFFER.with(|buffer| {
+        let mut buffer = buffer.borrow_mut();
+        buffer.clear();
+        if pretty {
+            serde_json::to_writer_pretty(&mut *buffer, value)?;
+        } else {
+            serde_json::to_writer(&mut *buffer, value)?;
+        }
+        // serde_json only writes valid UTF-8
+        let text = String::from_utf8_lossy(&buffer).into_owned();
+        if buffer.capacity() > MAX_KEPT_BUFFER {
+            *buffer = Vec::new();
+        }
+        Ok(text)
+    })
+}
+
+/// `to_string`, on the blocking pool when `value` is large.
+pub async fn render(value: Value, pretty: bool) -> Result<String, serde_json::Error> {
+    if is_large(&value) {
+        off_runtime(move || to_string(&value, pretty)).await
+    } else {
+        to_string(&value, pretty)
+    }
+}
+
+/// Runs CPU-heavy `work` on the blocking pool and waits for it; a panic in `work` is
+/// resumed on the caller.
+pub async fn off_runtime<T: Send + 'static>(work: impl FnOnce() -> T + Send + 'static) -> T {
+    match tokio::task::spawn_blocking(work).await {
+        Ok(output) => output,
+        Err(e) => std::panic::resume_unwind(e.into_panic()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// A history-sized value: `entries` transactions of a handful of fields each.
+    fn history(entries: usize) -> Value {
+        Value::Array(
+            (0..entries)
+                .map(|i| json!({ "hash": format!("tx-{}", i), "nonce": i, "tx": { "args": ["receiver", "1000", "AMA"], "signer": "signer" } }))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn size_is_counted_in_nodes() {
+        assert!(!is_large(&json!({ "a": [1, 2, 3] })));
+        assert!(!is_large(&Value::Array(vec![json!(1); LARGE_VALUE_NODES - 1])));
+        assert!(is_large(&Value::Array(vec![json!(1); LARGE_VALUE_NODES])));
+        // Nesting counts, not only the top level
+        assert!(is_large(&json!({ "page": { "txs": history(2000) } })));
+    }
+
+    #[test]
+    fn rendering_matches_serde_json() {
+        let value = json!({ "b": [1, { "c": null }], "a": "x" });
+        assert_eq!(to_string(&value, false).unwrap(), serde_json::to_string(&value).unwrap());
+        assert_eq!(to_string(&value, true).unwrap(), serde_json::to_string_pretty(&value).unwrap());
+        // The reused buffer carries nothing over from the previous render
+        assert_eq!(to_string(&json!(1), false).unwrap(), "1");
+    }
+
+    #[test]
+    fn an_oversized_buffer_is_not_kept() {
+        let big = Value::String("x".repeat(MAX_KEPT_BUFFER + 1));
+        to_string(&big, false).unwrap();
+        assert_eq!(BUFFER.with(|buffer| buffer.borrow().capacity()), 0);
+        to_string(&json!({ "a": 1 }), false).unwrap();
+        assert!(BUFFER.with(|buffer| buffer.borrow().capacity()) > 0);
+    }
+
+    #[tokio::test]
+    async fn large_and_small_values_render_the_same_way() {
+        for value in [json!({ "a": 1 }), history(3000)] {
+            assert_eq!(render(value.clone(), true).await.unwrap(), serde_json::to_string_pretty(&value).unwrap());
+        }
+    }
+
+    /// On a single-threaded runtime a render done inline would run to completion before the
+    /// other task got a turn; off the runtime the other task finishes first.
+    #[tokio::test(flavor = "current_thread")]
+    async fn a_large_render_does_not_hold_up_other_calls() {
+        let huge = history(60_000);
+        assert!(huge.to_string().len() > 5 * 1024 * 1024);
+        let rendering = tokio::spawn(render(huge, true));
+        let other = tokio::spawn(render(json!({ "height": 100 }), true));
+        assert_eq!(other.await.unwrap().unwrap(), serde_json::to_string_pretty(&json!({ "height": 100 })).unwrap());
+        assert!(!rendering.is_finished(), "the small call waited for the large render");
+        assert!(rendering.await.unwrap().unwrap().len() > 5 * 1024 * 1024);
+    }
+
+    #[tokio::test]
+    async fn a_panic_off_the_runtime_reaches_the_caller() {
+        let outcome = tokio::spawn(off_runtime(|| panic!("render failed"))).await;
+        let panic = outcome.unwrap_err().into_panic();
+        assert_eq!(panic.downcast_ref::<&str>(), Some(&"render failed"));
+    }
+}
@@ -0,0 +1,175 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use zeroize::Zeroize;
+
+const REDACTED: &str = "[redacted]";
+
+/// A secret string (base58 key, API key) that is wiped on drop and never shows up in
+/// Debug, Display or serialized output. Read it with `expose()` at the point of use only.
+#[derive(Clone, Default)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn new(value: String) -> Self {
+        Self(value)
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl From<String> for Secret {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl Drop for Secret {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl fmt::Display for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl Serialize for Secret {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(REDACTED)
+    }
+}
+
+impl<'de> Deserialize<'de> for Secret {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self)
+    }
+}
+
+impl validator::ValidateLength<u64> for Secret {
+    fn length(&self) -> Option<u64> {
+        Some(self.0.chars().count() as u64)
+    }
+}
+
+/// HMAC-SHA256 of `message` under a 32-byte key.
+pub fn hmac_sha256(key: &[u8; 32], message: &[u8]) -> [u8; 32] {
+    let mut ipad = [0x36u8; 64];
+    let mut opad = [0x5cu8; 64];
+    for (i, k) in key.iter().enumerate() {
+        ipad[i] ^= k;
+        opad[i] ^= k;
+    }
+    let inner = Sha256::new().chain_update(ipad).chain_update(message).finalize();
+    Sha256::new().chain_update(opad).chain_update(inner).finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::server_key::{ServerKey, SERVER_KEY_VAR};
+    use crate::blockchain::types::SigningFlowQuery;
+    use crate::config::ServerConfig;
+    use serde_json::json;
+
+    /// A 32-byte ed25519 seed for response signing.
+    fn ed25519_seed() -> Vec<u8> {
+        (0..32).map(|i| 200 - i).collect()
+    }
+
+    /// A 64-byte BLS seed for the server key.
+    fn bls_seed() -> Vec<u8> {
+        (0..64).map(|i| i * 3 + 1).collect()
+    }
+
+    /// Every way a seed could be printed: base58, hex, and Debug of its bytes.
+    fn forms(seed: &[u8]) -> Vec<String> {
+        let bytes = format!("{:?}", &seed[..8]);
+        vec![bs58::encode(seed).into_string(), hex::encode(seed), hex::encode_upper(seed), bytes.trim_end_matches(']').to_string()]
+    }
+
+    fn assert_hidden(printed: &str, seeds: &[&[u8]]) {
+        for form in seeds.iter().flat_map(|seed| forms(seed)) {
+            assert!(!printed.contains(&form), "{} leaks {}", printed, form);
+        }
+    }
+
+    fn config(response_key: &str, server_key: &str) -> Result<ServerConfig, String> {
+        ServerConfig::try_from_lookup(|key| match key {
+            "MCP_RESPONSE_SIGNING_SK" => Some(response_key.to_string()),
+            k if k == SERVER_KEY_VAR => Some(server_key.to_string()),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn secret_is_redacted_everywhere_but_expose() {
+        let secret = Secret::new("5KYZdUEo39z3FPrtuX2QbbwGnNP5zTd7yyr2SC1j299sBCnWjss".to_string());
+        for printed in [format!("{:?}", secret), secret.to_string(), serde_json::to_string(&secret).unwrap()] {
+            assert!(!printed.contains("5KYZ"), "{}", printed);
+            assert!(printed.contains(REDACTED));
+        }
+        assert_eq!(secret.expose(), "5KYZdUEo39z3FPrtuX2QbbwGnNP5zTd7yyr2SC1j299sBCnWjss");
+    }
+
+    #[test]
+    fn config_debug_hides_signing_keys() {
+        let (ed, bls) = (ed25519_seed(), bls_seed());
+        for response_key in [bs58::encode(&ed).into_string(), hex::encode(&ed)] {
+            let config = config(&response_key, &bs58::encode(&bls).into_string()).unwrap();
+            assert!(config.response_signing_key.is_some() && config.server_signing_key.is_some());
+            assert_hidden(&format!("{:?}", config), &[&ed, &bls]);
+            assert_hidden(&format!("{:#?}", config), &[&ed, &bls]);
+        }
+    }
+
+    #[test]
+    fn config_errors_do_not_echo_keys() {
+        let (short_ed, short_bls) = (&ed25519_seed()[..31], &bls_seed()[..63]);
+        let e = config(&bs58::encode(short_ed).into_string(), &bs58::encode(short_bls).into_string()).unwrap_err();
+        assert!(e.contains("MCP_RESPONSE_SIGNING_SK") && e.contains(SERVER_KEY_VAR), "{}", e);
+        assert_hidden(&e, &[short_ed, short_bls]);
+    }
+
+    #[test]
+    fn server_key_debugs_only_its_public_key() {
+        let bls = bls_seed();
+        let server_key = ServerKey::from_secret(&Secret::new(bs58::encode(&bls).into_string())).unwrap();
+        let printed = format!("{:?}", server_key);
+        assert!(printed.contains(server_key.public_key()));
+        assert_hidden(&printed, &[&bls]);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn response_signer_debugs_only_its_public_key() {
+        let ed = ed25519_seed();
+        let signer = crate::attest::ResponseSigner::from_secret(&Secret::new(hex::encode(&ed))).unwrap();
+        let printed = format!("{:?}", signer);
+        assert!(printed.contains(signer.public_key()));
+        assert_hidden(&printed, &[&ed]);
+    }
+
+    #[test]
+    fn tool_arguments_with_keys_are_redacted() {
+        let bls = bls_seed();
+        let query: SigningFlowQuery = serde_json::from_value(json!({ "secret_key": bs58::encode(&bls).into_string() })).unwrap();
+        assert_hidden(&format!("{:?}", query), &[&bls]);
+        assert_hidden(&serde_json::to_string(&query).unwrap(), &[&bls]);
+        assert_eq!(query.secret_key.unwrap().expose(), bs58::encode(&bls).into_string());
+    }
+}
@@ -1,116 +1,2244 @@
+use crate::attest::{self, ResponseSigner};
 use crate::blockchain::{
-    AccountQuery, BlockchainClient, BlockchainError, ChainStatsQuery, ContractStateQuery,
-    HeightQuery, SignedTransaction, TransactionHistoryQuery, TransactionQuery,
-    TransactionRequest, ValidatorsQuery,
+    AccountQuery, AddressLabelQuery, BlobFreshnessQuery, BlockAtTimeQuery, BlockRangeQuery, BlocksBySignerQuery, BlockWithTxsQuery, BlockchainClient, BlockchainError, ChainStatsQuery, CompactBlockEntry, EntryHashQuery, ContractCallPreviewQuery,
+    CompactEntryTxsPage, CompactList, CompactRichlist, CompactTransaction, ContractDiffQuery, ContractExportQuery, ContractKeysQuery, CorrelateValidatorsQuery, ContractStateQuery, DecodeTransactionQuery, EstimateFeeQuery, TokenInfoQuery, EmissionAddressQuery, EpochScoreQuery, BuiltTransactionsQuery, EmissionStatsQuery, EpochInfoQuery, FinalityQuery, FullHistoryQuery, HeightQuery, ImportLabelsQuery, InvestigateQuery, LivenessQuery, NodesQuery, NonceQuery, PeerList, RichlistQuery, OutputDetail,
+    RawNodeQuery, RebuildQuery, RecentBlocksQuery, RemovedValidatorsQuery, ServerKeyTransferQuery, SignerDistributionQuery, SignedTransaction, SigningFlowQuery, TraceFundsQuery, TransactionHistoryQuery, TransactionQuery, TransactionRequest, TxsInEntryQuery, PendingTransactionsQuery, ValidateAddressQuery, ValidateTransferQuery, VerifySignatureQuery, WaitForTransactionQuery,
+    ValidatorDetailsQuery, ValidatorInfo, ValidatorsQuery, PUBLIC_KEY_LEN,
 };
+use crate::blockchain::address::AddressCheck;
+use crate::blockchain::analytics::AccountActivity;
+use crate::blockchain::fee::FeeEstimate;
+use crate::blockchain::block_txs;
+use crate::blockchain::budget::{self, SessionBudget};
+use crate::blockchain::built::{self, BuiltLog, BuiltTransaction, DEFAULT_BUILT_LIST_LIMIT};
+use crate::blockchain::cache::TtlCache;
+use crate::blockchain::clock;
+use crate::blockchain::decode;
+use crate::blockchain::tx_pool::{self, PendingTransactions};
+use crate::blockchain::drift::DriftMonitor;
+use crate::blockchain::recovery;
+use crate::blockchain::server_key::ServerKey;
+use crate::blockchain::confirm;
+use crate::blockchain::correlate;
+use crate::blockchain::decimals::AMA_DECIMALS;
+use crate::blockchain::emission::{self, EmissionWindow};
+use crate::blockchain::epoch;
+use crate::blockchain::finality::{self, DEFAULT_FINALITY_SAMPLE};
+use crate::blockchain::full_history;
+use crate::blockchain::freshness::{self, BlobOrigin, Freshness, FreshnessStatus, NonceBasis, BLOB_ORIGIN_TTL, MAX_BLOB_ORIGINS};
+use crate::blockchain::metrics::{self, CallMetrics, CALL_METRICS};
+use crate::blockchain::idempotency::{self, SubmitRecord, IDEMPOTENCY_TTL, MAX_IDEMPOTENCY_KEYS};
+use crate::blockchain::investigate::DEFAULT_INVESTIGATE_BUDGET;
+use crate::blockchain::labels::{self, LabelBook, LabelRejection};
+use crate::blockchain::liveness::{self, DEFAULT_LIVENESS_SAMPLE};
+use crate::blockchain::range::{self, Range};
+use crate::blockchain::rebuild;
+use crate::blockchain::recent_blocks::DEFAULT_RECENT_BLOCKS;
+use crate::blockchain::richlist::{self, WindowRequest};
+use crate::blockchain::tip::{TipFollower, TipState};
+use crate::blockchain::trace::{DEFAULT_TRACE_BUDGET, DEFAULT_TRACE_HOPS, DEFAULT_TRACE_TXS_PER_ADDRESS};
+use crate::blockchain::signers::{self, DEFAULT_SIGNER_WINDOW};
+use crate::blockchain::signing;
+use crate::blockchain::state_diff::{self, SOURCE_OBSERVED};
+use crate::blockchain::state_export::{self, ExportFormat, DEFAULT_EXPORT_LIMIT, DEFAULT_LIST_LIMIT};
+use crate::blockchain::symbol;
+use crate::blockchain::templates;
+use crate::blockchain::transfer_check::TransferRequest;
+use crate::blockchain::wait::{self, WaitOutcome};
+use crate::catalog::{self, ToolFilter, TOOLS_VERSION};
+use crate::config::{RedactMode, ServerConfig};
+use crate::redact::Redactor;
+use crate::render;
+use crate::telemetry;
 use rmcp::{
-    handler::server::tool::{Parameters, ToolRouter},
+    handler::server::{tool::ToolRouter, wrapper::Parameters},
     model::*,
-    service::RequestContext,
-    tool, tool_handler, tool_router, ErrorData as McpError, Json, RoleServer, ServerHandler,
+    service::{RequestContext, ServiceError},
+    tool, tool_router, ErrorData as McpError, Json, Peer, RoleServer, ServerHandler,
 };
-use std::{future::Future, sync::Arc};
-use tracing::error;
+use rmcp::handler::server::tool::ToolCallContext;
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, RwLock,
+    },
+};
+use tracing::{error, field::Empty, info_span, warn, Instrument};
 use validator::Validate;
 
-#[derive(Clone)]
-pub struct BlockchainMcpServer {
-    blockchain: Arc<BlockchainClient>,
-    mainnet_url: String,
-    testnet_url: String,
-    tool_router: ToolRouter<Self>,
-}
+const VALIDATORS_URI: &str = "amadeus://validators";
+const STATUS_URI: &str = "amadeus://status";
+/// Resource URI prefix for entries; append the base58 entry hash.
+const ENTRY_URI: &str = "amadeus://entry/";
+
+/// Client and settings swapped as a unit on reload. Requests hold their own `Arc`, so
+/// in-flight calls finish on the client they started with.
+struct Upstream {
+    blockchain: BlockchainClient,
+    config: ServerConfig,
+    /// Mainnet tip follower; dropped, and so stopped, with the rest of the upstream
+    tip: Option<TipFollower>,
+    /// Background schema drift check (`MCP_DRIFT_CHECK_SECS`); stopped when dropped
+    drift: Option<DriftMonitor>,
+    /// Signs tool results when `MCP_RESPONSE_SIGNING_SK` is set
+    signer: Option<Arc<ResponseSigner>>,
+    /// Spent from by transfer_from_server_key when `AMADEUS_MCP_SIGNING_KEY` is set
+    server_key: Option<Arc<ServerKey>>,
+}
+
+impl Upstream {
+    fn new(blockchain: BlockchainClient, config: ServerConfig) -> Self {
+        let tip = config.tip_refresh.map(|interval| {
+            TipFollower::spawn(blockchain.clone(), config.mainnet_url.clone(), interval, config.clock_skew_threshold)
+        });
+        let drift = config
+            .drift_check_interval
+            .map(|interval| DriftMonitor::spawn(blockchain.clone(), config.mainnet_url.clone(), interval));
+        // Older self-hosted nodes lack some endpoints; find out before tools hit them
+        let (client, url) = (blockchain.clone(), config.mainnet_url.clone());
+        tokio::spawn(async move { client.probe_endpoints(&url).await });
+        let signer = config.response_signing_key.as_ref().and_then(|seed| match ResponseSigner::from_secret(seed) {
+            Ok(signer) => Some(Arc::new(signer)),
+            Err(e) => {
+                error!("MCP_RESPONSE_SIGNING_SK: {}; tool results will not be signed", e);
+                None
+            }
+        });
+        let server_key = config.server_signing_key.as_ref().and_then(|secret| match ServerKey::from_secret(secret) {
+            Ok(key) => Some(Arc::new(key)),
+            Err(e) => {
+                error!("AMADEUS_MCP_SIGNING_KEY: {}; transfer_from_server_key will fail", e);
+                None
+            }
+        });
+        Self { blockchain, config, tip, drift, signer, server_key }
+    }
+
+    /// Latest mainnet tip, once the follower has refreshed at least once.
+    fn tip(&self) -> Option<TipState> {
+        self.tip.as_ref().and_then(TipFollower::current)
+    }
+
+    /// Tip state to read for a query, only for mainnet since that is what the follower tracks.
+    fn tip_for(&self, network: Option<&str>) -> Option<TipState> {
+        match network {
+            Some("testnet") => None,
+            _ => self.tip(),
+        }
+    }
+
+    /// Adds `as_of_height` to object outputs of mainnet queries, and `reorg_warning` for a
+    /// few minutes after the follower saw a reorg.
+    fn stamp(&self, network: Option<&str>, output: Json<serde_json::Value>) -> Json<serde_json::Value> {
+        let Json(mut value) = output;
+        if let Some(tip) = self.tip_for(network) {
+            tip.stamp(&mut value);
+        }
+        Json(value)
+    }
+}
+
+#[derive(Clone)]
+pub struct BlockchainMcpServer {
+    upstream: Arc<RwLock<Arc<Upstream>>>,
+    tool_filter: Arc<RwLock<ToolFilter>>,
+    /// Number of runtime changes to the effective tool set
+    tools_revision: Arc<AtomicU64>,
+    redactor: Arc<Redactor>,
+    /// Accepted submits by scoped idempotency key, for replaying retried submits
+    submissions: Arc<TtlCache<String, SubmitRecord>>,
+    /// Blobs built by create_transaction by transaction hash, so submits can check their nonce
+    built_blobs: Arc<TtlCache<String, BlobOrigin>>,
+    /// Transactions built here, for marking them in lookups; persisted in the disk cache
+    /// directory when one is set
+    built_log: Arc<Mutex<BuiltLog>>,
+    /// User address labels shown in outputs; persisted in the disk cache directory when
+    /// one is set
+    labels: Arc<Mutex<LabelBook>>,
+    /// Upstream requests caused by this session's tool calls, checked against
+    /// `config.session_budget`
+    budget: Arc<Mutex<SessionBudget>>,
+    tool_router: ToolRouter<Self>,
+}
+
+#[tool_router]
+impl BlockchainMcpServer {
+    pub fn new(blockchain: BlockchainClient, config: ServerConfig) -> Self {
+        let built_log = config.disk_cache_dir.as_deref().map(BuiltLog::load).unwrap_or_default();
+        let label_book = config.disk_cache_dir.as_deref().map(LabelBook::load).unwrap_or_default();
+        telemetry::set_address_redaction(config.redact_addresses != RedactMode::Off);
+        Self {
+            tool_filter: Arc::new(RwLock::new(config.tool_filter.clone())),
+            redactor: Arc::new(Redactor::new(config.redact_addresses)),
+            upstream: Arc::new(RwLock::new(Arc::new(Upstream::new(blockchain, config)))),
+            tools_revision: Arc::new(AtomicU64::new(0)),
+            submissions: Arc::new(TtlCache::bounded(IDEMPOTENCY_TTL, MAX_IDEMPOTENCY_KEYS)),
+            built_blobs: Arc::new(TtlCache::bounded(BLOB_ORIGIN_TTL, MAX_BLOB_ORIGINS)),
+            built_log: Arc::new(Mutex::new(built_log)),
+            labels: Arc::new(Mutex::new(label_book)),
+            budget: Arc::new(Mutex::new(SessionBudget::default())),
+            tool_router: Self::tool_router(),
+        }
+    }
+
+    fn upstream(&self) -> Arc<Upstream> {
+        self.upstream.read().unwrap().clone()
+    }
+
+    /// Applies a new configuration: rebuilds the client for its node URL, carrying over the
+    /// history and caches the current one gathered, swaps it in for new requests and
+    /// re-evaluates the tool filter, notifying `peer` if the tool set changed. On error the
+    /// current configuration stays in place.
+    pub async fn reload(&self, config: ServerConfig, peer: Option<&Peer<RoleServer>>) -> Result<(), BlockchainError> {
+        let blockchain = BlockchainClient::from_config(&config)?.with_state_of(&self.upstream().blockchain);
+        let tool_filter = config.tool_filter.clone();
+        self.redactor.set_mode(config.redact_addresses);
+        telemetry::set_address_redaction(config.redact_addresses != RedactMode::Off);
+        *self.upstream.write().unwrap() = Arc::new(Upstream::new(blockchain, config));
+        self.set_tool_filter(tool_filter, peer).await;
+        Ok(())
+    }
+
+    /// Loads a configuration with `load`, e.g. [`ServerConfig::load`] on SIGHUP, and applies
+    /// it. A configuration that fails to load or apply is reported and the current one kept.
+    pub async fn reload_with<F>(&self, load: F, peer: Option<&Peer<RoleServer>>) -> Result<(), String>
+    where
+        F: FnOnce() -> Result<ServerConfig, String>,
+    {
+        let config = load().map_err(|e| format!("invalid configuration: {}", e))?;
+        self.reload(config, peer).await.map_err(|e| format!("failed to apply configuration: {}", e))
+    }
+
+    /// Configuration currently in effect, as of the last successful reload.
+    pub fn config(&self) -> ServerConfig {
+        self.upstream().config.clone()
+    }
+
+    /// Catalog version plus one for every runtime change to the exposed tools, so it only
+    /// ever grows within a process.
+    pub fn tools_version(&self) -> u64 {
+        TOOLS_VERSION + self.tools_revision.load(Ordering::Relaxed)
+    }
+
+    /// Tools this server currently exposes. Hosts that embed the server in their own
+    /// `ServerHandler` merge these into their `tools/list`.
+    pub fn tools(&self) -> Vec<Tool> {
+        self.enabled_tools()
+    }
+
+    /// Whether `name` is one of the exposed tools; hosts route such calls to this
+    /// server's `call_tool`, which applies the tool filter and redaction.
+    pub fn has_tool(&self, name: &str) -> bool {
+        self.enabled_tools().iter().any(|t| t.name == name)
+    }
+
+    /// Whether `uri` is served by this server's `read_resource`.
+    pub fn has_resource(uri: &str) -> bool {
+        let (base, _) = range::split_uri(uri);
+        base == VALIDATORS_URI
+            || base == STATUS_URI
+            || uri == catalog::SIGNING_WORKFLOW_URI
+            || uri == catalog::ERROR_RECOVERY_URI
+            || uri.strip_prefix(templates::TEMPLATES_URI).and_then(templates::template).is_some()
+            || uri.starts_with(catalog::TOOL_DOCS_URI)
+    }
+
+    fn enabled_tools(&self) -> Vec<Tool> {
+        let filter = self.tool_filter.read().unwrap();
+        self.tool_router
+            .list_all()
+            .into_iter()
+            .filter(|t| filter.allows(&t.name))
+            .map(Self::with_example)
+            .map(|tool| self.with_availability(tool))
+            .collect()
+    }
+
+    #[tool(
+        name = "list_tx_templates",
+        description = "Lists transaction templates for common operations: contract, function, the arguments in order with types and descriptions, and a fill-in create_transaction example. create_transaction checks calls that match a template."
+    )]
+    async fn list_tx_templates(&self) -> Result<Json<serde_json::Value>, McpError> {
+        let templates: Vec<serde_json::Value> = templates::TEMPLATES.iter().map(|t| t.describe()).collect();
+        Ok(Json(serde_json::json!({ "templates": templates })))
+    }
+
+    #[tool(
+        name = "get_signing_public_key",
+        description = "Returns the ed25519 public key (base58) that signs every tool result when response signing is on, and how the signed message is formed. enabled is false when the server does not sign."
+    )]
+    async fn get_signing_public_key(&self) -> Result<Json<serde_json::Value>, McpError> {
+        let Some(signer) = self.upstream().signer.clone() else {
+            return Ok(Json(serde_json::json!({ "enabled": false })));
+        };
+        Ok(Json(serde_json::json!({
+            "enabled": true,
+            "algorithm": attest::SIGNATURE_ALGORITHM,
+            "public_key": signer.public_key(),
+            "signed_message": attest::SIGNED_MESSAGE_FORMAT,
+        })))
+    }
+
+    /// Optional features as tools/list sees them: same config, same live tool filter.
+    fn features(&self) -> BTreeMap<&'static str, catalog::Feature> {
+        let filter = self.tool_filter.read().unwrap();
+        catalog::features(&self.upstream().config, &filter, catalog::Deployment::Stdio)
+    }
+
+    /// Marks a tool the mainnet node cannot serve, naming the endpoint it lacks.
+    fn with_availability(&self, mut tool: Tool) -> Tool {
+        let upstream = self.upstream();
+        if let Some(endpoint) = upstream.blockchain.capabilities(&upstream.config.mainnet_url).unavailable_reason(&tool.name) {
+            let description = tool.description.as_deref().unwrap_or_default();
+            tool.description = Some(format!("[Unavailable: the node does not support {}] {}", endpoint, description).into());
+        }
+        tool
+    }
+
+    /// Appends the catalog's first example arguments to a tool's description, since schemas
+    /// alone don't show things like amounts being atom strings.
+    fn with_example(mut tool: Tool) -> Tool {
+        if let Some(example) = catalog::tool_doc(&tool.name).and_then(|d| d.examples.first()) {
+            let description = tool.description.as_deref().unwrap_or_default();
+            tool.description = Some(format!("{} Example arguments: {}", description, example).into());
+        }
+        tool
+    }
+
+    /// Swaps the tool filter at runtime. When the exposed set changes, bumps the tools
+    /// version and sends `notifications/tools/list_changed` to `peer`. Returns whether
+    /// the set changed.
+    pub async fn set_tool_filter(&self, filter: ToolFilter, peer: Option<&Peer<RoleServer>>) -> bool {
+        let before = self.enabled_tools();
+        *self.tool_filter.write().unwrap() = filter;
+        let after = self.enabled_tools();
+
+        let names = |tools: &[Tool]| tools.iter().map(|t| t.name.to_string()).collect::<Vec<_>>();
+        if names(&before) == names(&after) {
+            return false;
+        }
+
+        self.tools_revision.fetch_add(1, Ordering::Relaxed);
+        if let Some(peer) = peer {
+            if let Err(e) = peer.notify_tool_list_changed().await {
+                warn!(error = %e, "failed to send tools/list_changed");
+            }
+        }
+        true
+    }
+
+    #[tool(
+        name = "get_capabilities",
+        description = "Returns the tool catalog version (toolsVersion), the tools exposed by this server, the optional features of this deployment with the reason each disabled one is off, the query limits tool arguments must stay within, and a changelog of catalog changes. Re-fetch tool schemas when toolsVersion differs from the cached one."
+    )]
+    async fn get_capabilities(&self) -> Result<Json<serde_json::Value>, McpError> {
+        let tools: Vec<String> = self.enabled_tools().iter().map(|t| t.name.to_string()).collect();
+        let limits = *self.upstream().blockchain.limits();
+        Ok(Json(catalog::capabilities(self.tools_version(), &tools, &self.features(), &limits)))
+    }
+
+    #[tool(
+        name = "validate_address",
+        description = "Checks a pasted address without touching the node: decodes it as base58 or hex (whitespace and a 0x prefix are ignored), checks it is 48 bytes and a BLS12-381 G1 subgroup point, and returns {valid, verdict, detail, encoding, normalized, base58, hex, short}; the encodings are given whenever the input decodes to 48 bytes. verdict is 'valid' or the failed check: empty, bad_encoding, wrong_length, not_on_curve, not_in_subgroup or infinity."
+    )]
+    async fn validate_address(
+        &self,
+        params: Parameters<ValidateAddressQuery>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let Json(mut output) = Self::to_json(AddressCheck::new(&params.0.address))?;
+        self.annotate_labels(&mut output);
+        Ok(Json(output))
+    }
+
+    #[tool(
+        name = "validate_transfer",
+        description = "Dry-runs a Coin transfer without building it: checks that source and destination are base58 48-byte public keys, the symbol is set, the amount parses (an integer is atoms, a decimal such as \"1.5\" is whole tokens) and the source balance covers it. Returns {valid, amount_atoms, available_atoms, checks: [{check, status: passed|failed|skipped, detail}]}; a failed balance lookup is skipped rather than failed. Optional network parameter: 'mainnet' (default) or 'testnet'."
+    )]
+    async fn validate_transfer(
+        &self,
+        params: Parameters<ValidateTransferQuery>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+
+        let upstream = self.upstream();
+        let url = match query.network.as_deref() {
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
+        };
+        let transfer = TransferRequest::new(&query.source, &query.destination, &query.amount, query.symbol.as_deref());
+        let report = upstream
+            .blockchain
+            .validate_transfer(&transfer, Some(&upstream.config.symbol_decimals), url)
+            .await;
+        let Json(mut output) = Self::to_json(report)?;
+        self.annotate_labels(&mut output);
+        Ok(upstream.stamp(query.network.as_deref(), Json(output)))
+    }
+
+    #[tool(
+        name = "create_transaction",
+        description = "Creates an unsigned transaction for any contract call. Takes signer public key, contract name, function name, and arguments. Returns transaction blob that only needs signing."
+    )]
+    async fn create_transaction(
+        &self,
+        params: Parameters<TransactionRequest>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let mut req = params.0;
+        req.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+
+        let upstream = self.upstream();
+        if let Some(transfer) = TransferRequest::from_request(&req) {
+            // Arguments are encoded as given, so the amount is checked as atoms only; an
+            // unreachable node skips the balance check rather than blocking the blob
+            upstream
+                .blockchain
+                .validate_transfer(&transfer, None, &upstream.config.mainnet_url)
+                .await
+                .into_result()
+                .map_err(|e| Self::blockchain_error("create_transaction", e))?;
+        } else if let Some(requested) = symbol::requested_symbol(&req) {
+            // Holdings only catch typos; an unreachable node must not block building the blob
+            if let Ok(account) = upstream.blockchain.get_account_balance(&req.signer, &upstream.config.mainnet_url).await {
+                symbol::check_held(requested, &account.balances)
+                    .map_err(|e| Self::blockchain_error("create_transaction", BlockchainError::ValidationFailed(e)))?;
+            }
+        }
+
+        let mut nonce_basis = if req.nonce.is_some() { NonceBasis::Explicit } else { NonceBasis::Clock };
+        let skew = upstream.tip().and_then(|tip| tip.clock_skew);
+        let clock_skew_warning = skew.and_then(|s| s.warning(upstream.config.clock_skew_threshold));
+        if let (None, Some(skew), Some(_)) = (req.nonce, skew, &clock_skew_warning) {
+            let history = upstream
+                .blockchain
+                .get_transaction_history(&req.signer, Some(freshness::NONCE_HISTORY_LIMIT), None, Some("desc"), &upstream.config.mainnet_url)
+                .await
+                .map_err(|e| Self::blockchain_error("create_transaction", e))?;
+            let nonce = clock::chain_nonce(freshness::latest_nonce(&req.signer, &history), skew.chain_time);
+            req.nonce = Some(nonce as i64);
+            nonce_basis = NonceBasis::Chain;
+        }
+        let built_req = req.clone();
+        let blob = upstream
+            .blockchain
+            .create_transaction_blob(req)
+            .await
+            .map_err(|e| Self::blockchain_error("create_transaction", e))?;
+        let origin = BlobOrigin { built_at: unix_now(), nonce_basis };
+        self.built_blobs.insert(blob.transaction_hash.clone(), origin.clone());
+        self.record_built(BuiltTransaction::new(&built_req, &blob.transaction_hash, origin.built_at));
+
+        let mut output = serde_json::json!({
+            "blob": blob.blob,
+            "signing_payload": blob.signing_payload,
+            "transaction_hash": blob.transaction_hash,
+            "nonce": blob.nonce.to_string(),
+            "nonce_basis": origin.nonce_basis,
+            "built_at": origin.built_at,
+            "status": "unsigned",
+            "next_step": "Sign the signing_payload with BLS12-381 and call submit_transaction"
+        });
+        if let Ok(fee) = FeeEstimate::for_blob(&blob.blob) {
+            output["estimated_fee"] = serde_json::to_value(fee).unwrap_or_default();
+        }
+        if let Some(warning) = clock_skew_warning {
+            output["clock_skew_warning"] = warning.into();
+        }
+        Ok(Json(output))
+    }
+
+    #[tool(
+        name = "list_built_transactions",
+        description = "Lists transactions this server built with create_transaction, newest first: hash, signer, contract, function, destination, amount, symbol, built_at and status ('observed' once a lookup saw it on chain, 'abandoned' when not seen an hour after building, 'pending' otherwise). Optional signer, status and limit (default 50, max 1000) filters. Empty when recording is off (MCP_RECORD_BUILT_TXS=0)."
+    )]
+    async fn list_built_transactions(
+        &self,
+        params: Parameters<BuiltTransactionsQuery>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+
+        let recording = self.upstream().config.record_built_transactions;
+        let transactions = self.built_log.lock().unwrap_or_else(|e| e.into_inner()).list(
+            query.signer.as_deref(),
+            query.status,
+            query.limit.unwrap_or(DEFAULT_BUILT_LIST_LIMIT) as usize,
+            unix_now(),
+        );
+        Ok(Json(serde_json::json!({
+            "count": transactions.len(),
+            "transactions": transactions,
+            "recording": recording,
+            "abandoned_after_secs": built::ABANDONED_AFTER_SECS,
+        })))
+    }
+
+    /// Remembers a transaction built here, unless recording is off.
+    fn record_built(&self, transaction: BuiltTransaction) {
+        let upstream = self.upstream();
+        if !upstream.config.record_built_transactions {
+            return;
+        }
+        let mut log = self.built_log.lock().unwrap_or_else(|e| e.into_inner());
+        log.record(transaction);
+        Self::persist_built(&log, &upstream.config);
+    }
+
+    /// Marks the transactions in a lookup output that were built here, and notes that
+    /// they reached the chain.
+    fn annotate_built(&self, output: &mut serde_json::Value) {
+        let upstream = self.upstream();
+        if !upstream.config.record_built_transactions {
+            return;
+        }
+        let seen = built::transactions_in(output);
+        let mut log = self.built_log.lock().unwrap_or_else(|e| e.into_inner());
+        let matching = log.matching(&seen);
+        if matching.is_empty() {
+            return;
+        }
+        built::annotate(output, &matching);
+        if log.observe(&seen) {
+            Self::persist_built(&log, &upstream.config);
+        }
+    }
+
+    fn persist_built(log: &BuiltLog, config: &ServerConfig) {
+        if let Some(dir) = config.disk_cache_dir.as_deref() {
+            if let Err(e) = log.save(dir) {
+                warn!(error = %e, "failed to save built transactions");
+            }
+        }
+    }
+
+    #[tool(
+        name = "add_address_label",
+        description = "Labels an address, e.g. 'exchange hot wallet', so later outputs show the label next to it (as <field>_label beside address, signer, from, to, destination and counterparty fields). Replaces the address's previous label. Built-in labels (the burn address) cannot be changed, and built-in names (burn, Coin, Epoch, Contract, Nft) cannot be used. Labels persist in MCP_DISK_CACHE_DIR when set."
+    )]
+    async fn add_address_label(
+        &self,
+        params: Parameters<AddressLabelQuery>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+
+        let mut book = self.labels.lock().unwrap_or_else(|e| e.into_inner());
+        let change = book.set(&query.address, &query.label, unix_now()).map_err(Self::label_error)?;
+        self.persist_labels(&book);
+        let Json(mut output) = Self::to_json(change)?;
+        output["address"] = serde_json::json!(query.address);
+        output["label"] = serde_json::json!(book.get(&query.address));
+        Ok(Json(output))
+    }
+
+    #[tool(
+        name = "export_address_labels",
+        description = "Returns every user address label ({address, label, added_at}), for backup or to move them to another server with import_address_labels. Built-in labels are not included."
+    )]
+    async fn export_address_labels(&self) -> Result<Json<serde_json::Value>, McpError> {
+        let labels = self.labels.lock().unwrap_or_else(|e| e.into_inner()).export();
+        Ok(Json(serde_json::json!({ "count": labels.len(), "labels": labels })))
+    }
+
+    #[tool(
+        name = "import_address_labels",
+        description = "Adds address labels in the form export_address_labels returns, replacing labels of the same address; with replace true every existing label is dropped first. Invalid entries are listed in rejected with the reason and the rest still apply. Returns the counts added, updated and unchanged."
+    )]
+    async fn import_address_labels(
+        &self,
+        params: Parameters<ImportLabelsQuery>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+
+        let mut book = self.labels.lock().unwrap_or_else(|e| e.into_inner());
+        let report = book.import(&query.labels, query.replace.unwrap_or(false), unix_now());
+        self.persist_labels(&book);
+        Self::to_json(report)
+    }
+
+    fn label_error(rejection: LabelRejection) -> McpError {
+        McpError::invalid_params(
+            "invalid_label",
+            Some(serde_json::json!({ "field": rejection.field, "message": rejection.reason })),
+        )
+    }
+
+    /// Adds the labels of the addresses in `output` next to them.
+    fn annotate_labels(&self, output: &mut serde_json::Value) {
+        let known = self
+            .labels
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .labels_for(&labels::addresses_in(output));
+        labels::annotate(output, &known);
+    }
+
+    fn persist_labels(&self, book: &LabelBook) {
+        if let Some(dir) = self.upstream().config.disk_cache_dir.as_deref() {
+            if let Err(e) = book.save(dir) {
+                warn!(error = %e, "failed to save address labels");
+            }
+        }
+    }
+
+    #[tool(
+        name = "submit_transaction",
+        description = "Submits a signed transaction to the blockchain network. Requires the transaction blob and signature from the signing process. Optional idempotency_key: a retry with the same key returns the earlier accepted result (replayed: true) instead of broadcasting again; failed submits are attempted again, and a key reused for a different blob is refused. Optional network parameter: 'mainnet' (default) or 'testnet'."
+    )]
+    async fn submit_transaction(
+        &self,
+        params: Parameters<SignedTransaction>,
+        peer: Peer<RoleServer>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let tx = params.0;
+        tx.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+
+        let upstream = self.upstream();
+        let url = match tx.network.as_deref() {
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
+        };
+
+        // A stdio server has a single client, so the key alone identifies the submit
+        let idempotency_key = tx
+            .idempotency_key
+            .as_deref()
+            .map(|key| idempotency::scoped_key("stdio", tx.network.as_deref(), key));
+        if let Some(record) = idempotency_key.as_ref().and_then(|key| self.submissions.get(key)) {
+            let recorded = record.replay(&tx.transaction).map_err(|reused| {
+                McpError::invalid_params("idempotency_key_reused", Some(serde_json::json!({ "message": reused })))
+            })?;
+            return Ok(Json(serde_json::json!({
+                "status": "success",
+                "message": "Transaction already submitted with this idempotency key",
+                "tx_hash": recorded.tx_hash,
+                "replayed": true
+            })));
+        }
+
+        let freshness = self.submit_freshness(&tx.transaction, url).await;
+        if let Some(stale) = freshness.as_ref().filter(|f| f.status == FreshnessStatus::Consumed) {
+            return Err(McpError::invalid_request("stale_blob", Some(serde_json::json!(stale))));
+        }
+        self.confirm_submit(&peer, &tx.transaction, tx.network.as_deref()).await?;
+
+        let record = idempotency_key.as_ref().map(|_| idempotency::blob_digest(&tx.transaction));
+        let response = upstream
+            .blockchain
+            .submit_signed_transaction(tx, url)
+            .await
+            .map_err(|e| Self::blockchain_error("submit_transaction", e))?;
+        if let (Some(key), Some(blob_digest)) = (idempotency_key, record) {
+            if idempotency::should_record(&response) {
+                self.submissions.insert(key, SubmitRecord { blob_digest, response: response.clone() });
+            }
+        }
+
+        match response.rejection {
+            None => {
+                let mut output = serde_json::json!({
+                    "status": "success",
+                    "message": "Transaction submitted successfully",
+                    "tx_hash": response.tx_hash
+                });
+                if let Some(warning) = freshness.and_then(|f| f.warning) {
+                    output["freshness_warning"] = warning.into();
+                }
+                Ok(Json(output))
+            }
+            Some(rejection) => Err(McpError::internal_error(
+                "submission_failed",
+                Some(serde_json::json!({
+                    "error": response.error,
+                    "hint": rejection.hint(),
+                    "recovery": recovery::for_rejection(&rejection),
+                    "rejection": rejection,
+                })),
+            )),
+        }
+    }
+
+    #[tool(
+        name = "transfer_from_server_key",
+        description = "WARNING: spends from the key held by this server (AMADEUS_MCP_SIGNING_KEY), not from the caller's wallet. Builds, signs and broadcasts a Coin.transfer of amount atoms of symbol (default AMA) to destination and returns the tx_hash and the server key's public key. Only listed when the server was started with a signing key; meant for automation such as funding test accounts. Optional network parameter: 'mainnet' (default) or 'testnet'."
+    )]
+    async fn transfer_from_server_key(
+        &self,
+        params: Parameters<ServerKeyTransferQuery>,
+        peer: Peer<RoleServer>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+
+        let upstream = self.upstream();
+        let Some(key) = upstream.server_key.clone() else {
+            return Err(McpError::invalid_request(
+                "server_key_unavailable",
+                Some(serde_json::json!({ "message": "AMADEUS_MCP_SIGNING_KEY is not set or not a valid 64-byte BLS secret" })),
+            ));
+        };
+        let url = match query.network.as_deref() {
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
+        };
+        let receiver = bs58::decode(&query.destination)
+            .into_vec()
+            .ok()
+            .filter(|pk| pk.len() == PUBLIC_KEY_LEN)
+            .ok_or_else(|| {
+                McpError::invalid_params(
+                    "invalid_destination",
+                    Some(serde_json::json!({ "destination": query.destination, "message": "expected a base58 public key" })),
+                )
+            })?;
+        let symbol = query.symbol.as_deref().map(str::trim).filter(|s| !s.is_empty()).unwrap_or("AMA");
+
+        // Same nonce rule as create_transaction: the clock, unless it is skewed against the tip
+        let skew = upstream.tip_for(query.network.as_deref()).and_then(|tip| tip.clock_skew);
+        let clock_skew_warning = skew.and_then(|s| s.warning(upstream.config.clock_skew_threshold));
+        let mut nonce = None;
+        if let (Some(skew), Some(_)) = (skew, &clock_skew_warning) {
+            let history = upstream
+                .blockchain
+                .get_transaction_history(key.public_key(), Some(freshness::NONCE_HISTORY_LIMIT), None, Some("desc"), url)
+                .await
+                .map_err(|e| Self::blockchain_error("transfer_from_server_key", e))?;
+            nonce = Some(clock::chain_nonce(freshness::latest_nonce(key.public_key(), &history), skew.chain_time));
+        }
+        let built = key.transfer(&receiver, symbol, query.amount, nonce).map_err(|e| {
+            McpError::internal_error("transfer_build_failed", Some(serde_json::json!({ "message": e })))
+        })?;
+        self.confirm_submit(&peer, &bs58::encode(&built.blob).into_string(), query.network.as_deref()).await?;
+
+        let response = upstream
+            .blockchain
+            .submit_packed_transaction(&built.packed, &built.hash, url)
+            .await
+            .map_err(|e| Self::blockchain_error("transfer_from_server_key", e))?;
+        match response.rejection {
+            None => {
+                let mut output = serde_json::json!({
+                    "status": "success",
+                    "tx_hash": response.tx_hash,
+                    "from": key.public_key(),
+                    "destination": query.destination,
+                    "amount": query.amount.to_string(),
+                    "symbol": symbol,
+                    "nonce": built.nonce.to_string(),
+                });
+                if let Some(warning) = clock_skew_warning {
+                    output["clock_skew_warning"] = warning.into();
+                }
+                self.annotate_labels(&mut output);
+                Ok(Json(output))
+            }
+            Some(rejection) => Err(McpError::internal_error(
+                "submission_failed",
+                Some(serde_json::json!({
+                    "error": response.error,
+                    "hint": rejection.hint(),
+                    "recovery": recovery::for_rejection(&rejection),
+                    "rejection": rejection,
+                })),
+            )),
+        }
+    }
+
+    #[tool(
+        name = "rebuild_and_compare",
+        description = "Rebuilds an unsigned transaction from the original create_transaction request (nonce required) and compares it byte for byte with a blob, e.g. one built on an air-gapped machine. Reports equality, the first differing byte offset and the rebuilt blob and hash."
+    )]
+    async fn rebuild_and_compare(
+        &self,
+        params: Parameters<RebuildQuery>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+        if query.request.nonce.is_none() {
+            return Err(McpError::invalid_params(
+                "nonce_required",
+                Some(serde_json::json!({ "message": "request.nonce must be set for a reproducible rebuild" })),
+            ));
+        }
+
+        let rebuilt = self
+            .upstream()
+            .blockchain
+            .create_transaction_blob(query.request)
+            .await
+            .map_err(|e| Self::blockchain_error("rebuild_and_compare", e))?;
+        let comparison = rebuild::compare_blob(&rebuilt, &query.blob).map_err(|e| {
+            McpError::invalid_params("invalid_blob", Some(serde_json::json!({ "message": e })))
+        })?;
+
+        Self::to_json(comparison)
+    }
+
+    #[tool(
+        name = "decode_transaction",
+        description = "Decodes a base58 transaction blob locally, without a node: whether it is unsigned (Tx, from create_transaction) or signed (TxU), the signer, nonce, contract, function, arguments (UTF-8 where valid, hex otherwise), attached symbol and amount, and the SHA-256 signing hash. Use it to check what a blob does before signing or submitting it."
+    )]
+    async fn decode_transaction(
+        &self,
+        params: Parameters<DecodeTransactionQuery>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+
+        let decoded = decode::decode_blob(&query.blob).map_err(|e| {
+            McpError::invalid_params("invalid_blob", Some(serde_json::json!({ "message": e })))
+        })?;
+        let Json(mut output) = Self::to_json(decoded)?;
+        self.annotate_labels(&mut output);
+        Ok(Json(output))
+    }
+
+    #[tool(
+        name = "estimate_fee",
+        description = "Estimates what a transaction will cost before it is signed, locally: give a blob from create_transaction (or a signed one), or the source, destination, amount (an integer is atoms, a decimal is whole tokens) and symbol of a Coin transfer to draft. Returns {tx_bytes, signed_bytes, fee_atoms, fee, basis}; the fee follows the protocol's byte rule on the signed size, as nodes quote no fees. create_transaction returns the same estimate as estimated_fee."
+    )]
+    async fn estimate_fee(
+        &self,
+        params: Parameters<EstimateFeeQuery>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        let estimate = query.estimate(&self.upstream().config.symbol_decimals).map_err(|e| {
+            McpError::invalid_params("invalid_transaction", Some(serde_json::json!({ "message": e })))
+        })?;
+        Self::to_json(estimate)
+    }
+
+    #[tool(
+        name = "verify_signature",
+        description = "Verifies a BLS signature from an external signer before submitting: checks it against the signing payload (hex signing_payload or base58 transaction_hash) and the signer's base58 public key with the Amadeus transaction DST. Returns valid and, when false, failed_check (bad_payload_encoding, bad_public_key, bad_signature_encoding, signature_not_in_subgroup or signature_mismatch) with a detail. Runs locally."
+    )]
+    async fn verify_signature(
+        &self,
+        params: Parameters<VerifySignatureQuery>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+
+        Self::to_json(signing::verify_external(&query.signing_payload, &query.signature, &query.public_key))
+    }
+
+    #[tool(
+        name = "check_blob_freshness",
+        description = "Checks whether an unsigned blob's nonce is still usable before signing or submitting it: compares it with the signer's latest on-chain nonce (consumed when not above it) and the clock (gapped when far in the future). For blobs built by this server it also reports the build time and age. submit_transaction runs the same check on blobs built here and refuses consumed ones. Optional network parameter: 'mainnet' (default) or 'testnet'."
+    )]
+    async fn check_blob_freshness(
+        &self,
+        params: Parameters<BlobFreshnessQuery>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+
+        let upstream = self.upstream();
+        let url = match query.network.as_deref() {
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
+        };
+        let freshness = self.blob_freshness(&query.transaction, url).await?;
+        Ok(upstream.stamp(query.network.as_deref(), Self::to_json(freshness)?))
+    }
+
+    #[tool(
+        name = "test_signing_flow",
+        description = "Diagnoses the create/sign/submit loop locally with a THROWAWAY secret key (never a funded key): builds a self-transfer blob with a fixed nonce, signs and verifies it with the Amadeus BLS DST, finalizes and decodes it, and reports which step fails (hash, DST or encoding mismatch). Pass signature to check an external signer's output for the returned signing_payload. secret_key may be omitted when the server sets MCP_KEY_TOOLS=1; a throwaway key is then generated. Never submits anything."
+    )]
+    async fn test_signing_flow(
+        &self,
+        params: Parameters<SigningFlowQuery>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+
+        let (secret_key, generated_key) = match query.secret_key {
+            Some(secret_key) => (secret_key, false),
+            None if self.upstream().config.key_tools => {
+                if query.signature.is_some() {
+                    return Err(McpError::invalid_params(
+                        "secret_key_required",
+                        Some(serde_json::json!({ "message": "signature can only be checked against the secret_key it was made with" })),
+                    ));
+                }
+                let secret_key = signing::throwaway_key().map_err(|e| {
+                    McpError::internal_error("key_generation_failed", Some(serde_json::json!({ "message": e })))
+                })?;
+                (secret_key, true)
+            }
+            None => {
+                return Err(McpError::invalid_params(
+                    "secret_key_required",
+                    Some(serde_json::json!({ "message": "pass a throwaway secret_key; generating one needs MCP_KEY_TOOLS=1" })),
+                ))
+            }
+        };
+
+        let mut report = signing::run_signing_flow(secret_key.expose(), query.signature.as_deref());
+        report.generated_key = generated_key;
+        Self::to_json(report)
+    }
+
+    #[tool(
+        name = "get_account_balance",
+        description = "Queries the balance of an account across all supported assets, or only of symbol when one is given, which takes a cheaper per-symbol lookup; a symbol the account does not hold comes back as a zero balance. Optional network parameter: 'mainnet' (default) or 'testnet'."
+    )]
+    async fn get_account_balance(
+        &self,
+        params: Parameters<AccountQuery>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+
+        let address = query.address().map_err(|e| {
+            McpError::invalid_params(
+                "invalid_address",
+                Some(serde_json::json!({ "address": query.address, "check": e.as_str(), "message": e.to_string() })),
+            )
+        })?;
+
+        let upstream = self.upstream();
+        let url = match query.network.as_deref() {
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
+        };
+
+        let balance = match query.symbol.as_deref() {
+            Some(symbol) => upstream.blockchain.get_account_symbol_balance(address.as_str(), symbol, url).await,
+            None => upstream.blockchain.get_account_balance(address.as_str(), url).await,
+        }
+        .map_err(|e| Self::blockchain_error("get_account_balance", e))?;
+
+        let Json(mut output) = Self::to_json(balance)?;
+        self.annotate_labels(&mut output);
+        Ok(upstream.stamp(query.network.as_deref(), Json(output)))
+    }
+
+    #[tool(
+        name = "check_node_health",
+        description = "Checks whether the configured node can be trusted: times a chain/stats call and, on mainnet when BLOCKCHAIN_FALLBACK_URLS is set, compares its height with the first fallback node's. Returns {url, reachable, latency_ms, height, tx_pool_size, reference_url, reference_height, height_lag, stale, error}; stale is true when the node trails the reference by more than 10 entries. An unreachable node is reported, not an error. Optional network parameter: 'mainnet' (default) or 'testnet'."
+    )]
+    async fn check_node_health(
+        &self,
+        params: Parameters<ChainStatsQuery>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        let upstream = self.upstream();
+        let (url, reference) = match query.network.as_deref() {
+            Some("testnet") => (&upstream.config.testnet_url, None),
+            _ => (&upstream.config.mainnet_url, upstream.config.mainnet_fallback_urls.first()),
+        };
+        let health = upstream.blockchain.health_check(url, reference.map(String::as_str)).await;
+        Ok(upstream.stamp(query.network.as_deref(), Self::to_json(health)?))
+    }
+
+    #[tool(
+        name = "get_chain_stats",
+        description = "Retrieves current blockchain statistics including height, total transactions, and total accounts. Optional network parameter: 'mainnet' (default) or 'testnet'."
+    )]
+    async fn get_chain_stats(
+        &self,
+        params: Parameters<ChainStatsQuery>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+
+        let upstream = self.upstream();
+        let url = match query.network.as_deref() {
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
+        };
+
+        // The follower refreshes at this interval anyway; reuse its stats while they are current
+        let fresh = upstream
+            .tip_for(query.network.as_deref())
+            .filter(|tip| upstream.config.tip_refresh.is_some_and(|interval| tip.age() <= interval));
+        let stats = match fresh {
+            Some(tip) => {
+                metrics::record(|m| m.cache_hit());
+                tip.stats
+            }
+            None => upstream
+                .blockchain
+                .get_chain_stats(url)
+                .await
+                .map_err(|e| Self::blockchain_error("get_chain_stats", e))?,
+        };
+
+        Self::to_json(stats).map(|output| upstream.stamp(query.network.as_deref(), output))
+    }
+
+    #[tool(
+        name = "get_emission_stats",
+        description = "Reports AMA emitted and burned over the last `blocks` entries, or since a moment (`since`, unix seconds or RFC3339, placed at a height like get_block_by_timestamp), by diffing chain stats snapshots taken at the window edges. States methodology and confidence, and that the direct per-entry methodology is unavailable; returns insufficient_historical_data when no earlier snapshot exists. Optional network parameter: 'mainnet' (default) or 'testnet'."
+    )]
+    async fn get_emission_stats(
+        &self,
+        params: Parameters<EmissionStatsQuery>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+
+        let window = query.window().map_err(|e| {
+            McpError::invalid_params("validation_failed", Some(serde_json::json!({ "errors": { "since": e } })))
+        })?;
+
+        let upstream = self.upstream();
+        let url = match query.network.as_deref() {
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
+        };
+
+        // Placed first, as the search reads chain stats itself and would record a later end
+        let found = match window {
+            EmissionWindow::Since(since) => Some(
+                upstream
+                    .blockchain
+                    .block_at_time(url, since, None)
+                    .await
+                    .map_err(|e| Self::blockchain_error("get_emission_stats", e))?,
+            ),
+            EmissionWindow::Blocks(_) => None,
+        };
+        let stats = upstream
+            .blockchain
+            .get_chain_stats(url)
+            .await
+            .map_err(|e| Self::blockchain_error("get_emission_stats", e))?;
+        let blocks = match (window, &found) {
+            (EmissionWindow::Blocks(blocks), _) => blocks,
+            (_, found) => found.as_ref().map_or(1, |found| emission::blocks_since(found, stats.height)),
+        };
+        let history = upstream.blockchain.stats_snapshots(url);
+
+        // get_chain_stats just recorded the window end, unless burned/circulating were missing
+        let report = history
+            .last()
+            .filter(|end| end.height == stats.height)
+            .and_then(|end| emission::emission_stats(&history, end, blocks));
+        let output = match report {
+            Some(report) => Self::to_json(emission::EmissionStats { since: found.map(|found| found.target), ..report })?,
+            None => Json(emission::insufficient_data(blocks, history.len())),
+        };
+        Ok(upstream.stamp(query.network.as_deref(), output))
+    }
+
+    #[tool(
+        name = "estimate_finality_time",
+        description = "Estimates how many seconds until a transaction submitted now is in a finalized entry. Walks back from the tip (up to sample_entries, default 32) to find how far finality trails it, and measures the entry production rate from chain stats this server recorded over the last 10 minutes. Returns the estimate with a low/high range, the finality lag and the sample sizes used, or insufficient_data rather than a guess. Optional network parameter: 'mainnet' (default) or 'testnet'."
+    )]
+    async fn estimate_finality_time(
+        &self,
+        params: Parameters<FinalityQuery>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+
+        let upstream = self.upstream();
+        let url = match query.network.as_deref() {
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
+        };
+
+        let sample = query.sample_entries.unwrap_or(DEFAULT_FINALITY_SAMPLE).into();
+        let entries = upstream
+            .blockchain
+            .entries_until_finalized(url, sample)
+            .await
+            .map_err(|e| Self::blockchain_error("estimate_finality_time", e))?;
+        let snapshots = upstream.blockchain.stats_snapshots(url);
+
+        let output = match finality::estimate(&entries, &snapshots) {
+            Some(estimate) => Self::to_json(estimate)?,
+            None => Json(finality::insufficient_data(entries.len(), finality::entry_pace(&snapshots).len())),
+        };
+        Ok(upstream.stamp(query.network.as_deref(), output))
+    }
+
+    #[tool(
+        name = "get_signer_distribution",
+        description = "Counts which validators signed the last `blocks` entries (default 100, max 1000) and joins the counts with the trainer set and epoch scores. Reports each signer's share, the expected share (1 / trainers), the ratio between them and a skew flag (over, under, normal, not_trainer), plus trainers that signed nothing. Optional network parameter: 'mainnet' (default) or 'testnet'."
+    )]
+    async fn get_signer_distribution(
+        &self,
+        params: Parameters<SignerDistributionQuery>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+
+        let upstream = self.upstream();
+        let url = match query.network.as_deref() {
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
+        };
+
+        let blocks = query.blocks.unwrap_or(DEFAULT_SIGNER_WINDOW);
+        let (from_height, to_height, entries) = upstream
+            .blockchain
+            .entries_in_window(url, blocks)
+            .await
+            .map_err(|e| Self::blockchain_error("get_signer_distribution", e))?;
+        let trainers = upstream
+            .blockchain
+            .get_validators(url, false)
+            .await
+            .map_err(|e| Self::blockchain_error("get_signer_distribution", e))?;
+
+        let distribution = signers::distribution(&entries, &trainers, from_height, to_height);
+        Ok(upstream.stamp(query.network.as_deref(), Self::to_json(distribution)?))
+    }
+
+    #[tool(
+        name = "correlate_validator_nodes",
+        description = "Matches each validator key to the peer node carrying it and reports the peer's status (connected, offline, not_connected, duplicate), version and the validator's last signed height over the last `blocks` entries (default 100, max 1000). When the peer list exposes no public keys every status is 'unavailable' instead of a guess. Optional network parameter: 'mainnet' (default) or 'testnet'."
+    )]
+    async fn correlate_validator_nodes(
+        &self,
+        params: Parameters<CorrelateValidatorsQuery>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+
+        let upstream = self.upstream();
+        let url = match query.network.as_deref() {
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
+        };
+
+        let blocks = query.blocks.unwrap_or(DEFAULT_SIGNER_WINDOW);
+        let (window, trainers, peers) = tokio::join!(
+            upstream.blockchain.entries_in_window(url, blocks),
+            upstream.blockchain.get_validators(url, false),
+            upstream.blockchain.get_peer_nodes(url),
+        );
+        let (from_height, to_height, entries) = window.map_err(|e| Self::blockchain_error("correlate_validator_nodes", e))?;
+        let trainers = trainers.map_err(|e| Self::blockchain_error("correlate_validator_nodes", e))?;
+        let peers = peers.map_err(|e| Self::blockchain_error("correlate_validator_nodes", e))?;
+
+        let correlation = correlate::correlate(&trainers, &peers, &entries, from_height, to_height);
+        Ok(upstream.stamp(query.network.as_deref(), Self::to_json(correlation)?))
+    }
+
+    #[tool(
+        name = "get_liveness",
+        description = "Answers whether the chain is alive: seconds since the latest entry, the longest gap between entries over the last `blocks` entries (default 50, max 500), txs_per_sec from chain stats and a verdict (healthy, degraded, stalled) from the server's thresholds. Optional network parameter: 'mainnet' (default) or 'testnet'."
+    )]
+    async fn get_liveness(
+        &self,
+        params: Parameters<LivenessQuery>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+
+        let upstream = self.upstream();
+        let url = match query.network.as_deref() {
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
+        };
+
+        let blocks = query.blocks.unwrap_or(DEFAULT_LIVENESS_SAMPLE);
+        let (_, _, entries) = upstream
+            .blockchain
+            .entries_in_window(url, blocks)
+            .await
+            .map_err(|e| Self::blockchain_error("get_liveness", e))?;
+        // The follower may have seen a newer entry and fresher stats than the sample
+        let tip = upstream.tip_for(query.network.as_deref());
+        let txs_per_sec = match &tip {
+            Some(tip) => tip.stats.txs_per_sec,
+            None => upstream.blockchain.get_chain_stats(url).await.ok().and_then(|s| s.txs_per_sec),
+        };
+        let tip_timestamp = tip.and_then(|t| t.clock_skew).map(|s| s.chain_time);
+
+        let liveness = liveness::assess(&entries, tip_timestamp, txs_per_sec, unix_now(), upstream.config.liveness);
+        Ok(upstream.stamp(query.network.as_deref(), Self::to_json(liveness)?))
+    }
+
+    #[tool(
+        name = "get_epoch_info",
+        description = "Returns the current epoch derived from the tip height (100000 heights per epoch): epoch, height_in_epoch, blocks_remaining, progress and next_epoch_height, with estimated_secs_to_next and estimated_next_epoch_at projected from the timestamps of recent entries. The estimates are absent when entries carry no usable timestamps. Optional network parameter: 'mainnet' (default) or 'testnet'."
+    )]
+    async fn get_epoch_info(
+        &self,
+        params: Parameters<EpochInfoQuery>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+
+        let upstream = self.upstream();
+        let url = match query.network.as_deref() {
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
+        };
+
+        let (tip, samples) = upstream
+            .blockchain
+            .epoch_pace_samples(url)
+            .await
+            .map_err(|e| Self::blockchain_error("get_epoch_info", e))?;
+        Ok(upstream.stamp(query.network.as_deref(), Self::to_json(epoch::epoch_info(tip, &samples))?))
+    }
+
+    #[tool(
+        name = "get_entry_tip",
+        description = "Returns the latest entry at the mainnet chain tip: hash, height, slot, signer, tx_count and whether it is finalized. Use it instead of guessing the current height."
+    )]
+    async fn get_entry_tip(&self) -> Result<Json<serde_json::Value>, McpError> {
+        let upstream = self.upstream();
+        let entry = upstream
+            .blockchain
+            .get_entry_tip(&upstream.config.mainnet_url)
+            .await
+            .map_err(|e| Self::blockchain_error("get_entry_tip", e))?;
+        Ok(Json(serde_json::json!({
+            "hash": entry.hash,
+            "height": entry.header.height,
+            "slot": entry.header.slot,
+            "signer": entry.header.signer,
+            "tx_count": entry.tx_count,
+            "finalized": entry.consensus.map(|c| c.finality_reached),
+        })))
+    }
+
+    #[tool(
+        name = "get_epoch_score",
+        description = "Returns the validators' mining scores for the current epoch as {count, scores}, each score with public_key, score and rank (1 = highest), highest first. With address, returns only that validator's score and rank; an address without a score this epoch is an invalid_params error. Optional network parameter: 'mainnet' (default) or 'testnet'."
+    )]
+    async fn get_epoch_score(
+        &self,
+        params: Parameters<EpochScoreQuery>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+
+        let upstream = self.upstream();
+        let url = match query.network.as_deref() {
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
+        };
+
+        let scores = upstream
+            .blockchain
+            .get_epoch_score(url, query.address.as_deref())
+            .await
+            .map_err(|e| Self::blockchain_error("get_epoch_score", e))?;
+        let output = serde_json::json!({ "count": scores.len(), "scores": scores });
+        Ok(upstream.stamp(query.network.as_deref(), Json(output)))
+    }
+
+    #[tool(
+        name = "get_emission_address",
+        description = "Returns where a validator's emissions are paid: {public_key, configured, emission_address}. configured is false, with a note and no emission_address, when the validator never set one. address is the validator's base58 public key. Optional network parameter: 'mainnet' (default) or 'testnet'."
+    )]
+    async fn get_emission_address(
+        &self,
+        params: Parameters<EmissionAddressQuery>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+
+        let upstream = self.upstream();
+        let url = match query.network.as_deref() {
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
+        };
+
+        let emission = upstream
+            .blockchain
+            .get_emission_address(&query.address, url)
+            .await
+            .map_err(|e| Self::blockchain_error("get_emission_address", e))?;
+        let Json(mut output) = Self::to_json(emission)?;
+        self.annotate_labels(&mut output);
+        Ok(upstream.stamp(query.network.as_deref(), Json(output)))
+    }
+
+    #[tool(
+        name = "get_removed_validators",
+        description = "Returns the validators removed from the set during the current epoch as {epoch, count, removed, still_listed}. still_listed holds removed keys that the current validator list still contains, which means the node's two lists disagree. epoch is left out when the node does not report it. Optional network parameter: 'mainnet' (default) or 'testnet'."
+    )]
+    async fn get_removed_validators(
+        &self,
+        params: Parameters<RemovedValidatorsQuery>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+
+        let upstream = self.upstream();
+        let url = match query.network.as_deref() {
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
+        };
+
+        let (removed, current) = tokio::try_join!(
+            upstream.blockchain.get_removed_trainers(url),
+            upstream.blockchain.get_validator_keys(url),
+        )
+        .map_err(|e| Self::blockchain_error("get_removed_validators", e))?;
+        let report = removed.against(&current);
+        if !report.still_listed.is_empty() {
+            warn!(keys = ?report.still_listed, "removed trainers are still in the trainer set");
+        }
+        let output = Self::to_json(report)?;
+        Ok(upstream.stamp(query.network.as_deref(), output))
+    }
+
+    #[tool(
+        name = "get_richlist",
+        description = "Returns the top AMA holders ranked by balance, each with rank, address, flat (atoms) and float (AMA), and the total number of holders. Use limit (default 20, max 500) and offset to page, or around_address to get the holders ranked just above and below an address together with its own rank. Optional network parameter: 'mainnet' (default) or 'testnet'. Optional detail parameter: 'full' or 'compact' (rank, address prefix and float; long pages collapsed)."
+    )]
+    async fn get_richlist(
+        &self,
+        params: Parameters<RichlistQuery>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+
+        let upstream = self.upstream();
+        let url = match query.network.as_deref() {
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
+        };
+        if let Some(limit) = query.limit {
+            upstream
+                .blockchain
+                .limits()
+                .check_richlist(limit)
+                .map_err(|e| Self::blockchain_error("get_richlist", e))?;
+        }
+
+        let holders = upstream
+            .blockchain
+            .get_richlist(url)
+            .await
+            .map_err(|e| Self::blockchain_error("get_richlist", e))?;
+        let around_address = query.around_address.as_deref();
+        // An unlisted address is placed by its own balance
+        let around_balance = match around_address {
+            Some(address) if !holders.iter().any(|h| h.address == address) => upstream
+                .blockchain
+                .get_account_balance(address, url)
+                .await
+                .ok()
+                .and_then(|b| b.balances.into_iter().find(|b| b.symbol == "AMA"))
+                .map(|b| b.float),
+            _ => None,
+        };
+        let request = WindowRequest {
+            limit: query.limit.map(|v| v as usize),
+            offset: query.offset.map(|v| v as usize),
+            around_address,
+            around_balance,
+        };
+        let window = richlist::window(&holders, |h| h.address.as_str(), |h| h.float, &request);
+        let output = match query.detail.unwrap_or(upstream.config.output_detail) {
+            OutputDetail::Full => {
+                let Json(mut output) = Self::to_json(window)?;
+                self.annotate_labels(&mut output);
+                Json(output)
+            }
+            OutputDetail::Compact => Self::to_json(CompactRichlist::from(&window))?,
+        };
+        Ok(upstream.stamp(query.network.as_deref(), output))
+    }
+
+    #[tool(
+        name = "get_nodes",
+        description = "Returns the peer nodes the upstream node is connected to, each with its IP, public key, version and latency, plus count (after filtering) and total. Optional filter keeps the peers whose IP starts with it or whose public key contains it. Optional network parameter: 'mainnet' (default) or 'testnet'."
+    )]
+    async fn get_nodes(
+        &self,
+        params: Parameters<NodesQuery>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+
+        let upstream = self.upstream();
+        let url = match query.network.as_deref() {
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
+        };
+
+        let nodes = upstream
+            .blockchain
+            .get_peer_nodes(url)
+            .await
+            .map_err(|e| Self::blockchain_error("get_nodes", e))?;
+        let output = Self::to_json(PeerList::new(nodes, query.filter.as_deref()))?;
+        Ok(upstream.stamp(query.network.as_deref(), output))
+    }
+
+    #[tool(
+        name = "get_block_by_timestamp",
+        description = "Finds the entries produced around a moment by binary-searching heights between genesis and the tip on entry timestamps, with at most max_probes entry fetches (default 40). timestamp is unix seconds or RFC3339 (e.g. '2024-06-01T12:00:00Z'). Returns {target, position, below, above, probes, converged, note}: below is the latest entry at or before the target and above the earliest after it, each {height, hash, timestamp, timestamp_ms, offset_secs}. position is exact, between, before_genesis (only above), after_tip or future (only below), with note explaining the last three. Optional network parameter: 'mainnet' (default) or 'testnet'."
+    )]
+    async fn get_block_by_timestamp(
+        &self,
+        params: Parameters<BlockAtTimeQuery>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+        let target = query.timestamp.unix_secs().map_err(|e| {
+            McpError::invalid_params("validation_failed", Some(serde_json::json!({ "errors": { "timestamp": e } })))
+        })?;
+
+        let upstream = self.upstream();
+        let url = match query.network.as_deref() {
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
+        };
+
+        let found = upstream
+            .blockchain
+            .block_at_time(url, target, query.max_probes)
+            .await
+            .map_err(|e| Self::blockchain_error("get_block_by_timestamp", e))?;
+        Ok(upstream.stamp(query.network.as_deref(), Self::to_json(found)?))
+    }
+
+    #[tool(
+        name = "get_block_by_height",
+        description = "Retrieves blockchain entries at a specific height. Returns all entries for that height. Optional network parameter: 'mainnet' (default) or 'testnet'. Optional detail parameter: 'full' or 'compact' (abbreviated entries)."
+    )]
+    async fn get_block_by_height(
+        &self,
+        params: Parameters<HeightQuery>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+
+        let upstream = self.upstream();
+        let url = match query.network.as_deref() {
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
+        };
+
+        if let Some(tip) = upstream.tip_for(query.network.as_deref()).filter(|tip| tip.is_beyond(query.height)) {
+            return Err(McpError::invalid_params(
+                "height_beyond_tip",
+                Some(serde_json::json!({ "height": query.height, "tip_height": tip.height })),
+            ));
+        }
+
+        let entries = upstream
+            .blockchain
+            .get_block_by_height(query.height, url)
+            .await
+            .map_err(|e| Self::blockchain_error("get_block_by_height", e))?;
+
+        let output = match query.detail.unwrap_or(upstream.config.output_detail) {
+            OutputDetail::Full => Self::to_json(entries)?,
+            OutputDetail::Compact => Self::to_json(CompactList::new(
+                entries.iter().map(CompactBlockEntry::from),
+            ))?,
+        };
+        Ok(upstream.stamp(query.network.as_deref(), output))
+    }
+
+    #[tool(
+        name = "get_entry_by_hash",
+        description = "Retrieves one blockchain entry by its base58 hash, in the same shape as the entries of get_block_by_height. The hash is checked to be base58 before the node is asked; an unknown hash fails with not_found. Also readable as the resource amadeus://entry/{hash}. Optional network parameter: 'mainnet' (default) or 'testnet'. Optional detail parameter: 'full' or 'compact'."
+    )]
+    async fn get_entry_by_hash(
+        &self,
+        params: Parameters<EntryHashQuery>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+
+        let upstream = self.upstream();
+        let url = match query.network.as_deref() {
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
+        };
+
+        let entry = upstream
+            .blockchain
+            .get_entry_by_hash(&query.hash, url)
+            .await
+            .map_err(|e| Self::blockchain_error("get_entry_by_hash", e))?;
+
+        let output = match query.detail.unwrap_or(upstream.config.output_detail) {
+            OutputDetail::Full => {
+                let Json(mut output) = Self::to_json(entry)?;
+                self.annotate_labels(&mut output);
+                Json(output)
+            }
+            OutputDetail::Compact => Self::to_json(CompactBlockEntry::from(&entry))?,
+        };
+        Ok(upstream.stamp(query.network.as_deref(), output))
+    }
+
+    #[tool(
+        name = "get_block_range",
+        description = "Retrieves the entries of every height from start_height to end_height (inclusive, at most 100 heights) in one call, fetched eight heights at a time. Returns heights in order, each with entry_count, tx_count and its entries, plus range totals. Heights that could not be fetched are listed under failed instead of failing the call. Optional network parameter: 'mainnet' (default) or 'testnet'."
+    )]
+    async fn get_block_range(
+        &self,
+        params: Parameters<BlockRangeQuery>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+
+        let upstream = self.upstream();
+        let url = match query.network.as_deref() {
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
+        };
+
+        if let Some(tip) = upstream.tip_for(query.network.as_deref()).filter(|tip| tip.is_beyond(query.end_height)) {
+            return Err(McpError::invalid_params(
+                "height_beyond_tip",
+                Some(serde_json::json!({ "height": query.end_height, "tip_height": tip.height })),
+            ));
+        }
+
+        let range = upstream
+            .blockchain
+            .entries_in_range(url, query.start_height, query.end_height)
+            .await
+            .map_err(|e| Self::blockchain_error("get_block_range", e))?;
+        let Json(mut output) = Self::to_json(range)?;
+        self.annotate_labels(&mut output);
+        Ok(upstream.stamp(query.network.as_deref(), Json(output)))
+    }
+
+    #[tool(
+        name = "get_blocks_by_signer",
+        description = "Lists the entries a validator key signed between start_height and end_height (inclusive, at most 500 heights), fetched eight heights at a time. Returns count, heights_signed, share_percent of the fetched entries, entries_scanned and the signed entries in height order. Heights that could not be fetched are listed under failed and left out of the share. Optional network parameter: 'mainnet' (default) or 'testnet'."
+    )]
+    async fn get_blocks_by_signer(
+        &self,
+        params: Parameters<BlocksBySignerQuery>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+
+        let upstream = self.upstream();
+        let url = match query.network.as_deref() {
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
+        };
+
+        if let Some(tip) = upstream.tip_for(query.network.as_deref()).filter(|tip| tip.is_beyond(query.end_height)) {
+            return Err(McpError::invalid_params(
+                "height_beyond_tip",
+                Some(serde_json::json!({ "height": query.end_height, "tip_height": tip.height })),
+            ));
+        }
+
+        let signed = upstream
+            .blockchain
+            .entries_signed_by(url, &query.signer, query.start_height, query.end_height)
+            .await
+            .map_err(|e| Self::blockchain_error("get_blocks_by_signer", e))?;
+        let Json(mut output) = Self::to_json(signed)?;
+        self.annotate_labels(&mut output);
+        Ok(upstream.stamp(query.network.as_deref(), Json(output)))
+    }
+
+    #[tool(
+        name = "get_recent_blocks",
+        description = "Summarizes the newest heights in one call: reads the tip height from chain stats, fetches the last count heights (default 10, max 50) eight at a time, and returns for each height its entry_hashes, signers, tx_count, timestamp_ms and interval_ms since the height below, oldest first. Totals: tx_count, unique_signers and avg_interval_ms. Heights that could not be fetched are listed under failed. Optional network parameter: 'mainnet' (default) or 'testnet'."
+    )]
+    async fn get_recent_blocks(
+        &self,
+        params: Parameters<RecentBlocksQuery>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+
+        let upstream = self.upstream();
+        let url = match query.network.as_deref() {
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
+        };
+
+        let recent = upstream
+            .blockchain
+            .recent_blocks(url, query.count.unwrap_or(DEFAULT_RECENT_BLOCKS))
+            .await
+            .map_err(|e| Self::blockchain_error("get_recent_blocks", e))?;
+        let Json(mut output) = Self::to_json(recent)?;
+        self.annotate_labels(&mut output);
+        Ok(upstream.stamp(query.network.as_deref(), Json(output)))
+    }
+
+    #[tool(
+        name = "get_block_with_txs",
+        description = "Retrieves the entries at a height together with their transactions, saving a get_transaction call per hash. Each entry carries txs_total and a page of txs (tx_limit, default 100, max 500, from tx_offset); when transactions were left out it also carries next_tx_offset and a note. Optional network parameter: 'mainnet' (default) or 'testnet'. Optional detail parameter: 'full' or 'compact' (abbreviated entry and transactions, long pages collapsed)."
+    )]
+    async fn get_block_with_txs(
+        &self,
+        params: Parameters<BlockWithTxsQuery>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+
+        let upstream = self.upstream();
+        let url = match query.network.as_deref() {
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
+        };
+
+        if let Some(tip) = upstream.tip_for(query.network.as_deref()).filter(|tip| tip.is_beyond(query.height)) {
+            return Err(McpError::invalid_params(
+                "height_beyond_tip",
+                Some(serde_json::json!({ "height": query.height, "tip_height": tip.height })),
+            ));
+        }
+
+        let entries = upstream
+            .blockchain
+            .get_block_with_txs(query.height, url)
+            .await
+            .map_err(|e| Self::blockchain_error("get_block_with_txs", e))?;
+        let pages = block_txs::page(
+            entries,
+            query.tx_offset.unwrap_or(0) as usize,
+            query.tx_limit.unwrap_or(block_txs::DEFAULT_BLOCK_TXS) as usize,
+        );
+        let output = match query.detail.unwrap_or(upstream.config.output_detail) {
+            OutputDetail::Full => {
+                let Json(mut output) = Self::to_json(pages)?;
+                self.annotate_labels(&mut output);
+                Json(output)
+            }
+            OutputDetail::Compact => Self::to_json(pages.iter().map(CompactEntryTxsPage::from).collect::<Vec<_>>())?,
+        };
+        Ok(upstream.stamp(query.network.as_deref(), output))
+    }
+
+    #[tool(
+        name = "get_txs_in_entry",
+        description = "Lists every transaction included in an entry, by entry hash, as {entry_hash, count, txs}; contract calls include decoded_action. Transactions the typed form does not cover are returned as sent under undecoded. An unknown entry hash fails with not_found. Optional network parameter: 'mainnet' (default) or 'testnet'. Optional detail parameter: 'full' or 'compact'."
+    )]
+    async fn get_txs_in_entry(
+        &self,
+        params: Parameters<TxsInEntryQuery>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+
+        let upstream = self.upstream();
+        let url = match query.network.as_deref() {
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
+        };
+
+        let entry = upstream
+            .blockchain
+            .get_txs_in_entry(&query.entry_hash, url)
+            .await
+            .map_err(|e| Self::blockchain_error("get_txs_in_entry", e))?;
+
+        let output = match query.detail.unwrap_or(upstream.config.output_detail) {
+            OutputDetail::Full => {
+                let Json(mut output) = Self::to_json(entry)?;
+                self.annotate_labels(&mut output);
+                Json(output)
+            }
+            OutputDetail::Compact => Self::to_json(CompactList::new(
+                entry.txs.iter().map(CompactTransaction::from),
+            ))?,
+        };
+        Ok(upstream.stamp(query.network.as_deref(), output))
+    }
+
+    #[tool(
+        name = "wait_for_transaction",
+        description = "Waits until the node knows a transaction, polling get_transaction every poll_interval_secs (default 2) for up to timeout_secs (default 60, max 300). Returns status 'found' with the transaction, the number of polls and elapsed_ms, or status 'pending' when the timeout passes first; a timeout is not an error. Stops as soon as the call is cancelled. Optional network parameter: 'mainnet' (default) or 'testnet'."
+    )]
+    async fn wait_for_transaction(
+        &self,
+        params: Parameters<WaitForTransactionQuery>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+
+        let upstream = self.upstream();
+        let url = match query.network.as_deref() {
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
+        };
+        let timeout_secs = query.timeout_secs.unwrap_or(wait::DEFAULT_WAIT_TIMEOUT_SECS);
+        let interval = std::time::Duration::from_secs(query.poll_interval_secs.unwrap_or(wait::DEFAULT_WAIT_POLL_SECS));
+        let started = std::time::Instant::now();
+        let deadline = started + std::time::Duration::from_secs(timeout_secs);
+        let cancelled = || McpError::internal_error("cancelled", Some(serde_json::json!({ "tx_hash": query.tx_hash })));
+
+        let mut polls = 0;
+        let outcome = loop {
+            polls += 1;
+            let polled = tokio::select! {
+                _ = context.ct.cancelled() => return Err(cancelled()),
+                polled = upstream.blockchain.get_transaction(&query.tx_hash, url) => polled,
+            };
+            let elapsed_ms = started.elapsed().as_millis() as u64;
+            let last_error = match polled {
+                Ok(transaction) => {
+                    break WaitOutcome::Found { polls, elapsed_ms, transaction: Box::new(transaction) };
+                }
+                Err(BlockchainError::NotFound { .. }) => None,
+                Err(e) if wait::keeps_polling(&e) => Some(e.to_string()),
+                Err(e) => return Err(Self::blockchain_error("wait_for_transaction", e)),
+            };
+            let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+            if remaining.is_zero() {
+                break WaitOutcome::Pending { polls, elapsed_ms, timeout_secs, last_error, hint: wait::PENDING_HINT };
+            }
+            tokio::select! {
+                _ = context.ct.cancelled() => return Err(cancelled()),
+                _ = tokio::time::sleep(interval.min(remaining)) => {}
+            }
+        };
+
+        let Json(mut output) = Self::to_json(outcome)?;
+        self.annotate_built(&mut output);
+        self.annotate_labels(&mut output);
+        Ok(upstream.stamp(query.network.as_deref(), Json(output)))
+    }
+
+    #[tool(
+        name = "get_account_nonce",
+        description = "Returns an account's latest nonce and the nonce to build its next transaction with, for building transactions outside this server: {address, nonce, has_transacted, next_nonce}. Nonces are strings of nanosecond values; an account that never sent a transaction has nonce '0'. Optional network parameter: 'mainnet' (default) or 'testnet'."
+    )]
+    async fn get_account_nonce(
+        &self,
+        params: Parameters<NonceQuery>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+
+        let upstream = self.upstream();
+        let url = match query.network.as_deref() {
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
+        };
+
+        let nonce = upstream
+            .blockchain
+            .get_account_nonce(&query.address, url)
+            .await
+            .map_err(|e| Self::blockchain_error("get_account_nonce", e))?;
+        let Json(mut output) = Self::to_json(nonce)?;
+        self.annotate_labels(&mut output);
+        Ok(upstream.stamp(query.network.as_deref(), Json(output)))
+    }
+
+    #[tool(
+        name = "get_pending_transactions",
+        description = "Lists transactions waiting in the node's pool, not yet included in an entry: {pool_size, count, transactions}. Pass address to keep only transactions it signed or receives a transfer in, e.g. to check whether a submitted transfer is stuck. An empty pool returns an empty list. Optional network parameter: 'mainnet' (default) or 'testnet'."
+    )]
+    async fn get_pending_transactions(
+        &self,
+        params: Parameters<PendingTransactionsQuery>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+
+        let upstream = self.upstream();
+        let url = match query.network.as_deref() {
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
+        };
+
+        let pool = upstream.blockchain.get_tx_pool(url).await.map_err(|e| {
+            let unsupported = matches!(e, BlockchainError::EndpointUnsupported { .. });
+            let mut mapped = Self::blockchain_error("get_pending_transactions", e);
+            if let Some(data) = mapped.data.as_mut().filter(|_| unsupported) {
+                data["message"] = serde_json::json!(tx_pool::TX_POOL_UNSUPPORTED);
+            }
+            mapped
+        })?;
+        let Json(mut output) = Self::to_json(PendingTransactions::new(pool, query.address.as_deref()))?;
+        self.annotate_labels(&mut output);
+        Ok(upstream.stamp(query.network.as_deref(), Json(output)))
+    }
+
+    #[tool(
+        name = "get_transaction",
+        description = "Retrieves a specific transaction by its hash. Returns detailed transaction information; contract calls include decoded_action with typed arguments. Optional network parameter: 'mainnet' (default) or 'testnet'."
+    )]
+    async fn get_transaction(
+        &self,
+        params: Parameters<TransactionQuery>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+
+        let upstream = self.upstream();
+        let url = match query.network.as_deref() {
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
+        };
+
+        let transaction = upstream
+            .blockchain
+            .get_transaction(&query.tx_hash, url)
+            .await
+            .map_err(|e| Self::blockchain_error("get_transaction", e))?;
+
+        let Json(mut output) = Self::to_json(transaction)?;
+        self.annotate_built(&mut output);
+        self.annotate_labels(&mut output);
+        Ok(upstream.stamp(query.network.as_deref(), Json(output)))
+    }
+
+    #[tool(
+        name = "get_transaction_history",
+        description = "Retrieves transaction history for a specific account. Supports pagination with limit, offset, and sort parameters. Optional network parameter: 'mainnet' (default) or 'testnet'. Optional detail parameter: 'full' or 'compact' (short hashes, only from/to/amount/symbol/height, long lists collapsed). Optional filters: direction ('sent' or 'received'), symbol, counterparty, after_timestamp and before_timestamp (unix seconds); with any filter set, pages are pulled until limit transactions match (default 20) and the response reports how many were scanned, with next_offset to continue."
+    )]
+    async fn get_transaction_history(
+        &self,
+        params: Parameters<TransactionHistoryQuery>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+
+        let upstream = self.upstream();
+        let url = match query.network.as_deref() {
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
+        };
+
+        let detail = query.detail.unwrap_or(upstream.config.output_detail);
+        let filter = query.filter();
+        let Json(mut output) = if filter.is_empty() {
+            let transactions = upstream
+                .blockchain
+                .get_transaction_history(
+                    &query.address,
+                    query.limit,
+                    query.offset,
+                    query.sort.as_deref(),
+                    url,
+                )
+                .await
+                .map_err(|e| Self::blockchain_error("get_transaction_history", e))?;
+            match detail {
+                OutputDetail::Full => Self::to_json(transactions)?,
+                OutputDetail::Compact => Self::to_json(CompactList::new(
+                    transactions.iter().map(CompactTransaction::from),
+                ))?,
+            }
+        } else {
+            let history = upstream
+                .blockchain
+                .get_filtered_history(
+                    &query.address,
+                    &filter,
+                    query.limit,
+                    query.offset,
+                    query.sort.as_deref(),
+                    url,
+                )
+                .await
+                .map_err(|e| Self::blockchain_error("get_transaction_history", e))?;
+            match detail {
+                OutputDetail::Full => Self::to_json(history)?,
+                OutputDetail::Compact => Self::to_json(history.map(|transactions| {
+                    CompactList::new(transactions.iter().map(CompactTransaction::from))
+                }))?,
+            }
+        };
+        self.annotate_built(&mut output);
+        self.annotate_labels(&mut output);
+        Ok(upstream.stamp(query.network.as_deref(), Json(output)))
+    }
+
+    #[tool(
+        name = "get_full_transaction_history",
+        description = "Collects an account's whole transaction history for exports, paging oldest first (page_size, default 100) until the node returns a short page or max_transactions is reached (default and max 10000 unless the server is configured otherwise). Returns {address, count, pages, duplicates, complete, max_transactions, first_height, last_height, first_timestamp, last_timestamp, transactions}; transactions a shifted page repeats are dropped and counted in duplicates, and complete is false when the cap cut the history short. Sends a progress notification per page when the request carries a progress token, and stops as soon as the call is cancelled. Optional network parameter: 'mainnet' (default) or 'testnet'."
+    )]
+    async fn get_full_transaction_history(
+        &self,
+        params: Parameters<FullHistoryQuery>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+
+        let upstream = self.upstream();
+        let url = match query.network.as_deref() {
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
+        };
+        let progress_token = context.meta.get_progress_token();
+        let on_page = |page: full_history::HistoryPage| {
+            let peer = context.peer.clone();
+            let progress_token = progress_token.clone();
+            async move {
+                let Some(progress_token) = progress_token else { return };
+                let notified = peer
+                    .notify_progress(ProgressNotificationParam {
+                        progress_token,
+                        progress: page.collected as f64,
+                        total: None,
+                        message: Some(format!("page {}: {} transactions collected", page.page, page.collected)),
+                    })
+                    .await;
+                if let Err(e) = notified {
+                    warn!(error = %e, "failed to send history progress");
+                }
+            }
+        };
+        let fetch = upstream.blockchain.get_full_transaction_history(
+            &query.address,
+            query.page_size,
+            query.max_transactions,
+            url,
+            on_page,
+        );
+        let history = tokio::select! {
+            _ = context.ct.cancelled() => {
+                return Err(McpError::internal_error("cancelled", Some(serde_json::json!({ "address": query.address }))));
+            }
+            history = fetch => history.map_err(|e| Self::blockchain_error("get_full_transaction_history", e))?,
+        };
+
+        let Json(mut output) = Self::to_json(history)?;
+        self.annotate_built(&mut output);
+        self.annotate_labels(&mut output);
+        Ok(upstream.stamp(query.network.as_deref(), Json(output)))
+    }
+
+    #[tool(
+        name = "analyze_account",
+        description = "Summarizes an account's whole history, collected as get_full_transaction_history does (page_size, max_transactions): per-symbol totals sent and received in atoms, the top 10 counterparties by transfer count and, per symbol, by volume, the fee total (exec_used of transactions it signed), failed and self transfers, and first/last heights and timestamps. complete is false when max_transactions cut the history short. Sends a progress notification per page when the request carries a progress token, and stops as soon as the call is cancelled. Optional network parameter: 'mainnet' (default) or 'testnet'."
+    )]
+    async fn analyze_account(
+        &self,
+        params: Parameters<FullHistoryQuery>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+
+        let address = query.address().map_err(|e| {
+            McpError::invalid_params(
+                "invalid_address",
+                Some(serde_json::json!({ "address": query.address, "check": e.as_str(), "message": e.to_string() })),
+            )
+        })?;
 
-#[tool_router]
-impl BlockchainMcpServer {
-    pub fn new(blockchain: BlockchainClient, mainnet_url: String, testnet_url: String) -> Self {
-        Self {
-            blockchain: Arc::new(blockchain),
-            mainnet_url,
-            testnet_url,
-            tool_router: Self::tool_router(),
-        }
+        let upstream = self.upstream();
+        let url = match query.network.as_deref() {
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
+        };
+        let progress_token = context.meta.get_progress_token();
+        let on_page = |page: full_history::HistoryPage| {
+            let peer = context.peer.clone();
+            let progress_token = progress_token.clone();
+            async move {
+                let Some(progress_token) = progress_token else { return };
+                let notified = peer
+                    .notify_progress(ProgressNotificationParam {
+                        progress_token,
+                        progress: page.collected as f64,
+                        total: None,
+                        message: Some(format!("page {}: {} transactions collected", page.page, page.collected)),
+                    })
+                    .await;
+                if let Err(e) = notified {
+                    warn!(error = %e, "failed to send history progress");
+                }
+            }
+        };
+        let fetch = upstream.blockchain.get_full_transaction_history(
+            address.as_str(),
+            query.page_size,
+            query.max_transactions,
+            url,
+            on_page,
+        );
+        let history = tokio::select! {
+            _ = context.ct.cancelled() => {
+                return Err(McpError::internal_error("cancelled", Some(serde_json::json!({ "address": address.as_str() }))));
+            }
+            history = fetch => history.map_err(|e| Self::blockchain_error("analyze_account", e))?,
+        };
+
+        let Json(mut output) = Self::to_json(AccountActivity::from_history(&history))?;
+        self.annotate_labels(&mut output);
+        Ok(upstream.stamp(query.network.as_deref(), Json(output)))
     }
 
     #[tool(
-        name = "create_transaction",
-        description = "Creates an unsigned transaction for any contract call. Takes signer public key, contract name, function name, and arguments. Returns transaction blob that only needs signing."
+        name = "get_validators",
+        description = "Retrieves the list of current validator nodes (trainers) in the network with their epoch score and rank. Set keys_only to skip the score lookup. Optional network parameter: 'mainnet' (default) or 'testnet'."
     )]
-    async fn create_transaction(
+    async fn get_validators(
         &self,
-        params: Parameters<TransactionRequest>,
+        params: Parameters<ValidatorsQuery>,
     ) -> Result<Json<serde_json::Value>, McpError> {
-        let req = params.0;
-        req.validate().map_err(|e| {
+        let query = params.0;
+        query.validate().map_err(|e| {
             McpError::invalid_params(
                 "validation_failed",
                 Some(serde_json::json!({ "errors": e })),
             )
         })?;
 
-        let blob = self
+        let upstream = self.upstream();
+        let url = match query.network.as_deref() {
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
+        };
+
+        let validators = upstream
             .blockchain
-            .create_transaction_blob(req)
+            .get_validators(url, query.keys_only.unwrap_or(false))
             .await
-            .map_err(|e| Self::blockchain_error("create_transaction", e))?;
+            .map_err(|e| Self::blockchain_error("get_validators", e))?;
 
-        Ok(Json(serde_json::json!({
-            "blob": blob.blob,
-            "signing_payload": blob.signing_payload,
-            "transaction_hash": blob.transaction_hash,
-            "status": "unsigned",
-            "next_step": "Sign the signing_payload with BLS12-381 and call submit_transaction"
-        })))
+        Ok(upstream.stamp(query.network.as_deref(), Json(Self::validators_envelope(&validators))))
     }
 
     #[tool(
-        name = "submit_transaction",
-        description = "Submits a signed transaction to the blockchain network. Requires the transaction blob and signature from the signing process. Optional network parameter: 'mainnet' (default) or 'testnet'."
+        name = "get_validator_details",
+        description = "Returns validators with their epoch score, rank, epoch and emission address, combining the trainer list with per-validator score and emission address lookups. Pass public_key for one validator; omit it for all (slower: two requests per validator). A failed lookup leaves that validator's fields out instead of failing the call. Optional network parameter: 'mainnet' (default) or 'testnet'."
     )]
-    async fn submit_transaction(
+    async fn get_validator_details(
         &self,
-        params: Parameters<SignedTransaction>,
+        params: Parameters<ValidatorDetailsQuery>,
     ) -> Result<Json<serde_json::Value>, McpError> {
-        let tx = params.0;
-        tx.validate().map_err(|e| {
+        let query = params.0;
+        query.validate().map_err(|e| {
             McpError::invalid_params(
                 "validation_failed",
                 Some(serde_json::json!({ "errors": e })),
             )
         })?;
 
-        let url = match tx.network.as_deref() {
-            Some("testnet") => &self.testnet_url,
-            _ => &self.mainnet_url,
+        let upstream = self.upstream();
+        let url = match query.network.as_deref() {
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
         };
 
-        let response = self
+        let validators = upstream
             .blockchain
-            .submit_signed_transaction(tx, url)
+            .get_validator_details(url, query.public_key.as_deref())
             .await
-            .map_err(|e| Self::blockchain_error("submit_transaction", e))?;
-
-        if response.error == "ok" {
-            Ok(Json(serde_json::json!({
-                "status": "success",
-                "message": "Transaction submitted successfully",
-                "tx_hash": response.tx_hash
-            })))
-        } else {
-            Err(McpError::internal_error(
-                "submission_failed",
-                Some(serde_json::json!({ "error": response.error })),
-            ))
-        }
+            .map_err(|e| Self::blockchain_error("get_validator_details", e))?;
+        let mut output = Self::validators_envelope(&validators);
+        self.annotate_labels(&mut output);
+        Ok(upstream.stamp(query.network.as_deref(), Json(output)))
     }
 
     #[tool(
-        name = "get_account_balance",
-        description = "Queries the balance of an account across all supported assets. Optional network parameter: 'mainnet' (default) or 'testnet'."
+        name = "preview_contract_call",
+        description = "Executes a contract call on the node without a transaction and returns its result, or the revert reason when it would fail. Arguments follow create_transaction conventions, so the preview matches what would be submitted. Unavailable on nodes without a read-only call endpoint. Optional network parameter: 'mainnet' (default) or 'testnet'."
     )]
-    async fn get_account_balance(
+    async fn preview_contract_call(
         &self,
-        params: Parameters<AccountQuery>,
+        params: Parameters<ContractCallPreviewQuery>,
     ) -> Result<Json<serde_json::Value>, McpError> {
         let query = params.0;
         query.validate().map_err(|e| {
@@ -120,27 +2248,58 @@ impl BlockchainMcpServer {
             )
         })?;
 
+        let upstream = self.upstream();
         let url = match query.network.as_deref() {
-            Some("testnet") => &self.testnet_url,
-            _ => &self.mainnet_url,
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
         };
 
-        let balance = self
+        let preview = upstream
             .blockchain
-            .get_account_balance(&query.address, url)
+            .preview_contract_call(&query.contract, &query.function, &query.args, url)
             .await
-            .map_err(|e| Self::blockchain_error("get_account_balance", e))?;
+            .map_err(|e| Self::blockchain_error("preview_contract_call", e))?;
+        Ok(upstream.stamp(query.network.as_deref(), Self::to_json(preview)?))
+    }
+
+    #[tool(
+        name = "get_token_info",
+        description = "Returns a token's total supply, decimals and burned amount from the Coin contract: {symbol, total_supply_flat, total_supply_float, decimals, burned}. Amounts use the token's own decimals. Omit symbol to list every token as {count, tokens}. An unknown symbol fails with not_found. Optional network parameter: 'mainnet' (default) or 'testnet'."
+    )]
+    async fn get_token_info(
+        &self,
+        params: Parameters<TokenInfoQuery>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+
+        let upstream = self.upstream();
+        let url = match query.network.as_deref() {
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
+        };
+        let registry = &upstream.config.symbol_decimals;
 
-        Self::to_json(balance)
+        let failed = |e| Self::blockchain_error("get_token_info", e);
+        let output = match query.symbol.as_deref() {
+            Some(symbol) => Self::to_json(upstream.blockchain.get_token_info(symbol, registry, url).await.map_err(failed)?)?,
+            None => Self::to_json(upstream.blockchain.list_tokens(registry, url).await.map_err(failed)?)?,
+        };
+        Ok(upstream.stamp(query.network.as_deref(), output))
     }
 
     #[tool(
-        name = "get_chain_stats",
-        description = "Retrieves current blockchain statistics including height, total transactions, and total accounts. Optional network parameter: 'mainnet' (default) or 'testnet'."
+        name = "get_contract_state",
+        description = "Retrieves a specific value from smart contract storage by contract address and key. Optional network parameter: 'mainnet' (default) or 'testnet'."
     )]
-    async fn get_chain_stats(
+    async fn get_contract_state(
         &self,
-        params: Parameters<ChainStatsQuery>,
+        params: Parameters<ContractStateQuery>,
     ) -> Result<Json<serde_json::Value>, McpError> {
         let query = params.0;
         query.validate().map_err(|e| {
@@ -150,27 +2309,38 @@ impl BlockchainMcpServer {
             )
         })?;
 
+        let upstream = self.upstream();
         let url = match query.network.as_deref() {
-            Some("testnet") => &self.testnet_url,
-            _ => &self.mainnet_url,
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
         };
 
-        let stats = self
+        let state = upstream
             .blockchain
-            .get_chain_stats(url)
+            .get_contract_state(&query.contract_address, &query.key, url)
             .await
-            .map_err(|e| Self::blockchain_error("get_chain_stats", e))?;
+            .map_err(|e| Self::blockchain_error("get_contract_state", e))?;
+        if let Some(tip) = upstream.tip_for(query.network.as_deref()) {
+            upstream
+                .blockchain
+                .record_contract_state(url, &query.contract_address, &query.key, tip.height, state.clone());
+        }
 
-        Self::to_json(stats)
+        let output = Json(serde_json::json!({
+            "contract_address": query.contract_address,
+            "key": query.key,
+            "value": state
+        }));
+        Ok(upstream.stamp(query.network.as_deref(), output))
     }
 
     #[tool(
-        name = "get_block_by_height",
-        description = "Retrieves blockchain entries at a specific height. Returns all entries for that height. Optional network parameter: 'mainnet' (default) or 'testnet'."
+        name = "export_contract_state",
+        description = "Exports a contract's storage keys under a prefix (all keys when empty) with each value tagged as integer, address, base58, utf8 or raw. format: 'json' (default) or 'csv'. Returns up to limit keys (default 200, max 1000) in key order; when more remain, complete is false and next_cursor is set: pass it back as cursor with the same contract_address and prefix for the next page. Optional network parameter: 'mainnet' (default) or 'testnet'."
     )]
-    async fn get_block_by_height(
+    async fn export_contract_state(
         &self,
-        params: Parameters<HeightQuery>,
+        params: Parameters<ContractExportQuery>,
     ) -> Result<Json<serde_json::Value>, McpError> {
         let query = params.0;
         query.validate().map_err(|e| {
@@ -179,28 +2349,45 @@ impl BlockchainMcpServer {
                 Some(serde_json::json!({ "errors": e })),
             )
         })?;
+        let after = query
+            .cursor
+            .as_deref()
+            .map(|cursor| state_export::decode_cursor(cursor, &query.contract_address, &query.prefix))
+            .transpose()
+            .map_err(|e| McpError::invalid_params("invalid_cursor", Some(serde_json::json!({ "message": e }))))?;
 
+        let upstream = self.upstream();
         let url = match query.network.as_deref() {
-            Some("testnet") => &self.testnet_url,
-            _ => &self.mainnet_url,
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
         };
-
-        let entries = self
+        let page = upstream
             .blockchain
-            .get_block_by_height(query.height, url)
+            .export_contract_state(
+                &query.contract_address,
+                &query.prefix,
+                after.as_deref(),
+                query.limit.unwrap_or(DEFAULT_EXPORT_LIMIT),
+                url,
+            )
             .await
-            .map_err(|e| Self::blockchain_error("get_block_by_height", e))?;
+            .map_err(|e| Self::blockchain_error("export_contract_state", e))?;
 
-        Self::to_json(entries)
+        let Json(mut output) = Self::to_json(&page)?;
+        if let (ExportFormat::Csv, Some(fields)) = (query.format.unwrap_or_default(), output.as_object_mut()) {
+            fields.remove("keys");
+            fields.insert("csv".to_string(), state_export::to_csv(&page.keys).into());
+        }
+        Ok(upstream.stamp(query.network.as_deref(), Json(output)))
     }
 
     #[tool(
-        name = "get_transaction",
-        description = "Retrieves a specific transaction by its hash. Returns detailed transaction information. Optional network parameter: 'mainnet' (default) or 'testnet'."
+        name = "list_contract_keys",
+        description = "Lists a contract's storage keys under a prefix (all keys when empty) with their values as {encoding, value}: text when printable, hex otherwise. Returns up to limit keys (default 100, max 200) in key order; when more remain, next_cursor is set: pass it back as cursor with the same contract_address and prefix. Optional network parameter: 'mainnet' (default) or 'testnet'."
     )]
-    async fn get_transaction(
+    async fn list_contract_keys(
         &self,
-        params: Parameters<TransactionQuery>,
+        params: Parameters<ContractKeysQuery>,
     ) -> Result<Json<serde_json::Value>, McpError> {
         let query = params.0;
         query.validate().map_err(|e| {
@@ -209,28 +2396,118 @@ impl BlockchainMcpServer {
                 Some(serde_json::json!({ "errors": e })),
             )
         })?;
+        let after = query
+            .cursor
+            .as_deref()
+            .map(|cursor| state_export::decode_cursor(cursor, &query.contract_address, &query.prefix))
+            .transpose()
+            .map_err(|e| McpError::invalid_params("invalid_cursor", Some(serde_json::json!({ "message": e }))))?;
 
+        let upstream = self.upstream();
         let url = match query.network.as_deref() {
-            Some("testnet") => &self.testnet_url,
-            _ => &self.mainnet_url,
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
         };
-
-        let transaction = self
+        let listing = upstream
             .blockchain
-            .get_transaction(&query.tx_hash, url)
+            .list_contract_state(
+                &query.contract_address,
+                &query.prefix,
+                after.as_deref(),
+                query.limit.unwrap_or(DEFAULT_LIST_LIMIT),
+                url,
+            )
             .await
-            .map_err(|e| Self::blockchain_error("get_transaction", e))?;
+            .map_err(|e| Self::blockchain_error("list_contract_keys", e))?;
+        Ok(upstream.stamp(query.network.as_deref(), Self::to_json(listing)?))
+    }
+
+    #[tool(
+        name = "diff_contract_state",
+        description = "Compares contract storage keys between height_a and height_b, returning added, removed and changed keys with before/after values. The node has no historical state queries, so values come from reads this server recorded (get_contract_state calls, and a live read when height_b is at the tip); each side reports the height and time it was observed, and keys without an observation are listed as unobserved. Mainnet only, up to 50 keys."
+    )]
+    async fn diff_contract_state(
+        &self,
+        params: Parameters<ContractDiffQuery>,
+    ) -> Result<Json<serde_json::Value>, McpError> {
+        let query = params.0;
+        query.validate().map_err(|e| {
+            McpError::invalid_params(
+                "validation_failed",
+                Some(serde_json::json!({ "errors": e })),
+            )
+        })?;
+        if query.height_a >= query.height_b {
+            return Err(McpError::invalid_params(
+                "invalid_range",
+                Some(serde_json::json!({ "message": "height_a must be below height_b" })),
+            ));
+        }
+
+        let upstream = self.upstream();
+        let url = &upstream.config.mainnet_url;
+        let Some(tip) = upstream.tip_for(query.network.as_deref()) else {
+            return Err(McpError::invalid_params(
+                "history_unavailable",
+                Some(serde_json::json!({ "message": "contract history is only recorded for mainnet while the tip follower is running" })),
+            ));
+        };
+
+        // The after side can be read live when it asks for the current state
+        if query.height_b >= tip.height {
+            for key in &query.keys {
+                let value = upstream
+                    .blockchain
+                    .get_contract_state(&query.contract_address, key, url)
+                    .await
+                    .map_err(|e| Self::blockchain_error("diff_contract_state", e))?;
+                upstream
+                    .blockchain
+                    .record_contract_state(url, &query.contract_address, key, tip.height, value);
+            }
+        }
+
+        let mut before = std::collections::BTreeMap::new();
+        let mut after = std::collections::BTreeMap::new();
+        let mut observations = serde_json::Map::new();
+        let mut unobserved = Vec::new();
+        for key in &query.keys {
+            let at = |height| upstream.blockchain.contract_state_at(url, &query.contract_address, key, height);
+            match (at(query.height_a), at(query.height_b)) {
+                (Some(a), Some(b)) => {
+                    observations.insert(key.clone(), serde_json::json!({
+                        "before": { "height": a.height, "recorded_at": a.recorded_at },
+                        "after": { "height": b.height, "recorded_at": b.recorded_at },
+                    }));
+                    before.insert(key.clone(), a.value);
+                    after.insert(key.clone(), b.value);
+                }
+                (a, b) => unobserved.push(serde_json::json!({
+                    "key": key,
+                    "before_observed": a.is_some(),
+                    "after_observed": b.is_some(),
+                })),
+            }
+        }
 
-        Self::to_json(transaction)
+        Ok(Json(serde_json::json!({
+            "contract_address": query.contract_address,
+            "height_a": query.height_a,
+            "height_b": query.height_b,
+            "source": SOURCE_OBSERVED,
+            "diff": state_diff::diff(&before, &after),
+            "observations": observations,
+            "unobserved": unobserved,
+        })))
     }
 
     #[tool(
-        name = "get_transaction_history",
-        description = "Retrieves transaction history for a specific account. Supports pagination with limit, offset, and sort parameters. Optional network parameter: 'mainnet' (default) or 'testnet'."
+        name = "investigate_addresses",
+        description = "Answers how two accounts relate in one call: both balances, direct transfers between them, counterparties both have transferred with, the heights of their first and last interaction, and each account's frequent counterparties (unlabelled ones come with a suggestion to label them). Reads history pages for both accounts until they end or max_requests upstream requests (4-50, default 10) are spent; the output reports how many transactions were examined per account and whether the budget ran out. The node cannot pin reads to a height, so the tip is read before and after: consistency.consistent is false (with the drift in entries) when the chain moved while the reads ran. Transfers are returned compact. Optional network parameter: 'mainnet' (default) or 'testnet'."
     )]
-    async fn get_transaction_history(
+    async fn investigate_addresses(
         &self,
-        params: Parameters<TransactionHistoryQuery>,
+        params: Parameters<InvestigateQuery>,
     ) -> Result<Json<serde_json::Value>, McpError> {
         let query = params.0;
         query.validate().map_err(|e| {
@@ -240,33 +2517,34 @@ impl BlockchainMcpServer {
             )
         })?;
 
+        let upstream = self.upstream();
         let url = match query.network.as_deref() {
-            Some("testnet") => &self.testnet_url,
-            _ => &self.mainnet_url,
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
         };
-
-        let transactions = self
+        let investigation = upstream
             .blockchain
-            .get_transaction_history(
-                &query.address,
-                query.limit,
-                query.offset,
-                query.sort.as_deref(),
+            .investigate_addresses(
+                &query.address_a,
+                &query.address_b,
+                query.max_requests.unwrap_or(DEFAULT_INVESTIGATE_BUDGET),
                 url,
             )
             .await
-            .map_err(|e| Self::blockchain_error("get_transaction_history", e))?;
+            .map_err(|e| Self::blockchain_error("investigate_addresses", e))?;
 
-        Self::to_json(transactions)
+        let Json(mut output) = Self::to_json(investigation)?;
+        self.annotate_labels(&mut output);
+        Ok(upstream.stamp(query.network.as_deref(), Json(output)))
     }
 
     #[tool(
-        name = "get_validators",
-        description = "Retrieves the list of current validator nodes (trainers) in the network. Optional network parameter: 'mainnet' (default) or 'testnet'."
+        name = "trace_funds",
+        description = "Follows Coin transfers out of (direction 'out', default) or into ('in') an address for up to max_hops hops (1-3, default 2), reading the latest txs_per_address transactions (default 25) of each address reached. Returns a graph for rendering (nodes with their hop, edges with per-symbol atom totals and transaction counts) and the endpoints the trace stopped at. Addresses reached twice are listed in revisited and expanded only once; at most max_requests history reads (default 20, max 50) are made and addresses left over are listed in unexpanded. Optional network parameter: 'mainnet' (default) or 'testnet'."
     )]
-    async fn get_validators(
+    async fn trace_funds(
         &self,
-        params: Parameters<ValidatorsQuery>,
+        params: Parameters<TraceFundsQuery>,
     ) -> Result<Json<serde_json::Value>, McpError> {
         let query = params.0;
         query.validate().map_err(|e| {
@@ -276,30 +2554,38 @@ impl BlockchainMcpServer {
             )
         })?;
 
+        let upstream = self.upstream();
         let url = match query.network.as_deref() {
-            Some("testnet") => &self.testnet_url,
-            _ => &self.mainnet_url,
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
         };
-
-        let validators = self
+        let (graph, requests_used) = upstream
             .blockchain
-            .get_validators(url)
+            .trace_funds(
+                &query.address,
+                query.direction,
+                query.max_hops.unwrap_or(DEFAULT_TRACE_HOPS),
+                query.txs_per_address.unwrap_or(DEFAULT_TRACE_TXS_PER_ADDRESS),
+                query.max_requests.unwrap_or(DEFAULT_TRACE_BUDGET),
+                url,
+            )
             .await
-            .map_err(|e| Self::blockchain_error("get_validators", e))?;
+            .map_err(|e| Self::blockchain_error("trace_funds", e))?;
 
-        Ok(Json(serde_json::json!({
-            "validators": validators,
-            "count": validators.len()
-        })))
+        let mut output = Self::to_json(graph)?;
+        output.0["address"] = serde_json::json!(query.address);
+        output.0["requests_used"] = serde_json::json!(requests_used);
+        self.annotate_labels(&mut output.0);
+        Ok(upstream.stamp(query.network.as_deref(), output))
     }
 
     #[tool(
-        name = "get_contract_state",
-        description = "Retrieves a specific value from smart contract storage by contract address and key. Optional network parameter: 'mainnet' (default) or 'testnet'."
+        name = "raw_node_query",
+        description = "GETs a node API path that has no dedicated tool yet and returns the JSON body. Only paths under the deployment's allowed prefixes are accepted. Optional network parameter: 'mainnet' (default) or 'testnet'."
     )]
-    async fn get_contract_state(
+    async fn raw_node_query(
         &self,
-        params: Parameters<ContractStateQuery>,
+        params: Parameters<RawNodeQuery>,
     ) -> Result<Json<serde_json::Value>, McpError> {
         let query = params.0;
         query.validate().map_err(|e| {
@@ -309,21 +2595,34 @@ impl BlockchainMcpServer {
             )
         })?;
 
+        let method = query.method.as_deref().unwrap_or("GET");
+        let checked = if method.eq_ignore_ascii_case("GET") {
+            self.tool_filter.read().unwrap().check_raw_path(&query.path)
+        } else {
+            Err(format!("method {} is not allowed, only GET", method))
+        };
+        if let Err(reason) = checked {
+            return Err(McpError::invalid_params(
+                "raw_query_rejected",
+                Some(serde_json::json!({ "path": query.path, "reason": reason })),
+            ));
+        }
+
+        let upstream = self.upstream();
         let url = match query.network.as_deref() {
-            Some("testnet") => &self.testnet_url,
-            _ => &self.mainnet_url,
+            Some("testnet") => &upstream.config.testnet_url,
+            _ => &upstream.config.mainnet_url,
         };
 
-        let state = self
+        let body = upstream
             .blockchain
-            .get_contract_state(&query.contract_address, &query.key, url)
+            .raw_query(&upstream.blockchain.api_paths().rebase(&query.path), url)
             .await
-            .map_err(|e| Self::blockchain_error("get_contract_state", e))?;
+            .map_err(|e| Self::blockchain_error("raw_node_query", e))?;
 
         Ok(Json(serde_json::json!({
-            "contract_address": query.contract_address,
-            "key": query.key,
-            "value": state
+            "path": query.path,
+            "body": body
         })))
     }
 
@@ -332,6 +2631,7 @@ impl BlockchainMcpServer {
         description = "Returns comprehensive documentation about the Amadeus blockchain, including overview, key concepts, RPC API endpoints, wallet operations, and ecosystem information."
     )]
     async fn get_amadeus_docs(&self) -> Result<Json<serde_json::Value>, McpError> {
+        let ama_decimals = self.upstream().config.symbol_decimals.get("AMA").unwrap_or(AMA_DECIMALS);
         Ok(Json(serde_json::json!({
             "overview": {
                 "title": "Amadeus Blockchain",
@@ -386,8 +2686,8 @@ impl BlockchainMcpServer {
             },
             "token": {
                 "symbol": "AMA",
-                "decimals": 9,
-                "note": "1.0 AMA = 1,000,000,000 atomic units",
+                "decimals": ama_decimals,
+                "note": format!("1.0 AMA = {} atomic units", 10u128.pow(ama_decimals)),
                 "utility": [
                     "Agent execution fees",
                     "Smart contract deployment gas",
@@ -484,7 +2784,7 @@ impl BlockchainMcpServer {
                     "response_fields": ["metadata.entry_hash", "metadata.entry_slot", "signature", "result.error", "hash", "tx.nonce", "tx.signer", "tx.actions"]
                 },
                 "amount_handling": {
-                    "decimals": 9,
+                    "decimals": ama_decimals,
                     "examples": {
                         "1.0_float": "1,000,000,000 atomic units (1 AMA)",
                         "1_integer": "1 atomic unit (0.000000001 AMA)",
@@ -550,10 +2850,14 @@ impl BlockchainMcpServer {
 
     fn blockchain_error(tool: &str, error: BlockchainError) -> McpError {
         error!(%error, tool, "blockchain operation failed");
-        match error {
-            BlockchainError::AccountNotFound { address } => McpError::resource_not_found(
-                "account_not_found",
-                Some(serde_json::json!({ "address": address })),
+        let hint = recovery::for_error(&error);
+        let mut mapped = match error {
+            BlockchainError::AccountNotFound { address } => McpError::invalid_params(
+                "invalid_address",
+                Some(serde_json::json!({
+                    "address": address,
+                    "message": "not a base58 48-byte public key"
+                })),
             ),
             BlockchainError::InsufficientBalance {
                 required,
@@ -566,13 +2870,138 @@ impl BlockchainMcpServer {
                 "validation_failed",
                 Some(serde_json::json!({ "message": msg })),
             ),
+            BlockchainError::LimitExceeded { argument, requested, max } => McpError::invalid_params(
+                "limit_exceeded",
+                Some(serde_json::json!({
+                    "argument": argument,
+                    "requested": requested,
+                    "max": max,
+                    "message": "above this server's query limits; see query_limits in get_capabilities"
+                })),
+            ),
+            BlockchainError::NotFound { argument, value } => McpError::resource_not_found(
+                "not_found",
+                Some(serde_json::json!({
+                    "argument": argument,
+                    "value": value,
+                    "message": format!("no {} {} on this node", argument, value)
+                })),
+            ),
+            BlockchainError::EndpointUnsupported { endpoint } => McpError::invalid_request(
+                "endpoint_unsupported",
+                Some(serde_json::json!({
+                    "endpoint": endpoint,
+                    "message": "the upstream node is too old for this tool"
+                })),
+            ),
+            BlockchainError::Offline(message) => McpError::invalid_request(
+                "offline_mode",
+                Some(serde_json::json!({
+                    "message": message,
+                    "hint": "the server runs with MCP_OFFLINE=1 and only answers reads it has fixtures for"
+                })),
+            ),
             e => McpError::internal_error(
                 "blockchain_error",
                 Some(serde_json::json!({ "error": e.to_string() })),
             ),
+        };
+        recovery::attach(&mut mapped.data, &hint);
+        mapped
+    }
+
+    /// Asks the user to confirm a submit through elicitation. Passes without asking when the
+    /// client cannot elicit or `MCP_ELICIT_CONFIRM=0`; anything but an explicit accept
+    /// (decline, cancel, no answer in time) stops the submit.
+    async fn confirm_submit(&self, peer: &Peer<RoleServer>, blob_b58: &str, network: Option<&str>) -> Result<(), McpError> {
+        let upstream = self.upstream();
+        if !upstream.config.elicit_confirm || !peer.supports_elicitation() {
+            return Ok(());
+        }
+
+        let message = confirm::summarize(blob_b58, network, &upstream.config.symbol_decimals, upstream.config.locale)
+            .unwrap_or_else(|e| {
+                format!("Submit a transaction to {}? It could not be decoded for review: {}", network.unwrap_or("mainnet"), e)
+            });
+        let requested_schema = serde_json::json!({
+            "type": "object",
+            "properties": {
+                "confirm": { "type": "boolean", "title": "Submit this transaction", "default": true }
+            },
+            "required": ["confirm"]
+        });
+        let request = CreateElicitationRequestParam {
+            message,
+            requested_schema: requested_schema.as_object().cloned().unwrap_or_default(),
+        };
+
+        let not_confirmed = |reason: &str| {
+            McpError::invalid_request(
+                "submission_not_confirmed",
+                Some(serde_json::json!({ "reason": reason, "message": "nothing was submitted" })),
+            )
+        };
+        match peer.create_elicitation_with_timeout(request, Some(confirm::ELICIT_TIMEOUT)).await {
+            Ok(CreateElicitationResult { action: ElicitationAction::Accept, content }) => {
+                let confirmed = content
+                    .as_ref()
+                    .and_then(|c| c.get("confirm"))
+                    .and_then(serde_json::Value::as_bool)
+                    .unwrap_or(false);
+                if confirmed { Ok(()) } else { Err(not_confirmed("declined")) }
+            }
+            Ok(CreateElicitationResult { action: ElicitationAction::Decline, .. }) => Err(not_confirmed("declined")),
+            Ok(CreateElicitationResult { action: ElicitationAction::Cancel, .. }) => Err(not_confirmed("cancelled")),
+            Err(ServiceError::Timeout { .. }) => Err(not_confirmed("timeout")),
+            Err(e) => {
+                warn!(error = %e, "submit confirmation failed");
+                Err(not_confirmed("confirmation_failed"))
+            }
+        }
+    }
+
+    /// Checks a blob's nonce against the signer's latest on-chain nonce.
+    async fn blob_freshness(&self, blob: &str, url: &str) -> Result<Freshness, McpError> {
+        let read = freshness::read_blob(blob).map_err(|e| {
+            McpError::invalid_params("validation_failed", Some(serde_json::json!({ "message": e })))
+        })?;
+        let history = self
+            .upstream()
+            .blockchain
+            .get_transaction_history(&read.signer, Some(freshness::NONCE_HISTORY_LIMIT), None, Some("desc"), url)
+            .await
+            .map_err(|e| Self::blockchain_error("check_blob_freshness", e))?;
+        let origin = self.built_blobs.get(&read.transaction_hash);
+        Ok(freshness::evaluate(
+            read.nonce,
+            freshness::latest_nonce(&read.signer, &history),
+            origin.as_ref(),
+            unix_now(),
+        ))
+    }
+
+    /// Freshness of a blob this server built, checked before submitting it. Blobs from
+    /// elsewhere, and checks that fail, are left to the node.
+    async fn submit_freshness(&self, blob: &str, url: &str) -> Option<Freshness> {
+        let hash = freshness::read_blob(blob).ok()?.transaction_hash;
+        self.built_blobs.get(&hash)?;
+        match self.blob_freshness(blob, url).await {
+            Ok(freshness) => Some(freshness),
+            Err(e) => {
+                warn!(error = ?e, "blob freshness check failed, submitting anyway");
+                None
+            }
         }
     }
 
+    fn validators_envelope(validators: &[ValidatorInfo]) -> serde_json::Value {
+        serde_json::json!({
+            "validators": validators,
+            "count": validators.len(),
+            "scores_available": validators.iter().any(|v| v.score.is_some())
+        })
+    }
+
     fn to_json<T: serde::Serialize>(value: T) -> Result<Json<serde_json::Value>, McpError> {
         Ok(Json(serde_json::to_value(value).map_err(|e| {
             McpError::internal_error(
@@ -583,29 +3012,153 @@ impl BlockchainMcpServer {
     }
 }
 
-#[tool_handler]
 impl ServerHandler for BlockchainMcpServer {
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
             capabilities: ServerCapabilities::builder()
                 .enable_tools()
+                .enable_tool_list_changed()
                 .enable_resources()
                 .enable_prompts()
                 .build(),
-            instructions: Some(
+            instructions: Some(format!(
                 "Blockchain MCP server for Amadeus. \
                 Use create_transaction to build unsigned transactions, sign externally with BLS12-381, \
-                then submit_transaction to broadcast."
-                    .into(),
-            ),
+                then submit_transaction to broadcast. toolsVersion: {} features: {}",
+                self.tools_version(),
+                serde_json::json!(self.features())
+            )),
             protocol_version: Default::default(),
             server_info: Implementation {
                 name: "amadeus-mcp".into(),
                 version: env!("CARGO_PKG_VERSION").into(),
+                title: None,
+                website_url: None,
+                icons: None,
             },
         }
     }
 
+    async fn call_tool(
+        &self,
+        mut request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.tool_filter.read().unwrap().allows(&request.name) {
+            return Err(with_recovery(McpError::invalid_params(
+                "tool_disabled",
+                Some(serde_json::json!({ "tool": request.name })),
+            )));
+        }
+        let limits = self.upstream().config.session_budget;
+        {
+            let budget = self.budget.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(reason) = budget.exhausted(limits, unix_now()) {
+                return Err(with_recovery(McpError::invalid_request(
+                    "session_budget_exhausted",
+                    Some(budget::exhausted_error(&reason, budget.usage(limits, unix_now()))),
+                )));
+            }
+        }
+        if self.upstream().config.strict_args {
+            let unknown = match (self.tool_router.map.get(request.name.as_ref()), &request.arguments) {
+                (Some(route), Some(arguments)) => catalog::unknown_arguments(&route.attr.input_schema, arguments),
+                _ => Vec::new(),
+            };
+            if !unknown.is_empty() {
+                return Err(with_recovery(McpError::invalid_params(
+                    "unknown_arguments",
+                    Some(serde_json::json!({ "tool": request.name, "unknown": unknown })),
+                )));
+            }
+        }
+        let request_id = context.id.to_string();
+        let span = info_span!(
+            "tool_call",
+            tool = %request.name,
+            correlation_id = %request_id,
+            arguments = %telemetry::sanitize_arguments(request.arguments.as_ref()),
+            duration_ms = Empty,
+            outcome = Empty,
+        );
+
+        // The caller's own addresses stay readable; everything else in the reply is redacted
+        let exempt = self.redactor.is_enabled().then(|| {
+            let mut arguments = serde_json::Value::Object(request.arguments.take().unwrap_or_default());
+            let exempt = self.redactor.resolve(&mut arguments);
+            if let serde_json::Value::Object(arguments) = arguments {
+                request.arguments = Some(arguments);
+            }
+            exempt
+        });
+
+        let call_metrics = Arc::new(CallMetrics::default());
+        let started = std::time::Instant::now();
+        let tcc = ToolCallContext::new(self, request, context);
+        let result = CALL_METRICS
+            .scope(call_metrics.clone(), self.tool_router.call(tcc).instrument(span.clone()))
+            .await;
+        let measured = call_metrics.timing(started.elapsed());
+        span.record("duration_ms", measured.duration_ms);
+        span.record("outcome", match &result {
+            Ok(r) if r.is_error == Some(true) => "tool_error",
+            Ok(_) => "ok",
+            Err(_) => "error",
+        });
+        // Failed calls still cost upstream requests
+        self.budget
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .record(measured.upstream_requests.into(), unix_now());
+        let mut result = result.map_err(with_recovery)?;
+        let timing = self.upstream().config.include_timing.then_some(measured);
+        let signer = self.upstream().signer.clone();
+
+        if exempt.is_some() || timing.is_some() || signer.is_some() {
+            let signed_at = unix_now();
+            let redactor = self.redactor.clone();
+            let post_process = move |mut result: CallToolResult| {
+                map_json_content(&mut result, |value| {
+                    if let Some(exempt) = &exempt {
+                        redactor.redact(value, exempt);
+                    }
+                    if let Some(timing) = timing {
+                        metrics::attach(value, timing);
+                    }
+                    // Last, so the signature covers exactly what the caller receives
+                    if let Some(signer) = &signer {
+                        signer.sign(value, signed_at, &request_id);
+                    }
+                });
+                result
+            };
+            let text_bytes: usize = result.content.iter().filter_map(|c| c.as_text()).map(|t| t.text.len()).sum();
+            result = if text_bytes >= render::LARGE_TEXT_BYTES {
+                render::off_runtime(move || post_process(result)).await
+            } else {
+                post_process(result)
+            };
+        }
+        Ok(result)
+    }
+
+    async fn list_tools(
+        &self,
+        request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, McpError> {
+        let cursor = request.as_ref().and_then(|r| r.cursor.as_deref());
+        let (tools, next_cursor) = catalog::page(
+            &self.enabled_tools(),
+            cursor,
+            catalog::DEFAULT_PAGE_SIZE,
+            self.tools_version(),
+        )
+        .map_err(|e| McpError::invalid_params("invalid_cursor", Some(serde_json::json!({ "message": e }))))?;
+
+        Ok(ListToolsResult { tools, next_cursor })
+    }
+
     async fn list_prompts(
         &self,
         _request: Option<PaginatedRequestParam>,
@@ -635,8 +3188,33 @@ impl ServerHandler for BlockchainMcpServer {
         _request: Option<PaginatedRequestParam>,
         _context: RequestContext<RoleServer>,
     ) -> Result<ListResourcesResult, McpError> {
+        let mut validators = RawResource::new(VALIDATORS_URI, "validators");
+        validators.description = Some(
+            "Current mainnet validator set with epoch scores; append ?offset=&limit= to read it in slices".into(),
+        );
+        validators.mime_type = Some("application/json".into());
+
+        let mut status = RawResource::new(STATUS_URI, "status");
+        status.description = Some("Server status: mainnet tip as seen by the follower and the last chain reorg".into());
+        status.mime_type = Some("application/json".into());
+
+        let mut signing_workflow = RawResource::new(catalog::SIGNING_WORKFLOW_URI, "signing_workflow");
+        signing_workflow.description = Some(
+            "How to go from create_transaction to submit_transaction: the DST, the exact bytes to sign and a live example blob".into(),
+        );
+        signing_workflow.mime_type = Some("text/plain".into());
+
+        let mut error_recovery = RawResource::new(catalog::ERROR_RECOVERY_URI, "error_recovery");
+        error_recovery.description = Some("The recovery actions errors carry in their data and what each asks of the caller".into());
+        error_recovery.mime_type = Some("text/plain".into());
+
         Ok(ListResourcesResult {
-            resources: vec![],
+            resources: vec![
+                validators.no_annotation(),
+                status.no_annotation(),
+                signing_workflow.no_annotation(),
+                error_recovery.no_annotation(),
+            ],
             next_cursor: None,
         })
     }
@@ -646,8 +3224,32 @@ impl ServerHandler for BlockchainMcpServer {
         _request: Option<PaginatedRequestParam>,
         _context: RequestContext<RoleServer>,
     ) -> Result<ListResourceTemplatesResult, McpError> {
+        let tool_docs = RawResourceTemplate {
+            title: None,
+            uri_template: format!("{}{{name}}", catalog::TOOL_DOCS_URI),
+            name: "tool_docs".into(),
+            description: Some("Long-form documentation for a tool".into()),
+            mime_type: Some("text/plain".into()),
+        };
+
+        let tx_templates = RawResourceTemplate {
+            title: None,
+            uri_template: format!("{}{{name}}", templates::TEMPLATES_URI),
+            name: "tx_templates".into(),
+            description: Some("Argument layout and fill-in example of a common transaction".into()),
+            mime_type: Some("application/json".into()),
+        };
+
+        let entries = RawResourceTemplate {
+            title: None,
+            uri_template: format!("{}{{hash}}", ENTRY_URI),
+            name: "entry".into(),
+            description: Some("A mainnet entry by its base58 hash, as get_entry_by_hash returns it".into()),
+            mime_type: Some("application/json".into()),
+        };
+
         Ok(ListResourceTemplatesResult {
-            resource_templates: vec![],
+            resource_templates: vec![tool_docs.no_annotation(), tx_templates.no_annotation(), entries.no_annotation()],
             next_cursor: None,
         })
     }
@@ -657,10 +3259,205 @@ impl ServerHandler for BlockchainMcpServer {
         request: ReadResourceRequestParam,
         _context: RequestContext<RoleServer>,
     ) -> Result<ReadResourceResult, McpError> {
-        let uri = request.uri.as_str();
-        Err(McpError::invalid_params(
-            "invalid_uri",
-            Some(serde_json::json!({ "message": format!("Unknown resource URI: {}", uri) })),
-        ))
+        let mut result = self.read_resource_contents(request.uri.as_str()).await?;
+        // Resources show the same addresses tools do, so they are redacted the same way
+        if self.redactor.is_enabled() {
+            for content in result.contents.iter_mut() {
+                if let ResourceContents::TextResourceContents { text, .. } = content {
+                    redact_json_text(&self.redactor, text);
+                }
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl BlockchainMcpServer {
+    async fn read_resource_contents(&self, uri: &str) -> Result<ReadResourceResult, McpError> {
+        let (base, query) = range::split_uri(uri);
+        let range = Range::from_query(query).map_err(|e| {
+            McpError::invalid_params("invalid_range", Some(serde_json::json!({ "message": e, "uri": uri })))
+        })?;
+        if range.is_some() && base != VALIDATORS_URI {
+            return Err(McpError::invalid_params(
+                "invalid_range",
+                Some(serde_json::json!({ "message": "offset and limit only apply to list resources", "uri": uri })),
+            ));
+        }
+        match base {
+            VALIDATORS_URI => {
+                let upstream = self.upstream();
+                let validators = upstream
+                    .blockchain
+                    .get_validators(&upstream.config.mainnet_url, false)
+                    .await
+                    .map_err(|e| Self::blockchain_error("read_resource", e))?;
+                let envelope = match range {
+                    Some(range) => {
+                        let (slice, info) = range.apply(&validators);
+                        let mut envelope = Self::validators_envelope(slice);
+                        envelope["range"] = serde_json::json!(info);
+                        envelope
+                    }
+                    None => Self::validators_envelope(&validators),
+                };
+                let pretty = upstream.config.output_detail != OutputDetail::Compact;
+                let text = render::render(envelope, pretty)
+                    .await
+                    .map_err(|e| {
+                        McpError::internal_error(
+                            "serialization_error",
+                            Some(serde_json::json!({ "error": e.to_string() })),
+                        )
+                    })?;
+
+                Ok(ReadResourceResult {
+                    contents: vec![ResourceContents::text(text, uri)],
+                })
+            }
+            STATUS_URI => {
+                let upstream = self.upstream();
+                let tip = upstream.tip();
+                let capabilities = upstream.blockchain.capabilities(&upstream.config.mainnet_url);
+                let session_budget = self
+                    .budget
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .usage(upstream.config.session_budget, unix_now());
+                let status = serde_json::json!({
+                    "tools_version": self.tools_version(),
+                    "mainnet_url": upstream.config.mainnet_url,
+                    "tip": tip.as_ref().map(|t| serde_json::json!({
+                        "height": t.height,
+                        "hash": t.hash,
+                        "observed_at": t.observed_at,
+                    })),
+                    "last_reorg": tip.as_ref().and_then(|t| t.last_reorg.clone()),
+                    "node_capabilities": {
+                        "probed": capabilities.probed,
+                        "missing_endpoints": capabilities.missing,
+                        "unavailable_tools": capabilities.unavailable_tools(),
+                        "batch": capabilities.batch,
+                    },
+                    "session_budget": session_budget,
+                    "liveness": tip.as_ref().map(|t| liveness::assess(
+                        &[],
+                        t.clock_skew.map(|s| s.chain_time),
+                        t.stats.txs_per_sec,
+                        unix_now(),
+                        upstream.config.liveness,
+                    )),
+                    "schema_drift": upstream.drift.as_ref().and_then(DriftMonitor::current),
+                });
+
+                Ok(ReadResourceResult {
+                    contents: vec![ResourceContents::text(status.to_string(), uri)],
+                })
+            }
+            catalog::SIGNING_WORKFLOW_URI => {
+                let doc = signing::workflow_doc().map_err(|e| {
+                    McpError::internal_error("signing_workflow_failed", Some(serde_json::json!({ "error": e })))
+                })?;
+
+                Ok(ReadResourceResult {
+                    contents: vec![ResourceContents::text(doc, uri)],
+                })
+            }
+            catalog::ERROR_RECOVERY_URI => Ok(ReadResourceResult {
+                contents: vec![ResourceContents::text(recovery::doc(), uri)],
+            }),
+            _ if uri.starts_with(ENTRY_URI) => {
+                let hash = uri.strip_prefix(ENTRY_URI).unwrap_or_default();
+                let upstream = self.upstream();
+                let entry = upstream
+                    .blockchain
+                    .get_entry_by_hash(hash, &upstream.config.mainnet_url)
+                    .await
+                    .map_err(|e| Self::blockchain_error("read_resource", e))?;
+                let Json(mut output) = Self::to_json(entry)?;
+                self.annotate_labels(&mut output);
+
+                Ok(ReadResourceResult {
+                    contents: vec![ResourceContents::text(output.to_string(), uri)],
+                })
+            }
+            _ if uri.starts_with(templates::TEMPLATES_URI) => {
+                let template = uri
+                    .strip_prefix(templates::TEMPLATES_URI)
+                    .and_then(templates::template)
+                    .ok_or_else(|| {
+                        McpError::invalid_params(
+                            "invalid_uri",
+                            Some(serde_json::json!({ "message": format!("No template for {}", uri) })),
+                        )
+                    })?;
+
+                Ok(ReadResourceResult {
+                    contents: vec![ResourceContents::text(template.describe().to_string(), uri)],
+                })
+            }
+            _ if uri.starts_with(catalog::TOOL_DOCS_URI) => {
+                let doc = uri
+                    .strip_prefix(catalog::TOOL_DOCS_URI)
+                    .and_then(catalog::tool_doc)
+                    .ok_or_else(|| {
+                        McpError::invalid_params(
+                            "invalid_uri",
+                            Some(serde_json::json!({ "message": format!("No docs for {}", uri) })),
+                        )
+                    })?;
+
+                Ok(ReadResourceResult {
+                    contents: vec![ResourceContents::text(doc.render(), uri)],
+                })
+            }
+            _ => Err(McpError::invalid_params(
+                "invalid_uri",
+                Some(serde_json::json!({ "message": format!("Unknown resource URI: {}", uri) })),
+            )),
+        }
+    }
+}
+
+/// Redacts the addresses in `text` when it holds JSON, keeping its layout; other text is
+/// left as it is.
+fn redact_json_text(redactor: &Redactor, text: &mut String) {
+    let Ok(mut value) = serde_json::from_str::<serde_json::Value>(text) else {
+        return;
+    };
+    redactor.redact(&mut value, &std::collections::HashSet::new());
+    let pretty = text.contains('\n');
+    if let Ok(redacted) = render::to_string(&value, pretty) {
+        *text = redacted;
+    }
+}
+
+/// Applies `f` to the structured output and to every text content that holds JSON.
+fn map_json_content(result: &mut CallToolResult, f: impl Fn(&mut serde_json::Value)) {
+    if let Some(structured) = result.structured_content.as_mut() {
+        f(structured);
+    }
+    for content in result.content.iter_mut() {
+        if let RawContent::Text(text) = &mut content.raw {
+            if let Ok(mut value) = serde_json::from_str::<serde_json::Value>(&text.text) {
+                f(&mut value);
+                text.text = render::to_string(&value, false).unwrap_or_else(|_| value.to_string());
+            }
+        }
+    }
+}
+
+/// Adds the recovery hint for the error's code unless it already has one.
+fn with_recovery(mut error: McpError) -> McpError {
+    if let Some(hint) = recovery::for_code(&error.message, error.data.as_ref()) {
+        recovery::attach(&mut error.data, &hint);
     }
+    error
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
 }
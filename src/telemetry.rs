@@ -0,0 +1,187 @@
+//! Tracing helpers for tool calls and upstream requests. With the `otel` feature and
+//! `OTEL_EXPORTER_OTLP_ENDPOINT` set, spans are exported over OTLP and upstream requests
+//! carry a W3C `traceparent` header; otherwise nothing leaves the process.
+//!
+//! Span taxonomy: one `tool_call` span per tools/call (tool, correlation_id, duration_ms,
+//! outcome), the client method spans below it, and one `upstream_request` span per attempt
+//! (method, endpoint, attempt, status). Fields go through the policy here: secret argument
+//! keys are never recorded, and while `MCP_REDACT_ADDRESSES` is on, addresses in fields are
+//! cut to their first and last characters.
+
+use crate::blockchain::is_valid_address;
+use serde_json::{Map, Value};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Argument keys whose values never reach span fields.
+const REDACTED_KEYS: &[&str] = &["secret_key", "sk", "seed", "private_key", "api_key"];
+
+/// Strings longer than this (blobs, signatures) are truncated in span fields.
+const MAX_ARG_LEN: usize = 64;
+
+/// Characters of an address kept at each end while redaction is on.
+const ADDRESS_EDGE_CHARS: usize = 6;
+
+static REDACT_ADDRESSES: AtomicBool = AtomicBool::new(false);
+
+/// Turns address redaction in span fields on or off; follows `MCP_REDACT_ADDRESSES`,
+/// whatever its mode, since span fields have no per-session token map.
+pub fn set_address_redaction(enabled: bool) {
+    REDACT_ADDRESSES.store(enabled, Ordering::Relaxed);
+}
+
+/// An address for a span field.
+pub fn address_field(address: &str) -> String {
+    if !REDACT_ADDRESSES.load(Ordering::Relaxed) {
+        return address.to_string();
+    }
+    let chars: Vec<char> = address.chars().collect();
+    if chars.len() <= 2 * ADDRESS_EDGE_CHARS {
+        return address.to_string();
+    }
+    let head: String = chars[..ADDRESS_EDGE_CHARS].iter().collect();
+    let tail: String = chars[chars.len() - ADDRESS_EDGE_CHARS..].iter().collect();
+    format!("{}…{}", head, tail)
+}
+
+/// `text` (a path or any free-form field) with every embedded address passed through
+/// [`address_field`].
+pub fn redact_text(text: &str) -> String {
+    if !REDACT_ADDRESSES.load(Ordering::Relaxed) {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut run = String::new();
+    for c in text.chars().chain(std::iter::once('/')) {
+        if c.is_ascii_alphanumeric() {
+            run.push(c);
+            continue;
+        }
+        if is_valid_address(&run) {
+            out.push_str(&address_field(&run));
+        } else {
+            out.push_str(&run);
+        }
+        run.clear();
+        out.push(c);
+    }
+    out.pop();
+    out
+}
+
+/// Renders tool arguments for a span field with secrets redacted and long values cut.
+pub fn sanitize_arguments(arguments: Option<&Map<String, Value>>) -> String {
+    let Some(arguments) = arguments else {
+        return "{}".to_string();
+    };
+    let sanitized: Map<String, Value> = arguments
+        .iter()
+        .map(|(key, value)| {
+            let value = if REDACTED_KEYS.contains(&key.to_ascii_lowercase().as_str()) {
+                Value::String("[redacted]".to_string())
+            } else {
+                truncate(&redact_addresses(value))
+            };
+            (key.clone(), value)
+        })
+        .collect();
+    Value::Object(sanitized).to_string()
+}
+
+fn redact_addresses(value: &Value) -> Value {
+    match value {
+        Value::String(s) if is_valid_address(s) => Value::String(address_field(s)),
+        Value::Array(items) => Value::Array(items.iter().map(redact_addresses).collect()),
+        Value::Object(fields) => Value::Object(fields.iter().map(|(k, v)| (k.clone(), redact_addresses(v))).collect()),
+        other => other.clone(),
+    }
+}
+
+fn truncate(value: &Value) -> Value {
+    match value {
+        Value::String(s) if s.chars().count() > MAX_ARG_LEN => {
+            let prefix: String = s.chars().take(MAX_ARG_LEN).collect();
+            Value::String(format!("{}…", prefix))
+        }
+        Value::Array(items) => Value::Array(items.iter().map(truncate).collect()),
+        Value::Object(fields) => Value::Object(fields.iter().map(|(k, v)| (k.clone(), truncate(v))).collect()),
+        other => other.clone(),
+    }
+}
+
+/// Adds trace context headers for the current span to an upstream request.
+#[cfg(feature = "otel")]
+pub fn inject_trace_context(request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+    use opentelemetry::global;
+    use std::collections::HashMap;
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+    let context = tracing::Span::current().context();
+    let mut headers = HashMap::new();
+    global::get_text_map_propagator(|propagator| propagator.inject_context(&context, &mut headers));
+    headers
+        .into_iter()
+        .fold(request, |request, (name, value)| request.header(name, value))
+}
+
+#[cfg(not(feature = "otel"))]
+pub fn inject_trace_context(request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+    request
+}
+
+/// OTLP export layer, or `None` when `OTEL_EXPORTER_OTLP_ENDPOINT` is unset or the
+/// exporter cannot be built.
+#[cfg(feature = "otel")]
+pub fn otel_layer<S>() -> Option<tracing_opentelemetry::OpenTelemetryLayer<S, opentelemetry_sdk::trace::Tracer>>
+where
+    S: tracing::Subscriber + for<'span> tracing_subscriber::registry::LookupSpan<'span>,
+{
+    use opentelemetry::{global, trace::TracerProvider as _, KeyValue};
+    use opentelemetry_otlp::WithExportConfig;
+    use opentelemetry_sdk::{propagation::TraceContextPropagator, runtime, trace::TracerProvider, Resource};
+
+    let endpoint = std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT").ok()?;
+    let exporter = match opentelemetry_otlp::SpanExporter::builder()
+        .with_tonic()
+        .with_endpoint(endpoint)
+        .build()
+    {
+        Ok(exporter) => exporter,
+        Err(e) => {
+            eprintln!("failed to build OTLP exporter, tracing export disabled: {}", e);
+            return None;
+        }
+    };
+
+    let provider = TracerProvider::builder()
+        .with_batch_exporter(exporter, runtime::Tokio)
+        .with_resource(Resource::new(vec![KeyValue::new("service.name", "amadeus-mcp")]))
+        .build();
+    let tracer = provider.tracer("amadeus-mcp");
+    global::set_text_map_propagator(TraceContextPropagator::new());
+    global::set_tracer_provider(provider);
+
+    Some(tracing_opentelemetry::layer().with_tracer(tracer))
+}
+
+/// Flushes pending spans before exit.
+#[cfg(feature = "otel")]
+pub fn shutdown() {
+    opentelemetry::global::shutdown_tracer_provider();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn secret_arguments_are_never_recorded_and_long_ones_cut() {
+        let arguments = json!({ "Secret_Key": "abc", "seed": [1, 2], "blob": "x".repeat(100), "limit": 5 });
+        let recorded: Value = serde_json::from_str(&sanitize_arguments(arguments.as_object())).unwrap();
+        assert_eq!(recorded["Secret_Key"], "[redacted]");
+        assert_eq!(recorded["seed"], "[redacted]");
+        assert_eq!(recorded["blob"], format!("{}…", "x".repeat(MAX_ARG_LEN)));
+        assert_eq!(recorded["limit"], 5);
+        assert_eq!(sanitize_arguments(None), "{}");
+    }
+}
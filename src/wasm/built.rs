@@ -0,0 +1,153 @@
+use crate::blockchain::built::{self, BuiltStatus, BuiltTransaction, DEFAULT_BUILT_LIST_LIMIT, MAX_BUILT_LIST_LIMIT, MAX_BUILT_TRANSACTIONS};
+use crate::blockchain::BuiltTransactionsQuery;
+use serde_json::{json, Value};
+use worker::{console_warn, Date, Env};
+
+#[derive(serde::Deserialize)]
+struct BuiltRow {
+    record: String,
+    observed_height: Option<f64>,
+}
+
+impl BuiltRow {
+    fn into_built(self) -> Option<BuiltTransaction> {
+        let mut built: BuiltTransaction = serde_json::from_str(&self.record).ok()?;
+        built.observed_height = self.observed_height.map(|h| h as u64);
+        Some(built)
+    }
+}
+
+fn now() -> u64 {
+    Date::now().as_millis() / 1000
+}
+
+/// Stores a built transaction and drops the signer's oldest beyond `MAX_BUILT_TRANSACTIONS`.
+/// Failures are logged; building the blob already succeeded.
+pub async fn record(env: &Env, transaction: &BuiltTransaction) {
+    if let Err(e) = try_record(env, transaction).await {
+        console_warn!("failed to record built transaction {}: {}", transaction.transaction_hash, e);
+    }
+}
+
+async fn try_record(env: &Env, transaction: &BuiltTransaction) -> Result<(), String> {
+    let db = env.d1("MCP_DATABASE").map_err(|e| e.to_string())?;
+    let record = serde_json::to_string(transaction).map_err(|e| e.to_string())?;
+    db.prepare("INSERT OR IGNORE INTO built_transactions (hash, signer, record, built_at) VALUES (?1, ?2, ?3, ?4)")
+        .bind(&[
+            transaction.transaction_hash.clone().into(),
+            transaction.signer.clone().into(),
+            record.into(),
+            (transaction.built_at as f64).into(),
+        ])
+        .map_err(|e| e.to_string())?
+        .run()
+        .await
+        .map_err(|e| e.to_string())?;
+    db.prepare(
+        "DELETE FROM built_transactions WHERE signer = ?1 AND hash NOT IN \
+         (SELECT hash FROM built_transactions WHERE signer = ?1 ORDER BY built_at DESC LIMIT ?2)",
+    )
+    .bind(&[transaction.signer.clone().into(), (MAX_BUILT_TRANSACTIONS as f64).into()])
+    .map_err(|e| e.to_string())?
+    .run()
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Marks the transactions in a lookup output that were built here and stores the height
+/// they were seen at. A missing table leaves the output as it is.
+pub async fn annotate(env: &Env, output: &mut Value) {
+    if let Err(e) = try_annotate(env, output).await {
+        console_warn!("failed to match built transactions: {}", e);
+    }
+}
+
+async fn try_annotate(env: &Env, output: &mut Value) -> Result<(), String> {
+    let seen = built::transactions_in(output);
+    if seen.is_empty() {
+        return Ok(());
+    }
+    let hashes: Vec<&str> = seen.iter().map(|(hash, _)| hash.as_str()).collect();
+    let db = env.d1("MCP_DATABASE").map_err(|e| e.to_string())?;
+    let rows: Vec<BuiltRow> = db
+        .prepare("SELECT record, observed_height FROM built_transactions WHERE hash IN (SELECT value FROM json_each(?1))")
+        .bind(&[json!(hashes).to_string().into()])
+        .map_err(|e| e.to_string())?
+        .all()
+        .await
+        .map_err(|e| e.to_string())?
+        .results()
+        .map_err(|e| e.to_string())?;
+    let matching: Vec<BuiltTransaction> = rows.into_iter().filter_map(BuiltRow::into_built).collect();
+    if matching.is_empty() {
+        return Ok(());
+    }
+    built::annotate(output, &matching);
+
+    for (hash, height) in &seen {
+        let (Some(height), Some(_)) = (height, matching.iter().find(|b| &b.transaction_hash == hash && b.observed_height.is_none())) else {
+            continue;
+        };
+        db.prepare("UPDATE built_transactions SET observed_height = ?1 WHERE hash = ?2 AND observed_height IS NULL")
+            .bind(&[(*height as f64).into(), hash.clone().into()])
+            .map_err(|e| e.to_string())?
+            .run()
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Lists one signer's built transactions, newest first. The worker serves many callers,
+/// so unlike the stdio server it needs the signer.
+pub async fn list_built(env: &Env, args: &Value, recording: bool) -> Result<Value, Value> {
+    let query: BuiltTransactionsQuery = serde_json::from_value(args.clone()).map_err(super::invalid_args)?;
+    let signer = query.signer.as_deref().ok_or_else(|| err("missing signer"))?;
+    let status = query.status;
+    let limit = query.limit.unwrap_or(DEFAULT_BUILT_LIST_LIMIT).clamp(1, MAX_BUILT_LIST_LIMIT);
+    let now = now();
+    let condition = match status {
+        None => "1 = 1",
+        Some(BuiltStatus::Observed) => "observed_height IS NOT NULL",
+        Some(BuiltStatus::Abandoned) => "observed_height IS NULL AND built_at <= ?3",
+        Some(BuiltStatus::Pending) => "observed_height IS NULL AND built_at > ?3",
+    };
+    let mut bindings: Vec<wasm_bindgen::JsValue> = vec![signer.into(), (limit as f64).into()];
+    if matches!(status, Some(BuiltStatus::Abandoned | BuiltStatus::Pending)) {
+        bindings.push((now.saturating_sub(built::ABANDONED_AFTER_SECS) as f64).into());
+    }
+
+    let db = env.d1("MCP_DATABASE").map_err(|e| err(&e.to_string()))?;
+    let rows: Vec<BuiltRow> = db
+        .prepare(format!(
+            "SELECT record, observed_height FROM built_transactions WHERE signer = ?1 AND ({}) ORDER BY built_at DESC LIMIT ?2",
+            condition
+        ))
+        .bind(&bindings)
+        .map_err(|e| err(&e.to_string()))?
+        .all()
+        .await
+        .map_err(|e| err(&e.to_string()))?
+        .results()
+        .map_err(|e| err(&e.to_string()))?;
+    let transactions: Vec<Value> = rows
+        .into_iter()
+        .filter_map(BuiltRow::into_built)
+        .map(|b| built::listed(&b, now))
+        .collect();
+    Ok(ok(&json!({
+        "count": transactions.len(),
+        "transactions": transactions,
+        "recording": recording,
+        "abandoned_after_secs": built::ABANDONED_AFTER_SECS,
+    })))
+}
+
+fn err(msg: &str) -> Value {
+    json!({ "code": -32603, "message": msg })
+}
+
+fn ok<T: serde::Serialize>(data: &T) -> Value {
+    json!({ "content": [{ "type": "text", "text": serde_json::to_string_pretty(data).unwrap() }] })
+}
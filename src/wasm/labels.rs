@@ -0,0 +1,179 @@
+use crate::blockchain::labels::{self, AddressLabel, ImportReport, LabelChange, LabelRejection, MAX_LABELS};
+use crate::blockchain::recovery;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use worker::{console_warn, D1Database, Date, Env};
+
+/// Secret holding the comma-separated bearer tokens allowed to change labels. Labels are
+/// shared by every caller of the worker, so reading them needs no token.
+pub const LABEL_TOKENS: &str = "MCP_LABEL_TOKENS";
+
+#[derive(serde::Deserialize)]
+struct LabelRow {
+    address: String,
+    label: String,
+    added_at: f64,
+}
+
+impl From<LabelRow> for AddressLabel {
+    fn from(row: LabelRow) -> Self {
+        Self { address: row.address, label: row.label, added_at: row.added_at as u64 }
+    }
+}
+
+fn now() -> u64 {
+    Date::now().as_millis() / 1000
+}
+
+/// Checks the request's bearer token against `MCP_LABEL_TOKENS`.
+fn authorize(env: &Env, headers: &HashMap<String, String>) -> Result<(), Value> {
+    let allowed = env
+        .secret(LABEL_TOKENS)
+        .map(|v| v.to_string())
+        .map_err(|_| err("address labels cannot be changed on this server"))?;
+    let token = headers
+        .get("authorization")
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .ok_or_else(|| err("changing labels requires an Authorization: Bearer token"))?;
+    if !allowed.split(',').map(str::trim).any(|t| t == token) {
+        return Err(err("invalid label token"));
+    }
+    Ok(())
+}
+
+pub async fn add_label(env: &Env, headers: &HashMap<String, String>, args: &Value) -> Result<Value, Value> {
+    authorize(env, headers)?;
+    let address = args["address"].as_str().ok_or_else(|| err("missing address"))?;
+    let label = args["label"].as_str().ok_or_else(|| err("missing label"))?;
+    let db = env.d1("MCP_DATABASE").map_err(|e| err(&e.to_string()))?;
+    let change = store(&db, &AddressLabel { address: address.to_string(), label: label.to_string(), added_at: now() })
+        .await?
+        .map_err(|rejection| invalid_label(&rejection))?;
+    let mut output = serde_json::to_value(&change).map_err(|e| err(&e.to_string()))?;
+    output["address"] = json!(address);
+    output["label"] = json!(label.trim());
+    Ok(ok(&output))
+}
+
+pub async fn export_labels(env: &Env) -> Result<Value, Value> {
+    let db = env.d1("MCP_DATABASE").map_err(|e| err(&e.to_string()))?;
+    let labels: Vec<AddressLabel> = db
+        .prepare("SELECT address, label, added_at FROM address_labels ORDER BY address")
+        .all()
+        .await
+        .map_err(|e| err(&e.to_string()))?
+        .results::<LabelRow>()
+        .map_err(|e| err(&e.to_string()))?
+        .into_iter()
+        .map(AddressLabel::from)
+        .collect();
+    Ok(ok(&json!({ "count": labels.len(), "labels": labels })))
+}
+
+pub async fn import_labels(env: &Env, headers: &HashMap<String, String>, args: &Value) -> Result<Value, Value> {
+    authorize(env, headers)?;
+    let entries: Vec<AddressLabel> = serde_json::from_value(args["labels"].clone()).map_err(|e| err(&e.to_string()))?;
+    let db = env.d1("MCP_DATABASE").map_err(|e| err(&e.to_string()))?;
+    if args["replace"].as_bool().unwrap_or(false) {
+        db.prepare("DELETE FROM address_labels").run().await.map_err(|e| err(&e.to_string()))?;
+    }
+    let now = now();
+    let mut report = ImportReport::default();
+    for entry in &entries {
+        let added_at = if entry.added_at == 0 { now } else { entry.added_at };
+        match store(&db, &AddressLabel { added_at, ..entry.clone() }).await? {
+            Ok(change) => report.count(&change),
+            Err(rejection) => report.reject(entry, &rejection),
+        }
+    }
+    Ok(ok(&report))
+}
+
+/// Validates and upserts one label. The outer error is a storage failure, the inner one a
+/// label the caller has to fix.
+async fn store(db: &D1Database, entry: &AddressLabel) -> Result<Result<LabelChange, LabelRejection>, Value> {
+    let label = match labels::validate(&entry.address, &entry.label) {
+        Ok(label) => label,
+        Err(rejection) => return Ok(Err(rejection)),
+    };
+    let previous: Option<String> = db
+        .prepare("SELECT label FROM address_labels WHERE address = ?1")
+        .bind(&[entry.address.clone().into()])
+        .map_err(|e| err(&e.to_string()))?
+        .first(Some("label"))
+        .await
+        .map_err(|e| err(&e.to_string()))?;
+    let change = match previous {
+        Some(previous) if previous == label => return Ok(Ok(LabelChange::Unchanged)),
+        Some(previous_label) => LabelChange::Updated { previous_label },
+        None => {
+            let count: Option<f64> = db
+                .prepare("SELECT COUNT(*) AS count FROM address_labels")
+                .first(Some("count"))
+                .await
+                .map_err(|e| err(&e.to_string()))?;
+            if count.unwrap_or(0.0) as usize >= MAX_LABELS {
+                return Ok(Err(LabelRejection {
+                    field: "address",
+                    reason: format!("at most {} labels are kept; replace one instead", MAX_LABELS),
+                }));
+            }
+            LabelChange::Added
+        }
+    };
+    db.prepare("INSERT OR REPLACE INTO address_labels (address, label, added_at) VALUES (?1, ?2, ?3)")
+        .bind(&[entry.address.clone().into(), label.into(), (entry.added_at as f64).into()])
+        .map_err(|e| err(&e.to_string()))?
+        .run()
+        .await
+        .map_err(|e| err(&e.to_string()))?;
+    Ok(Ok(change))
+}
+
+/// Adds the labels of the addresses in a tool output next to them. Without the table only
+/// the built-in labels are added.
+pub async fn annotate(env: &Env, output: &mut Value) {
+    let known = match lookup(env, &labels::addresses_in(output)).await {
+        Ok(known) => known,
+        Err(e) => {
+            console_warn!("failed to look up address labels: {}", e);
+            HashMap::new()
+        }
+    };
+    labels::annotate(output, &known);
+}
+
+async fn lookup(env: &Env, addresses: &[String]) -> Result<HashMap<String, String>, String> {
+    if addresses.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let db = env.d1("MCP_DATABASE").map_err(|e| e.to_string())?;
+    let rows: Vec<LabelRow> = db
+        .prepare("SELECT address, label, added_at FROM address_labels WHERE address IN (SELECT value FROM json_each(?1))")
+        .bind(&[json!(addresses).to_string().into()])
+        .map_err(|e| e.to_string())?
+        .all()
+        .await
+        .map_err(|e| e.to_string())?
+        .results()
+        .map_err(|e| e.to_string())?;
+    Ok(rows.into_iter().map(|row| (row.address, row.label)).collect())
+}
+
+fn invalid_label(rejection: &LabelRejection) -> Value {
+    let mut data = Some(json!({ "field": rejection.field, "message": rejection.reason }));
+    if let Some(hint) = recovery::for_code("invalid_label", data.as_ref()) {
+        recovery::attach(&mut data, &hint);
+    }
+    json!({ "code": -32602, "message": "invalid_label", "data": data })
+}
+
+fn err(msg: &str) -> Value {
+    json!({ "code": -32603, "message": msg })
+}
+
+fn ok<T: serde::Serialize>(data: &T) -> Value {
+    json!({ "content": [{ "type": "text", "text": serde_json::to_string_pretty(data).unwrap() }] })
+}
@@ -1,48 +1,70 @@
 use super::tx;
+use crate::blockchain::address::Address;
+use crate::blockchain::clock::{self, ClockSkew};
+use crate::config::ServerConfig;
+use crate::secret::Secret;
 use serde_json::{json, Value};
 use worker::Env;
+use zeroize::Zeroizing;
 
-const FAUCET_AMOUNT: i128 = 100_000_000_000;
-const FAUCET_SYMBOL: &str = "AMA";
+/// Secret holding the faucet's base58 key.
+pub const FAUCET_KEY: &str = "AMADEUS_TESTNET_SK";
 
-pub async fn transfer(env: &Env, address: &str) -> Result<String, Value> {
+/// Sends `whole` units of `symbol` from the faucet key to `address`; the amount is
+/// converted to atoms through the decimals registry. Returns the node's answer and, when
+/// the worker clock is skewed against the testnet tip, a warning; the nonce is then taken
+/// from the chain's time instead of the clock.
+pub async fn transfer(env: &Env, address: &Address, symbol: &str, whole: u64) -> Result<(String, Option<String>), Value> {
     let rpc = env
         .var("AMADEUS_TESTNET_RPC")
         .map(|v| v.to_string())
         .map_err(|_| err("AMADEUS_TESTNET_RPC not configured"))?;
     let key_b58 = env
-        .var("AMADEUS_TESTNET_SK")
-        .map(|v| v.to_string())
+        .secret(FAUCET_KEY)
+        .map(|v| Secret::new(v.to_string()))
         .map_err(|_| err("AMADEUS_TESTNET_SK not configured"))?;
 
-    let sk = bs58::decode(&key_b58)
-        .into_vec()
-        .map_err(|_| err("invalid mint key encoding"))?;
-    let receiver = bs58::decode(address)
-        .into_vec()
-        .map_err(|_| err("invalid address encoding"))?;
-
-    if receiver.len() < 44 || receiver.len() > 48 {
-        return Err(err("address must be 44-48 bytes"));
-    }
-
-    let built = tx::build_transfer_tx(&sk, &receiver, FAUCET_SYMBOL, FAUCET_AMOUNT).map_err(err)?;
+    let sk = Zeroizing::new(
+        bs58::decode(key_b58.expose())
+            .into_vec()
+            .map_err(|_| err("invalid mint key encoding"))?,
+    );
+    let config = ServerConfig::from_lookup(|key| env.var(key).ok().map(|v| v.to_string()));
+    let amount = config
+        .symbol_decimals
+        .to_atoms(symbol, whole)
+        .ok_or_else(|| err(&format!("no decimals configured for {} or the faucet amount does not fit them", symbol)))?;
+    let skew = tip_clock_skew(&rpc, &config).await;
+    let clock_skew_warning = skew.and_then(|s| s.warning(config.clock_skew_threshold));
+    let nonce = skew.filter(|_| clock_skew_warning.is_some()).map(|s| clock::chain_nonce(None, s.chain_time));
+    let built = tx::build_transfer_tx(&sk, address.as_bytes(), symbol, amount, nonce).map_err(err)?;
     let tx_b58 = bs58::encode(&built.packed).into_string();
     let tx_hash = bs58::encode(&built.hash).into_string();
 
-    let url = format!("{}/api/tx/submit/{}", rpc.trim_end_matches('/'), tx_b58);
+    let url = format!("{}{}", rpc.trim_end_matches('/'), config.api_paths.tx(&format!("tx/submit/{}", tx_b58)));
     let mut resp = worker::Fetch::Url(worker::Url::parse(&url).map_err(|e| err(&e.to_string()))?)
         .send()
         .await
         .map_err(|e| err(&e.to_string()))?;
 
     let body = resp.text().await.map_err(|e| err(&e.to_string()))?;
-    Ok(format!(
+    let result = format!(
         "status={} tx_hash={} body={}",
         resp.status_code(),
         tx_hash,
         body
-    ))
+    );
+    Ok((result, clock_skew_warning))
+}
+
+/// Worker clock against the testnet tip entry's timestamp; `None` when the tip cannot be
+/// read or carries no timestamp.
+async fn tip_clock_skew(rpc: &str, config: &ServerConfig) -> Option<ClockSkew> {
+    let url = format!("{}{}", rpc.trim_end_matches('/'), config.api_paths.api("chain/tip"));
+    let mut resp = worker::Fetch::Url(worker::Url::parse(&url).ok()?).send().await.ok()?;
+    let tip: Value = resp.json().await.ok()?;
+    let timestamp = tip["entry"]["header"]["timestamp"].as_u64()?;
+    Some(ClockSkew::measure((worker::Date::now().as_millis() / 1000) as u64, timestamp))
 }
 
 fn err(msg: &str) -> Value {
@@ -1,12 +1,23 @@
 pub mod tx;
 
+#[cfg(target_arch = "wasm32")]
+mod built;
+#[cfg(target_arch = "wasm32")]
+mod labels;
 #[cfg(target_arch = "wasm32")]
 mod mint;
+#[cfg(target_arch = "wasm32")]
+mod session;
+#[cfg(target_arch = "wasm32")]
+mod watch;
 
 #[cfg(target_arch = "wasm32")]
 mod worker_handlers {
-use super::mint;
+use super::{built, labels, mint, session, watch};
 use crate::blockchain::*;
+use crate::blockchain::faucet::{ClaimLedger, FaucetTokens};
+use crate::catalog::{self, TOOLS_VERSION};
+use crate::config::{ServerConfig, UpstreamTransport, BLOCKCHAIN_SERVICE_BINDING};
 use crate::BlockchainClient;
 use serde_json::{json, Value};
 use std::collections::HashMap;
@@ -14,12 +25,9 @@ use worker::*;
 
 #[event(fetch)]
 pub async fn main(mut req: Request, env: Env, _ctx: Context) -> Result<Response> {
-    let blockchain_url = env
-        .var("BLOCKCHAIN_URL")
-        .map(|v| v.to_string())
-        .unwrap_or_else(|_| "https://nodes.amadeus.bot".to_string());
+    let blockchain_url = blockchain_url(&env);
 
-    let client = BlockchainClient::new(blockchain_url.clone())
+    let client = upstream_client(&env, &blockchain_url)
         .map_err(|e| format!("failed to create client: {}", e))?;
 
     let url = req.url()?;
@@ -29,10 +37,17 @@ pub async fn main(mut req: Request, env: Env, _ctx: Context) -> Result<Response>
         return serve_faucet_page();
     }
 
-    if req.method() == Method::Post {
-        let client_ip = req.headers().get("CF-Connecting-IP").ok().flatten();
-        let headers: HashMap<String, String> = req.headers().entries().collect();
-        let body: Value = req.json().await?;
+    let headers: HashMap<String, String> = req.headers().entries().collect();
+    let body: Option<Value> = match req.method() {
+        Method::Post => Some(req.json().await?),
+        _ => None,
+    };
+    if let Some(response) = session::route(&env, req.method(), &headers, body.as_ref()).await {
+        return response;
+    }
+
+    if let Some(body) = body {
+        let client_ip = headers.get("cf-connecting-ip").cloned();
         Response::from_json(&handle_mcp_request(&client, &env, &blockchain_url, client_ip, headers, body).await)
     } else {
         Response::from_json(&json!({
@@ -43,6 +58,29 @@ pub async fn main(mut req: Request, env: Env, _ctx: Context) -> Result<Response>
     }
 }
 
+pub(super) fn blockchain_url(env: &Env) -> String {
+    env.var("BLOCKCHAIN_URL")
+        .map(|v| v.to_string())
+        .unwrap_or_else(|_| "https://nodes.amadeus.bot".to_string())
+}
+
+/// Client for `rpc`, routed through the service binding when it is bound and enabled.
+pub(super) fn upstream_client(env: &Env, rpc: &str) -> std::result::Result<BlockchainClient, BlockchainError> {
+    let config = ServerConfig::from_lookup(|key| env.var(key).ok().map(|v| v.to_string()));
+    let client = BlockchainClient::new(rpc.to_string())?
+        .with_fallbacks(config.mainnet_fallback_urls.clone())
+        .with_api_paths(config.api_paths.clone())
+        .with_limits(config.query_limits);
+    let binding = env.service(BLOCKCHAIN_SERVICE_BINDING).ok();
+    if binding.is_none() && config.upstream_transport == UpstreamTransport::Service {
+        console_warn!("{} is not bound, falling back to public fetch", BLOCKCHAIN_SERVICE_BINDING);
+    }
+    Ok(match config.upstream_transport.select(binding) {
+        Some(service) => client.with_service(service),
+        None => client,
+    })
+}
+
 fn serve_faucet_page() -> Result<Response> {
     let html = r#"<!DOCTYPE html>
 <html lang="en">
@@ -314,20 +352,85 @@ fn serve_faucet_page() -> Result<Response> {
     Response::from_html(html)
 }
 
-async fn handle_mcp_request(
+pub(super) async fn handle_mcp_request(
     client: &BlockchainClient, env: &Env, rpc: &str, client_ip: Option<String>,
     headers: HashMap<String, String>, request: Value,
 ) -> Value {
     let method = request["method"].as_str().unwrap_or("");
     let id = request.get("id").cloned();
+    let config = ServerConfig::from_lookup(|key| env.var(key).ok().map(|v| v.to_string()));
     let result: std::result::Result<Value, Value> = match method {
         "initialize" => Ok(json!({
             "protocolVersion": "2024-11-05",
-            "capabilities": { "tools": {} },
-            "serverInfo": { "name": "amadeus-mcp", "version": env!("CARGO_PKG_VERSION") }
+            "capabilities": { "tools": {}, "resources": {} },
+            "serverInfo": { "name": "amadeus-mcp", "version": env!("CARGO_PKG_VERSION") },
+            "instructions": format!(
+                "Blockchain MCP server for Amadeus. toolsVersion: {} features: {}",
+                TOOLS_VERSION,
+                json!(catalog::features(&config, &config.tool_filter, deployment(env)))
+            )
         })),
-        "tools/list" => Ok(tools_list()),
-        "tools/call" => handle_tool_call(client, env, rpc, client_ip, headers, &request["params"]).await,
+        "tools/list" => tools_page(&config, &request["params"]),
+        "resources/list" => Ok(json!({ "resources": [{
+            "uri": catalog::SIGNING_WORKFLOW_URI,
+            "name": "signing_workflow",
+            "description": "How to go from create_transaction to submit_transaction: the DST, the exact bytes to sign and a live example blob",
+            "mimeType": "text/plain"
+        }, {
+            "uri": catalog::ERROR_RECOVERY_URI,
+            "name": "error_recovery",
+            "description": "The recovery actions errors carry in their data and what each asks of the caller",
+            "mimeType": "text/plain"
+        }] })),
+        "resources/templates/list" => Ok(json!({ "resourceTemplates": [{
+            "uriTemplate": format!("{}{{name}}", catalog::TOOL_DOCS_URI),
+            "name": "tool_docs",
+            "description": "Long-form documentation for a tool",
+            "mimeType": "text/plain"
+        }, {
+            "uriTemplate": format!("{}{{name}}", templates::TEMPLATES_URI),
+            "name": "tx_templates",
+            "description": "Argument layout and fill-in example of a common transaction",
+            "mimeType": "application/json"
+        }] })),
+        "resources/read" => read_tool_doc(&request["params"]),
+        "tools/call" => {
+            let limits = config.session_budget;
+            let budget_scope = submit_scope(&client_ip, &headers).filter(|_| !limits.is_unlimited());
+            let mut session_budget = match &budget_scope {
+                Some(scope) => load_budget(env, scope).await.unwrap_or_else(|e| {
+                    console_warn!("session budget unavailable, not enforcing it: {}", e);
+                    None
+                }),
+                None => None,
+            };
+            let now = Date::now().as_millis() / 1000;
+            if let Some((reason, usage)) = session_budget
+                .as_ref()
+                .and_then(|b| b.exhausted(limits, now).map(|reason| (reason, b.usage(limits, now))))
+            {
+                return json!({ "jsonrpc": "2.0", "id": id, "error": {
+                    "code": -32600,
+                    "message": "session budget exhausted",
+                    "data": with_recovery("session_budget_exhausted", budget::exhausted_error(&reason, usage)),
+                } });
+            }
+
+            let (client, call_metrics) = client.measured();
+            let started = Date::now().as_millis();
+            let mut result = handle_tool_call(&client, env, &config, rpc, client_ip, headers, &request["params"]).await;
+            let timing = call_metrics.timing(std::time::Duration::from_millis(Date::now().as_millis() - started));
+            if let (Some(scope), Some(session_budget)) = (&budget_scope, session_budget.as_mut()) {
+                session_budget.record(timing.upstream_requests.into(), Date::now().as_millis() / 1000);
+                if let Err(e) = store_budget(env, scope, session_budget).await {
+                    console_warn!("failed to store session budget: {}", e);
+                }
+            }
+            if let (true, Ok(result)) = (config.include_timing, result.as_mut()) {
+                with_timing(result, timing);
+            }
+            result
+        }
         _ => Err(err("unknown method")),
     };
 
@@ -337,19 +440,152 @@ async fn handle_mcp_request(
     }
 }
 
+/// Adds `timing` to the JSON text content of a tool result.
+fn with_timing(result: &mut Value, timing: metrics::Timing) {
+    let Some(text) = result["content"][0]["text"].as_str() else { return };
+    if let Ok(mut output) = serde_json::from_str::<Value>(text) {
+        metrics::attach(&mut output, timing);
+        result["content"][0]["text"] = json!(serde_json::to_string_pretty(&output).unwrap_or_default());
+    }
+}
+
 async fn handle_tool_call(
-    client: &BlockchainClient, env: &Env, rpc: &str, client_ip: Option<String>,
+    client: &BlockchainClient, env: &Env, config: &ServerConfig, rpc: &str, client_ip: Option<String>,
     headers: HashMap<String, String>, params: &Value,
 ) -> std::result::Result<Value, Value> {
     let tool = params["name"].as_str().unwrap_or("");
     let args = &params["arguments"];
+    if !config.tool_filter.allows(tool) {
+        return Err(err(&format!("tool disabled: {}", tool)));
+    }
+    if config.strict_args {
+        let schema = tools_list(config).into_iter().find(|t| t["name"] == tool);
+        let unknown = match (schema.as_ref().and_then(|t| t["inputSchema"].as_object()), args.as_object()) {
+            (Some(schema), Some(arguments)) => catalog::unknown_arguments(schema, arguments),
+            _ => Vec::new(),
+        };
+        if !unknown.is_empty() {
+            let data = with_recovery("unknown_arguments", json!({ "tool": tool, "unknown": unknown }));
+            return Err(json!({ "code": -32602, "message": "unknown arguments", "data": data }));
+        }
+    }
+    let detail = args["detail"]
+        .as_str()
+        .and_then(|d| d.parse().ok())
+        .unwrap_or(config.output_detail);
     match tool {
+        "get_capabilities" => {
+            let tools: Vec<String> = tools_list(config)
+                .iter()
+                .filter_map(|t| t["name"].as_str().map(String::from))
+                .collect();
+            let features = catalog::features(config, &config.tool_filter, deployment(env));
+            Ok(ok(&catalog::capabilities(TOOLS_VERSION, &tools, &features, client.limits())))
+        }
+        "list_tx_templates" => {
+            let templates: Vec<Value> = templates::TEMPLATES.iter().map(|t| t.describe()).collect();
+            Ok(ok(&json!({ "templates": templates })))
+        }
         "create_transaction" => {
             let req: TransactionRequest =
                 serde_json::from_value(args.clone()).map_err(|e| err(&e.to_string()))?;
-            client.create_transaction_blob(req).await
-                .map(|b| ok(&json!({ "blob": b.blob, "signing_payload": b.signing_payload, "transaction_hash": b.transaction_hash, "status": "unsigned" })))
-                .map_err(|e| err(&e.to_string()))
+            if let Some(transfer) = transfer_check::TransferRequest::from_request(&req) {
+                client.validate_transfer(&transfer, None, rpc).await.into_result().map_err(chain_err)?;
+            } else if let Some(requested) = symbol::requested_symbol(&req) {
+                if let Ok(account) = client.get_account_balance(&req.signer, rpc).await {
+                    symbol::check_held(requested, &account.balances).map_err(|e| err(&e))?;
+                }
+            }
+            let nonce_basis = if req.nonce.is_some() { freshness::NonceBasis::Explicit } else { freshness::NonceBasis::Clock };
+            let built_at = Date::now().as_millis() / 1000;
+            let record = config.record_built_transactions.then(|| req.clone());
+            let b = client.create_transaction_blob(req).await.map_err(chain_err)?;
+            if let Some(req) = record {
+                built::record(env, &crate::blockchain::built::BuiltTransaction::new(&req, &b.transaction_hash, built_at)).await;
+            }
+            let mut output = json!({
+                "blob": b.blob,
+                "signing_payload": b.signing_payload,
+                "transaction_hash": b.transaction_hash,
+                "nonce": b.nonce.to_string(),
+                "nonce_basis": nonce_basis,
+                "built_at": built_at,
+                "status": "unsigned"
+            });
+            if let Ok(fee) = fee::FeeEstimate::for_blob(&b.blob) {
+                output["estimated_fee"] = serde_json::to_value(fee).unwrap_or_default();
+            }
+            Ok(ok(&output))
+        }
+        "estimate_fee" => {
+            let query: EstimateFeeQuery = serde_json::from_value(args.clone()).map_err(|e| err(&e.to_string()))?;
+            let estimate = query.estimate(&config.symbol_decimals).map_err(|e| err(&e))?;
+            Ok(ok(&estimate))
+        }
+        "check_node_health" => {
+            let (url, reference) = match args["network"].as_str() {
+                Some("testnet") => (config.testnet_url.clone(), None),
+                _ => (rpc.to_string(), config.mainnet_fallback_urls.first()),
+            };
+            Ok(ok(&client.health_check(&url, reference.map(String::as_str)).await))
+        }
+        "validate_address" => {
+            let query: ValidateAddressQuery =
+                serde_json::from_value(args.clone()).map_err(|e| err(&e.to_string()))?;
+            Ok(ok(&address::AddressCheck::new(&query.address)))
+        }
+        "validate_transfer" => {
+            let query: ValidateTransferQuery =
+                serde_json::from_value(args.clone()).map_err(|e| err(&e.to_string()))?;
+            let url = match query.network.as_deref() {
+                Some("testnet") => config.testnet_url.clone(),
+                _ => rpc.to_string(),
+            };
+            let transfer = transfer_check::TransferRequest::new(&query.source, &query.destination, &query.amount, query.symbol.as_deref());
+            let report = client.validate_transfer(&transfer, Some(&config.symbol_decimals), &url).await;
+            let mut output = serde_json::to_value(&report).map_err(|e| err(&e.to_string()))?;
+            labels::annotate(env, &mut output).await;
+            Ok(ok(&output))
+        }
+        "check_blob_freshness" => {
+            let query: BlobFreshnessQuery =
+                serde_json::from_value(args.clone()).map_err(|e| err(&e.to_string()))?;
+            let url = match query.network.as_deref() {
+                Some("testnet") => config.testnet_url.clone(),
+                _ => rpc.to_string(),
+            };
+            let read = freshness::read_blob(&query.transaction).map_err(err)?;
+            let history = client
+                .get_transaction_history(&read.signer, Some(freshness::NONCE_HISTORY_LIMIT), None, Some("desc"), &url)
+                .await
+                .map_err(chain_err)?;
+            // The worker keeps no record of the blobs it built, so the origin is always unknown
+            let latest = freshness::latest_nonce(&read.signer, &history);
+            Ok(ok(&freshness::evaluate(read.nonce, latest, None, Date::now().as_millis() / 1000)))
+        }
+        "decode_transaction" => {
+            let query: DecodeTransactionQuery =
+                serde_json::from_value(args.clone()).map_err(|e| err(&e.to_string()))?;
+            let decoded = decode::decode_blob(&query.blob).map_err(|e| err(&e))?;
+            let mut output = serde_json::to_value(&decoded).map_err(|e| err(&e.to_string()))?;
+            labels::annotate(env, &mut output).await;
+            Ok(ok(&output))
+        }
+        "verify_signature" => {
+            let query: VerifySignatureQuery =
+                serde_json::from_value(args.clone()).map_err(|e| err(&e.to_string()))?;
+            Ok(ok(&signing::verify_external(&query.signing_payload, &query.signature, &query.public_key)))
+        }
+        "rebuild_and_compare" => {
+            let query: RebuildQuery =
+                serde_json::from_value(args.clone()).map_err(|e| err(&e.to_string()))?;
+            if query.request.nonce.is_none() {
+                return Err(err("request.nonce must be set for a reproducible rebuild"));
+            }
+            let rebuilt = client.create_transaction_blob(query.request).await.map_err(chain_err)?;
+            rebuild::compare_blob(&rebuilt, &query.blob)
+                .map(|c| ok(&c))
+                .map_err(|e| err(&e))
         }
         "submit_transaction" => {
             let tx: SignedTransaction =
@@ -358,25 +594,49 @@ async fn handle_tool_call(
                 Some("testnet") => env.var("AMADEUS_TESTNET_RPC").map(|v| v.to_string()).unwrap_or_else(|_| "https://testnet.amadeus.bot".to_string()),
                 _ => rpc.to_string(),
             };
-            client
-                .submit_signed_transaction(tx, &url)
-                .await
-                .map(|r| ok(&r))
-                .map_err(|e| err(&e.to_string()))
+            submit_idempotent(client, env, submit_scope(&client_ip, &headers), tx, &url).await
         }
         "get_account_balance" => {
-            let addr = args["address"]
-                .as_str()
-                .ok_or_else(|| err("missing address"))?;
+            let query: AccountQuery = serde_json::from_value(args.clone()).map_err(|e| err(&e.to_string()))?;
+            let address = query.address().map_err(|e| err(&format!("invalid address: {}", e)))?;
+            let addr = address.as_str();
             let url = match args["network"].as_str() {
                 Some("testnet") => env.var("AMADEUS_TESTNET_RPC").map(|v| v.to_string()).unwrap_or_else(|_| "https://testnet.amadeus.bot".to_string()),
                 _ => rpc.to_string(),
             };
-            client
-                .get_account_balance(addr, &url)
-                .await
-                .map(|b| ok(&b))
-                .map_err(|e| err(&e.to_string()))
+            let balance = match query.symbol.as_deref() {
+                Some(symbol) => client.get_account_symbol_balance(addr, symbol, &url).await,
+                None => client.get_account_balance(addr, &url).await,
+            }
+            .map_err(chain_err)?;
+            let mut output = serde_json::to_value(&balance).map_err(|e| err(&e.to_string()))?;
+            labels::annotate(env, &mut output).await;
+            Ok(ok(&output))
+        }
+        "get_account_nonce" => {
+            let addr = args["address"].as_str().ok_or_else(|| err("missing address"))?;
+            let url = match args["network"].as_str() {
+                Some("testnet") => config.testnet_url.clone(),
+                _ => rpc.to_string(),
+            };
+            let nonce = client.get_account_nonce(addr, &url).await.map_err(chain_err)?;
+            let mut output = serde_json::to_value(&nonce).map_err(|e| err(&e.to_string()))?;
+            labels::annotate(env, &mut output).await;
+            Ok(ok(&output))
+        }
+        "get_pending_transactions" => {
+            let url = match args["network"].as_str() {
+                Some("testnet") => config.testnet_url.clone(),
+                _ => rpc.to_string(),
+            };
+            let pool = client.get_tx_pool(&url).await.map_err(|e| match e {
+                BlockchainError::EndpointUnsupported { .. } => err(tx_pool::TX_POOL_UNSUPPORTED),
+                e => chain_err(e),
+            })?;
+            let mut output = serde_json::to_value(tx_pool::PendingTransactions::new(pool, args["address"].as_str()))
+                .map_err(|e| err(&e.to_string()))?;
+            labels::annotate(env, &mut output).await;
+            Ok(ok(&output))
         }
         "get_chain_stats" => {
             let url = match args["network"].as_str() {
@@ -387,21 +647,57 @@ async fn handle_tool_call(
                 .get_chain_stats(&url)
                 .await
                 .map(|s| ok(&s))
-                .map_err(|e| err(&e.to_string()))
+                .map_err(chain_err)
+        }
+        "get_emission_stats" => {
+            let query: EmissionStatsQuery = serde_json::from_value(args.clone()).map_err(invalid_args)?;
+            let window = query.window().map_err(|e| err(&e))?;
+            let found = match window {
+                emission::EmissionWindow::Since(since) => {
+                    Some(client.block_at_time(rpc, since, None).await.map_err(chain_err)?)
+                }
+                emission::EmissionWindow::Blocks(_) => None,
+            };
+            let stats = client.get_chain_stats(rpc).await.map_err(chain_err)?;
+            let blocks = match (window, &found) {
+                (emission::EmissionWindow::Blocks(blocks), _) => blocks.max(1),
+                (_, found) => found.as_ref().map_or(1, |found| emission::blocks_since(found, stats.height)),
+            };
+            let now = Date::now().as_millis() / 1000;
+            let Some(end) = emission::StatsSnapshot::from_stats(&stats, now) else {
+                return Ok(ok(&emission::insufficient_data(blocks, 0)));
+            };
+            // The snapshots the scheduled handler records, bracketing the window start
+            let history = stats_snapshots_around(env, end.height.saturating_sub(blocks)).await?;
+            match emission::emission_stats(&history, &end, blocks) {
+                Some(report) => Ok(ok(&emission::EmissionStats { since: found.map(|found| found.target), ..report })),
+                None => Ok(ok(&emission::insufficient_data(blocks, history.len()))),
+            }
+        }
+        "get_block_by_timestamp" => {
+            let query: BlockAtTimeQuery =
+                serde_json::from_value(args.clone()).map_err(|e| err(&e.to_string()))?;
+            let target = query.timestamp.unix_secs().map_err(|e| err(&e))?;
+            let url = match query.network.as_deref() {
+                Some("testnet") => config.testnet_url.clone(),
+                _ => rpc.to_string(),
+            };
+            client.block_at_time(&url, target, query.max_probes).await.map(|found| ok(&found)).map_err(chain_err)
         }
         "get_block_by_height" => {
-            let height = args["height"]
-                .as_u64()
-                .ok_or_else(|| err("missing height"))?;
-            let url = match args["network"].as_str() {
+            let query: HeightQuery = serde_json::from_value(args.clone()).map_err(invalid_args)?;
+            let url = match query.network.as_deref() {
                 Some("testnet") => env.var("AMADEUS_TESTNET_RPC").map(|v| v.to_string()).unwrap_or_else(|_| "https://testnet.amadeus.bot".to_string()),
                 _ => rpc.to_string(),
             };
             client
-                .get_block_by_height(height, &url)
+                .get_block_by_height(query.height, &url)
                 .await
-                .map(|e| ok(&e))
-                .map_err(|e| err(&e.to_string()))
+                .map(|e| match detail {
+                    OutputDetail::Full => ok(&e),
+                    OutputDetail::Compact => ok(&CompactList::new(e.iter().map(CompactBlockEntry::from))),
+                })
+                .map_err(chain_err)
         }
         "get_transaction" => {
             let hash = args["tx_hash"]
@@ -411,39 +707,96 @@ async fn handle_tool_call(
                 Some("testnet") => env.var("AMADEUS_TESTNET_RPC").map(|v| v.to_string()).unwrap_or_else(|_| "https://testnet.amadeus.bot".to_string()),
                 _ => rpc.to_string(),
             };
-            client
-                .get_transaction(hash, &url)
-                .await
-                .map(|t| ok(&t))
-                .map_err(|e| err(&e.to_string()))
+            let t = client.get_transaction(hash, &url).await.map_err(chain_err)?;
+            let mut output = serde_json::to_value(&t).map_err(|e| err(&e.to_string()))?;
+            if config.record_built_transactions {
+                built::annotate(env, &mut output).await;
+            }
+            labels::annotate(env, &mut output).await;
+            Ok(ok(&output))
         }
         "get_transaction_history" => {
-            let addr = args["address"]
-                .as_str()
-                .ok_or_else(|| err("missing address"))?;
-            let limit = args["limit"].as_u64().map(|v| v as u32);
-            let offset = args["offset"].as_u64().map(|v| v as u32);
-            let sort = args["sort"].as_str();
-            let url = match args["network"].as_str() {
+            let query: TransactionHistoryQuery = serde_json::from_value(args.clone()).map_err(invalid_args)?;
+            let url = match query.network.as_deref() {
                 Some("testnet") => env.var("AMADEUS_TESTNET_RPC").map(|v| v.to_string()).unwrap_or_else(|_| "https://testnet.amadeus.bot".to_string()),
                 _ => rpc.to_string(),
             };
-            client
-                .get_transaction_history(addr, limit, offset, sort, &url)
+            let filter = query.filter();
+            let mut output = if filter.is_empty() {
+                let t = client
+                    .get_transaction_history(&query.address, query.limit, query.offset, query.sort.as_deref(), &url)
+                    .await
+                    .map_err(chain_err)?;
+                match detail {
+                    OutputDetail::Full => serde_json::to_value(&t),
+                    OutputDetail::Compact => serde_json::to_value(CompactList::new(t.iter().map(CompactTransaction::from))),
+                }
+            } else {
+                let h = client
+                    .get_filtered_history(&query.address, &filter, query.limit, query.offset, query.sort.as_deref(), &url)
+                    .await
+                    .map_err(chain_err)?;
+                match detail {
+                    OutputDetail::Full => serde_json::to_value(&h),
+                    OutputDetail::Compact => serde_json::to_value(
+                        h.map(|t| CompactList::new(t.iter().map(CompactTransaction::from))),
+                    ),
+                }
+            }
+            .map_err(|e| err(&e.to_string()))?;
+            if config.record_built_transactions {
+                built::annotate(env, &mut output).await;
+            }
+            labels::annotate(env, &mut output).await;
+            Ok(ok(&output))
+        }
+        "get_full_transaction_history" => {
+            let query: FullHistoryQuery = serde_json::from_value(args.clone()).map_err(|e| err(&e.to_string()))?;
+            let url = match query.network.as_deref() {
+                Some("testnet") => env.var("AMADEUS_TESTNET_RPC").map(|v| v.to_string()).unwrap_or_else(|_| "https://testnet.amadeus.bot".to_string()),
+                _ => rpc.to_string(),
+            };
+            let h = client
+                .get_full_transaction_history(&query.address, query.page_size, query.max_transactions, &url, |_| async {})
+                .await
+                .map_err(chain_err)?;
+            let mut output = serde_json::to_value(&h).map_err(|e| err(&e.to_string()))?;
+            if config.record_built_transactions {
+                built::annotate(env, &mut output).await;
+            }
+            labels::annotate(env, &mut output).await;
+            Ok(ok(&output))
+        }
+        "analyze_account" => {
+            let query: FullHistoryQuery = serde_json::from_value(args.clone()).map_err(|e| err(&e.to_string()))?;
+            let address = query.address().map_err(|e| err(&format!("invalid address: {}", e)))?;
+            let url = match query.network.as_deref() {
+                Some("testnet") => config.testnet_url.clone(),
+                _ => rpc.to_string(),
+            };
+            let h = client
+                .get_full_transaction_history(address.as_str(), query.page_size, query.max_transactions, &url, |_| async {})
                 .await
-                .map(|t| ok(&t))
-                .map_err(|e| err(&e.to_string()))
+                .map_err(chain_err)?;
+            let mut output = serde_json::to_value(analytics::AccountActivity::from_history(&h)).map_err(|e| err(&e.to_string()))?;
+            labels::annotate(env, &mut output).await;
+            Ok(ok(&output))
         }
         "get_validators" => {
             let url = match args["network"].as_str() {
                 Some("testnet") => env.var("AMADEUS_TESTNET_RPC").map(|v| v.to_string()).unwrap_or_else(|_| "https://testnet.amadeus.bot".to_string()),
                 _ => rpc.to_string(),
             };
+            let keys_only = args["keys_only"].as_bool().unwrap_or(false);
             client
-                .get_validators(&url)
+                .get_validators(&url, keys_only)
                 .await
-                .map(|v| ok(&json!({ "validators": v, "count": v.len() })))
-                .map_err(|e| err(&e.to_string()))
+                .map(|v| ok(&json!({
+                    "validators": v,
+                    "count": v.len(),
+                    "scores_available": v.iter().any(|i| i.score.is_some())
+                })))
+                .map_err(chain_err)
         }
         "get_contract_state" => {
             let addr = args["contract_address"]
@@ -458,43 +811,212 @@ async fn handle_tool_call(
                 .get_contract_state(addr, key, &url)
                 .await
                 .map(|s| ok(&json!({ "contract_address": addr, "key": key, "value": s })))
-                .map_err(|e| err(&e.to_string()))
+                .map_err(chain_err)
+        }
+        "list_contract_keys" => {
+            let query: ContractKeysQuery = serde_json::from_value(args.clone()).map_err(|e| err(&e.to_string()))?;
+            let after = query
+                .cursor
+                .as_deref()
+                .map(|cursor| state_export::decode_cursor(cursor, &query.contract_address, &query.prefix))
+                .transpose()
+                .map_err(|e| err(&e))?;
+            let url = match query.network.as_deref() {
+                Some("testnet") => config.testnet_url.clone(),
+                _ => rpc.to_string(),
+            };
+            client
+                .list_contract_state(
+                    &query.contract_address,
+                    &query.prefix,
+                    after.as_deref(),
+                    query.limit.unwrap_or(state_export::DEFAULT_LIST_LIMIT).clamp(1, state_export::MAX_LIST_LIMIT),
+                    &url,
+                )
+                .await
+                .map(|listing| ok(&listing))
+                .map_err(chain_err)
+        }
+        "get_token_info" => {
+            let url = match args["network"].as_str() {
+                Some("testnet") => config.testnet_url.clone(),
+                _ => rpc.to_string(),
+            };
+            match args["symbol"].as_str() {
+                Some(symbol) => client.get_token_info(symbol, &config.symbol_decimals, &url).await.map(|t| ok(&t)),
+                None => client.list_tokens(&config.symbol_decimals, &url).await.map(|l| ok(&l)),
+            }
+            .map_err(chain_err)
+        }
+        "export_contract_state" => {
+            let query: ContractExportQuery = serde_json::from_value(args.clone()).map_err(|e| err(&e.to_string()))?;
+            let after = query
+                .cursor
+                .as_deref()
+                .map(|cursor| state_export::decode_cursor(cursor, &query.contract_address, &query.prefix))
+                .transpose()
+                .map_err(|e| err(&e))?;
+            let url = match query.network.as_deref() {
+                Some("testnet") => config.testnet_url.clone(),
+                _ => rpc.to_string(),
+            };
+            let page = client
+                .export_contract_state(
+                    &query.contract_address,
+                    &query.prefix,
+                    after.as_deref(),
+                    query.limit.unwrap_or(state_export::DEFAULT_EXPORT_LIMIT).clamp(1, state_export::MAX_EXPORT_LIMIT),
+                    &url,
+                )
+                .await
+                .map_err(chain_err)?;
+            if query.format.unwrap_or_default() == state_export::ExportFormat::Csv {
+                return Ok(ok(&json!({
+                    "contract_address": page.contract_address,
+                    "prefix": page.prefix,
+                    "csv": state_export::to_csv(&page.keys),
+                    "count": page.count,
+                    "next_cursor": page.next_cursor,
+                    "complete": page.complete,
+                })));
+            }
+            Ok(ok(&page))
         }
-        "claim_testnet_ama" => claim_testnet_ama(env, client_ip, headers, args).await,
-        "get_entry_tip" => fetch_json(&format!("{rpc}/api/chain/tip")).await,
+        "preview_contract_call" => {
+            let query: ContractCallPreviewQuery =
+                serde_json::from_value(args.clone()).map_err(|e| err(&e.to_string()))?;
+            let url = match query.network.as_deref() {
+                Some("testnet") => config.testnet_url.clone(),
+                _ => rpc.to_string(),
+            };
+            client
+                .preview_contract_call(&query.contract, &query.function, &query.args, &url)
+                .await
+                .map(|preview| ok(&preview))
+                .map_err(chain_err)
+        }
+        "raw_node_query" => {
+            let path = args["path"].as_str().ok_or_else(|| err("missing path"))?;
+            let method = args["method"].as_str().unwrap_or("GET");
+            if !method.eq_ignore_ascii_case("GET") {
+                return Err(err(&format!("method {} is not allowed, only GET", method)));
+            }
+            config.tool_filter.check_raw_path(path).map_err(|reason| err(&reason))?;
+            let url = match args["network"].as_str() {
+                Some("testnet") => config.testnet_url.clone(),
+                _ => rpc.to_string(),
+            };
+            client
+                .raw_query(&client.api_paths().rebase(path), &url)
+                .await
+                .map(|body| ok(&json!({ "path": path, "body": body })))
+                .map_err(chain_err)
+        }
+        "claim_testnet_ama" => claim_testnet_ama(env, config, client_ip, headers, args).await,
+        "create_watch" => watch::create_watch(client, env, rpc, &headers, args).await,
+        "list_watches" => watch::list_watches(env, &headers).await,
+        "list_built_transactions" => built::list_built(env, args, config.record_built_transactions).await,
+        "delete_watch" => watch::delete_watch(env, &headers, args).await,
+        "add_address_label" => labels::add_label(env, &headers, args).await,
+        "export_address_labels" => labels::export_labels(env).await,
+        "import_address_labels" => labels::import_labels(env, &headers, args).await,
+        "get_entry_tip" => fetch_json(client, &client.api_url(rpc, "chain/tip")).await,
         "get_entry_by_hash" => {
             let h = args["hash"].as_str().ok_or_else(|| err("missing hash"))?;
-            fetch_json(&format!("{rpc}/api/chain/hash/{h}")).await
+            let entry = client.get_entry_by_hash(h, rpc).await.map_err(chain_err)?;
+            match detail {
+                OutputDetail::Full => Ok(ok(&entry)),
+                OutputDetail::Compact => Ok(ok(&CompactBlockEntry::from(&entry))),
+            }
         }
         "get_block_with_txs" => {
-            let h = args["height"].as_u64().ok_or_else(|| err("missing height"))?;
-            fetch_json(&format!("{rpc}/api/chain/height_with_txs/{h}")).await
+            let query: BlockWithTxsQuery = serde_json::from_value(args.clone()).map_err(invalid_args)?;
+            let entries = client.get_block_with_txs(query.height, rpc).await.map_err(chain_err)?;
+            let offset = query.tx_offset.unwrap_or(0) as usize;
+            let limit = query.tx_limit.unwrap_or(block_txs::DEFAULT_BLOCK_TXS).clamp(1, block_txs::MAX_BLOCK_TXS);
+            let pages = block_txs::page(entries, offset, limit as usize);
+            match detail {
+                OutputDetail::Full => {
+                    let mut output = serde_json::to_value(&pages).map_err(|e| err(&e.to_string()))?;
+                    labels::annotate(env, &mut output).await;
+                    Ok(ok(&output))
+                }
+                OutputDetail::Compact => Ok(ok(&pages.iter().map(CompactEntryTxsPage::from).collect::<Vec<_>>())),
+            }
         }
         "get_txs_in_entry" => {
             let h = args["entry_hash"].as_str().ok_or_else(|| err("missing entry_hash"))?;
-            fetch_json(&format!("{rpc}/api/chain/txs_in_entry/{h}")).await
+            let entry = client.get_txs_in_entry(h, rpc).await.map_err(chain_err)?;
+            match detail {
+                OutputDetail::Full => {
+                    let mut output = serde_json::to_value(&entry).map_err(|e| err(&e.to_string()))?;
+                    labels::annotate(env, &mut output).await;
+                    Ok(ok(&output))
+                }
+                OutputDetail::Compact => Ok(ok(&CompactList::new(entry.txs.iter().map(CompactTransaction::from)))),
+            }
         }
         "get_epoch_score" => {
-            let url = match args["address"].as_str() {
-                Some(pk) => format!("{rpc}/api/epoch/score/{pk}"),
-                None => format!("{rpc}/api/epoch/score"),
-            };
-            fetch_json(&url).await
+            let scores = client.get_epoch_score(rpc, args["address"].as_str()).await.map_err(chain_err)?;
+            Ok(ok(&json!({ "count": scores.len(), "scores": scores })))
         }
         "get_emission_address" => {
             let pk = args["address"].as_str().ok_or_else(|| err("missing address"))?;
-            fetch_json(&format!("{rpc}/api/epoch/get_emission_address/{pk}")).await
+            let emission = client.get_emission_address(pk, rpc).await.map_err(chain_err)?;
+            let mut output = serde_json::to_value(&emission).map_err(|e| err(&e.to_string()))?;
+            labels::annotate(env, &mut output).await;
+            Ok(ok(&output))
+        }
+        "get_richlist" => {
+            let query: RichlistQuery = serde_json::from_value(args.clone()).map_err(invalid_args)?;
+            if let Some(limit) = query.limit {
+                client.limits().check_richlist(limit).map_err(chain_err)?;
+            }
+            let holders = client.get_richlist(rpc).await.map_err(chain_err)?;
+            let around_address = query.around_address.as_deref();
+            let around_balance = match around_address {
+                Some(addr) if !holders.iter().any(|h| h.address == addr) => client
+                    .get_account_balance(addr, rpc)
+                    .await
+                    .ok()
+                    .and_then(|b| b.balances.into_iter().find(|b| b.symbol == "AMA"))
+                    .map(|b| b.float),
+                _ => None,
+            };
+            let req = richlist::WindowRequest {
+                limit: query.limit.map(|v| v as usize),
+                offset: query.offset.map(|v| v as usize),
+                around_address,
+                around_balance,
+            };
+            let window = richlist::window(&holders, |h| h.address.as_str(), |h| h.float, &req);
+            match detail {
+                OutputDetail::Full => {
+                    let mut output = serde_json::to_value(&window).map_err(|e| err(&e.to_string()))?;
+                    labels::annotate(env, &mut output).await;
+                    Ok(ok(&output))
+                }
+                OutputDetail::Compact => Ok(ok(&CompactRichlist::from(&window))),
+            }
+        }
+        "get_nodes" => {
+            let nodes = client.get_peer_nodes(rpc).await.map_err(chain_err)?;
+            Ok(ok(&PeerList::new(nodes, args["filter"].as_str())))
+        }
+        "get_removed_validators" => {
+            let removed = client.get_removed_trainers(rpc).await.map_err(chain_err)?;
+            let current = client.get_validator_keys(rpc).await.map_err(chain_err)?;
+            Ok(ok(&removed.against(&current)))
         }
-        "get_richlist" => fetch_json(&format!("{rpc}/api/contract/richlist")).await,
-        "get_nodes" => fetch_json(&format!("{rpc}/api/peer/nodes")).await,
-        "get_removed_validators" => fetch_json(&format!("{rpc}/api/peer/removed_trainers")).await,
         _ => Err(err("unknown tool")),
     }
 }
 
-fn tools_list() -> Value {
-    json!({ "tools": [
-        tool("create_transaction", "Creates unsigned transaction for any contract call",
+fn tools_list(config: &ServerConfig) -> Vec<Value> {
+    let tools: Vec<Value> = vec![
+        tool("get_capabilities", json!({}), vec![]),
+        tool("list_tx_templates", json!({}), vec![]),
+        tool("create_transaction",
             json!({
                 "signer": str_prop(),
                 "contract": str_prop(),
@@ -505,60 +1027,495 @@ fn tools_list() -> Value {
                 "nonce": { "type": "number" }
             }),
             vec!["signer", "contract", "function", "args"]),
-        tool("submit_transaction", "Submits a signed transaction to the blockchain network",
-            json!({ "transaction": str_prop(), "signature": str_prop(), "network": str_prop() }), vec!["transaction", "signature"]),
-        tool("get_account_balance", "Queries the balance of an account across all supported assets",
-            json!({ "address": str_prop() }), vec!["address"]),
-        tool("get_chain_stats", "Retrieves current blockchain statistics", json!({}), vec![]),
-        tool("get_block_by_height", "Retrieves blockchain entries at a specific height",
-            json!({ "height": { "type": "number" } }), vec!["height"]),
-        tool("get_transaction", "Retrieves a specific transaction by its hash",
-            json!({ "tx_hash": str_prop() }), vec!["tx_hash"]),
-        tool("get_transaction_history", "Retrieves transaction history for a specific account",
-            json!({ "address": str_prop(), "limit": { "type": "number" }, "offset": { "type": "number" }, "sort": str_prop() }), vec!["address"]),
-        tool("get_validators", "Retrieves the list of current validator nodes", json!({}), vec![]),
-        tool("get_contract_state", "Retrieves a specific value from smart contract storage",
-            json!({ "contract_address": str_prop(), "key": str_prop() }), vec!["contract_address", "key"]),
-        tool("claim_testnet_ama", "Claims testnet AMA tokens to the specified address (once per 24 hours per IP)",
-            json!({ "address": str_prop() }), vec!["address"]),
-        tool("get_entry_tip", "Get the latest blockchain entry", json!({}), vec![]),
-        tool("get_entry_by_hash", "Get entry by hash", json!({ "hash": str_prop() }), vec!["hash"]),
-        tool("get_block_with_txs", "Get block at height with full transactions", json!({ "height": { "type": "number" } }), vec!["height"]),
-        tool("get_txs_in_entry", "Get all transactions in an entry", json!({ "entry_hash": str_prop() }), vec!["entry_hash"]),
-        tool("get_epoch_score", "Get validator mining scores (optionally for specific address)", json!({ "address": str_prop() }), vec![]),
-        tool("get_emission_address", "Get emission address for a validator", json!({ "address": str_prop() }), vec!["address"]),
-        tool("get_richlist", "Get top AMA token holders", json!({}), vec![]),
-        tool("get_nodes", "Get connected peer nodes", json!({}), vec![]),
-        tool("get_removed_validators", "Get validators removed this epoch", json!({}), vec![]),
-    ]})
-}
-
-fn tool(name: &str, desc: &str, props: Value, required: Vec<&str>) -> Value {
-    json!({ "name": name, "description": desc, "inputSchema": { "type": "object", "properties": props, "required": required }})
+        tool("validate_address", json!({ "address": str_prop() }), vec!["address"]),
+        tool("validate_transfer",
+            json!({
+                "source": str_prop(),
+                "destination": str_prop(),
+                "amount": { "type": ["integer", "number", "string"] },
+                "symbol": str_prop(),
+                "network": str_prop()
+            }),
+            vec!["source", "destination", "amount"]),
+        tool("check_blob_freshness", json!({ "transaction": str_prop(), "network": str_prop() }), vec!["transaction"]),
+        tool("decode_transaction", json!({ "blob": str_prop() }), vec!["blob"]),
+        tool("verify_signature",
+            json!({ "signing_payload": str_prop(), "signature": str_prop(), "public_key": str_prop() }), vec!["signing_payload", "signature", "public_key"]),
+        tool("rebuild_and_compare", json!({ "request": { "type": "object" }, "blob": str_prop() }), vec!["request", "blob"]),
+        tool("submit_transaction",
+            json!({ "transaction": str_prop(), "signature": str_prop(), "network": str_prop(), "idempotency_key": str_prop() }), vec!["transaction", "signature"]),
+        tool("get_account_balance", json!({ "address": str_prop(), "symbol": str_prop(), "network": str_prop() }), vec!["address"]),
+        tool("get_account_nonce", json!({ "address": str_prop(), "network": str_prop() }), vec!["address"]),
+        tool("get_pending_transactions", json!({ "address": str_prop(), "network": str_prop() }), vec![]),
+        tool("get_chain_stats", json!({ "network": str_prop() }), vec![]),
+        tool("check_node_health", json!({ "network": str_prop() }), vec![]),
+        tool("get_emission_stats", json!({ "blocks": { "type": "number" }, "since": { "type": ["integer", "string"] } }), vec![]),
+        tool("get_block_by_height", json!({ "height": { "type": "number" }, "detail": detail_prop() }), vec!["height"]),
+        tool("get_block_by_timestamp",
+            json!({
+                "timestamp": { "type": ["integer", "string"] },
+                "max_probes": { "type": "number" },
+                "network": str_prop()
+            }),
+            vec!["timestamp"]),
+        tool("get_transaction", json!({ "tx_hash": str_prop(), "network": str_prop() }), vec!["tx_hash"]),
+        tool("get_transaction_history",
+            json!({ "address": str_prop(), "limit": { "type": "number" }, "offset": { "type": "number" }, "sort": str_prop(), "detail": detail_prop(), "network": str_prop(),
+                "direction": { "type": "string", "enum": ["sent", "received"] }, "symbol": str_prop(), "counterparty": str_prop(),
+                "after_timestamp": { "type": "number" }, "before_timestamp": { "type": "number" } }),
+            vec!["address"]),
+        tool("get_full_transaction_history",
+            json!({ "address": str_prop(), "page_size": { "type": "number" }, "max_transactions": { "type": "number" }, "network": str_prop() }),
+            vec!["address"]),
+        tool("analyze_account",
+            json!({ "address": str_prop(), "page_size": { "type": "number" }, "max_transactions": { "type": "number" }, "network": str_prop() }),
+            vec!["address"]),
+        tool("estimate_fee",
+            json!({ "blob": str_prop(), "source": str_prop(), "destination": str_prop(), "amount": { "type": ["integer", "number", "string"] }, "symbol": str_prop() }),
+            vec![]),
+        tool("get_validators", json!({ "keys_only": { "type": "boolean" }, "network": str_prop() }), vec![]),
+        tool("get_contract_state", json!({ "contract_address": str_prop(), "key": str_prop(), "network": str_prop() }), vec!["contract_address", "key"]),
+        tool("list_built_transactions",
+            json!({ "signer": str_prop(), "status": { "type": "string", "enum": ["pending", "observed", "abandoned"] }, "limit": { "type": "number" } }),
+            vec!["signer"]),
+        tool("list_contract_keys",
+            json!({ "contract_address": str_prop(), "prefix": str_prop(), "limit": { "type": "number" }, "cursor": str_prop(), "network": str_prop() }),
+            vec!["contract_address"]),
+        tool("get_token_info", json!({ "symbol": str_prop(), "network": str_prop() }), vec![]),
+        tool("export_contract_state",
+            json!({ "contract_address": str_prop(), "prefix": str_prop(), "format": str_prop(), "limit": { "type": "number" }, "cursor": str_prop(), "network": str_prop() }),
+            vec!["contract_address"]),
+        tool("preview_contract_call",
+            json!({ "contract": str_prop(), "function": str_prop(), "args": { "type": "array" }, "network": str_prop() }),
+            vec!["contract", "function"]),
+        tool("raw_node_query", json!({ "path": str_prop(), "method": str_prop(), "network": str_prop() }), vec!["path"]),
+        faucet_tool(&config.faucet_tokens),
+        tool("create_watch",
+            json!({
+                "address": str_prop(),
+                "webhook_url": str_prop(),
+                "filter": {
+                    "type": "object",
+                    "properties": {
+                        "direction": { "type": "string", "enum": ["in", "out", "any"] },
+                        "symbol": str_prop(),
+                        "min_amount": str_prop()
+                    }
+                }
+            }),
+            vec!["address", "webhook_url"]),
+        tool("list_watches", json!({}), vec![]),
+        tool("delete_watch", json!({ "id": str_prop() }), vec!["id"]),
+        tool("add_address_label", json!({ "address": str_prop(), "label": str_prop() }), vec!["address", "label"]),
+        tool("export_address_labels", json!({}), vec![]),
+        tool("import_address_labels", json!({ "labels": { "type": "array" }, "replace": { "type": "boolean" } }), vec!["labels"]),
+        tool("get_entry_tip", json!({}), vec![]),
+        tool("get_entry_by_hash", json!({ "hash": str_prop(), "detail": detail_prop() }), vec!["hash"]),
+        tool("get_block_with_txs",
+            json!({ "height": { "type": "number" }, "tx_offset": { "type": "number" }, "tx_limit": { "type": "number" }, "detail": detail_prop() }), vec!["height"]),
+        tool("get_txs_in_entry", json!({ "entry_hash": str_prop(), "detail": detail_prop() }), vec!["entry_hash"]),
+        tool("get_epoch_score", json!({ "address": str_prop() }), vec![]),
+        tool("get_emission_address", json!({ "address": str_prop() }), vec!["address"]),
+        tool("get_richlist",
+            json!({ "limit": { "type": "number" }, "offset": { "type": "number" }, "around_address": str_prop(), "detail": detail_prop() }), vec![]),
+        tool("get_nodes", json!({ "filter": str_prop() }), vec![]),
+        tool("get_removed_validators", json!({}), vec![]),
+    ];
+    tools
+        .into_iter()
+        .filter(|t| config.tool_filter.allows(t["name"].as_str().unwrap_or("")))
+        .collect()
+}
+
+/// Tool descriptions and examples come from the shared catalog; the long form is served as a
+/// docs resource.
+fn tool(name: &str, props: Value, required: Vec<&str>) -> Value {
+    let doc = catalog::tool_doc(name);
+    json!({
+        "name": name,
+        "description": doc.map(|d| d.summary).unwrap_or_default(),
+        "inputSchema": { "type": "object", "properties": props, "required": required },
+        "examples": doc.map(|d| d.examples()).unwrap_or_default(),
+    })
+}
+
+/// claim_testnet_ama with the configured faucet symbols enumerated in its schema and
+/// description.
+fn faucet_tool(tokens: &FaucetTokens) -> Value {
+    let symbols = tokens.symbols();
+    let mut claim = tool(
+        "claim_testnet_ama",
+        json!({ "address": str_prop(), "symbol": { "type": "string", "enum": symbols } }),
+        vec!["address"],
+    );
+    claim["description"] = json!(format!(
+        "{} Claimable symbols: {} (default {}).",
+        claim["description"].as_str().unwrap_or_default(),
+        symbols.join(", "),
+        faucet::DEFAULT_FAUCET_SYMBOL
+    ));
+    claim
+}
+
+fn tools_page(config: &ServerConfig, params: &Value) -> std::result::Result<Value, Value> {
+    let page_size = params["pageSize"]
+        .as_u64()
+        .map(|n| n as usize)
+        .unwrap_or(catalog::DEFAULT_PAGE_SIZE);
+    let (tools, next_cursor) = catalog::page(&tools_list(config), params["cursor"].as_str(), page_size, TOOLS_VERSION)
+        .map_err(|e| json!({ "code": -32602, "message": e }))?;
+    let mut result = json!({ "tools": tools });
+    if let Some(cursor) = next_cursor {
+        result["nextCursor"] = json!(cursor);
+    }
+    Ok(result)
+}
+
+/// The worker's optional features depend on which secrets are bound.
+fn deployment(env: &Env) -> catalog::Deployment {
+    catalog::Deployment::Worker {
+        faucet_key: env.secret(mint::FAUCET_KEY).is_ok(),
+        watch_tokens: env.secret(watch::WATCH_TOKENS).is_ok(),
+        label_tokens: env.secret(labels::LABEL_TOKENS).is_ok(),
+    }
+}
+
+fn read_tool_doc(params: &Value) -> std::result::Result<Value, Value> {
+    let uri = params["uri"].as_str().unwrap_or("");
+    if uri == catalog::SIGNING_WORKFLOW_URI {
+        let doc = signing::workflow_doc().map_err(err)?;
+        return Ok(json!({ "contents": [{ "uri": uri, "mimeType": "text/plain", "text": doc }] }));
+    }
+    if uri == catalog::ERROR_RECOVERY_URI {
+        return Ok(json!({ "contents": [{ "uri": uri, "mimeType": "text/plain", "text": recovery::doc() }] }));
+    }
+    if let Some(name) = uri.strip_prefix(templates::TEMPLATES_URI) {
+        let template = templates::template(name)
+            .ok_or_else(|| json!({ "code": -32602, "message": format!("Unknown resource URI: {}", uri) }))?;
+        return Ok(json!({ "contents": [{ "uri": uri, "mimeType": "application/json", "text": template.describe().to_string() }] }));
+    }
+    let doc = uri
+        .strip_prefix(catalog::TOOL_DOCS_URI)
+        .and_then(catalog::tool_doc)
+        .ok_or_else(|| json!({ "code": -32602, "message": format!("Unknown resource URI: {}", uri) }))?;
+    Ok(json!({ "contents": [{ "uri": uri, "mimeType": "text/plain", "text": doc.render() }] }))
 }
 
 fn str_prop() -> Value {
     json!({ "type": "string" })
 }
+fn detail_prop() -> Value {
+    json!({ "type": "string", "enum": ["full", "compact"] })
+}
 fn err(msg: &str) -> Value {
     json!({ "code": -32603, "message": msg })
 }
+/// Arguments that do not deserialize into the tool's query, as stdio reports them.
+fn invalid_args(e: serde_json::Error) -> Value {
+    json!({ "code": -32602, "message": "invalid_arguments", "data": { "message": e.to_string() } })
+}
+/// `data` with the recovery hint for the native error `code` it corresponds to.
+fn with_recovery(code: &str, data: Value) -> Value {
+    let mut data = Some(data);
+    if let Some(hint) = recovery::for_code(code, data.as_ref()) {
+        recovery::attach(&mut data, &hint);
+    }
+    data.unwrap_or_default()
+}
+/// A node or builder failure, with the recovery hint for its kind.
+fn chain_err(e: BlockchainError) -> Value {
+    let code = match e {
+        BlockchainError::LimitExceeded { .. }
+        | BlockchainError::ValidationFailed(_)
+        | BlockchainError::AccountNotFound { .. } => -32602,
+        BlockchainError::NotFound { .. } => -32002,
+        _ => -32603,
+    };
+    json!({ "code": code, "message": e.to_string(), "data": { "recovery": recovery::for_error(&e) } })
+}
 fn ok<T: serde::Serialize>(data: &T) -> Value {
     json!({ "content": [{ "type": "text", "text": serde_json::to_string_pretty(data).unwrap() }] })
 }
 
-async fn fetch_json(url: &str) -> std::result::Result<Value, Value> {
-    let mut resp = worker::Fetch::Url(worker::Url::parse(url).map_err(|e| err(&e.to_string()))?)
-        .send().await.map_err(|e| err(&e.to_string()))?;
-    let json: Value = serde_json::from_str(&resp.text().await.map_err(|e| err(&e.to_string()))?)
-        .map_err(|e| err(&e.to_string()))?;
-    Ok(ok(&json))
+/// KV namespace holding accepted submits by idempotency key. Without it submits are never deduplicated.
+const IDEMPOTENCY_KV: &str = "MCP_IDEMPOTENCY";
+
+/// Who is calling, for scoping idempotency keys and session budgets: the MCP session when
+/// the client sends one, otherwise the client address.
+fn submit_scope(client_ip: &Option<String>, headers: &HashMap<String, String>) -> Option<String> {
+    headers
+        .get("mcp-session-id")
+        .map(|session| format!("session:{}", session))
+        .or_else(|| client_ip.as_ref().map(|ip| format!("ip:{}", ip)))
+}
+
+/// The request budget recorded for `scope`, fresh for scopes not seen yet. `None` when
+/// the session_budgets table is missing or unreadable, in which case no budget is enforced.
+async fn load_budget(env: &Env, scope: &str) -> std::result::Result<Option<budget::SessionBudget>, String> {
+    let db = env.d1("MCP_DATABASE").map_err(|e| e.to_string())?;
+    let stored: Option<String> = db
+        .prepare("SELECT budget FROM session_budgets WHERE scope = ?1")
+        .bind(&[scope.into()])
+        .map_err(|e| e.to_string())?
+        .first(Some("budget"))
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(Some(stored.and_then(|b| serde_json::from_str(&b).ok()).unwrap_or_default()))
+}
+
+async fn store_budget(env: &Env, scope: &str, session_budget: &budget::SessionBudget) -> std::result::Result<(), String> {
+    let db = env.d1("MCP_DATABASE").map_err(|e| e.to_string())?;
+    let stored = serde_json::to_string(session_budget).map_err(|e| e.to_string())?;
+    db.prepare("INSERT OR REPLACE INTO session_budgets (scope, budget, updated_at) VALUES (?1, ?2, ?3)")
+        .bind(&[scope.into(), stored.into(), ((Date::now().as_millis() / 1000) as f64).into()])
+        .map_err(|e| e.to_string())?
+        .run()
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Submits `tx`, or replays the recorded result when the same scope already submitted it
+/// under the same idempotency key. Only accepted submits are recorded, and a key reused for a
+/// different blob is refused.
+async fn submit_idempotent(
+    client: &BlockchainClient, env: &Env, scope: Option<String>, tx: SignedTransaction, url: &str,
+) -> std::result::Result<Value, Value> {
+    let store = env.kv(IDEMPOTENCY_KV).ok();
+    let key = match (&store, scope, tx.idempotency_key.as_deref()) {
+        (Some(_), Some(scope), Some(key)) => Some(idempotency::scoped_key(&scope, tx.network.as_deref(), key)),
+        _ => None,
+    };
+    if tx.idempotency_key.is_some() && key.is_none() {
+        console_warn!("{} is not bound or the caller is unknown, submitting without deduplication", IDEMPOTENCY_KV);
+    }
+
+    if let (Some(store), Some(key)) = (&store, &key) {
+        if let Ok(Some(record)) = store.get(key).json::<idempotency::SubmitRecord>().await {
+            let recorded = record.replay(&tx.transaction).map_err(|reused| {
+                json!({ "code": -32602, "message": "idempotency_key_reused", "data": { "message": reused } })
+            })?;
+            let mut replayed = serde_json::to_value(recorded).unwrap_or_default();
+            replayed["replayed"] = json!(true);
+            return Ok(ok(&replayed));
+        }
+    }
+
+    let blob_digest = idempotency::blob_digest(&tx.transaction);
+    let response = client
+        .submit_signed_transaction(tx, url)
+        .await
+        .map_err(chain_err)?;
+    if let (Some(store), Some(key)) = (&store, &key) {
+        if idempotency::should_record(&response) {
+            let record = idempotency::SubmitRecord { blob_digest, response: response.clone() };
+            let put = match store.put(key, &record) {
+                Ok(put) => put.expiration_ttl(idempotency::IDEMPOTENCY_TTL.as_secs()).execute().await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = put {
+                console_warn!("failed to record idempotency key: {:?}", e);
+            }
+        }
+    }
+    Ok(ok(&response))
+}
+
+async fn fetch_json(client: &BlockchainClient, url: &str) -> std::result::Result<Value, Value> {
+    fetch_raw(client, url).await.map(|json| ok(&json))
+}
+
+async fn fetch_raw(client: &BlockchainClient, url: &str) -> std::result::Result<Value, Value> {
+    client.get_raw(url).await.map_err(chain_err)
+}
+
+#[event(scheduled)]
+pub async fn scheduled(_event: ScheduledEvent, env: Env, _ctx: ScheduleContext) {
+    if let Err(e) = record_stats_snapshot(&env).await {
+        console_error!("failed to record chain stats snapshot: {}", e);
+    }
+    if let Err(e) = record_tip(&env).await {
+        console_error!("failed to record chain tip: {}", e);
+    }
+    if let Err(e) = run_watches(&env).await {
+        console_error!("failed to dispatch watches: {}", e);
+    }
+}
+
+async fn run_watches(env: &Env) -> std::result::Result<(), String> {
+    let rpc = env
+        .var("BLOCKCHAIN_URL")
+        .map(|v| v.to_string())
+        .unwrap_or_else(|_| "https://nodes.amadeus.bot".to_string());
+    let client = upstream_client(env, &rpc).map_err(|e| e.to_string())?;
+    watch::dispatch_watches(env, &client, &rpc).await
+}
+
+#[derive(serde::Deserialize)]
+struct TipRow {
+    height: f64,
+    hash: String,
+}
+
+/// Records the mainnet tip hash, first checking that the recently recorded tips are still
+/// on the chain. A mismatch is stored in chain_reorgs and the orphaned tips dropped.
+async fn record_tip(env: &Env) -> std::result::Result<(), String> {
+    let rpc = env
+        .var("BLOCKCHAIN_URL")
+        .map(|v| v.to_string())
+        .unwrap_or_else(|_| "https://nodes.amadeus.bot".to_string());
+    let client = upstream_client(env, &rpc).map_err(|e| e.to_string())?;
+    let db = env.d1("MCP_DATABASE").map_err(|e| e.to_string())?;
+    let now = Date::now().as_millis() / 1000;
+
+    let rows: Vec<TipRow> = db
+        .prepare("SELECT height, hash FROM chain_tips ORDER BY height DESC LIMIT ?1")
+        .bind(&[(reorg::MAX_FORK_WALK as f64).into()])
+        .map_err(|e| e.to_string())?
+        .all()
+        .await
+        .map_err(|e| e.to_string())?
+        .results()
+        .map_err(|e| e.to_string())?;
+    let mut history = reorg::TipHistory::default();
+    for row in rows {
+        history.record(row.height as u64, row.hash);
+    }
+
+    let mut canonical = std::collections::BTreeMap::new();
+    for height in history.recent_heights() {
+        let Ok(body) = client.get_raw(&client.api_url(rpc, &format!("chain/height/{height}"))).await else {
+            break;
+        };
+        let hashes: std::collections::BTreeSet<String> = body["entries"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|e| e["hash"].as_str().map(String::from))
+            .collect();
+        let still_canonical = history.hash_at(height).is_some_and(|h| hashes.contains(h));
+        canonical.insert(height, hashes);
+        if still_canonical {
+            break;
+        }
+    }
+    if let Some(event) = history.fork_height(&canonical).and_then(|fork| history.rewind(fork, now)) {
+        console_warn!("chain reorg detected at height {} (depth {})", event.fork_height, event.depth);
+        db.prepare("INSERT INTO chain_reorgs (fork_height, depth, orphaned_hash, detected_at) VALUES (?1, ?2, ?3, ?4)")
+            .bind(&[
+                (event.fork_height as f64).into(),
+                (event.depth as f64).into(),
+                event.orphaned_hash.clone().into(),
+                (event.detected_at as f64).into(),
+            ])
+            .map_err(|e| e.to_string())?
+            .run()
+            .await
+            .map_err(|e| e.to_string())?;
+        db.prepare("DELETE FROM chain_tips WHERE height >= ?1")
+            .bind(&[(event.fork_height as f64).into()])
+            .map_err(|e| e.to_string())?
+            .run()
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    let tip = client.get_raw(&client.api_url(rpc, "chain/tip")).await.map_err(|e| e.to_string())?;
+    let (Some(height), Some(hash)) = (tip["entry"]["header"]["height"].as_u64(), tip["entry"]["hash"].as_str()) else {
+        return Err("tip entry missing height or hash".to_string());
+    };
+    db.prepare("INSERT OR REPLACE INTO chain_tips (height, hash, recorded_at) VALUES (?1, ?2, ?3)")
+        .bind(&[(height as f64).into(), hash.into(), (now as f64).into()])
+        .map_err(|e| e.to_string())?
+        .run()
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Stores the current mainnet chain stats so get_emission_stats can diff across windows.
+async fn record_stats_snapshot(env: &Env) -> std::result::Result<(), String> {
+    let rpc = env
+        .var("BLOCKCHAIN_URL")
+        .map(|v| v.to_string())
+        .unwrap_or_else(|_| "https://nodes.amadeus.bot".to_string());
+    let client = upstream_client(env, &rpc).map_err(|e| e.to_string())?;
+    let stats = client.get_chain_stats(&rpc).await.map_err(|e| e.to_string())?;
+    let snapshot = emission::StatsSnapshot::from_stats(&stats, Date::now().as_millis() / 1000)
+        .ok_or("chain stats missing burned/circulating")?;
+
+    let db = env.d1("MCP_DATABASE").map_err(|e| e.to_string())?;
+    db.prepare("INSERT OR IGNORE INTO chain_stats_snapshots (height, burned, circulating, recorded_at) VALUES (?1, ?2, ?3, ?4)")
+        .bind(&[
+            (snapshot.height as f64).into(),
+            snapshot.burned.into(),
+            snapshot.circulating.into(),
+            (snapshot.recorded_at as f64).into(),
+        ])
+        .map_err(|e| e.to_string())?
+        .run()
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// The snapshots bracketing `start_height`: the latest at or before it and the earliest after it.
+async fn stats_snapshots_around(
+    env: &Env,
+    start_height: u64,
+) -> std::result::Result<Vec<emission::StatsSnapshot>, Value> {
+    let db = env.d1("MCP_DATABASE").map_err(|e| err(&e.to_string()))?;
+    let mut snapshots = Vec::new();
+    for sql in [
+        "SELECT height, burned, circulating, recorded_at FROM chain_stats_snapshots WHERE height <= ?1 ORDER BY height DESC LIMIT 1",
+        "SELECT height, burned, circulating, recorded_at FROM chain_stats_snapshots WHERE height > ?1 ORDER BY height ASC LIMIT 1",
+    ] {
+        let snapshot: Option<emission::StatsSnapshot> = db
+            .prepare(sql)
+            .bind(&[(start_height as f64).into()])
+            .map_err(|e| err(&e.to_string()))?
+            .first(None)
+            .await
+            .map_err(|e| err(&e.to_string()))?;
+        snapshots.extend(snapshot);
+    }
+    Ok(snapshots)
+}
+
+/// Faucet claims in the `faucet_claims` table, keyed by IP and symbol.
+struct D1Claims<'a> {
+    db: &'a D1Database,
 }
 
-const CLAIM_COOLDOWN_SECS: f64 = 86400.0;
+impl ClaimLedger for D1Claims<'_> {
+    async fn last_claim(&self, ip: &str, symbol: &str) -> std::result::Result<Option<f64>, String> {
+        self.db
+            .prepare("SELECT claimed_at FROM faucet_claims WHERE ip = ?1 AND symbol = ?2")
+            .bind(&[ip.into(), symbol.into()])
+            .map_err(|e| e.to_string())?
+            .first(Some("claimed_at"))
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    async fn record(&self, ip: &str, symbol: &str, address: &str, now: f64) -> std::result::Result<(), String> {
+        self.db
+            .prepare(
+                "INSERT INTO faucet_claims (ip, symbol, address, claimed_at) VALUES (?1, ?2, ?3, ?4) \
+                 ON CONFLICT (ip, symbol) DO UPDATE SET address = excluded.address, claimed_at = excluded.claimed_at",
+            )
+            .bind(&[ip.into(), symbol.into(), address.into(), now.into()])
+            .map_err(|e| e.to_string())?
+            .run()
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
 
 async fn claim_testnet_ama(
     env: &Env,
+    config: &ServerConfig,
     client_ip: Option<String>,
     headers: HashMap<String, String>,
     args: &Value,
@@ -573,6 +1530,11 @@ async fn claim_testnet_ama(
     let address = args["address"]
         .as_str()
         .ok_or_else(|| err("missing address"))?;
+    // Claims go to wallets, so the address must also be a key that can sign
+    let address = address::Address::parse(address)
+        .and_then(|a| a.check_point().map(|_| a))
+        .map_err(|e| err(&format!("invalid address: {}", e)))?;
+    let (symbol, whole) = config.faucet_tokens.resolve(args["symbol"].as_str()).map_err(|e| err(&e))?;
     let now = (Date::now().as_millis() / 1000) as f64;
 
     let db = env.d1("MCP_DATABASE").map_err(|e| err(&e.to_string()))?;
@@ -585,46 +1547,17 @@ async fn claim_testnet_ama(
         .map_err(|e| err(&e.to_string()))?
         .run()
         .await;
-    let existing: Option<f64> = db
-        .prepare("SELECT claimed_at FROM faucet_claims WHERE ip = ?1")
-        .bind(&[ip.clone().into()])
-        .map_err(|e| err(&e.to_string()))?
-        .first(Some("claimed_at"))
-        .await
-        .map_err(|e| err(&e.to_string()))?;
-
-    if let Some(claimed_at) = existing {
-        let elapsed = now - claimed_at;
-        if elapsed < CLAIM_COOLDOWN_SECS {
-            let remaining = (CLAIM_COOLDOWN_SECS - elapsed) as i64;
-            let hours = remaining / 3600;
-            let minutes = (remaining % 3600) / 60;
-            return Err(err(&format!(
-                "can only claim once per day, wait {}h {}m",
-                hours, minutes
-            )));
-        }
-    }
+    let claims = D1Claims { db: &db };
+    faucet::check_cooldown(&claims, &ip, symbol, now).await.map_err(|e| err(&e))?;
 
-    let tx_hash = mint::transfer(env, address).await?;
+    let (tx_hash, clock_skew_warning) = mint::transfer(env, &address, symbol, whole).await?;
+    claims.record(&ip, symbol, address.as_str(), now).await.map_err(|e| err(&e))?;
 
-    if existing.is_some() {
-        db.prepare("UPDATE faucet_claims SET claimed_at = ?1, address = ?2 WHERE ip = ?3")
-            .bind(&[now.into(), address.into(), ip.into()])
-            .map_err(|e| err(&e.to_string()))?
-            .run()
-            .await
-            .map_err(|e| err(&e.to_string()))?;
-    } else {
-        db.prepare("INSERT INTO faucet_claims (ip, address, claimed_at) VALUES (?1, ?2, ?3)")
-            .bind(&[ip.into(), address.into(), now.into()])
-            .map_err(|e| err(&e.to_string()))?
-            .run()
-            .await
-            .map_err(|e| err(&e.to_string()))?;
+    let mut output = json!({ "status": "success", "symbol": symbol, "amount": whole, "tx_hash": tx_hash });
+    if let Some(warning) = clock_skew_warning {
+        output["clock_skew_warning"] = json!(warning);
     }
-
-    Ok(ok(&json!({ "status": "success", "tx_hash": tx_hash })))
+    Ok(ok(&output))
 }
 
 }
@@ -0,0 +1,174 @@
+use super::worker_handlers::{blockchain_url, handle_mcp_request, upstream_client};
+use crate::blockchain::session::{
+    parse_last_event_id, to_sse, SessionEvent, SessionState, LAST_EVENT_ID_HEADER, SESSION_HEADER, SESSION_TTL_SECS,
+};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Duration;
+use worker::*;
+
+/// Durable Object namespace holding one object per MCP session. Without it the worker
+/// answers every POST statelessly, as before sessions existed.
+pub const SESSIONS_BINDING: &str = "MCP_SESSIONS";
+const STATE_KEY: &str = "session";
+
+fn now() -> u64 {
+    Date::now().as_millis() / 1000
+}
+
+/// Sends an MCP request to its session object: requests carrying `Mcp-Session-Id`, and
+/// initialize requests, which are given a new session id. `None` when sessions aren't
+/// bound or the request isn't part of one, so the caller falls back to stateless handling.
+pub async fn route(
+    env: &Env, method: Method, headers: &HashMap<String, String>, body: Option<&Value>,
+) -> Option<Result<Response>> {
+    let namespace = env.durable_object(SESSIONS_BINDING).ok()?;
+    let session_id = match headers.get(SESSION_HEADER) {
+        Some(id) => id.clone(),
+        None if method == Method::Post && body.is_some_and(|b| b["method"] == "initialize") => new_session_id().ok()?,
+        None => return None,
+    };
+    Some(forward(&namespace, &session_id, method, headers, body).await)
+}
+
+async fn forward(
+    namespace: &ObjectNamespace, session_id: &str, method: Method, headers: &HashMap<String, String>,
+    body: Option<&Value>,
+) -> Result<Response> {
+    let forwarded = Headers::new();
+    // The body is re-serialized, so its length may differ from the original.
+    for (name, value) in headers.iter().filter(|(name, _)| name.as_str() != "content-length") {
+        forwarded.set(name, value)?;
+    }
+    forwarded.set(SESSION_HEADER, session_id)?;
+    let mut init = RequestInit::new();
+    init.with_method(method).with_headers(forwarded);
+    if let Some(body) = body {
+        init.with_body(Some(body.to_string().into()));
+    }
+    let request = Request::new_with_init("https://session/mcp", &init)?;
+    let stub = namespace.id_from_name(session_id)?.get_stub()?;
+    stub.fetch_with_request(request).await
+}
+
+fn new_session_id() -> Result<String> {
+    let mut id = [0u8; 16];
+    getrandom::getrandom(&mut id).map_err(|e| Error::RustError(e.to_string()))?;
+    Ok(hex::encode(id))
+}
+
+/// One MCP session. POSTs are answered here so every response gets the session's next
+/// event id and is buffered for `Last-Event-ID` replay; the queueing and replay rules are
+/// in `blockchain::session`. An alarm drops the storage once the session has been idle
+/// for `SESSION_TTL_SECS`.
+#[durable_object]
+pub struct McpSession {
+    state: State,
+    env: Env,
+}
+
+#[durable_object]
+impl DurableObject for McpSession {
+    fn new(state: State, env: Env) -> Self {
+        Self { state, env }
+    }
+
+    async fn fetch(&mut self, req: Request) -> Result<Response> {
+        // Set here rather than by the caller: headers of a fetched response are immutable.
+        let session_id = req.headers().get(SESSION_HEADER)?.unwrap_or_default();
+        let mut response = match req.method() {
+            Method::Post => self.post(req).await,
+            Method::Get => self.reconnect(req).await,
+            Method::Delete => {
+                self.state.storage().delete_all().await?;
+                Ok(Response::empty()?.with_status(204))
+            }
+            _ => Response::error("method not allowed", 405),
+        }?;
+        response.headers_mut().set(SESSION_HEADER, &session_id)?;
+        Ok(response)
+    }
+
+    async fn alarm(&mut self) -> Result<Response> {
+        match self.load().await {
+            Some(session) if !session.is_expired(now(), SESSION_TTL_SECS) => {
+                let remaining = session.expires_at(SESSION_TTL_SECS).saturating_sub(now());
+                self.state.storage().set_alarm(Duration::from_secs(remaining.max(1))).await?;
+            }
+            _ => self.state.storage().delete_all().await?,
+        }
+        Response::ok("")
+    }
+}
+
+impl McpSession {
+    /// The stored session, `None` when it was never opened or has expired.
+    async fn load(&self) -> Option<SessionState> {
+        self.state.storage().get::<SessionState>(STATE_KEY).await.ok()
+    }
+
+    async fn live(&self) -> Option<SessionState> {
+        self.load().await.filter(|s| s.is_open() && !s.is_expired(now(), SESSION_TTL_SECS))
+    }
+
+    async fn save(&self, session: &SessionState) -> Result<()> {
+        let mut storage = self.state.storage();
+        storage.put(STATE_KEY, session).await?;
+        storage.set_alarm(Duration::from_secs(SESSION_TTL_SECS)).await
+    }
+
+    async fn post(&mut self, mut req: Request) -> Result<Response> {
+        let headers: HashMap<String, String> = req.headers().entries().collect();
+        let body: Value = req.json().await?;
+        let initialize = body["method"] == "initialize";
+        if !initialize && self.live().await.is_none() {
+            return Response::error("unknown or expired session; initialize a new one", 404);
+        }
+
+        let rpc = blockchain_url(&self.env);
+        let client = upstream_client(&self.env, &rpc).map_err(|e| format!("failed to create client: {}", e))?;
+        let client_ip = headers.get("cf-connecting-ip").cloned();
+        let wants_stream = headers.get("accept").is_some_and(|a| a.contains("text/event-stream"));
+        let response = handle_mcp_request(&client, &self.env, &rpc, client_ip, headers, body).await;
+
+        // Reload after the upstream call: other requests on this session may have pushed
+        // events while it was in flight.
+        let mut session = match (initialize, response["result"]["protocolVersion"].as_str()) {
+            (true, Some(version)) => SessionState::open(version, now()),
+            (true, None) => return Response::from_json(&response),
+            (false, _) => match self.live().await {
+                Some(session) => session,
+                None => return Response::error("session expired while the request was in flight", 404),
+            },
+        };
+        session.touch(now());
+        let id = session.push(response.clone());
+        self.save(&session).await?;
+
+        if wants_stream {
+            stream(&[SessionEvent { id, message: response }])
+        } else {
+            Response::from_json(&response)
+        }
+    }
+
+    /// A GET stream: replays the events after `Last-Event-ID`, then closes.
+    async fn reconnect(&mut self, req: Request) -> Result<Response> {
+        let Some(session) = self.live().await else {
+            return Response::error("unknown or expired session; initialize a new one", 404);
+        };
+        let last_event_id = parse_last_event_id(req.headers().get(LAST_EVENT_ID_HEADER)?.as_deref());
+        match session.replay_after(last_event_id) {
+            Ok(events) => stream(events),
+            Err(e) => Response::error(e.to_string(), 409),
+        }
+    }
+}
+
+fn stream(events: &[SessionEvent]) -> Result<Response> {
+    let mut response = Response::ok(to_sse(events))?;
+    let headers = response.headers_mut();
+    headers.set("content-type", "text/event-stream")?;
+    headers.set("cache-control", "no-cache")?;
+    Ok(response)
+}
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
 
 mod args_serde {
     use serde::{Deserialize, Deserializer, Serialize, Serializer};
@@ -40,15 +41,90 @@ pub struct Tx {
 pub struct UnsignedTx {
     pub tx_blob: Vec<u8>,
     pub signing_hash: [u8; 32],
+    pub nonce: i128,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-struct TxU {
+pub struct TxU {
     #[serde(with = "serde_bytes")]
-    hash: Vec<u8>,
+    pub hash: Vec<u8>,
     #[serde(with = "serde_bytes")]
-    signature: Vec<u8>,
-    tx: Tx,
+    pub signature: Vec<u8>,
+    pub tx: Tx,
+}
+
+/// Domain separation tag for transaction signatures.
+pub const SIGNING_DST: &[u8] = b"AMADEUS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_TX_";
+
+/// A BLS secret key derived from a 64-byte seed together with its compressed G1 public key.
+pub struct SigningKey {
+    sk: blst::min_pk::SecretKey,
+    pub public_key: Vec<u8>,
+}
+
+impl SigningKey {
+    pub fn from_seed(sk_bytes: &[u8]) -> Result<Self, &'static str> {
+        use bls12_381::Scalar;
+        use group::Curve;
+
+        if sk_bytes.len() != 64 {
+            return Err("secret key must be 64 bytes");
+        }
+        let bytes_64: Zeroizing<[u8; 64]> =
+            Zeroizing::new(sk_bytes.try_into().map_err(|_| "invalid sk length")?);
+        let sk_scalar = Scalar::from_bytes_wide(&bytes_64);
+        let public_key = (bls12_381::G1Projective::generator() * sk_scalar).to_affine().to_compressed().to_vec();
+
+        let mut sk_be = Zeroizing::new(sk_scalar.to_bytes());
+        sk_be.reverse();
+        let sk = blst::min_pk::SecretKey::from_bytes(sk_be.as_slice()).map_err(|_| "invalid secret key")?;
+        Ok(Self { sk, public_key })
+    }
+
+    pub fn sign(&self, hash: &[u8]) -> Vec<u8> {
+        self.sk.sign(hash, SIGNING_DST, &[]).to_bytes().to_vec()
+    }
+}
+
+/// Which part of a BLS signature check failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureError {
+    BadPublicKey,
+    BadSignatureEncoding,
+    NotInSubgroup,
+    Mismatch,
+}
+
+impl SignatureError {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::BadPublicKey => "bad_public_key",
+            Self::BadSignatureEncoding => "bad_signature_encoding",
+            Self::NotInSubgroup => "signature_not_in_subgroup",
+            Self::Mismatch => "signature_mismatch",
+        }
+    }
+}
+
+/// Verifies a signature over `message` under the given domain separation tag.
+pub fn verify_with_dst(public_key: &[u8], message: &[u8], signature: &[u8], dst: &[u8]) -> Result<(), SignatureError> {
+    let pk = blst::min_pk::PublicKey::key_validate(public_key).map_err(|_| SignatureError::BadPublicKey)?;
+    let sig = blst::min_pk::Signature::from_bytes(signature).map_err(|_| SignatureError::BadSignatureEncoding)?;
+    sig.validate(true).map_err(|_| SignatureError::NotInSubgroup)?;
+    match sig.verify(false, message, dst, &[], &pk, false) {
+        blst::BLST_ERROR::BLST_SUCCESS => Ok(()),
+        _ => Err(SignatureError::Mismatch),
+    }
+}
+
+/// Verifies a transaction signature over its signing hash.
+pub fn verify_signature(public_key: &[u8], hash: &[u8], signature: &[u8]) -> Result<(), SignatureError> {
+    verify_with_dst(public_key, hash, signature, SIGNING_DST)
+}
+
+/// Decodes a packed signed transaction.
+pub fn decode_signed(packed: &[u8]) -> Result<TxU, &'static str> {
+    vecpak::from_slice(packed).map_err(|_| "failed to decode txu")
 }
 
 pub struct FinalizedTx {
@@ -71,6 +147,16 @@ pub fn finalize_transaction(tx_blob_b58: &str, signature_b58: &str) -> Result<Fi
     Ok(FinalizedTx { packed, hash })
 }
 
+/// Nonce from the clock: the current time in nanoseconds.
+pub fn clock_nonce() -> i128 {
+    #[cfg(target_arch = "wasm32")]
+    { js_sys::Date::now() as i128 * 1_000_000 }
+    #[cfg(not(target_arch = "wasm32"))]
+    { std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_nanos() as i128 }
+}
+
+/// Encodes an unsigned call. With an explicit `nonce` the blob and hash depend only on
+/// the inputs; without one the nonce is taken from the clock.
 pub fn build_unsigned(
     signer_pk: &[u8],
     contract: &str,
@@ -80,12 +166,7 @@ pub fn build_unsigned(
     attached_amount: Option<&[u8]>,
     nonce: Option<i64>,
 ) -> Result<UnsignedTx, &'static str> {
-    let nonce_val = nonce.map(|n| n as i128).unwrap_or_else(|| {
-        #[cfg(target_arch = "wasm32")]
-        { js_sys::Date::now() as i128 * 1_000_000 }
-        #[cfg(not(target_arch = "wasm32"))]
-        { std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_nanos() as i128 }
-    });
+    let nonce_val = nonce.map(|n| n as i128).unwrap_or_else(clock_nonce);
 
     let action = TxAction {
         op: "call".to_string(),
@@ -108,33 +189,31 @@ pub fn build_unsigned(
     Ok(UnsignedTx {
         tx_blob: tx_encoded,
         signing_hash: hash,
+        nonce: nonce_val,
     })
 }
 
-#[cfg(target_arch = "wasm32")]
+/// A signed Coin.transfer ready to submit.
 pub struct BuiltTx {
+    /// The unsigned `Tx`, as create_transaction would return it
+    pub blob: Vec<u8>,
+    /// The signed `TxU`
     pub packed: Vec<u8>,
     pub hash: [u8; 32],
+    pub nonce: i128,
 }
 
-#[cfg(target_arch = "wasm32")]
+/// Builds and signs a Coin.transfer of `amount` atoms from the key derived from `sk_bytes`.
 pub fn build_transfer_tx(
     sk_bytes: &[u8],
     receiver: &[u8],
     symbol: &str,
     amount: i128,
+    nonce: Option<i128>,
 ) -> Result<BuiltTx, &'static str> {
-    use bls12_381::Scalar;
-    use group::Curve;
-
-    if sk_bytes.len() != 64 {
-        return Err("secret key must be 64 bytes");
-    }
-    let bytes_64: [u8; 64] = sk_bytes.try_into().map_err(|_| "invalid sk length")?;
-    let sk_scalar = Scalar::from_bytes_wide(&bytes_64);
-    let pk = (bls12_381::G1Projective::generator() * sk_scalar).to_affine().to_compressed().to_vec();
+    let key = SigningKey::from_seed(sk_bytes)?;
 
-    let nonce = js_sys::Date::now() as i128 * 1_000_000;
+    let nonce = nonce.unwrap_or_else(clock_nonce);
     let action = TxAction {
         op: "call".to_string(),
         contract: "Coin".to_string(),
@@ -144,16 +223,13 @@ pub fn build_transfer_tx(
         attached_amount: None,
     };
 
-    let tx = Tx { signer: pk.clone(), nonce, action };
+    let tx = Tx { signer: key.public_key.clone(), nonce, action };
     let tx_encoded = vecpak::to_vec(&tx).map_err(|_| "failed to encode tx")?;
     let hash: [u8; 32] = Sha256::digest(&tx_encoded).into();
 
-    let mut sk_be = sk_scalar.to_bytes();
-    sk_be.reverse();
-    let sk = blst::min_pk::SecretKey::from_bytes(&sk_be).map_err(|_| "invalid secret key")?;
-    let signature = sk.sign(&hash, b"AMADEUS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_TX_", &[]).to_bytes().to_vec();
+    let signature = key.sign(&hash);
 
     let txu = TxU { hash: hash.to_vec(), signature, tx };
     let packed = vecpak::to_vec(&txu).map_err(|_| "failed to encode txu")?;
-    Ok(BuiltTx { packed, hash })
+    Ok(BuiltTx { blob: tx_encoded, packed, hash, nonce })
 }
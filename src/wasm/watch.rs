@@ -0,0 +1,242 @@
+use crate::blockchain::watch::{self, CreateWatchRequest, HistorySource, Watch, WebhookSink, MAX_WATCHES_PER_OWNER};
+use crate::blockchain::{is_valid_address, BlockchainClient, Transaction};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::time::Duration;
+use worker::{console_error, console_warn, Date, Delay, Env, Fetch, Headers, Method, Request, RequestInit, RequestRedirect};
+
+/// Secret holding the comma-separated bearer tokens allowed to manage watches.
+pub const WATCH_TOKENS: &str = "MCP_WATCH_TOKENS";
+/// Watches dispatched per scheduled run, oldest first.
+const WATCHES_PER_RUN: u32 = 200;
+const WEBHOOK_BACKOFF: Duration = Duration::from_millis(500);
+
+#[derive(serde::Deserialize)]
+struct WatchRow {
+    id: String,
+    owner: String,
+    address: String,
+    webhook_url: String,
+    filter: String,
+    secret: String,
+    cursor_height: f64,
+    created_at: f64,
+}
+
+impl WatchRow {
+    fn into_watch(self) -> Option<Watch> {
+        Some(Watch {
+            filter: serde_json::from_str(&self.filter).ok()?,
+            id: self.id,
+            owner: self.owner,
+            address: self.address,
+            webhook_url: self.webhook_url,
+            secret: self.secret,
+            cursor_height: self.cursor_height as u64,
+            created_at: self.created_at as u64,
+        })
+    }
+}
+
+/// The owner for the request's bearer token, if the token is one of `MCP_WATCH_TOKENS`.
+fn authorize(env: &Env, headers: &HashMap<String, String>) -> Result<String, Value> {
+    let allowed = env
+        .secret(WATCH_TOKENS)
+        .map(|v| v.to_string())
+        .map_err(|_| err("watches are not enabled on this server"))?;
+    let token = headers
+        .get("authorization")
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .ok_or_else(|| err("watch tools require an Authorization: Bearer token"))?;
+    if !allowed.split(',').map(str::trim).any(|t| t == token) {
+        return Err(err("invalid watch token"));
+    }
+    Ok(watch::owner_of(token))
+}
+
+pub async fn create_watch(
+    client: &BlockchainClient, env: &Env, rpc: &str, headers: &HashMap<String, String>, args: &Value,
+) -> Result<Value, Value> {
+    let owner = authorize(env, headers)?;
+    let request: CreateWatchRequest = serde_json::from_value(args.clone()).map_err(|e| err(&e.to_string()))?;
+    if !is_valid_address(&request.address) {
+        return Err(err("address is not a base58 48-byte public key"));
+    }
+    watch::validate_webhook_url(&request.webhook_url).map_err(|e| err(&e))?;
+    request.filter.validate().map_err(|e| err(&e))?;
+
+    let db = env.d1("MCP_DATABASE").map_err(|e| err(&e.to_string()))?;
+    let count: Option<f64> = db
+        .prepare("SELECT COUNT(*) AS count FROM watches WHERE owner = ?1")
+        .bind(&[owner.clone().into()])
+        .map_err(|e| err(&e.to_string()))?
+        .first(Some("count"))
+        .await
+        .map_err(|e| err(&e.to_string()))?;
+    if count.unwrap_or(0.0) as usize >= MAX_WATCHES_PER_OWNER {
+        return Err(err(&format!("at most {} watches per token; delete one first", MAX_WATCHES_PER_OWNER)));
+    }
+
+    // Start at the current tip so only transactions after creation are reported
+    let tip = client.get_raw(&client.api_url(rpc, "chain/tip")).await.map_err(|e| err(&e.to_string()))?;
+    let cursor_height = tip["entry"]["header"]["height"]
+        .as_u64()
+        .ok_or_else(|| err("tip entry missing height"))?;
+
+    let mut id = [0u8; 16];
+    let mut secret = [0u8; 32];
+    getrandom::getrandom(&mut id).map_err(|e| err(&e.to_string()))?;
+    getrandom::getrandom(&mut secret).map_err(|e| err(&e.to_string()))?;
+    let watch = Watch {
+        id: hex::encode(id),
+        owner,
+        address: request.address,
+        webhook_url: request.webhook_url,
+        filter: request.filter,
+        secret: hex::encode(secret),
+        cursor_height,
+        created_at: Date::now().as_millis() / 1000,
+    };
+    let filter = serde_json::to_string(&watch.filter).map_err(|e| err(&e.to_string()))?;
+
+    db.prepare("INSERT INTO watches (id, owner, address, webhook_url, filter, secret, cursor_height, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)")
+        .bind(&[
+            watch.id.clone().into(),
+            watch.owner.clone().into(),
+            watch.address.clone().into(),
+            watch.webhook_url.clone().into(),
+            filter.into(),
+            watch.secret.clone().into(),
+            (watch.cursor_height as f64).into(),
+            (watch.created_at as f64).into(),
+        ])
+        .map_err(|e| err(&e.to_string()))?
+        .run()
+        .await
+        .map_err(|e| err(&e.to_string()))?;
+
+    let mut created = serde_json::to_value(&watch).unwrap_or_default();
+    created["secret"] = json!(watch.secret);
+    created["signature_header"] = json!(watch::SIGNATURE_HEADER);
+    Ok(ok(&created))
+}
+
+pub async fn list_watches(env: &Env, headers: &HashMap<String, String>) -> Result<Value, Value> {
+    let owner = authorize(env, headers)?;
+    let db = env.d1("MCP_DATABASE").map_err(|e| err(&e.to_string()))?;
+    let rows: Vec<WatchRow> = db
+        .prepare("SELECT * FROM watches WHERE owner = ?1 ORDER BY created_at")
+        .bind(&[owner.into()])
+        .map_err(|e| err(&e.to_string()))?
+        .all()
+        .await
+        .map_err(|e| err(&e.to_string()))?
+        .results()
+        .map_err(|e| err(&e.to_string()))?;
+    let watches: Vec<Watch> = rows.into_iter().filter_map(WatchRow::into_watch).collect();
+    Ok(ok(&json!({ "watches": watches, "count": watches.len(), "limit": MAX_WATCHES_PER_OWNER })))
+}
+
+pub async fn delete_watch(env: &Env, headers: &HashMap<String, String>, args: &Value) -> Result<Value, Value> {
+    let owner = authorize(env, headers)?;
+    let id = args["id"].as_str().ok_or_else(|| err("missing id"))?;
+    let db = env.d1("MCP_DATABASE").map_err(|e| err(&e.to_string()))?;
+    let deleted: Option<String> = db
+        .prepare("DELETE FROM watches WHERE id = ?1 AND owner = ?2 RETURNING id")
+        .bind(&[id.into(), owner.into()])
+        .map_err(|e| err(&e.to_string()))?
+        .first(Some("id"))
+        .await
+        .map_err(|e| err(&e.to_string()))?;
+    match deleted {
+        Some(id) => Ok(ok(&json!({ "status": "deleted", "id": id }))),
+        None => Err(err("no watch with this id for this token")),
+    }
+}
+
+struct NodeHistory<'a> {
+    client: &'a BlockchainClient,
+    rpc: &'a str,
+}
+
+impl HistorySource for NodeHistory<'_> {
+    async fn recent(&self, address: &str, limit: u32) -> Result<Vec<Transaction>, String> {
+        self.client
+            .get_transaction_history(address, Some(limit), None, Some("desc"), self.rpc)
+            .await
+            .map_err(|e| e.to_string())
+    }
+}
+
+struct FetchWebhook;
+
+impl WebhookSink for FetchWebhook {
+    async fn post(&self, url: &str, body: &str, signature: &str) -> Result<(), String> {
+        let headers = Headers::new();
+        headers.set("Content-Type", "application/json").map_err(|e| e.to_string())?;
+        headers.set(watch::SIGNATURE_HEADER, signature).map_err(|e| e.to_string())?;
+        let mut init = RequestInit::new();
+        init.with_method(Method::Post);
+        init.with_headers(headers);
+        init.with_body(Some(body.into()));
+        // A redirect could lead past the URL validation to an internal address
+        init.with_redirect(RequestRedirect::Manual);
+
+        let request = Request::new_with_init(url, &init).map_err(|e| e.to_string())?;
+        let response = Fetch::Request(request).send().await.map_err(|e| e.to_string())?;
+        match response.status_code() {
+            200..=299 => Ok(()),
+            status => Err(format!("webhook answered HTTP {}", status)),
+        }
+    }
+
+    async fn backoff(&self, attempt: u32) {
+        Delay::from(WEBHOOK_BACKOFF * 2u32.pow(attempt - 1)).await;
+    }
+}
+
+/// Runs every stored watch once: new matching transactions are posted to the webhook and the
+/// cursor moved past what was handled.
+pub async fn dispatch_watches(env: &Env, client: &BlockchainClient, rpc: &str) -> Result<(), String> {
+    let db = env.d1("MCP_DATABASE").map_err(|e| e.to_string())?;
+    let rows: Vec<WatchRow> = db
+        .prepare("SELECT * FROM watches ORDER BY created_at LIMIT ?1")
+        .bind(&[(WATCHES_PER_RUN as f64).into()])
+        .map_err(|e| e.to_string())?
+        .all()
+        .await
+        .map_err(|e| e.to_string())?
+        .results()
+        .map_err(|e| e.to_string())?;
+
+    let history = NodeHistory { client, rpc };
+    for watch in rows.into_iter().filter_map(WatchRow::into_watch) {
+        let outcome = watch::dispatch(&watch, &history, &FetchWebhook).await;
+        if let Some(e) = &outcome.error {
+            console_warn!("watch {}: {}", watch.id, e);
+        }
+        if outcome.cursor_height != watch.cursor_height {
+            let update = db
+                .prepare("UPDATE watches SET cursor_height = ?1 WHERE id = ?2")
+                .bind(&[(outcome.cursor_height as f64).into(), watch.id.clone().into()]);
+            let result = match update {
+                Ok(statement) => statement.run().await.map(|_| ()),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                console_error!("failed to advance watch {}: {}", watch.id, e);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn err(msg: &str) -> Value {
+    json!({ "code": -32603, "message": msg })
+}
+
+fn ok<T: serde::Serialize>(data: &T) -> Value {
+    json!({ "content": [{ "type": "text", "text": serde_json::to_string_pretty(data).unwrap() }] })
+}
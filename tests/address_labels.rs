@@ -0,0 +1,88 @@
+//! Labels added with add_address_label show up next to addresses in later outputs, survive
+//! a restart through the disk cache directory and move between servers by export and import.
+
+mod common;
+
+use common::{McpClient, MockNode};
+use serde_json::{json, Value};
+
+const SIGNER: &str = "29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw";
+const RECEIVER: &str = "42KfUgyU2XHCPwDD6g9gryCvtj9vqJAvM6h3Yzs3VRbuyT2JCHwvuk5iAbzXrioerf";
+const BURN: &str = "111111111111111111111111111111111111111111111111";
+
+fn transaction(hash: &str) -> Value {
+    json!({
+        "hash": hash,
+        "metadata": { "entry_hash": "entry", "entry_height": 77 },
+        "signature": "sig",
+        "result": { "error": "ok" },
+        "tx": {
+            "action": { "args": [RECEIVER, "1000000000", "AMA"], "function": "transfer", "op": "call", "contract": "Coin" },
+            "nonce": 1,
+            "signer": SIGNER
+        },
+        "receipt": { "success": true, "result": "ok", "logs": [], "exec_used": "0" }
+    })
+}
+
+async fn node() -> MockNode {
+    MockNode::start(|request, _| match request.path.strip_prefix("/api/chain/tx/") {
+        Some(hash) => (200, transaction(hash)),
+        None => (404, json!({ "error": "not_found" })),
+    })
+    .await
+}
+
+#[tokio::test]
+async fn labels_annotate_later_outputs_and_persist() {
+    let node = node().await;
+    let dir = std::env::temp_dir().join(format!("amadeus-address-labels-{}", std::process::id()));
+    let dir = dir.to_str().unwrap();
+    let settings = [("BLOCKCHAIN_URL", node.url.as_str()), ("MCP_DISK_CACHE_DIR", dir)];
+    let (mut client, running) = McpClient::connect(common::server(common::config(&settings))).await;
+
+    let added = client.call_ok("add_address_label", json!({ "address": SIGNER, "label": " exchange hot wallet " })).await;
+    assert_eq!((added["change"].as_str(), added["label"].as_str()), (Some("added"), Some("exchange hot wallet")));
+    let updated = client.call_ok("add_address_label", json!({ "address": SIGNER, "label": "exchange" })).await;
+    assert_eq!((updated["change"].as_str(), updated["previous_label"].as_str()), (Some("updated"), Some("exchange hot wallet")));
+
+    let tx = client.call_ok("get_transaction", json!({ "tx_hash": "abc" })).await;
+    assert_eq!(tx["tx"]["signer_label"], "exchange", "{}", tx);
+    drop((client, running));
+
+    let (mut client, _running) = McpClient::connect(common::server(common::config(&settings))).await;
+    let exported = client.call_ok("export_address_labels", json!({})).await;
+    assert_eq!(exported["count"], 1);
+    assert_eq!((exported["labels"][0]["address"].as_str(), exported["labels"][0]["label"].as_str()), (Some(SIGNER), Some("exchange")));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn export_and_import_move_labels_between_servers() {
+    let (mut source, _source) = McpClient::connect(common::server(common::config(&[]))).await;
+    source.call_ok("add_address_label", json!({ "address": SIGNER, "label": "exchange" })).await;
+    source.call_ok("add_address_label", json!({ "address": RECEIVER, "label": "treasury" })).await;
+    let exported = source.call_ok("export_address_labels", json!({})).await;
+
+    let (mut target, _target) = McpClient::connect(common::server(common::config(&[]))).await;
+    target.call_ok("add_address_label", json!({ "address": RECEIVER, "label": "old name" })).await;
+    let mut labels = exported["labels"].as_array().unwrap().clone();
+    labels.push(json!({ "address": BURN, "label": "sink" }));
+    let report = target.call_ok("import_address_labels", json!({ "labels": labels })).await;
+    assert_eq!((report["added"].as_u64(), report["updated"].as_u64()), (Some(1), Some(1)), "{}", report);
+    assert_eq!(report["rejected"][0]["reason"], "address: already has the built-in label 'burn'");
+
+    let copied = target.call_ok("export_address_labels", json!({})).await;
+    assert_eq!(copied["labels"], exported["labels"]);
+}
+
+#[tokio::test]
+async fn built_in_labels_cannot_be_taken() {
+    let (mut client, _running) = McpClient::connect(common::server(common::config(&[]))).await;
+    for (address, label, field) in [(SIGNER, "Coin", "label"), (SIGNER, "burn", "label"), (BURN, "sink", "address")] {
+        let reply = client.call("add_address_label", json!({ "address": address, "label": label })).await;
+        assert_eq!(reply["error"]["message"], "invalid_label", "{}", reply);
+        assert_eq!(reply["error"]["data"]["field"], field, "{}", reply);
+    }
+    assert_eq!(client.call_ok("export_address_labels", json!({})).await["count"], 0);
+}
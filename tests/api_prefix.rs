@@ -0,0 +1,54 @@
+//! A node mounted under custom prefixes is reached at the prefixed paths for reads and for
+//! transaction submission.
+
+mod common;
+
+use common::{McpClient, MockNode};
+use serde_json::json;
+
+const SIGNER: &str = "29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw";
+const RECEIVER: &str = "42KfUgyU2XHCPwDD6g9gryCvtj9vqJAvM6h3Yzs3VRbuyT2JCHwvuk5iAbzXrioerf";
+
+#[tokio::test]
+async fn reads_and_submits_use_the_configured_prefixes() {
+    let node = MockNode::start(|request, _| match request.path.as_str() {
+        "/node/v2/contract/richlist" => {
+            (200, json!({ "error": "ok", "richlist": [{ "address": "holder", "flat": 1_000_000_000u64, "float": 1.0 }] }))
+        }
+        "/node/v2/tx/tx/submit" => (200, json!({ "error": "ok" })),
+        _ => (404, json!({ "error": "not_found" })),
+    })
+    .await;
+    let server = common::server(common::config(&[
+        ("BLOCKCHAIN_URL", &node.url),
+        ("BLOCKCHAIN_API_PREFIX", "/node/v2"),
+        ("BLOCKCHAIN_TX_API_PREFIX", "/node/v2/tx"),
+    ]));
+    let (mut client, _running) = McpClient::connect(server).await;
+
+    let richlist = client.call_ok("get_richlist", json!({})).await;
+    assert_eq!(richlist["total"], 1);
+
+    let built = client
+        .call_ok(
+            "create_transaction",
+            json!({
+                "signer": SIGNER,
+                "contract": "Coin",
+                "function": "transfer",
+                "args": [{ "b58": RECEIVER }, "1000000000", "AMA"],
+                "nonce": 1_718_000_000_000_000_000i64
+            }),
+        )
+        .await;
+    let signature = bs58::encode([7u8; 96]).into_string();
+    let submitted = client.call_ok("submit_transaction", json!({ "transaction": built["blob"], "signature": signature })).await;
+    assert_eq!(submitted["status"], "success");
+
+    // Startup probes are HEADs; every GET and POST went under the prefixes
+    let requests = node.requests();
+    let calls: Vec<_> = requests.iter().filter(|r| r.method != "HEAD").collect();
+    assert!(calls.iter().all(|r| r.path.starts_with("/node/v2/")), "{:?}", calls.iter().map(|r| &r.path).collect::<Vec<_>>());
+    assert!(calls.iter().any(|r| r.method == "POST" && r.path == "/node/v2/tx/tx/submit"));
+    assert!(requests.iter().all(|r| !r.path.starts_with("/api/")));
+}
@@ -0,0 +1,112 @@
+//! A window fan-out gives the same result through the node's batch endpoint as through
+//! separate requests, and a call the batch fails is fetched on its own.
+
+mod common;
+
+use common::{McpClient, MockNode, NodeRequest};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+const TIP: u64 = 6;
+/// Height the batch endpoint answers with an error
+const FAILING: u64 = 4;
+
+fn entry(height: u64) -> Value {
+    json!({
+        "hash": format!("h{}", height),
+        "header": {
+            "slot": height, "height": height, "dr": "dr", "vr": "vr", "prev_hash": "prev",
+            "signer": if height.is_multiple_of(3) { "pk_b" } else { "pk_a" },
+            "root_tx": "rtx", "root_validator": "rv", "prev_slot": height - 1, "timestamp": 1_700_000_000_000u64 + height * 500
+        },
+        "tx_count": 0
+    })
+}
+
+/// What the node answers for a GET of `path`.
+fn get(path: &str) -> (u16, Value) {
+    match path {
+        "/api/chain/tip" => (200, json!({ "error": "ok", "entry": entry(TIP) })),
+        "/api/peer/trainers" => (200, json!({ "error": "ok", "trainers": ["pk_a", "pk_b", "pk_c"] })),
+        path => match path.strip_prefix("/api/chain/height/").and_then(|h| h.parse::<u64>().ok()) {
+            Some(height @ 1..=TIP) => (200, json!({ "error": "ok", "entries": [entry(height)] })),
+            _ => (404, json!({ "error": "not_found" })),
+        },
+    }
+}
+
+/// Answers a batch request, failing the call for `FAILING` and leaving the rest to `get`.
+fn batch(request: &NodeRequest) -> (u16, Value) {
+    let calls: Vec<Value> = serde_json::from_str(&request.body).unwrap();
+    let answers = calls
+        .iter()
+        .map(|call| {
+            let path = call["params"]["path"].as_str().unwrap();
+            if path == format!("/api/chain/height/{}", FAILING) {
+                json!({ "jsonrpc": "2.0", "id": call["id"], "error": { "code": -32000, "message": "busy" } })
+            } else {
+                json!({ "jsonrpc": "2.0", "id": call["id"], "result": get(path).1 })
+            }
+        })
+        .collect();
+    (200, Value::Array(answers))
+}
+
+async fn node(batching: bool) -> MockNode {
+    MockNode::start(move |request, _| match (request.path.as_str(), batching) {
+        ("/api/rpc/batch", false) => (404, json!({ "error": "not_found" })),
+        ("/api/rpc/batch", true) if request.method == "POST" => batch(request),
+        ("/api/rpc/batch", true) => (200, json!({ "error": "ok" })),
+        (path, _) => get(path),
+    })
+    .await
+}
+
+/// The signer distribution over the whole chain, once the startup probe has run.
+async fn distribution(node: &MockNode) -> Value {
+    let (mut client, _running) = McpClient::connect(common::server(common::config(&[("BLOCKCHAIN_URL", &node.url)]))).await;
+    for _ in 0..100 {
+        let reply = client.request("resources/read", json!({ "uri": "amadeus://status" })).await;
+        let status: Value = serde_json::from_str(reply["result"]["contents"][0]["text"].as_str().unwrap()).unwrap();
+        if status["node_capabilities"]["probed"] == true {
+            return client.call_ok("get_signer_distribution", json!({ "blocks": TIP })).await;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("the startup probe never finished");
+}
+
+fn height_gets(node: &MockNode) -> Vec<String> {
+    let mut paths: Vec<String> = node
+        .requests()
+        .into_iter()
+        .filter(|r| r.method == "GET" && r.path.starts_with("/api/chain/height/"))
+        .map(|r| r.path)
+        .collect();
+    paths.sort();
+    paths
+}
+
+#[tokio::test]
+async fn batched_and_separate_fetches_agree() {
+    let batched_node = node(true).await;
+    let separate_node = node(false).await;
+    let mut batched = distribution(&batched_node).await;
+    let mut separate = distribution(&separate_node).await;
+
+    // One batch round trip stands in for five height requests
+    let batched_requests = batched["timing"]["upstream_requests"].as_u64().unwrap();
+    assert!(batched_requests < separate["timing"]["upstream_requests"].as_u64().unwrap(), "{} {}", batched, separate);
+    batched.as_object_mut().unwrap().remove("timing");
+    separate.as_object_mut().unwrap().remove("timing");
+    assert_eq!(batched, separate);
+    assert_eq!(batched["from_height"], 1, "{}", batched);
+    assert_eq!(batched["to_height"], TIP);
+
+    let posts = batched_node.requests().into_iter().filter(|r| r.method == "POST" && r.path == "/api/rpc/batch").count();
+    assert_eq!(posts, 1);
+    // Only the height the batch failed is fetched separately
+    assert_eq!(height_gets(&batched_node), [format!("/api/chain/height/{}", FAILING)]);
+    assert_eq!(height_gets(&separate_node).len(), TIP as usize);
+    assert!(separate_node.requests().iter().all(|r| r.method != "POST"));
+}
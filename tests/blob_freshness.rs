@@ -0,0 +1,91 @@
+//! check_blob_freshness and the check submit_transaction runs on blobs this server built.
+
+mod common;
+
+use common::{McpClient, MockNode};
+use serde_json::{json, Value};
+
+const SIGNER: &str = "29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw";
+const RECEIVER: &str = "42KfUgyU2XHCPwDD6g9gryCvtj9vqJAvM6h3Yzs3VRbuyT2JCHwvuk5iAbzXrioerf";
+const SUBMIT: &str = "/api/tx/submit";
+const NONCE: i64 = 1_718_000_000_000_000_000;
+
+/// A node whose history says `SIGNER` last used `latest`.
+async fn node(latest: i64) -> MockNode {
+    MockNode::start(move |request, _| {
+        if request.path.starts_with(&format!("/api/chain/tx_events_by_account/{}", SIGNER)) {
+            let tx = json!({
+                "hash": "landed",
+                "metadata": { "entry_hash": "entry", "entry_height": 1 },
+                "signature": "sig",
+                "result": { "error": "ok" },
+                "tx": {
+                    "action": { "args": [RECEIVER, "1", "AMA"], "function": "transfer", "op": "call", "contract": "Coin" },
+                    "nonce": latest,
+                    "signer": SIGNER
+                },
+                "receipt": { "success": true, "result": "ok", "logs": [], "exec_used": "0" }
+            });
+            (200, json!({ "error": "ok", "txs": [tx] }))
+        } else if request.path == SUBMIT {
+            (200, json!({ "error": "ok" }))
+        } else {
+            (404, json!({ "error": "not_found" }))
+        }
+    })
+    .await
+}
+
+async fn build(client: &mut McpClient) -> Value {
+    client
+        .call_ok(
+            "create_transaction",
+            json!({
+                "signer": SIGNER,
+                "contract": "Coin",
+                "function": "transfer",
+                "args": [{ "b58": RECEIVER }, "1000000000", "AMA"],
+                "nonce": NONCE
+            }),
+        )
+        .await
+}
+
+fn submits(node: &MockNode) -> usize {
+    node.requests().iter().filter(|r| r.method == "POST" && r.path == SUBMIT).count()
+}
+
+#[tokio::test]
+async fn a_consumed_blob_built_here_is_refused_at_submit() {
+    let node = node(NONCE + 1).await;
+    let server = common::server(common::config(&[("BLOCKCHAIN_URL", &node.url)]));
+    let (mut client, _running) = McpClient::connect(server).await;
+
+    let built = build(&mut client).await;
+    assert_eq!(built["nonce_basis"], "explicit");
+    assert!(built["built_at"].as_u64().is_some());
+
+    let freshness = client.call_ok("check_blob_freshness", json!({ "transaction": built["blob"] })).await;
+    assert_eq!(freshness["status"], "consumed");
+    assert_eq!(freshness["origin"], "this_server");
+    assert_eq!(freshness["latest_account_nonce"], (NONCE + 1).to_string());
+
+    let signature = bs58::encode([7u8; 96]).into_string();
+    let reply = client.call("submit_transaction", json!({ "transaction": built["blob"], "signature": signature })).await;
+    assert_eq!(reply["error"]["message"], "stale_blob", "{}", reply);
+    assert_eq!(reply["error"]["data"]["status"], "consumed");
+    assert_eq!(submits(&node), 0);
+}
+
+#[tokio::test]
+async fn a_fresh_blob_built_here_is_submitted() {
+    let node = node(NONCE - 1).await;
+    let server = common::server(common::config(&[("BLOCKCHAIN_URL", &node.url)]));
+    let (mut client, _running) = McpClient::connect(server).await;
+
+    let built = build(&mut client).await;
+    let signature = bs58::encode([7u8; 96]).into_string();
+    let reply = client.call_ok("submit_transaction", json!({ "transaction": built["blob"], "signature": signature })).await;
+    assert_eq!(reply["status"], "success");
+    assert_eq!(submits(&node), 1);
+}
@@ -0,0 +1,76 @@
+//! Transactions built by create_transaction are marked in lookups once seen on chain and
+//! listed by list_built_transactions, unless recording is turned off.
+
+mod common;
+
+use common::{McpClient, MockNode};
+use serde_json::{json, Value};
+
+const SIGNER: &str = "29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw";
+const RECEIVER: &str = "42KfUgyU2XHCPwDD6g9gryCvtj9vqJAvM6h3Yzs3VRbuyT2JCHwvuk5iAbzXrioerf";
+
+fn transaction(hash: &str) -> Value {
+    json!({
+        "hash": hash,
+        "metadata": { "entry_hash": "entry", "entry_height": 77 },
+        "signature": "sig",
+        "result": { "error": "ok" },
+        "tx": {
+            "action": { "args": [RECEIVER, "1000000000", "AMA"], "function": "transfer", "op": "call", "contract": "Coin" },
+            "nonce": 1,
+            "signer": SIGNER
+        },
+        "receipt": { "success": true, "result": "ok", "logs": [], "exec_used": "0" }
+    })
+}
+
+/// A node that has included every transaction it is asked about.
+async fn node() -> MockNode {
+    MockNode::start(|request, _| match request.path.strip_prefix("/api/chain/tx/") {
+        Some(hash) => (200, transaction(hash)),
+        None => (404, json!({ "error": "not_found" })),
+    })
+    .await
+}
+
+async fn build(client: &mut McpClient) -> String {
+    let request = json!({ "signer": SIGNER, "contract": "Coin", "function": "transfer", "args": [{ "b58": RECEIVER }, "1000000000", "AMA"] });
+    let built = client.call_ok("create_transaction", request).await;
+    built["transaction_hash"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn built_transactions_are_marked_and_tracked() {
+    let node = node().await;
+    let (mut client, _running) = McpClient::connect(common::server(common::config(&[("BLOCKCHAIN_URL", &node.url)]))).await;
+    let hash = build(&mut client).await;
+
+    let listed = client.call_ok("list_built_transactions", json!({})).await;
+    assert_eq!((listed["count"].clone(), listed["recording"].clone()), (json!(1), json!(true)));
+    let entry = &listed["transactions"][0];
+    assert_eq!((entry["transaction_hash"].as_str(), entry["status"].as_str()), (Some(hash.as_str()), Some("pending")));
+    assert_eq!((entry["destination"].as_str(), entry["amount"].as_str()), (Some(RECEIVER), Some("1000000000")));
+
+    let tx = client.call_ok("get_transaction", json!({ "tx_hash": hash })).await;
+    assert_eq!(tx["built_by_this_server"], true, "{}", tx);
+    assert_eq!(tx["built_at"], entry["built_at"]);
+    let external = client.call_ok("get_transaction", json!({ "tx_hash": "external" })).await;
+    assert!(external.get("built_by_this_server").is_none());
+
+    let observed = client.call_ok("list_built_transactions", json!({ "status": "observed" })).await;
+    assert_eq!(observed["transactions"][0]["observed_height"], 77);
+    assert_eq!(client.call_ok("list_built_transactions", json!({ "status": "pending" })).await["count"], 0);
+}
+
+#[tokio::test]
+async fn recording_can_be_turned_off() {
+    let node = node().await;
+    let settings = [("BLOCKCHAIN_URL", node.url.as_str()), ("MCP_RECORD_BUILT_TXS", "0")];
+    let (mut client, _running) = McpClient::connect(common::server(common::config(&settings))).await;
+    let hash = build(&mut client).await;
+
+    let listed = client.call_ok("list_built_transactions", json!({})).await;
+    assert_eq!((listed["count"].clone(), listed["recording"].clone()), (json!(0), json!(false)));
+    let tx = client.call_ok("get_transaction", json!({ "tx_hash": hash })).await;
+    assert!(tx.get("built_by_this_server").is_none(), "{}", tx);
+}
@@ -0,0 +1,75 @@
+//! get_capabilities, the initialize instructions and tools/list agree on which optional
+//! features are on, whatever flags the deployment sets.
+
+mod common;
+
+use amadeus_mcp::catalog::RAW_QUERY_TOOL;
+use common::McpClient;
+use serde_json::{json, Value};
+
+/// Features that are on exactly when their tool is listed.
+const TOOL_FEATURES: &[(&str, &str)] = &[
+    ("submit_transaction", "submit_transaction"),
+    ("raw_node_query", RAW_QUERY_TOOL),
+    ("server_key_transfer", "transfer_from_server_key"),
+    ("address_labels", "add_address_label"),
+];
+
+/// The features object embedded in the initialize instructions.
+fn instruction_features(client: &McpClient) -> Value {
+    let init = client.seen.iter().find(|frame| frame["result"]["instructions"].is_string()).expect("initialize result");
+    let instructions = init["result"]["instructions"].as_str().unwrap();
+    let (_, features) = instructions.split_once("features: ").unwrap_or_else(|| panic!("{}", instructions));
+    serde_json::from_str(features).unwrap()
+}
+
+async fn check(settings: &[(&str, &str)]) -> Value {
+    let mut settings = settings.to_vec();
+    settings.push(("BLOCKCHAIN_URL", "http://127.0.0.1:9"));
+    let (mut client, _running) = McpClient::connect(common::server(common::config(&settings))).await;
+
+    let listed = client.tool_names().await;
+    let capabilities = client.call_ok("get_capabilities", json!({})).await;
+    let features = &capabilities["features"];
+
+    let mut reported: Vec<&str> = capabilities["tools"].as_array().unwrap().iter().map(|t| t.as_str().unwrap()).collect();
+    let mut expected: Vec<&str> = listed.iter().map(String::as_str).collect();
+    reported.sort_unstable();
+    expected.sort_unstable();
+    assert_eq!(reported, expected, "{:?}", settings);
+
+    for (feature, tool) in TOOL_FEATURES {
+        let enabled = features[feature]["enabled"].as_bool().unwrap_or_else(|| panic!("no {} in {}", feature, features));
+        assert_eq!(enabled, listed.iter().any(|name| name == tool), "{} with {:?}", feature, settings);
+        // Every disabled feature says why
+        assert_eq!(features[feature]["reason"].is_string(), !enabled, "{}", features[feature]);
+    }
+    assert_eq!(&instruction_features(&client), features, "{:?}", settings);
+    features.clone()
+}
+
+#[tokio::test]
+async fn the_default_deployment_agrees_with_its_tool_list() {
+    let features = check(&[]).await;
+    assert_eq!(features["submit_transaction"]["enabled"], true);
+    assert_eq!(features["raw_node_query"]["enabled"], false);
+    assert_eq!(features["faucet"], json!({ "enabled": false, "reason": "worker only" }));
+    assert_eq!(features["strict_args"]["reason"], "MCP_STRICT_ARGS is not set");
+}
+
+#[tokio::test]
+async fn flipped_flags_change_features_and_tools_together() {
+    let features = check(&[("MCP_READ_ONLY", "1"), ("MCP_RAW_QUERY_ALLOW", "/api/chain"), ("MCP_STRICT_ARGS", "1")]).await;
+    assert_eq!(features["submit_transaction"]["enabled"], false);
+    assert_eq!(features["raw_node_query"]["enabled"], true);
+    assert_eq!(features["strict_args"], json!({ "enabled": true }));
+
+    let features = check(&[("MCP_TOOLS_DENY", "submit_transaction,add_address_label"), ("MCP_INCLUDE_TIMING", "0")]).await;
+    assert_eq!(features["submit_transaction"]["enabled"], false);
+    assert_eq!(features["address_labels"]["enabled"], false);
+    assert_eq!(features["timing"], json!({ "enabled": false, "reason": "MCP_INCLUDE_TIMING=0" }));
+
+    let features = check(&[("MCP_TOOLS_ALLOW", "get_capabilities,get_chain_stats,submit_transaction")]).await;
+    assert_eq!(features["submit_transaction"]["enabled"], true);
+    assert_eq!(features["address_labels"]["enabled"], false);
+}
@@ -0,0 +1,98 @@
+//! A server clock skewed against the tip entry makes create_transaction derive its nonce
+//! from the chain and say so.
+
+mod common;
+
+use common::{McpClient, MockNode};
+use serde_json::{json, Value};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const SIGNER: &str = "29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw";
+const RECEIVER: &str = "42KfUgyU2XHCPwDD6g9gryCvtj9vqJAvM6h3Yzs3VRbuyT2JCHwvuk5iAbzXrioerf";
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
+
+/// A node whose tip entry was stamped at `chain_time` (milliseconds) and whose history
+/// says `SIGNER` last used `latest`.
+async fn node(chain_time: u64, latest: i64) -> MockNode {
+    MockNode::start(move |request, _| match request.path.as_str() {
+        "/api/chain/stats" => (200, json!({ "error": "ok", "stats": { "height": 100 } })),
+        "/api/chain/tip" => (
+            200,
+            json!({ "error": "ok", "entry": {
+                "hash": "tip",
+                "header": {
+                    "slot": 100, "height": 100, "dr": "dr", "vr": "vr", "prev_hash": "prev", "signer": "signer",
+                    "root_tx": "rtx", "root_validator": "rv", "prev_slot": 99, "timestamp": chain_time
+                },
+                "tx_count": 0
+            } }),
+        ),
+        path if path.starts_with(&format!("/api/chain/tx_events_by_account/{}", SIGNER)) => {
+            let tx = json!({
+                "hash": "landed",
+                "metadata": { "entry_hash": "entry", "entry_height": 1 },
+                "signature": "sig",
+                "result": { "error": "ok" },
+                "tx": {
+                    "action": { "args": [RECEIVER, "1", "AMA"], "function": "transfer", "op": "call", "contract": "Coin" },
+                    "nonce": latest,
+                    "signer": SIGNER
+                },
+                "receipt": { "success": true, "result": "ok", "logs": [], "exec_used": "0" }
+            });
+            (200, json!({ "error": "ok", "txs": [tx] }))
+        }
+        _ => (404, json!({ "error": "not_found" })),
+    })
+    .await
+}
+
+fn transfer() -> Value {
+    json!({ "signer": SIGNER, "contract": "Coin", "function": "transfer", "args": [{ "b58": RECEIVER }, "1000000000", "AMA"] })
+}
+
+/// Builds a transfer once the tip follower has published the tip, so its skew is known.
+async fn build_after_first_tip(client: &mut McpClient) -> Value {
+    for _ in 0..200 {
+        let reply = client.request("resources/read", json!({ "uri": "amadeus://status" })).await;
+        let status: Value = serde_json::from_str(reply["result"]["contents"][0]["text"].as_str().unwrap()).unwrap();
+        if !status["tip"].is_null() {
+            return client.call_ok("create_transaction", transfer()).await;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("the tip follower never published the tip");
+}
+
+async fn connect(node: &MockNode) -> (McpClient, impl Sized) {
+    let settings = [("BLOCKCHAIN_URL", node.url.as_str()), ("MCP_TIP_REFRESH_SECS", "60")];
+    McpClient::connect(common::server(common::config(&settings))).await
+}
+
+#[tokio::test]
+async fn a_skewed_clock_switches_to_chain_nonces() {
+    // The chain is an hour behind the local clock
+    let chain_time = now() - 3600;
+    let latest = chain_time as i64 * 1_000_000_000 + 500;
+    let node = node(chain_time * 1000, latest).await;
+    let (mut client, _running) = connect(&node).await;
+
+    let built = build_after_first_tip(&mut client).await;
+    assert_eq!(built["nonce_basis"], "chain", "{}", built);
+    assert_eq!(built["nonce"], (latest + 1).to_string());
+    let warning = built["clock_skew_warning"].as_str().unwrap();
+    assert!(warning.starts_with("the server clock is") && warning.contains("ahead of the chain"), "{}", warning);
+}
+
+#[tokio::test]
+async fn a_clock_in_step_keeps_clock_nonces() {
+    let node = node(now() * 1000, 1).await;
+    let (mut client, _running) = connect(&node).await;
+
+    let built = build_after_first_tip(&mut client).await;
+    assert_eq!(built["nonce_basis"], "clock", "{}", built);
+    assert!(built.get("clock_skew_warning").is_none());
+}
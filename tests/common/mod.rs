@@ -0,0 +1,308 @@
+//! Helpers shared by the integration tests: an MCP client over an in-memory stdio pipe,
+//! a scripted node over local HTTP, and a tracing layer that records spans.
+
+#![allow(dead_code)]
+
+use amadeus_mcp::{BlockchainClient, BlockchainMcpServer, ServerConfig};
+use rmcp::service::{RoleServer, RunningService};
+use rmcp::{ServerHandler, ServiceExt};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader, DuplexStream, Lines, ReadHalf, WriteHalf};
+use tokio::net::TcpListener;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+/// A configuration from `settings`, with the tip follower off so nothing runs in the
+/// background.
+pub fn config(settings: &[(&str, &str)]) -> ServerConfig {
+    let mut settings: HashMap<String, String> =
+        settings.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect();
+    settings.entry("MCP_TIP_REFRESH_SECS".to_string()).or_insert_with(|| "0".to_string());
+    ServerConfig::try_from_lookup(|key| settings.get(key).cloned()).unwrap()
+}
+
+pub fn server(config: ServerConfig) -> BlockchainMcpServer {
+    let client = BlockchainClient::from_config(&config).unwrap();
+    BlockchainMcpServer::new(client, config)
+}
+
+/// The client end of an in-memory stdio session, recording every frame it reads.
+pub struct McpClient {
+    lines: Lines<BufReader<ReadHalf<DuplexStream>>>,
+    writer: WriteHalf<DuplexStream>,
+    next_id: u64,
+    pub seen: Vec<Value>,
+}
+
+impl McpClient {
+    /// Serves `server` over a pipe and runs the initialize handshake.
+    pub async fn connect<S: ServerHandler>(server: S) -> (Self, RunningService<RoleServer, S>) {
+        Self::connect_with(server, json!({})).await
+    }
+
+    /// Like `connect`, advertising client `capabilities` such as elicitation.
+    pub async fn connect_with<S: ServerHandler>(server: S, capabilities: Value) -> (Self, RunningService<RoleServer, S>) {
+        let (client_side, server_side) = tokio::io::duplex(1 << 20);
+        let serving = tokio::spawn(server.serve(tokio::io::split(server_side)));
+        let (reader, writer) = tokio::io::split(client_side);
+        let mut client = Self { lines: BufReader::new(reader).lines(), writer, next_id: 0, seen: Vec::new() };
+
+        let init = client
+            .request(
+                "initialize",
+                json!({ "protocolVersion": "2024-11-05", "capabilities": capabilities, "clientInfo": { "name": "test", "version": "0" } }),
+            )
+            .await;
+        assert!(init.get("result").is_some(), "initialize failed: {}", init);
+        client.send(json!({ "jsonrpc": "2.0", "method": "notifications/initialized" })).await;
+        let running = serving.await.unwrap().unwrap();
+        (client, running)
+    }
+
+    pub async fn send(&mut self, message: Value) {
+        let mut line = message.to_string();
+        line.push('\n');
+        self.writer.write_all(line.as_bytes()).await.unwrap();
+        self.writer.flush().await.unwrap();
+    }
+
+    pub async fn next(&mut self) -> Value {
+        self.next_within(Duration::from_secs(10)).await
+    }
+
+    /// The next frame, failing the test when none arrives within `limit`.
+    pub async fn next_within(&mut self, limit: Duration) -> Value {
+        let line = tokio::time::timeout(limit, self.lines.next_line())
+            .await
+            .expect("no frame from the server")
+            .unwrap()
+            .expect("server closed the session");
+        let frame: Value = serde_json::from_str(&line).unwrap();
+        self.seen.push(frame.clone());
+        frame
+    }
+
+    /// Sends a request and waits for its response, recording anything in between.
+    pub async fn request(&mut self, method: &str, params: Value) -> Value {
+        self.next_id += 1;
+        let id = self.next_id;
+        self.send(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })).await;
+        loop {
+            let frame = self.next().await;
+            if frame["id"] == id && frame.get("method").is_none() {
+                return frame;
+            }
+        }
+    }
+
+    /// The JSON-RPC response to a tools/call.
+    pub async fn call(&mut self, tool: &str, arguments: Value) -> Value {
+        self.request("tools/call", json!({ "name": tool, "arguments": arguments })).await
+    }
+
+    /// The output of a successful tools/call.
+    pub async fn call_ok(&mut self, tool: &str, arguments: Value) -> Value {
+        let reply = self.call(tool, arguments).await;
+        let result = &reply["result"];
+        assert!(result.is_object() && result["isError"] != true, "{} failed: {}", tool, reply);
+        match result.get("structuredContent") {
+            Some(structured) => structured.clone(),
+            None => serde_json::from_str(result["content"][0]["text"].as_str().unwrap()).unwrap(),
+        }
+    }
+
+    pub async fn wait_for(&mut self, method: &str) {
+        if self.seen.iter().any(|frame| frame["method"] == method) {
+            return;
+        }
+        while self.next().await["method"] != method {}
+    }
+
+    pub fn count(&self, method: &str) -> usize {
+        self.seen.iter().filter(|frame| frame["method"] == method).count()
+    }
+
+    /// Every tool, following tools/list cursors.
+    pub async fn tools(&mut self) -> Vec<Value> {
+        let mut tools = Vec::new();
+        let mut cursor = Value::Null;
+        for _ in 0..50 {
+            let params = if cursor.is_null() { json!({}) } else { json!({ "cursor": cursor }) };
+            let reply = self.request("tools/list", params).await;
+            tools.extend(reply["result"]["tools"].as_array().unwrap().iter().cloned());
+            cursor = reply["result"]["nextCursor"].clone();
+            if cursor.is_null() {
+                break;
+            }
+        }
+        tools
+    }
+
+    /// Every tool name, following tools/list cursors.
+    pub async fn tool_names(&mut self) -> Vec<String> {
+        self.tools().await.iter().map(|t| t["name"].as_str().unwrap().to_string()).collect()
+    }
+}
+
+/// A request the scripted node received.
+#[derive(Debug, Clone)]
+pub struct NodeRequest {
+    pub method: String,
+    pub path: String,
+    /// Lowercased header names
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+type Script = dyn Fn(&NodeRequest, usize) -> (u16, Value) + Send + Sync;
+
+/// A node on a local port answering each request from a script, which gets the request
+/// and how many earlier requests had the same path.
+pub struct MockNode {
+    pub url: String,
+    requests: Arc<Mutex<Vec<NodeRequest>>>,
+}
+
+impl MockNode {
+    pub async fn start(script: impl Fn(&NodeRequest, usize) -> (u16, Value) + Send + Sync + 'static) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let script: Arc<Script> = Arc::new(script);
+        let seen = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let (script, seen) = (script.clone(), seen.clone());
+                tokio::spawn(async move {
+                    let Some(request) = read_request(&mut stream).await else {
+                        return;
+                    };
+                    let earlier = {
+                        let mut seen = seen.lock().unwrap();
+                        let earlier = seen.iter().filter(|r: &&NodeRequest| r.path == request.path).count();
+                        seen.push(request.clone());
+                        earlier
+                    };
+                    let (status, body) = script(&request, earlier);
+                    let body = body.to_string();
+                    let response = format!(
+                        "HTTP/1.1 {} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        status,
+                        body.len(),
+                        body
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                    let _ = stream.shutdown().await;
+                });
+            }
+        });
+        Self { url, requests }
+    }
+
+    pub fn requests(&self) -> Vec<NodeRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+async fn read_request(stream: &mut tokio::net::TcpStream) -> Option<NodeRequest> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let head_end = loop {
+        let n = stream.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        buffer.extend_from_slice(&chunk[..n]);
+        if let Some(end) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break end;
+        }
+    };
+    let head = String::from_utf8_lossy(&buffer[..head_end]).to_string();
+    let mut lines = head.lines();
+    let mut start = lines.next()?.split(' ');
+    let (method, path) = (start.next()?.to_string(), start.next()?.to_string());
+    let headers: HashMap<String, String> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect();
+    let length: usize = headers.get("content-length").and_then(|v| v.parse().ok()).unwrap_or(0);
+    while buffer.len() < head_end + 4 + length {
+        let n = stream.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            break;
+        }
+        buffer.extend_from_slice(&chunk[..n]);
+    }
+    let end = buffer.len().min(head_end + 4 + length);
+    let body = String::from_utf8_lossy(&buffer[head_end + 4..end]).to_string();
+    Some(NodeRequest { method, path, headers, body })
+}
+
+/// A span as recorded by [`SpanCapture`].
+#[derive(Debug, Clone)]
+pub struct CapturedSpan {
+    pub id: u64,
+    pub name: String,
+    pub parent: Option<u64>,
+    pub fields: BTreeMap<String, String>,
+}
+
+/// A layer recording every span with its parent and fields.
+#[derive(Clone, Default)]
+pub struct SpanCapture {
+    spans: Arc<Mutex<Vec<CapturedSpan>>>,
+}
+
+impl SpanCapture {
+    pub fn spans(&self) -> Vec<CapturedSpan> {
+        self.spans.lock().unwrap().clone()
+    }
+
+    pub fn named(&self, name: &str) -> Vec<CapturedSpan> {
+        self.spans().into_iter().filter(|s| s.name == name).collect()
+    }
+
+    pub fn children(&self, parent: &CapturedSpan) -> Vec<CapturedSpan> {
+        self.spans().into_iter().filter(|s| s.parent == Some(parent.id)).collect()
+    }
+}
+
+struct Fields<'a>(&'a mut BTreeMap<String, String>);
+
+impl Visit for Fields<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0.insert(field.name().to_string(), format!("{:?}", value));
+    }
+}
+
+impl<S: tracing::Subscriber + for<'a> LookupSpan<'a>> Layer<S> for SpanCapture {
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut fields = BTreeMap::new();
+        attrs.record(&mut Fields(&mut fields));
+        let parent = ctx.span(id).and_then(|span| span.parent()).map(|parent| parent.id().into_u64());
+        self.spans.lock().unwrap().push(CapturedSpan {
+            id: id.into_u64(),
+            name: attrs.metadata().name().to_string(),
+            parent,
+            fields,
+        });
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, _ctx: Context<'_, S>) {
+        let mut spans = self.spans.lock().unwrap();
+        // Ids are reused once a span closes, so the latest span with the id is the live one
+        if let Some(span) = spans.iter_mut().rev().find(|s| s.id == id.into_u64()) {
+            values.record(&mut Fields(&mut span.fields));
+        }
+    }
+}
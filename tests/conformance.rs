@@ -0,0 +1,70 @@
+//! The conformance run passes against the in-process server, and a transport that
+//! breaks the protocol is caught by the same steps.
+#![cfg(feature = "dev-tools")]
+
+mod common;
+
+use amadeus_mcp::conformance::{self, Exchange};
+use serde_json::{json, Value};
+
+const LIFECYCLE: [&str; 9] = [
+    "initialize",
+    "tools/list",
+    "tools/list bad cursor",
+    "tools/call",
+    "tools/call failing",
+    "tools/call unknown tool",
+    "resources",
+    "resource templates",
+    "prompts",
+];
+
+#[tokio::test]
+async fn the_in_process_server_conforms() {
+    let config = common::config(&[("BLOCKCHAIN_URL", "http://127.0.0.1:9")]);
+    let report = conformance::in_process(config).await.unwrap();
+    let failed: Vec<_> = report.steps.iter().filter(|s| !s.passed).map(|s| format!("{}: {}", s.step, s.detail)).collect();
+    assert!(report.passed, "{:?}", failed);
+    let steps: Vec<&str> = report.steps.iter().map(|s| s.step.as_str()).collect();
+    assert_eq!(steps, LIFECYCLE);
+}
+
+/// A transport that answers every request with `reply`, keeping the request's id.
+struct Scripted {
+    reply: Value,
+    requests: Vec<Value>,
+}
+
+impl Exchange for Scripted {
+    async fn request(&mut self, message: Value) -> Result<Value, String> {
+        let mut reply = self.reply.clone();
+        reply["id"] = message["id"].clone();
+        self.requests.push(message);
+        Ok(reply)
+    }
+
+    async fn notify(&mut self, _message: Value) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn a_failed_initialize_stops_the_run() {
+    let reply = json!({ "jsonrpc": "2.0", "error": { "code": -32603, "message": "boom" } });
+    let mut exchange = Scripted { reply, requests: Vec::new() };
+    let report = conformance::run(&mut exchange).await;
+    assert!(!report.passed);
+    assert_eq!(report.steps.len(), 1);
+    assert_eq!(report.steps[0].step, "initialize");
+    assert_eq!(exchange.requests.len(), 1);
+    assert_eq!(exchange.requests[0]["method"], "initialize");
+}
+
+#[tokio::test]
+async fn malformed_framing_fails_initialize() {
+    // No jsonrpc member and no result: initialize must not pass
+    let mut exchange = Scripted { reply: json!({ "result": null }), requests: Vec::new() };
+    let report = conformance::run(&mut exchange).await;
+    assert!(!report.passed);
+    assert!(!report.steps[0].passed, "{:?}", report.steps[0].detail);
+}
@@ -0,0 +1,81 @@
+//! investigate_addresses reads the tip before and after its sub-queries and flags the
+//! answer as inconsistent when the chain moved in between.
+
+mod common;
+
+use common::{McpClient, MockNode};
+use serde_json::{json, Value};
+
+const A: &str = "29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw";
+const B: &str = "42KfUgyU2XHCPwDD6g9gryCvtj9vqJAvM6h3Yzs3VRbuyT2JCHwvuk5iAbzXrioerf";
+
+fn tip(height: u64) -> Value {
+    json!({
+        "error": "ok",
+        "entry": {
+            "hash": format!("tip{}", height),
+            "header": {
+                "slot": height,
+                "height": height,
+                "dr": "dr",
+                "vr": "vr",
+                "prev_hash": "prev",
+                "signer": A,
+                "root_tx": "rt",
+                "root_validator": "rv",
+                "prev_slot": height - 1
+            },
+            "tx_count": 0
+        }
+    })
+}
+
+/// A node whose tip is at 100 on the first read and at `100 + moved` afterwards.
+async fn node(moved: u64) -> MockNode {
+    MockNode::start(move |request, earlier| {
+        let path = request.path.as_str();
+        if path == "/api/chain/tip" {
+            (200, tip(if earlier == 0 { 100 } else { 100 + moved }))
+        } else if path.starts_with("/api/wallet/balance_all/") {
+            (200, json!({ "error": "ok", "balances": [{ "symbol": "AMA", "flat": 5_000_000_000u64, "float": 5.0 }] }))
+        } else if path.starts_with("/api/chain/tx_events_by_account/") {
+            (200, json!({ "error": "ok", "txs": [] }))
+        } else {
+            (404, json!({ "error": "not_found" }))
+        }
+    })
+    .await
+}
+
+async fn investigate(node: &MockNode) -> Value {
+    let server = common::server(common::config(&[("BLOCKCHAIN_URL", &node.url)]));
+    let (mut client, running) = McpClient::connect(server).await;
+    let output = client.call_ok("investigate_addresses", json!({ "address_a": A, "address_b": B })).await;
+    let _ = running.cancel().await;
+    output
+}
+
+#[tokio::test]
+async fn a_still_tip_is_consistent() {
+    let node = node(0).await;
+    let output = investigate(&node).await;
+    assert_eq!(
+        output["consistency"],
+        json!({ "start_height": 100, "end_height": 100, "consistent": true, "drift": 0 })
+    );
+}
+
+#[tokio::test]
+async fn a_tip_that_moves_mid_call_flips_the_flag() {
+    let node = node(2).await;
+    let output = investigate(&node).await;
+    assert_eq!(
+        output["consistency"],
+        json!({ "start_height": 100, "end_height": 102, "consistent": false, "drift": 2 })
+    );
+    // The tip was read once before and once after the balance and history reads
+    let paths: Vec<String> = node.requests().into_iter().filter(|r| r.method == "GET").map(|r| r.path).collect();
+    assert_eq!(paths.first().map(String::as_str), Some("/api/chain/tip"));
+    assert_eq!(paths.last().map(String::as_str), Some("/api/chain/tip"));
+    assert_eq!(paths.iter().filter(|p| *p == "/api/chain/tip").count(), 2);
+}
@@ -0,0 +1,83 @@
+//! Transaction lookups carry decoded_action for contract calls, and an undecodable call
+//! still returns with the raw parts explained.
+
+mod common;
+
+use common::{McpClient, MockNode};
+use serde_json::{json, Value};
+
+const SIGNER: &str = "29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw";
+const RECEIVER: &str = "42KfUgyU2XHCPwDD6g9gryCvtj9vqJAvM6h3Yzs3VRbuyT2JCHwvuk5iAbzXrioerf";
+
+fn transaction(hash: &str, args: Value) -> Value {
+    json!({
+        "hash": hash,
+        "metadata": { "entry_hash": "4Kz8f9LDqx7GzpM2a3n2ZbCqQ6hJ8Y9sW1tX5rV3uE7d", "entry_height": 100 },
+        "signature": "sig",
+        "result": { "error": "ok" },
+        "tx": {
+            "action": { "args": args, "function": "transfer", "op": "call", "contract": "Coin" },
+            "nonce": 1,
+            "signer": SIGNER
+        },
+        "receipt": { "success": true, "result": "ok", "logs": [], "exec_used": "0" }
+    })
+}
+
+fn transfer() -> Value {
+    transaction("transfer", json!([RECEIVER, "1000000000", "AMA"]))
+}
+
+fn garbled() -> Value {
+    transaction("garbled", json!([RECEIVER, "\u{FFFD}\u{FFFD}", "AMA"]))
+}
+
+async fn node() -> MockNode {
+    MockNode::start(|request, _| match request.path.as_str() {
+        "/api/chain/tx/transfer" => (200, transfer()),
+        "/api/chain/tx/garbled" => (200, garbled()),
+        path if path.starts_with("/api/chain/tx_events_by_account/") => (200, json!({ "txs": [transfer(), garbled()] })),
+        _ => (404, json!({ "error": "not_found" })),
+    })
+    .await
+}
+
+#[tokio::test]
+async fn calls_are_decoded_in_lookups_and_history() {
+    let node = node().await;
+    let (mut client, _running) = McpClient::connect(common::server(common::config(&[("BLOCKCHAIN_URL", &node.url)]))).await;
+
+    let tx = client.call_ok("get_transaction", json!({ "tx_hash": "transfer" })).await;
+    assert_eq!(
+        tx["decoded_action"],
+        json!({
+            "contract": "Coin",
+            "function": "transfer",
+            "args": [
+                { "type": "address", "value": RECEIVER },
+                { "type": "integer", "value": "1000000000" },
+                { "type": "utf8", "value": "AMA" }
+            ]
+        }),
+        "{}",
+        tx
+    );
+    // The node's own form stays alongside
+    assert_eq!(tx["tx"]["action"]["args"][0], RECEIVER);
+
+    let history = client.call_ok("get_transaction_history", json!({ "address": SIGNER })).await;
+    assert_eq!(history[0]["decoded_action"], tx["decoded_action"], "{}", history);
+    assert_eq!(history[1]["decoded_action"]["args"][1]["type"], "raw");
+}
+
+#[tokio::test]
+async fn an_undecodable_call_is_returned_raw_with_a_note() {
+    let node = node().await;
+    let (mut client, _running) = McpClient::connect(common::server(common::config(&[("BLOCKCHAIN_URL", &node.url)]))).await;
+
+    let tx = client.call_ok("get_transaction", json!({ "tx_hash": "garbled" })).await;
+    let decoded = &tx["decoded_action"];
+    assert_eq!(decoded["args"][1], json!({ "type": "raw", "value": "\u{FFFD}\u{FFFD}" }), "{}", tx);
+    assert_eq!(decoded["decode_error"], "argument 1 is neither text nor base58");
+    assert_eq!(decoded["args"][0]["type"], "address");
+}
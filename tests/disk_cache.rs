@@ -0,0 +1,76 @@
+//! The disk cache answers finalized lookups across a restart, and only finalized responses
+//! are written to it.
+
+mod common;
+
+use amadeus_mcp::blockchain::disk_cache::DiskCache;
+use amadeus_mcp::BlockchainClient;
+use common::MockNode;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+
+/// An entry at `height`, finalized or not.
+fn entry(height: u64, finalized: bool) -> Value {
+    json!({
+        "hash": format!("entry-{}", height),
+        "header": {
+            "slot": height, "height": height, "dr": "dr", "vr": "vr", "prev_hash": "prev", "signer": "signer",
+            "root_tx": "rtx", "root_validator": "rv", "prev_slot": height - 1
+        },
+        "tx_count": 0,
+        "consensus": { "score": 1.0, "finality_reached": finalized, "mut_hash": "mut" }
+    })
+}
+
+/// Heights up to 100 are finalized; above that they are not.
+async fn node() -> MockNode {
+    MockNode::start(|request, _| match request.path.strip_prefix("/api/chain/height/").and_then(|h| h.parse::<u64>().ok()) {
+        Some(height) => (200, json!({ "error": "ok", "entries": [entry(height, height <= 100)] })),
+        None => (404, json!({ "error": "not_found" })),
+    })
+    .await
+}
+
+fn cache_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("amadeus-mcp-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn client(dir: &Path, node: &MockNode) -> BlockchainClient {
+    BlockchainClient::new(node.url.clone()).unwrap().with_disk_cache(DiskCache::open(dir, 1 << 20).unwrap())
+}
+
+fn gets(node: &MockNode, height: u64) -> usize {
+    node.requests().iter().filter(|r| r.method == "GET" && r.path == format!("/api/chain/height/{}", height)).count()
+}
+
+#[tokio::test]
+async fn finalized_entries_are_served_from_disk_after_a_restart() {
+    let node = node().await;
+    let dir = cache_dir("disk-cache-restart");
+
+    let first = client(&dir, &node).get_block_by_height(90, &node.url).await.unwrap();
+    assert_eq!(gets(&node, 90), 1);
+
+    // A new client, as after a restart, reads the same directory
+    let second = client(&dir, &node).get_block_by_height(90, &node.url).await.unwrap();
+    assert_eq!(gets(&node, 90), 1);
+    assert_eq!(serde_json::to_value(&first).unwrap(), serde_json::to_value(&second).unwrap());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn unfinalized_entries_are_always_fetched() {
+    let node = node().await;
+    let dir = cache_dir("disk-cache-unfinalized");
+
+    let client = client(&dir, &node);
+    client.get_block_by_height(150, &node.url).await.unwrap();
+    client.get_block_by_height(150, &node.url).await.unwrap();
+    assert_eq!(gets(&node, 150), 2);
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,185 @@
+//! Submit confirmation over elicitation: a client that approves, declines, cancels or never
+//! answers, and the servers and clients that skip the prompt.
+
+mod common;
+
+use amadeus_mcp::blockchain::TransactionRequest;
+use amadeus_mcp::BlockchainClient;
+use common::{McpClient, MockNode};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+const SIGNER: &str = "29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw";
+const RECEIVER: &str = "42KfUgyU2XHCPwDD6g9gryCvtj9vqJAvM6h3Yzs3VRbuyT2JCHwvuk5iAbzXrioerf";
+const SUBMIT: &str = "/api/tx/submit";
+const CALL_ID: u64 = 900;
+
+/// Submit arguments for a signed transfer. The blob is built outside the server so the submit
+/// skips the freshness check and the node only sees the broadcast.
+async fn transfer() -> Value {
+    let request: TransactionRequest = serde_json::from_value(json!({
+        "signer": SIGNER,
+        "contract": "Coin",
+        "function": "transfer",
+        "args": [{ "b58": RECEIVER }, "1000000000", "AMA"],
+        "nonce": 1_718_000_000_000_000_000i64
+    }))
+    .unwrap();
+    let built = BlockchainClient::new(String::new()).unwrap().create_transaction_blob(request).await.unwrap();
+    // The node is scripted, so any well-formed signature will do
+    json!({ "transaction": built.blob, "signature": bs58::encode([7u8; 96]).into_string() })
+}
+
+async fn node() -> MockNode {
+    MockNode::start(|request, _| match request.path.as_str() {
+        SUBMIT => (200, json!({ "error": "ok" })),
+        _ => (404, json!({ "error": "not_found" })),
+    })
+    .await
+}
+
+fn submits(node: &MockNode) -> usize {
+    node.requests().iter().filter(|r| r.method == "POST" && r.path == SUBMIT).count()
+}
+
+async fn connect(node: &MockNode, settings: &[(&str, &str)], capabilities: Value) -> (McpClient, impl Sized) {
+    let mut settings = settings.to_vec();
+    settings.push(("BLOCKCHAIN_URL", &node.url));
+    McpClient::connect_with(common::server(common::config(&settings)), capabilities).await
+}
+
+async fn start_submit(client: &mut McpClient) {
+    let arguments = transfer().await;
+    client
+        .send(json!({
+            "jsonrpc": "2.0",
+            "id": CALL_ID,
+            "method": "tools/call",
+            "params": { "name": "submit_transaction", "arguments": arguments }
+        }))
+        .await;
+}
+
+/// The elicitation request the server sends while the submit is pending.
+async fn prompt(client: &mut McpClient) -> Value {
+    loop {
+        let frame = client.next().await;
+        assert!(frame["id"] != CALL_ID || frame.get("method").is_some(), "submitted without a prompt: {}", frame);
+        if frame["method"] == "elicitation/create" {
+            return frame;
+        }
+    }
+}
+
+async fn answer(client: &mut McpClient, prompt: &Value, result: Value) {
+    client.send(json!({ "jsonrpc": "2.0", "id": prompt["id"], "result": result })).await;
+}
+
+/// The response to the submit, waiting up to `limit`.
+async fn submit_reply(client: &mut McpClient, limit: Duration) -> Value {
+    loop {
+        let frame = client.next_within(limit).await;
+        if frame["id"] == CALL_ID && frame.get("method").is_none() {
+            return frame;
+        }
+    }
+}
+
+fn not_confirmed_reason(reply: &Value) -> &str {
+    assert_eq!(reply["error"]["message"], "submission_not_confirmed", "{}", reply);
+    assert_eq!(reply["error"]["data"]["message"], "nothing was submitted");
+    reply["error"]["data"]["reason"].as_str().unwrap()
+}
+
+#[tokio::test]
+async fn an_approved_prompt_submits() {
+    let node = node().await;
+    let (mut client, _running) = connect(&node, &[], json!({ "elicitation": {} })).await;
+
+    start_submit(&mut client).await;
+    let prompt = prompt(&mut client).await;
+    let message = prompt["params"]["message"].as_str().unwrap();
+    assert!(message.starts_with("Submit this transaction to mainnet?"), "{}", message);
+    assert!(message.contains(&format!("Signer: {}", SIGNER)), "{}", message);
+    assert!(message.contains(&format!("to {}", RECEIVER)), "{}", message);
+    assert_eq!(prompt["params"]["requestedSchema"]["required"], json!(["confirm"]));
+    assert_eq!(submits(&node), 0, "submitted before the user answered");
+
+    answer(&mut client, &prompt, json!({ "action": "accept", "content": { "confirm": true } })).await;
+    let reply = submit_reply(&mut client, Duration::from_secs(10)).await;
+    assert_eq!(reply["result"]["structuredContent"]["status"], "success", "{}", reply);
+    assert_eq!(submits(&node), 1);
+}
+
+#[tokio::test]
+async fn a_declined_or_cancelled_prompt_submits_nothing() {
+    let node = node().await;
+    let (mut client, _running) = connect(&node, &[], json!({ "elicitation": {} })).await;
+
+    for (result, reason) in [
+        (json!({ "action": "decline" }), "declined"),
+        // Accepting the form with the box unticked is a decline too
+        (json!({ "action": "accept", "content": { "confirm": false } }), "declined"),
+        (json!({ "action": "cancel" }), "cancelled"),
+    ] {
+        start_submit(&mut client).await;
+        let prompt = prompt(&mut client).await;
+        answer(&mut client, &prompt, result).await;
+        let reply = submit_reply(&mut client, Duration::from_secs(10)).await;
+        assert_eq!(not_confirmed_reason(&reply), reason);
+    }
+    assert_eq!(submits(&node), 0);
+}
+
+#[tokio::test]
+async fn an_accept_without_the_confirmation_submits_nothing() {
+    let node = node().await;
+    let (mut client, _running) = connect(&node, &[], json!({ "elicitation": {} })).await;
+
+    // Only an explicit confirm=true approves; an empty or missing form is not consent
+    for result in [
+        json!({ "action": "accept", "content": {} }),
+        json!({ "action": "accept" }),
+        json!({ "action": "accept", "content": { "confirm": "yes" } }),
+    ] {
+        start_submit(&mut client).await;
+        let prompt = prompt(&mut client).await;
+        answer(&mut client, &prompt, result).await;
+        let reply = submit_reply(&mut client, Duration::from_secs(10)).await;
+        assert_eq!(not_confirmed_reason(&reply), "declined");
+    }
+    assert_eq!(submits(&node), 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn an_unanswered_prompt_times_out() {
+    let node = node().await;
+    let (mut client, _running) = connect(&node, &[], json!({ "elicitation": {} })).await;
+
+    start_submit(&mut client).await;
+    prompt(&mut client).await;
+    // The paused clock jumps ahead to the prompt deadline once everything is idle
+    let reply = submit_reply(&mut client, Duration::from_secs(300)).await;
+    assert_eq!(not_confirmed_reason(&reply), "timeout");
+    assert_eq!(submits(&node), 0);
+}
+
+#[tokio::test]
+async fn clients_without_elicitation_are_not_prompted() {
+    let node = node().await;
+    let (mut client, _running) = connect(&node, &[], json!({})).await;
+    let reply = client.call_ok("submit_transaction", transfer().await).await;
+    assert_eq!(reply["status"], "success");
+    assert_eq!(client.count("elicitation/create"), 0);
+    assert_eq!(submits(&node), 1);
+}
+
+#[tokio::test]
+async fn the_prompt_can_be_turned_off() {
+    let node = node().await;
+    let (mut client, _running) = connect(&node, &[("MCP_ELICIT_CONFIRM", "0")], json!({ "elicitation": {} })).await;
+    let reply = client.call_ok("submit_transaction", transfer().await).await;
+    assert_eq!(reply["status"], "success");
+    assert_eq!(client.count("elicitation/create"), 0);
+    assert_eq!(submits(&node), 1);
+}
@@ -0,0 +1,90 @@
+//! A host server that embeds the Amadeus tools next to its own, delegating to a
+//! `BlockchainMcpServer` as the crate docs describe.
+
+mod common;
+
+use amadeus_mcp::{catalog, BlockchainMcpServer};
+use common::{McpClient, MockNode};
+use rmcp::{model::*, service::RequestContext, ErrorData as McpError, RoleServer, ServerHandler};
+use serde_json::json;
+use std::sync::Arc;
+
+#[derive(Clone)]
+struct Host {
+    amadeus: BlockchainMcpServer,
+}
+
+impl ServerHandler for Host {
+    fn get_info(&self) -> ServerInfo {
+        self.amadeus.get_info()
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, McpError> {
+        let mut tools = self.amadeus.tools();
+        tools.push(Tool::new("ping", "Replies with pong", Arc::new(Default::default())));
+        Ok(ListToolsResult::with_all_items(tools))
+    }
+
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        if self.amadeus.has_tool(&request.name) {
+            return self.amadeus.call_tool(request, context).await;
+        }
+        match request.name.as_ref() {
+            "ping" => Ok(CallToolResult::success(vec![Content::text("pong")])),
+            _ => Err(McpError::invalid_params("unknown_tool", None)),
+        }
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, McpError> {
+        if BlockchainMcpServer::has_resource(&request.uri) {
+            return self.amadeus.read_resource(request, context).await;
+        }
+        Err(McpError::invalid_params("unknown_resource", None))
+    }
+}
+
+#[tokio::test]
+async fn host_lists_and_routes_both_tool_sets() {
+    let node = MockNode::start(|request, _| match request.path.as_str() {
+        "/api/chain/stats" => (200, json!({ "error": "ok", "stats": { "height": 42 } })),
+        _ => (404, json!({ "error": "not_found" })),
+    })
+    .await;
+    let amadeus = common::server(common::config(&[("BLOCKCHAIN_URL", &node.url)]));
+    let embedded = amadeus.tools().len();
+    let (mut client, running) = McpClient::connect(Host { amadeus }).await;
+
+    let names = client.tool_names().await;
+    assert_eq!(names.len(), embedded + 1);
+    for name in ["ping", "get_chain_stats", "get_capabilities"] {
+        assert!(names.contains(&name.to_string()), "{} missing from {:?}", name, names);
+    }
+
+    let pong = client.call("ping", json!({})).await;
+    assert_eq!(pong["result"]["content"][0]["text"], "pong");
+    assert_eq!(client.call_ok("get_chain_stats", json!({})).await["height"], 42);
+    assert_eq!(node.requests().iter().filter(|r| r.method == "GET" && r.path == "/api/chain/stats").count(), 1);
+
+    let unknown = client.call("no_such_tool", json!({})).await;
+    assert_eq!(unknown["error"]["code"], -32602);
+
+    let uri = format!("{}get_chain_stats", catalog::TOOL_DOCS_URI);
+    let doc = client.request("resources/read", json!({ "uri": uri })).await;
+    assert!(doc["result"]["contents"][0]["text"].as_str().unwrap().starts_with("Retrieves current blockchain statistics"));
+    let foreign = client.request("resources/read", json!({ "uri": "host://other" })).await;
+    assert_eq!(foreign["error"]["message"], "unknown_resource");
+
+    let _ = running.cancel().await;
+}
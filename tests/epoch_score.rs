@@ -0,0 +1,37 @@
+//! get_epoch_score returns ranked scores, and an address the node has no score for is an
+//! invalid_params error rather than an opaque upstream failure.
+
+mod common;
+
+use common::{McpClient, MockNode};
+use serde_json::json;
+
+const VALIDATOR: &str = "29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw";
+const OTHER: &str = "42KfUgyU2XHCPwDD6g9gryCvtj9vqJAvM6h3Yzs3VRbuyT2JCHwvuk5iAbzXrioerf";
+
+async fn node() -> MockNode {
+    MockNode::start(|request, _| match request.path.as_str() {
+        "/api/epoch/score" => (200, json!({ "error": "ok", "scores": [[OTHER, "3.5"], [VALIDATOR, 7]] })),
+        path if path == format!("/api/epoch/score/{}", VALIDATOR) => (200, json!({ "error": "ok", "score": 7 })),
+        _ => (200, json!({ "error": "not_found" })),
+    })
+    .await
+}
+
+#[tokio::test]
+async fn scores_are_ranked_and_non_validators_are_rejected() {
+    let node = node().await;
+    let (mut client, _running) = McpClient::connect(common::server(common::config(&[("BLOCKCHAIN_URL", &node.url)]))).await;
+
+    let all = client.call_ok("get_epoch_score", json!({})).await;
+    assert_eq!(all["count"], 2, "{}", all);
+    assert_eq!(all["scores"][0], json!({ "public_key": VALIDATOR, "score": 7.0, "rank": 1 }));
+    assert_eq!(all["scores"][1]["rank"], 2);
+
+    let one = client.call_ok("get_epoch_score", json!({ "address": VALIDATOR })).await;
+    assert_eq!(one["scores"], json!([{ "public_key": VALIDATOR, "score": 7.0, "rank": 1 }]));
+
+    let missing = client.call("get_epoch_score", json!({ "address": OTHER })).await;
+    assert_eq!(missing["error"]["code"], -32602, "{}", missing);
+    assert!(missing["error"].to_string().contains("has no score in the current epoch"), "{}", missing);
+}
@@ -0,0 +1,125 @@
+//! Retrying submit_transaction with an idempotency key: an accepted submit is replayed
+//! without broadcasting again, a rejected one is attempted again, and a key reused for a
+//! different transaction is refused.
+
+mod common;
+
+use common::{McpClient, MockNode};
+use serde_json::{json, Value};
+
+const SIGNER: &str = "29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw";
+const RECEIVER: &str = "42KfUgyU2XHCPwDD6g9gryCvtj9vqJAvM6h3Yzs3VRbuyT2JCHwvuk5iAbzXrioerf";
+const SUBMIT: &str = "/api/tx/submit";
+
+const NONCE: i64 = 1_718_000_000_000_000_000;
+
+async fn signed(client: &mut McpClient, key: &str) -> Value {
+    signed_with_nonce(client, key, NONCE).await
+}
+
+async fn signed_with_nonce(client: &mut McpClient, key: &str, nonce: i64) -> Value {
+    let built = client
+        .call_ok(
+            "create_transaction",
+            json!({
+                "signer": SIGNER,
+                "contract": "Coin",
+                "function": "transfer",
+                "args": [{ "b58": RECEIVER }, "1000000000", "AMA"],
+                "nonce": nonce
+            }),
+        )
+        .await;
+    // The node is scripted, so any well-formed signature will do
+    json!({ "transaction": built["blob"], "signature": bs58::encode([7u8; 96]).into_string(), "idempotency_key": key })
+}
+
+fn submits(node: &MockNode) -> usize {
+    node.requests().iter().filter(|r| r.method == "POST" && r.path == SUBMIT).count()
+}
+
+#[tokio::test]
+async fn a_retry_after_success_is_replayed_without_broadcasting() {
+    let node = MockNode::start(|request, _| match request.path.as_str() {
+        SUBMIT => (200, json!({ "error": "ok" })),
+        _ => (404, json!({ "error": "not_found" })),
+    })
+    .await;
+    let server = common::server(common::config(&[("BLOCKCHAIN_URL", &node.url)]));
+    let (mut client, running) = McpClient::connect(server).await;
+
+    let submit = signed(&mut client, "retry-1").await;
+    let first = client.call_ok("submit_transaction", submit.clone()).await;
+    assert_eq!(first["status"], "success");
+    assert!(first.get("replayed").is_none());
+
+    let second = client.call_ok("submit_transaction", submit.clone()).await;
+    assert_eq!(second["replayed"], true);
+    assert_eq!(second["tx_hash"], first["tx_hash"]);
+    assert_eq!(submits(&node), 1);
+
+    // A different key is a different submit
+    let mut other = submit;
+    other["idempotency_key"] = json!("retry-2");
+    assert!(client.call_ok("submit_transaction", other).await.get("replayed").is_none());
+    assert_eq!(submits(&node), 2);
+
+    let _ = running.cancel().await;
+}
+
+#[tokio::test]
+async fn a_retry_after_failure_is_attempted_again() {
+    // The node rejects the first broadcast and accepts the second
+    let node = MockNode::start(|request, earlier| match (request.path.as_str(), earlier) {
+        (SUBMIT, 0) => (200, json!({ "error": "tx_nonce_too_low" })),
+        (SUBMIT, _) => (200, json!({ "error": "ok" })),
+        _ => (404, json!({ "error": "not_found" })),
+    })
+    .await;
+    let server = common::server(common::config(&[("BLOCKCHAIN_URL", &node.url)]));
+    let (mut client, running) = McpClient::connect(server).await;
+
+    let submit = signed(&mut client, "retry-1").await;
+    let rejected = client.call("submit_transaction", submit.clone()).await;
+    assert!(rejected.get("error").is_some(), "{}", rejected);
+
+    let retried = client.call_ok("submit_transaction", submit.clone()).await;
+    assert_eq!(retried["status"], "success");
+    assert!(retried.get("replayed").is_none());
+    assert_eq!(submits(&node), 2);
+
+    // Now that it was accepted, the next retry replays
+    assert_eq!(client.call_ok("submit_transaction", submit).await["replayed"], true);
+    assert_eq!(submits(&node), 2);
+
+    let _ = running.cancel().await;
+}
+
+#[tokio::test]
+async fn a_key_reused_for_another_transaction_is_refused() {
+    let node = MockNode::start(|request, _| match request.path.as_str() {
+        SUBMIT => (200, json!({ "error": "ok" })),
+        _ => (404, json!({ "error": "not_found" })),
+    })
+    .await;
+    let server = common::server(common::config(&[("BLOCKCHAIN_URL", &node.url)]));
+    let (mut client, running) = McpClient::connect(server).await;
+
+    let submit = signed(&mut client, "retry-1").await;
+    assert_eq!(client.call_ok("submit_transaction", submit.clone()).await["status"], "success");
+
+    let other = signed_with_nonce(&mut client, "retry-1", NONCE + 1).await;
+    assert_ne!(other["transaction"], submit["transaction"]);
+    let refused = client.call("submit_transaction", other).await;
+    let error = &refused["error"];
+    assert_eq!(error["code"], -32602, "{}", refused);
+    assert_eq!(error["message"], "idempotency_key_reused");
+    assert_eq!(error["data"]["message"], "idempotency key reused with a different transaction");
+    assert_eq!(submits(&node), 1);
+
+    // The original transaction still replays under its key
+    assert_eq!(client.call_ok("submit_transaction", submit).await["replayed"], true);
+    assert_eq!(submits(&node), 1);
+
+    let _ = running.cancel().await;
+}
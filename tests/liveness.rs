@@ -0,0 +1,72 @@
+//! get_liveness and amadeus://status give the same verdict for the same tip.
+
+mod common;
+
+use common::{McpClient, MockNode};
+use serde_json::{json, Value};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
+
+fn entry(height: u64, millis: u64) -> Value {
+    json!({
+        "hash": format!("h{}", height),
+        "header": {
+            "slot": height, "height": height, "dr": "dr", "vr": "vr", "prev_hash": "prev", "signer": "signer",
+            "root_tx": "rtx", "root_validator": "rv", "prev_slot": height - 1, "timestamp": millis
+        },
+        "tx_count": 0
+    })
+}
+
+/// A node whose entries 1..=3 were produced `silence` seconds ago and two seconds apart.
+async fn node(silence: u64) -> MockNode {
+    let newest = now() - silence;
+    let millis = move |height: u64| (newest - (3 - height) * 2) * 1000;
+    MockNode::start(move |request, _| match request.path.as_str() {
+        "/api/chain/stats" => (200, json!({ "error": "ok", "stats": { "height": 3, "txs_per_sec": 1.5 } })),
+        "/api/chain/tip" => (200, json!({ "error": "ok", "entry": entry(3, millis(3)) })),
+        path => match path.strip_prefix("/api/chain/height/").and_then(|h| h.parse::<u64>().ok()) {
+            Some(height @ 1..=3) => (200, json!({ "error": "ok", "entries": [entry(height, millis(height))] })),
+            _ => (404, json!({ "error": "not_found" })),
+        },
+    })
+    .await
+}
+
+/// The status resource once the tip follower has published the tip.
+async fn status_after_first_tip(client: &mut McpClient) -> Value {
+    for _ in 0..200 {
+        let reply = client.request("resources/read", json!({ "uri": "amadeus://status" })).await;
+        let status: Value = serde_json::from_str(reply["result"]["contents"][0]["text"].as_str().unwrap()).unwrap();
+        if !status["tip"].is_null() {
+            return status;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("the tip follower never published the tip");
+}
+
+async fn verdicts(silence: u64) -> (Value, Value) {
+    let node = node(silence).await;
+    let settings = [("BLOCKCHAIN_URL", node.url.as_str()), ("MCP_TIP_REFRESH_SECS", "60")];
+    let (mut client, _running) = McpClient::connect(common::server(common::config(&settings))).await;
+    let status = status_after_first_tip(&mut client).await;
+    let liveness = client.call_ok("get_liveness", json!({ "blocks": 3 })).await;
+    assert_eq!(liveness["entries_sampled"], 3, "{}", liveness);
+    assert_eq!(liveness["longest_gap_secs"], 2);
+    assert_eq!(liveness["txs_per_sec"], 1.5);
+    (liveness["verdict"].clone(), status["liveness"]["verdict"].clone())
+}
+
+#[tokio::test]
+async fn a_producing_chain_is_healthy_everywhere() {
+    assert_eq!(verdicts(0).await, (json!("healthy"), json!("healthy")));
+}
+
+#[tokio::test]
+async fn a_silent_chain_is_stalled_everywhere() {
+    assert_eq!(verdicts(120).await, (json!("stalled"), json!("stalled")));
+}
@@ -0,0 +1,87 @@
+//! A node that lacks some endpoints: the startup probes find them, the tools that need
+//! them are marked unavailable, and the status resource reports the capability set.
+
+mod common;
+
+use common::{McpClient, MockNode};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+const MISSING: [&str; 2] = ["/api/contract/richlist", "/api/chain/height_with_txs/1"];
+
+/// Whether the scripted node lacks the endpoint serving `path`.
+fn lacks(path: &str) -> bool {
+    path.starts_with("/api/contract/richlist") || path.starts_with("/api/chain/height_with_txs/")
+}
+
+async fn status(client: &mut McpClient) -> Value {
+    let reply = client.request("resources/read", json!({ "uri": "amadeus://status" })).await;
+    serde_json::from_str(reply["result"]["contents"][0]["text"].as_str().unwrap()).unwrap()
+}
+
+#[tokio::test]
+async fn tools_behind_missing_endpoints_are_marked_unavailable() {
+    let node = MockNode::start(|request, _| {
+        match lacks(&request.path) {
+            true => (404, json!({ "error": "not_found" })),
+            false => (200, json!({ "error": "ok" })),
+        }
+    })
+    .await;
+    let server = common::server(common::config(&[("BLOCKCHAIN_URL", &node.url)]));
+    let (mut client, running) = McpClient::connect(server).await;
+
+    let mut capabilities = Value::Null;
+    for _ in 0..100 {
+        capabilities = status(&mut client).await["node_capabilities"].clone();
+        if capabilities["probed"] == true {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(capabilities["missing_endpoints"], json!(["/api/chain/height_with_txs/", "/api/contract/richlist"]));
+    assert_eq!(capabilities["unavailable_tools"]["get_richlist"], "/api/contract/richlist");
+    assert_eq!(capabilities["unavailable_tools"]["get_block_with_txs"], "/api/chain/height_with_txs/");
+    assert!(capabilities["unavailable_tools"].get("get_chain_stats").is_none());
+
+    // Probes are cheap HEADs
+    let probes: Vec<_> = node.requests().into_iter().filter(|r| MISSING.contains(&r.path.as_str())).collect();
+    assert!(!probes.is_empty() && probes.iter().all(|r| r.method == "HEAD"));
+
+    let mut descriptions = std::collections::HashMap::new();
+    let mut page = client.request("tools/list", json!({})).await;
+    loop {
+        for tool in page["result"]["tools"].as_array().unwrap() {
+            descriptions.insert(tool["name"].as_str().unwrap().to_string(), tool["description"].as_str().unwrap().to_string());
+        }
+        let cursor = page["result"]["nextCursor"].clone();
+        if cursor.is_null() {
+            break;
+        }
+        page = client.request("tools/list", json!({ "cursor": cursor })).await;
+    }
+    assert!(descriptions["get_richlist"].starts_with("[Unavailable: the node does not support /api/contract/richlist]"));
+    assert!(descriptions["get_block_with_txs"].starts_with("[Unavailable: the node does not support /api/chain/height_with_txs/]"));
+    assert!(!descriptions["get_chain_stats"].starts_with("[Unavailable"));
+
+    let _ = running.cancel().await;
+}
+
+#[tokio::test]
+async fn a_missing_endpoint_fails_once_instead_of_retrying_404s() {
+    let node = MockNode::start(|request, _| match lacks(&request.path) {
+        true => (404, json!({ "error": "not_found" })),
+        false => (200, json!({ "error": "ok" })),
+    })
+    .await;
+    let server = common::server(common::config(&[("BLOCKCHAIN_URL", &node.url)]));
+    let (mut client, running) = McpClient::connect(server).await;
+
+    let reply = client.call("get_richlist", json!({})).await;
+    assert_eq!(reply["error"]["message"], "endpoint_unsupported", "{}", reply);
+    assert_eq!(reply["error"]["data"]["endpoint"], "/api/contract/richlist");
+    let gets = node.requests().iter().filter(|r| r.method == "GET" && r.path.starts_with("/api/contract/richlist")).count();
+    assert!(gets <= 1, "{} GETs for a missing endpoint", gets);
+
+    let _ = running.cancel().await;
+}
@@ -0,0 +1,85 @@
+//! MCP_OFFLINE=1: a whole session (initialize, tools/list, tools/call) answered from a
+//! fixture directory with no node to reach.
+
+mod common;
+
+use amadeus_mcp::blockchain::TransactionRequest;
+use amadeus_mcp::BlockchainClient;
+use common::McpClient;
+use serde_json::json;
+use std::path::PathBuf;
+
+const ADDRESS: &str = "29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw";
+
+/// A fixture directory with chain stats and balances, in the format `fixtures refresh`
+/// writes, removed when the test ends.
+struct Fixtures(PathBuf);
+
+impl Fixtures {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("amadeus-mcp-offline-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        for (scenario, response) in [
+            ("stats", json!({ "error": "ok", "stats": { "height": "<height>", "tx_pool_size": "<metric>" } })),
+            ("balances", json!({ "error": "ok", "balances": [{ "symbol": "AMA", "flat": 5_000_000_000u64, "float": 5.0 }] })),
+        ] {
+            std::fs::write(dir.join(format!("{}.json", scenario)), response.to_string()).unwrap();
+        }
+        Self(dir)
+    }
+}
+
+impl Drop for Fixtures {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+async fn connect(fixtures: &Fixtures) -> (McpClient, impl Sized) {
+    let dir = fixtures.0.to_string_lossy().to_string();
+    // Nothing listens on the discard port, so any request that escaped offline mode would fail
+    let settings = [("BLOCKCHAIN_URL", "http://127.0.0.1:9"), ("MCP_OFFLINE", "1"), ("MCP_OFFLINE_DIR", dir.as_str())];
+    McpClient::connect(common::server(common::config(&settings))).await
+}
+
+#[tokio::test]
+async fn a_session_runs_entirely_from_fixtures() {
+    let fixtures = Fixtures::new("session");
+    let (mut client, _running) = connect(&fixtures).await;
+
+    assert!(client.tool_names().await.iter().any(|name| name == "get_chain_stats"));
+
+    let stats = client.call_ok("get_chain_stats", json!({})).await;
+    assert_eq!(stats["height"], 1_000_000, "{}", stats);
+    // The same fixture gives the same answer on every call
+    let again = client.call_ok("get_chain_stats", json!({})).await;
+    assert_eq!(again["height"], stats["height"]);
+
+    let balance = client.call_ok("get_account_balance", json!({ "address": ADDRESS })).await;
+    assert_eq!(balance["balances"][0]["symbol"], "AMA", "{}", balance);
+}
+
+#[tokio::test]
+async fn reads_without_a_fixture_and_submits_fail_with_offline_mode() {
+    let fixtures = Fixtures::new("gaps");
+    let (mut client, _running) = connect(&fixtures).await;
+
+    let reply = client.call("get_richlist", json!({})).await;
+    assert_eq!(reply["error"]["message"], "offline_mode", "{}", reply);
+    assert!(reply["error"]["data"]["message"].as_str().unwrap().starts_with("no fixture in"));
+
+    let request: TransactionRequest = serde_json::from_value(json!({
+        "signer": ADDRESS,
+        "contract": "Coin",
+        "function": "transfer",
+        "args": [{ "b58": ADDRESS }, "1000000000", "AMA"],
+        "nonce": 1_718_000_000_000_000_000i64
+    }))
+    .unwrap();
+    let built = BlockchainClient::new(String::new()).unwrap().create_transaction_blob(request).await.unwrap();
+    let signature = bs58::encode([7u8; 96]).into_string();
+    let reply = client.call("submit_transaction", json!({ "transaction": built.blob, "signature": signature })).await;
+    assert_eq!(reply["error"]["message"], "offline_mode", "{}", reply);
+    assert_eq!(reply["error"]["data"]["message"], "transactions cannot be submitted");
+}
@@ -0,0 +1,69 @@
+//! preview_contract_call against a node that returns a result, one that reverts, and one
+//! without the read-only call endpoint.
+
+mod common;
+
+use common::{McpClient, MockNode};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+const ADDRESS: &str = "29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw";
+
+fn call(function: &str) -> Value {
+    json!({ "contract": "Coin", "function": function, "args": [{ "b58": ADDRESS }, "AMA"] })
+}
+
+fn views(node: &MockNode) -> Vec<common::NodeRequest> {
+    node.requests().into_iter().filter(|r| r.method == "POST" && r.path.starts_with("/api/contract/view/")).collect()
+}
+
+#[tokio::test]
+async fn a_call_returns_its_result_or_revert_reason() {
+    let node = MockNode::start(|request, _| match request.path.as_str() {
+        "/api/contract/view/Coin/balance" => (200, json!({ "error": "ok", "result": "5000000000" })),
+        "/api/contract/view/Coin/transfer" => (200, json!({ "error": "insufficient_funds" })),
+        _ => (200, json!({ "error": "ok" })),
+    })
+    .await;
+    let server = common::server(common::config(&[("BLOCKCHAIN_URL", &node.url)]));
+    let (mut client, _running) = McpClient::connect(server).await;
+
+    let ok = client.call_ok("preview_contract_call", call("balance")).await;
+    assert_eq!((&ok["status"], &ok["result"]), (&json!("ok"), &json!("5000000000")));
+
+    let reverted = client.call_ok("preview_contract_call", call("transfer")).await;
+    assert_eq!((&reverted["status"], &reverted["reason"]), (&json!("reverted"), &json!("insufficient_funds")));
+    assert!(reverted.get("result").is_none());
+
+    // The arguments are encoded as create_transaction encodes them
+    let sent: Value = serde_json::from_str(&views(&node)[0].body).unwrap();
+    assert_eq!(sent, json!({ "args": [ADDRESS, bs58::encode(b"AMA").into_string()] }));
+}
+
+#[tokio::test]
+async fn a_node_without_the_endpoint_marks_the_tool_unavailable() {
+    let node = MockNode::start(|request, _| match request.path.starts_with("/api/contract/view") {
+        true => (404, json!({ "error": "not_found" })),
+        false => (200, json!({ "error": "ok" })),
+    })
+    .await;
+    let server = common::server(common::config(&[("BLOCKCHAIN_URL", &node.url)]));
+    let (mut client, _running) = McpClient::connect(server).await;
+
+    let mut description = String::new();
+    for _ in 0..100 {
+        let tools = client.tools().await;
+        let preview = tools.iter().find(|t| t["name"] == "preview_contract_call");
+        description = preview.and_then(|t| t["description"].as_str()).unwrap_or_default().to_string();
+        if description.starts_with("[Unavailable") {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert!(description.starts_with("[Unavailable: the node does not support /api/contract/view]"), "{}", description);
+
+    let reply = client.call("preview_contract_call", call("balance")).await;
+    assert_eq!(reply["error"]["message"], "endpoint_unsupported", "{}", reply);
+    assert_eq!(reply["error"]["data"]["endpoint"], "/api/contract/view");
+    assert!(views(&node).len() <= 1);
+}
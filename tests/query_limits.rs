@@ -0,0 +1,68 @@
+//! MCP_QUERY_LIMITS=small: each limit turns away an argument above it before the node is
+//! asked, and get_capabilities reports the effective limits.
+
+mod common;
+
+use common::{McpClient, MockNode};
+use serde_json::{json, Value};
+
+const ADDRESS: &str = "29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw";
+
+async fn small_node_client(overrides: &[(&str, &str)]) -> (McpClient, MockNode, impl Sized) {
+    let node = MockNode::start(|_, _| (404, json!({ "error": "not_found" }))).await;
+    let mut settings = vec![("BLOCKCHAIN_URL", node.url.as_str()), ("MCP_QUERY_LIMITS", "small")];
+    settings.extend_from_slice(overrides);
+    let (client, running) = McpClient::connect(common::server(common::config(&settings))).await;
+    (client, node, running)
+}
+
+/// Calls `tool` and checks it failed on `argument` without reaching the node.
+async fn assert_exceeds(client: &mut McpClient, node: &MockNode, tool: &str, arguments: Value, argument: &str, max: u64) {
+    let before = node.requests().iter().filter(|r| r.method != "HEAD").count();
+    let reply = client.call(tool, arguments).await;
+    assert_eq!(reply["error"]["message"], "limit_exceeded", "{}: {}", tool, reply);
+    assert_eq!(reply["error"]["data"]["argument"], argument, "{}", tool);
+    assert_eq!(reply["error"]["data"]["max"], max, "{}", tool);
+    let after = node.requests().iter().filter(|r| r.method != "HEAD").count();
+    assert_eq!(before, after, "{} reached the node", tool);
+}
+
+#[tokio::test]
+async fn each_limit_turns_away_a_larger_argument() {
+    let (mut client, node, _running) = small_node_client(&[]).await;
+    let cases = [
+        ("get_transaction_history", json!({ "address": ADDRESS, "limit": 51 }), "limit", 50),
+        ("get_signer_distribution", json!({ "blocks": 101 }), "blocks", 100),
+        ("export_contract_state", json!({ "contract_address": "Coin", "limit": 101 }), "limit", 100),
+        ("get_richlist", json!({ "limit": 51 }), "limit", 50),
+        ("get_full_transaction_history", json!({ "address": ADDRESS, "max_transactions": 1001 }), "max_transactions", 1000),
+        ("get_block_by_timestamp", json!({ "timestamp": 1_700_000_000, "max_probes": 33 }), "max_probes", 32),
+    ];
+    for (tool, arguments, argument, max) in cases {
+        assert_exceeds(&mut client, &node, tool, arguments, argument, max).await;
+    }
+}
+
+#[tokio::test]
+async fn capabilities_report_the_effective_limits() {
+    let (mut client, _node, _running) = small_node_client(&[("MCP_MAX_BLOCK_SPAN", "5")]).await;
+    let capabilities = client.call_ok("get_capabilities", json!({})).await;
+    assert_eq!(
+        capabilities["queryLimits"],
+        json!({
+            "max_history_limit": 50,
+            "max_block_span": 5,
+            "max_batch_calls": 10,
+            "max_contract_keys": 100,
+            "max_richlist_limit": 50,
+            "max_full_history": 1000,
+            "max_time_probes": 32
+        })
+    );
+}
+
+#[tokio::test]
+async fn an_override_tightens_one_limit() {
+    let (mut client, node, _running) = small_node_client(&[("MCP_MAX_BLOCK_SPAN", "5")]).await;
+    assert_exceeds(&mut client, &node, "get_liveness", json!({ "blocks": 6 }), "blocks", 5).await;
+}
@@ -0,0 +1,104 @@
+//! Address redaction (`MCP_REDACT_ADDRESSES`): counterparties come back as tokens, and a
+//! token passed to a later call reaches the node as the real address. Resources are
+//! redacted the same way.
+
+mod common;
+
+use common::{McpClient, MockNode};
+use serde_json::{json, Value};
+
+fn address(byte: u8) -> String {
+    bs58::encode([byte; 48]).into_string()
+}
+
+fn transfer(signer: &str, to: &str) -> Value {
+    json!({
+        "hash": "5Hb4vXnPp1nA6uE3JzLHqzFZ8zjMbZK8ZTKZqYxjG1aQ",
+        "metadata": { "entry_hash": "4Kz8f9LDqx7GzpM2a3n2ZbCqQ6hJ8Y9sW1tX5rV3uE7d", "entry_height": 100 },
+        "signature": "sig",
+        "result": { "error": "ok" },
+        "tx": {
+            "action": { "args": [to, "1000", "AMA"], "function": "transfer", "op": "call", "contract": "Coin" },
+            "nonce": 1,
+            "signer": signer
+        },
+        "receipt": { "success": true, "result": "ok", "logs": [], "exec_used": "0" }
+    })
+}
+
+/// A node whose every account history holds one transfer from the owner to `counterparty`.
+async fn node(owner: String, counterparty: String) -> MockNode {
+    MockNode::start(move |request, _| {
+        if request.path.starts_with("/api/chain/tx_events_by_account/") {
+            (200, json!({ "error": "ok", "txs": [transfer(&owner, &counterparty)] }))
+        } else {
+            (404, json!({ "error": "not_found" }))
+        }
+    })
+    .await
+}
+
+async fn round_trip(mode: &str) {
+    let (owner, counterparty) = (address(1), address(2));
+    let node = node(owner.clone(), counterparty.clone()).await;
+    let server = common::server(common::config(&[("BLOCKCHAIN_URL", &node.url), ("MCP_REDACT_ADDRESSES", mode)]));
+    let (mut client, running) = McpClient::connect(server).await;
+
+    let reply = client.call("get_transaction_history", json!({ "address": owner })).await;
+    let text = reply.to_string();
+    assert!(!text.contains(&counterparty), "{} leaked the counterparty: {}", mode, text);
+    // The queried address is the caller's own and stays readable
+    assert!(text.contains(&owner), "{}", text);
+
+    let history = client.call_ok("get_transaction_history", json!({ "address": owner })).await;
+    let token = history[0]["tx"]["action"]["args"][0].as_str().expect("redacted counterparty").to_string();
+    assert_ne!(token, counterparty);
+
+    // Following up on the token asks the node about the real account
+    client.call_ok("get_transaction_history", json!({ "address": token })).await;
+    let paths: Vec<_> = node.requests().into_iter().map(|r| r.path).collect();
+    assert!(
+        paths.iter().any(|p| p.starts_with(&format!("/api/chain/tx_events_by_account/{}", counterparty))),
+        "{:?}",
+        paths
+    );
+
+    let _ = running.cancel().await;
+}
+
+#[tokio::test]
+async fn hashed_token_resolves_in_a_follow_up_call() {
+    round_trip("hash").await;
+}
+
+#[tokio::test]
+async fn partial_token_resolves_in_a_follow_up_call() {
+    round_trip("partial").await;
+}
+
+#[tokio::test]
+async fn resources_are_redacted_like_tool_outputs() {
+    let validator = address(3);
+    let key = validator.clone();
+    let node = MockNode::start(move |request, _| match request.path.as_str() {
+        "/api/peer/trainers" => (200, json!({ "error": "ok", "trainers": [key] })),
+        _ => (404, json!({ "error": "not_found" })),
+    })
+    .await;
+    let server = common::server(common::config(&[("BLOCKCHAIN_URL", &node.url), ("MCP_REDACT_ADDRESSES", "hash")]));
+    let (mut client, running) = McpClient::connect(server).await;
+
+    let reply = client.request("resources/read", json!({ "uri": "amadeus://validators" })).await;
+    let text = reply["result"]["contents"][0]["text"].as_str().unwrap_or_else(|| panic!("{}", reply));
+    assert!(!text.contains(&validator), "the resource leaked a validator key: {}", text);
+    let resource: Value = serde_json::from_str(text).unwrap();
+    let token = resource["validators"][0]["pk"].as_str().unwrap().to_string();
+    assert!(token.starts_with("addr:"), "{}", token);
+
+    // A token read from a resource resolves in a tool call like one from a tool output
+    client.call("get_transaction_history", json!({ "address": token })).await;
+    let paths: Vec<_> = node.requests().into_iter().map(|r| r.path).collect();
+    assert!(paths.iter().any(|p| p.starts_with(&format!("/api/chain/tx_events_by_account/{}", validator))), "{:?}", paths);
+
+    let _ = running.cancel().await;
+}
@@ -0,0 +1,48 @@
+//! Reloading the configuration, as on SIGHUP: a valid configuration is applied and an
+//! invalid one leaves the current configuration in place.
+
+use amadeus_mcp::{BlockchainClient, BlockchainMcpServer, ServerConfig};
+use std::collections::HashMap;
+
+/// A configuration from `settings`, with nothing left running against the network.
+fn load(settings: &[(&str, &str)]) -> Result<ServerConfig, String> {
+    let mut settings: HashMap<String, String> =
+        settings.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect();
+    settings.entry("MCP_TIP_REFRESH_SECS".to_string()).or_insert_with(|| "0".to_string());
+    ServerConfig::try_from_lookup(|key| settings.get(key).cloned())
+}
+
+fn server() -> BlockchainMcpServer {
+    let config = load(&[("BLOCKCHAIN_URL", "http://127.0.0.1:9")]).unwrap();
+    let client = BlockchainClient::from_config(&config).unwrap();
+    BlockchainMcpServer::new(client, config)
+}
+
+#[tokio::test]
+async fn valid_then_invalid_reload_keeps_the_valid_config() {
+    let server = server();
+    assert!(server.has_tool("get_richlist"));
+
+    let valid = || load(&[("BLOCKCHAIN_URL", "http://127.0.0.2:9"), ("MCP_TOOLS_DENY", "get_richlist")]);
+    server.reload_with(valid, None).await.unwrap();
+    assert_eq!(server.config().mainnet_url, "http://127.0.0.2:9");
+    assert!(!server.has_tool("get_richlist"));
+    let version = server.tools_version();
+
+    let invalid = || load(&[("BLOCKCHAIN_URL", "http://127.0.0.3:9"), ("MCP_MAX_HISTORY_LIMIT", "0")]);
+    let err = server.reload_with(invalid, None).await.unwrap_err();
+    assert!(err.contains("MCP_MAX_HISTORY_LIMIT"), "{}", err);
+    assert_eq!(server.config().mainnet_url, "http://127.0.0.2:9");
+    assert!(!server.has_tool("get_richlist"));
+    assert_eq!(server.tools_version(), version);
+}
+
+#[tokio::test]
+async fn config_that_fails_to_apply_is_not_swapped_in() {
+    let server = server();
+    let missing_fixtures = || load(&[("MCP_OFFLINE", "1"), ("MCP_OFFLINE_DIR", "/nonexistent/amadeus-fixtures")]);
+    let err = server.reload_with(missing_fixtures, None).await.unwrap_err();
+    assert!(err.starts_with("failed to apply configuration"), "{}", err);
+    assert_eq!(server.config().mainnet_url, "http://127.0.0.1:9");
+    assert!(!server.config().offline);
+}
@@ -0,0 +1,71 @@
+//! Range hints on the validators resource: slices, default limits, out-of-range offsets and
+//! resources that are not lists.
+
+mod common;
+
+use common::{McpClient, MockNode};
+use serde_json::{json, Value};
+
+const VALIDATORS: usize = 250;
+
+async fn node() -> MockNode {
+    MockNode::start(|request, _| match request.path.as_str() {
+        "/api/peer/trainers" => {
+            let keys: Vec<String> = (0..VALIDATORS).map(|i| format!("trainer-{:03}", i)).collect();
+            (200, json!({ "error": "ok", "trainers": keys }))
+        }
+        // Without scores the resource lists the keys alone
+        _ => (404, json!({ "error": "not_found" })),
+    })
+    .await
+}
+
+async fn read(client: &mut McpClient, uri: &str) -> Value {
+    client.request("resources/read", json!({ "uri": uri })).await
+}
+
+fn contents(reply: &Value) -> Value {
+    let text = reply["result"]["contents"][0]["text"].as_str().unwrap_or_else(|| panic!("no contents: {}", reply));
+    serde_json::from_str(text).unwrap()
+}
+
+fn keys(resource: &Value) -> Vec<&str> {
+    resource["validators"].as_array().unwrap().iter().map(|v| v["pk"].as_str().unwrap()).collect()
+}
+
+#[tokio::test]
+async fn validators_are_read_in_slices() {
+    let node = node().await;
+    let server = common::server(common::config(&[("BLOCKCHAIN_URL", &node.url)]));
+    let (mut client, _running) = McpClient::connect(server).await;
+
+    let whole = contents(&read(&mut client, "amadeus://validators").await);
+    assert_eq!(keys(&whole).len(), VALIDATORS);
+    assert!(whole.get("range").is_none());
+
+    let slice = contents(&read(&mut client, "amadeus://validators?offset=10&limit=5").await);
+    assert_eq!(keys(&slice), vec!["trainer-010", "trainer-011", "trainer-012", "trainer-013", "trainer-014"]);
+    assert_eq!(slice["range"], json!({ "offset": 10, "limit": 5, "total_available": VALIDATORS, "next_offset": 15 }));
+
+    // An offset alone gets the default limit
+    let default = contents(&read(&mut client, "amadeus://validators?offset=200").await);
+    assert_eq!(keys(&default).len(), 50);
+    assert_eq!(default["range"], json!({ "offset": 200, "limit": 100, "total_available": VALIDATORS, "next_offset": null }));
+
+    let past_end = contents(&read(&mut client, "amadeus://validators?offset=900&limit=10").await);
+    assert!(keys(&past_end).is_empty());
+    assert_eq!(past_end["range"]["total_available"], VALIDATORS);
+}
+
+#[tokio::test]
+async fn bad_ranges_and_ranges_on_other_resources_are_rejected() {
+    let node = node().await;
+    let server = common::server(common::config(&[("BLOCKCHAIN_URL", &node.url)]));
+    let (mut client, _running) = McpClient::connect(server).await;
+
+    for uri in ["amadeus://validators?limit=0", "amadeus://validators?page=2", "amadeus://status?offset=1"] {
+        let reply = read(&mut client, uri).await;
+        assert_eq!(reply["error"]["message"], "invalid_range", "{}: {}", uri, reply);
+        assert_eq!(reply["error"]["data"]["uri"], uri);
+    }
+}
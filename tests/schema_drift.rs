@@ -0,0 +1,68 @@
+//! With MCP_DRIFT_CHECK_SECS set, a node whose responses drifted from the parsed types
+//! shows up in amadeus://status as schema_drift, down to the field.
+
+mod common;
+
+use common::{McpClient, MockNode};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// A node that renamed the tip header's `height` to `block_height` and added a stats
+/// field; everything else matches.
+async fn drifted_node() -> MockNode {
+    MockNode::start(|request, _| match request.path.as_str() {
+        "/api/chain/stats" => (200, json!({ "error": "ok", "stats": { "height": 9, "new_metric": 1 } })),
+        "/api/chain/tip" => (200, json!({ "error": "ok", "entry": {
+            "hash": "h",
+            "header": {
+                "slot": 9, "block_height": 9, "dr": "dr", "vr": "vr", "prev_hash": "prev", "signer": "pk",
+                "root_tx": "rt", "root_validator": "rv", "prev_slot": 8
+            },
+            "tx_count": 0
+        } })),
+        "/api/peer/nodes" => (200, json!({ "error": "ok", "nodes": [{ "ip4": "1.2.3.4", "online": true }] })),
+        "/api/peer/trainers" => (200, json!({ "error": "ok", "trainers": ["pk"] })),
+        _ => (404, json!({ "error": "not_found" })),
+    })
+    .await
+}
+
+async fn drift_report(client: &mut McpClient) -> Value {
+    for _ in 0..200 {
+        let reply = client.request("resources/read", json!({ "uri": "amadeus://status" })).await;
+        let status: Value = serde_json::from_str(reply["result"]["contents"][0]["text"].as_str().unwrap()).unwrap();
+        if !status["schema_drift"].is_null() {
+            return status["schema_drift"].clone();
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("the drift check never reported");
+}
+
+#[tokio::test]
+async fn drifted_fields_are_reported_in_status() {
+    let node = drifted_node().await;
+    let settings = [("BLOCKCHAIN_URL", node.url.as_str()), ("MCP_DRIFT_CHECK_SECS", "60")];
+    let (mut client, _running) = McpClient::connect(common::server(common::config(&settings))).await;
+    let report = drift_report(&mut client).await;
+
+    let endpoint = |path: &str| report["endpoints"].as_array().unwrap().iter().find(|e| e["endpoint"] == path).cloned().unwrap();
+    let tip = endpoint("chain/tip");
+    assert_eq!(tip["status"], "broken", "{}", report);
+    assert_eq!(tip["missing"], json!(["entry.header.height"]));
+    assert_eq!(tip["unexpected"], json!(["entry.header.block_height"]));
+    assert_eq!(endpoint("chain/stats")["status"], "ok");
+    assert_eq!(endpoint("peer/trainers")["status"], "ok");
+    // Without a parsable tip there is no sample address for the account endpoints
+    assert_eq!(endpoint("wallet/balance_all/{address}")["status"], "unchecked");
+    assert_eq!(report["drifted"], 1);
+}
+
+#[tokio::test]
+async fn status_has_no_report_when_the_check_is_off() {
+    let node = drifted_node().await;
+    let (mut client, _running) = McpClient::connect(common::server(common::config(&[("BLOCKCHAIN_URL", &node.url)]))).await;
+    let reply = client.request("resources/read", json!({ "uri": "amadeus://status" })).await;
+    let status: Value = serde_json::from_str(reply["result"]["contents"][0]["text"].as_str().unwrap()).unwrap();
+    assert!(status["schema_drift"].is_null(), "{}", status);
+}
@@ -0,0 +1,67 @@
+//! transfer_from_server_key is listed only with AMADEUS_MCP_SIGNING_KEY set and outside
+//! read-only mode, submits a transfer signed by that key, and never shows the key.
+
+mod common;
+
+use common::{McpClient, MockNode, SpanCapture};
+use serde_json::json;
+use tracing_subscriber::layer::SubscriberExt;
+
+const RECEIVER: &str = "42KfUgyU2XHCPwDD6g9gryCvtj9vqJAvM6h3Yzs3VRbuyT2JCHwvuk5iAbzXrioerf";
+const SUBMIT: &str = "/api/tx/submit";
+const TOOL: &str = "transfer_from_server_key";
+
+fn secret() -> String {
+    bs58::encode([7u8; 64]).into_string()
+}
+
+async fn listed(settings: &[(&str, &str)]) -> bool {
+    let node = MockNode::start(|_, _| (404, json!({ "error": "not_found" }))).await;
+    let settings: Vec<(&str, &str)> = [("BLOCKCHAIN_URL", node.url.as_str())].into_iter().chain(settings.iter().copied()).collect();
+    let (mut client, _running) = McpClient::connect(common::server(common::config(&settings))).await;
+    client.tool_names().await.iter().any(|name| name == TOOL)
+}
+
+#[tokio::test]
+async fn the_tool_is_listed_only_with_a_key_and_writes_allowed() {
+    let secret = secret();
+    assert!(!listed(&[]).await);
+    assert!(listed(&[("AMADEUS_MCP_SIGNING_KEY", &secret)]).await);
+    assert!(!listed(&[("AMADEUS_MCP_SIGNING_KEY", &secret), ("MCP_READ_ONLY", "1")]).await);
+}
+
+#[tokio::test]
+async fn a_transfer_is_signed_by_the_server_key_and_submitted() {
+    let capture = SpanCapture::default();
+    let _guard = tracing::subscriber::set_default(tracing_subscriber::registry().with(capture.clone()));
+
+    let node = MockNode::start(|request, _| match request.path.as_str() {
+        SUBMIT => (200, json!({ "error": "ok" })),
+        _ => (404, json!({ "error": "not_found" })),
+    })
+    .await;
+    let secret = secret();
+    let settings = [("BLOCKCHAIN_URL", node.url.as_str()), ("AMADEUS_MCP_SIGNING_KEY", &secret)];
+    let (mut client, _running) = McpClient::connect(common::server(common::config(&settings))).await;
+
+    let sent = client.call_ok(TOOL, json!({ "destination": RECEIVER, "amount": 1_500_000_000u64 })).await;
+    assert_eq!((sent["status"].as_str(), sent["symbol"].as_str()), (Some("success"), Some("AMA")), "{}", sent);
+    assert_eq!((sent["destination"].as_str(), sent["amount"].as_str()), (Some(RECEIVER), Some("1500000000")));
+
+    let submits: Vec<_> = node.requests().into_iter().filter(|r| r.method == "POST" && r.path == SUBMIT).collect();
+    assert_eq!(submits.len(), 1);
+    let decoded = client.call_ok("decode_transaction", json!({ "blob": submits[0].body })).await;
+    assert_eq!((&decoded["kind"], &decoded["signer"]), (&json!("signed"), &sent["from"]), "{}", decoded);
+    assert_eq!(decoded["transaction_hash"], sent["tx_hash"]);
+    assert_eq!(decoded["hash_matches"], true);
+
+    let bad = client.call(TOOL, json!({ "destination": "nope", "amount": 1 })).await;
+    assert_eq!(bad["error"]["message"], "invalid_destination", "{}", bad);
+
+    // The key shows up in no reply, request or span
+    assert!(client.seen.iter().all(|frame| !frame.to_string().contains(&secret)));
+    assert!(node.requests().iter().all(|r| !r.body.contains(&secret) && !r.path.contains(&secret)));
+    for span in capture.spans() {
+        assert!(span.fields.values().all(|value| !value.contains(&secret)), "{:?}", span.fields);
+    }
+}
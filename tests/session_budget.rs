@@ -0,0 +1,61 @@
+//! MCP_SESSION_REQUEST_LIMIT: tool calls stop once the session has used its upstream
+//! requests, list methods keep working, and the counts show in amadeus://status.
+
+mod common;
+
+use common::{McpClient, MockNode};
+use serde_json::{json, Value};
+
+async fn node() -> MockNode {
+    MockNode::start(|request, _| match request.path.as_str() {
+        "/api/chain/stats" => (200, json!({ "error": "ok", "stats": { "height": 100, "tx_pool_size": 0 } })),
+        _ => (404, json!({ "error": "not_found" })),
+    })
+    .await
+}
+
+fn gets(node: &MockNode) -> usize {
+    node.requests().iter().filter(|r| r.method == "GET").count()
+}
+
+async fn session_budget(client: &mut McpClient) -> Value {
+    let reply = client.request("resources/read", json!({ "uri": "amadeus://status" })).await;
+    let text = reply["result"]["contents"][0]["text"].as_str().unwrap_or_else(|| panic!("no contents: {}", reply));
+    serde_json::from_str::<Value>(text).unwrap()["session_budget"].clone()
+}
+
+#[tokio::test]
+async fn tool_calls_stop_once_the_budget_is_used() {
+    let node = node().await;
+    let settings = [("BLOCKCHAIN_URL", node.url.as_str()), ("MCP_SESSION_REQUEST_LIMIT", "2")];
+    let (mut client, _running) = McpClient::connect(common::server(common::config(&settings))).await;
+
+    client.call_ok("get_chain_stats", json!({})).await;
+    client.call_ok("get_chain_stats", json!({ "network": "mainnet" })).await;
+    let used = gets(&node);
+    let usage = session_budget(&mut client).await;
+    assert_eq!((&usage["limit"], &usage["exhausted"]), (&json!(2), &json!(true)), "{}", usage);
+    assert_eq!(usage["total"], json!(used));
+
+    let reply = client.call("get_chain_stats", json!({})).await;
+    assert_eq!(reply["error"]["message"], "session_budget_exhausted", "{}", reply);
+    let message = reply["error"]["data"]["message"].as_str().unwrap();
+    assert!(message.contains("Stop calling tools and summarize"), "{}", message);
+    assert_eq!(reply["error"]["data"]["usage"]["total"], json!(used));
+    assert_eq!(gets(&node), used, "a refused call must not reach the node");
+
+    // Listing still works so the agent can see what happened
+    assert!(!client.tool_names().await.is_empty());
+}
+
+#[tokio::test]
+async fn sessions_are_unlimited_by_default() {
+    let node = node().await;
+    let (mut client, _running) = McpClient::connect(common::server(common::config(&[("BLOCKCHAIN_URL", &node.url)]))).await;
+    for _ in 0..5 {
+        client.call_ok("get_chain_stats", json!({})).await;
+    }
+    let usage = session_budget(&mut client).await;
+    assert_eq!(usage["exhausted"], false);
+    assert!(usage.get("limit").is_none() && usage.get("limit_per_hour").is_none(), "{}", usage);
+}
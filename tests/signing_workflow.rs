@@ -0,0 +1,34 @@
+//! amadeus://docs/signing-workflow is listed, and its example blob decodes through
+//! decode_transaction to what the doc says it is.
+
+mod common;
+
+use common::McpClient;
+use serde_json::json;
+
+const URI: &str = "amadeus://docs/signing-workflow";
+
+fn field<'a>(doc: &'a str, label: &str) -> &'a str {
+    doc.lines().find_map(|line| line.trim().strip_prefix(label)).unwrap_or_else(|| panic!("no {} in {}", label, doc)).trim()
+}
+
+#[tokio::test]
+async fn the_example_blob_decodes_to_the_documented_transfer() {
+    let server = common::server(common::config(&[("BLOCKCHAIN_URL", "http://127.0.0.1:9")]));
+    let (mut client, _running) = McpClient::connect(server).await;
+
+    let listed = client.request("resources/list", json!({})).await;
+    let resource = listed["result"]["resources"].as_array().unwrap().iter().find(|r| r["uri"] == URI).cloned();
+    assert_eq!(resource.expect("listed")["mimeType"], "text/plain");
+
+    let read = client.request("resources/read", json!({ "uri": URI })).await;
+    let doc = read["result"]["contents"][0]["text"].as_str().unwrap_or_else(|| panic!("{}", read)).to_string();
+    assert!(doc.starts_with("Signing workflow: create_transaction -> sign locally -> submit_transaction"));
+
+    let decoded = client.call_ok("decode_transaction", json!({ "blob": field(&doc, "blob:") })).await;
+    assert_eq!((&decoded["kind"], &decoded["contract"], &decoded["function"]), (&json!("unsigned"), &json!("Coin"), &json!("transfer")));
+    assert_eq!(decoded["signer"], bs58::encode([0u8; 48]).into_string());
+    assert_eq!(decoded["args"][1], json!({ "encoding": "utf8", "value": "0" }));
+    assert_eq!(decoded["nonce"], field(&doc, "nonce:"));
+    assert_eq!(decoded["signing_hash"], field(&doc, "signing_payload:"));
+}
@@ -0,0 +1,204 @@
+//! Span tree of a nested aggregate call, and address redaction in span fields. Kept apart
+//! from the other tracing tests since the span redaction switch is process-wide.
+
+mod common;
+
+use common::{CapturedSpan, McpClient, MockNode, SpanCapture};
+use serde_json::{json, Value};
+use tracing_subscriber::layer::SubscriberExt;
+
+fn address(byte: u8) -> String {
+    bs58::encode([byte; 48]).into_string()
+}
+
+fn transfer(signer: &str, to: &str) -> Value {
+    json!({
+        "hash": "5Hb4vXnPp1nA6uE3JzLHqzFZ8zjMbZK8ZTKZqYxjG1aQ",
+        "metadata": { "entry_hash": "4Kz8f9LDqx7GzpM2a3n2ZbCqQ6hJ8Y9sW1tX5rV3uE7d", "entry_height": 100 },
+        "signature": "sig",
+        "result": { "error": "ok" },
+        "tx": {
+            "action": { "args": [to, "1000", "AMA"], "function": "transfer", "op": "call", "contract": "Coin" },
+            "nonce": 1,
+            "signer": signer
+        },
+        "receipt": { "success": true, "result": "ok", "logs": [], "exec_used": "0" }
+    })
+}
+
+/// An entry at `height` produced by `signer`, as `/api/chain/height/{height}` lists it.
+fn entry(height: u64, signer: &str) -> Value {
+    json!({
+        "hash": format!("entry{}", height),
+        "header": {
+            "slot": height, "height": height, "dr": "dr", "vr": "vr", "prev_hash": "prev", "signer": signer,
+            "root_tx": "rtx", "root_validator": "rv", "prev_slot": height - 1, "timestamp": 1_700_000_000u64 + height
+        },
+        "tx_count": 1
+    })
+}
+
+/// Whether `span` sits anywhere below `ancestor`.
+fn descends_from(spans: &[CapturedSpan], span: &CapturedSpan, ancestor: &CapturedSpan) -> bool {
+    let mut parent = span.parent;
+    while let Some(id) = parent {
+        if id == ancestor.id {
+            return true;
+        }
+        parent = spans.iter().rev().find(|s| s.id == id).and_then(|s| s.parent);
+    }
+    false
+}
+
+/// Fails on any captured span field that holds one of `addresses` whole.
+fn assert_no_full_address(capture: &SpanCapture, addresses: &[&str]) {
+    for span in capture.spans() {
+        for (name, value) in &span.fields {
+            for address in addresses {
+                assert!(!value.contains(address), "{}.{} records a full address: {}", span.name, name, value);
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn an_aggregate_call_nests_its_requests_and_records_no_full_address() {
+    let capture = SpanCapture::default();
+    let _guard = tracing::subscriber::set_default(tracing_subscriber::registry().with(capture.clone()));
+
+    let (a, b) = (address(1), address(2));
+    let (from, to) = (a.clone(), b.clone());
+    let node = MockNode::start(move |request, _| match request.path.as_str() {
+        "/api/chain/tip" => (200, json!({ "error": "ok", "entry": {
+            "hash": "tip",
+            "header": {
+                "slot": 100, "height": 100, "dr": "dr", "vr": "vr", "prev_hash": "prev", "signer": "signer",
+                "root_tx": "rtx", "root_validator": "rv", "prev_slot": 99
+            },
+            "tx_count": 0
+        } })),
+        path if path.starts_with("/api/wallet/balance_all/") => {
+            (200, json!({ "error": "ok", "balances": [{ "symbol": "AMA", "flat": 5_000_000_000u64, "float": 5.0 }] }))
+        }
+        path if path.starts_with("/api/chain/tx_events_by_account/") => {
+            (200, json!({ "error": "ok", "txs": [transfer(&from, &to)] }))
+        }
+        _ => (404, json!({ "error": "not_found" })),
+    })
+    .await;
+    let settings = [("BLOCKCHAIN_URL", node.url.as_str()), ("MCP_REDACT_ADDRESSES", "partial")];
+    let (mut client, running) = McpClient::connect(common::server(common::config(&settings))).await;
+
+    let investigation = client.call_ok("investigate_addresses", json!({ "address_a": a, "address_b": b })).await;
+    assert_eq!(investigation["address_a"]["transactions_examined"], 1, "{}", investigation);
+
+    let spans = capture.spans();
+    let calls = capture.named("tool_call");
+    assert_eq!(calls.len(), 1);
+    let call = &calls[0];
+    assert_eq!((call.fields["tool"].as_str(), call.fields["outcome"].as_str()), ("investigate_addresses", "ok"));
+
+    let methods = capture.children(call);
+    let method = methods.iter().find(|s| s.name == "investigate_addresses").expect("client method span under tool_call");
+    let children = capture.children(method);
+    let mut steps: Vec<&str> = children.iter().map(|s| s.name.as_str()).collect();
+    steps.sort_unstable();
+    steps.dedup();
+    assert!(steps.contains(&"get_account_balance") && steps.contains(&"get_transaction_history"), "{:?}", steps);
+
+    let requests: Vec<_> = spans.iter().filter(|s| s.name == "upstream_request" && descends_from(&spans, s, call)).collect();
+    let endpoints: Vec<&str> = requests.iter().map(|s| s.fields["endpoint"].as_str()).collect();
+    assert!(endpoints.iter().any(|e| e.starts_with("/api/wallet/balance_all/")), "{:?}", endpoints);
+    assert!(endpoints.iter().any(|e| e.starts_with("/api/chain/tx_events_by_account/")), "{:?}", endpoints);
+    // Every request sits under a client method span, never directly under the tool call
+    assert!(requests.iter().all(|s| s.parent != Some(call.id)));
+
+    // Addresses reach span fields cut to their ends, never whole
+    let shortened = format!("{}…{}", &a[..6], &a[a.len() - 6..]);
+    assert!(call.fields["arguments"].contains(&shortened), "{}", call.fields["arguments"]);
+    assert_no_full_address(&capture, &[&a, &b]);
+
+    let _ = running.cancel().await;
+}
+
+#[tokio::test]
+async fn a_filtered_history_records_no_full_address() {
+    let capture = SpanCapture::default();
+    let _guard = tracing::subscriber::set_default(tracing_subscriber::registry().with(capture.clone()));
+
+    let (a, b) = (address(3), address(4));
+    let (from, to) = (b.clone(), a.clone());
+    let node = MockNode::start(move |request, _| match request.path.as_str() {
+        path if path.starts_with("/api/chain/tx_events_by_account/") => {
+            (200, json!({ "error": "ok", "txs": [transfer(&from, &to)] }))
+        }
+        _ => (404, json!({ "error": "not_found" })),
+    })
+    .await;
+    let settings = [("BLOCKCHAIN_URL", node.url.as_str()), ("MCP_REDACT_ADDRESSES", "partial")];
+    let (mut client, running) = McpClient::connect(common::server(common::config(&settings))).await;
+
+    let arguments = json!({ "address": a, "direction": "received", "counterparty": b, "symbol": "AMA" });
+    let history = client.call_ok("get_transaction_history", arguments).await;
+    assert_eq!(history["transactions"].as_array().map(Vec::len), Some(1), "{}", history);
+    assert_eq!(capture.named("get_filtered_history").len(), 1);
+    assert_no_full_address(&capture, &[&a, &b]);
+
+    let _ = running.cancel().await;
+}
+
+#[tokio::test]
+async fn a_full_history_export_records_no_full_address() {
+    let capture = SpanCapture::default();
+    let _guard = tracing::subscriber::set_default(tracing_subscriber::registry().with(capture.clone()));
+
+    let (a, b) = (address(5), address(6));
+    let (from, to) = (a.clone(), b.clone());
+    let node = MockNode::start(move |request, _| match request.path.as_str() {
+        path if path.starts_with("/api/chain/tx_events_by_account/") => {
+            (200, json!({ "error": "ok", "txs": [transfer(&from, &to)] }))
+        }
+        "/api/chain/height/100" => (200, json!({ "error": "ok", "entries": [entry(100, "signer")] })),
+        _ => (404, json!({ "error": "not_found" })),
+    })
+    .await;
+    let settings = [("BLOCKCHAIN_URL", node.url.as_str()), ("MCP_REDACT_ADDRESSES", "partial")];
+    let (mut client, running) = McpClient::connect(common::server(common::config(&settings))).await;
+
+    let history = client.call_ok("get_full_transaction_history", json!({ "address": a })).await;
+    assert_eq!(history["count"], 1, "{}", history);
+    assert_eq!(capture.named("get_full_transaction_history").len(), 1);
+    assert_no_full_address(&capture, &[&a, &b]);
+
+    let _ = running.cancel().await;
+}
+
+#[tokio::test]
+async fn a_signer_scan_records_no_full_address() {
+    let capture = SpanCapture::default();
+    let _guard = tracing::subscriber::set_default(tracing_subscriber::registry().with(capture.clone()));
+
+    let (signer, other) = (address(7), address(8));
+    let (mine, theirs) = (signer.clone(), other.clone());
+    let node = MockNode::start(move |request, _| {
+        let height: Option<u64> = request.path.strip_prefix("/api/chain/height/").and_then(|h| h.parse().ok());
+        match height {
+            Some(height) => {
+                let by = if height % 2 == 0 { &mine } else { &theirs };
+                (200, json!({ "error": "ok", "entries": [entry(height, by)] }))
+            }
+            None => (404, json!({ "error": "not_found" })),
+        }
+    })
+    .await;
+    let settings = [("BLOCKCHAIN_URL", node.url.as_str()), ("MCP_REDACT_ADDRESSES", "partial")];
+    let (mut client, running) = McpClient::connect(common::server(common::config(&settings))).await;
+
+    let arguments = json!({ "signer": signer, "start_height": 10, "end_height": 13 });
+    let signed = client.call_ok("get_blocks_by_signer", arguments).await;
+    assert_eq!(signed["entries"].as_array().map(Vec::len), Some(2), "{}", signed);
+    assert_eq!(capture.named("entries_signed_by").len(), 1);
+    assert_no_full_address(&capture, &[&signer, &other]);
+
+    let _ = running.cancel().await;
+}
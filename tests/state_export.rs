@@ -0,0 +1,112 @@
+//! export_contract_state pages a fixture contract's storage through next_cursor, each
+//! page from a fresh server, with no key missed or repeated.
+
+mod common;
+
+use common::{McpClient, MockNode};
+use serde_json::{json, Value};
+
+const KEYS: usize = 23;
+
+/// The `after` query value of a prefix listing request, percent-decoded.
+fn after(path: &str) -> Option<String> {
+    let encoded = path.split_once("&after=")?.1;
+    let mut bytes = Vec::new();
+    let mut rest = encoded.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        if b == b'%' {
+            bytes.push(u8::from_str_radix(std::str::from_utf8(&tail[..2]).unwrap(), 16).unwrap());
+            rest = &tail[2..];
+        } else {
+            bytes.push(b);
+            rest = tail;
+        }
+    }
+    Some(String::from_utf8(bytes).unwrap())
+}
+
+fn limit(path: &str) -> usize {
+    let query = path.split_once("?limit=").unwrap().1;
+    query.split('&').next().unwrap().parse().unwrap()
+}
+
+/// A node holding `owner:00` .. `owner:22` and one key outside that prefix.
+async fn node() -> MockNode {
+    MockNode::start(|request, _| {
+        let Some(listing) = request.path.strip_prefix("/api/contract/get_prefix/Nft/owner:") else {
+            return (404, json!({ "error": "not_found" }));
+        };
+        if request.method == "HEAD" || !listing.starts_with('?') {
+            return (200, json!({}));
+        }
+        let after = after(&request.path);
+        let keys: Vec<Value> = (0..KEYS)
+            .map(|i| format!("owner:{:02}", i))
+            .filter(|key| after.as_deref().is_none_or(|a| key.as_str() > a))
+            .take(limit(&request.path))
+            .map(|key| json!({ "key": key, "value": "42KfUgyU2XHCPwDD6g9gryCvtj9vqJAvM6h3Yzs3VRbuyT2JCHwvuk5iAbzXrioerf" }))
+            .collect();
+        (200, json!({ "error": "ok", "result": keys }))
+    })
+    .await
+}
+
+/// Calls export_contract_state on a fresh server; `ok` unwraps a successful page.
+async fn export(node: &MockNode, arguments: Value, ok: bool) -> Value {
+    let (mut client, _running) = McpClient::connect(common::server(common::config(&[("BLOCKCHAIN_URL", &node.url)]))).await;
+    match ok {
+        true => client.call_ok("export_contract_state", arguments).await,
+        false => client.call("export_contract_state", arguments).await,
+    }
+}
+
+#[tokio::test]
+async fn cursors_resume_across_servers_without_gaps_or_duplicates() {
+    let node = node().await;
+    let mut exported = Vec::new();
+    let mut cursor = Value::Null;
+    let mut pages = 0;
+    loop {
+        let mut arguments = json!({ "contract_address": "Nft", "prefix": "owner:", "limit": 10 });
+        if !cursor.is_null() {
+            arguments["cursor"] = cursor.clone();
+        }
+        let page = export(&node, arguments, true).await;
+        pages += 1;
+        for key in page["keys"].as_array().unwrap() {
+            assert_eq!(key["type"], "address");
+            exported.push(key["key"].as_str().unwrap().to_string());
+        }
+        if page["complete"] == true {
+            assert!(page.get("next_cursor").is_none());
+            break;
+        }
+        cursor = page["next_cursor"].clone();
+    }
+    assert_eq!(pages, 3);
+    assert_eq!(exported, (0..KEYS).map(|i| format!("owner:{:02}", i)).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn csv_pages_carry_the_same_cursor() {
+    let node = node().await;
+    let page = export(&node, json!({ "contract_address": "Nft", "prefix": "owner:", "limit": 2, "format": "csv" }), true).await;
+    assert!(page.get("keys").is_none());
+    let csv = page["csv"].as_str().unwrap();
+    assert!(csv.starts_with("key,type,value\nowner:00,address,"), "{}", csv);
+    assert_eq!(csv.lines().count(), 3);
+    assert_eq!(page["complete"], false);
+    assert!(page["next_cursor"].is_string());
+}
+
+#[tokio::test]
+async fn a_cursor_from_another_export_is_refused() {
+    let node = node().await;
+    let first = export(&node, json!({ "contract_address": "Nft", "prefix": "owner:", "limit": 5 }), true).await;
+    let cursor = first["next_cursor"].clone();
+
+    let other = export(&node, json!({ "contract_address": "Nft", "prefix": "owner:1", "cursor": cursor }), false).await;
+    assert_eq!(other["error"]["message"], "invalid_cursor", "{}", other);
+    let garbage = export(&node, json!({ "contract_address": "Nft", "prefix": "owner:", "cursor": "not-hex" }), false).await;
+    assert_eq!(garbage["error"]["data"]["message"], "invalid cursor", "{}", garbage);
+}
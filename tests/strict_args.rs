@@ -0,0 +1,47 @@
+//! MCP_STRICT_ARGS rejects tool calls with undeclared arguments before they reach the node.
+
+mod common;
+
+use common::{McpClient, MockNode};
+use serde_json::json;
+
+async fn node() -> MockNode {
+    MockNode::start(|request, _| match request.path.as_str() {
+        "/api/contract/richlist" => {
+            (200, json!({ "error": "ok", "richlist": [{ "address": "holder", "flat": 1_000_000_000u64, "float": 1.0 }] }))
+        }
+        _ => (404, json!({ "error": "not_found" })),
+    })
+    .await
+}
+
+fn gets(node: &MockNode) -> usize {
+    node.requests().iter().filter(|r| r.method == "GET").count()
+}
+
+#[tokio::test]
+async fn strict_mode_lists_unknown_arguments_with_suggestions() {
+    let node = node().await;
+    let server = common::server(common::config(&[("BLOCKCHAIN_URL", &node.url), ("MCP_STRICT_ARGS", "1")]));
+    let (mut client, _running) = McpClient::connect(server).await;
+
+    let reply = client.call("get_richlist", json!({ "limt": 5, "denomination": "AMA" })).await;
+    assert_eq!(reply["error"]["message"], "unknown_arguments", "{}", reply);
+    assert_eq!(reply["error"]["data"]["tool"], "get_richlist");
+    assert_eq!(
+        reply["error"]["data"]["unknown"],
+        json!([{ "name": "denomination" }, { "name": "limt", "did_you_mean": "limit" }])
+    );
+    assert_eq!(gets(&node), 0);
+
+    // Declared arguments still go through
+    assert_eq!(client.call_ok("get_richlist", json!({ "limit": 5, "network": "mainnet" })).await["total"], 1);
+}
+
+#[tokio::test]
+async fn unknown_arguments_are_ignored_by_default() {
+    let node = node().await;
+    let server = common::server(common::config(&[("BLOCKCHAIN_URL", &node.url)]));
+    let (mut client, _running) = McpClient::connect(server).await;
+    assert_eq!(client.call_ok("get_richlist", json!({ "limt": 5, "denomination": "AMA" })).await["total"], 1);
+}
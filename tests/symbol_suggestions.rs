@@ -0,0 +1,55 @@
+//! create_transaction refuses a transfer of a symbol the signer does not hold when a held
+//! symbol is a near match.
+
+mod common;
+
+use common::{McpClient, MockNode};
+use serde_json::{json, Value};
+
+const SIGNER: &str = "29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw";
+const RECEIVER: &str = "42KfUgyU2XHCPwDD6g9gryCvtj9vqJAvM6h3Yzs3VRbuyT2JCHwvuk5iAbzXrioerf";
+
+fn transfer(symbol: &str) -> Value {
+    json!({
+        "signer": SIGNER,
+        "contract": "Coin",
+        "function": "transfer",
+        "args": [{ "b58": RECEIVER }, "1000000000", symbol],
+        "nonce": 1_718_000_000_000_000_000i64
+    })
+}
+
+#[tokio::test]
+async fn a_mistyped_symbol_names_the_held_one() {
+    let node = MockNode::start(|request, _| {
+        if request.path == format!("/api/wallet/balance_all/{}", SIGNER) {
+            (200, json!({ "error": "ok", "balances": [
+                { "symbol": "AMA", "flat": 5_000_000_000u64, "float": 5.0 },
+                { "symbol": "USDX", "flat": 10, "float": 0.00000001 }
+            ] }))
+        } else {
+            (404, json!({ "error": "not_found" }))
+        }
+    })
+    .await;
+    let server = common::server(common::config(&[("BLOCKCHAIN_URL", &node.url)]));
+    let (mut client, _running) = McpClient::connect(server).await;
+
+    let reply = client.call("create_transaction", transfer("ama")).await;
+    assert_eq!(reply["error"]["message"], "validation_failed", "{}", reply);
+    let message = reply["error"]["data"]["message"].as_str().unwrap();
+    assert!(message.contains("the signer holds no ama; did you mean AMA? Holdings: AMA, USDX"), "{}", message);
+
+    // A held symbol builds; one nothing held is close to fails on the balance, not as a typo
+    assert!(client.call_ok("create_transaction", transfer("AMA")).await["blob"].is_string());
+    let unrelated = client.call("create_transaction", transfer("NEWTOKEN")).await;
+    assert_eq!(unrelated["error"]["message"], "insufficient_balance", "{}", unrelated);
+}
+
+#[tokio::test]
+async fn an_unreachable_node_does_not_block_building() {
+    let node = MockNode::start(|_, _| (404, json!({ "error": "not_found" }))).await;
+    let server = common::server(common::config(&[("BLOCKCHAIN_URL", &node.url)]));
+    let (mut client, _running) = McpClient::connect(server).await;
+    assert!(client.call_ok("create_transaction", transfer("ama")).await["blob"].is_string());
+}
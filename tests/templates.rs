@@ -0,0 +1,59 @@
+//! list_tx_templates and amadeus://templates/{name} serve the same templates that
+//! create_transaction checks templated calls against.
+
+mod common;
+
+use common::{McpClient, MockNode};
+use serde_json::{json, Value};
+
+const SIGNER: &str = "29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw";
+const RECEIVER: &str = "42KfUgyU2XHCPwDD6g9gryCvtj9vqJAvM6h3Yzs3VRbuyT2JCHwvuk5iAbzXrioerf";
+
+fn transfer(args: Value) -> Value {
+    json!({ "signer": SIGNER, "contract": "Coin", "function": "transfer", "args": args })
+}
+
+#[tokio::test]
+async fn listed_and_served_templates_agree() {
+    let server = common::server(common::config(&[("BLOCKCHAIN_URL", "http://127.0.0.1:9")]));
+    let (mut client, _running) = McpClient::connect(server).await;
+
+    let listed = client.call_ok("list_tx_templates", json!({})).await;
+    let templates = listed["templates"].as_array().unwrap();
+    let names: Vec<&str> = templates.iter().map(|t| t["name"].as_str().unwrap()).collect();
+    assert_eq!(names, ["transfer", "set_emission_address"]);
+
+    for t in templates {
+        let uri = format!("amadeus://templates/{}", t["name"].as_str().unwrap());
+        let read = client.request("resources/read", json!({ "uri": uri })).await;
+        let text = read["result"]["contents"][0]["text"].as_str().unwrap_or_else(|| panic!("{}", read));
+        assert_eq!(&serde_json::from_str::<Value>(text).unwrap(), t);
+    }
+
+    let transfer = &templates[0];
+    let arg_names: Vec<&str> = transfer["args"].as_array().unwrap().iter().map(|a| a["name"].as_str().unwrap()).collect();
+    assert_eq!(arg_names, ["recipient", "amount", "symbol"]);
+    assert_eq!(transfer["example"]["args"][0], json!({ "b58": "<recipient>" }));
+}
+
+#[tokio::test]
+async fn create_transaction_checks_calls_against_the_template() {
+    let node = MockNode::start(|_, _| (404, json!({ "error": "not_found" }))).await;
+    let server = common::server(common::config(&[("BLOCKCHAIN_URL", &node.url)]));
+    let (mut client, _running) = McpClient::connect(server).await;
+
+    let swapped = client.call("create_transaction", transfer(json!(["1000000000", { "b58": RECEIVER }, "AMA"]))).await;
+    assert_eq!(swapped["error"]["message"], "validation_failed", "{}", swapped);
+
+    let plain = json!({ "signer": SIGNER, "contract": "Epoch", "function": "set_emission_address", "args": [RECEIVER] });
+    let plain = client.call("create_transaction", plain).await;
+    assert_eq!(plain["error"]["message"], "validation_failed", "{}", plain);
+    let message = plain["error"]["data"]["message"].as_str().unwrap();
+    assert!(message.contains("argument 0 (address) of Epoch.set_emission_address must be a 48-byte public key"), "{}", message);
+
+    let short = client.call("create_transaction", transfer(json!([{ "b58": RECEIVER }]))).await;
+    assert!(short["error"]["data"]["message"].as_str().unwrap().contains("see template transfer"), "{}", short);
+
+    let built = client.call_ok("create_transaction", transfer(json!([{ "b58": RECEIVER }, "1000000000", "AMA"]))).await;
+    assert!(built["blob"].is_string(), "{}", built);
+}
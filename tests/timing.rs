@@ -0,0 +1,59 @@
+//! The `timing` block of tool outputs counts this call's upstream requests, retries and
+//! cache hits.
+
+mod common;
+
+use common::{McpClient, MockNode};
+use serde_json::json;
+
+const RICHLIST: &str = "/api/contract/richlist";
+
+/// A node whose richlist fails once with a 502 and then answers.
+async fn node() -> MockNode {
+    MockNode::start(|request, earlier| match (request.path.as_str(), earlier) {
+        (RICHLIST, 0) => (502, json!({ "error": "bad gateway" })),
+        (RICHLIST, _) => (
+            200,
+            json!({ "error": "ok", "richlist": [
+                { "address": "holder-a", "flat": 3_000_000_000u64, "float": 3.0 },
+                { "address": "holder-b", "flat": 1_000_000_000u64, "float": 1.0 }
+            ] }),
+        ),
+        _ => (404, json!({ "error": "not_found" })),
+    })
+    .await
+}
+
+fn richlist_gets(node: &MockNode) -> usize {
+    node.requests().iter().filter(|r| r.method == "GET" && r.path == RICHLIST).count()
+}
+
+#[tokio::test]
+async fn a_retry_and_a_cache_hit_are_counted_per_call() {
+    let node = node().await;
+    let server = common::server(common::config(&[("BLOCKCHAIN_URL", &node.url)]));
+    let (mut client, _running) = McpClient::connect(server).await;
+
+    let first = client.call_ok("get_richlist", json!({})).await;
+    assert_eq!(first["entries"].as_array().map(Vec::len), Some(2), "{}", first);
+    let timing = &first["timing"];
+    assert_eq!((&timing["upstream_requests"], &timing["retries"], &timing["cache_hits"]), (&json!(2), &json!(1), &json!(0)));
+    assert!(timing["duration_ms"].is_u64());
+    assert_eq!(richlist_gets(&node), 2);
+
+    // The second call is served from the richlist cache without reaching the node
+    let second = client.call_ok("get_richlist", json!({ "offset": 1 })).await;
+    let timing = &second["timing"];
+    assert_eq!((&timing["upstream_requests"], &timing["retries"], &timing["cache_hits"]), (&json!(0), &json!(0), &json!(1)));
+    assert_eq!(richlist_gets(&node), 2);
+}
+
+#[tokio::test]
+async fn timing_can_be_turned_off() {
+    let node = node().await;
+    let server = common::server(common::config(&[("BLOCKCHAIN_URL", &node.url), ("MCP_INCLUDE_TIMING", "0")]));
+    let (mut client, _running) = McpClient::connect(server).await;
+    let output = client.call_ok("get_richlist", json!({})).await;
+    assert!(output.get("entries").is_some(), "{}", output);
+    assert!(output.get("timing").is_none(), "{}", output);
+}
@@ -0,0 +1,178 @@
+//! Every tool carries catalog examples, and each example validates against the tool's
+//! own input schema, so examples cannot rot as arguments change.
+
+mod common;
+
+use amadeus_mcp::catalog::{self, ToolFilter, TOOL_DOCS};
+use serde_json::{Map, Value};
+
+/// Validates `value` against the subset of JSON Schema that schemars emits for tool
+/// parameters, collecting one message per violation.
+fn validate(schema: &Value, value: &Value, root: &Value, path: &str, errors: &mut Vec<String>) {
+    let Some(schema) = schema.as_object() else {
+        return;
+    };
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        let target = reference
+            .strip_prefix('#')
+            .and_then(|pointer| root.pointer(pointer))
+            .unwrap_or_else(|| panic!("unresolved $ref {}", reference));
+        validate(target, value, root, path, errors);
+    }
+
+    if let Some(types) = schema.get("type") {
+        let types: Vec<&str> = match types {
+            Value::Array(types) => types.iter().filter_map(Value::as_str).collect(),
+            other => other.as_str().into_iter().collect(),
+        };
+        if !types.iter().any(|t| has_type(value, t)) {
+            errors.push(format!("{}: expected {:?}, got {}", path, types, value));
+            return;
+        }
+    }
+    if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+        if !options.contains(value) {
+            errors.push(format!("{}: {} is not one of {:?}", path, value, options));
+        }
+    }
+    if let Some(constant) = schema.get("const") {
+        if constant != value {
+            errors.push(format!("{}: expected {}", path, constant));
+        }
+    }
+
+    let bound = |key: &str| schema.get(key).and_then(Value::as_f64);
+    if let Some(n) = value.as_f64() {
+        if bound("minimum").is_some_and(|min| n < min) || bound("maximum").is_some_and(|max| n > max) {
+            errors.push(format!("{}: {} is out of range", path, n));
+        }
+    }
+    if let Some(s) = value.as_str() {
+        let len = s.chars().count() as f64;
+        if bound("minLength").is_some_and(|min| len < min) || bound("maxLength").is_some_and(|max| len > max) {
+            errors.push(format!("{}: length {} is out of range", path, len));
+        }
+    }
+
+    if let Some(object) = value.as_object() {
+        validate_object(schema, object, root, path, errors);
+    }
+    if let (Some(items), Some(array)) = (schema.get("items"), value.as_array()) {
+        for (i, item) in array.iter().enumerate() {
+            validate(items, item, root, &format!("{}[{}]", path, i), errors);
+        }
+    }
+
+    for (key, exactly_one) in [("anyOf", false), ("oneOf", true)] {
+        if let Some(options) = schema.get(key).and_then(Value::as_array) {
+            let matching = options
+                .iter()
+                .filter(|option| {
+                    let mut option_errors = Vec::new();
+                    validate(option, value, root, path, &mut option_errors);
+                    option_errors.is_empty()
+                })
+                .count();
+            if matching == 0 || (exactly_one && matching > 1) {
+                errors.push(format!("{}: {} of the {} alternatives match {}", path, matching, key, value));
+            }
+        }
+    }
+    if let Some(all) = schema.get("allOf").and_then(Value::as_array) {
+        for option in all {
+            validate(option, value, root, path, errors);
+        }
+    }
+}
+
+fn validate_object(schema: &Map<String, Value>, object: &Map<String, Value>, root: &Value, path: &str, errors: &mut Vec<String>) {
+    let properties = schema.get("properties").and_then(Value::as_object);
+    for required in schema.get("required").and_then(Value::as_array).into_iter().flatten() {
+        let required = required.as_str().unwrap();
+        if !object.contains_key(required) {
+            errors.push(format!("{}: missing required {}", path, required));
+        }
+    }
+    for (key, field) in object {
+        let field_path = format!("{}.{}", path, key);
+        match properties.and_then(|p| p.get(key)) {
+            Some(property) => validate(property, field, root, &field_path, errors),
+            None if schema.get("additionalProperties") == Some(&Value::Bool(false)) || properties.is_some() => {
+                errors.push(format!("{}: not a declared argument", field_path))
+            }
+            None => {}
+        }
+    }
+}
+
+fn has_type(value: &Value, expected: &str) -> bool {
+    match expected {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "string" => value.is_string(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        _ => true,
+    }
+}
+
+/// A server with every optional tool exposed.
+async fn all_tools() -> Vec<rmcp::model::Tool> {
+    let server = common::server(common::config(&[("BLOCKCHAIN_URL", "http://127.0.0.1:9")]));
+    let everything = ToolFilter { raw_query_allow: vec!["/api".to_string()], server_key: true, ..ToolFilter::default() };
+    server.set_tool_filter(everything, None).await;
+    server.tools()
+}
+
+#[tokio::test]
+async fn every_tool_has_examples_that_match_its_schema() {
+    let tools = all_tools().await;
+    assert!(tools.iter().any(|t| t.name == catalog::RAW_QUERY_TOOL));
+
+    let mut errors = Vec::new();
+    for tool in &tools {
+        let Some(doc) = catalog::tool_doc(&tool.name) else {
+            errors.push(format!("{} has no catalog entry", tool.name));
+            continue;
+        };
+        if doc.examples.is_empty() {
+            errors.push(format!("{} has no examples", tool.name));
+        }
+        let schema = Value::Object((*tool.input_schema).clone());
+        for (i, example) in doc.examples.iter().enumerate() {
+            match serde_json::from_str::<Value>(example) {
+                Ok(example) => validate(&schema, &example, &schema, &format!("{} example {}", tool.name, i), &mut errors),
+                Err(e) => errors.push(format!("{} example {} is not JSON: {}", tool.name, i, e)),
+            }
+        }
+    }
+    assert!(errors.is_empty(), "{}", errors.join("\n"));
+}
+
+#[test]
+fn worker_only_examples_are_argument_objects() {
+    // Tools the stdio server does not have (watches, the faucet) are only checked for shape
+    for doc in TOOL_DOCS {
+        assert!(!doc.examples.is_empty(), "{} has no examples", doc.name);
+        assert_eq!(doc.examples().len(), doc.examples.len(), "{} has an example that is not JSON", doc.name);
+        assert!(doc.examples().iter().all(Value::is_object), "{} has a non-object example", doc.name);
+    }
+}
+
+#[tokio::test]
+async fn the_validator_catches_bad_examples() {
+    let tool = all_tools().await.into_iter().find(|t| t.name == "get_transaction_history").unwrap();
+    let schema = Value::Object((*tool.input_schema).clone());
+    let check = |example: Value| {
+        let mut errors = Vec::new();
+        validate(&schema, &example, &schema, "example", &mut errors);
+        errors
+    };
+    let address = "29VitN55TTRXyV9yHZ5ub8wTHdc4cCUpfyoSDbdV9vhCoxupVKvD3naG2SaQSERYBw";
+    assert!(check(serde_json::json!({ "address": address })).is_empty());
+    assert!(!check(serde_json::json!({})).is_empty());
+    assert!(!check(serde_json::json!({ "address": address, "limit": "ten" })).is_empty());
+    assert!(!check(serde_json::json!({ "address": address, "limmit": 10 })).is_empty());
+}
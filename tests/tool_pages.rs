@@ -0,0 +1,58 @@
+//! tools/list is paginated with opaque cursors and long tool prose is served
+//! as `amadeus://docs/tools/{name}` resources.
+
+mod common;
+
+use amadeus_mcp::catalog::{self, DEFAULT_PAGE_SIZE, TOOL_DOCS_URI};
+use common::McpClient;
+use serde_json::json;
+use std::collections::HashSet;
+
+#[tokio::test]
+async fn tools_list_pages_walk_the_whole_catalog_once() {
+    let server = common::server(common::config(&[("BLOCKCHAIN_URL", "http://127.0.0.1:9")]));
+    let (mut client, running) = McpClient::connect(server).await;
+
+    let first = client.request("tools/list", json!({})).await;
+    assert_eq!(first["result"]["tools"].as_array().unwrap().len(), DEFAULT_PAGE_SIZE);
+    assert!(first["result"]["nextCursor"].is_string());
+
+    let names = client.tool_names().await;
+    assert!(names.len() > DEFAULT_PAGE_SIZE);
+    let unique: HashSet<&String> = names.iter().collect();
+    assert_eq!(unique.len(), names.len(), "a tool was listed twice");
+    assert!(names.contains(&"get_capabilities".to_string()));
+    assert!(names.contains(&"get_removed_validators".to_string()));
+
+    let bad = client.request("tools/list", json!({ "cursor": "not-a-cursor" })).await;
+    assert_eq!(bad["error"]["code"], -32602);
+
+    let _ = running.cancel().await;
+}
+
+#[tokio::test]
+async fn tool_docs_are_readable_resources() {
+    let server = common::server(common::config(&[("BLOCKCHAIN_URL", "http://127.0.0.1:9")]));
+    let (mut client, running) = McpClient::connect(server).await;
+
+    let templates = client.request("resources/templates/list", json!({})).await;
+    let uris: Vec<&str> = templates["result"]["resourceTemplates"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|t| t["uriTemplate"].as_str())
+        .collect();
+    assert!(uris.contains(&format!("{}{{name}}", TOOL_DOCS_URI).as_str()));
+
+    let uri = format!("{}create_transaction", TOOL_DOCS_URI);
+    let reply = client.request("resources/read", json!({ "uri": uri })).await;
+    let contents = &reply["result"]["contents"][0];
+    assert_eq!(contents["uri"], uri);
+    let text = contents["text"].as_str().unwrap();
+    assert!(text.starts_with(catalog::tool_doc("create_transaction").unwrap().doc));
+
+    let missing = client.request("resources/read", json!({ "uri": format!("{}no_such_tool", TOOL_DOCS_URI) })).await;
+    assert_eq!(missing["error"]["code"], -32602);
+
+    let _ = running.cancel().await;
+}
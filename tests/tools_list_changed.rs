@@ -0,0 +1,32 @@
+//! Changing the tool filter at runtime, as a reload does: the client is sent
+//! `notifications/tools/list_changed` and sees the new tool set and toolsVersion.
+
+mod common;
+
+use amadeus_mcp::catalog::{ToolFilter, TOOLS_VERSION};
+use common::McpClient;
+use serde_json::json;
+
+#[tokio::test]
+async fn filter_change_notifies_and_bumps_the_version() {
+    let server = common::server(common::config(&[("BLOCKCHAIN_URL", "http://127.0.0.1:9")]));
+    let (mut client, running) = McpClient::connect(server.clone()).await;
+    assert_eq!(client.seen[0]["result"]["capabilities"]["tools"]["listChanged"], true);
+    let tools_version = |capabilities: serde_json::Value| capabilities["toolsVersion"].as_u64().unwrap();
+
+    assert!(client.tool_names().await.contains(&"get_richlist".to_string()));
+    assert_eq!(tools_version(client.call_ok("get_capabilities", json!({})).await), TOOLS_VERSION);
+
+    let deny = ToolFilter { deny: vec!["get_richlist".to_string()], ..ToolFilter::default() };
+    assert!(server.set_tool_filter(deny.clone(), Some(running.peer())).await);
+    client.wait_for("notifications/tools/list_changed").await;
+    assert!(!client.tool_names().await.contains(&"get_richlist".to_string()));
+    assert_eq!(tools_version(client.call_ok("get_capabilities", json!({})).await), TOOLS_VERSION + 1);
+
+    // The same filter again changes nothing, so there is no second notification
+    assert!(!server.set_tool_filter(deny, Some(running.peer())).await);
+    assert_eq!(tools_version(client.call_ok("get_capabilities", json!({})).await), TOOLS_VERSION + 1);
+    assert_eq!(client.count("notifications/tools/list_changed"), 1);
+
+    let _ = running.cancel().await;
+}
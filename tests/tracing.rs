@@ -0,0 +1,79 @@
+//! Span hierarchy of a tool call: `tool_call`, the client method below it and one
+//! `upstream_request` per attempt with the status the node answered.
+
+mod common;
+
+use common::{McpClient, MockNode, SpanCapture};
+use serde_json::json;
+use tracing_subscriber::layer::SubscriberExt;
+
+#[tokio::test]
+async fn tool_call_spans_nest_with_one_span_per_attempt() {
+    let capture = SpanCapture::default();
+    let _guard = tracing::subscriber::set_default(tracing_subscriber::registry().with(capture.clone()));
+
+    // The first stats request fails, the retry succeeds
+    let node = MockNode::start(|request, earlier| match (request.path.as_str(), earlier) {
+        ("/api/chain/stats", 0) => (503, json!({ "error": "busy" })),
+        ("/api/chain/stats", _) => (200, json!({ "error": "ok", "stats": { "height": 42 } })),
+        _ => (404, json!({ "error": "not_found" })),
+    })
+    .await;
+    let server = common::server(common::config(&[("BLOCKCHAIN_URL", &node.url)]));
+    let (mut client, running) = McpClient::connect(server).await;
+
+    let stats = client.call_ok("get_chain_stats", json!({ "secret_key": "do-not-log" })).await;
+    assert_eq!(stats["height"], 42);
+
+    let calls = capture.named("tool_call");
+    assert_eq!(calls.len(), 1);
+    let call = &calls[0];
+    assert_eq!(call.fields["tool"], "get_chain_stats");
+    assert_eq!(call.fields["outcome"], "ok");
+    assert!(call.fields.contains_key("duration_ms"));
+    assert!(!call.fields["arguments"].contains("do-not-log"), "{}", call.fields["arguments"]);
+
+    let methods = capture.children(call);
+    let method = methods.iter().find(|s| s.name == "get_chain_stats").expect("client method span under tool_call");
+
+    let attempts = capture.children(method);
+    let attempts: Vec<_> = attempts.iter().filter(|s| s.name == "upstream_request").collect();
+    let summary: Vec<_> =
+        attempts.iter().map(|s| (s.fields["attempt"].as_str(), s.fields["status"].as_str(), s.fields["endpoint"].as_str())).collect();
+    assert_eq!(summary, [("1", "503", "/api/chain/stats"), ("2", "200", "/api/chain/stats")]);
+    assert!(attempts.iter().all(|s| s.fields["method"] == "GET"));
+
+    // Without the otel feature nothing adds trace headers
+    #[cfg(not(feature = "otel"))]
+    assert!(node.requests().iter().all(|r| !r.headers.contains_key("traceparent")));
+
+    let _ = running.cancel().await;
+}
+
+#[cfg(feature = "otel")]
+#[tokio::test]
+async fn upstream_requests_carry_the_trace_context() {
+    use opentelemetry::trace::TracerProvider as _;
+
+    opentelemetry::global::set_text_map_propagator(opentelemetry_sdk::propagation::TraceContextPropagator::new());
+    let provider = opentelemetry_sdk::trace::TracerProvider::builder().build();
+    let otel = tracing_opentelemetry::layer().with_tracer(provider.tracer("test"));
+    let _guard = tracing::subscriber::set_default(tracing_subscriber::registry().with(otel));
+
+    let node = MockNode::start(|_, _| (200, json!({ "error": "ok", "stats": { "height": 42 } }))).await;
+    let server = common::server(common::config(&[("BLOCKCHAIN_URL", &node.url)]));
+    let (mut client, running) = McpClient::connect(server).await;
+    client.call_ok("get_chain_stats", json!({})).await;
+
+    let requests = node.requests();
+    assert!(!requests.is_empty());
+    for request in requests {
+        let traceparent = request.headers.get("traceparent").expect("traceparent header");
+        let parts: Vec<_> = traceparent.split('-').collect();
+        assert_eq!(parts.len(), 4, "{}", traceparent);
+        assert_eq!((parts[0], parts[1].len(), parts[2].len()), ("00", 32, 16), "{}", traceparent);
+        assert_ne!(parts[1], "0".repeat(32));
+    }
+
+    let _ = running.cancel().await;
+}